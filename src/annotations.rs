@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fs;
+
+/// Notes for `--annotations`, by line number, in the order they were given.
+pub type Annotations = HashMap<usize, Vec<String>>;
+
+/// Reads an annotations file: a JSON object mapping line numbers to a message or
+/// an array of them (`{"12": "check this", "40": ["a", "b"]}`), or plain text
+/// with one `LINE: message` per line, where blank lines and `#` comments are
+/// skipped. Errors say where the file went wrong.
+pub fn load(path: &str) -> Result<Annotations, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    if text.trim_start().starts_with('{') {
+        parse_json(&text)
+    } else {
+        parse_plain(&text)
+    }
+}
+
+fn parse_plain(text: &str) -> Result<Annotations, String> {
+    let mut annotations = Annotations::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (number, message) = line.split_once(':').ok_or_else(|| format!("line {}: expected LINE: text", i + 1))?;
+        let number = line_number(number.trim()).ok_or_else(|| format!("line {}: invalid line number '{}'", i + 1, number.trim()))?;
+        annotations.entry(number).or_default().push(message.trim().to_string());
+    }
+    Ok(annotations)
+}
+
+fn line_number(text: &str) -> Option<usize> {
+    text.parse().ok().filter(|&n| n > 0)
+}
+
+fn parse_json(text: &str) -> Result<Annotations, String> {
+    let mut parser = Json::new(text);
+    let mut annotations = Annotations::new();
+    parser.expect('{')?;
+    if !parser.eat('}') {
+        loop {
+            let key = parser.string()?;
+            let number = line_number(&key).ok_or_else(|| parser.error(&format!("invalid line number '{}'", key)))?;
+            parser.expect(':')?;
+            let messages = annotations.entry(number).or_default();
+            if parser.eat('[') {
+                if !parser.eat(']') {
+                    loop {
+                        messages.push(parser.string()?);
+                        if !parser.eat(',') {
+                            break;
+                        }
+                    }
+                    parser.expect(']')?;
+                }
+            } else {
+                messages.push(parser.string()?);
+            }
+            if !parser.eat(',') {
+                break;
+            }
+        }
+        parser.expect('}')?;
+    }
+    parser.skip_whitespace();
+    if parser.at < text.len() {
+        return Err(parser.error("unexpected text after the object"));
+    }
+    Ok(annotations)
+}
+
+/// Just enough of a JSON reader for the annotations object and the events of
+/// an asciinema recording.
+pub struct Json<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl<'a> Json<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Json { text, at: 0 }
+    }
+
+    pub fn error(&self, message: &str) -> String {
+        let line = self.text[..self.at].matches('\n').count() + 1;
+        format!("line {}: {}", line, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.at..];
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes `c` if it comes next.
+    pub fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.text[self.at..].starts_with(c) {
+            self.at += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    pub fn number(&mut self) -> Result<f64, String> {
+        self.skip_whitespace();
+        let rest = &self.text[self.at..];
+        let length = rest.find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')).unwrap_or(rest.len());
+        let number = rest[..length].parse().map_err(|_| self.error("expected a number"))?;
+        self.at += length;
+        Ok(number)
+    }
+
+    pub fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        let mut chars = self.text[self.at..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.at += i + 1;
+                    return Ok(value);
+                },
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            let unit = u32::from_str_radix(&hex, 16).ok();
+                            // Characters outside the BMP come as a surrogate pair
+                            let code = match unit {
+                                Some(high @ 0xD800..=0xDBFF) => {
+                                    let low: String = chars.by_ref().skip(2).take(4).map(|(_, c)| c).collect();
+                                    u32::from_str_radix(&low, 16).ok().map(|low| 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF))
+                                },
+                                unit => unit,
+                            };
+                            code.and_then(char::from_u32).unwrap_or('\u{FFFD}')
+                        },
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => return Err(self.error("invalid escape in string")),
+                    };
+                    value.push(escaped);
+                },
+                c => value.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(annotations: &Annotations, line: usize) -> Vec<&str> {
+        annotations.get(&line).map_or_else(Vec::new, |notes| notes.iter().map(String::as_str).collect())
+    }
+
+    #[test]
+    fn plain_format() {
+        let annotations = parse_plain("# review\n1: first line\n\n 12 :  check this \n1: again: with a colon\n").unwrap();
+        assert_eq!(notes(&annotations, 1), ["first line", "again: with a colon"]);
+        assert_eq!(notes(&annotations, 12), ["check this"]);
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(parse_plain("1 first").unwrap_err(), "line 1: expected LINE: text");
+        assert_eq!(parse_plain("\n0: none").unwrap_err(), "line 2: invalid line number '0'");
+    }
+
+    #[test]
+    fn json_format() {
+        let annotations = parse_json("{\"3\": \"tab\\there\", \"40\": [\"a\", \"b \\u00e9\\ud83d\\ude00\"], \"3\": \"more\"}").unwrap();
+        assert_eq!(notes(&annotations, 3), ["tab\there", "more"]);
+        assert_eq!(notes(&annotations, 40), ["a", "b é😀"]);
+        assert!(parse_json("{}").unwrap().is_empty());
+        assert!(parse_json("{\"4\": []}").unwrap()[&4].is_empty());
+    }
+
+    #[test]
+    fn json_errors_say_where() {
+        assert_eq!(parse_json("{\n\"x\": \"a\"}").unwrap_err(), "line 2: invalid line number 'x'");
+        assert_eq!(parse_json("{\"1\": \"a\"\n\"2\": \"b\"}").unwrap_err(), "line 2: expected '}'");
+        assert_eq!(parse_json("{\"1\": \"a\"} extra").unwrap_err(), "line 1: unexpected text after the object");
+        assert_eq!(parse_json("{\"1\": \"\\q\"}").unwrap_err(), "line 1: invalid escape in string");
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use zip::ZipArchive;
+
+use crate::{columns, units, Config};
+
+/// An entry of a zip archive, read as it's decompressed. An entry borrows its
+/// archive, so both stay on a thread of their own that sends the data over in
+/// chunks.
+pub struct EntryReader {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    at: usize,
+}
+
+/// Opens `entry` (its full path inside the archive) for reading.
+pub fn open_entry(archive: &Path, entry: &str) -> io::Result<EntryReader> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    // Here rather than on the thread, so it's reported like a missing file
+    if zip.index_for_name(entry).is_none() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, msg!("zip-no-entry")));
+    }
+    let (sender, chunks) = mpsc::sync_channel(4);
+    let entry = entry.to_string();
+    thread::spawn(move || {
+        let mut file = match zip.by_name(&entry) {
+            Ok(file) => file,
+            Err(err) => {
+                let _ = sender.send(Err(err.into()));
+                return;
+            }
+        };
+        loop {
+            let mut chunk = vec![0; 64 * 1024];
+            match file.read(&mut chunk) {
+                Ok(0) => return,
+                Ok(read) => {
+                    chunk.truncate(read);
+                    // The reader was dropped
+                    if sender.send(Ok(chunk)).is_err() {
+                        return;
+                    }
+                },
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(err) => {
+                    let _ = sender.send(Err(err));
+                    return;
+                }
+            }
+        }
+    });
+    Ok(EntryReader { chunks, chunk: Vec::new(), at: 0 })
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.at == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.at = 0;
+                },
+                // The thread is done with the entry
+                Err(_) => return Ok(0),
+            }
+        }
+        let read = buf.len().min(self.chunk.len() - self.at);
+        buf[..read].copy_from_slice(&self.chunk[self.at..self.at + read]);
+        self.at += read;
+        Ok(read)
+    }
+}
+
+/// `--list`: the entries of a zip archive with their sizes unpacked, as lines
+/// for the output, colored like `--list-dirs` does. Directories end in `/`
+/// and have no size.
+pub fn list(archive: &Path, config: &Config) -> io::Result<Vec<String>> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    let mut rows = Vec::new();
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        let (size, color) = if entry.is_dir() {
+            ("-".to_string(), &config.colors.highlight)
+        } else {
+            (units::size(entry.size(), config), &config.colors.normal)
+        };
+        rows.push(vec![size, format!("{}{}{}", color, entry.name(), config.colors.reset)]);
+    }
+    Ok(columns::table(&rows, &[0]))
+}
@@ -0,0 +1,197 @@
+/// ASCII-art cats for `--banner`, by variant name.
+pub const CATS: &[(&str, &[&str])] = &[
+    ("sitting", &[
+        r" /\_/\ ",
+        r"( o.o )",
+        r" > ^ < ",
+    ]),
+    ("loaf", &[
+        r"  /\_/\  ",
+        r" ( ^.^ ) ",
+        r#" (")_(") "#,
+    ]),
+    ("sleeping", &[
+        r"      |\      _,,,---,,_",
+        r"ZZZzz /,`.-'`'    -.  ;-;;,_",
+        r"     |,4-  ) )-,_. ,\ (  `'-'",
+        r"    '---''(_/--'  `-'\_)",
+    ]),
+];
+
+pub fn cat_art(variant: &str) -> Option<&'static [&'static str]> {
+    CATS.iter().find(|(name, _)| *name == variant).map(|(_, art)| *art)
+}
+
+const GLYPH_HEIGHT: usize = 5;
+
+/// Five-row block font. Letters are case-insensitive; `#` marks a filled cell.
+const FONT: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [" ### ", "#   #", "#####", "#   #", "#   #"]),
+    ('B', ["#### ", "#   #", "#### ", "#   #", "#### "]),
+    ('C', [" ####", "#    ", "#    ", "#    ", " ####"]),
+    ('D', ["#### ", "#   #", "#   #", "#   #", "#### "]),
+    ('E', ["#####", "#    ", "#### ", "#    ", "#####"]),
+    ('F', ["#####", "#    ", "#### ", "#    ", "#    "]),
+    ('G', [" ####", "#    ", "#  ##", "#   #", " ####"]),
+    ('H', ["#   #", "#   #", "#####", "#   #", "#   #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  ###", "   # ", "   # ", "#  # ", " ##  "]),
+    ('K', ["#   #", "#  # ", "###  ", "#  # ", "#   #"]),
+    ('L', ["#    ", "#    ", "#    ", "#    ", "#####"]),
+    ('M', ["#   #", "## ##", "# # #", "#   #", "#   #"]),
+    ('N', ["#   #", "##  #", "# # #", "#  ##", "#   #"]),
+    ('O', [" ### ", "#   #", "#   #", "#   #", " ### "]),
+    ('P', ["#### ", "#   #", "#### ", "#    ", "#    "]),
+    ('Q', [" ### ", "#   #", "# # #", "#  # ", " ## #"]),
+    ('R', ["#### ", "#   #", "#### ", "#  # ", "#   #"]),
+    ('S', [" ####", "#    ", " ### ", "    #", "#### "]),
+    ('T', ["#####", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('U', ["#   #", "#   #", "#   #", "#   #", " ### "]),
+    ('V', ["#   #", "#   #", "#   #", " # # ", "  #  "]),
+    ('W', ["#   #", "#   #", "# # #", "## ##", "#   #"]),
+    ('X', ["#   #", " # # ", "  #  ", " # # ", "#   #"]),
+    ('Y', ["#   #", " # # ", "  #  ", "  #  ", "  #  "]),
+    ('Z', ["#####", "   # ", "  #  ", " #   ", "#####"]),
+    ('0', [" ### ", "#  ##", "# # #", "##  #", " ### "]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', [" ### ", "#   #", "  ## ", " #   ", "#####"]),
+    ('3', ["#### ", "    #", " ### ", "    #", "#### "]),
+    ('4', ["#   #", "#   #", "#####", "    #", "    #"]),
+    ('5', ["#####", "#    ", "#### ", "    #", "#### "]),
+    ('6', [" ### ", "#    ", "#### ", "#   #", " ### "]),
+    ('7', ["#####", "    #", "   # ", "  #  ", "  #  "]),
+    ('8', [" ### ", "#   #", " ### ", "#   #", " ### "]),
+    ('9', [" ### ", "#   #", " ####", "    #", " ### "]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('.', [" ", " ", " ", " ", "#"]),
+    (',', ["  ", "  ", "  ", " #", "# "]),
+    ('!', ["#", "#", "#", " ", "#"]),
+    ('?', [" ### ", "#   #", "  ## ", "     ", "  #  "]),
+    ('-', ["    ", "    ", "####", "    ", "    "]),
+    ('_', ["     ", "     ", "     ", "     ", "#####"]),
+    (':', [" ", "#", " ", "#", " "]),
+    (';', ["  ", " #", "  ", " #", "# "]),
+    ('\'', ["#", "#", " ", " ", " "]),
+    ('"', ["# #", "# #", "   ", "   ", "   "]),
+    ('(', [" #", "# ", "# ", "# ", " #"]),
+    (')', ["# ", " #", " #", " #", "# "]),
+    ('[', ["##", "# ", "# ", "# ", "##"]),
+    (']', ["##", " #", " #", " #", "##"]),
+    ('/', ["    #", "   # ", "  #  ", " #   ", "#    "]),
+    ('\\', ["#    ", " #   ", "  #  ", "   # ", "    #"]),
+    ('+', ["     ", "  #  ", "#####", "  #  ", "     "]),
+    ('=', ["    ", "####", "    ", "####", "    "]),
+    ('*', ["     ", "# # #", " ### ", "# # #", "     "]),
+    ('#', [" # # ", "#####", " # # ", "#####", " # # "]),
+    ('@', [" ### ", "# ###", "# # #", "# ###", " ### "]),
+    ('&', [" ##  ", "#  # ", " ## #", "#  # ", " ## #"]),
+    ('%', ["##  #", "## # ", "  #  ", " # ##", "#  ##"]),
+    ('$', [" ####", "# #  ", " ### ", "  # #", "#### "]),
+    ('<', ["   #", "  # ", " #  ", "  # ", "   #"]),
+    ('>', ["#   ", " #  ", "  # ", " #  ", "#   "]),
+];
+
+fn glyph(c: char) -> &'static [&'static str; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+    FONT.iter()
+        .find(|(glyph_char, _)| *glyph_char == c)
+        .or_else(|| FONT.iter().find(|(glyph_char, _)| *glyph_char == '?'))
+        .map(|(_, rows)| rows)
+        .expect("font has a fallback glyph")
+}
+
+/// Renders text in the block font, one column of spacing between glyphs.
+/// Words are moved to a new block row when a line would exceed `max_width`,
+/// and a word wider than that on its own is broken across rows.
+pub fn render_title(text: &str, max_width: usize) -> Vec<String> {
+    let glyph_width = |c: char| glyph(c)[0].chars().count();
+    let width_of = |text: &str| text.chars().map(|c| glyph_width(c) + 1).sum::<usize>().saturating_sub(1);
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let joined = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+        if width_of(&joined) <= max_width {
+            current = joined;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        for c in word.chars() {
+            if !current.is_empty() && width_of(&current) + 1 + glyph_width(c) > max_width {
+                lines.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        for row in 0..GLYPH_HEIGHT {
+            let rendered: Vec<&str> = line.chars().map(|c| glyph(c)[row]).collect();
+            rows.push(rendered.join(" ").trim_end().replace('#', "\u{2588}"));
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `render_title` with `#` back for the filled cells, for readable goldens.
+    fn render(text: &str, max_width: usize) -> Vec<String> {
+        render_title(text, max_width).iter().map(|row| row.replace('\u{2588}', "#")).collect()
+    }
+
+    #[test]
+    fn title_letters_and_punctuation() {
+        assert_eq!(render("Hi!", 80), [
+            "#   # ### #",
+            "#   #  #  #",
+            "#####  #  #",
+            "#   #  #",
+            "#   # ### #",
+        ]);
+    }
+
+    #[test]
+    fn title_digits_and_lowercase_fallback() {
+        assert_eq!(render("42.", 80), [
+            "#   #  ###",
+            "#   # #   #",
+            "#####   ##",
+            "    #  #",
+            "    # ##### #",
+        ]);
+        assert_eq!(render("~", 80), render("?", 80));
+    }
+
+    #[test]
+    fn title_words_wrap_to_the_width() {
+        // "HI" is exactly 9 columns wide
+        assert_eq!(render("HI", 9).len(), GLYPH_HEIGHT);
+        assert_eq!(render("HI HI", 9), [render("HI", 9), render("HI", 9)].concat());
+    }
+
+    #[test]
+    fn title_words_wider_than_the_terminal_are_broken() {
+        let rows = render("MEOWMEOW", 20);
+        assert_eq!(rows, [render("MEO", 20), render("WME", 20), render("OW", 20)].concat());
+        assert!(rows.iter().all(|row| row.chars().count() <= 20));
+        // A single glyph that can't fit still gets a row of its own
+        assert_eq!(render("MM", 3), [render("M", 3), render("M", 3)].concat());
+    }
+
+    #[test]
+    fn every_cat_has_a_variant_name() {
+        for (name, art) in CATS {
+            assert_eq!(cat_art(name), Some(*art));
+        }
+        assert_eq!(cat_art("tabby"), None);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::VecDeque;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::{comment_style_for, print_line, CappedLines, Config};
+
+/// Colors for the nesting depths, outermost first, repeating past the last.
+/// Red is left out so it only ever means an unmatched bracket.
+const PALETTE: [&str; 6] = ["\x1B[33m", "\x1B[35m", "\x1B[36m", "\x1B[32m", "\x1B[34m", "\x1B[93m"];
+
+/// What the scanner skips besides the brackets themselves, judged from the file
+/// name. Unknown types get no skipping at all, so a `(` in prose or a string
+/// counts like any other.
+#[derive(Clone, Copy)]
+struct Syntax {
+    /// hash, slash or semicolon, as for `--strip-comments`
+    comments: Option<&'static str>,
+    /// Whether `'` quotes a string; in Rust it only does for a char literal,
+    /// so lifetimes don't swallow the rest of the line
+    single_quotes: bool,
+    rust: bool,
+    strings: bool,
+}
+
+impl Syntax {
+    fn for_file(file_name: &str, comment_style: Option<&str>) -> Self {
+        let extension = Path::new(file_name).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+        let comments = match comment_style {
+            Some("hash") => Some("hash"),
+            Some("slash") => Some("slash"),
+            Some("semicolon") => Some("semicolon"),
+            _ => comment_style_for(file_name),
+        };
+        let json = matches!(extension.as_deref(), Some("json" | "jsonl" | "ndjson"));
+        Syntax {
+            comments,
+            // In Lisp `'` quotes an expression, not a string
+            single_quotes: comments.is_some_and(|style| style != "semicolon"),
+            rust: extension.as_deref() == Some("rs"),
+            strings: comments.is_some() || json,
+        }
+    }
+}
+
+/// How a bracket is shown.
+#[derive(Clone, Copy)]
+pub enum Paint {
+    /// The nesting depth it opens or closes, from 0
+    Depth(usize),
+    /// A closer with nothing open to match
+    Unexpected,
+}
+
+impl Paint {
+    pub fn color<'a>(&self, config: &'a Config) -> &'a str {
+        match self {
+            Paint::Depth(depth) => PALETTE[depth % PALETTE.len()],
+            Paint::Unexpected => &config.colors.error,
+        }
+    }
+}
+
+/// Where a bracket is, 1-based. Once the input is over, an opener on the stack
+/// or a closer with nothing open is a bracket without a partner.
+#[derive(Clone, Copy)]
+pub struct Problem {
+    pub line: usize,
+    pub column: usize,
+    pub bracket: char,
+}
+
+impl Problem {
+    /// A closer with nothing open, rather than an opener never closed
+    pub fn is_closer(&self) -> bool {
+        matches!(self.bracket, ')' | ']' | '}')
+    }
+}
+
+/// `--match-brackets`: pairs up `()`, `[]` and `{}` over a whole input, a line
+/// at a time, and works out the color of each. Like `--strip-comments` it's a
+/// heuristic: strings end with their line and only block comments carry over.
+pub struct Brackets {
+    syntax: Syntax,
+    /// The brackets open so far, innermost last
+    stack: Vec<Problem>,
+    in_block_comment: bool,
+    unexpected: Vec<Problem>,
+    /// Colors of the lines scanned but not yet written, by source line
+    painted: VecDeque<(usize, Vec<(usize, Paint)>)>,
+    /// How many lines may be held back before they're written (`--before-context`)
+    keep: usize,
+}
+
+impl Brackets {
+    pub fn new(file_name: &str, config: &Config) -> Self {
+        Brackets {
+            syntax: Syntax::for_file(file_name, config.comment_style.as_deref()),
+            stack: Vec::new(),
+            in_block_comment: false,
+            unexpected: Vec::new(),
+            painted: VecDeque::new(),
+            keep: config.before_context,
+        }
+    }
+
+    /// Pairs the brackets in `line` with those before it. Returns the byte
+    /// offset of each bracket and how to show it.
+    fn scan(&mut self, line: &str, line_number: usize) -> Vec<(usize, Paint)> {
+        let syntax = self.syntax;
+        let mut colors = Vec::new();
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        let mut previous: Option<char> = None;
+        let mut chars = line.char_indices().peekable();
+        let mut column = 0;
+
+        while let Some((i, c)) = chars.next() {
+            column += 1;
+            let rest = &line[i..];
+            let at_word_start = previous.is_none_or(char::is_whitespace);
+            previous = Some(c);
+
+            if self.in_block_comment {
+                if rest.starts_with("*/") {
+                    self.in_block_comment = false;
+                    chars.next();
+                    column += 1;
+                }
+                continue;
+            }
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+
+            let comment = match syntax.comments {
+                Some("hash") => c == '#' && at_word_start,
+                Some("semicolon") => c == ';' && at_word_start,
+                Some(_) => rest.starts_with("//"),
+                None => false,
+            };
+            if comment {
+                break;
+            }
+            if syntax.comments == Some("slash") && rest.starts_with("/*") {
+                self.in_block_comment = true;
+                chars.next();
+                column += 1;
+                continue;
+            }
+            if syntax.strings && (c == '"' || c == '\'' && syntax.single_quotes && (!syntax.rust || is_char_literal(rest))) {
+                quote = Some(c);
+                continue;
+            }
+
+            match c {
+                '(' | '[' | '{' => {
+                    colors.push((i, Paint::Depth(self.stack.len())));
+                    self.stack.push(Problem { bracket: c, line: line_number, column });
+                },
+                ')' | ']' | '}' => {
+                    let opener = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    if self.stack.last().is_some_and(|open| open.bracket == opener) {
+                        self.stack.pop();
+                        colors.push((i, Paint::Depth(self.stack.len())));
+                    } else {
+                        colors.push((i, Paint::Unexpected));
+                        self.unexpected.push(Problem { line: line_number, column, bracket: c });
+                    }
+                },
+                _ => {},
+            }
+        }
+        colors
+    }
+
+    /// Scans a line as it's read, keeping its colors until `take` asks for them.
+    /// Every line should come through here, shown or not, or the depths drift.
+    pub fn add(&mut self, line: &str, line_number: usize, config: &Config) {
+        let colors = self.scan(line, line_number);
+        // Lines further back than the context window won't be written any more
+        while self.painted.front().is_some_and(|&(number, _)| number + self.keep < line_number) {
+            self.painted.pop_front();
+        }
+        if config.use_colors && !colors.is_empty() {
+            self.painted.push_back((line_number, colors));
+        }
+    }
+
+    /// The colors for a line that's being written, as byte offsets into it.
+    pub fn take(&mut self, line_number: usize) -> Vec<(usize, Paint)> {
+        while let Some((number, colors)) = self.painted.pop_front() {
+            if number == line_number {
+                return colors;
+            }
+            if number > line_number {
+                self.painted.push_front((number, colors));
+                break;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Every bracket left without a partner, in the order they appear.
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems: Vec<Problem> = self.stack.iter().chain(&self.unexpected).copied().collect();
+        problems.sort_by_key(|problem| (problem.line, problem.column));
+        problems
+    }
+}
+
+/// `'x'`, `'\n'` or `'\u{1F431}'` at the start of `rest`, rather than a lifetime.
+fn is_char_literal(rest: &str) -> bool {
+    let mut chars = rest.chars().skip(1);
+    match chars.next() {
+        Some('\\') => rest[2..].find('\'').is_some_and(|end| end <= 10),
+        Some(_) => chars.next() == Some('\''),
+        None => false,
+    }
+}
+
+/// A list of problems for a message, the first few with their position.
+pub fn describe(problems: &[Problem]) -> String {
+    let mut shown: Vec<String> = problems.iter().take(5).map(|problem| format!("'{}' {}:{}", problem.bracket, problem.line, problem.column)).collect();
+    if problems.len() > 5 {
+        shown.push("...".to_string());
+    }
+    shown.join(", ")
+}
+
+/// `--match-brackets=check`: reports where the brackets of each input (stdin if
+/// none) don't pair up, instead of showing the content. Returns false if any
+/// don't, or an input couldn't be read.
+pub fn check(config: &Config) -> bool {
+    let mut ok = true;
+    let sources = config.sources();
+    for source in &sources {
+        let name = source.name();
+        let input = match source.open() {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset);
+                ok = false;
+                continue;
+            }
+        };
+        let mut brackets = Brackets::new(&name, config);
+        let mut lines = CappedLines::new(BufReader::new(input.reader), config.max_line_bytes);
+        if let Some(separator) = &config.record_separator {
+            lines.separator = separator.clone();
+        }
+        for (i, line) in lines.enumerate() {
+            match line {
+                Ok(line) => {
+                    brackets.scan(&line, i + 1);
+                },
+                Err(err) => {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset);
+                    ok = false;
+                    break;
+                }
+            }
+        }
+        for problem in brackets.problems() {
+            let what = if problem.is_closer() {
+                msg!("bracket-unexpected", problem.bracket)
+            } else {
+                msg!("bracket-unclosed", problem.bracket)
+            };
+            print_line(&format!("{}{}{}:{}:{}: {}{}{}", config.colors.filename, name, config.colors.reset,
+                                problem.line, problem.column, config.colors.error, what, config.colors.reset));
+            ok = false;
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The brackets of `lines` in a file called `file_name`, each with its
+    /// depth or `!` when nothing matches it.
+    fn paint(file_name: &str, lines: &[&str]) -> (Vec<String>, Brackets) {
+        let mut brackets = Brackets::new(file_name, &Config::new());
+        let painted = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                brackets
+                    .scan(line, i + 1)
+                    .iter()
+                    .map(|&(at, paint)| match paint {
+                        Paint::Depth(depth) => format!("{}{}", &line[at..at + 1], depth),
+                        Paint::Unexpected => format!("{}!", &line[at..at + 1]),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        (painted, brackets)
+    }
+
+    #[test]
+    fn depths_carry_across_lines() {
+        let (painted, brackets) = paint("data.txt", &["{ [ (", ") ] }", "()[]"]);
+        assert_eq!(painted, ["{0 [1 (2", ")2 ]1 }0", "(0 )0 [0 ]0"]);
+        assert!(brackets.problems().is_empty());
+    }
+
+    #[test]
+    fn unmatched_brackets() {
+        let (painted, brackets) = paint("data.txt", &["(]", "{"]);
+        assert_eq!(painted, ["(0 ]!", "{1"]);
+        let problems = brackets.problems();
+        assert_eq!(describe(&problems), "'(' 1:1, ']' 1:2, '{' 2:1");
+        assert!(!problems[0].is_closer() && problems[1].is_closer());
+    }
+
+    #[test]
+    fn strings_and_comments_skipped_for_known_types() {
+        let lines = ["let s = \"(\"; // )", "/* ] { */ f('[')", "let c: &'a str = ')';"];
+        let (painted, brackets) = paint("main.rs", &lines);
+        assert_eq!(painted, ["", "(0 )0", ""]);
+        assert!(brackets.problems().is_empty());
+        // Without a known type everything counts
+        let (_, brackets) = paint("notes.txt", &lines);
+        assert!(!brackets.problems().is_empty());
+    }
+
+    #[test]
+    fn json_and_lisp() {
+        let (painted, _) = paint("a.json", &[r#"{"key": "[\"x\"", "list": [1]}"#]);
+        assert_eq!(painted, ["{0 [1 ]1 }0"]);
+        let (painted, brackets) = paint("init.el", &["(quote 'a) ; )"]);
+        assert_eq!(painted, ["(0 )0"]);
+        assert!(brackets.problems().is_empty());
+    }
+
+    #[test]
+    fn char_literals() {
+        assert!(is_char_literal("'x'"));
+        assert!(is_char_literal("'\\n'"));
+        assert!(is_char_literal("'\\u{1F431}'"));
+        assert!(!is_char_literal("'a str"));
+        assert!(!is_char_literal("'"));
+    }
+
+    #[test]
+    fn describe_shows_the_first_five() {
+        let problems: Vec<Problem> = (1..=7).map(|line| Problem { line, column: 1, bracket: '(' }).collect();
+        assert_eq!(describe(&problems), "'(' 1:1, '(' 2:1, '(' 3:1, '(' 4:1, '(' 5:1, ...");
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::annotations::Json;
+use crate::{report_write_error, Config};
+
+/// The format version of an asciinema recording, from the header line at the
+/// start of `head`: 2 (event times from the start) or 3 (from the event
+/// before). None if it isn't one.
+pub fn version(head: &[u8]) -> Option<u32> {
+    let end = head.iter().position(|&b| b == b'\n')?;
+    let header = std::str::from_utf8(&head[..end]).ok()?.trim();
+    if !header.starts_with('{') {
+        return None;
+    }
+    let rest = header[header.find("\"version\"")? + "\"version\"".len()..].trim_start();
+    let version = rest.strip_prefix(':')?.trim_start();
+    match version.as_bytes().first() {
+        Some(b'2') => Some(2),
+        Some(b'3') => Some(3),
+        _ => None,
+    }
+}
+
+/// `--raw-passthrough=timed`: writes the output events of an asciinema
+/// recording as they came, byte for byte and with their original timing
+/// (scaled by `--replay-speed`). Other events (input, resizes, markers) are
+/// left out. Returns false if an event can't be read.
+pub fn play(mut reader: impl BufRead, version: u32, file_name: &str, config: &Config) -> bool {
+    let mut out = io::stdout().lock();
+    let start = Instant::now();
+    let mut time = 0.0;
+    let mut line = String::new();
+    let mut number = 0;
+    loop {
+        line.clear();
+        number += 1;
+        match reader.read_line(&mut line) {
+            Ok(0) => return true,
+            Ok(_) => {},
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_name, err), config.colors.reset);
+                return false;
+            }
+        }
+        // The header
+        if number == 1 || line.trim().is_empty() {
+            continue;
+        }
+
+        let Ok((at, kind, data)) = parse_event(&line) else {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("cast-invalid-event", file_name, number), config.colors.reset);
+            return false;
+        };
+
+        time = if version == 2 { at } else { time + at };
+        if kind != "o" {
+            continue;
+        }
+        let due = Duration::from_secs_f64((time / config.replay_speed).max(0.0));
+        if let Some(wait) = due.checked_sub(start.elapsed()) {
+            thread::sleep(wait);
+        }
+        if let Err(err) = out.write_all(data.as_bytes()).and_then(|()| out.flush()) {
+            report_write_error(Err(err), config);
+            return true;
+        }
+    }
+}
+
+/// `[time, "o", "data"]`: the time, the kind of event and its data.
+fn parse_event(line: &str) -> Result<(f64, String, String), String> {
+    let mut json = Json::new(line);
+    json.expect('[')?;
+    let at = json.number()?;
+    json.expect(',')?;
+    let kind = json.string()?;
+    json.expect(',')?;
+    let data = json.string()?;
+    Ok((at, kind, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        assert_eq!(version(b"{\"version\": 2, \"width\": 80}\n[0.1, \"o\", \"x\"]\n"), Some(2));
+        assert_eq!(version(b"{\"version\":3,\"term\":{\"cols\":80}}\n"), Some(3));
+        assert_eq!(version(b"{\"version\": 1}\n"), None);
+        assert_eq!(version(b"{\"width\": 80}\n"), None);
+        assert_eq!(version(b"plain text\n"), None);
+        // The whole header line has to be there
+        assert_eq!(version(b"{\"version\": 2"), None);
+    }
+
+    #[test]
+    fn events() {
+        let (at, kind, data) = parse_event("[1.25, \"o\", \"\\u001b[31mred\\r\\n\"]\n").unwrap();
+        assert_eq!((at, kind.as_str(), data.as_str()), (1.25, "o", "\x1B[31mred\r\n"));
+        let (_, kind, _) = parse_event("[0, \"r\", \"80x24\"]").unwrap();
+        assert_eq!(kind, "r");
+        assert!(parse_event("[\"o\", 1.0, \"x\"]").is_err());
+        assert!(parse_event("{\"time\": 1}").is_err());
+        assert!(parse_event("[1.0, \"o\"").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::env;
+
+/// How many colors the terminal can show, per `--color-depth`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Depth {
+    /// The 16 ANSI colors
+    Ansi16,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// 24-bit RGB
+    TrueColor,
+}
+
+impl Depth {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "16" => Some(Depth::Ansi16),
+            "256" => Some(Depth::Ansi256),
+            "truecolor" | "24bit" => Some(Depth::TrueColor),
+            _ => None,
+        }
+    }
+
+    /// Guesses from `$COLORTERM` and `$TERM`, like most terminal programs do.
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Depth::TrueColor
+        } else if term.contains("256color") {
+            Depth::Ansi256
+        } else {
+            Depth::Ansi16
+        }
+    }
+}
+
+/// The 16 ANSI colors as xterm draws them, for finding the nearest one.
+const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0], [205, 0, 0], [0, 205, 0], [205, 205, 0], [0, 0, 238], [205, 0, 205], [0, 205, 205], [229, 229, 229],
+    [127, 127, 127], [255, 0, 0], [0, 255, 0], [255, 255, 0], [92, 92, 255], [255, 0, 255], [0, 255, 255], [255, 255, 255],
+];
+
+/// Levels of each channel in the 6×6×6 cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The escape sequence for a foreground color, as close as `depth` allows.
+pub fn fg(rgb: [u8; 3], depth: Depth) -> String {
+    match depth {
+        Depth::TrueColor => format!("\x1B[38;2;{};{};{}m", rgb[0], rgb[1], rgb[2]),
+        Depth::Ansi256 => format!("\x1B[38;5;{}m", to_256(rgb)),
+        Depth::Ansi16 => match to_16(rgb) {
+            index @ 0..=7 => format!("\x1B[{}m", 30 + index),
+            index => format!("\x1B[{}m", 90 + index - 8),
+        },
+    }
+}
+
+/// Like `fg`, for the background.
+pub fn bg(rgb: [u8; 3], depth: Depth) -> String {
+    match depth {
+        Depth::TrueColor => format!("\x1B[48;2;{};{};{}m", rgb[0], rgb[1], rgb[2]),
+        Depth::Ansi256 => format!("\x1B[48;5;{}m", to_256(rgb)),
+        Depth::Ansi16 => match to_16(rgb) {
+            index @ 0..=7 => format!("\x1B[{}m", 40 + index),
+            index => format!("\x1B[{}m", 100 + index - 8),
+        },
+    }
+}
+
+/// The nearest entry of the 256-color palette: a cube color or a gray.
+pub fn to_256(rgb: [u8; 3]) -> u8 {
+    let level = |value: u8| match value {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (value - 35) / 40,
+    };
+    let cube = rgb.map(level);
+    let cube_rgb = cube.map(|index| CUBE_LEVELS[index as usize]);
+
+    // Grays run from 8 to 238 in steps of 10
+    let average = (rgb.iter().map(|&value| u32::from(value)).sum::<u32>() / 3) as u8;
+    let gray_index = if average < 8 { 0 } else { ((average - 8) / 10).min(23) };
+    let gray = 8 + gray_index * 10;
+
+    if distance(rgb, [gray; 3]) < distance(rgb, cube_rgb) {
+        232 + gray_index
+    } else {
+        16 + 36 * cube[0] + 6 * cube[1] + cube[2]
+    }
+}
+
+/// The nearest of the 16 ANSI colors, 0 to 7 normal and 8 to 15 bright.
+pub fn to_16(rgb: [u8; 3]) -> u8 {
+    (0..16).min_by_key(|&index| distance(rgb, ANSI16[index as usize])).unwrap_or(7)
+}
+
+/// Squared distance between two colors, weighted for how the eye sees them.
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    let delta = |i: usize| (i32::from(a[i]) - i32::from(b[i])).unsigned_abs();
+    2 * delta(0).pow(2) + 4 * delta(1).pow(2) + 3 * delta(2).pow(2)
+}
+
+/// A fully saturated color `hue` degrees around the color wheel, for `--rainbow`.
+pub fn hue(hue: u32) -> [u8; 3] {
+    let sector = hue % 360 / 60;
+    let rising = ((hue % 60) * 255 / 60) as u8;
+    let falling = 255 - rising;
+    match sector {
+        0 => [255, rising, 0],
+        1 => [falling, 255, 0],
+        2 => [0, 255, rising],
+        3 => [0, falling, 255],
+        4 => [rising, 0, 255],
+        _ => [255, 0, falling],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truecolor_to_256() {
+        assert_eq!(to_256([255, 0, 0]), 196);
+        assert_eq!(to_256([0, 0, 0]), 16);
+        assert_eq!(to_256([255, 255, 255]), 231);
+        // Closer to a cube color than a gray, and the other way around
+        assert_eq!(to_256([255, 135, 0]), 208);
+        assert_eq!(to_256([128, 128, 128]), 244);
+        assert_eq!(to_256([100, 102, 98]), 241);
+    }
+
+    #[test]
+    fn truecolor_to_16() {
+        assert_eq!(to_16([255, 0, 0]), 9);
+        assert_eq!(to_16([200, 10, 10]), 1);
+        assert_eq!(to_16([0, 0, 0]), 0);
+        assert_eq!(to_16([250, 250, 250]), 15);
+        assert_eq!(to_16([0, 190, 200]), 6);
+    }
+
+    #[test]
+    fn escape_sequences_per_depth() {
+        let orange = [255, 135, 0];
+        assert_eq!(fg(orange, Depth::TrueColor), "\x1B[38;2;255;135;0m");
+        assert_eq!(fg(orange, Depth::Ansi256), "\x1B[38;5;208m");
+        assert_eq!(fg([255, 0, 0], Depth::Ansi16), "\x1B[91m");
+        assert_eq!(bg([200, 10, 10], Depth::Ansi16), "\x1B[41m");
+        assert_eq!(bg([0, 0, 0], Depth::Ansi256), "\x1B[48;5;16m");
+    }
+
+    #[test]
+    fn depth_names_and_hues() {
+        assert_eq!(Depth::from_name("256"), Some(Depth::Ansi256));
+        assert_eq!(Depth::from_name("24bit"), Some(Depth::TrueColor));
+        assert_eq!(Depth::from_name("88"), None);
+        assert_eq!(hue(0), [255, 0, 0]);
+        assert_eq!(hue(120), [0, 255, 0]);
+        assert_eq!(hue(390), hue(30));
+    }
+}
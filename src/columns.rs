@@ -0,0 +1,628 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crate::{send_output, terminal_width, ColorConfig, Config};
+
+/// Between the two columns of `--side-by-side`.
+const GUTTER: &str = " \u{2502} ";
+
+/// `--side-by-side`: shows two files next to each other, line by line, without
+/// diffing them. Lines too long for their column are cut with `…`, or wrapped
+/// with `--side-by-side-wrap`; the shorter file is padded with blank lines.
+pub fn side_by_side(left: &str, right: &str, config: &Config) -> bool {
+    let read = |path: &str| match fs::read(path) {
+        Ok(bytes) => Some(String::from_utf8_lossy(&bytes).lines().map(|line| line.trim_end_matches('\r').to_string()).collect::<Vec<_>>()),
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+            None
+        }
+    };
+    let (Some(left_lines), Some(right_lines)) = (read(left), read(right)) else {
+        return false;
+    };
+
+    let rows = left_lines.len().max(right_lines.len());
+    let number_width = if config.show_line_numbers { rows.to_string().len() + 1 } else { 0 };
+    let column = (terminal_width().saturating_sub(display_width(GUTTER)) / 2).saturating_sub(number_width).max(1);
+
+    send_output(config, |out| {
+        write_row(out, &[format!("{}{}{}", config.colors.filename, left, config.colors.reset)],
+                  &[format!("{}{}{}", config.colors.filename, right, config.colors.reset)], None, column, number_width, config)?;
+        for i in 0..rows {
+            let cell = |lines: &[String]| lines.get(i).map_or_else(|| vec![String::new()], |line| fit(line, column, config.side_by_side_wrap, config.tab_width));
+            let number = |lines: &[String]| (i < lines.len()).then_some(i + 1);
+            let (left_cell, right_cell) = (cell(&left_lines), cell(&right_lines));
+            write_row(out, &left_cell, &right_cell, Some((number(&left_lines), number(&right_lines))), column, number_width, config)?;
+        }
+        Ok(())
+    });
+    true
+}
+
+/// Writes one line of each file, which may take several rows when wrapped.
+fn write_row(out: &mut dyn Write, left: &[String], right: &[String], numbers: Option<(Option<usize>, Option<usize>)>,
+             column: usize, number_width: usize, config: &Config) -> io::Result<()> {
+    let gutter = |number: Option<usize>, first: bool| match number {
+        Some(number) if first && number_width > 0 => format!("{}{:>width$}{} ", config.colors.number, number, config.colors.reset, width = number_width - 1),
+        _ => " ".repeat(number_width),
+    };
+
+    for i in 0..left.len().max(right.len()) {
+        let (left_number, right_number) = numbers.unwrap_or_default();
+        let left_text = left.get(i).map_or("", String::as_str);
+        let right_text = right.get(i).map_or("", String::as_str);
+        let pad = column.saturating_sub(display_width(left_text));
+        writeln!(out, "{}{}{}{}{}{}{}{}",
+                 gutter(left_number, i == 0), left_text, " ".repeat(pad),
+                 config.colors.dim, GUTTER, config.colors.reset,
+                 gutter(right_number, i == 0), right_text.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Splits a line into rows at most `width` columns wide: all of it when
+/// wrapping, else just the first row, cut with `…`. Tabs are expanded to
+/// `tab_width` stops and escape sequences kept: a row that has any ends with a reset, and the next
+/// one starts with them again.
+pub fn fit(line: &str, width: usize, wrap: bool, tab_width: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    let mut row_width = 0;
+    // The escape sequences since the last reset
+    let mut active = String::new();
+    // Cut lines leave room for the ellipsis
+    let limit = if !wrap && display_width(line) > width { width - 1 } else { width };
+
+    let finish = |row: &mut String, active: &str| {
+        if !active.is_empty() {
+            row.push_str("\x1B[0m");
+        }
+        std::mem::replace(row, active.to_string())
+    };
+
+    for (_, piece) in pieces(line) {
+        if piece.starts_with('\x1B') {
+            row.push_str(piece);
+            track_escapes(piece, &mut active);
+            continue;
+        }
+
+        let (text, w) = if piece == "\t" {
+            let w = tab_width - row_width % tab_width;
+            (" ".repeat(w), w)
+        } else {
+            (piece.to_string(), cluster_width(piece))
+        };
+        if row_width + w > limit && !wrap {
+            row.push('\u{2026}');
+            return vec![finish(&mut row, &active)];
+        }
+        if row_width + w > limit && row_width > 0 {
+            rows.push(finish(&mut row, &active));
+            row_width = 0;
+        }
+        row.push_str(&text);
+        row_width += w;
+    }
+    rows.push(finish(&mut row, &active));
+    rows
+}
+
+/// The lines of a table with its columns lined up, two spaces apart. Cells may
+/// be colored; the columns in `right` line up on the right, and the last one
+/// isn't padded unless it's among them.
+pub fn table(rows: &[Vec<String>], right: &[usize]) -> Vec<String> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        widths.resize(widths.len().max(row.len()), 0);
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    rows.iter().map(|row| {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            let pad = " ".repeat(widths[i] - display_width(cell));
+            if i > 0 {
+                line.push_str("  ");
+            }
+            if right.contains(&i) {
+                line.push_str(&pad);
+                line.push_str(cell);
+            } else {
+                line.push_str(cell);
+                if i + 1 < row.len() {
+                    line.push_str(&pad);
+                }
+            }
+        }
+        line
+    }).collect()
+}
+
+/// Columns taken by text on a terminal, not counting escape sequences.
+pub fn display_width(text: &str) -> usize {
+    pieces(text).filter(|(_, piece)| !piece.starts_with('\x1B')).map(|(_, cluster)| cluster_width(cluster)).sum()
+}
+
+/// `--fold`: breaks a line into rows at most `width` columns wide at spaces,
+/// like `fold -s`, with rows after the first `indent` columns narrower. The
+/// spaces at a break are dropped; only a word longer than a whole row is split.
+/// Escape sequences carry over to the next row as in `fit`.
+pub fn fold_words(line: &str, width: usize, indent: usize, tab_width: usize) -> Vec<String> {
+    // Where each row ends and the next one starts
+    let mut breaks: Vec<(usize, usize)> = Vec::new();
+    let mut limit = width;
+    let mut row_start = 0;
+    let mut row_width = 0;
+    // The last run of spaces in the row: its start, its end and the width up to there
+    let mut gap: Option<(usize, usize, usize)> = None;
+
+    for (i, piece) in pieces(line) {
+        if piece.starts_with('\x1B') {
+            continue;
+        }
+
+        let w = if piece == "\t" { tab_width - row_width % tab_width } else { cluster_width(piece) };
+        if piece == " " || piece == "\t" {
+            let start = match gap {
+                Some((start, end, _)) if end == i => start,
+                _ => i,
+            };
+            row_width += w;
+            gap = Some((start, i + 1, row_width));
+            continue;
+        }
+        if row_width + w > limit && row_width > 0 {
+            match gap.filter(|&(start, _, _)| start > row_start) {
+                Some((start, end, gap_width)) => {
+                    breaks.push((start, end));
+                    row_start = end;
+                    row_width -= gap_width;
+                },
+                None => {
+                    breaks.push((i, i));
+                    row_start = i;
+                    row_width = 0;
+                },
+            }
+            gap = None;
+            limit = width.saturating_sub(indent).max(1);
+        }
+        row_width += w;
+    }
+    breaks.push((line.len(), line.len()));
+
+    let mut rows = Vec::with_capacity(breaks.len());
+    let mut active = String::new();
+    let mut start = 0;
+    for (end, next) in breaks {
+        let mut row = active.clone();
+        row.push_str(&line[start..end]);
+        track_escapes(&line[start..next], &mut active);
+        if !active.is_empty() {
+            row.push_str("\x1B[0m");
+        }
+        rows.push(row);
+        start = next;
+    }
+    rows
+}
+
+/// Updates `active`, the escape sequences since the last reset, with those in `text`.
+fn track_escapes(text: &str, active: &mut String) {
+    let mut rest = text;
+    while let Some(at) = rest.find('\x1B') {
+        let len = escape_len(&rest[at..]);
+        let sequence = &rest[at..at + len];
+        if sequence == "\x1B[0m" || sequence == "\x1B[m" {
+            active.clear();
+        } else {
+            active.push_str(sequence);
+        }
+        rest = &rest[at + len..];
+    }
+}
+
+/// Byte offset of the first grapheme cluster in `text` that ends past display
+/// column `column`, None when all of it fits. Tabs advance to the next
+/// multiple of `tab_width`; escape sequences take no room.
+pub fn column_end(text: &str, column: usize, tab_width: usize) -> Option<usize> {
+    let mut width = 0;
+    for (i, piece) in pieces(text) {
+        if piece.starts_with('\x1B') {
+            continue;
+        }
+        width += if piece == "\t" { tab_width - width % tab_width } else { cluster_width(piece) };
+        if width > column {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// `--truncate-middle`: `text` cut down to `width` columns by putting `…` in
+/// place of its middle, so both ends stay readable. None if it already fits.
+/// Grapheme clusters are kept whole, and escape sequences from the part
+/// that's left out are kept after the `…` so colors carry on.
+pub fn truncate_middle(text: &str, width: usize, tab_width: usize) -> Option<String> {
+    column_end(text, width, tab_width)?;
+
+    // (byte range, is an escape sequence) for each cluster or sequence
+    let units: Vec<_> = pieces(text).map(|(i, piece)| (i, i + piece.len(), piece.starts_with('\x1B'))).collect();
+    let width_of = |&(start, end, escape): &(usize, usize, bool), column: usize| match &text[start..end] {
+        _ if escape => 0,
+        "\t" => tab_width - column % tab_width,
+        cluster => cluster_width(cluster),
+    };
+
+    // The start gets the odd column when there is one
+    let room = width - 1;
+    let mut head = 0;
+    let mut used = 0;
+    for unit in &units {
+        let w = width_of(unit, used);
+        if used + w > room - room / 2 {
+            break;
+        }
+        used += w;
+        head += 1;
+    }
+    // Tabs at the end are counted at full width, not knowing where they'll land
+    let mut tail = units.len();
+    let mut used = 0;
+    for unit in units[head..].iter().rev() {
+        let w = width_of(unit, 0);
+        if used + w > room / 2 {
+            break;
+        }
+        used += w;
+        tail -= 1;
+    }
+
+    let mut shortened = text[..units.get(head).map_or(text.len(), |unit| unit.0)].to_string();
+    shortened.push('\u{2026}');
+    for &(start, end, escape) in &units[head..tail] {
+        if escape {
+            shortened.push_str(&text[start..end]);
+        }
+    }
+    shortened.push_str(&text[units.get(tail).map_or(text.len(), |unit| unit.0)..]);
+    Some(shortened)
+}
+
+/// `--ruler`: column numbers `width` wide, the tens digit of every tenth column
+/// over the units of each. Columns past `max_col` are in the error color.
+pub fn ruler(width: usize, max_col: Option<usize>, colors: &ColorConfig) -> [String; 2] {
+    let cut = max_col.unwrap_or(width).min(width);
+    let tens: String = (1..=width)
+        .map(|column| if column % 10 == 0 { char::from(b'0' + (column / 10 % 10) as u8) } else { ' ' })
+        .collect();
+    let units: String = (1..=width).map(|column| char::from(b'0' + (column % 10) as u8)).collect();
+
+    // Both rows are ASCII, so columns are bytes
+    let paint = |row: &str| {
+        let row = row.trim_end();
+        let (inside, past) = row.split_at(cut.min(row.len()));
+        let past = if past.is_empty() { String::new() } else { format!("{}{}", colors.error, past) };
+        format!("{}{}{}{}", colors.dim, inside, past, colors.reset)
+    };
+    [paint(&tens), paint(&units)]
+}
+
+/// Byte length of the escape sequence `text` starts with: CSI sequences end
+/// with a byte in @..~, others after one character.
+fn escape_len(text: &str) -> usize {
+    match text[1..].chars().next() {
+        Some('[') => text[2..].find(|c| ('@'..='~').contains(&c)).map_or(text.len(), |end| end + 3),
+        Some(c) => 1 + c.len_utf8(),
+        None => 1,
+    }
+}
+
+/// The escape sequences and grapheme clusters of `text` in order, each with
+/// its byte offset. A cluster never starts with ESC, so a piece that does is
+/// a sequence.
+fn pieces(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        let rest = &text[at..];
+        if rest.is_empty() {
+            return None;
+        }
+        let len = if rest.starts_with('\x1B') { escape_len(rest) } else { cluster_len(rest) };
+        at += len;
+        Some((at - len, &rest[..len]))
+    })
+}
+
+/// The grapheme clusters of `text`: what a reader takes for one character,
+/// such as a letter with its accents, a flag or a 👨‍👩‍👧 family.
+pub fn graphemes(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (cluster, after) = rest.split_at(cluster_len(rest));
+        rest = after;
+        Some(cluster)
+    })
+}
+
+/// Byte length of the grapheme cluster `text` starts with, after the rules
+/// of Unicode's UAX #29 that matter on a terminal: marks, variation
+/// selectors, emoji modifiers and tags stay on their base, a zero-width
+/// joiner glues two emoji, regional indicators pair into flags and Hangul
+/// jamo into syllables. Only CR LF spans control characters.
+fn cluster_len(text: &str) -> usize {
+    // ASCII followed by ASCII is a cluster of its own, but for CR LF
+    let bytes = text.as_bytes();
+    if bytes[0].is_ascii() && bytes.get(1).is_none_or(u8::is_ascii) {
+        return if bytes.starts_with(b"\r\n") { 2 } else { 1 };
+    }
+
+    let mut chars = text.char_indices();
+    let Some((_, mut prev)) = chars.next() else {
+        return 0;
+    };
+    // Regional indicators in a row so far, which pair up from the first
+    let mut regional = usize::from(is_regional(prev));
+    for (i, c) in chars {
+        let joins = match (prev, c) {
+            ('\r', '\n') => true,
+            _ if prev.is_control() || c.is_control() => false,
+            _ if is_extend(c) => true,
+            ('\u{200D}', c) => is_pictographic(c),
+            _ if is_regional(c) => regional % 2 == 1,
+            _ => matches!((jamo(prev), jamo(c)),
+                (Some(Jamo::L), Some(Jamo::L | Jamo::V | Jamo::Lv | Jamo::Lvt))
+                | (Some(Jamo::V | Jamo::Lv), Some(Jamo::V | Jamo::T))
+                | (Some(Jamo::T | Jamo::Lvt), Some(Jamo::T))),
+        };
+        if !joins {
+            return i;
+        }
+        regional = if is_regional(c) { regional + 1 } else { 0 };
+        prev = c;
+    }
+    text.len()
+}
+
+/// Columns a grapheme cluster takes: those of its base, except that a flag
+/// is two wide and so is a narrow symbol asking for emoji presentation.
+pub fn cluster_width(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    let Some(base) = chars.next() else {
+        return 0;
+    };
+    match chars.next() {
+        Some(c) if is_regional(base) && is_regional(c) => 2,
+        Some('\u{FE0F}') if char_width(base) == 1 => 2,
+        _ => char_width(base),
+    }
+}
+
+/// Characters that extend the cluster before them: combining marks, the
+/// joiners, variation selectors, emoji skin tones and tag characters.
+fn is_extend(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x0610..=0x061A | 0x064B..=0x065F
+        | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF
+        | 0x200C..=0x200D | 0x20D0..=0x20FF | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F
+        | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F | 0xE0100..=0xE01EF)
+}
+
+/// Emoji and the symbols that may take part in a zero-width joiner sequence.
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32,
+        0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139 | 0x2194..=0x21AA | 0x2300..=0x23FF
+        | 0x25A0..=0x27BF | 0x2934..=0x2935 | 0x2B00..=0x2BFF | 0x3030 | 0x303D | 0x3297 | 0x3299
+        | 0x1F000..=0x1FAFF)
+}
+
+fn is_regional(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// The parts Hangul syllables are built of: leading consonants, vowels,
+/// trailing consonants, and precomposed syllables without or with a trailing one.
+enum Jamo {
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+}
+
+fn jamo(c: char) -> Option<Jamo> {
+    Some(match c as u32 {
+        0x1100..=0x115F | 0xA960..=0xA97C => Jamo::L,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => Jamo::V,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => Jamo::T,
+        code @ 0xAC00..=0xD7A3 if (code - 0xAC00) % 28 == 0 => Jamo::Lv,
+        0xAC00..=0xD7A3 => Jamo::Lvt,
+        _ => return None,
+    })
+}
+
+/// 0 for combining marks and other zero-width characters, 2 for East Asian
+/// wide characters and emoji, 1 for the rest.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x00..=0x1F | 0x7F..=0x9F
+        | 0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x0610..=0x061A | 0x064B..=0x065F
+        | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F | 0x2028..=0x202E | 0x2060..=0x2064 | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F | 0xFEFF | 0xE0100..=0xE01EF => 0,
+        0x1100..=0x115F | 0x231A..=0x231B | 0x2329..=0x232A | 0x23E9..=0x23EC | 0x23F0 | 0x23F3
+        | 0x25FD..=0x25FE | 0x2614..=0x2615 | 0x2648..=0x2653 | 0x267F | 0x2693 | 0x26A1
+        | 0x26AA..=0x26AB | 0x26BD..=0x26BE | 0x26C4..=0x26C5 | 0x26CE | 0x26D4 | 0x26EA
+        | 0x26F2..=0x26F3 | 0x26F5 | 0x26FA | 0x26FD | 0x2705 | 0x270A..=0x270B | 0x2728 | 0x274C
+        | 0x274E | 0x2753..=0x2755 | 0x2757 | 0x2795..=0x2797 | 0x27B0 | 0x27BF | 0x2B1B..=0x2B1C
+        | 0x2B50 | 0x2B55 | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF | 0xA960..=0xA97F | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE10..=0xFE19
+        | 0xFE30..=0xFE6F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x1F004 | 0x1F0CF | 0x1F18E
+        | 0x1F191..=0x1F19A | 0x1F200..=0x1F251 | 0x1F300..=0x1F64F | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB | 0x1F90C..=0x1F9FF | 0x1FA70..=0x1FAFF | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("\x1B[31mred\x1B[0m"), 3);
+        assert_eq!(char_width('🐱'), 2);
+    }
+
+    #[test]
+    fn fit_cuts_with_an_ellipsis() {
+        assert_eq!(fit("short", 10, false, 8), ["short"]);
+        assert_eq!(fit("0123456789ab", 10, false, 8), ["012345678\u{2026}"]);
+        // A wide character that doesn't fit isn't split
+        assert_eq!(fit("abcd日本", 5, false, 8), ["abcd\u{2026}"]);
+        assert_eq!(fit("a\tb", 10, false, 8), ["a       b"]);
+    }
+
+    #[test]
+    fn fit_wraps_and_carries_colors() {
+        assert_eq!(fit("0123456789", 4, true, 8), ["0123", "4567", "89"]);
+        assert_eq!(fit("日本語", 4, true, 8), ["日本", "語"]);
+        assert_eq!(fit("\x1B[31mabcdef\x1B[0mgh", 4, true, 8), ["\x1B[31mabcd\x1B[0m", "\x1B[31mef\x1B[0mgh"]);
+    }
+
+    #[test]
+    fn column_end_counts_tabs_and_wide_characters() {
+        assert_eq!(column_end("abcdef", 6, 8), None);
+        assert_eq!(column_end("abcdefg", 6, 8), Some(6));
+        // The tab reaches column 8, b is in 9
+        assert_eq!(column_end("a\tb", 9, 8), None);
+        assert_eq!(column_end("a\tb", 8, 8), Some(2));
+        assert_eq!(column_end("a\tb", 7, 8), Some(1));
+        assert_eq!(column_end("a\tb", 4, 4), Some(2));
+        // 日 takes columns 3 and 4, so it's past 3 as a whole
+        assert_eq!(column_end("ab日本", 3, 8), Some(2));
+        assert_eq!(column_end("ab日本", 4, 8), Some(5));
+        assert_eq!(column_end("\x1B[31mabc\x1B[0m", 3, 8), None);
+    }
+
+    #[test]
+    fn ruler_rows() {
+        let plain = ColorConfig::new(false, crate::color::Depth::Ansi16);
+        assert_eq!(ruler(25, None, &plain), ["         1         2".to_string(), "1234567890123456789012345".to_string()]);
+        let colors = ColorConfig::new(true, crate::color::Depth::Ansi16);
+        let [tens, units] = ruler(12, Some(10), &colors);
+        assert_eq!(tens, format!("{}         1{}", colors.dim, colors.reset));
+        assert_eq!(units, format!("{}1234567890{}12{}", colors.dim, colors.error, colors.reset));
+    }
+
+    #[test]
+    fn fold_never_splits_words() {
+        let sentence = "the quick brown fox jumps over the lazy dog and keeps on running far away";
+        let rows = fold_words(sentence, 20, 0, 8);
+        assert_eq!(rows, ["the quick brown fox", "jumps over the lazy", "dog and keeps on", "running far away"]);
+        assert!(rows.iter().all(|row| display_width(row) <= 20));
+        assert_eq!(rows.join(" "), sentence);
+    }
+
+    #[test]
+    fn fold_indent_wide_characters_and_long_words() {
+        // Rows after the first leave room for the indent
+        assert_eq!(fold_words("aaa bbb ccc ddd", 8, 2, 8), ["aaa bbb", "ccc", "ddd"]);
+        assert_eq!(fold_words("日本語 日本語", 7, 0, 8), ["日本語", "日本語"]);
+        assert_eq!(fold_words("abcdefghij xy", 4, 0, 8), ["abcd", "efgh", "ij", "xy"]);
+        assert_eq!(fold_words("short", 10, 0, 8), ["short"]);
+    }
+
+    #[test]
+    fn fold_carries_colors() {
+        assert_eq!(fold_words("\x1B[1mbold text\x1B[0m end", 5, 0, 8), ["\x1B[1mbold\x1B[0m", "\x1B[1mtext\x1B[0m", "end"]);
+    }
+
+    #[test]
+    fn truncate_middle_keeps_both_ends() {
+        let path = "/home/user/projects/meow/src/very/deeply/nested/module/main.rs";
+        let short = truncate_middle(path, 30, 8).unwrap();
+        assert_eq!(short, "/home/user/proj\u{2026}module/main.rs");
+        assert_eq!(display_width(&short), 30);
+        assert_eq!(truncate_middle("fits", 4, 8), None);
+        // The start gets the odd column
+        assert_eq!(truncate_middle("abcdefgh", 6, 8).unwrap(), "abc\u{2026}gh");
+    }
+
+    #[test]
+    fn truncate_middle_counts_columns() {
+        let wide = truncate_middle("日本語のテキストです", 9, 8).unwrap();
+        assert_eq!(wide, "日本\u{2026}です");
+        assert!(display_width(&wide) <= 9);
+        // A mark goes with its letter
+        assert_eq!(truncate_middle("e\u{301}abcdefx\u{301}y", 5, 8).unwrap(), "e\u{301}a\u{2026}x\u{301}y");
+        assert_eq!(truncate_middle("ab\tcdefghijklmnop", 8, 8).unwrap(), "ab\u{2026}nop");
+    }
+
+    #[test]
+    fn truncate_middle_keeps_colors() {
+        let short = truncate_middle("\x1B[31mred text\x1B[0m and the rest of it", 10, 8).unwrap();
+        assert_eq!(short, "\x1B[31mred t\u{2026}\x1B[0mf it");
+    }
+
+    #[test]
+    fn table_lines_up_columns() {
+        let rows: Vec<Vec<String>> = [["name", "size", "note"], ["a.txt", "12 B", "x"], ["\x1B[35mlonger.log\x1B[0m", "1.5 KiB", ""]]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect();
+        assert_eq!(table(&rows, &[1]), [
+            "name           size  note",
+            "a.txt          12 B  x",
+            "\x1B[35mlonger.log\x1B[0m  1.5 KiB  ",
+        ]);
+    }
+
+    #[test]
+    fn clusters_keep_sequences_together() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(graphemes(family).collect::<Vec<_>>(), [family]);
+        assert_eq!(graphemes("ae\u{301}\u{308}b").collect::<Vec<_>>(), ["a", "e\u{301}\u{308}", "b"]);
+        // Leading consonant, vowel and trailing consonant make 한, next to the precomposed 국
+        assert_eq!(graphemes("\u{1112}\u{1161}\u{11AB}\u{AD6D}").collect::<Vec<_>>(), ["\u{1112}\u{1161}\u{11AB}", "\u{AD6D}"]);
+        // Regional indicators pair up from the first
+        assert_eq!(graphemes("\u{1F1EB}\u{1F1F7}\u{1F1E9}\u{1F1EA}\u{1F1EF}").collect::<Vec<_>>(),
+            ["\u{1F1EB}\u{1F1F7}", "\u{1F1E9}\u{1F1EA}", "\u{1F1EF}"]);
+        assert_eq!(graphemes("\u{1F44D}\u{1F3FD}\u{2764}\u{FE0F}").collect::<Vec<_>>(), ["\u{1F44D}\u{1F3FD}", "\u{2764}\u{FE0F}"]);
+        assert_eq!(graphemes("a\r\nb").collect::<Vec<_>>(), ["a", "\r\n", "b"]);
+        // A joiner before a letter doesn't reach past itself
+        assert_eq!(graphemes("\u{1F468}\u{200D}a").collect::<Vec<_>>(), ["\u{1F468}\u{200D}", "a"]);
+    }
+
+    #[test]
+    fn cluster_columns() {
+        assert_eq!(display_width("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"), 2);
+        assert_eq!(display_width("\u{1112}\u{1161}\u{11AB}\u{AD6D}\u{C5B4}"), 6);
+        assert_eq!(display_width("caf\u{65}\u{301} na\u{303}o"), 8);
+        assert_eq!(display_width("中文 text"), 9);
+        assert_eq!(display_width("\u{1F1EB}\u{1F1F7}"), 2);
+        // A narrow heart turns into a wide emoji with VS16
+        assert_eq!(display_width("\u{2764}"), 1);
+        assert_eq!(display_width("\u{2764}\u{FE0F}"), 2);
+        assert_eq!(display_width("\u{1F44D}\u{1F3FD}"), 2);
+    }
+
+    #[test]
+    fn emoji_sequences_are_never_split() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let line = format!("ab{0}cd{0}", family);
+        assert_eq!(fit(&line, 3, true, 8), ["ab".to_string(), format!("{}c", family), format!("d{}", family)]);
+        assert_eq!(fit(&line, 5, false, 8), [format!("ab{}\u{2026}", family)]);
+        assert_eq!(column_end(&line, 3, 8), Some(2));
+        assert_eq!(column_end(&line, 4, 8), Some(2 + family.len()));
+        assert_eq!(fold_words(&format!("{0}{0} {0}", family), 4, 0, 8), [format!("{0}{0}", family), family.to_string()]);
+    }
+}
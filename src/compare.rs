@@ -0,0 +1,171 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+
+use crate::{send_output, Config};
+
+/// Bytes per row of the hex context, as in `hexdump -C`.
+const ROW: usize = 16;
+
+/// Differing rows shown with their hex context; the rest are only counted.
+const SHOWN: usize = 3;
+
+/// A row of both inputs that differs, for the hex context.
+struct Row {
+    offset: u64,
+    left: Vec<u8>,
+    right: Vec<u8>,
+}
+
+fn open(path: &str) -> io::Result<Box<dyn Read>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin()))
+    } else {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
+/// Fills `row` as far as the input goes, so it's only short at the end.
+fn read_row(reader: &mut impl Read, row: &mut [u8; ROW]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < ROW {
+        match reader.read(&mut row[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+/// `--compare`: reads both inputs side by side, like `cmp`, and reports
+/// whether they're identical. If not, it tells where they first differ (byte
+/// offset and line of `left`), how many bytes differ in all and where the
+/// shorter one ends, and shows the first few differing rows in hex. Regular
+/// files of different sizes can't be identical, so with those it stops at the
+/// first difference. Some(true) if identical, None if one couldn't be read.
+pub fn run(left: &str, right: &str, config: &Config) -> Option<bool> {
+    let fail = |path: &str, err: io::Error| {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+        None
+    };
+    if left == "-" && right == "-" {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("compare-stdin-twice"), config.colors.reset);
+        return None;
+    }
+    let size = |path: &str| {
+        let metadata = fs::metadata(path).ok().filter(|_| path != "-")?;
+        metadata.is_file().then_some(metadata.len())
+    };
+    let sizes = match (size(left), size(right)) {
+        (Some(left_size), Some(right_size)) if left_size != right_size => Some((left_size, right_size)),
+        _ => None,
+    };
+    let mut left_reader = match open(left) {
+        Ok(reader) => BufReader::with_capacity(64 * 1024, reader),
+        Err(err) => return fail(left, err),
+    };
+    let mut right_reader = match open(right) {
+        Ok(reader) => BufReader::with_capacity(64 * 1024, reader),
+        Err(err) => return fail(right, err),
+    };
+
+    let (mut left_row, mut right_row) = ([0; ROW], [0; ROW]);
+    let mut offset: u64 = 0;
+    let mut line = 1;
+    // Offset and line of the first difference
+    let mut first: Option<(u64, usize)> = None;
+    let mut differing: u64 = 0;
+    // The shorter input and its length
+    let mut ends: Option<(&str, u64)> = None;
+    let mut shown = Vec::new();
+    loop {
+        let left_len = match read_row(&mut left_reader, &mut left_row) {
+            Ok(len) => len,
+            Err(err) => return fail(left, err),
+        };
+        let right_len = match read_row(&mut right_reader, &mut right_row) {
+            Ok(len) => len,
+            Err(err) => return fail(right, err),
+        };
+        if left_len == 0 && right_len == 0 {
+            break;
+        }
+
+        let newlines = |bytes: &[u8]| bytes.iter().filter(|&&byte| byte == b'\n').count();
+        let mut row_differs = false;
+        // Bytes past the end of the shorter input count as differing
+        for i in 0..left_len.max(right_len) {
+            if i < left_len && i < right_len && left_row[i] == right_row[i] {
+                continue;
+            }
+            differing += 1;
+            row_differs = true;
+            if first.is_none() {
+                first = Some((offset + i as u64, line + newlines(&left_row[..i.min(left_len)])));
+            }
+        }
+        if left_len != right_len && ends.is_none() {
+            ends = Some(if left_len < right_len { (left, offset + left_len as u64) } else { (right, offset + right_len as u64) });
+        }
+        if row_differs && shown.len() < SHOWN {
+            shown.push(Row { offset, left: left_row[..left_len].to_vec(), right: right_row[..right_len].to_vec() });
+        }
+        if row_differs && sizes.is_some() {
+            break;
+        }
+        line += newlines(&left_row[..left_len]);
+        offset += left_len.max(right_len) as u64;
+    }
+
+    let Some((at, at_line)) = first else {
+        send_output(config, |out| {
+            writeln!(out, "{}{}{}", config.colors.success, msg!("compare-identical", left, right, offset), config.colors.reset)
+        });
+        return Some(true);
+    };
+    send_output(config, |out| {
+        writeln!(out, "{}{}{}", config.colors.error, msg!("compare-differ", left, right), config.colors.reset)?;
+        writeln!(out, "  {}", msg!("compare-first", at, format!("{:x}", at), at_line))?;
+        match sizes {
+            Some((left_size, right_size)) => writeln!(out, "  {}", msg!("compare-sizes", left, left_size, right, right_size))?,
+            None => {
+                writeln!(out, "  {}", msg!("compare-count", differing))?;
+                if let Some((path, length)) = ends {
+                    writeln!(out, "  {}", msg!("compare-shorter", path, length))?;
+                }
+            },
+        }
+        for row in &shown {
+            writeln!(out)?;
+            write_row(out, row.offset, &row.left, &row.right, left, config)?;
+            write_row(out, row.offset, &row.right, &row.left, right, config)?;
+        }
+        Ok(())
+    });
+    Some(false)
+}
+
+/// A row like `hexdump -C` prints it, with the bytes that differ from `other`
+/// in the error color, followed by the input's name.
+fn write_row(out: &mut dyn Write, offset: u64, bytes: &[u8], other: &[u8], name: &str, config: &Config) -> io::Result<()> {
+    let mut hex = String::new();
+    let mut text = String::new();
+    for i in 0..ROW {
+        if i == ROW / 2 {
+            hex.push(' ');
+        }
+        let Some(&byte) = bytes.get(i) else {
+            hex.push_str("   ");
+            text.push(' ');
+            continue;
+        };
+        let (on, off) = if other.get(i) == Some(&byte) { ("", "") } else { (config.colors.error.as_str(), config.colors.reset.as_str()) };
+        hex.push_str(&format!("{}{:02x}{} ", on, byte, off));
+        let shown = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+        text.push_str(&format!("{}{}{}", on, shown, off));
+    }
+    writeln!(out, "{}{:08x}{}  {} |{}|  {}{}{}",
+             config.colors.number, offset, config.colors.reset, hex, text,
+             config.colors.filename, name, config.colors.reset)
+}
@@ -0,0 +1,415 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, BufReader, Write};
+use std::process::{Child, Command, Output, Stdio};
+#[cfg(feature = "notify")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use crate::{process_input, send_output, Config, RunState};
+
+/// Least time between two `--bell` alerts, however often the command runs.
+const BELL_GAP: Duration = Duration::from_secs(5);
+
+/// `--exec`: shows a command's stdout instead of a file. With `--watch-interval`
+/// the command is re-run until interrupted, `--diff-changes` highlights the
+/// lines that weren't in the previous run's output and `--bell` alerts when
+/// some of them match `--grep`. The command's stderr goes
+/// straight to ours. Returns false if the command couldn't run or (with
+/// `--exec-stop-on-error`, or without watching) exited unsuccessfully.
+pub fn run(command: &str, config: &Config) -> bool {
+    let argv = if config.exec_shell {
+        vec!["sh".to_string(), "-c".to_string(), command.to_string()]
+    } else {
+        match split_words(command) {
+            Some(words) if !words.is_empty() => words,
+            _ => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("invalid-exec-command", command), config.colors.reset);
+                return false;
+            }
+        }
+    };
+
+    let mut previous: Option<Vec<String>> = None;
+    let mut bell = Bell::default();
+    loop {
+        let output = match Command::new(&argv[0]).args(&argv[1..]).stdin(Stdio::null()).stderr(Stdio::inherit()).output() {
+            Ok(output) => output,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-run-exec", command, err), config.colors.reset);
+                return false;
+            }
+        };
+
+        let lines: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+        let mut run_config = config.clone();
+        let changed = previous.as_ref().map(|previous| changed_lines(previous, &lines)).unwrap_or_default();
+        if config.diff_changes {
+            run_config.changed_lines = changed.clone();
+        }
+
+        send_output(&run_config, |out| {
+            if let Some(interval) = config.watch_interval {
+                // Redraw in place like watch(1) when it's a terminal
+                if atty::is(atty::Stream::Stdout) {
+                    write!(out, "\x1B[2J\x1B[H")?;
+                }
+                write_status(out, command, interval, &output, config)?;
+            }
+            process_input(&mut BufReader::new(&output.stdout[..]), &run_config, command, &mut 0, &mut RunState::default(), out).map(|_| ())
+        });
+        
+        if config.bell {
+            // In order, so the notification shows the first of them
+            let mut numbers: Vec<usize> = changed.iter().copied().collect();
+            numbers.sort_unstable();
+            let new_matches: Vec<&str> = numbers
+                .into_iter()
+                .map(|number| lines[number - 1].as_str())
+                .filter(|line| config.grep_selects(line))
+                .collect();
+            if let Some((first, count)) = bell.ring(&new_matches, Instant::now()) {
+                alert(command, &first, count, config);
+            }
+        }
+
+        if !output.status.success() && (config.watch_interval.is_none() || config.exec_stop_on_error) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("exec-failed", command, output.status), config.colors.reset);
+            return false;
+        }
+
+        match config.watch_interval {
+            Some(interval) => thread::sleep(interval),
+            None => return true,
+        }
+        previous = Some(lines);
+    }
+}
+
+/// `--bell`: when to alert about new matching lines, at most once per
+/// `BELL_GAP`. Matches that come sooner wait for the next alert.
+#[derive(Default)]
+struct Bell {
+    last: Option<Instant>,
+    /// New matches not alerted about yet: the first and how many
+    unalerted: Option<(String, usize)>,
+}
+
+impl Bell {
+    /// Takes a run's `new_matches` at `now`, and returns the first and the
+    /// count of those to alert about if the gap since the last alert is over.
+    fn ring(&mut self, new_matches: &[&str], now: Instant) -> Option<(String, usize)> {
+        if let Some(first) = new_matches.first() {
+            self.unalerted.get_or_insert_with(|| (first.to_string(), 0)).1 += new_matches.len();
+        }
+        if self.last.is_some_and(|last| now.duration_since(last) < BELL_GAP) {
+            return None;
+        }
+        let due = self.unalerted.take()?;
+        self.last = Some(now);
+        Some(due)
+    }
+}
+
+/// Set once a `--bell=notify` notification failed; the bell rings from then on.
+#[cfg(feature = "notify")]
+static NOTIFY_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Rings the terminal bell, on stderr so it stays out of redirected output, or
+/// with `--bell=notify` sends a desktop notification.
+fn alert(command: &str, first: &str, count: usize, config: &Config) {
+    #[cfg(feature = "notify")]
+    if config.bell_notify && !NOTIFY_FAILED.load(Ordering::Relaxed) {
+        let body = format!("{}\n{}", msg!("bell-new-lines", count), first);
+        match notify_rust::Notification::new().summary(&format!("meow: {}", command)).body(&body).show() {
+            Ok(_) => return,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("notify-failed", err), config.colors.reset);
+                NOTIFY_FAILED.store(true, Ordering::Relaxed);
+            },
+        }
+    }
+    #[cfg(not(feature = "notify"))]
+    let _ = (command, first, count, config);
+    let mut stderr = io::stderr();
+    let _ = stderr.write_all(b"\x07");
+    let _ = stderr.flush();
+}
+
+fn write_status(out: &mut dyn Write, command: &str, interval: Duration, output: &Output, config: &Config) -> io::Result<()> {
+    let status = if output.status.success() {
+        String::new()
+    } else {
+        format!("  {}{}{}", config.colors.error, msg!("exec-status", output.status), config.colors.reset)
+    };
+    writeln!(out, "{}{}{}  {}{}\n",
+             config.colors.dim,
+             msg!("exec-every", humanize(interval), command),
+             config.colors.reset,
+             Local::now().format("%Y-%m-%d %H:%M:%S"),
+             status)
+}
+
+fn humanize(interval: Duration) -> String {
+    if interval.subsec_millis() == 0 {
+        format!("{}s", interval.as_secs())
+    } else {
+        format!("{}ms", interval.as_millis())
+    }
+}
+
+/// 1-based numbers of the lines in `current` that weren't in `previous`. Each
+/// previous line can only vouch for one current line, so a duplicated row still
+/// counts as new.
+fn changed_lines(previous: &[String], current: &[String]) -> HashSet<usize> {
+    let mut unmatched: HashMap<&str, usize> = HashMap::new();
+    for line in previous {
+        *unmatched.entry(line.as_str()).or_insert(0) += 1;
+    }
+
+    let mut changed = HashSet::new();
+    for (i, line) in current.iter().enumerate() {
+        match unmatched.get_mut(line.as_str()) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => {
+                changed.insert(i + 1);
+            }
+        }
+    }
+    changed
+}
+
+/// `--each-line-command`: runs a command for every line `--grep` selects. A
+/// `{}` in any of its words is replaced by the line, like `find -exec`;
+/// without one the line is written to the command's stdin. Up to
+/// `--each-line-jobs` commands run at once, the oldest waited for when that
+/// many are running. With `--each-line-output` each command's output is shown
+/// below its line instead of discarded, so they run one at a time.
+pub struct EachLine<'a> {
+    argv: &'a [String],
+    config: &'a Config,
+    running: VecDeque<Child>,
+    /// Commands that exited unsuccessfully, reported by `finish`
+    failed: usize,
+    /// Set once the command can't be started, which is reported once
+    broken: bool,
+}
+
+impl<'a> EachLine<'a> {
+    pub fn new(argv: &'a [String], config: &'a Config) -> Self {
+        EachLine { argv, config, running: VecDeque::new(), failed: 0, broken: false }
+    }
+
+    pub fn run(&mut self, line: &str, out: &mut dyn Write) -> io::Result<()> {
+        if self.broken {
+            return Ok(());
+        }
+        let as_argument = self.argv.iter().any(|word| word.contains("{}"));
+        // Interleaved output would break the JSON array
+        let show_output = self.config.each_line_output && !self.config.json_array;
+        while self.running.len() >= self.config.each_line_jobs {
+            if let Some(child) = self.running.pop_front() {
+                self.reap(child);
+            }
+        }
+
+        let mut words = self.argv.iter().map(|word| word.replace("{}", line));
+        let mut command = Command::new(words.next().unwrap_or_default());
+        command.args(words)
+            .stdin(if as_argument { Stdio::null() } else { Stdio::piped() })
+            .stdout(if show_output { Stdio::piped() } else { Stdio::null() })
+            .stderr(Stdio::inherit());
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", self.config.colors.error, msg!("cannot-run-exec", self.argv.join(" "), err), self.config.colors.reset);
+                self.broken = true;
+                return Ok(());
+            }
+        };
+        // From another thread, so a command that writes before it reads can't block us
+        let feeder = child.stdin.take().map(|mut stdin| {
+            let input = format!("{}\n", line);
+            thread::spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            })
+        });
+
+        if show_output {
+            out.flush()?;
+            let output = child.wait_with_output();
+            if let Some(feeder) = feeder {
+                let _ = feeder.join();
+            }
+            match output {
+                Ok(output) => {
+                    out.write_all(&output.stdout)?;
+                    if !output.stdout.is_empty() && !output.stdout.ends_with(b"\n") {
+                        writeln!(out)?;
+                    }
+                    if !output.status.success() {
+                        self.failed += 1;
+                    }
+                },
+                Err(_) => self.failed += 1,
+            }
+        } else {
+            self.running.push_back(child);
+        }
+        Ok(())
+    }
+
+    fn reap(&mut self, mut child: Child) {
+        if !child.wait().is_ok_and(|status| status.success()) {
+            self.failed += 1;
+        }
+    }
+
+    /// Waits for the commands still running and reports how many failed.
+    pub fn finish(&mut self, file_name: &str) {
+        while let Some(child) = self.running.pop_front() {
+            self.reap(child);
+        }
+        if self.failed > 0 {
+            eprintln!("{}meow: {}{}", self.config.colors.error, msg!("each-line-failed", file_name, self.argv.join(" "), self.failed), self.config.colors.reset);
+        }
+    }
+}
+
+/// Parses `--watch-interval`: a number with an optional `ms`, `s` or `m` unit
+/// (seconds by default).
+pub fn parse_interval(text: &str) -> Option<Duration> {
+    let (number, scale) = if let Some(number) = text.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = text.strip_suffix('s') {
+        (number, 1.0)
+    } else if let Some(number) = text.strip_suffix('m') {
+        (number, 60.0)
+    } else {
+        (text, 1.0)
+    };
+    number.parse::<f64>().ok()
+        .filter(|n| n.is_finite() && *n > 0.0)
+        .map(|n| Duration::from_secs_f64(n * scale))
+}
+
+/// Splits a command line like a POSIX shell would, minus expansions: single
+/// quotes are literal, double quotes allow backslash escapes, and a backslash
+/// outside quotes escapes the next character. None for unterminated quotes.
+pub fn split_words(command: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            },
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => word.push(c),
+                            '\n' => {},
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            },
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some('\n') | None => {},
+                    Some(c) => word.push(c),
+                }
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            },
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn split_words_like_a_shell() {
+        assert_eq!(split_words("kubectl get  pods"), Some(strings(&["kubectl", "get", "pods"])));
+        assert_eq!(split_words(r#"echo 'a  b' "c \"d\" \n" e\ f"#), Some(strings(&["echo", "a  b", r#"c "d" \n"#, "e f"])));
+        assert_eq!(split_words("a '' \"\""), Some(strings(&["a", "", ""])));
+        assert_eq!(split_words("echo 'open"), None);
+        assert_eq!(split_words("echo \"open"), None);
+        assert_eq!(split_words("   "), Some(Vec::new()));
+    }
+
+    #[test]
+    fn intervals_take_units() {
+        assert_eq!(parse_interval("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_interval("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_interval("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_interval("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_interval("0.5s"), Some(Duration::from_millis(500)));
+        for bad in ["0", "-1s", "s", "5h", "inf"] {
+            assert_eq!(parse_interval(bad), None, "{}", bad);
+        }
+        assert_eq!(humanize(Duration::from_secs(5)), "5s");
+        assert_eq!(humanize(Duration::from_millis(1500)), "1500ms");
+    }
+
+    #[test]
+    fn changed_lines_are_the_new_ones() {
+        let previous = strings(&["a", "b", "b", "c"]);
+        assert_eq!(changed_lines(&previous, &strings(&["a", "b", "c"])), HashSet::new());
+        // Moved lines aren't changes, a third "b" is
+        assert_eq!(changed_lines(&previous, &strings(&["c", "b", "x", "b", "b"])), HashSet::from([3, 5]));
+        assert_eq!(changed_lines(&[], &strings(&["a"])), HashSet::from([1]));
+    }
+
+    #[test]
+    fn bell_keeps_matches_that_come_too_soon() {
+        let start = Instant::now();
+        let mut bell = Bell::default();
+        assert_eq!(bell.ring(&[], start), None);
+        assert_eq!(bell.ring(&["error 1"], start), Some(("error 1".to_string(), 1)));
+        // Within the gap: held, not lost
+        assert_eq!(bell.ring(&["error 2", "error 3"], start + Duration::from_secs(1)), None);
+        assert_eq!(bell.ring(&["error 4"], start + Duration::from_secs(2)), None);
+        assert_eq!(bell.ring(&[], start + BELL_GAP), Some(("error 2".to_string(), 3)));
+        // Nothing new, nothing to ring about
+        assert_eq!(bell.ring(&[], start + BELL_GAP * 3), None);
+        assert_eq!(bell.ring(&["error 5"], start + BELL_GAP * 3), Some(("error 5".to_string(), 1)));
+    }
+}
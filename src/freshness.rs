@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{format_mtime, logtime, Config};
+
+/// Default `--freshness` thresholds: green under an hour, yellow under a day.
+pub const DEFAULT_THRESHOLDS: (u64, u64) = (60 * 60, 60 * 60 * 24);
+
+/// Seconds since `time`, 0 for times in the future. None if either end
+/// can't be put on the clock.
+pub fn age(time: SystemTime) -> Option<u64> {
+    let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(now.saturating_sub(secs))
+}
+
+/// A `--warn-older-than` or `--freshness` span in seconds: `90s`, `15 min`,
+/// `2h`, `3 days`.
+pub fn parse_span(text: &str) -> Option<u64> {
+    u64::try_from(logtime::parse_span(text.trim())?.num_seconds()).ok()
+}
+
+/// `--freshness=FRESH,STALE`: the two thresholds, the first below the second.
+pub fn parse_thresholds(text: &str) -> Option<(u64, u64)> {
+    let (fresh, stale) = text.split_once(',')?;
+    let (fresh, stale) = (parse_span(fresh)?, parse_span(stale)?);
+    (fresh < stale).then_some((fresh, stale))
+}
+
+/// `modified` (as `format_mtime` wrote it) in the color of its age with
+/// `--freshness`: green while fresh, yellow until stale, red after.
+pub fn paint(modified: &str, time: Option<SystemTime>, config: &Config) -> String {
+    let (Some((fresh, stale)), Some(age)) = (config.freshness, time.and_then(age)) else {
+        return modified.to_string();
+    };
+    let color = if age < fresh {
+        &config.colors.success
+    } else if age < stale {
+        &config.colors.number
+    } else {
+        &config.colors.error
+    };
+    format!("{}{}{}", color, modified, config.colors.reset)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Before reading `path`: warns when it's older than `--warn-older-than`,
+/// or newer than `--warn-newer-than`, as a file still being written may be.
+/// Returns its modification time, for `check_after`.
+pub fn check_before(path: &Path, name: &str, config: &Config) -> Option<SystemTime> {
+    let time = modified(path)?;
+    let age = age(time)?;
+    if let Some(limit) = config.warn_older_than.filter(|&limit| age > limit) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-older-than", name, format_mtime(time, config), limit_text(limit)), config.colors.reset);
+    }
+    if let Some(limit) = config.warn_newer_than.filter(|&limit| age < limit) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-newer-than", name, format_mtime(time, config), limit_text(limit)), config.colors.reset);
+    }
+    Some(time)
+}
+
+/// After reading `path`: warns if it was modified since `check_before`, so
+/// what was shown (and its `--meta`) may be neither the old nor the new file.
+pub fn check_after(path: &Path, name: &str, before: SystemTime, config: &Config) {
+    if modified(path).is_some_and(|after| after != before) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-changed-while-read", name), config.colors.reset);
+    }
+}
+
+/// A limit as given, in the largest unit that divides it.
+fn limit_text(secs: u64) -> String {
+    match secs {
+        0 => "0s".to_string(),
+        secs if secs % (60 * 60 * 24) == 0 => format!("{}d", secs / (60 * 60 * 24)),
+        secs if secs % (60 * 60) == 0 => format!("{}h", secs / (60 * 60)),
+        secs if secs % 60 == 0 => format!("{}m", secs / 60),
+        secs => format!("{}s", secs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn thresholds_must_increase() {
+        assert_eq!(parse_thresholds("1h,1d"), Some(DEFAULT_THRESHOLDS));
+        assert_eq!(parse_thresholds("90s, 15 min"), Some((90, 15 * 60)));
+        assert_eq!(parse_thresholds("2h,1h"), None);
+        assert_eq!(parse_thresholds("1h,1h"), None);
+        assert_eq!(parse_thresholds("1h"), None);
+        assert_eq!(parse_thresholds("soon,later"), None);
+    }
+
+    #[test]
+    fn times_are_painted_by_age() {
+        let mut config = Config::new();
+        config.apply_option("freshness", Some("1h,1d"));
+        let ago = |secs| Some(SystemTime::now() - Duration::from_secs(secs));
+        let (green, yellow, red, reset) = (&config.colors.success, &config.colors.number, &config.colors.error, &config.colors.reset);
+        assert_eq!(paint("x", ago(60), &config), format!("{}x{}", green, reset));
+        assert_eq!(paint("x", ago(60 * 60 + 1), &config), format!("{}x{}", yellow, reset));
+        assert_eq!(paint("x", ago(2 * 24 * 60 * 60), &config), format!("{}x{}", red, reset));
+        // A time in the future is as fresh as can be
+        assert_eq!(paint("x", Some(SystemTime::now() + Duration::from_secs(600)), &config), format!("{}x{}", green, reset));
+        assert_eq!(paint("x", None, &config), "x");
+        assert_eq!(paint("x", ago(60), &Config::new()), "x");
+    }
+
+    #[test]
+    fn limits_use_the_largest_unit() {
+        assert_eq!(limit_text(0), "0s");
+        assert_eq!(limit_text(90), "90s");
+        assert_eq!(limit_text(120), "2m");
+        assert_eq!(limit_text(2 * 60 * 60), "2h");
+        assert_eq!(limit_text(3 * 24 * 60 * 60), "3d");
+        assert_eq!(limit_text(25 * 60 * 60), "25h");
+    }
+}
@@ -0,0 +1,198 @@
+/// A substring within `Fuzzy::distance` edits of the pattern: its byte range
+/// in the line and how many edits it takes.
+#[derive(Clone, Copy)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub distance: usize,
+}
+
+/// `--grep-fuzzy`: finds the parts of a line that are at most `distance`
+/// insertions, deletions or substitutions away from the pattern, with the
+/// Levenshtein table set up so a match may start anywhere (Sellers'
+/// algorithm). Lines that can't hold a match are turned away by cheaper
+/// checks first, as most lines of a big log don't.
+#[derive(Clone)]
+pub struct Fuzzy {
+    pattern: Vec<char>,
+    /// How often the pattern's characters fall in each bucket, see `bucket`
+    counts: Vec<usize>,
+    pub distance: usize,
+}
+
+/// Characters sharing a bucket count as the same for `Fuzzy::ruled_out`,
+/// which only makes it let through a line it could have turned away.
+fn bucket(c: char) -> usize {
+    c as usize % 256
+}
+
+impl Fuzzy {
+    pub fn new(pattern: &str, distance: usize) -> Self {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut counts = vec![0; 256];
+        for &c in &pattern {
+            counts[bucket(c)] += 1;
+        }
+        Fuzzy { pattern, counts, distance }
+    }
+
+    /// The pattern's length in characters; `distance` has to stay below it.
+    pub fn pattern_len(&self) -> usize {
+        self.pattern.len()
+    }
+
+    /// Whether `text` can't hold a match: every match keeps at least all but
+    /// `distance` of the pattern's characters, so the line needs that many
+    /// characters, and that many of the pattern's own.
+    fn ruled_out(&self, text: &[char]) -> bool {
+        let needed = self.pattern.len().saturating_sub(self.distance);
+        if text.len() < needed {
+            return true;
+        }
+        let mut left = self.counts.clone();
+        let mut found = 0;
+        for &c in text {
+            let slot = &mut left[bucket(c)];
+            if *slot > 0 {
+                *slot -= 1;
+                found += 1;
+                if found >= needed {
+                    return false;
+                }
+            }
+        }
+        found < needed
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        let text: Vec<char> = line.chars().collect();
+        !self.ruled_out(&text) && self.next_match(&text, 0).is_some()
+    }
+
+    /// The matches in `line` from left to right, none overlapping.
+    pub fn find_iter(&self, line: &str) -> Vec<Match> {
+        let offsets: Vec<usize> = line.char_indices().map(|(at, _)| at).collect();
+        let text: Vec<char> = line.chars().collect();
+        if self.ruled_out(&text) {
+            return Vec::new();
+        }
+        let byte = |i: usize| offsets.get(i).copied().unwrap_or(line.len());
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while let Some((start, end, distance)) = self.next_match(&text, from) {
+            matches.push(Match { start: byte(start), end: byte(end), distance });
+            from = end;
+        }
+        matches
+    }
+
+    /// The closest match in `line`, the first of them on a tie.
+    pub fn find(&self, line: &str) -> Option<Match> {
+        self.find_iter(line).into_iter().min_by_key(|found| found.distance)
+    }
+
+    /// The first match in `text[from..]` as character positions and distance.
+    /// It ends where the distance first gets within bounds, moved on while it
+    /// stays as low or gets lower, so `time0ut` matches as a whole and not as
+    /// `time`. The start is the earliest one giving that distance.
+    fn next_match(&self, text: &[char], from: usize) -> Option<(usize, usize, usize)> {
+        let m = self.pattern.len();
+        // The last row of the table is the distance of the best match ending here
+        let mut column: Vec<usize> = (0..=m).collect();
+        let mut found: Option<(usize, usize)> = None;
+        for (j, &c) in text.iter().enumerate().skip(from) {
+            step(self.pattern.iter(), &mut column, c, 0);
+            let distance = column[m];
+            match found {
+                Some((_, best)) if distance <= best => found = Some((j + 1, distance)),
+                Some(_) => break,
+                None if distance <= self.distance => found = Some((j + 1, distance)),
+                None => {},
+            }
+        }
+        let (end, best) = found?;
+
+        // Again backwards from the end, with the match anchored there
+        let mut column: Vec<usize> = (0..=m).collect();
+        let mut start = end;
+        for (length, &c) in text[from..end].iter().rev().enumerate() {
+            step(self.pattern.iter().rev(), &mut column, c, length + 1);
+            if column[m] == best {
+                start = end - length - 1;
+            }
+            if column.iter().all(|&value| value > best) {
+                break;
+            }
+        }
+        Some((start, end, best))
+    }
+}
+
+/// Moves `column` of the table one text character on, with the pattern read
+/// in the order `pattern` gives. `top` is the new value of the first row: 0
+/// lets a match start anywhere.
+fn step<'a>(pattern: impl Iterator<Item = &'a char>, column: &mut [usize], c: char, top: usize) {
+    let mut diagonal = column[0];
+    column[0] = top;
+    for (i, &p) in pattern.enumerate() {
+        let left = column[i + 1];
+        column[i + 1] = (diagonal + usize::from(p != c)).min(left + 1).min(column[i] + 1);
+        diagonal = left;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(pattern: &str, allowed: usize, line: &str) -> Option<usize> {
+        Fuzzy::new(pattern, allowed).find(line).map(|found| found.distance)
+    }
+
+    #[test]
+    fn each_kind_of_edit_counts_one() {
+        assert_eq!(distance("timeout", 2, "a timeout here"), Some(0));
+        // Inserted, deleted and substituted characters
+        assert_eq!(distance("timeout", 2, "timeeout"), Some(1));
+        assert_eq!(distance("timeout", 2, "timout"), Some(1));
+        assert_eq!(distance("timeout", 2, "time0ut"), Some(1));
+        assert_eq!(distance("timeout", 2, "tmeot"), Some(2));
+        assert_eq!(distance("timeout", 2, "tim0eoutt"), Some(1));
+    }
+
+    #[test]
+    fn distance_is_the_limit() {
+        assert!(Fuzzy::new("timeout", 1).is_match("tmeout"));
+        assert!(!Fuzzy::new("timeout", 1).is_match("tmout"));
+        assert!(Fuzzy::new("timeout", 2).is_match("tmout"));
+        assert!(!Fuzzy::new("timeout", 0).is_match("timeot"));
+        assert!(Fuzzy::new("timeout", 0).is_match("timeout"));
+    }
+
+    #[test]
+    fn too_short_lines_are_ruled_out() {
+        let fuzzy = Fuzzy::new("timeout", 2);
+        assert!(fuzzy.ruled_out(&"time".chars().collect::<Vec<_>>()));
+        assert!(!fuzzy.ruled_out(&"tmeot".chars().collect::<Vec<_>>()));
+        assert!(!fuzzy.is_match("nothing at all"));
+    }
+
+    #[test]
+    fn matches_cover_the_whole_word() {
+        let line = "a time0ut and a timeout";
+        let matches = Fuzzy::new("timeout", 1).find_iter(line);
+        let found: Vec<(&str, usize)> = matches.iter().map(|m| (&line[m.start..m.end], m.distance)).collect();
+        assert_eq!(found, [("time0ut", 1), ("timeout", 0)]);
+        // The closest one
+        let best = Fuzzy::new("timeout", 1).find(line).unwrap();
+        assert_eq!(&line[best.start..best.end], "timeout");
+    }
+
+    #[test]
+    fn offsets_are_bytes() {
+        let line = "é tïmeout";
+        let found = Fuzzy::new("timeout", 1).find(line).unwrap();
+        assert_eq!(&line[found.start..found.end], "tïmeout");
+        assert_eq!(found.distance, 1);
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Read};
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl Algorithm {
+    /// Parses a name as used on the command line or in BSD-style manifests
+    /// (`md5`, `SHA1`, `sha-256`, ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Some(Algorithm::Md5),
+            "sha1" => Some(Algorithm::Sha1),
+            "sha256" => Some(Algorithm::Sha256),
+            "sha512" => Some(Algorithm::Sha512),
+            "blake3" => Some(Algorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Guesses the algorithm of a GNU-style manifest line from the digest length.
+    /// 64 digits could also be BLAKE3, which only BSD-style lines can name.
+    pub fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(Algorithm::Md5),
+            40 => Some(Algorithm::Sha1),
+            64 => Some(Algorithm::Sha256),
+            128 => Some(Algorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// Hashes everything `reader` yields in fixed-size chunks and returns the
+/// lowercase hex digest.
+pub fn hash_reader<R: Read>(algorithm: Algorithm, reader: &mut R) -> io::Result<String> {
+    match algorithm {
+        Algorithm::Md5 => digest::<Md5, R>(reader),
+        Algorithm::Sha1 => digest::<Sha1, R>(reader),
+        Algorithm::Sha256 => digest::<Sha256, R>(reader),
+        Algorithm::Sha512 => digest::<Sha512, R>(reader),
+        Algorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            feed(reader, |chunk| {
+                hasher.update(chunk);
+            })?;
+            Ok(hasher.finalize().to_hex().to_string())
+        },
+    }
+}
+
+fn digest<D: Digest, R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut hasher = D::new();
+    feed(reader, |chunk| hasher.update(chunk))?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn feed<R: Read>(reader: &mut R, mut update: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => update(&buffer[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// One entry of a checksum manifest.
+pub struct ManifestEntry {
+    pub algorithm: Algorithm,
+    pub digest: String,
+    pub file: String,
+}
+
+/// Parses a `sha256sum`-style line (`HEX  FILE`, or `HEX *FILE` for binary mode)
+/// or a BSD-style line (`SHA256 (FILE) = HEX`). Returns None for malformed lines.
+pub fn parse_manifest_line(line: &str) -> Option<ManifestEntry> {
+    let line = line.trim_end_matches('\r');
+
+    // BSD style: the tag names the algorithm
+    if let Some((tag, rest)) = line.split_once(" (") {
+        if let Some((file, digest)) = rest.rsplit_once(") = ") {
+            let algorithm = Algorithm::from_name(tag)?;
+            return valid_digest(digest, algorithm).then(|| ManifestEntry {
+                algorithm,
+                digest: digest.to_ascii_lowercase(),
+                file: file.to_string(),
+            });
+        }
+    }
+
+    let (digest, rest) = line.split_once(' ')?;
+    let file = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    let algorithm = Algorithm::from_hex_len(digest.len())?;
+    (valid_digest(digest, algorithm) && !file.is_empty()).then(|| ManifestEntry {
+        algorithm,
+        digest: digest.to_ascii_lowercase(),
+        file: file.to_string(),
+    })
+}
+
+fn valid_digest(digest: &str, algorithm: Algorithm) -> bool {
+    let expected_len = match algorithm {
+        Algorithm::Md5 => 32,
+        Algorithm::Sha1 => 40,
+        Algorithm::Sha256 | Algorithm::Blake3 => 64,
+        Algorithm::Sha512 => 128,
+    };
+    digest.len() == expected_len && digest.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALPHA_SHA256: &str = "b6a98d9ce9a2d9149288fa3df42d377c3e42737afdcdaf714e33c0a100b51060";
+
+    fn parsed(line: &str) -> Option<(bool, String, String)> {
+        parse_manifest_line(line).map(|entry| (entry.algorithm == Algorithm::Sha256, entry.digest, entry.file))
+    }
+
+    #[test]
+    fn manifest_lines_in_both_styles() {
+        let expected = Some((true, ALPHA_SHA256.to_string(), "dir/a b.txt".to_string()));
+        assert_eq!(parsed(&format!("{}  dir/a b.txt", ALPHA_SHA256)), expected);
+        assert_eq!(parsed(&format!("{} *dir/a b.txt\r", ALPHA_SHA256)), expected);
+        assert_eq!(parsed(&format!("SHA256 (dir/a b.txt) = {}", ALPHA_SHA256.to_uppercase())), expected);
+        let md5 = parse_manifest_line("MD5 (x) = 9f9f90dbe3e5ee1218c86b8839db1995").unwrap();
+        assert!(md5.algorithm == Algorithm::Md5);
+    }
+
+    #[test]
+    fn malformed_manifest_lines() {
+        for line in ["", "not a checksum", &format!("{}  ", ALPHA_SHA256), &format!("{}x  a", &ALPHA_SHA256[1..]),
+                     "SHA256 (a) = 123", &format!("ROT13 (a) = {}", ALPHA_SHA256), "abc  a"] {
+            assert!(parsed(line).is_none(), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn digests_of_a_stream() {
+        let digest = hash_reader(Algorithm::Sha256, &mut &b"alpha\n"[..]).unwrap();
+        assert_eq!(digest, ALPHA_SHA256);
+        let digest = hash_reader(Algorithm::Md5, &mut &b"alpha\n"[..]).unwrap();
+        assert_eq!(digest, "9f9f90dbe3e5ee1218c86b8839db1995");
+    }
+}
@@ -0,0 +1,846 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A message catalog: language code plus `(key, template)` pairs.
+/// Templates use positional placeholders (`{0}`, `{1}`) so translations can
+/// reorder arguments.
+pub struct Catalog {
+    pub code: &'static str,
+    pub messages: &'static [(&'static str, &'static str)],
+}
+
+/// Formats a catalog message with positional arguments.
+#[macro_export]
+macro_rules! msg {
+    ($key:expr) => {
+        $crate::i18n::format_message($key, &[])
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format_message($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+pub static CATALOGS: &[Catalog] = &[
+    Catalog { code: "en", messages: EN },
+    Catalog { code: "ru", messages: RU },
+];
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Picks the language from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order.
+pub fn init_from_env() {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(value) = env::var(var) {
+            if !value.is_empty() {
+                set_language(&value);
+                return;
+            }
+        }
+    }
+}
+
+/// Selects a language by code (`ru`, `ru_RU.UTF-8`, ...). Returns false and
+/// keeps the current language when there is no catalog for it.
+pub fn set_language(code: &str) -> bool {
+    let lang = code.split(['_', '.', '@', '-']).next().unwrap_or("").to_lowercase();
+    match CATALOGS.iter().position(|catalog| catalog.code == lang) {
+        Some(index) => {
+            CURRENT.store(index, Ordering::Relaxed);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Looks up a message in the current language, falling back to English.
+pub fn lookup(key: &str) -> Option<&'static str> {
+    let find = |catalog: &Catalog| catalog.messages.iter().find(|(k, _)| *k == key).map(|(_, text)| *text);
+    find(&CATALOGS[CURRENT.load(Ordering::Relaxed)]).or_else(|| find(&CATALOGS[0]))
+}
+
+/// Template for `key`, or the key itself so a missing entry is easy to spot.
+pub fn message(key: &str) -> &str {
+    lookup(key).unwrap_or(key)
+}
+
+pub fn format_message(key: &str, args: &[&dyn Display]) -> String {
+    let template = message(key);
+    let mut formatted = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        formatted.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let placeholder = after
+            .find('}')
+            .and_then(|close| after[..close].parse::<usize>().ok().map(|index| (index, close)));
+        match placeholder {
+            Some((index, close)) if index < args.len() => {
+                formatted.push_str(&args[index].to_string());
+                rest = &after[close + 1..];
+            },
+            _ => {
+                formatted.push('{');
+                rest = after;
+            }
+        }
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+static EN: &[(&str, &str)] = &[
+    // Argument parsing
+    ("unknown-option", "unknown option: {0}"),
+    ("option-takes-no-value", "--{0} does not take a value"),
+    ("option-requires-value", "--{0} requires a value"),
+    ("short-option-requires-value", "-{0} requires an argument ({1})"),
+    ("unhandled-option", "unhandled option: --{0}"),
+    ("invalid-regex", "invalid regex '{0}': {1}"),
+    ("invalid-image-protocol", "invalid image protocol '{0}' (expected auto, blocks, kitty or iterm)"),
+    ("invalid-image-width", "invalid image width: {0}"),
+    ("invalid-sample-interval", "invalid sample interval: {0}"),
+    ("invalid-sample-probability", "sample probability must be between 0 and 1: {0}"),
+    ("preview-skipped", "... {0} lines skipped ..."),
+    ("dupes-none", "{0}: no duplicate lines"),
+    ("dupes-found", "{0}: {1} duplicated lines"),
+    ("invisible-nbsp", "{0} × no-break space"),
+    ("invisible-bom", "{0} × byte order mark"),
+    ("invisible-zero-width", "{0} × zero-width character"),
+    ("invisible-bidi", "{0} × bidi control"),
+    ("invisible-space", "{0} × unusual space"),
+    ("invisible-control", "{0} × control character"),
+    ("invisible-more-lines", "{0}: and {1} more lines"),
+    ("invisible-total", "{0}: invisible characters on {1} lines: {2}"),
+    ("invisible-none", "{0}: no invisible characters"),
+    ("dupes-lines", "lines {0}"),
+    ("dupes-hashed", "<hash {0}, {1} chars>"),
+    ("side-by-side-needs-two", "--side-by-side needs exactly two files"),
+    ("compare-needs-two", "--compare needs exactly two files"),
+    ("compare-stdin-twice", "--compare can't read stdin for both inputs"),
+    ("compare-identical", "{0} and {1} are identical ({2} bytes)"),
+    ("compare-differ", "{0} and {1} differ"),
+    ("compare-first", "first difference: byte {0} (0x{1}), line {2}"),
+    ("compare-count", "differing bytes: {0}"),
+    ("compare-shorter", "{0} ends after {1} bytes"),
+    ("compare-sizes", "sizes differ: {0} has {1} bytes, {2} has {3}"),
+    ("invalid-time", "invalid time '{0}' (expected e.g. '2024-08-07 14:00', '14:00', 'yesterday' or '15 min ago')"),
+    ("invalid-comment-style", "invalid comment style '{0}' (expected hash, slash, semicolon or auto)"),
+    ("invalid-nonprint-style", "invalid style '{0}' (expected caret, hex or unicode)"),
+    ("invalid-index-format", "invalid index format '{0}' (expected tsv or json)"),
+    ("invalid-record-separator", "the record separator can't be empty"),
+    ("invalid-init-shell", "unsupported shell '{0}' (expected bash, zsh or fish)"),
+    ("record-needs-interactive", "--record records the interactive shell, add --interactive"),
+    ("bell-needs-watch", "--bell alerts on new lines of a watched command, add --exec and --watch-interval"),
+    ("record-error", "cannot write the recording {0}: {1}"),
+    ("replay-not-recording", "{0} is not a meow recording"),
+    ("replay-unsupported-version", "{0} was recorded in format version {1}, which this meow can't play"),
+    ("replay-truncated", "{0} ends in the middle of a frame, the rest was lost"),
+    ("invalid-brackets-mode", "invalid --match-brackets mode '{0}' (expected check)"),
+    ("invalid-raw-mode", "invalid --raw-passthrough mode '{0}' (expected timed)"),
+    ("cast-invalid-event", "{0}: line {1} is not an asciinema event"),
+    ("brackets-unmatched", "{0}: {1} unmatched brackets: {2}"),
+    ("bracket-unexpected", "'{0}' closes nothing"),
+    ("bracket-unclosed", "'{0}' is never closed"),
+    ("invalid-heat-thresholds", "invalid --heat-thresholds '{0}' (expected two increasing numbers, e.g. 100,1000)"),
+    ("invalid-freshness", "invalid --freshness '{0}' (expected two increasing spans, e.g. 1h,1d)"),
+    ("invalid-span", "invalid --{0} '{1}' (expected e.g. '90s', '15 min', '2h' or '3 days')"),
+    ("file-older-than", "WARNING: {0} was last modified {1}, more than {2} ago"),
+    ("file-newer-than", "WARNING: {0} was modified {1}, less than {2} ago; it may still be being written"),
+    ("file-changed-while-read", "WARNING: {0} changed while it was being read; the output may mix old and new content"),
+    ("heat-auto-fallback", "{0}: --heat-auto needs a regular file with numbers in it, using --heat-thresholds"),
+    ("split-needs-output", "--split-output needs --output=TEMPLATE, e.g. --output=part_%03d.txt"),
+    ("split-template-count", "the output template '{0}' needs exactly one number placeholder, e.g. %03d"),
+    ("split-template-placeholder", "invalid placeholder in the output template '{0}' (expected %d, %03d or %%)"),
+    ("split-written", "files written ({0}): {1}"),
+    ("output-error", "cannot write to {0}: {1}"),
+    ("invalid-color-depth", "invalid color depth '{0}' (expected 16, 256 or truecolor)"),
+    ("annotations-past-end", "{0}: annotations past the last line ({1}): {2}"),
+    ("invalid-number-hex", "invalid --number-hex value '{0}' (expected 0x)"),
+    ("invalid-escape-mode", "invalid escape mode '{0}' (expected shell, c or json)"),
+    ("invalid-shell-quote-mode", "invalid --shell-quote mode '{0}' (expected array)"),
+    ("fuzzy-distance-too-large", "--fuzzy-distance {0} would match anything: the --grep-fuzzy pattern is {1} characters long"),
+    ("colors-sample", "The quick brown fox jumps over the lazy dog 0123456789"),
+    ("syntax-error", "{0}:{1}:{2}: {3}"),
+    ("yaml-tab-indent", "tabs are not allowed in indentation"),
+    ("yaml-unexpected-indent", "unexpected indentation: the key above already has a value"),
+    ("yaml-unterminated-quote", "unterminated quoted string"),
+    ("folded-lines", "{0} lines folded"),
+    ("xml-collapsed", "{0} elements"),
+    ("invalid-banner-variant", "unknown banner '{0}' (available: {1})"),
+    ("indentation", "{0}: indentation: {1}"),
+    ("control-sequences-found", "{0}: contains terminal control sequences, showing them escaped (--unsafe to pass them through)"),
+    ("escapes-neutralized", "{0}: neutralized {1} escape sequences"),
+    ("redactions", "{0}: redacted values: {1}"),
+    ("invalid-redact-mode", "invalid --redact mode '{0}' (expected pii)"),
+    ("invalid-redact-style", "invalid redaction style '{0}' (expected block or hash)"),
+    ("indent-tabs", "tabs"),
+    ("indent-spaces", "{0} spaces"),
+    ("indent-none", "none"),
+    ("invalid-byte-count", "invalid byte count: {0}"),
+    ("unsupported-language", "unsupported language '{0}'"),
+    // Input and output
+    ("file-error", "{0}: {1}"),
+    ("write-error", "write error: {0}"),
+    ("tee-error", "cannot write to {0}: {1}"),
+    ("invalid-exec-command", "cannot parse the command '{0}' (unbalanced quotes?)"),
+    ("cannot-run-exec", "cannot run '{0}': {1}"),
+    ("exec-failed", "'{0}' failed: {1}"),
+    ("exec-status", "[{0}]"),
+    ("exec-every", "Every {0}: {1}"),
+    ("invalid-interval", "invalid interval '{0}' (expected e.g. 5s, 500ms or 2m)"),
+    ("invalid-resume-mode", "invalid value '{0}' for --resume (only 'clear' is accepted)"),
+    ("resumed-at", "\u{00B7}\u{00B7}\u{00B7} resumed at line {0}"),
+    ("resume-save-error", "cannot save the position to {0}: {1}"),
+    ("read-error", "error reading content: {0}"),
+    ("kind-fifo", "named pipe"),
+    ("kind-char-device", "character device"),
+    ("kind-block-device", "block device"),
+    ("kind-socket", "socket"),
+    ("kind-terminal", "terminal"),
+    ("kind-file", "regular file"),
+    ("kind-directory", "directory"),
+    ("kind-symlink", "symbolic link"),
+    ("kind-other", "other"),
+    ("refusing-special", "{0}: refusing to read {1}"),
+    ("waiting-for-input", "{0}: {1}, waiting for input..."),
+    ("binary-image", "{0}: binary file ({1} image) not shown"),
+    ("cannot-render-image", "{0}: cannot render image: {1}"),
+    ("invalid-date-format", "invalid date format '{0}'"),
+    ("invalid-port", "invalid port '{0}'"),
+    ("invalid-bind-address", "invalid address '{0}'"),
+    ("invalid-expand-env-mode", "invalid --expand-env mode '{0}' (expected strict)"),
+    ("undefined-variables", "{0}: undefined variables: {1}"),
+    ("invalid-checksum-algorithm", "unknown checksum algorithm '{0}' (expected md5, sha1, sha256, sha512 or blake3)"),
+    ("invalid-field-list", "invalid field list '{0}' (expected e.g. 1,3-5 or 2-)"),
+    ("empty-delimiter", "the delimiter must not be empty"),
+    ("invalid-sort-key", "invalid sort key '{0}' (expected name, size or mtime)"),
+    ("invalid-count", "invalid value '{1}' for --{0}"),
+    ("match-index-out-of-range", "{0}: no match number {1} (found {2})"),
+    ("invalid-marks", "invalid --marks '{0}' (expected line numbers, e.g. 120,340,702)"),
+    ("mark-label", "mark {0}"),
+    ("mark-index-entry", "{0} → line {1}: {2}"),
+    ("marks-past-end", "{0}: marks past the end ({1} lines): {2}"),
+    ("marks-index-needs-file", "{0}: --marks-index needs a regular file, no index shown"),
+    ("empty-backup-suffix", "--backup needs a non-empty suffix"),
+    ("in-place-stdin", "--in-place needs files, it cannot rewrite stdin"),
+    ("in-place-filter", "--in-place cannot be combined with --filter"),
+    ("in-place-no-transform", "--in-place needs an option that changes the text (e.g. -s, --grep, --escape)"),
+    ("not-a-regular-file", "not a regular file"),
+    ("in-place-incomplete", "could not be read to the end, left unchanged"),
+    ("rewritten", "rewritten"),
+    ("malformed-checksum-line", "{0}:{1}: malformed checksum line"),
+    ("no-checksum-lines", "{0}: no checksum lines found"),
+    ("verify-ok", "OK"),
+    ("verify-failed", "FAILED"),
+    ("verify-missing", "MISSING"),
+    ("verify-summary", "{0} ok, {1} failed, {2} missing"),
+    ("cannot-listen", "cannot listen on {0}: {1}"),
+    ("serving", "serving on {0} (Ctrl-C to stop)"),
+    ("config-error", "{0}: {1}"),
+    ("profile-invalid", "{0}: profiles.{1}: invalid options, profile ignored"),
+    ("preprocessor-failed", "{0}: binary file not shown (preprocessor {1} failed)"),
+    ("no-preprocessors", "no preprocessors configured in {0}"),
+    ("no-views", "no views configured in {0}"),
+    ("unknown-view", "unknown view '{0}' (available: {1})"),
+    ("unknown-view-none", "unknown view '{0}' (no views are configured)"),
+    ("recursive-view", "view '{0}' refers back to itself"),
+    ("long-lines-split", "{0}: lines longer than {1} bytes were split"),
+    ("qr-too-large", "{0}: {1} bytes do not fit in a QR code at this terminal width (max {2} bytes)"),
+    ("qr-unsupported", "QR codes are not supported in this build (rebuild with --features qr)"),
+    ("zip-unsupported", "zip archives are not supported in this build (rebuild with --features zip)"),
+    ("zip-no-entry", "no such entry in the archive (see meow --list)"),
+    ("title-matches", "{0} matches"),
+    ("large-input-prompt", "{0} is {1} — display anyway? [y/N/p(age)]"),
+    ("waiting-for-file", "waiting for {0} to be created..."),
+    ("thousands-separator", ","),
+    ("decimal-point", "."),
+    ("fold-sections-hidden", "(+ {0} lines)"),
+    ("fold-sections-status", "{0}  line {1}/{2}  Enter: open/close  a: open all  c: close all  /: search  q: quit"),
+    ("fold-sections-not-found", "not found: {0}"),
+    ("fold-sections-failed", "cannot show the sections: {0}"),
+    ("notify-unsupported", "desktop notifications are not supported in this build (rebuild with --features notify)"),
+    ("invalid-bell-mode", "invalid --bell mode '{0}' (expected notify)"),
+    ("notify-failed", "cannot send a notification, ringing the bell instead: {0}"),
+    ("bell-new-lines", "{0} new matching lines"),
+    ("each-line-failed", "{0}: '{1}' failed for {2} lines"),
+    ("cannot-run-filter", "{0}: cannot run filter '{1}': {2}"),
+    ("filter-failed", "{0}: filter '{1}' failed ({2})"),
+    ("meta-minutes-ago", "{0} mins ago"),
+    ("meta-hours-ago", "{0} hours ago"),
+    ("meta-days-ago", "{0} days ago"),
+    ("meta-unknown-time", "unknown time"),
+    ("meta-read-only", "read-only"),
+    ("meta-read-write", "read-write"),
+    ("meta-profile", "profile {0}"),
+    ("stat-name", "name"),
+    ("stat-size", "size"),
+    ("stat-modified", "modified"),
+    ("stat-type", "type"),
+    ("stat-permissions", "permissions"),
+    ("summary-read", "read"),
+    ("summary-printed", "shown"),
+    ("summary-matches", "matches"),
+    ("summary-errors", "errors"),
+    ("summary-time", "time"),
+    ("summary-total", "total"),
+    // Editor
+    ("nothing-to-edit", "nothing to open in the editor"),
+    ("no-editor", "no editor configured (set $VISUAL or $EDITOR)"),
+    ("cannot-launch-editor", "cannot launch editor '{0}': {1}"),
+    ("editor-failed", "editor '{0}' failed: {1}"),
+    ("cannot-start-pager", "cannot start pager '{0}', showing the output here: {1}"),
+    // Git integration
+    ("blame-regular-only", "{0}: blame is only available for regular files"),
+    ("blame-failed", "{0}: git blame failed, showing file without blame"),
+    ("changes-regular-only", "{0}: --changes is only available for regular files"),
+    ("changes-no-repo", "{0}: not in a git repository, ignoring --changes"),
+    ("changes-unknown-ref", "{0}: unknown revision '{1}', ignoring --changes"),
+    ("changes-failed", "{0}: git diff failed, ignoring --changes"),
+    // Diff
+    ("diff-identical", "Files are identical"),
+    // Interactive shell
+    ("shell-title", "=== Meow Interactive Shell ==="),
+    ("shell-intro", "Type 'help' for available commands, 'exit' to quit"),
+    ("shell-commands", "Available commands:"),
+    ("shell-help-cat", "  cat <file>    - Display file contents"),
+    ("shell-help-grep", "  grep <pattern> <file> - Find pattern in file"),
+    ("shell-help-highlight", "  highlight <pattern> <file> - Highlight pattern in file"),
+    ("shell-help-rainbow", "  rainbow <file> - Display file with rainbow colors"),
+    ("shell-help-diff", "  diff <file1> <file2> - Show colored diff between files"),
+    ("shell-help-view", "  view <name> <file> - Display file with the options of a view from the config"),
+    ("shell-help-edit", "  edit <file>   - Open file in $EDITOR"),
+    ("shell-help-history", "  history       - Show command history"),
+    ("shell-help-exit", "  exit/quit     - Exit the shell"),
+    ("shell-usage", "Usage: {0}"),
+    ("shell-open-error", "Error: Could not open file '{0}'"),
+    ("pick-needs-terminal", "--pick needs a terminal for input and output"),
+    ("pick-failed", "cannot show the file list: {0}"),
+    ("pick-return", "-- any key: back to the list, q: quit --"),
+    ("shell-history", "Command history:"),
+    ("shell-unknown-command", "Unknown command: '{0}'"),
+    ("shell-unknown-hint", "Type 'help' to see available commands"),
+    // Help
+    ("help-usage", "Usage:"),
+    ("help-description", "Concatenate FILE(s) to standard output with enhancements."),
+    ("help-stdin", "If FILE is not specified or is -, read standard input."),
+    ("help-examples", "Examples:"),
+    ("help-report-bugs", "Report bugs to: {0}"),
+];
+
+/// Option and example descriptions default to the English text in the option
+/// tables, so only translations list them (`option.<long name>`, `example.<n>`).
+static RU: &[(&str, &str)] = &[
+    ("unknown-option", "неизвестная опция: {0}"),
+    ("option-takes-no-value", "--{0} не принимает значение"),
+    ("option-requires-value", "--{0} требует значение"),
+    ("short-option-requires-value", "-{0} требует аргумент ({1})"),
+    ("unhandled-option", "необработанная опция: --{0}"),
+    ("invalid-regex", "некорректное регулярное выражение '{0}': {1}"),
+    ("invalid-image-protocol", "некорректный протокол изображений '{0}' (ожидается auto, blocks, kitty или iterm)"),
+    ("invalid-image-width", "некорректная ширина изображения: {0}"),
+    ("invalid-sample-interval", "некорректный интервал выборки: {0}"),
+    ("invalid-sample-probability", "вероятность выборки должна быть от 0 до 1: {0}"),
+    ("preview-skipped", "... пропущено строк: {0} ..."),
+    ("dupes-none", "{0}: повторяющихся строк нет"),
+    ("dupes-found", "{0}: повторяющихся строк: {1}"),
+    ("invisible-nbsp", "{0} × неразрывный пробел"),
+    ("invisible-bom", "{0} × метка порядка байтов (BOM)"),
+    ("invisible-zero-width", "{0} × символ нулевой ширины"),
+    ("invisible-bidi", "{0} × управляющий символ направления текста"),
+    ("invisible-space", "{0} × необычный пробел"),
+    ("invisible-control", "{0} × управляющий символ"),
+    ("invisible-more-lines", "{0}: и ещё строк: {1}"),
+    ("invisible-total", "{0}: невидимые символы в строках: {1}; всего {2}"),
+    ("invisible-none", "{0}: невидимых символов нет"),
+    ("dupes-lines", "строки {0}"),
+    ("dupes-hashed", "<хеш {0}, символов: {1}>"),
+    ("side-by-side-needs-two", "для --side-by-side нужно ровно два файла"),
+    ("compare-needs-two", "для --compare нужно ровно два файла"),
+    ("compare-stdin-twice", "--compare не может читать stdin для обоих входов"),
+    ("compare-identical", "{0} и {1} совпадают (байт: {2})"),
+    ("compare-differ", "{0} и {1} различаются"),
+    ("compare-first", "первое различие: байт {0} (0x{1}), строка {2}"),
+    ("compare-count", "различающихся байт: {0}"),
+    ("compare-shorter", "{0} заканчивается после байта {1}"),
+    ("compare-sizes", "размеры различаются: {0} — {1} байт, {2} — {3}"),
+    ("invalid-time", "некорректное время '{0}' (ожидается, например, '2024-08-07 14:00', '14:00', 'yesterday' или '15 min ago')"),
+    ("invalid-comment-style", "некорректный стиль комментариев '{0}' (ожидается hash, slash, semicolon или auto)"),
+    ("syntax-error", "{0}:{1}:{2}: {3}"),
+    ("yaml-tab-indent", "табуляция в отступах недопустима"),
+    ("yaml-unexpected-indent", "неожиданный отступ: у ключа выше уже есть значение"),
+    ("yaml-unterminated-quote", "незакрытая строка в кавычках"),
+    ("folded-lines", "свёрнуто строк: {0}"),
+    ("xml-collapsed", "элементов: {0}"),
+    ("invalid-nonprint-style", "некорректный стиль '{0}' (ожидается caret, hex или unicode)"),
+    ("invalid-index-format", "некорректный формат индекса '{0}' (ожидается tsv или json)"),
+    ("invalid-record-separator", "разделитель записей не может быть пустым"),
+    ("invalid-init-shell", "неподдерживаемая оболочка '{0}' (ожидается bash, zsh или fish)"),
+    ("record-needs-interactive", "--record записывает интерактивную оболочку, добавьте --interactive"),
+    ("bell-needs-watch", "--bell сообщает о новых строках отслеживаемой команды, добавьте --exec и --watch-interval"),
+    ("record-error", "не удалось записать сеанс в {0}: {1}"),
+    ("replay-not-recording", "{0} не является записью сеанса meow"),
+    ("replay-unsupported-version", "{0} записан в формате версии {1}, который этот meow не умеет воспроизводить"),
+    ("replay-truncated", "{0} обрывается посреди кадра, остаток потерян"),
+    ("invalid-brackets-mode", "некорректный режим --match-brackets '{0}' (ожидается check)"),
+    ("invalid-raw-mode", "некорректный режим --raw-passthrough '{0}' (ожидается timed)"),
+    ("cast-invalid-event", "{0}: строка {1} не является событием asciinema"),
+    ("brackets-unmatched", "{0}: непарных скобок: {1}: {2}"),
+    ("bracket-unexpected", "'{0}' ничего не закрывает"),
+    ("bracket-unclosed", "'{0}' не закрыта"),
+    ("invalid-heat-thresholds", "некорректное значение --heat-thresholds '{0}' (ожидаются два возрастающих числа, например 100,1000)"),
+    ("invalid-freshness", "некорректное значение --freshness '{0}' (ожидаются два возрастающих промежутка, например 1h,1d)"),
+    ("invalid-span", "некорректное значение --{0} '{1}' (ожидается, например, '90s', '15 min', '2h' или '3 days')"),
+    ("file-older-than", "ВНИМАНИЕ: {0} изменён {1}, больше {2} назад"),
+    ("file-newer-than", "ВНИМАНИЕ: {0} изменён {1}, меньше {2} назад; возможно, в него ещё пишут"),
+    ("file-changed-while-read", "ВНИМАНИЕ: {0} изменился во время чтения; вывод может смешивать старое и новое содержимое"),
+    ("heat-auto-fallback", "{0}: для --heat-auto нужен обычный файл с числами, используются --heat-thresholds"),
+    ("split-needs-output", "для --split-output нужен --output=ШАБЛОН, например --output=part_%03d.txt"),
+    ("split-template-count", "в шаблоне '{0}' должен быть ровно один номер, например %03d"),
+    ("split-template-placeholder", "некорректная подстановка в шаблоне '{0}' (ожидается %d, %03d или %%)"),
+    ("split-written", "записано файлов: {0}: {1}"),
+    ("output-error", "не удалось записать в {0}: {1}"),
+    ("invalid-color-depth", "некорректная глубина цвета '{0}' (ожидается 16, 256 или truecolor)"),
+    ("annotations-past-end", "{0}: аннотации после последней строки ({1}): {2}"),
+    ("invalid-number-hex", "некорректное значение --number-hex '{0}' (ожидается 0x)"),
+    ("invalid-escape-mode", "некорректный режим экранирования '{0}' (ожидается shell, c или json)"),
+    ("invalid-shell-quote-mode", "некорректный режим --shell-quote '{0}' (ожидается array)"),
+    ("fuzzy-distance-too-large", "с --fuzzy-distance {0} подойдёт что угодно: в шаблоне --grep-fuzzy всего символов: {1}"),
+    ("colors-sample", "Съешь же ещё этих мягких французских булок 0123456789"),
+    ("invalid-banner-variant", "неизвестный баннер '{0}' (доступны: {1})"),
+    ("indentation", "{0}: отступы: {1}"),
+    ("control-sequences-found", "{0}: содержит управляющие последовательности терминала, они показаны экранированными (--unsafe, чтобы передать их как есть)"),
+    ("escapes-neutralized", "{0}: обезврежено escape-последовательностей: {1}"),
+    ("redactions", "{0}: скрыто значений: {1}"),
+    ("invalid-redact-mode", "некорректный режим --redact '{0}' (ожидается pii)"),
+    ("invalid-redact-style", "некорректный стиль скрытия '{0}' (ожидается block или hash)"),
+    ("indent-tabs", "табуляция"),
+    ("indent-spaces", "пробелы по {0}"),
+    ("indent-none", "нет"),
+    ("invalid-byte-count", "некорректное количество байт: {0}"),
+    ("unsupported-language", "язык '{0}' не поддерживается"),
+    ("file-error", "{0}: {1}"),
+    ("write-error", "ошибка записи: {0}"),
+    ("tee-error", "не удалось записать в {0}: {1}"),
+    ("invalid-exec-command", "не удалось разобрать команду '{0}' (незакрытые кавычки?)"),
+    ("cannot-run-exec", "не удалось запустить '{0}': {1}"),
+    ("exec-failed", "'{0}' завершилась с ошибкой: {1}"),
+    ("exec-status", "[{0}]"),
+    ("exec-every", "Каждые {0}: {1}"),
+    ("invalid-interval", "некорректный интервал '{0}' (ожидается, например, 5s, 500ms или 2m)"),
+    ("invalid-resume-mode", "некорректное значение '{0}' для --resume (допускается только 'clear')"),
+    ("resumed-at", "\u{00B7}\u{00B7}\u{00B7} продолжение со строки {0}"),
+    ("resume-save-error", "не удалось сохранить позицию в {0}: {1}"),
+    ("read-error", "ошибка чтения: {0}"),
+    ("kind-fifo", "именованный канал"),
+    ("kind-char-device", "символьное устройство"),
+    ("kind-block-device", "блочное устройство"),
+    ("kind-socket", "сокет"),
+    ("kind-terminal", "терминал"),
+    ("kind-file", "обычный файл"),
+    ("kind-directory", "каталог"),
+    ("kind-symlink", "символическая ссылка"),
+    ("kind-other", "другое"),
+    ("refusing-special", "{0}: отказ читать: {1}"),
+    ("waiting-for-input", "{0}: {1}, ожидание ввода..."),
+    ("binary-image", "{0}: двоичный файл (изображение {1}) не показан"),
+    ("cannot-render-image", "{0}: не удалось отобразить изображение: {1}"),
+    ("invalid-date-format", "некорректный формат даты '{0}'"),
+    ("invalid-port", "некорректный порт '{0}'"),
+    ("invalid-bind-address", "некорректный адрес '{0}'"),
+    ("invalid-expand-env-mode", "некорректный режим --expand-env '{0}' (ожидается strict)"),
+    ("undefined-variables", "{0}: неопределённые переменные: {1}"),
+    ("invalid-checksum-algorithm", "неизвестный алгоритм контрольной суммы '{0}' (ожидается md5, sha1, sha256, sha512 или blake3)"),
+    ("invalid-field-list", "некорректный список полей '{0}' (ожидается, например, 1,3-5 или 2-)"),
+    ("empty-delimiter", "разделитель не может быть пустым"),
+    ("invalid-sort-key", "некорректный ключ сортировки '{0}' (ожидается name, size или mtime)"),
+    ("invalid-count", "некорректное значение '{1}' для --{0}"),
+    ("match-index-out-of-range", "{0}: нет совпадения номер {1} (найдено {2})"),
+    ("invalid-marks", "некорректное значение --marks '{0}' (ожидаются номера строк, например 120,340,702)"),
+    ("mark-label", "метка {0}"),
+    ("mark-index-entry", "{0} → строка {1}: {2}"),
+    ("marks-past-end", "{0}: метки за концом файла (строк: {1}): {2}"),
+    ("marks-index-needs-file", "{0}: для --marks-index нужен обычный файл, список меток не показан"),
+    ("empty-backup-suffix", "для --backup нужен непустой суффикс"),
+    ("in-place-stdin", "для --in-place нужны файлы, stdin перезаписать нельзя"),
+    ("in-place-filter", "--in-place нельзя использовать вместе с --filter"),
+    ("in-place-no-transform", "для --in-place нужна опция, изменяющая текст (например, -s, --grep, --escape)"),
+    ("not-a-regular-file", "не обычный файл"),
+    ("in-place-incomplete", "не удалось прочитать до конца, файл не изменён"),
+    ("rewritten", "перезаписан"),
+    ("malformed-checksum-line", "{0}:{1}: некорректная строка с контрольной суммой"),
+    ("no-checksum-lines", "{0}: не найдено строк с контрольными суммами"),
+    ("verify-ok", "OK"),
+    ("verify-failed", "ОШИБКА"),
+    ("verify-missing", "НЕТ ФАЙЛА"),
+    ("verify-summary", "в порядке: {0}, с ошибкой: {1}, отсутствует: {2}"),
+    ("cannot-listen", "не удалось открыть {0}: {1}"),
+    ("serving", "раздаём по адресу {0} (Ctrl-C для остановки)"),
+    ("config-error", "{0}: {1}"),
+    ("profile-invalid", "{0}: profiles.{1}: некорректные опции, профиль пропущен"),
+    ("preprocessor-failed", "{0}: двоичный файл не показан (ошибка препроцессора {1})"),
+    ("no-preprocessors", "в {0} не настроены препроцессоры"),
+    ("no-views", "в {0} не настроены представления"),
+    ("unknown-view", "неизвестное представление '{0}' (доступны: {1})"),
+    ("unknown-view-none", "неизвестное представление '{0}' (представления не настроены)"),
+    ("recursive-view", "представление '{0}' ссылается само на себя (возможно, через другие)"),
+    ("long-lines-split", "{0}: строки длиннее {1} байт были разбиты"),
+    ("qr-too-large", "{0}: {1} байт не помещаются в QR-код при такой ширине терминала (максимум {2} байт)"),
+    ("qr-unsupported", "QR-коды не поддерживаются этой сборкой (пересоберите с --features qr)"),
+    ("zip-unsupported", "zip-архивы не поддерживаются этой сборкой (пересоберите с --features zip)"),
+    ("zip-no-entry", "в архиве нет такого файла (см. meow --list)"),
+    ("title-matches", "совпадений: {0}"),
+    ("large-input-prompt", "{0} занимает {1} — всё равно показать? [y/N/p — в пейджере]"),
+    ("waiting-for-file", "ожидание появления {0}..."),
+    ("thousands-separator", "\u{a0}"),
+    ("decimal-point", ","),
+    ("fold-sections-hidden", "(+ строк: {0})"),
+    ("fold-sections-status", "{0}  строка {1}/{2}  Enter: раскрыть/свернуть  a: раскрыть все  c: свернуть все  /: поиск  q: выход"),
+    ("fold-sections-not-found", "не найдено: {0}"),
+    ("fold-sections-failed", "не удалось показать разделы: {0}"),
+    ("notify-unsupported", "уведомления на рабочем столе не поддерживаются этой сборкой (пересоберите с --features notify)"),
+    ("invalid-bell-mode", "некорректный режим --bell '{0}' (ожидается notify)"),
+    ("notify-failed", "не удалось отправить уведомление, вместо него будет звуковой сигнал: {0}"),
+    ("bell-new-lines", "новых подходящих строк: {0}"),
+    ("each-line-failed", "{0}: '{1}' завершилась с ошибкой для строк: {2}"),
+    ("cannot-run-filter", "{0}: не удалось запустить фильтр '{1}': {2}"),
+    ("filter-failed", "{0}: фильтр '{1}' завершился с ошибкой ({2})"),
+    ("meta-minutes-ago", "{0} мин. назад"),
+    ("meta-hours-ago", "{0} ч. назад"),
+    ("meta-days-ago", "{0} дн. назад"),
+    ("meta-unknown-time", "время неизвестно"),
+    ("meta-read-only", "только чтение"),
+    ("meta-read-write", "чтение и запись"),
+    ("meta-profile", "профиль {0}"),
+    ("stat-name", "имя"),
+    ("stat-size", "размер"),
+    ("stat-modified", "изменён"),
+    ("stat-type", "тип"),
+    ("stat-permissions", "права"),
+    ("summary-read", "прочитано"),
+    ("summary-printed", "показано"),
+    ("summary-matches", "совпадений"),
+    ("summary-errors", "ошибок"),
+    ("summary-time", "время"),
+    ("summary-total", "всего"),
+    ("nothing-to-edit", "нечего открывать в редакторе"),
+    ("no-editor", "редактор не настроен (задайте $VISUAL или $EDITOR)"),
+    ("cannot-launch-editor", "не удалось запустить редактор '{0}': {1}"),
+    ("editor-failed", "редактор '{0}' завершился с ошибкой: {1}"),
+    ("cannot-start-pager", "не удалось запустить пейджер '{0}', вывод показан здесь: {1}"),
+    ("blame-regular-only", "{0}: blame доступен только для обычных файлов"),
+    ("blame-failed", "{0}: git blame завершился с ошибкой, файл показан без blame"),
+    ("changes-regular-only", "{0}: --changes доступен только для обычных файлов"),
+    ("changes-no-repo", "{0}: не git-репозиторий, --changes игнорируется"),
+    ("changes-unknown-ref", "{0}: неизвестная ревизия '{1}', --changes игнорируется"),
+    ("changes-failed", "{0}: git diff завершился с ошибкой, --changes игнорируется"),
+    ("diff-identical", "Файлы идентичны"),
+    ("shell-title", "=== Интерактивная оболочка Meow ==="),
+    ("shell-intro", "Введите 'help' для списка команд, 'exit' для выхода"),
+    ("shell-commands", "Доступные команды:"),
+    ("shell-help-cat", "  cat <файл>    - Показать содержимое файла"),
+    ("shell-help-grep", "  grep <шаблон> <файл> - Найти шаблон в файле"),
+    ("shell-help-highlight", "  highlight <шаблон> <файл> - Выделить шаблон в файле"),
+    ("shell-help-rainbow", "  rainbow <файл> - Показать файл радужными цветами"),
+    ("shell-help-diff", "  diff <файл1> <файл2> - Показать цветное сравнение файлов"),
+    ("shell-help-view", "  view <имя> <файл> - Показать файл с опциями представления из файла настроек"),
+    ("shell-help-edit", "  edit <файл>   - Открыть файл в $EDITOR"),
+    ("shell-help-history", "  history       - Показать историю команд"),
+    ("shell-help-exit", "  exit/quit     - Выйти из оболочки"),
+    ("shell-usage", "Использование: {0}"),
+    ("shell-open-error", "Ошибка: не удалось открыть файл '{0}'"),
+    ("pick-needs-terminal", "для --pick ввод и вывод должны быть терминалом"),
+    ("pick-failed", "не удалось показать список файлов: {0}"),
+    ("pick-return", "-- любая клавиша: назад к списку, q: выход --"),
+    ("shell-history", "История команд:"),
+    ("shell-unknown-command", "Неизвестная команда: '{0}'"),
+    ("shell-unknown-hint", "Введите 'help', чтобы увидеть доступные команды"),
+    ("help-usage", "Использование:"),
+    ("help-description", "Выводит ФАЙЛ(ы) на стандартный вывод с улучшениями."),
+    ("help-stdin", "Если ФАЙЛ не указан или равен -, читается стандартный ввод."),
+    ("help-examples", "Примеры:"),
+    ("help-report-bugs", "Сообщения об ошибках: {0}"),
+    ("option.number", "нумеровать все выходные строки"),
+    ("option.number-nonblank", "нумеровать только непустые строки"),
+    ("option.number-hex", "показывать номера строк в шестнадцатеричном виде, с =0x — с префиксом"),
+    ("option.show-ends", "показывать $ в конце каждой строки"),
+    ("option.show-tabs", "показывать символы TAB как ^I"),
+    ("option.squeeze-blank", "сжимать повторяющиеся пустые строки"),
+    ("option.show-nonprinting", "показывать все непечатаемые символы"),
+    ("option.nonprint-style", "показывать управляющие символы как caret (^[, по умолчанию), hex (\\x1b) или unicode (\u{241B})"),
+    ("option.show-length", "показывать количество строк и символов, а также ширину в колонках, если она другая"),
+    ("option.ruler", "печатать линейку с номерами колонок перед каждым файлом"),
+    ("option.ruler-every", "повторять линейку каждые N строк"),
+    ("option.max-col", "выделять текст после колонки N"),
+    ("option.truncate-middle", "укорачивать строки шире N колонок, заменяя середину на …"),
+    ("option.tab-width", "считать позиции табуляции через каждые N колонок для --max-col, --truncate-middle, --fold и --side-by-side (по умолчанию 8)"),
+    ("option.fold", "переносить строки по пробелам, чтобы они помещались в N колонок"),
+    ("option.fold-indent", "с --fold отступать в строках-продолжениях на N пробелов"),
+    ("option.rainbow", "включить радужный режим"),
+    ("option.no-color", "отключить цвета"),
+    ("option.color-depth", "использовать 16, 256 или truecolor цветов (по умолчанию — по $COLORTERM и $TERM)"),
+    ("option.interactive", "войти в интерактивный режим после обработки"),
+    ("option.record", "с --interactive записать сеанс в FILE для --replay"),
+    ("option.replay", "воспроизвести сеанс, записанный с --record, ничего не выполняя"),
+    ("option.replay-speed", "с --replay или --raw-passthrough=timed воспроизводить в N раз быстрее (например 2x, 0.5x)"),
+    ("option.replay-instant", "с --replay показать весь сеанс сразу"),
+    ("option.pick", "выбрать файлы текущего каталога из списка с фильтром (ввод фильтрует, Enter показывает, Esc выходит)"),
+    ("option.meta", "показать метаданные файла"),
+    ("option.date-format", "показывать время в --meta в формате strftime FMT вместо относительного"),
+    ("option.freshness", "окрашивать время изменения по давности: зелёным до FRESH, жёлтым до STALE, красным позже (по умолчанию 1h,1d)"),
+    ("option.warn-older-than", "предупреждать, если файл последний раз изменялся больше SPAN назад, например 2h"),
+    ("option.warn-newer-than", "предупреждать, если файл изменён меньше SPAN назад или во время чтения"),
+    ("option.page", "использовать пейджер (например, less)"),
+    ("option.more", "останавливаться после каждого экрана, как more (пробел, Enter, q)"),
+    ("option.animate", "анимированный вывод текста"),
+    ("option.throttle", "выводить не больше RATE строк в секунду (0 — без ограничения)"),
+    ("option.quiet", "не выводить информационные сообщения"),
+    ("option.grep", "показывать только строки, содержащие шаблон"),
+    ("option.context", "с --grep показывать также N строк вокруг каждого совпадения"),
+    ("option.before-context", "с --grep показывать также N строк перед каждым совпадением"),
+    ("option.after-context", "с --grep показывать также N строк после каждого совпадения"),
+    ("option.record-separator", "делить ввод на записи по SEP, а не по переводам строк (понимаются \\r, \\t, \\0 и \\xHH)"),
+    ("option.group-separator", "выводить SEP между группами строк контекста (по умолчанию --)"),
+    ("option.no-group-separator", "ничего не выводить между группами строк контекста"),
+    ("option.match-index", "с --grep показать только N-е совпадение (и его контекст)"),
+    ("option.marks", "рисовать пронумерованную черту над строками из LIST (например 120,340,702); с --more m и M переходят между ними"),
+    ("option.mark-pattern", "отмечать строки, подходящие под RE, как --marks"),
+    ("option.marks-index", "перед каждым файлом выводить список меток с началом их строк"),
+    ("option.max-count", "с --grep прекратить чтение файла после N подходящих строк, как grep -m"),
+    ("option.max-count-total", "считать --max-count по всем файлам вместе, а не для каждого"),
+    ("option.grep-file", "показывать только строки, содержащие любой из шаблонов в FILE (по одному на строку), как grep -f"),
+    ("option.grep-file-comments", "пропускать строки, начинающиеся с #, в файле шаблонов --grep-file"),
+    ("option.grep-fuzzy", "показывать только строки, в которых есть фрагмент не дальше --fuzzy-distance правок от шаблона"),
+    ("option.fuzzy-distance", "сколько вставок, удалений и замен допускает --grep-fuzzy (по умолчанию 2)"),
+    ("option.show-distance", "с --grep-fuzzy показывать, сколько правок понадобилось для совпадения в строке"),
+    ("option.min-length", "показывать только строки длиной не меньше N символов"),
+    ("option.max-length", "показывать только строки длиной не больше N символов"),
+    ("option.preview", "показать первые и последние N строк с числом пропущенных между ними"),
+    ("option.fields", "показывать только эти поля, например 1,3-5 или 2-"),
+    ("option.fields-invert", "показывать все поля, кроме указанных в --fields"),
+    ("option.delimiter", "разделитель полей для --fields (по умолчанию пробельные символы) и --highlight-field"),
+    ("option.output-delimiter", "соединять выбранные поля через DELIM"),
+    ("option.csv", "разбирать поля как CSV, не разделяя по разделителям в кавычках"),
+    ("option.highlight", "выделить шаблон в выводе"),
+    ("option.with-filename", "выводить имя файла перед каждой строкой"),
+    ("option.file-colors", "свой цвет выделения для каждого файла"),
+    ("option.highlight-regex", "выделить все совпадения с регулярным выражением"),
+    ("option.match-brackets", "раскрасить пары (), [] и {} по глубине вложенности, а непарные — как ошибки; =check только сообщает о непарных"),
+    ("option.number-heat", "раскрасить числа от жёлтого к красному по мере превышения --heat-thresholds"),
+    ("option.heat-units", "с --number-heat учитывать и числа с единицами, например 5ms или 12KB"),
+    ("option.heat-thresholds", "с --number-heat: где начинается раскраска и где она становится красной (по умолчанию 100,1000)"),
+    ("option.heat-auto", "с --number-heat брать пороги из 90-го и 99-го процентилей чисел каждого файла"),
+    ("option.highlight-field", "выделить N-е поле каждой строки, разделённой по --delimiter (по умолчанию табуляция)"),
+    ("option.list-matches", "вывести вместо содержимого все различные совпадения с RE во всех файлах, по алфавиту"),
+    ("option.list-matches-count", "с --list-matches показать перед каждым совпадением, сколько раз оно встретилось"),
+    ("option.image-protocol", "протокол предпросмотра изображений: auto, blocks, kitty, iterm"),
+    ("option.image-width", "ширина предпросмотра изображения в колонках"),
+    ("option.sample", "показывать только каждую N-ю строку"),
+    ("option.sample-spread", "вывести N строк, равномерно распределённых по входным данным"),
+    ("option.sample-seed", "сделать --sample-spread для потоков воспроизводимым"),
+    ("option.sample-random", "показывать каждую строку с вероятностью P (0..1)"),
+    ("option.exec", "показать вывод команды CMD вместо файлов"),
+    ("option.exec-shell", "запускать команду --exec через sh -c"),
+    ("option.watch-interval", "перезапускать команду --exec каждые TIME (например, 5s, 500ms, 2m)"),
+    ("option.diff-changes", "с --watch-interval подсвечивать строки, изменившиеся с прошлого запуска"),
+    ("option.exec-stop-on-error", "прекратить наблюдение, если команда завершилась с ошибкой"),
+    ("option.each-line-command", "запускать CMD для каждой строки, показанной --grep, подставляя строку вместо {} или передавая её на stdin (--exec — это вывод команды вместо файла)"),
+    ("option.each-line-output", "показывать вывод --each-line-command под каждой строкой"),
+    ("option.each-line-jobs", "запускать не больше N команд --each-line-command одновременно (по умолчанию 4)"),
+    ("option.bell", "с --watch-interval подавать звуковой сигнал, когда появляются новые строки, подходящие под --grep; =notify отправляет уведомление на рабочий стол"),
+    ("option.filter", "пропускать каждый ввод через CMD ({} - путь к файлу)"),
+    ("option.expand-env", "подставлять $VAR и ${VAR} из окружения; strict отмечает неопределённые и завершается с ошибкой"),
+    ("option.env-file", "с --expand-env загрузить также переменные из dotenv-файла FILE"),
+    ("option.annotations", "показывать заметки из FILE (JSON или 'СТРОКА: текст') рядом с их строками"),
+    ("option.annotations-block", "показывать заметки --annotations отдельными строками под строкой"),
+    ("option.annotations-always", "показывать строки с заметками, даже если --grep их не выбирает"),
+    ("option.rules", "раскрашивать совпадения по правилам с регулярными выражениями из FILE или из rules/FILE.toml рядом с файлом настроек"),
+    ("option.theme", "взять цвета из файла темы FILE в формате TOML"),
+    ("option.export-theme", "записать действующие цвета (с учётом --theme и --color-depth) в FILE как тему и выйти"),
+    ("option.strip-comments", "удалять комментарии и строки, состоящие только из комментариев"),
+    ("option.comment-style", "синтаксис комментариев для --strip-comments: hash, slash, semicolon или auto"),
+    ("option.since", "показывать только строки журнала с меткой времени не раньше TIME (например, '2024-08-07 14:00', '15 min ago')"),
+    ("option.until", "показывать только строки журнала с меткой времени не позже TIME"),
+    ("option.time-format", "формат strftime меток времени в начале строк журнала"),
+    ("option.skip-untimed", "с --since/--until пропускать строки без метки времени, а не относить их к строке выше"),
+    ("option.redact", "скрыть API-ключи, токены, пароли и закрытые ключи; =pii также скрывает адреса email"),
+    ("option.redact-style", "заменять секреты блоком (по умолчанию) или хешем, чтобы одинаковые секреты совпадали"),
+    ("option.redact-pattern", "также скрывать совпадения RE или его первой группы (можно повторять)"),
+    ("option.json-array", "вывести результат как JSON-массив объектов {file, line, text}"),
+    ("option.group-by-file", "как --json-array, но одним объектом с {\"lines\": [...]} для каждого файла"),
+    ("option.yaml", "подсветить и проверить ввод как YAML (для .yaml/.yml на терминале — автоматически)"),
+    ("option.toml", "подсветить и проверить ввод как TOML (для .toml на терминале — автоматически)"),
+    ("option.plain", "вывести ввод без изменений, не применяя опции отображения (в том числе подсветку YAML и TOML)"),
+    ("option.raw-passthrough", "как --plain, для ANSI-графики и записей терминала; =timed воспроизводит записи asciinema в исходном темпе"),
+    ("option.fold-depth", "в YAML свернуть уровни глубже N"),
+    ("option.fold-sections", "свернуть строки после каждой строки, подходящей под RE; в терминале Enter раскрывает раздел, a — все, / — поиск"),
+    ("option.xml", "расставить отступы и подсветить XML или HTML"),
+    ("option.xml-indent", "отступ вывода --xml в N пробелов на уровень (по умолчанию 2)"),
+    ("option.xml-collapse", "с --xml показывать элементы TAG одной строкой-сводкой (можно повторять)"),
+    ("option.escape", "экранировать строки как литералы shell, c или json"),
+    ("option.shell-quote", "заключить каждую строку в кавычки для POSIX shell, без цветов и номеров; =array — все строки как слова одной строки"),
+    ("option.null", "завершать каждую строку вывода байтом NUL вместо перевода строки, для xargs -0"),
+    ("option.edit", "открыть первое совпадение (или строку 1) в $VISUAL/$EDITOR"),
+    ("option.edit-all", "открыть в редакторе все файлы с совпадениями"),
+    ("option.edit-cmd", "шаблон редактора: vim, emacs, code или, например, 'subl {file}:{line}'"),
+    ("option.tail-bytes", "показать строки из последних N байт (нумерация с начала хвоста)"),
+    ("option.serve", "раздавать вывод по HTTP на порту PORT (по умолчанию 8000)"),
+    ("option.serve-bind", "с --serve слушать адрес ADDR (по умолчанию 127.0.0.1; 0.0.0.0 — для всей сети)"),
+    ("option.serve-raw", "раздавать файлы без обработки"),
+    ("option.serve-once", "остановиться после первого запроса"),
+    ("option.checksum", "вывести хеши md5, sha1, sha256, sha512 или blake3 вместо содержимого (с --meta — в заголовке)"),
+    ("option.index", "вывести номер, смещение в байтах и длину в байтах каждой строки (или совпадения --grep) вместо содержимого"),
+    ("option.index-format", "формат записей --index: tsv (по умолчанию) или json lines"),
+    ("option.verify", "проверить файлы по спискам контрольных сумм (формат sha256sum или BSD)"),
+    ("option.side-by-side", "показать два файла рядом, в две колонки"),
+    ("option.compare", "как cmp, сообщить, совпадают ли два файла и где они начинают различаться (код 1, если различаются)"),
+    ("option.side-by-side-wrap", "с --side-by-side переносить длинные строки, а не обрезать их"),
+    ("option.merge", "чередовать строки файлов: первая строка каждого, затем вторая и так далее"),
+    ("option.merge-delimiter", "с --merge собирать строки одного круга в одну строку через SEP"),
+    ("option.merge-shortest", "с --merge остановиться на конце самого короткого файла, а не дополнять пустыми строками"),
+    ("option.stat", "вывести размер, время изменения, тип и права вместо содержимого"),
+    ("option.max-terminal-bytes", "спрашивать, прежде чем выводить в терминал файл больше SIZE (по умолчанию 50 МБ; 0 — не спрашивать)"),
+    ("option.force", "выводить большие файлы в терминал, не спрашивая"),
+    ("option.wait-for-file", "если файла ещё нет, дождаться его создания и затем показать"),
+    ("option.wait-timeout", "с --wait-for-file — перестать ждать через MS миллисекунд"),
+    ("option.si", "показывать размеры в степенях 1000 (KB, MB), а не 1024 (KiB, MiB)"),
+    ("option.bytes-exact", "показывать размеры точным числом байт, а числа без разделителей — для скриптов"),
+    ("option.thousands-separator", "разделять группы разрядов в числах строкой SEP (по умолчанию — как принято в языке; пустая строка — без разделителя)"),
+    ("option.summary", "после содержимого вывести таблицу: размер каждого входа, прочитанные и показанные строки, совпадения, ошибки и время"),
+    ("option.summary-only", "вывести только таблицу --summary, без содержимого"),
+    ("option.resume", "продолжить файлы с места, где остановился прошлый запуск; =clear забывает сохранённую позицию"),
+    ("option.tee", "также записывать вывод в FILE в точности как на экране (можно повторять)"),
+    ("option.tee-plain", "также записывать вывод в FILE без цветов (можно повторять)"),
+    ("option.output", "записывать вывод в FILE вместо stdout, без цветов"),
+    ("option.split-output", "с --output=TEMPLATE начинать новый пронумерованный файл каждые N строк"),
+    ("option.split-output-bytes", "как --split-output, но по размеру (K, M, G), не разрывая строк"),
+    ("option.in-place", "записать преобразованный вывод обратно в каждый файл"),
+    ("option.backup", "с --in-place сохранить исходный файл как FILE+SUFFIX"),
+    ("option.preserve-times", "с --in-place сохранить время изменения файла"),
+    ("option.blame", "показывать git blame (коммит, автор, возраст)"),
+    ("option.changes", "отмечать строки, изменённые относительно индекса git или REF"),
+    ("option.banner", "сначала вывести ASCII-котика (sitting, loaf, sleeping)"),
+    ("option.title", "вывести TEXT крупными буквами перед содержимым"),
+    ("option.force-art", "выводить --banner и --title, даже если stdout не терминал"),
+    ("option.prepend", "выводить TEXT перед содержимым каждого файла (@FILE — взять текст из FILE)"),
+    ("option.append", "выводить TEXT после содержимого каждого файла (@FILE — взять текст из FILE)"),
+    ("option.skip-blank-numbers", "с -n оставлять пустой номер у пустых строк и не учитывать их"),
+    ("option.continuous-numbering", "продолжать нумерацию между файлами, а не начинать заново"),
+    ("option.max-line-bytes", "разбивать строки длиннее N байт, не загружая их целиком"),
+    ("option.qr", "показать содержимое в виде QR-кода"),
+    ("option.qr-invert", "поменять цвета QR-кода местами для светлого фона терминала"),
+    ("option.find-dupes", "сообщать о строках, встречающихся во входных данных больше одного раза"),
+    ("option.count-invisible", "сообщать, в каких строках есть неразрывные пробелы, символы нулевой ширины, управляющие символы и т. п."),
+    ("option.mark-dupes", "отмечать повторные вхождения строки на полях"),
+    ("option.dupes-ignore-case", "искать повторы без учёта регистра"),
+    ("option.dupes-ignore-whitespace", "искать повторы, не учитывая различия в пробелах"),
+    ("option.detect-indent", "сообщить, чем сделаны отступы во входных данных: табуляцией или пробелами"),
+    ("option.no-preprocess", "показывать файлы как есть, без препроцессоров из файла настроек"),
+    ("option.list-preprocessors", "вывести препроцессоры из файла настроек и выйти"),
+    ("option.no-profiles", "не применять профили опций для типов файлов из файла настроек"),
+    ("option.view", "использовать аргументы, сохранённые в файле настроек как представление NAME (или @NAME)"),
+    ("option.list-views", "вывести представления из файла настроек и выйти"),
+    ("option.recursive", "читать все файлы в каталогах рекурсивно"),
+    ("option.exclude", "с -R пропускать файлы и каталоги, подходящие под GLOB (можно повторять)"),
+    ("option.include", "с -R читать только файлы, подходящие под GLOB (можно повторять)"),
+    ("option.flatten", "не выводить заголовки файлов, единым потоком"),
+    ("option.tree", "выводить заголовки файлов в виде дерева каталогов"),
+    ("option.list-dirs", "выводить содержимое каталогов вместо ошибки"),
+    ("option.set-title", "показывать в заголовке окна терминала читаемый файл, прогресс и число совпадений --grep"),
+    ("option.list", "выводить список файлов в zip-архивах вместо их содержимого"),
+    ("option.zip-entry", "показать файл PATH из каждого zip-архива (или ARCHIVE.zip:PATH)"),
+    ("option.sort-dir", "сортировать список каталога по name, size или mtime"),
+    ("option.dirs-first", "показывать подкаталоги первыми в списке каталога"),
+    ("option.no-special", "не читать FIFO, устройства и сокеты"),
+    ("option.safe", "показывать escape-последовательности из входных данных как ^[ вместо передачи в терминал"),
+    ("option.unsafe", "передавать escape-последовательности из входных данных как есть, даже подозрительные"),
+    ("option.preserve-ansi", "сохранять цвета из входных данных после номеров строк и других полей слева"),
+    ("option.lang", "язык сообщений (en, ru)"),
+    ("option.init", "вывести функцию cat для bash, zsh или fish, которая в терминале вызывает meow"),
+    ("option.help", "показать эту справку и выйти"),
+    ("example.0", "Показать файл с номерами строк"),
+    ("example.1", "Показать табуляции и концы строк"),
+    ("example.2", "Показать только строки с 'pattern'"),
+    ("example.3", "Радужный вывод"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn keys(messages: &[(&'static str, &str)]) -> BTreeSet<&'static str> {
+        messages.iter().map(|(key, _)| *key).collect()
+    }
+
+    /// The numbers of a template's `{n}` placeholders.
+    fn placeholders(template: &str) -> BTreeSet<usize> {
+        template.split('{').skip(1).filter_map(|rest| rest.split_once('}')?.0.parse().ok()).collect()
+    }
+
+    #[test]
+    fn catalogs_have_no_duplicate_keys() {
+        for catalog in CATALOGS {
+            assert_eq!(keys(catalog.messages).len(), catalog.messages.len(), "duplicate key in {}", catalog.code);
+        }
+    }
+
+    #[test]
+    fn every_message_is_translated() {
+        let english = keys(EN);
+        for catalog in &CATALOGS[1..] {
+            let translated = keys(catalog.messages);
+            let missing: Vec<_> = english.difference(&translated).collect();
+            assert!(missing.is_empty(), "{} lacks {:?}", catalog.code, missing);
+            let unknown: Vec<_> = translated.iter().filter(|key| !english.contains(*key) && !key.starts_with("option.") && !key.starts_with("example.")).collect();
+            assert!(unknown.is_empty(), "{} has keys English lacks: {:?}", catalog.code, unknown);
+        }
+    }
+
+    #[test]
+    fn translations_keep_the_placeholders() {
+        for catalog in &CATALOGS[1..] {
+            for (key, template) in catalog.messages {
+                let english = EN.iter().find(|(k, _)| k == key).map(|(_, text)| *text);
+                if let Some(english) = english {
+                    assert_eq!(placeholders(template), placeholders(english), "{} in {}", key, catalog.code);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_option_and_example_is_translated() {
+        for catalog in &CATALOGS[1..] {
+            let translated = keys(catalog.messages);
+            for spec in crate::OPTIONS.iter().filter(|spec| !spec.hidden) {
+                assert!(translated.contains(format!("option.{}", spec.long).as_str()), "{} lacks option.{}", catalog.code, spec.long);
+            }
+            for i in 0..crate::EXAMPLES.len() {
+                assert!(translated.contains(format!("example.{}", i).as_str()), "{} lacks example.{}", catalog.code, i);
+            }
+        }
+    }
+
+    #[test]
+    fn every_key_the_code_uses_is_in_the_catalog() {
+        let english = keys(EN);
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let code = std::fs::read_to_string(&path).unwrap();
+            for used in code.split("msg!(\"").skip(1) {
+                let key = &used[..used.find('"').unwrap()];
+                assert!(english.contains(key), "{} uses {}, which isn't in the catalog", path.display(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn arguments_are_positional() {
+        let args: [&dyn Display; 2] = [&"a.txt", &3];
+        assert_eq!(format_message("short-option-requires-value", &args), "-a.txt requires an argument (3)");
+        assert_eq!(format_message("option-takes-no-value", &args[..0]), "--{0} does not take a value");
+    }
+}
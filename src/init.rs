@@ -0,0 +1,187 @@
+use crate::OPTIONS;
+
+/// Options meow shares with cat. Scripts using them get real cat, whose output
+/// for them differs from meow's.
+const CAT_SHORTS: &str = "nbETsA";
+const CAT_LONGS: &[&str] = &["number", "number-nonblank", "show-ends", "show-tabs", "squeeze-blank", "show-nonprinting"];
+
+/// `--init=SHELL`: a `cat` function for the shell's rc file that runs meow in a
+/// terminal. Output that goes elsewhere is left alone: `meow --plain` copies the
+/// files as they are and cat's own options go to real cat, so scripts don't
+/// break; options only meow has still run meow. Options meow doesn't know go to
+/// real cat everywhere. They come from `OPTIONS`, so the function stays in step
+/// with the binary that printed it.
+pub fn snippet(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(posix("bash", "~/.bashrc")),
+        "zsh" => Some(posix("zsh", "~/.zshrc")),
+        "fish" => Some(fish()),
+        _ => None,
+    }
+}
+
+/// Letters of the short options, e.g. `nbETs`.
+fn short_options() -> String {
+    OPTIONS.iter().filter_map(|spec| spec.short).collect()
+}
+
+/// Shell patterns for the long options only meow has: `--name`, `--name=*` or both.
+fn long_patterns() -> Vec<String> {
+    let mut patterns = Vec::new();
+    for spec in OPTIONS.iter().filter(|spec| !spec.hidden && !CAT_LONGS.contains(&spec.long)) {
+        if spec.value.is_none() || spec.optional {
+            patterns.push(format!("--{}", spec.long));
+        }
+        if spec.value.is_some() {
+            patterns.push(format!("--{}=*", spec.long));
+        }
+    }
+    patterns
+}
+
+/// Joins `items` with `separator`, starting a new line after `indent` once a
+/// line gets long.
+fn wrap(items: &[String], separator: &str, continuation: &str, indent: &str) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            text.push_str(separator);
+            if line_length > 72 {
+                text.push_str(continuation);
+                text.push('\n');
+                text.push_str(indent);
+                line_length = 0;
+            }
+        }
+        text.push_str(item);
+        line_length += item.len() + separator.len();
+    }
+    text
+}
+
+fn posix(shell: &str, rc_file: &str) -> String {
+    let shorts = short_options();
+    let cat_longs: Vec<String> = CAT_LONGS.iter().map(|long| format!("--{}", long)).collect();
+    let cat_longs = cat_longs.join("|");
+    let longs = wrap(&long_patterns(), "|", "\\", "            ");
+    format!(r#"# meow integration for {shell}, add to {rc_file}:
+#     eval "$(meow --init={shell})"
+cat() {{
+    local arg options= extended=
+    for arg in "$@"; do
+        case "$arg" in
+            -) ;;
+            {cat_longs})
+                options=1 ;;
+            {longs})
+                extended=1 ;;
+            -[{shorts}]*)
+                case "${{arg#-}}" in
+                    *[!{shorts}]*) command cat "$@"; return ;;
+                    *[!{CAT_SHORTS}]*) extended=1 ;;
+                esac
+                options=1 ;;
+            -*) command cat "$@"; return ;;
+        esac
+    done
+    if [ -t 1 ] || [ -n "$extended" ]; then
+        command meow "$@"
+    elif [ -n "$options" ]; then
+        command cat "$@"
+    else
+        command meow --plain "$@"
+    fi
+}}
+"#)
+}
+
+fn fish() -> String {
+    let shorts = short_options();
+    let longs: Vec<String> = long_patterns().iter().map(|pattern| format!("'{}'", pattern)).collect();
+    let longs = wrap(&longs, " ", "\\", "            ");
+    let cat_longs: Vec<String> = CAT_LONGS.iter().map(|long| format!("--{}", long)).collect();
+    let cat_longs = cat_longs.join(" ");
+    format!(r#"# meow integration for fish, add to ~/.config/fish/config.fish:
+#     meow --init=fish | source
+function cat --wraps cat --description 'meow in a terminal, plain cat elsewhere'
+    set -l options 0
+    set -l extended 0
+    for arg in $argv
+        switch $arg
+            case -
+            case {cat_longs}
+                set options 1
+            case {longs}
+                set extended 1
+            case '-*'
+                if string match -qr -- '^-[{CAT_SHORTS}]+$' $arg
+                    set options 1
+                else if string match -qr -- '^-[{shorts}]+$' $arg
+                    set extended 1
+                else
+                    command cat $argv
+                    return
+                end
+        end
+    end
+    if isatty stdout; or test $extended = 1
+        command meow $argv
+    else if test $options = 1
+        command cat $argv
+    else
+        command meow --plain $argv
+    end
+end
+"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// Whether `shell -n` accepts `script`; None when the shell isn't installed.
+    fn parses(shell: &str, script: &str) -> Option<bool> {
+        let output = Command::new(shell).arg("-n").arg("-c").arg(script).output().ok()?;
+        assert!(output.stderr.is_empty() || !output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        Some(output.status.success())
+    }
+
+    #[test]
+    fn snippets_parse() {
+        for (shell, checkers) in [("bash", &["bash", "sh"][..]), ("zsh", &["zsh", "sh"]), ("fish", &["fish"])] {
+            let script = snippet(shell).unwrap();
+            for checker in checkers {
+                assert_ne!(parses(checker, &script), Some(false), "{} snippet doesn't parse with {}:\n{}", shell, checker, script);
+            }
+        }
+        // sh is always there, so a broken POSIX snippet can't go unnoticed
+        assert_eq!(parses("sh", "cat() {"), Some(false));
+    }
+
+    #[test]
+    fn unknown_shell() {
+        assert!(snippet("tcsh").is_none());
+        assert!(snippet("").is_none());
+    }
+
+    #[test]
+    fn patterns_follow_the_option_table() {
+        let patterns = long_patterns();
+        assert!(patterns.contains(&"--grep=*".to_string()));
+        assert!(patterns.contains(&"--plain".to_string()));
+        // cat's own options stay with cat
+        assert!(!patterns.contains(&"--number".to_string()) && !patterns.contains(&"--show-ends".to_string()));
+        assert!(short_options().contains('n') && short_options().contains('A'));
+    }
+
+    #[test]
+    fn wrap_breaks_long_lines() {
+        let items: Vec<String> = (0..40).map(|i| format!("--option-{}", i)).collect();
+        let text = wrap(&items, "|", "\\", "    ");
+        assert!(text.lines().count() > 1);
+        assert!(text.lines().all(|line| line.len() <= 90), "{}", text);
+        assert_eq!(text.replace("\\\n    ", "").split('|').count(), 40);
+    }
+}
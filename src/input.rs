@@ -0,0 +1,185 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Stdin};
+use std::path::{Path, PathBuf};
+
+/// Where an input comes from. A new kind of input gets a variant here and an arm
+/// in `open`; the main loop only deals with the opened `Input`.
+pub enum InputSource {
+    Stdin,
+    File(PathBuf),
+    /// A zip archive and the path of an entry in it
+    ZipEntry(PathBuf, String),
+}
+
+impl InputSource {
+    /// `-` stands for stdin, like in cat, and `ARCHIVE.zip:PATH` for an entry
+    /// of a zip archive, unless a file has that name.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            return InputSource::Stdin;
+        }
+        if let Some(at) = arg.find(".zip:").filter(|_| !Path::new(arg).exists()) {
+            let (archive, entry) = arg.split_at(at + ".zip".len());
+            return InputSource::ZipEntry(PathBuf::from(archive), entry[1..].to_string());
+        }
+        InputSource::File(PathBuf::from(arg))
+    }
+
+    /// The name used in headers and messages.
+    pub fn name(&self) -> String {
+        match self {
+            InputSource::Stdin => "stdin".to_string(),
+            InputSource::File(path) => path.to_string_lossy().into_owned(),
+            InputSource::ZipEntry(archive, entry) => format!("{}:{}", archive.to_string_lossy(), entry),
+        }
+    }
+
+    /// The path on disk, for features that need more than the bytes
+    /// (preprocessors, directory listings, metadata).
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            InputSource::Stdin | InputSource::ZipEntry(..) => None,
+            InputSource::File(path) => Some(path),
+        }
+    }
+
+    pub fn open(&self) -> io::Result<Input> {
+        match self {
+            InputSource::Stdin => Ok(Input {
+                metadata: None,
+                reader: InputReader::Stdin(io::stdin()),
+            }),
+            InputSource::File(path) => {
+                let file = File::open(path)?;
+                Ok(Input {
+                    metadata: file.metadata().ok(),
+                    reader: InputReader::File(file),
+                })
+            },
+            #[cfg(feature = "zip")]
+            InputSource::ZipEntry(archive, entry) => Ok(Input {
+                metadata: None,
+                reader: InputReader::ZipEntry(crate::archive::open_entry(archive, entry)?),
+            }),
+            #[cfg(not(feature = "zip"))]
+            InputSource::ZipEntry(..) => Err(io::Error::new(io::ErrorKind::Unsupported, msg!("zip-unsupported"))),
+        }
+    }
+}
+
+/// An opened input source.
+pub struct Input {
+    /// None for stdin, or when the file couldn't be stat'ed
+    pub metadata: Option<fs::Metadata>,
+    pub reader: InputReader,
+}
+
+impl Input {
+    /// Regular files can be seeked, which `--tail-bytes` and `--sample-spread` use
+    /// to avoid reading everything.
+    pub fn is_regular_file(&self) -> bool {
+        self.metadata.as_ref().is_some_and(|meta| meta.is_file())
+    }
+}
+
+pub enum InputReader {
+    Stdin(Stdin),
+    File(File),
+    #[cfg(feature = "zip")]
+    ZipEntry(crate::archive::EntryReader),
+}
+
+impl Read for InputReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            InputReader::Stdin(stdin) => stdin.read(buf),
+            InputReader::File(file) => file.read(buf),
+            #[cfg(feature = "zip")]
+            InputReader::ZipEntry(entry) => entry.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(source: &InputSource) -> io::Result<String> {
+        let mut text = String::new();
+        source.open()?.reader.read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    #[test]
+    fn stdin_source() {
+        let source = InputSource::from_arg("-");
+        assert!(matches!(source, InputSource::Stdin));
+        assert_eq!(source.name(), "stdin");
+        assert_eq!(source.path(), None);
+        let input = source.open().unwrap();
+        assert!(input.metadata.is_none());
+        assert!(!input.is_regular_file());
+    }
+
+    #[test]
+    fn file_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "hello\n").unwrap();
+        let source = InputSource::from_arg(path.to_str().unwrap());
+        assert_eq!(source.path(), Some(path.as_path()));
+        assert_eq!(source.name(), path.to_string_lossy());
+        assert!(source.open().unwrap().is_regular_file());
+        assert_eq!(read_all(&source).unwrap(), "hello\n");
+
+        let missing = InputSource::from_arg(dir.path().join("missing").to_str().unwrap());
+        assert_eq!(missing.open().err().unwrap().kind(), io::ErrorKind::NotFound);
+        // A directory opens, but isn't a regular file
+        assert!(!InputSource::from_arg(dir.path().to_str().unwrap()).open().unwrap().is_regular_file());
+    }
+
+    #[test]
+    fn zip_entry_source() {
+        let source = InputSource::from_arg("logs.zip:app/today.log");
+        assert!(matches!(&source, InputSource::ZipEntry(archive, entry) if archive == Path::new("logs.zip") && entry == "app/today.log"));
+        assert_eq!(source.name(), "logs.zip:app/today.log");
+        assert_eq!(source.path(), None);
+    }
+
+    #[test]
+    fn existing_file_wins_over_zip_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("odd.zip:name");
+        fs::write(&path, "plain").unwrap();
+        let source = InputSource::from_arg(path.to_str().unwrap());
+        assert!(matches!(source, InputSource::File(_)));
+        assert_eq!(read_all(&source).unwrap(), "plain");
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn zip_entry_opens_the_entry() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("logs.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("app/today.log", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"entry text\n").unwrap();
+        zip.finish().unwrap();
+
+        let source = InputSource::from_arg(&format!("{}:app/today.log", archive.display()));
+        let input = source.open().unwrap();
+        assert!(input.metadata.is_none());
+        assert_eq!(read_all(&source).unwrap(), "entry text\n");
+        let missing = InputSource::from_arg(&format!("{}:nope", archive.display()));
+        assert!(missing.open().is_err());
+    }
+
+    #[cfg(not(feature = "zip"))]
+    #[test]
+    fn zip_entry_needs_the_feature() {
+        let source = InputSource::from_arg("logs.zip:app/today.log");
+        assert_eq!(source.open().err().unwrap().kind(), io::ErrorKind::Unsupported);
+    }
+}
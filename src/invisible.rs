@@ -0,0 +1,117 @@
+use crate::Config;
+
+/// Lines reported one by one before the rest are only counted.
+const LISTED: usize = 20;
+
+/// Kinds of characters that don't show, or show as an ordinary space, in
+/// the order they're reported. No-break spaces come first: they're the most
+/// common of them to break a parser or a match.
+const KINDS: [&str; 6] = [
+    "invisible-nbsp",
+    "invisible-bom",
+    "invisible-zero-width",
+    "invisible-bidi",
+    "invisible-space",
+    "invisible-control",
+];
+
+/// Which of `KINDS` `c` is, None for characters that show. Tabs are taken as
+/// meant; they have `-T`.
+fn kind(c: char) -> Option<usize> {
+    match c {
+        '\u{A0}' | '\u{2007}' | '\u{202F}' => Some(0),
+        '\u{FEFF}' => Some(1),
+        '\u{AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' => Some(2),
+        '\u{61C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => Some(3),
+        '\u{1680}' | '\u{2000}'..='\u{2006}' | '\u{2008}'..='\u{200A}' | '\u{205F}' | '\u{3000}' => Some(4),
+        '\t' => None,
+        c if c.is_control() => Some(5),
+        _ => None,
+    }
+}
+
+/// `--count-invisible`: how many characters of each kind every line held.
+#[derive(Default)]
+pub struct Tally {
+    /// Lines with any, and the count of each kind on them
+    lines: Vec<(usize, [usize; KINDS.len()])>,
+}
+
+impl Tally {
+    /// Counts the characters of `line`, number `source_line` of the input. A
+    /// CR ending the line is part of the line break, not of the text.
+    pub fn add(&mut self, source_line: usize, line: &str) {
+        let mut counts = [0; KINDS.len()];
+        for c in line.strip_suffix('\r').unwrap_or(line).chars() {
+            if let Some(kind) = kind(c) {
+                counts[kind] += 1;
+            }
+        }
+        if counts.iter().any(|&count| count > 0) {
+            self.lines.push((source_line, counts));
+        }
+    }
+
+    /// Reports to stderr the lines that had any, the first `LISTED` of them
+    /// one by one, then the total for the file.
+    pub fn report(&self, file_name: &str, config: &Config) {
+        let describe = |counts: &[usize; KINDS.len()]| {
+            let parts: Vec<String> = KINDS.iter().zip(counts).filter(|(_, &count)| count > 0).map(|(&key, &count)| {
+                if key == "invisible-nbsp" {
+                    format!("{}{}{}", config.colors.highlight, msg!(key, count), config.colors.reset)
+                } else {
+                    msg!(key, count)
+                }
+            }).collect();
+            parts.join(", ")
+        };
+        for (source_line, counts) in self.lines.iter().take(LISTED) {
+            eprintln!("meow: {}:{}: {}", file_name, source_line, describe(counts));
+        }
+        if self.lines.len() > LISTED {
+            eprintln!("meow: {}", msg!("invisible-more-lines", file_name, self.lines.len() - LISTED));
+        }
+
+        let mut totals = [0; KINDS.len()];
+        for (_, counts) in &self.lines {
+            for (total, count) in totals.iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
+        if self.lines.is_empty() {
+            eprintln!("meow: {}", msg!("invisible-none", file_name));
+        } else {
+            eprintln!("meow: {}", msg!("invisible-total", file_name, self.lines.len(), describe(&totals)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_are_sorted_into_kinds() {
+        let kinds: Vec<Option<&str>> = ['\u{A0}', '\u{202F}', '\u{FEFF}', '\u{200B}', '\u{AD}', '\u{202E}', '\u{2003}', '\u{1}', '\u{7F}', '\u{85}']
+            .iter().map(|&c| kind(c).map(|kind| KINDS[kind])).collect();
+        assert_eq!(kinds, [
+            Some("invisible-nbsp"), Some("invisible-nbsp"), Some("invisible-bom"), Some("invisible-zero-width"),
+            Some("invisible-zero-width"), Some("invisible-bidi"), Some("invisible-space"),
+            Some("invisible-control"), Some("invisible-control"), Some("invisible-control"),
+        ]);
+        for c in ['a', ' ', '\t', '\u{e9}', '\u{4E2D}'] {
+            assert_eq!(kind(c), None, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn tally_keeps_lines_with_any() {
+        let mut tally = Tally::default();
+        tally.add(1, "a\u{A0}b\u{A0}");
+        tally.add(2, "clean");
+        // The CR of a CRLF line end doesn't count, one inside the line does
+        tally.add(3, "x\u{1}y\r");
+        tally.add(4, "x\ry");
+        assert_eq!(tally.lines, [(1, [2, 0, 0, 0, 0, 0]), (3, [0, 0, 0, 0, 0, 1]), (4, [0, 0, 0, 0, 0, 1])]);
+    }
+}
@@ -0,0 +1,310 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Milliseconds since the Unix epoch.
+pub type Millis = i64;
+
+/// Below this many bytes the search for the start of `--since` stops and the
+/// rest is read line by line.
+const SEEK_GRANULARITY: u64 = 64 * 1024;
+
+/// Parses a `--since`/`--until` value: a timestamp like the ones in log lines,
+/// a date, a time of today, `now`, `today`, `yesterday` or `N UNIT ago`.
+pub fn parse_bound(text: &str) -> Option<Millis> {
+    let text = text.trim();
+    let now = Local::now();
+    let midnight = |date: NaiveDate| local_millis(date.and_time(NaiveTime::MIN));
+
+    match text {
+        "now" => return Some(now.timestamp_millis()),
+        "today" => return midnight(now.date_naive()),
+        "yesterday" => return midnight(now.date_naive().pred_opt()?),
+        _ => {},
+    }
+    if let Some(ago) = text.strip_suffix("ago") {
+        return Some(now.timestamp_millis() - parse_span(ago.trim())?.num_milliseconds());
+    }
+    if let Some(time) = line_time(text, None) {
+        return Some(time);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return midnight(date);
+    }
+    ["%H:%M:%S", "%H:%M"].iter()
+        .find_map(|format| NaiveTime::parse_from_str(text, format).ok())
+        .and_then(|time| local_millis(now.date_naive().and_time(time)))
+}
+
+/// `15 min`, `2h`, `3 days` and the like.
+pub fn parse_span(text: &str) -> Option<Duration> {
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let count: i64 = text[..digits].parse().ok()?;
+    let unit = match text[digits..].trim() {
+        "s" | "sec" | "secs" | "second" | "seconds" => Duration::seconds(1),
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(1),
+        "d" | "day" | "days" => Duration::days(1),
+        "w" | "week" | "weeks" => Duration::weeks(1),
+        _ => return None,
+    };
+    unit.checked_mul(i32::try_from(count).ok()?)
+}
+
+/// The timestamp a line starts with (optionally in brackets): ISO 8601 with or
+/// without a zone, syslog's `Aug  7 14:00:01`, or epoch seconds/milliseconds.
+/// With `format` (strftime), only that is tried. Times without a zone are local.
+pub fn line_time(line: &str, format: Option<&str>) -> Option<Millis> {
+    let text = line.strip_prefix('[').unwrap_or(line);
+    match format {
+        Some(format) => custom_time(text, format),
+        None => iso_time(text).or_else(|| syslog_time(text)).or_else(|| epoch_time(text)),
+    }
+}
+
+fn custom_time(text: &str, format: &str) -> Option<Millis> {
+    if let Ok((time, _)) = DateTime::parse_and_remainder(text, format) {
+        return Some(time.timestamp_millis());
+    }
+    let (time, _) = NaiveDateTime::parse_and_remainder(text, format).ok()?;
+    local_millis(time)
+}
+
+/// Reads `N` ASCII digits at `at`.
+fn number(bytes: &[u8], at: usize, len: usize) -> Option<u32> {
+    let digits = bytes.get(at..at + len)?;
+    digits.iter().try_fold(0, |n, &b| b.is_ascii_digit().then(|| n * 10 + u32::from(b - b'0')))
+}
+
+/// `2024-08-07T14:00:01.123+02:00`, seconds, fraction and zone being optional.
+fn iso_time(text: &str) -> Option<Millis> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 16 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b' ') || bytes[13] != b':' {
+        return None;
+    }
+    let date = NaiveDate::from_ymd_opt(number(bytes, 0, 4)? as i32, number(bytes, 5, 2)?, number(bytes, 8, 2)?)?;
+    let (hour, minute) = (number(bytes, 11, 2)?, number(bytes, 14, 2)?);
+
+    let mut at = 16;
+    let mut second = 0;
+    let mut millis = 0;
+    if bytes.get(at) == Some(&b':') {
+        second = number(bytes, at + 1, 2)?;
+        at += 3;
+        if matches!(bytes.get(at), Some(b'.' | b',')) {
+            let digits = bytes[at + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            // Only the first three digits matter for milliseconds
+            let kept = digits.min(3);
+            millis = number(bytes, at + 1, kept).unwrap_or(0) * 10u32.pow(3 - kept as u32);
+            at += 1 + digits;
+        }
+    }
+    let time = date.and_hms_milli_opt(hour, minute, second, millis)?;
+
+    if bytes.get(at) == Some(&b' ') && matches!(bytes.get(at + 1), Some(b'Z' | b'+' | b'-')) {
+        at += 1;
+    }
+    match bytes.get(at) {
+        Some(b'Z') => Some(Utc.from_utc_datetime(&time).timestamp_millis()),
+        Some(&sign @ (b'+' | b'-')) => {
+            let hours = number(bytes, at + 1, 2)?;
+            let colon = usize::from(bytes.get(at + 3) == Some(&b':'));
+            let minutes = number(bytes, at + 3 + colon, 2).unwrap_or(0);
+            let seconds = (hours * 3600 + minutes * 60) as i32;
+            let offset = FixedOffset::east_opt(if sign == b'-' { -seconds } else { seconds })?;
+            Some(offset.from_local_datetime(&time).single()?.timestamp_millis())
+        },
+        _ => local_millis(time),
+    }
+}
+
+/// `Aug  7 14:00:01`. The year isn't written, so it's this year unless that
+/// would put the line in the future.
+fn syslog_time(text: &str) -> Option<Millis> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let month = MONTHS.iter().position(|month| text.starts_with(month))? as u32 + 1;
+    let rest = text[3..].strip_prefix(' ')?.trim_start_matches(' ');
+    let bytes = rest.as_bytes();
+    let day_len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    let day = number(bytes, 0, day_len.min(2))?;
+    let at = day_len + 1;
+    if bytes.get(day_len) != Some(&b' ') || bytes.get(at + 2) != Some(&b':') || bytes.get(at + 5) != Some(&b':') {
+        return None;
+    }
+    let time = NaiveTime::from_hms_opt(number(bytes, at, 2)?, number(bytes, at + 3, 2)?, number(bytes, at + 6, 2)?)?;
+
+    let now = Local::now();
+    let this_year = local_millis(NaiveDate::from_ymd_opt(now.year(), month, day)?.and_time(time))?;
+    if this_year > (now + Duration::days(1)).timestamp_millis() {
+        local_millis(NaiveDate::from_ymd_opt(now.year() - 1, month, day)?.and_time(time))
+    } else {
+        Some(this_year)
+    }
+}
+
+/// 10 digits of epoch seconds (with an optional fraction) or 13 of milliseconds.
+fn epoch_time(text: &str) -> Option<Millis> {
+    let bytes = text.as_bytes();
+    let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    let value: i64 = text[..digits].parse().ok()?;
+    match digits {
+        13 if !bytes.get(13).is_some_and(u8::is_ascii_alphanumeric) => Some(value),
+        10 => {
+            let mut millis = value * 1000;
+            let mut at = 10;
+            if bytes.get(at) == Some(&b'.') {
+                let fraction = bytes[at + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+                let kept = fraction.min(3);
+                millis += i64::from(number(bytes, at + 1, kept).unwrap_or(0) * 10u32.pow(3 - kept as u32));
+                at += 1 + fraction;
+            }
+            (!bytes.get(at).is_some_and(u8::is_ascii_alphanumeric)).then_some(millis)
+        },
+        _ => None,
+    }
+}
+
+fn local_millis(time: NaiveDateTime) -> Option<Millis> {
+    Some(Local.from_local_datetime(&time).earliest()?.timestamp_millis())
+}
+
+/// Positions a log file shortly before the first line stamped `since` or later,
+/// by bisecting on byte offsets instead of reading everything before it. This
+/// assumes the timestamps only go up. Anything but a regular file is read from
+/// the start.
+pub fn seek_to(file: File, since: Millis, format: Option<&str>) -> io::Result<BufReader<File>> {
+    let metadata = file.metadata()?;
+    let mut reader = BufReader::new(file);
+    if !metadata.is_file() {
+        return Ok(reader);
+    }
+
+    // The first timestamp after `low` is before `since`, the first after `high` isn't
+    let (mut low, mut high) = (0, metadata.len());
+    while high - low > SEEK_GRANULARITY {
+        let middle = low + (high - low) / 2;
+        match first_time_after(&mut reader, middle, high, format)? {
+            Some(time) if time < since => low = middle,
+            _ => high = middle,
+        }
+    }
+
+    reader.seek(SeekFrom::Start(low))?;
+    if low > 0 {
+        reader.read_until(b'\n', &mut Vec::new())?;
+    }
+    Ok(reader)
+}
+
+/// The timestamp of the first whole line starting between `offset` and `limit`.
+fn first_time_after(reader: &mut BufReader<File>, offset: u64, limit: u64, format: Option<&str>) -> io::Result<Option<Millis>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut line = Vec::new();
+    let mut position = offset + reader.read_until(b'\n', &mut line)? as u64;
+
+    while position < limit {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        position += read as u64;
+        if let Some(time) = line_time(&String::from_utf8_lossy(&line), format) {
+            return Ok(Some(time));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn utc(text: &str) -> Millis {
+        DateTime::parse_from_rfc3339(text).unwrap().timestamp_millis()
+    }
+
+    #[test]
+    fn iso_times() {
+        assert_eq!(line_time("2024-08-07T14:00:01Z hello", None), Some(utc("2024-08-07T14:00:01Z")));
+        assert_eq!(line_time("2024-08-07 14:00:01.123456+02:00 x", None), Some(utc("2024-08-07T12:00:01.123Z")));
+        assert_eq!(line_time("[2024-08-07T14:00 -0130] x", None), Some(utc("2024-08-07T15:30:00Z")));
+        let local = Local.with_ymd_and_hms(2024, 8, 7, 14, 0, 1).earliest().unwrap();
+        assert_eq!(line_time("2024-08-07T14:00:01 no zone", None), Some(local.timestamp_millis()));
+        assert_eq!(line_time("2024-13-07T14:00:01Z", None), None);
+        assert_eq!(line_time("no time here", None), None);
+    }
+
+    #[test]
+    fn epoch_times() {
+        assert_eq!(line_time("1700000000 x", None), Some(1_700_000_000_000));
+        assert_eq!(line_time("1700000000.25 x", None), Some(1_700_000_000_250));
+        assert_eq!(line_time("1700000000123 x", None), Some(1_700_000_000_123));
+        // Part of a longer word, or too short
+        assert_eq!(line_time("1700000000abc", None), None);
+        assert_eq!(line_time("12345 x", None), None);
+    }
+
+    #[test]
+    fn syslog_times_are_never_far_in_the_future() {
+        let now = Local::now();
+        let time = line_time("Jan  1 00:00:00 host x", None).unwrap();
+        assert!(time <= (now + Duration::days(1)).timestamp_millis());
+        assert!(time > (now - Duration::days(367)).timestamp_millis());
+        assert!(line_time("Aug 7 14:00:01 host", None).is_some());
+        assert_eq!(line_time("Aug 7 14:00 host", None), None);
+    }
+
+    #[test]
+    fn custom_formats() {
+        assert_eq!(line_time("07/08/2024 14:00:01 +0000 x", Some("%d/%m/%Y %H:%M:%S %z")), Some(utc("2024-08-07T14:00:01Z")));
+        // Only the format given is tried
+        assert_eq!(line_time("2024-08-07T14:00:01Z", Some("%d/%m/%Y %H:%M:%S")), None);
+    }
+
+    #[test]
+    fn spans_and_bounds() {
+        assert_eq!(parse_span("15 min"), Some(Duration::minutes(15)));
+        assert_eq!(parse_span("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse_span("3 fortnights"), None);
+        assert_eq!(parse_span("min"), None);
+
+        let now = Local::now().timestamp_millis();
+        let ago = parse_bound("15 min ago").unwrap();
+        assert!((now - 15 * 60 * 1000 - ago).abs() < 5000);
+        assert!(parse_bound("yesterday").unwrap() < parse_bound("today").unwrap());
+        assert_eq!(parse_bound("2024-08-07T14:00:01Z"), Some(utc("2024-08-07T14:00:01Z")));
+        let midnight = Local.with_ymd_and_hms(2024, 8, 7, 0, 0, 0).earliest().unwrap();
+        assert_eq!(parse_bound("2024-08-07"), Some(midnight.timestamp_millis()));
+        assert!(parse_bound("12:30").is_some());
+        assert_eq!(parse_bound("whenever"), None);
+    }
+
+    #[test]
+    fn seek_lands_just_before_since() {
+        let mut file = tempfile::tempfile().unwrap();
+        let mut offsets = Vec::new();
+        let mut text = String::new();
+        for i in 0..100_000 {
+            offsets.push(text.len() as u64);
+            text.push_str(&format!("{} line {}\n", 1_700_000_000 + i, i));
+        }
+        file.write_all(text.as_bytes()).unwrap();
+
+        let target = offsets[70_000];
+        let mut reader = seek_to(file.try_clone().unwrap(), 1_700_070_000_000, None).unwrap();
+        let position = reader.stream_position().unwrap();
+        assert!(position <= target && target - position <= SEEK_GRANULARITY, "{} vs {}", position, target);
+        // At the start of a whole line
+        assert!(offsets.binary_search(&position).is_ok());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line_time(&line, None).unwrap() <= 1_700_070_000_000);
+
+        // Before the first line, nothing is skipped
+        let mut reader = seek_to(file, 1_600_000_000_000, None).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 0);
+    }
+}
@@ -1,11 +1,73 @@
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
 use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read, Write};
-use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fs::{self, File};
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use std::process::{Command, Stdio};
 use std::thread;
 use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use regex::Regex;
+
+use annotations::Annotations;
+use color::Depth;
+use input::{InputReader, InputSource};
+use settings::{Preprocessor, Profile, View, ViewError};
+
+#[macro_use]
+mod i18n;
+mod art;
+#[cfg(feature = "images")]
+mod preview;
+#[cfg(feature = "qr")]
+mod qr;
+#[cfg(feature = "zip")]
+mod archive;
+mod settings;
+mod serve;
+mod hash;
+mod marks;
+mod input;
+mod resume;
+mod exec;
+mod structured;
+mod xml;
+mod logtime;
+mod columns;
+mod redact;
+mod color;
+mod more;
+mod annotations;
+mod merge;
+mod pick;
+mod init;
+mod rules;
+mod split;
+mod brackets;
+mod record;
+mod numbers;
+mod summary;
+mod cast;
+mod fuzzy;
+mod compare;
+mod terminal;
+mod sections;
+mod units;
+mod invisible;
+mod freshness;
+mod theme;
+mod sgr;
 
 #[derive(Clone)]
 struct ColorConfig {
@@ -15,11 +77,17 @@ struct ColorConfig {
     error: String,
     success: String,
     filename: String,
+    /// Faint text for markers that shouldn't compete with the content
+    dim: String,
     reset: String,
+    blame_age: Vec<String>,
+    /// Highlight colors cycled per input file with `--file-colors`
+    file_palette: Vec<String>,
 }
 
 impl ColorConfig {
-    fn new(use_colors: bool) -> Self {
+    /// `depth` only matters for colors beyond the basic 16.
+    fn new(use_colors: bool, depth: Depth) -> Self {
         if use_colors {
             ColorConfig {
                 normal: "\x1B[0m".to_string(),
@@ -28,7 +96,21 @@ impl ColorConfig {
                 error: "\x1B[31m".to_string(),    // Red
                 success: "\x1B[32m".to_string(),  // Green
                 filename: "\x1B[35m".to_string(), // Magenta
+                dim: "\x1B[2m".to_string(),
                 reset: "\x1B[0m".to_string(),
+                // Newest to oldest, brighter backgrounds for fresher lines
+                blame_age: [(30, 208), (30, 168), (37, 108), (37, 68), (37, 38)]
+                    .iter()
+                    .map(|&(text, gray)| format!("\x1B[{}m{}", text, color::bg([gray; 3], depth)))
+                    .collect(),
+                file_palette: vec![
+                    "\x1B[36m".to_string(), // Cyan
+                    "\x1B[33m".to_string(), // Yellow
+                    "\x1B[32m".to_string(), // Green
+                    "\x1B[35m".to_string(), // Magenta
+                    "\x1B[34m".to_string(), // Blue
+                    "\x1B[31m".to_string(), // Red
+                ],
             }
         } else {
             ColorConfig {
@@ -38,7 +120,10 @@ impl ColorConfig {
                 error: "".to_string(),
                 success: "".to_string(),
                 filename: "".to_string(),
+                dim: "".to_string(),
                 reset: "".to_string(),
+                blame_age: vec!["".to_string(); 5],
+                file_palette: vec!["".to_string()],
             }
         }
     }
@@ -54,21 +139,559 @@ struct Config {
     show_all_nonprinting: bool,
     show_line_length: bool,
     rainbow_mode: bool,
+    /// `--color-depth`: what escape sequences colors beyond the basic 16 use
+    color_depth: Depth,
     use_colors: bool,
     interactive_mode: bool,
+    /// `--record`: save the interactive session to this file
+    record: Option<String>,
+    /// `--replay`: play back a recorded session instead of showing files
+    replay: Option<String>,
+    /// `--replay-speed`, as a factor
+    replay_speed: f64,
+    replay_instant: bool,
+    /// `--pick`: choose files to show from a list of the current directory
+    pick: bool,
     show_meta: bool,
+    /// `--freshness`: seconds until a file is no longer fresh, and until it's stale
+    freshness: Option<(u64, u64)>,
+    /// `--warn-older-than`/`--warn-newer-than`, in seconds
+    warn_older_than: Option<u64>,
+    warn_newer_than: Option<u64>,
     grep_pattern: Option<String>,
+    /// `--grep-file`: the patterns file, and a regex matching any of them once loaded
+    grep_file: Option<String>,
+    grep_any: Option<Regex>,
+    /// `--grep-file-comments`: skip `#` lines in the patterns file
+    grep_file_comments: bool,
+    /// `--grep-fuzzy`, given its distance once all options are read
+    grep_fuzzy: Option<fuzzy::Fuzzy>,
+    /// `--fuzzy-distance`; None picks 2, or less for short patterns
+    fuzzy_distance: Option<usize>,
+    /// `--show-distance`: the edits the `--grep-fuzzy` match took, before each line
+    show_distance: bool,
     page_mode: bool,
+    /// `--more`: pause after each screenful when on a terminal
+    more: bool,
     animate: bool,
+    /// `--throttle`: lines per second
+    throttle: Option<f64>,
     highlight_pattern: Option<String>,
+    highlight_regex: Option<Regex>,
+    /// `--highlight-field`: the 1-based field to color in every line
+    highlight_field: Option<usize>,
+    /// `--match-brackets`: color bracket pairs by depth; `=check` only reports the unmatched ones
+    match_brackets: bool,
+    brackets_check: bool,
+    /// `--number-heat`: color numbers by size against `heat_thresholds` (warning,
+    /// error), or against each file's own numbers with `heat_auto`
+    number_heat: bool,
+    heat_units: bool,
+    heat_thresholds: (f64, f64),
+    heat_auto: bool,
+    /// `--list-matches`: print the distinct matches of this instead of the content
+    list_matches: Option<Regex>,
+    list_matches_count: bool,
+    blame: bool,
+    changes: bool,
+    changes_ref: Option<String>,
+    quiet: bool,
+    no_special: bool,
+    /// `--safe` (true) or `--unsafe` (false); None escapes input once it has
+    /// anything but color codes in it
+    safe: Option<bool>,
+    /// `--preserve-ansi`: bring the input's colors back after the gutter on each line
+    preserve_ansi: bool,
+    image_protocol: String,
+    image_width: Option<usize>,
+    sample_every: Option<usize>,
+    sample_probability: Option<f64>,
+    /// `--sample-spread`: show this many lines spread over the whole input
+    sample_spread: Option<usize>,
+    sample_seed: Option<u64>,
+    filter: Option<String>,
+    escape_mode: Option<String>,
+    /// `--shell-quote`: `--escape=shell` with nothing else on the line; `=array`
+    /// puts all the lines on one, as separate words
+    shell_quote: bool,
+    shell_quote_array: bool,
+    /// `--null`: end each line with a NUL byte instead of a newline
+    null_records: bool,
+    /// How control characters are shown: caret, hex or unicode
+    nonprint_style: String,
+    /// `--number-hex`: line numbers in hexadecimal, after this prefix
+    number_hex: Option<&'static str>,
+    /// `--ruler`: column numbers above the content, again every `ruler_every` lines
+    ruler: bool,
+    ruler_every: Option<usize>,
+    /// `--max-col`: text past this display column is marked
+    max_col: Option<usize>,
+    /// `--truncate-middle`: lines wider than this lose their middle to `…`
+    truncate_middle: Option<usize>,
+    tab_width: usize,
+    /// `--fold`: wrap lines at spaces to this width, indenting continuations by `fold_indent`
+    fold: Option<usize>,
+    fold_indent: usize,
+    strip_comments: bool,
+    find_dupes: bool,
+    /// `--count-invisible`: report hidden characters after each input
+    count_invisible: bool,
+    mark_dupes: bool,
+    dupes_ignore_case: bool,
+    dupes_ignore_whitespace: bool,
+    /// hash, slash or semicolon; None picks one from the file extension
+    comment_style: Option<String>,
+    edit: bool,
+    edit_cmd: Option<String>,
+    edit_all: bool,
+    tail_bytes: Option<u64>,
+    generate_man: bool,
+    /// `--show-colors`: preview the color roles instead of showing files
+    show_colors: bool,
+    /// `--set-title`: show the input and progress in the terminal's title
+    set_title: bool,
+    /// `--init`: print the shell integration for this shell
+    init: Option<String>,
+    banner: Option<String>,
+    title: Option<String>,
+    force_art: bool,
+    /// `--prepend`/`--append`: text around each input's content
+    prepend: Option<String>,
+    append: Option<String>,
+    detect_indent: bool,
+    continuous_numbering: bool,
+    qr: bool,
+    qr_invert: bool,
+    max_line_bytes: Option<usize>,
+    preprocessors: Vec<Preprocessor>,
+    no_preprocess: bool,
+    list_preprocessors: bool,
+    /// `[profiles]` from the config file, off with `--no-profiles`
+    profiles: Vec<Profile>,
+    no_profiles: bool,
+    /// The pattern of the profile applied to the current file
+    profile: Option<String>,
+    /// Options given on the command line, which profiles leave alone
+    explicit: HashSet<&'static str>,
+    /// `[views]` from the config file, already expanded in the arguments
+    views: Vec<View>,
+    list_views: bool,
+    date_format: Option<String>,
+    serve: bool,
+    serve_port: u16,
+    /// Address `--serve` listens on, only this machine unless `--serve-bind` says otherwise
+    serve_bind: IpAddr,
+    serve_raw: bool,
+    serve_once: bool,
+    with_filename: bool,
+    file_colors: bool,
+    verify: bool,
+    /// `--stat`: print file metadata instead of the content
+    stat: bool,
+    /// `--summary`: a table of what each input held after the content;
+    /// `--summary-only` leaves the content out
+    summary: bool,
+    summary_only: bool,
+    /// `--side-by-side`: show two files in columns
+    side_by_side: bool,
+    /// `--compare`: report whether two inputs are byte for byte the same
+    compare: bool,
+    /// `--fold-sections`: lines starting a section, which is shown folded
+    fold_sections: Option<Regex>,
+    side_by_side_wrap: bool,
+    /// `--merge`: interleave the lines of all files, see merge::Merge
+    merge: bool,
+    merge_delimiter: Option<String>,
+    merge_shortest: bool,
+    checksum: Option<hash::Algorithm>,
+    /// `--index`: print line offsets instead of the content, as `index_format`
+    index: bool,
+    index_format: String,
+    skip_blank_numbers: bool,
+    /// `--expand-env`: variables to substitute, None when off
+    env_vars: Option<HashMap<String, String>>,
+    env_strict: bool,
+    env_file: Option<String>,
+    /// `--annotations`: the notes file, and its notes once loaded
+    annotations_path: Option<String>,
+    annotations: Option<Annotations>,
+    annotations_block: bool,
+    annotations_always: bool,
+    /// `--rules`: the rules file or name, and its rules once loaded
+    rules_path: Option<String>,
+    /// `--theme`: a file of colors to use instead of the built-in ones
+    theme_path: Option<String>,
+    /// `--export-theme`: write the colors in effect to this file and exit
+    export_theme: Option<String>,
+    rules: Vec<rules::Rule>,
+    recursive: bool,
+    /// `--exclude`/`--include` globs for what `-R` picks up
+    exclude: Vec<String>,
+    include: Vec<String>,
+    flatten: bool,
+    tree: bool,
+    /// `--tree` header lines for each file, computed while recursing
+    tree_headers: HashMap<String, Vec<String>>,
+    list_dirs: bool,
+    /// `--list`: the entries of zip archives instead of their bytes
+    list_archives: bool,
+    /// `--zip-entry`: read this entry of each input, which are zip archives
+    zip_entry: Option<String>,
+    dir_sort: String,
+    dirs_first: bool,
+    /// `--exec`: show this command's output instead of files
+    exec: Option<String>,
+    exec_shell: bool,
+    exec_stop_on_error: bool,
+    /// `--watch-interval`: re-run the `--exec` command this often
+    watch_interval: Option<Duration>,
+    diff_changes: bool,
+    /// `--bell`: alert when a run has new lines matching `--grep`; `=notify`
+    /// sends a desktop notification instead of ringing the terminal bell
+    bell: bool,
+    bell_notify: bool,
+    /// `--each-line-command`: run this for every selected line, at most
+    /// `each_line_jobs` at once, or one by one showing its output with `each_line_output`
+    each_line_command: Option<Vec<String>>,
+    each_line_output: bool,
+    each_line_jobs: usize,
+    /// Lines of the current `--exec` run that weren't in the previous one
+    changed_lines: HashSet<usize>,
+    /// `--redact` and `--redact-pattern`: mask secrets before anything else sees them
+    redactor: Option<redact::Redactor>,
+    /// `--json-array`: one JSON array of line objects instead of text
+    json_array: bool,
+    /// `--group-by-file`: a JSON object with the lines of each input instead of the array
+    group_by_file: bool,
+    /// `--yaml`, `--toml` or `--xml`; None picks one from the extension unless `--plain`
+    structured: Option<String>,
+    /// `--plain`: copy the inputs as they are, whatever else is asked for
+    plain: bool,
+    /// `--raw-passthrough=timed`: play asciinema recordings at their own pace
+    raw_timed: bool,
+    /// `--fold-depth`: collapse YAML nested deeper than this
+    fold_depth: Option<usize>,
+    xml_indent: usize,
+    /// `--xml-collapse`: elements shown as a one-line summary
+    xml_collapse: Vec<String>,
+    /// `--resume`: continue each file where the last run stopped
+    resume: bool,
+    /// `--resume=clear`: forget the saved positions instead
+    resume_clear: bool,
+    /// `--tee` and `--tee-plain` files, with whether colors are stripped
+    tee_files: Vec<(String, bool)>,
+    /// `--output`: a file (or with `--split-output`, a template for numbered
+    /// files) that gets the output instead of stdout
+    output: Option<String>,
+    /// `--split-output`/`--split-output-bytes`: when to move on to the next file
+    split_output: split::Limit,
+    /// `--output` checked as a template, once `--split-output` asks for one
+    output_template: Option<split::Template>,
+    in_place: bool,
+    backup_suffix: Option<String>,
+    preserve_times: bool,
+    match_index: Option<usize>,
+    /// `--max-count`: stop reading a file after this many matches were shown
+    max_count: Option<usize>,
+    /// `--max-count-total`: the `--max-count` is for all files together
+    max_count_total: bool,
+    /// `--si`: sizes in steps of 1000 (KB) rather than 1024 (KiB)
+    si: bool,
+    /// `--max-terminal-bytes`: ask before writing bigger inputs to a terminal
+    max_terminal_bytes: Option<u64>,
+    /// `--force`: don't ask about big inputs
+    force: bool,
+    /// `--wait-for-file`: wait for missing files to be created instead of failing
+    wait_for_file: bool,
+    /// `--wait-timeout`: give up waiting after this long
+    wait_timeout: Option<Duration>,
+    /// `--bytes-exact`: sizes and counts as plain numbers, for scripts
+    bytes_exact: bool,
+    /// `--thousands-separator`: None takes the language's
+    thousands_separator: Option<String>,
+    min_length: Option<usize>,
+    /// `--fields` ranges, 1-based and inclusive
+    fields: Option<Vec<(usize, usize)>>,
+    /// `--preview`: show only the first and last N lines
+    preview: Option<usize>,
+    fields_invert: bool,
+    delimiter: Option<String>,
+    output_delimiter: Option<String>,
+    csv: bool,
+    max_length: Option<usize>,
+    /// `--since`/`--until`: only show log entries in this window, in epoch milliseconds
+    since: Option<logtime::Millis>,
+    until: Option<logtime::Millis>,
+    /// `--time-format`: strftime format of the line timestamps
+    time_format: Option<String>,
+    /// `--skip-untimed`: drop lines without a timestamp instead of keeping them with the entry above
+    skip_untimed: bool,
+    before_context: usize,
+    after_context: usize,
+    /// `--marks`: lines to draw a numbered rule above, sorted
+    marks: Vec<usize>,
+    /// `--mark-pattern`: lines that are marks too
+    mark_pattern: Option<Regex>,
+    /// `--marks-index`: list the marks before each file
+    marks_index: bool,
+    /// `--record-separator`: split the input on these bytes instead of newlines
+    record_separator: Option<Vec<u8>>,
+    /// Printed between non-adjacent groups of grep context; None with `--no-group-separator`
+    group_separator: Option<String>,
     files: Vec<String>,
     colors: ColorConfig,
 }
 
+/// Command line option metadata shared by `parse_args`, `--help` and `--generate-man`.
+struct OptionSpec {
+    short: Option<char>,
+    long: &'static str,
+    /// Placeholder for the option's value, if it takes one
+    value: Option<&'static str>,
+    /// The value may be omitted (`--changes` vs `--changes=REF`)
+    optional: bool,
+    hidden: bool,
+    help: &'static str,
+}
+
+const fn flag(short: Option<char>, long: &'static str, help: &'static str) -> OptionSpec {
+    OptionSpec { short, long, value: None, optional: false, hidden: false, help }
+}
+
+const fn with_value(short: Option<char>, long: &'static str, value: &'static str, help: &'static str) -> OptionSpec {
+    OptionSpec { short, long, value: Some(value), optional: false, hidden: false, help }
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    flag(Some('n'), "number", "number all output lines"),
+    flag(Some('b'), "number-nonblank", "number nonempty output lines"),
+    flag(None, "skip-blank-numbers", "with -n, leave the gutter empty on blank lines without counting them"),
+    flag(None, "continuous-numbering", "keep numbering across files instead of restarting"),
+    OptionSpec { short: None, long: "number-hex", value: Some("0x"), optional: true, hidden: false, help: "show line numbers in hexadecimal, with =0x prefixed" },
+    flag(Some('E'), "show-ends", "display $ at end of each line"),
+    flag(Some('T'), "show-tabs", "display TAB characters as ^I"),
+    flag(Some('s'), "squeeze-blank", "suppress repeated empty output lines"),
+    flag(Some('A'), "show-nonprinting", "show all non-printing characters"),
+    with_value(None, "nonprint-style", "STYLE", "show control characters as caret (^[, default), hex (\\x1b) or unicode (\u{241B})"),
+    flag(Some('l'), "show-length", "show line and character count, and the width in columns where it differs"),
+    flag(None, "ruler", "print a column ruler above each file"),
+    with_value(None, "ruler-every", "N", "repeat the column ruler every N lines"),
+    with_value(None, "max-col", "N", "mark the text past display column N"),
+    with_value(None, "truncate-middle", "N", "shorten lines wider than N columns by replacing their middle with …"),
+    with_value(None, "tab-width", "N", "count tab stops every N columns for --max-col, --truncate-middle, --fold and --side-by-side (default 8)"),
+    with_value(None, "fold", "N", "wrap lines at spaces so they fit in N columns"),
+    with_value(None, "fold-indent", "N", "with --fold, indent continuation lines by N spaces"),
+    flag(Some('r'), "rainbow", "enable rainbow text mode"),
+    flag(Some('C'), "no-color", "disable colors"),
+    with_value(None, "color-depth", "DEPTH", "use 16, 256 or truecolor colors (default: from $COLORTERM and $TERM)"),
+    flag(Some('i'), "interactive", "enter interactive mode after processing"),
+    with_value(None, "record", "FILE", "with --interactive, record the session to FILE for --replay"),
+    with_value(None, "replay", "FILE", "play back a session recorded with --record, without running anything"),
+    with_value(None, "replay-speed", "N", "with --replay or --raw-passthrough=timed, play N times as fast (e.g. 2x, 0.5x)"),
+    flag(None, "replay-instant", "with --replay, show the whole session at once"),
+    flag(None, "pick", "choose files in the current directory from a filtered list (type to filter, Enter to show, Esc to quit)"),
+    flag(Some('m'), "meta", "show file metadata"),
+    with_value(None, "date-format", "FMT", "show --meta times as strftime FMT instead of relative age"),
+    OptionSpec { short: None, long: "freshness", value: Some("FRESH,STALE"), optional: true, hidden: false, help: "color modification times by age: green under FRESH, yellow under STALE, red after (default 1h,1d)" },
+    with_value(None, "warn-older-than", "SPAN", "warn when a file was last modified longer than SPAN ago, e.g. 2h"),
+    with_value(None, "warn-newer-than", "SPAN", "warn when a file was modified less than SPAN ago, or while it was read"),
+    flag(Some('p'), "page", "use pager (like less) for output"),
+    flag(None, "more", "pause after each screenful, like more (Space, Enter, q)"),
+    flag(None, "set-title", "show the file being read, how far in and the --grep matches in the terminal title"),
+    flag(Some('a'), "animate", "animate text display"),
+    with_value(None, "throttle", "RATE", "print at most RATE lines per second (0 for no limit)"),
+    flag(Some('q'), "quiet", "suppress informational notices"),
+    with_value(Some('g'), "grep", "PATTERN", "only show lines matching pattern"),
+    with_value(None, "context", "N", "with --grep, also show N lines around each match"),
+    with_value(None, "before-context", "N", "with --grep, also show N lines before each match"),
+    with_value(None, "after-context", "N", "with --grep, also show N lines after each match"),
+    with_value(None, "record-separator", "SEP", "split the input into records at SEP instead of newlines (\\r, \\t, \\0 and \\xHH work)"),
+    with_value(None, "group-separator", "SEP", "print SEP between groups of context lines (default --)"),
+    flag(None, "no-group-separator", "print nothing between groups of context lines"),
+    with_value(None, "match-index", "N", "with --grep, show only the Nth match (and its context)"),
+    with_value(None, "marks", "LIST", "draw a numbered rule above the lines in LIST (like 120,340,702); with --more, m and M jump between them"),
+    with_value(None, "mark-pattern", "RE", "mark the lines matching RE like --marks"),
+    flag(None, "marks-index", "list the marks and the start of their lines before each file"),
+    with_value(None, "max-count", "N", "with --grep, stop reading a file after N matching lines, like grep -m"),
+    flag(None, "max-count-total", "count --max-count over all files rather than for each one"),
+    with_value(None, "grep-file", "FILE", "only show lines containing any of the patterns in FILE, one per line, like grep -f"),
+    flag(None, "grep-file-comments", "skip lines starting with # in the --grep-file patterns"),
+    with_value(None, "grep-fuzzy", "PATTERN", "only show lines with a part within --fuzzy-distance edits of PATTERN"),
+    with_value(None, "fuzzy-distance", "N", "insertions, deletions and substitutions --grep-fuzzy allows (default 2)"),
+    flag(None, "show-distance", "with --grep-fuzzy, show how many edits each line's match took"),
+    with_value(None, "min-length", "N", "only show lines at least N characters long"),
+    with_value(None, "max-length", "N", "only show lines at most N characters long"),
+    with_value(None, "since", "TIME", "only show log lines stamped TIME or later (e.g. '2024-08-07 14:00', '15 min ago')"),
+    with_value(None, "until", "TIME", "only show log lines stamped TIME or earlier"),
+    with_value(None, "time-format", "FMT", "strftime format of the timestamps at the start of log lines"),
+    flag(None, "skip-untimed", "with --since/--until, drop lines without a timestamp instead of keeping them with the line above"),
+    with_value(None, "preview", "N", "show the first and last N lines with the skipped count between"),
+    with_value(None, "fields", "LIST", "only show these fields, e.g. 1,3-5 or 2-"),
+    flag(None, "fields-invert", "show all fields except those in --fields"),
+    with_value(None, "delimiter", "DELIM", "field delimiter for --fields (default: runs of whitespace) and --highlight-field"),
+    with_value(None, "output-delimiter", "DELIM", "join selected fields with DELIM"),
+    flag(None, "csv", "split --fields as CSV, keeping quoted delimiters inside fields"),
+    with_value(Some('H'), "highlight", "PATTERN", "highlight pattern in output"),
+    flag(None, "with-filename", "prefix each output line with its file name"),
+    flag(None, "file-colors", "give each file its own highlight color"),
+    with_value(None, "highlight-regex", "RE", "highlight all matches of a regular expression"),
+    with_value(None, "highlight-field", "N", "highlight field N of each line, split on --delimiter (default: tab)"),
+    OptionSpec { short: None, long: "match-brackets", value: Some("check"), optional: true, hidden: false, help: "color (), [] and {} pairs by nesting depth and unmatched ones as errors; =check only reports the unmatched ones" },
+    flag(None, "number-heat", "color numbers from yellow to red as they pass the --heat-thresholds"),
+    flag(None, "heat-units", "with --number-heat, also take numbers with a unit like 5ms or 12KB"),
+    with_value(None, "heat-thresholds", "WARN,ERROR", "with --number-heat, where the colors start and where they turn red (default 100,1000)"),
+    flag(None, "heat-auto", "with --number-heat, set the thresholds from the 90th and 99th percentile of each file's numbers"),
+    with_value(None, "list-matches", "RE", "print the distinct matches of RE in all inputs, sorted, instead of the content"),
+    flag(None, "list-matches-count", "with --list-matches, put how often each match occurs before it"),
+    with_value(None, "image-protocol", "P", "image preview protocol: auto, blocks, kitty, iterm"),
+    with_value(None, "image-width", "N", "image preview width in columns"),
+    with_value(None, "sample", "N", "print only every Nth line"),
+    with_value(None, "sample-random", "P", "print each line with probability P (0..1)"),
+    with_value(None, "sample-spread", "N", "print N lines spread evenly across the input"),
+    with_value(None, "sample-seed", "SEED", "make --sample-spread on streams reproducible"),
+    with_value(None, "exec", "CMD", "show the output of CMD instead of files"),
+    flag(None, "exec-shell", "run the --exec command through sh -c"),
+    with_value(None, "watch-interval", "TIME", "re-run the --exec command every TIME (e.g. 5s, 500ms, 2m)"),
+    flag(None, "diff-changes", "with --watch-interval, highlight lines that changed since the last run"),
+    flag(None, "exec-stop-on-error", "stop watching when the command fails"),
+    OptionSpec { short: None, long: "bell", value: Some("notify"), optional: true, hidden: false, help: "with --watch-interval, ring the terminal bell when new lines match --grep; =notify sends a desktop notification" },
+    with_value(None, "each-line-command", "CMD", "run CMD for each line shown by --grep, with the line in place of {} or on its stdin (--exec is the one that shows a command's output)"),
+    flag(None, "each-line-output", "show the output of --each-line-command below each line"),
+    with_value(None, "each-line-jobs", "N", "run at most N --each-line-command commands at once (default 4)"),
+    with_value(None, "filter", "CMD", "pipe each input through CMD ({} is the file path)"),
+    OptionSpec { short: None, long: "expand-env", value: Some("strict"), optional: true, hidden: false, help: "substitute $VAR and ${VAR} from the environment; strict marks undefined ones and fails" },
+    with_value(None, "env-file", "FILE", "with --expand-env, also load variables from a dotenv FILE"),
+    with_value(None, "annotations", "FILE", "show notes from FILE (JSON or 'LINE: text') next to their lines"),
+    flag(None, "annotations-block", "show --annotations notes on their own lines below the line"),
+    flag(None, "annotations-always", "show annotated lines even when --grep doesn't select them"),
+    with_value(None, "rules", "FILE", "color matches with the regex rules in FILE, or in rules/FILE.toml next to the config file"),
+    with_value(None, "theme", "FILE", "take the colors from the TOML theme FILE"),
+    with_value(None, "export-theme", "FILE", "write the colors in effect (with --theme and --color-depth) to FILE as a theme and exit"),
+    flag(None, "strip-comments", "remove comments and comment-only lines"),
+    with_value(None, "comment-style", "STYLE", "comment syntax for --strip-comments: hash, slash, semicolon or auto"),
+    OptionSpec { short: None, long: "redact", value: Some("pii"), optional: true, hidden: false, help: "mask API keys, tokens, passwords and private keys; =pii also masks email addresses" },
+    with_value(None, "redact-style", "STYLE", "replace secrets with a block (default) or a hash, so equal secrets match"),
+    with_value(None, "redact-pattern", "RE", "also mask matches of RE, or its first group (repeatable)"),
+    flag(None, "json-array", "print the output as a JSON array of {file, line, text} objects"),
+    flag(None, "group-by-file", "like --json-array, but as one object of {\"lines\": [...]} per input file"),
+    flag(None, "yaml", "colorize and validate the input as YAML (automatic for .yaml/.yml on a terminal)"),
+    flag(None, "toml", "colorize and validate the input as TOML (automatic for .toml on a terminal)"),
+    flag(None, "plain", "copy the input unchanged, ignoring every display option (also stops YAML and TOML colors)"),
+    OptionSpec { short: None, long: "raw-passthrough", value: Some("timed"), optional: true, hidden: false, help: "like --plain, for ANSI art and terminal recordings; =timed plays asciinema recordings at their original pace" },
+    with_value(None, "fold-depth", "N", "with YAML, collapse levels nested deeper than N"),
+    with_value(None, "fold-sections", "RE", "fold the lines after each line matching RE; on a terminal, Enter opens a section, a opens all, / searches"),
+    flag(None, "xml", "re-indent and colorize XML or HTML"),
+    with_value(None, "xml-indent", "N", "indent --xml output by N spaces per level (default 2)"),
+    with_value(None, "xml-collapse", "TAG", "with --xml, show TAG elements as a one-line summary (repeatable)"),
+    with_value(None, "escape", "MODE", "escape each line as a shell, c or json literal"),
+    OptionSpec { short: None, long: "shell-quote", value: Some("array"), optional: true, hidden: false, help: "quote each line for POSIX shells, without colors or gutter; =array quotes all lines as words of one line" },
+    flag(None, "null", "end each output line with a NUL byte instead of a newline, for xargs -0"),
+    flag(None, "edit", "open the first match (or line 1) in $VISUAL/$EDITOR"),
+    flag(None, "edit-all", "open every matching file in the editor"),
+    with_value(None, "edit-cmd", "TEMPLATE", "editor template: vim, emacs, code or e.g. 'subl {file}:{line}'"),
+    with_value(None, "max-line-bytes", "N", "split lines longer than N bytes instead of buffering them whole"),
+    with_value(None, "tail-bytes", "N", "show the lines within the last N bytes (numbers start at the tail)"),
+    OptionSpec { short: None, long: "serve", value: Some("PORT"), optional: true, hidden: false, help: "serve the output over HTTP on PORT (default 8000)" },
+    with_value(None, "serve-bind", "ADDR", "with --serve, listen on ADDR (default 127.0.0.1; 0.0.0.0 for the whole network)"),
+    flag(None, "serve-raw", "serve the files unprocessed"),
+    flag(None, "serve-once", "stop serving after the first request"),
+    with_value(None, "checksum", "ALGO", "print md5, sha1, sha256, sha512 or blake3 hashes instead of the content (in the header with --meta)"),
+    flag(None, "index", "print the line number, byte offset and byte length of each line (or --grep match) instead of the content"),
+    with_value(None, "index-format", "FORMAT", "write --index records as tsv (default) or json lines"),
+    flag(None, "verify", "check the files listed in checksum manifests (sha256sum or BSD format)"),
+    flag(None, "stat", "print size, modification time, type and permissions instead of the content"),
+    flag(None, "si", "show sizes in powers of 1000 (KB, MB) rather than 1024 (KiB, MiB)"),
+    with_value(None, "max-terminal-bytes", "SIZE", "ask before showing a file bigger than SIZE on a terminal (default 50 MB; 0 never asks)"),
+    flag(None, "force", "show big files on a terminal without asking"),
+    flag(None, "wait-for-file", "wait for a file that doesn't exist yet to be created, then show it"),
+    with_value(None, "wait-timeout", "MS", "with --wait-for-file, give up after MS milliseconds"),
+    flag(None, "bytes-exact", "show sizes as exact byte counts and counts without separators, for scripts"),
+    with_value(None, "thousands-separator", "SEP", "put SEP between groups of digits in counts (default from the language; empty for none)"),
+    flag(None, "summary", "after the content, print a table of each input's size, lines read and shown, matches, errors and time"),
+    flag(None, "summary-only", "print only the --summary table, not the content"),
+    flag(None, "side-by-side", "show two files next to each other in columns"),
+    flag(None, "compare", "like cmp, report whether two files are identical and where they first differ (exit 1 if they do)"),
+    flag(None, "side-by-side-wrap", "with --side-by-side, wrap long lines instead of cutting them"),
+    flag(None, "merge", "interleave the files: line 1 of each, then line 2 of each, and so on"),
+    with_value(None, "merge-delimiter", "SEP", "with --merge, join each round of lines into one line separated by SEP"),
+    flag(None, "merge-shortest", "with --merge, stop at the end of the shortest file instead of filling in blank lines"),
+    OptionSpec { short: None, long: "resume", value: Some("clear"), optional: true, hidden: false, help: "continue files where the last run stopped; =clear forgets the saved position" },
+    with_value(None, "tee", "FILE", "also write the output to FILE, exactly as shown (repeatable)"),
+    with_value(None, "tee-plain", "FILE", "also write the output to FILE without colors (repeatable)"),
+    with_value(None, "output", "FILE", "write the output to FILE instead of stdout, without colors"),
+    with_value(None, "split-output", "N", "with --output=TEMPLATE, start a new numbered file every N lines"),
+    with_value(None, "split-output-bytes", "SIZE", "like --split-output, but by size (K, M, G), never splitting a line"),
+    flag(None, "in-place", "write the transformed output back to each file"),
+    with_value(None, "backup", "SUFFIX", "with --in-place, keep the original as FILE+SUFFIX"),
+    flag(None, "preserve-times", "with --in-place, keep the original modification time"),
+    flag(None, "blame", "show git blame gutter (commit, author, age)"),
+    OptionSpec { short: None, long: "changes", value: Some("REF"), optional: true, hidden: false, help: "mark lines changed against the git index or REF" },
+    OptionSpec { short: None, long: "banner", value: Some("VARIANT"), optional: true, hidden: false, help: "print an ASCII-art cat first (sitting, loaf, sleeping)" },
+    with_value(None, "title", "TEXT", "print TEXT in large letters before the content"),
+    flag(None, "force-art", "print --banner and --title even when stdout is not a terminal"),
+    with_value(None, "prepend", "TEXT", "print TEXT before the content of each input (@FILE reads it from FILE)"),
+    with_value(None, "append", "TEXT", "print TEXT after the content of each input (@FILE reads it from FILE)"),
+    flag(None, "qr", "show the contents as a QR code"),
+    flag(None, "qr-invert", "swap QR colors for light terminal backgrounds"),
+    flag(None, "find-dupes", "report lines that occur more than once anywhere in each input"),
+    flag(None, "count-invisible", "report which lines hold no-break spaces, zero-width, bidi or control characters"),
+    flag(None, "mark-dupes", "mark repeated occurrences of a line in the gutter"),
+    flag(None, "dupes-ignore-case", "compare lines case-insensitively for duplicates"),
+    flag(None, "dupes-ignore-whitespace", "ignore differences in whitespace when looking for duplicates"),
+    flag(None, "detect-indent", "report whether each input is indented with tabs or spaces"),
+    flag(None, "no-preprocess", "show files as-is, ignoring preprocessors from the config file"),
+    flag(None, "list-preprocessors", "list the preprocessors from the config file and exit"),
+    flag(None, "no-profiles", "ignore the per-file option profiles from the config file"),
+    with_value(None, "view", "NAME", "use the arguments saved as view NAME in the config file (also @NAME)"),
+    flag(None, "list-views", "list the views from the config file and exit"),
+    flag(Some('R'), "recursive", "read all files under each directory, recursively"),
+    with_value(None, "exclude", "GLOB", "with -R, skip files and directories matching GLOB (repeatable)"),
+    with_value(None, "include", "GLOB", "with -R, only read files matching GLOB (repeatable)"),
+    flag(None, "flatten", "print no file headers, as one continuous stream"),
+    flag(None, "tree", "show file headers as a directory tree"),
+    flag(None, "list-dirs", "list the entries of directories instead of failing"),
+    flag(None, "list", "list the entries of zip archives instead of their content"),
+    with_value(None, "zip-entry", "PATH", "show the entry PATH of each zip archive (also ARCHIVE.zip:PATH)"),
+    with_value(None, "sort-dir", "KEY", "sort directory listings by name, size or mtime"),
+    flag(None, "dirs-first", "put subdirectories first in directory listings"),
+    flag(None, "no-special", "refuse to read FIFOs, devices and sockets"),
+    flag(None, "safe", "show escape sequences from the input as ^[ instead of sending them to the terminal"),
+    flag(None, "unsafe", "pass escape sequences from the input through, even suspicious ones"),
+    flag(None, "preserve-ansi", "keep the input's colors going past line numbers and other gutters"),
+    with_value(None, "lang", "CODE", "language for messages (en, ru)"),
+    with_value(None, "init", "SHELL", "print a cat function for bash, zsh or fish that uses meow in a terminal"),
+    flag(Some('h'), "help", "display this help and exit"),
+    OptionSpec { short: None, long: "generate-man", value: None, optional: false, hidden: true, help: "print the meow(1) man page in roff format" },
+    OptionSpec { short: None, long: "show-colors", value: None, optional: false, hidden: true, help: "print a sample of each color role and the rainbow palette" },
+];
+
+const EXAMPLES: &[(&str, &str)] = &[
+    ("meow -n file.txt", "Display file with line numbers"),
+    ("meow -ET file.txt", "Show tabs and line endings"),
+    ("meow -g 'pattern' file.txt", "Only show lines matching 'pattern'"),
+    ("meow -r file.txt", "Display rainbow text"),
+];
+
+const ENVIRONMENT: &[(&str, &str)] = &[
+    ("MEOW_OPTS", "default options, inserted before the command line arguments"),
+    ("MEOW_CONFIG", "config file (default: $XDG_CONFIG_HOME/meow/config.toml)"),
+    ("MEOW_PAGER", "pager command used by --page (default: less -R)"),
+    ("NO_COLOR", "disable colors when set to a non-empty value"),
+    ("LC_ALL, LC_MESSAGES, LANG", "language for messages, overridden by --lang"),
+    ("VISUAL, EDITOR", "editor launched by --edit"),
+];
+
+const EXIT_STATUS: &[(&str, &str)] = &[
+    ("0", "all inputs were processed successfully"),
+    ("1", "an input could not be read or a filter failed"),
+];
+
 impl Config {
     fn new() -> Self {
-        let use_colors = atty::is(atty::Stream::Stdout);
-        let colors = ColorConfig::new(use_colors);
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let use_colors = atty::is(atty::Stream::Stdout) && !no_color;
+        let color_depth = Depth::detect();
+        let colors = ColorConfig::new(use_colors, color_depth);
         
         Config {
             show_line_numbers: false,
@@ -79,13 +702,204 @@ impl Config {
             show_all_nonprinting: false,
             show_line_length: false,
             rainbow_mode: false,
+            color_depth,
             use_colors,
             interactive_mode: false,
+            record: None,
+            replay: None,
+            replay_speed: 1.0,
+            replay_instant: false,
+            pick: false,
             show_meta: false,
+            freshness: None,
+            warn_older_than: None,
+            warn_newer_than: None,
             grep_pattern: None,
+            grep_file: None,
+            grep_any: None,
+            grep_file_comments: false,
+            grep_fuzzy: None,
+            fuzzy_distance: None,
+            show_distance: false,
             page_mode: false,
+            more: false,
             animate: false,
+            throttle: None,
             highlight_pattern: None,
+            highlight_regex: None,
+            highlight_field: None,
+            match_brackets: false,
+            brackets_check: false,
+            number_heat: false,
+            heat_units: false,
+            heat_thresholds: (100.0, 1000.0),
+            heat_auto: false,
+            list_matches: None,
+            list_matches_count: false,
+            blame: false,
+            changes: false,
+            changes_ref: None,
+            quiet: false,
+            no_special: false,
+            safe: None,
+            preserve_ansi: false,
+            image_protocol: "auto".to_string(),
+            image_width: None,
+            sample_every: None,
+            sample_probability: None,
+            sample_spread: None,
+            sample_seed: None,
+            filter: None,
+            escape_mode: None,
+            shell_quote: false,
+            shell_quote_array: false,
+            null_records: false,
+            nonprint_style: "caret".to_string(),
+            number_hex: None,
+            ruler: false,
+            ruler_every: None,
+            max_col: None,
+            truncate_middle: None,
+            tab_width: 8,
+            fold: None,
+            fold_indent: 0,
+            strip_comments: false,
+            find_dupes: false,
+            count_invisible: false,
+            mark_dupes: false,
+            dupes_ignore_case: false,
+            dupes_ignore_whitespace: false,
+            comment_style: None,
+            edit: false,
+            edit_cmd: None,
+            edit_all: false,
+            tail_bytes: None,
+            generate_man: false,
+            show_colors: false,
+            set_title: false,
+            init: None,
+            banner: None,
+            title: None,
+            force_art: false,
+            prepend: None,
+            append: None,
+            detect_indent: false,
+            continuous_numbering: false,
+            qr: false,
+            qr_invert: false,
+            max_line_bytes: None,
+            preprocessors: Vec::new(),
+            no_preprocess: false,
+            list_preprocessors: false,
+            profiles: Vec::new(),
+            no_profiles: false,
+            profile: None,
+            explicit: HashSet::new(),
+            views: Vec::new(),
+            list_views: false,
+            date_format: None,
+            serve: false,
+            serve_port: 8000,
+            serve_bind: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            serve_raw: false,
+            serve_once: false,
+            with_filename: false,
+            file_colors: false,
+            verify: false,
+            stat: false,
+            summary: false,
+            summary_only: false,
+            side_by_side: false,
+            compare: false,
+            fold_sections: None,
+            side_by_side_wrap: false,
+            merge: false,
+            merge_delimiter: None,
+            merge_shortest: false,
+            checksum: None,
+            index: false,
+            index_format: "tsv".to_string(),
+            skip_blank_numbers: false,
+            env_vars: None,
+            env_strict: false,
+            env_file: None,
+            annotations_path: None,
+            annotations: None,
+            annotations_block: false,
+            annotations_always: false,
+            rules_path: None,
+            theme_path: None,
+            export_theme: None,
+            rules: Vec::new(),
+            recursive: false,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            flatten: false,
+            tree: false,
+            tree_headers: HashMap::new(),
+            list_dirs: false,
+            list_archives: false,
+            zip_entry: None,
+            dir_sort: "name".to_string(),
+            dirs_first: false,
+            exec: None,
+            exec_shell: false,
+            exec_stop_on_error: false,
+            watch_interval: None,
+            diff_changes: false,
+            bell: false,
+            bell_notify: false,
+            each_line_command: None,
+            each_line_output: false,
+            each_line_jobs: 4,
+            changed_lines: HashSet::new(),
+            redactor: None,
+            json_array: false,
+            group_by_file: false,
+            structured: None,
+            plain: false,
+            raw_timed: false,
+            fold_depth: None,
+            xml_indent: 2,
+            xml_collapse: Vec::new(),
+            resume: false,
+            resume_clear: false,
+            tee_files: Vec::new(),
+            output: None,
+            split_output: split::Limit::None,
+            output_template: None,
+            in_place: false,
+            backup_suffix: None,
+            preserve_times: false,
+            match_index: None,
+            max_count: None,
+            max_count_total: false,
+            si: false,
+            bytes_exact: false,
+            max_terminal_bytes: Some(50_000_000),
+            force: false,
+            wait_for_file: false,
+            wait_timeout: None,
+            thousands_separator: None,
+            min_length: None,
+            fields: None,
+            preview: None,
+            fields_invert: false,
+            delimiter: None,
+            output_delimiter: None,
+            csv: false,
+            max_length: None,
+            since: None,
+            until: None,
+            time_format: None,
+            skip_untimed: false,
+            before_context: 0,
+            after_context: 0,
+            marks: Vec::new(),
+            mark_pattern: None,
+            marks_index: false,
+            record_separator: None,
+            group_separator: Some("--".to_string()),
             files: Vec::new(),
             colors,
         }
@@ -96,80 +910,57 @@ impl Config {
         while i < args.len() {
             let arg = &args[i];
             
-            if arg.starts_with("--") {
-                // Long options
-                match arg.as_str() {
-                    "--help" => return false,
-                    "--number" => self.show_line_numbers = true,
-                    "--show-ends" => self.show_ends = true,
-                    "--show-tabs" => self.show_tabs = true,
-                    "--squeeze-blank" => self.squeeze_blank = true,
-                    "--number-nonblank" => self.number_nonblank = true,
-                    "--show-nonprinting" => self.show_all_nonprinting = true,
-                    "--show-length" => self.show_line_length = true,
-                    "--rainbow" => self.rainbow_mode = true,
-                    "--no-color" => {
-                        self.use_colors = false;
-                        self.colors = ColorConfig::new(false);
-                    },
-                    "--interactive" => self.interactive_mode = true,
-                    "--meta" => self.show_meta = true,
-                    "--page" => self.page_mode = true,
-                    "--animate" => self.animate = true,
-                    _ if arg.starts_with("--grep=") => {
-                        self.grep_pattern = Some(arg[7..].to_string());
-                    },
-                    _ if arg.starts_with("--highlight=") => {
-                        self.highlight_pattern = Some(arg[12..].to_string());
-                    },
-                    _ => {
-                        eprintln!("{}meow: unknown option: {}{}", self.colors.error, arg, self.colors.reset);
+            if let Some(long) = arg.strip_prefix("--") {
+                // Long options, values are attached with '='
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (long, None),
+                };
+                let spec = match OPTIONS.iter().find(|spec| spec.long == name) {
+                    Some(spec) => spec,
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("unknown-option", arg), self.colors.reset);
                         return false;
                     }
+                };
+                
+                if spec.value.is_none() && value.is_some() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("option-takes-no-value", name), self.colors.reset);
+                    return false;
+                }
+                if spec.value.is_some() && !spec.optional && value.is_none() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("option-requires-value", name), self.colors.reset);
+                    return false;
+                }
+                
+                if !self.take_option(spec.long, value) {
+                    return false;
                 }
             } else if arg.starts_with('-') && arg.len() > 1 {
-                // Short options
+                // Short options, a value (if any) is the next argument
                 for c in arg[1..].chars() {
-                    match c {
-                        'n' => self.show_line_numbers = true,
-                        'E' => self.show_ends = true,
-                        'T' => self.show_tabs = true,
-                        's' => self.squeeze_blank = true,
-                        'b' => self.number_nonblank = true,
-                        'A' => self.show_all_nonprinting = true,
-                        'l' => self.show_line_length = true,
-                        'r' => self.rainbow_mode = true,
-                        'C' => {
-                            self.use_colors = false;
-                            self.colors = ColorConfig::new(false);
-                        },
-                        'i' => self.interactive_mode = true,
-                        'm' => self.show_meta = true,
-                        'p' => self.page_mode = true,
-                        'a' => self.animate = true,
-                        'g' => {
-                            if i + 1 < args.len() {
-                                self.grep_pattern = Some(args[i + 1].clone());
-                                i += 1;
-                            } else {
-                                eprintln!("{}meow: -g requires a pattern{}", self.colors.error, self.colors.reset);
-                                return false;
-                            }
-                        },
-                        'H' => {
-                            if i + 1 < args.len() {
-                                self.highlight_pattern = Some(args[i + 1].clone());
-                                i += 1;
-                            } else {
-                                eprintln!("{}meow: -H requires a pattern{}", self.colors.error, self.colors.reset);
-                                return false;
-                            }
-                        },
-                        'h' => return false,
-                        _ => {
-                            eprintln!("{}meow: unknown option: -{}{}", self.colors.error, c, self.colors.reset);
+                    let spec = match OPTIONS.iter().find(|spec| spec.short == Some(c)) {
+                        Some(spec) => spec,
+                        None => {
+                            eprintln!("{}meow: {}{}", self.colors.error, msg!("unknown-option", format!("-{}", c)), self.colors.reset);
+                            return false;
+                        }
+                    };
+                    
+                    let value = if spec.value.is_some() && !spec.optional {
+                        if i + 1 < args.len() {
+                            i += 1;
+                            Some(args[i].as_str())
+                        } else {
+                            eprintln!("{}meow: {}{}", self.colors.error, msg!("short-option-requires-value", c, spec.value.unwrap_or("VALUE")), self.colors.reset);
                             return false;
                         }
+                    } else {
+                        None
+                    };
+                    
+                    if !self.take_option(spec.long, value) {
+                        return false;
                     }
                 }
             } else {
@@ -180,398 +971,6222 @@ impl Config {
             i += 1;
         }
         
-        true
-    }
-}
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let mut config = Config::new();
-    
-    if !config.parse_args(&args) {
-        print_help(&config);
-        return;
-    }
-    
-    // If no files, read from stdin
-    if config.files.is_empty() {
-        let stdin = io::stdin();
-        process_input(&mut BufReader::new(stdin), &config, "stdin");
-    } else {
-        // Process each file
-        for file_path in &config.files {
-            let path = Path::new(file_path);
-            match File::open(path) {
-                Ok(file) => {
-                    if config.files.len() > 1 {
-                        println!("\n===> {}{}{}{}{}",
-                                config.colors.filename,
-                                file_path,
-                                config.colors.reset,
-                                if config.show_meta { get_file_meta(path) } else { "".to_string() },
-                                " <===");
-                    }
-                    
-                    let mut reader = BufReader::new(file);
-                    
-                    if config.page_mode {
-                        let content = read_all_content(&mut reader);
-                        page_content(&content);
-                    } else {
-                        process_input(&mut reader, &config, file_path);
-                    }
-                },
+        // Checked once everything is in, since --output can come either side of --split-output
+        if !matches!(self.split_output, split::Limit::None) {
+            let Some(output) = &self.output else {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("split-needs-output"), self.colors.reset);
+                return false;
+            };
+            match split::Template::parse(output) {
+                Ok(template) => self.output_template = Some(template),
                 Err(err) => {
-                    eprintln!("{}meow: {}: {}{}", config.colors.error, file_path, err, config.colors.reset);
+                    eprintln!("{}meow: {}{}", self.colors.error, err, self.colors.reset);
+                    return false;
                 }
             }
         }
+        if let Some(fuzzy) = &mut self.grep_fuzzy {
+            let length = fuzzy.pattern_len();
+            fuzzy.distance = match self.fuzzy_distance {
+                Some(distance) if distance >= length => {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("fuzzy-distance-too-large", distance, length), self.colors.reset);
+                    return false;
+                },
+                Some(distance) => distance,
+                None => 2.min(length.saturating_sub(1)),
+            };
+        }
+        if self.record.is_some() && !self.interactive_mode {
+            eprintln!("{}meow: {}{}", self.colors.error, msg!("record-needs-interactive"), self.colors.reset);
+            return false;
+        }
+        if self.bell && (self.exec.is_none() || self.watch_interval.is_none()) {
+            eprintln!("{}meow: {}{}", self.colors.error, msg!("bell-needs-watch"), self.colors.reset);
+            return false;
+        }
+        if self.output.is_some() {
+            self.use_colors = false;
+            self.colors = ColorConfig::new(false, self.color_depth);
+        }
+        // Records for another program: the text and nothing else
+        if self.shell_quote || self.null_records {
+            self.use_colors = false;
+            self.colors = ColorConfig::new(false, self.color_depth);
+            self.show_line_numbers = false;
+            self.number_nonblank = false;
+            self.with_filename = false;
+            self.blame = false;
+            self.changes = false;
+            self.mark_dupes = false;
+            self.flatten = true;
+            self.group_separator = None;
+        }
+        
+        true
     }
     
-    // Interactive mode prompt after all files are processed
-    if config.interactive_mode {
-        interactive_shell(&config);
-    }
-}
-
-fn read_all_content<R: Read>(reader: &mut BufReader<R>) -> String {
-    let mut content = String::new();
-    if let Err(e) = reader.read_to_string(&mut content) {
-        eprintln!("Error reading content: {}", e);
+    /// Applies an option from the command line, or from a profile unless the
+    /// command line already set it.
+    fn take_option(&mut self, name: &'static str, value: Option<&str>) -> bool {
+        if self.profile.is_none() {
+            self.explicit.insert(name);
+        } else if self.explicit.contains(name) {
+            return true;
+        }
+        self.apply_option(name, value)
     }
-    content
-}
-
-fn page_content(content: &str) {
-    let mut pager = Command::new("less")
-        .stdin(Stdio::piped())
-        .spawn()
-        .expect("Failed to start pager");
     
-    {
-        let stdin = pager.stdin.as_mut().expect("Failed to open stdin");
-        stdin.write_all(content.as_bytes()).expect("Failed to write to stdin");
+    /// These settings plus the options of `profile`, tagged with its pattern.
+    /// None if one of the options is invalid.
+    fn with_profile(&self, profile: &Profile) -> Option<Config> {
+        let mut config = self.clone();
+        config.profile = Some(profile.pattern.clone());
+        let args: Vec<String> = std::iter::once(String::new()).chain(profile.options.iter().cloned()).collect();
+        config.parse_args(&args).then_some(config)
     }
     
-    pager.wait().expect("Failed to wait on pager");
-}
-
-fn get_file_meta(path: &Path) -> String {
-    let metadata = match path.metadata() {
-        Ok(meta) => meta,
-        Err(_) => return "".to_string(),
-    };
+    /// Settings for the file at `index` on the command line: those of the profile
+    /// matching `path`, if any. With `--file-colors` the highlight and file name
+    /// colors come from a palette cycled per file.
+    fn for_file(&self, index: usize, path: Option<&Path>) -> Config {
+        let profile = path
+            .filter(|_| !self.no_profiles)
+            .and_then(|path| settings::find_profile(&self.profiles, path))
+            .and_then(|profile| self.with_profile(profile));
+        let mut config = profile.unwrap_or_else(|| self.clone());
+        if self.file_colors {
+            let palette = &self.colors.file_palette;
+            config.colors.highlight = palette[index % palette.len()].clone();
+            config.colors.filename = config.colors.highlight.clone();
+        }
+        config
+    }
     
-    let size = metadata.len();
-    let size_str = if size < 1024 {
-        format!("{} B", size)
-    } else if size < 1024 * 1024 {
-        format!("{:.1} KB", size as f64 / 1024.0)
-    } else if size < 1024 * 1024 * 1024 {
-        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
-    } else {
-        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
-    };
+    /// The inputs to read: stdin when no files are given, entries of zip
+    /// archives with `--zip-entry`.
+    fn sources(&self) -> Vec<InputSource> {
+        if self.files.is_empty() {
+            return vec![InputSource::Stdin];
+        }
+        self.files.iter().map(|file| match &self.zip_entry {
+            Some(entry) => InputSource::ZipEntry(PathBuf::from(file), entry.clone()),
+            None => InputSource::from_arg(file),
+        }).collect()
+    }
     
-    let modified = match metadata.modified() {
-        Ok(time) => {
-            let duration = match time.duration_since(UNIX_EPOCH) {
-                Ok(duration) => duration,
-                Err(_) => return format!(" [{}]", size_str),
-            };
-            
-            let secs = duration.as_secs();
-            let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
-                Ok(now) => now.as_secs(),
-                Err(_) => return format!(" [{}]", size_str),
-            };
-            
-            if now - secs < 60 * 60 {
-                format!("{} mins ago", (now - secs) / 60)
-            } else if now - secs < 60 * 60 * 24 {
-                format!("{} hours ago", (now - secs) / (60 * 60))
-            } else {
-                format!("{} days ago", (now - secs) / (60 * 60 * 24))
-            }
-        },
-        Err(_) => "unknown time".to_string(),
-    };
+    /// Whether lines are selected by `--grep`, `--grep-file` or `--grep-fuzzy`.
+    fn searching(&self) -> bool {
+        self.grep_pattern.is_some() || self.grep_any.is_some() || self.grep_fuzzy.is_some()
+    }
     
-    format!(" [{}] [{}]", size_str, modified)
-}
-
-fn process_input<R: Read>(reader: &mut BufReader<R>, config: &Config, file_name: &str) {
-    // Check if we need to animate the output
-    if config.animate {
-        let content = read_all_content(reader);
-        animate_text(&content);
-        return;
+    /// Whether `line` passes `--grep`, `--grep-file` and `--grep-fuzzy`, those given.
+    fn grep_selects(&self, line: &str) -> bool {
+        self.grep_pattern.as_ref().is_none_or(|pattern| line.contains(pattern.as_str()))
+            && self.grep_any.as_ref().is_none_or(|any| any.is_match(line))
+            && self.grep_fuzzy.as_ref().is_none_or(|fuzzy| fuzzy.is_match(line))
     }
     
-    let mut line_num = 0;
-    let mut prev_blank = false;
+    /// Whether any option changes the text itself, as opposed to how it is shown.
+    /// `--in-place` refuses to run without one.
+    fn transforms_content(&self) -> bool {
+        self.show_line_numbers || self.number_nonblank || self.show_ends || self.show_tabs
+            || self.squeeze_blank || self.show_all_nonprinting || self.searching()
+            || self.escape_mode.is_some() || self.sample_every.is_some() || self.sample_probability.is_some()
+            || self.max_line_bytes.is_some() || self.with_filename
+            || self.min_length.is_some() || self.max_length.is_some() || self.fields.is_some()
+            || self.strip_comments || self.preview.is_some() || self.since.is_some() || self.until.is_some()
+            || self.redactor.is_some() || self.record_separator.is_some() || self.null_records
+    }
     
-    let mut lines = reader.lines();
-    while let Some(line_result) = lines.next() {
-        match line_result {
-            Ok(line) => {
-                let is_blank = line.trim().is_empty();
-                
-                // Skip blank lines with squeeze_blank option
-                if config.squeeze_blank && is_blank && prev_blank {
-                    continue;
+    /// Applies a single option from `OPTIONS` by its long name. Returns false to stop
+    /// parsing and show the help (after printing an error for invalid values).
+    fn apply_option(&mut self, name: &str, value: Option<&str>) -> bool {
+        match (name, value) {
+            ("help", _) => return false,
+            ("number", _) => self.show_line_numbers = true,
+            ("skip-blank-numbers", _) => self.skip_blank_numbers = true,
+            ("continuous-numbering", _) => self.continuous_numbering = true,
+            ("number-hex", None) => self.number_hex = Some(""),
+            ("number-hex", Some("0x")) => self.number_hex = Some("0x"),
+            ("number-hex", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-number-hex", other), self.colors.reset);
+                return false;
+            },
+            ("show-ends", _) => self.show_ends = true,
+            ("show-tabs", _) => self.show_tabs = true,
+            ("squeeze-blank", _) => self.squeeze_blank = true,
+            ("number-nonblank", _) => self.number_nonblank = true,
+            ("show-nonprinting", _) => self.show_all_nonprinting = true,
+            ("show-length", _) => self.show_line_length = true,
+            ("nonprint-style", Some(style)) => {
+                if !["caret", "hex", "unicode"].contains(&style) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-nonprint-style", style), self.colors.reset);
+                    return false;
                 }
-                
-                // Skip lines that don't match the grep pattern
-                if let Some(pattern) = &config.grep_pattern {
-                    if !line.contains(pattern) {
-                        continue;
+                self.nonprint_style = style.to_string();
+            },
+            ("ruler", _) => self.ruler = true,
+            ("ruler-every" | "max-col" | "truncate-middle" | "tab-width", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => match name {
+                        "ruler-every" => {
+                            self.ruler = true;
+                            self.ruler_every = Some(n);
+                        },
+                        "max-col" => self.max_col = Some(n),
+                        "truncate-middle" => self.truncate_middle = Some(n),
+                        _ => self.tab_width = n,
+                    },
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
                     }
                 }
-                
-                prev_blank = is_blank;
-                
-                // Handle line numbering
-                if config.number_nonblank {
-                    if !is_blank {
-                        line_num += 1;
-                        print!("{}{:6}{} | ", config.colors.number, line_num, config.colors.reset);
-                    } else {
-                        print!("       | ");
+            },
+            ("fold" | "fold-indent", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if name == "fold-indent" => self.fold_indent = n,
+                    Ok(n) if n > 0 => self.fold = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
                     }
-                } else if config.show_line_numbers {
-                    line_num += 1;
-                    print!("{}{:6}{} | ", config.colors.number, line_num, config.colors.reset);
                 }
-                
-                // Process and print the line
-                let mut output_line = String::new();
-                
-                if config.show_all_nonprinting {
-                    // Show non-printing characters
-                    for c in line.chars() {
-                        if c.is_control() && c != '\t' {
-                            output_line.push('^');
-                            output_line.push((c as u8 + 64) as char);
-                        } else if c == '\t' && config.show_tabs {
-                            output_line.push_str("^I");
-                        } else {
-                            output_line.push(c);
-                        }
+            },
+            ("rainbow", _) => self.rainbow_mode = true,
+            ("no-color", _) => {
+                self.use_colors = false;
+                self.colors = ColorConfig::new(false, self.color_depth);
+            },
+            ("color-depth", Some(depth)) => {
+                match Depth::from_name(depth) {
+                    Some(depth) => {
+                        self.color_depth = depth;
+                        self.colors = ColorConfig::new(self.use_colors, depth);
+                    },
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-color-depth", depth), self.colors.reset);
+                        return false;
                     }
-                } else {
-                    // Normal printing with tab handling
-                    if config.show_tabs {
-                        output_line = line.replace('\t', "^I");
-                    } else {
-                        output_line = line;
+                }
+            },
+            ("interactive", _) => self.interactive_mode = true,
+            ("record", Some(path)) => self.record = Some(path.to_string()),
+            ("replay", Some(path)) => self.replay = Some(path.to_string()),
+            ("replay-speed", Some(speed)) => {
+                match record::parse_speed(speed) {
+                    Some(speed) => self.replay_speed = speed,
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, speed), self.colors.reset);
+                        return false;
                     }
                 }
-                
-                // Highlight pattern if specified
-                if let Some(pattern) = &config.highlight_pattern {
-                    if output_line.contains(pattern) {
-                        let parts: Vec<&str> = output_line.split(pattern).collect();
-                        print!("{}", parts[0]);
-                        
-                        for i in 1..parts.len() {
-                            print!("{}{}{}{}", config.colors.highlight, pattern, config.colors.reset, parts[i]);
-                        }
-                    } else {
-                        print!("{}", output_line);
+            },
+            ("replay-instant", _) => self.replay_instant = true,
+            ("pick", _) => self.pick = true,
+            ("meta", _) => self.show_meta = true,
+            ("date-format", Some(format)) => {
+                // chrono only reports a bad specifier when formatting, and then panics
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-date-format", format), self.colors.reset);
+                    return false;
+                }
+                self.date_format = Some(format.to_string());
+            },
+            ("page", _) => self.page_mode = true,
+            ("more", _) => self.more = true,
+            ("animate", _) => self.animate = true,
+            ("throttle", Some(rate)) => {
+                match rate.parse::<f64>() {
+                    Ok(rate) if rate.is_finite() && rate >= 0.0 => self.throttle = (rate > 0.0).then_some(rate),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, rate), self.colors.reset);
+                        return false;
                     }
-                } else if config.rainbow_mode {
-                    // Rainbow mode - colorize each character
-                    let rainbow_colors = [
-                        "\x1B[31m", "\x1B[33m", "\x1B[32m", "\x1B[36m", "\x1B[34m", "\x1B[35m",
-                    ];
-                    
-                    for (i, c) in output_line.chars().enumerate() {
-                        let color_index = i % rainbow_colors.len();
-                        print!("{}{}{}", rainbow_colors[color_index], c, config.colors.reset);
+                }
+            },
+            ("quiet", _) => self.quiet = true,
+            ("grep", Some(pattern)) => self.grep_pattern = Some(pattern.to_string()),
+            ("since" | "until", Some(time)) => {
+                match logtime::parse_bound(time) {
+                    Some(millis) if name == "since" => self.since = Some(millis),
+                    Some(millis) => self.until = Some(millis),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-time", time), self.colors.reset);
+                        return false;
                     }
-                } else {
-                    print!("{}", output_line);
                 }
-                
-                // Show line length if requested
-                if config.show_line_length {
-                    print!(" {}[{}L, {}C]{}", 
-                           config.colors.normal, 
-                           output_line.lines().count(), 
-                           output_line.chars().count(),
-                           config.colors.reset);
+            },
+            ("time-format", Some(format)) => {
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-date-format", format), self.colors.reset);
+                    return false;
                 }
-                
-                // Show end of line marker
-                if config.show_ends {
-                    print!("{}${}",
-                          if config.use_colors { config.colors.highlight.clone() } else { "".to_string() },
-                          config.colors.reset);
+                self.time_format = Some(format.to_string());
+            },
+            ("skip-untimed", _) => self.skip_untimed = true,
+            ("preview", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.preview = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
                 }
-                
-                println!();
             },
-            Err(err) => {
-                eprintln!("{}meow: {}: {}{}", config.colors.error, file_name, err, config.colors.reset);
-                break;
-            }
-        }
-    }
-}
-
-fn animate_text(content: &str) {
-    for line in content.lines() {
-        for c in line.chars() {
-            print!("{}", c);
-            io::stdout().flush().unwrap();
-            thread::sleep(Duration::from_millis(10));
-        }
-        println!();
-        thread::sleep(Duration::from_millis(50));
-    }
-}
-
-fn interactive_shell(config: &Config) {
-    let mut command_history: Vec<String> = Vec::new();
-    let current_config = config.clone();
-    
-    println!("\n{}=== Meow Interactive Shell ==={}", config.colors.success, config.colors.reset);
-    println!("Type 'help' for available commands, 'exit' to quit\n");
-    
-    loop {
-        print!("{}meow>{} ", config.colors.success, config.colors.reset);
-        io::stdout().flush().unwrap();
-        
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            break;
-        }
-        
-        let input = input.trim();
-        if input.is_empty() {
-            continue;
-        }
-        
-        command_history.push(input.to_string());
-        
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        
-        if parts.is_empty() {
-            continue;
-        }
-        
-        match parts[0] {
-            "exit" | "quit" => break,
-            "help" => {
-                println!("Available commands:");
-                println!("  cat <file>    - Display file contents");
-                println!("  grep <pattern> <file> - Find pattern in file");
-                println!("  highlight <pattern> <file> - Highlight pattern in file");
-                println!("  rainbow <file> - Display file with rainbow colors");
-                println!("  history       - Show command history");
-                println!("  exit/quit     - Exit the shell");
-            },
-            "cat" => {
-                if parts.len() < 2 {
-                    println!("{}Usage: cat <file>{}", config.colors.error, config.colors.reset);
-                    continue;
+            ("fields", Some(list)) => {
+                match parse_field_list(list) {
+                    Some(ranges) => self.fields = Some(ranges),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-field-list", list), self.colors.reset);
+                        return false;
+                    }
                 }
-                
-                if let Ok(file) = File::open(parts[1]) {
-                    let mut reader = BufReader::new(file);
-                    process_input(&mut reader, &current_config, parts[1]);
-                } else {
-                    println!("{}Error: Could not open file '{}'{}", config.colors.error, parts[1], config.colors.reset);
+            },
+            ("fields-invert", _) => self.fields_invert = true,
+            ("delimiter", Some(delimiter)) => {
+                if delimiter.is_empty() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("empty-delimiter"), self.colors.reset);
+                    return false;
                 }
+                self.delimiter = Some(delimiter.to_string());
             },
-            "grep" => {
-                if parts.len() < 3 {
-                    println!("{}Usage: grep <pattern> <file>{}", config.colors.error, config.colors.reset);
-                    continue;
+            ("output-delimiter", Some(delimiter)) => self.output_delimiter = Some(delimiter.to_string()),
+            ("csv", _) => self.csv = true,
+            ("min-length" | "max-length", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if name == "min-length" => self.min_length = Some(n),
+                    Ok(n) => self.max_length = Some(n),
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
                 }
-                
-                if let Ok(file) = File::open(parts[2]) {
-                    let mut local_config = current_config.clone();
-                    local_config.grep_pattern = Some(parts[1].to_string());
-                    let mut reader = BufReader::new(file);
-                    process_input(&mut reader, &local_config, parts[2]);
-                } else {
-                    println!("{}Error: Could not open file '{}'{}", config.colors.error, parts[2], config.colors.reset);
+            },
+            ("record-separator", Some(separator)) => {
+                let separator = parse_separator(separator);
+                if separator.is_empty() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-record-separator"), self.colors.reset);
+                    return false;
                 }
+                self.record_separator = Some(separator);
             },
-            "highlight" => {
-                if parts.len() < 3 {
-                    println!("{}Usage: highlight <pattern> <file>{}", config.colors.error, config.colors.reset);
-                    continue;
+            ("group-separator", Some(separator)) => self.group_separator = Some(separator.to_string()),
+            ("grep-file", Some(file)) => self.grep_file = Some(file.to_string()),
+            ("grep-file-comments", _) => self.grep_file_comments = true,
+            ("max-count-total", _) => self.max_count_total = true,
+            ("grep-fuzzy", Some(pattern)) => self.grep_fuzzy = Some(fuzzy::Fuzzy::new(pattern, 0)),
+            ("show-distance", _) => self.show_distance = true,
+            ("no-group-separator", _) => self.group_separator = None,
+            ("context" | "before-context" | "after-context" | "match-index" | "fuzzy-distance" | "max-count", Some(n)) => {
+                let n = match n.parse::<usize>() {
+                    Ok(n) if n > 0 || name != "match-index" => n,
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                };
+                match name {
+                    "context" => {
+                        self.before_context = n;
+                        self.after_context = n;
+                    },
+                    "before-context" => self.before_context = n,
+                    "after-context" => self.after_context = n,
+                    "fuzzy-distance" => self.fuzzy_distance = Some(n),
+                    "max-count" => self.max_count = Some(n),
+                    _ => self.match_index = Some(n),
                 }
-                
-                if let Ok(file) = File::open(parts[2]) {
-                    let mut local_config = current_config.clone();
-                    local_config.highlight_pattern = Some(parts[1].to_string());
-                    let mut reader = BufReader::new(file);
-                    process_input(&mut reader, &local_config, parts[2]);
-                } else {
-                    println!("{}Error: Could not open file '{}'{}", config.colors.error, parts[2], config.colors.reset);
+            },
+            ("marks", Some(list)) => {
+                match list.split(',').map(|n| n.trim().parse::<usize>().ok().filter(|&n| n > 0)).collect::<Option<Vec<_>>>() {
+                    Some(mut marks) => {
+                        marks.sort_unstable();
+                        marks.dedup();
+                        self.marks = marks;
+                    },
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-marks", list), self.colors.reset);
+                        return false;
+                    }
                 }
             },
-            "rainbow" => {
-                if parts.len() < 2 {
-                    println!("{}Usage: rainbow <file>{}", config.colors.error, config.colors.reset);
-                    continue;
+            ("mark-pattern", Some(pattern)) => {
+                match Regex::new(pattern) {
+                    Ok(re) => self.mark_pattern = Some(re),
+                    Err(err) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-regex", pattern, err), self.colors.reset);
+                        return false;
+                    }
                 }
-                
-                if let Ok(file) = File::open(parts[1]) {
-                    let mut local_config = current_config.clone();
-                    local_config.rainbow_mode = true;
-                    let mut reader = BufReader::new(file);
-                    process_input(&mut reader, &local_config, parts[1]);
-                } else {
-                    println!("{}Error: Could not open file '{}'{}", config.colors.error, parts[1], config.colors.reset);
+            },
+            ("marks-index", _) => self.marks_index = true,
+            ("highlight", Some(pattern)) => self.highlight_pattern = Some(pattern.to_string()),
+            ("match-brackets", None) => self.match_brackets = true,
+            ("match-brackets", Some("check")) => self.brackets_check = true,
+            ("match-brackets", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-brackets-mode", other), self.colors.reset);
+                return false;
+            },
+            ("number-heat", _) => self.number_heat = true,
+            ("heat-units", _) => self.heat_units = true,
+            ("heat-auto", _) => self.heat_auto = true,
+            ("freshness", None) => self.freshness = Some(freshness::DEFAULT_THRESHOLDS),
+            ("freshness", Some(text)) => {
+                match freshness::parse_thresholds(text) {
+                    Some(thresholds) => self.freshness = Some(thresholds),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-freshness", text), self.colors.reset);
+                        return false;
+                    }
                 }
             },
-            "history" => {
-                println!("Command history:");
-                for (i, cmd) in command_history.iter().enumerate() {
-                    println!("  {}. {}", i + 1, cmd);
+            ("warn-older-than" | "warn-newer-than", Some(text)) => {
+                match freshness::parse_span(text) {
+                    Some(secs) if name == "warn-older-than" => self.warn_older_than = Some(secs),
+                    Some(secs) => self.warn_newer_than = Some(secs),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-span", name, text), self.colors.reset);
+                        return false;
+                    }
                 }
             },
-            _ => {
-                println!("{}Unknown command: '{}'{}", config.colors.error, parts[0], config.colors.reset);
-                println!("Type 'help' to see available commands");
-            }
-        }
-    }
-}
-
-fn print_help(config: &Config) {
-    println!("{}Usage:{} meow [OPTIONS]... [FILE]...", config.colors.success, config.colors.reset);
-    println!("Concatenate FILE(s) to standard output with enhancements.");
-    println!();
-    println!("If FILE is not specified or is -, read standard input.");
-    println!();
-    println!("  -n, --number             number all output lines");
-    println!("  -b, --number-nonblank    number nonempty output lines");
-    println!("  -E, --show-ends          display $ at end of each line");
-    println!("  -T, --show-tabs          display TAB characters as ^I");
-    println!("  -s, --squeeze-blank      suppress repeated empty output lines");
-    println!("  -A, --show-nonprinting   show all non-printing characters");
-    println!("  -l, --show-length        show line and character count");
-    println!("  -r, --rainbow            enable rainbow text mode");
-    println!("  -C, --no-color           disable colors");
-    println!("  -i, --interactive        enter interactive mode after processing");
-    println!("  -m, --meta               show file metadata");
-    println!("  -p, --page               use pager (like less) for output");
-    println!("  -a, --animate            animate text display");
-    println!("  -g <pattern>, --grep=<pattern>    only show lines matching pattern");
-    println!("  -H <pattern>, --highlight=<pattern>  highlight pattern in output");
-    println!("  -h, --help               display this help and exit");
-    println!();
-    println!("Examples:");
-    println!("  meow -n file.txt            Display file with line numbers");
-    println!("  meow -ET file.txt           Show tabs and line endings");
-    println!("  meow -g 'pattern' file.txt  Only show lines matching 'pattern'");
-    println!("  meow -r file.txt            Display rainbow text");
-    println!();
-    println!("Report bugs to: github.com/anmitalidev/meow");
-}
\ No newline at end of file
+            ("heat-thresholds", Some(text)) => {
+                match numbers::parse_thresholds(text) {
+                    Some(thresholds) => self.heat_thresholds = thresholds,
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-heat-thresholds", text), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("highlight-field", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.highlight_field = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("with-filename", _) => self.with_filename = true,
+            ("file-colors", _) => self.file_colors = true,
+            ("highlight-regex" | "list-matches" | "fold-sections", Some(pattern)) => {
+                match Regex::new(pattern) {
+                    Ok(re) if name == "list-matches" => self.list_matches = Some(re),
+                    Ok(re) if name == "fold-sections" => self.fold_sections = Some(re),
+                    Ok(re) => self.highlight_regex = Some(re),
+                    Err(err) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-regex", pattern, err), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("list-matches-count", _) => self.list_matches_count = true,
+            ("image-protocol", Some(protocol)) => {
+                if !["auto", "blocks", "kitty", "iterm"].contains(&protocol) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-image-protocol", protocol), self.colors.reset);
+                    return false;
+                }
+                self.image_protocol = protocol.to_string();
+            },
+            ("image-width", Some(width)) => {
+                match width.parse::<usize>() {
+                    Ok(width) if width > 0 => self.image_width = Some(width),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-image-width", width), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("sample", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.sample_every = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-sample-interval", n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("sample-spread", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.sample_spread = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("sample-seed", Some(seed)) => {
+                match seed.parse::<u64>() {
+                    Ok(seed) => self.sample_seed = Some(seed),
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, seed), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("sample-random", Some(p)) => {
+                match p.parse::<f64>() {
+                    Ok(p) if (0.0..=1.0).contains(&p) => self.sample_probability = Some(p),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-sample-probability", p), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("exec", Some(command)) => self.exec = Some(command.to_string()),
+            ("exec-shell", _) => self.exec_shell = true,
+            ("watch-interval", Some(interval)) => {
+                match exec::parse_interval(interval) {
+                    Some(interval) => self.watch_interval = Some(interval),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-interval", interval), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("diff-changes", _) => self.diff_changes = true,
+            ("each-line-command", Some(command)) => {
+                match exec::split_words(command) {
+                    Some(words) if !words.is_empty() => self.each_line_command = Some(words),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-exec-command", command), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("each-line-output", _) => self.each_line_output = true,
+            ("each-line-jobs", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.each_line_jobs = n,
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("exec-stop-on-error", _) => self.exec_stop_on_error = true,
+            ("bell", None) => self.bell = true,
+            ("bell", Some("notify")) => {
+                if !cfg!(feature = "notify") {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("notify-unsupported"), self.colors.reset);
+                    return false;
+                }
+                self.bell = true;
+                self.bell_notify = true;
+            },
+            ("bell", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-bell-mode", other), self.colors.reset);
+                return false;
+            },
+            ("filter", Some(command)) => self.filter = Some(command.to_string()),
+            ("expand-env", mode) => {
+                if mode.is_some_and(|mode| mode != "strict") {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-expand-env-mode", mode.unwrap_or_default()), self.colors.reset);
+                    return false;
+                }
+                self.env_vars = Some(env::vars().collect());
+                self.env_strict = mode.is_some();
+            },
+            ("env-file", Some(file)) => self.env_file = Some(file.to_string()),
+            ("annotations", Some(file)) => self.annotations_path = Some(file.to_string()),
+            ("rules", Some(file)) => self.rules_path = Some(file.to_string()),
+            ("theme", Some(file)) => self.theme_path = Some(file.to_string()),
+            ("export-theme", Some(file)) => self.export_theme = Some(file.to_string()),
+            ("annotations-block", _) => self.annotations_block = true,
+            ("annotations-always", _) => self.annotations_always = true,
+            ("strip-comments", _) => self.strip_comments = true,
+            ("find-dupes", _) => self.find_dupes = true,
+            ("count-invisible", _) => self.count_invisible = true,
+            ("mark-dupes", _) => self.mark_dupes = true,
+            ("dupes-ignore-case", _) => self.dupes_ignore_case = true,
+            ("dupes-ignore-whitespace", _) => self.dupes_ignore_whitespace = true,
+            ("comment-style", Some(style)) => {
+                if !["hash", "slash", "semicolon", "auto"].contains(&style) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-comment-style", style), self.colors.reset);
+                    return false;
+                }
+                self.comment_style = (style != "auto").then(|| style.to_string());
+            },
+            ("redact", value) => {
+                let redactor = self.redactor.get_or_insert_with(redact::Redactor::new);
+                match value {
+                    None => {},
+                    Some("pii") => redactor.add_pii(),
+                    Some(other) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-redact-mode", other), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("redact-style", Some(style)) => {
+                let hash = match style {
+                    "block" => false,
+                    "hash" => true,
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-redact-style", style), self.colors.reset);
+                        return false;
+                    }
+                };
+                self.redactor.get_or_insert_with(redact::Redactor::new).hash = hash;
+            },
+            ("redact-pattern", Some(pattern)) => {
+                match Regex::new(pattern) {
+                    Ok(regex) => self.redactor.get_or_insert_with(redact::Redactor::new).add_pattern(regex),
+                    Err(err) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-regex", pattern, err), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("json-array", _) => self.json_array = true,
+            ("group-by-file", _) => {
+                self.json_array = true;
+                self.group_by_file = true;
+            },
+            ("yaml", _) => self.structured = Some("yaml".to_string()),
+            ("toml", _) => self.structured = Some("toml".to_string()),
+            ("plain", _) => self.plain = true,
+            ("raw-passthrough", None) => self.plain = true,
+            ("raw-passthrough", Some("timed")) => {
+                self.plain = true;
+                self.raw_timed = true;
+            },
+            ("raw-passthrough", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-raw-mode", other), self.colors.reset);
+                return false;
+            },
+            ("xml", _) => self.structured = Some("xml".to_string()),
+            ("xml-indent", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) => self.xml_indent = n,
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("xml-collapse", Some(tags)) => self.xml_collapse.extend(tags.split(',').filter(|tag| !tag.is_empty()).map(str::to_string)),
+            ("fold-depth", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) => self.fold_depth = Some(n),
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("escape", Some(mode)) => {
+                if !["shell", "c", "json"].contains(&mode) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-escape-mode", mode), self.colors.reset);
+                    return false;
+                }
+                self.escape_mode = Some(mode.to_string());
+            },
+            ("shell-quote", None | Some("array")) => {
+                self.escape_mode = Some("shell".to_string());
+                self.shell_quote = true;
+                self.shell_quote_array = value.is_some();
+            },
+            ("shell-quote", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-shell-quote-mode", other), self.colors.reset);
+                return false;
+            },
+            ("null", _) => self.null_records = true,
+            ("edit", _) => self.edit = true,
+            ("edit-all", _) => {
+                self.edit = true;
+                self.edit_all = true;
+            },
+            ("edit-cmd", Some(template)) => {
+                self.edit = true;
+                self.edit_cmd = Some(template.to_string());
+            },
+            ("tail-bytes", Some(n)) => {
+                match n.parse::<u64>() {
+                    Ok(n) => self.tail_bytes = Some(n),
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-byte-count", n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("max-line-bytes", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.max_line_bytes = Some(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-byte-count", n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("serve", port) => {
+                self.serve = true;
+                if let Some(port) = port {
+                    match port.parse::<u16>() {
+                        Ok(port) => self.serve_port = port,
+                        Err(_) => {
+                            eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-port", port), self.colors.reset);
+                            return false;
+                        }
+                    }
+                }
+            },
+            ("serve-bind", Some(address)) => {
+                self.serve = true;
+                match address.parse::<IpAddr>() {
+                    Ok(address) => self.serve_bind = address,
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-bind-address", address), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("serve-raw", _) => {
+                self.serve = true;
+                self.serve_raw = true;
+            },
+            ("serve-once", _) => {
+                self.serve = true;
+                self.serve_once = true;
+            },
+            ("checksum", Some(algorithm)) => {
+                match hash::Algorithm::from_name(algorithm) {
+                    Some(algorithm) => self.checksum = Some(algorithm),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-checksum-algorithm", algorithm), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("index", _) => self.index = true,
+            ("index-format", Some(format)) => {
+                if !["tsv", "json"].contains(&format) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-index-format", format), self.colors.reset);
+                    return false;
+                }
+                self.index_format = format.to_string();
+            },
+            ("verify", _) => self.verify = true,
+            ("stat", _) => self.stat = true,
+            ("si", _) => self.si = true,
+            ("bytes-exact", _) => self.bytes_exact = true,
+            ("force", _) => self.force = true,
+            ("wait-for-file", _) => self.wait_for_file = true,
+            ("wait-timeout", Some(ms)) => {
+                match ms.parse::<u64>() {
+                    Ok(ms) => self.wait_timeout = Some(Duration::from_millis(ms)),
+                    Err(_) => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, ms), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("max-terminal-bytes", Some("0")) => self.max_terminal_bytes = None,
+            ("max-terminal-bytes", Some(size)) => {
+                match split::parse_size(size) {
+                    Some(size) => self.max_terminal_bytes = Some(size),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-byte-count", size), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("thousands-separator", Some(separator)) => self.thousands_separator = Some(separator.to_string()),
+            ("summary", _) => self.summary = true,
+            ("summary-only", _) => {
+                self.summary = true;
+                self.summary_only = true;
+            },
+            ("side-by-side", _) => self.side_by_side = true,
+            ("compare", _) => self.compare = true,
+            ("side-by-side-wrap", _) => self.side_by_side_wrap = true,
+            ("merge", _) => self.merge = true,
+            ("merge-delimiter", Some(delimiter)) => self.merge_delimiter = Some(delimiter.to_string()),
+            ("merge-shortest", _) => self.merge_shortest = true,
+            ("resume", None) => self.resume = true,
+            ("resume", Some("clear")) => self.resume_clear = true,
+            ("resume", Some(other)) => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-resume-mode", other), self.colors.reset);
+                return false;
+            },
+            ("tee" | "tee-plain", Some(path)) => self.tee_files.push((path.to_string(), name == "tee-plain")),
+            ("output", Some(path)) => self.output = Some(path.to_string()),
+            ("split-output", Some(n)) => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => self.split_output = split::Limit::Lines(n),
+                    _ => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-count", name, n), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("split-output-bytes", Some(size)) => {
+                match split::parse_size(size) {
+                    Some(size) => self.split_output = split::Limit::Bytes(size),
+                    None => {
+                        eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-byte-count", size), self.colors.reset);
+                        return false;
+                    }
+                }
+            },
+            ("in-place", _) => self.in_place = true,
+            ("backup", Some(suffix)) => {
+                if suffix.is_empty() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("empty-backup-suffix"), self.colors.reset);
+                    return false;
+                }
+                self.backup_suffix = Some(suffix.to_string());
+            },
+            ("preserve-times", _) => self.preserve_times = true,
+            ("blame", _) => self.blame = true,
+            ("changes", reference) => {
+                self.changes = true;
+                self.changes_ref = reference.map(|reference| reference.to_string());
+            },
+            ("banner", variant) => {
+                let variant = variant.unwrap_or("sitting");
+                if art::cat_art(variant).is_none() {
+                    let variants: Vec<&str> = art::CATS.iter().map(|(name, _)| *name).collect();
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-banner-variant", variant, variants.join(", ")), self.colors.reset);
+                    return false;
+                }
+                self.banner = Some(variant.to_string());
+            },
+            ("title", Some(text)) => self.title = Some(text.to_string()),
+            ("prepend" | "append", Some(text)) => {
+                let text = match text.strip_prefix('@') {
+                    Some(path) => match fs::read_to_string(path) {
+                        Ok(content) => content.strip_suffix('\n').unwrap_or(&content).to_string(),
+                        Err(err) => {
+                            eprintln!("{}meow: {}{}", self.colors.error, msg!("file-error", path, err), self.colors.reset);
+                            return false;
+                        }
+                    },
+                    None => text.to_string(),
+                };
+                if name == "prepend" {
+                    self.prepend = Some(text);
+                } else {
+                    self.append = Some(text);
+                }
+            },
+            ("force-art", _) => self.force_art = true,
+            ("qr", _) => self.qr = true,
+            ("qr-invert", _) => {
+                self.qr = true;
+                self.qr_invert = true;
+            },
+            ("detect-indent", _) => self.detect_indent = true,
+            ("no-preprocess", _) => self.no_preprocess = true,
+            ("list-preprocessors", _) => self.list_preprocessors = true,
+            ("no-profiles", _) => self.no_profiles = true,
+            // Expanded before parsing, see main
+            ("view", _) => {},
+            ("list-views", _) => self.list_views = true,
+            ("recursive", _) => self.recursive = true,
+            ("exclude", Some(pattern)) => self.exclude.push(pattern.to_string()),
+            ("include", Some(pattern)) => self.include.push(pattern.to_string()),
+            ("flatten", _) => {
+                self.flatten = true;
+                self.tree = false;
+            },
+            ("tree", _) => {
+                self.tree = true;
+                self.flatten = false;
+            },
+            ("list-dirs", _) => self.list_dirs = true,
+            ("list", _) => self.list_archives = true,
+            ("zip-entry", Some(entry)) => self.zip_entry = Some(entry.to_string()),
+            ("sort-dir", Some(key)) => {
+                if !["name", "size", "mtime"].contains(&key) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-sort-key", key), self.colors.reset);
+                    return false;
+                }
+                self.list_dirs = true;
+                self.dir_sort = key.to_string();
+            },
+            ("dirs-first", _) => {
+                self.list_dirs = true;
+                self.dirs_first = true;
+            },
+            ("no-special", _) => self.no_special = true,
+            ("safe", _) => self.safe = Some(true),
+            ("unsafe", _) => self.safe = Some(false),
+            ("preserve-ansi", _) => self.preserve_ansi = true,
+            ("lang", Some(code)) => {
+                if !i18n::set_language(code) {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("unsupported-language", code), self.colors.reset);
+                }
+            },
+            ("generate-man", _) => self.generate_man = true,
+            ("show-colors", _) => self.show_colors = true,
+            ("set-title", _) => self.set_title = true,
+            ("init", Some(shell)) => {
+                if init::snippet(shell).is_none() {
+                    eprintln!("{}meow: {}{}", self.colors.error, msg!("invalid-init-shell", shell), self.colors.reset);
+                    return false;
+                }
+                self.init = Some(shell.to_string());
+            },
+            _ => {
+                eprintln!("{}meow: {}{}", self.colors.error, msg!("unhandled-option", name), self.colors.reset);
+                return false;
+            }
+        }
+        
+        true
+    }
+}
+
+fn main() {
+    i18n::init_from_env();
+    let mut args: Vec<String> = env::args().collect();
+    
+    // Default options from the environment go before the real arguments
+    if let Ok(opts) = env::var("MEOW_OPTS") {
+        let defaults: Vec<String> = opts.split_whitespace().map(|opt| opt.to_string()).collect();
+        args.splice(1..1, defaults);
+    }
+    let mut config = Config::new();
+    
+    // Read first, since views expand into arguments before those are parsed
+    let config_file = settings::config_path().map(|path| (settings::load(&path), path));
+    let views = match &config_file {
+        Some((Ok(table), path)) => settings::views(table).unwrap_or_else(|err| {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("config-error", path.display(), err), config.colors.reset);
+            Vec::new()
+        }),
+        _ => Vec::new(),
+    };
+    match settings::expand_views(&args, &views) {
+        Ok(expanded) => args = expanded,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, view_error(&err, &views), config.colors.reset);
+            std::process::exit(1);
+        }
+    }
+    config.views = views;
+    
+    if !config.parse_args(&args) {
+        print_help(&config);
+        return;
+    }
+    
+    if config.generate_man {
+        print!("{}", generate_man());
+        return;
+    }
+    
+    // Also loaded without colors, so a broken theme is reported and can be exported
+    if config.theme_path.is_some() || config.export_theme.is_some() {
+        let mut colors = ColorConfig::new(true, config.color_depth);
+        if let Some(path) = &config.theme_path {
+            if let Err(err) = theme::load(Path::new(path), &mut colors, config.color_depth) {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("config-error", path, err), config.colors.reset);
+                std::process::exit(1);
+            }
+        }
+        if let Some(path) = &config.export_theme {
+            if let Err(err) = theme::export(Path::new(path), &colors) {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+                std::process::exit(1);
+            }
+            std::process::exit(0);
+        }
+        if config.use_colors {
+            config.colors = colors;
+        }
+    }
+    
+    if config.show_colors {
+        show_colors(&config);
+        return;
+    }
+    
+    if let Some(snippet) = config.init.as_deref().and_then(init::snippet) {
+        print!("{}", snippet);
+        return;
+    }
+    
+    if config.recursive {
+        expand_directories(&mut config);
+    }
+    
+    if let Some(path) = &config.annotations_path {
+        match annotations::load(path) {
+            Ok(annotations) => config.annotations = Some(annotations),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("config-error", path, err), config.colors.reset);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if let Some(name) = &config.rules_path {
+        let path = rules::resolve(name);
+        match rules::load(&path, config.use_colors, config.color_depth) {
+            Ok(rules) => config.rules = rules,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("config-error", path.display(), err), config.colors.reset);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if let Some(path) = &config.grep_file {
+        match load_grep_file(path, config.grep_file_comments) {
+            Ok(any) => config.grep_any = Some(any),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if let (Some(vars), Some(env_file)) = (config.env_vars.as_mut(), &config.env_file) {
+        // Like dotenv, the real environment wins over the file
+        match fs::read_to_string(env_file) {
+            Ok(text) => {
+                for (name, value) in parse_dotenv(&text) {
+                    vars.entry(name).or_insert(value);
+                }
+            },
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", env_file, err), config.colors.reset);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if let Some((table, path)) = &config_file {
+        let loaded = table.clone().and_then(|table| Ok((settings::preprocessors(&table)?, settings::profiles(&table)?)));
+        match loaded {
+            Ok((preprocessors, profiles)) => {
+                config.preprocessors = preprocessors;
+                config.profiles = profiles;
+            },
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("config-error", path.display(), err), config.colors.reset);
+            }
+        }
+        
+        // Check the profiles once, rather than complaining for every file
+        let profiles = if config.no_profiles { Vec::new() } else { std::mem::take(&mut config.profiles) };
+        config.profiles = profiles
+            .into_iter()
+            .filter(|profile| {
+                let valid = config.with_profile(profile).is_some();
+                if !valid {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("profile-invalid", path.display(), profile.pattern), config.colors.reset);
+                }
+                valid
+            })
+            .collect();
+    }
+    
+    if config.list_preprocessors {
+        list_preprocessors(&config);
+        return;
+    }
+    
+    if config.list_views {
+        list_views(&config);
+        return;
+    }
+    
+    if let Some(command) = &config.exec {
+        let ok = exec::run(command, &config);
+        terminal::restore_title();
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+    
+    if config.serve {
+        std::process::exit(if serve::serve(&config) { 0 } else { 1 });
+    }
+    
+    if config.verify {
+        std::process::exit(if verify_manifests(&config) { 0 } else { 1 });
+    }
+    
+    if config.stat {
+        std::process::exit(if print_stats(&config) { 0 } else { 1 });
+    }
+    
+    if config.pick {
+        std::process::exit(if pick::run(&config) { 0 } else { 1 });
+    }
+    
+    if let Some(path) = &config.replay {
+        std::process::exit(if record::replay(path, &config) { 0 } else { 1 });
+    }
+    
+    if let Some(pattern) = &config.list_matches {
+        std::process::exit(if list_matches(pattern, &config) { 0 } else { 1 });
+    }
+    
+    if config.brackets_check {
+        std::process::exit(if brackets::check(&config) { 0 } else { 1 });
+    }
+    
+    if config.side_by_side {
+        if config.files.len() != 2 {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("side-by-side-needs-two"), config.colors.reset);
+            std::process::exit(1);
+        }
+        std::process::exit(if columns::side_by_side(&config.files[0], &config.files[1], &config) { 0 } else { 1 });
+    }
+    
+    if let Some(header) = &config.fold_sections {
+        std::process::exit(if sections::run(header, &config) { 0 } else { 1 });
+    }
+    
+    // Exit codes as with cmp: 1 for different, 2 for trouble
+    if config.compare {
+        if config.files.len() != 2 {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("compare-needs-two"), config.colors.reset);
+            std::process::exit(2);
+        }
+        std::process::exit(match compare::run(&config.files[0], &config.files[1], &config) {
+            Some(true) => 0,
+            Some(false) => 1,
+            None => 2,
+        });
+    }
+    
+    if config.in_place {
+        std::process::exit(if rewrite_in_place(&config) { 0 } else { 1 });
+    }
+    
+    if config.resume_clear {
+        std::process::exit(if clear_resume_positions(&config) { 0 } else { 1 });
+    }
+    
+    if !open_tees(&config) {
+        std::process::exit(1);
+    }
+    open_output(&config);
+    
+    // Created up front so a bad path fails before the files are shown
+    let recorder = match &config.record {
+        Some(path) => match record::Recorder::create(path) {
+            Ok(recorder) => Some(recorder),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    
+    print_art(&config);
+    
+    let mut failed = false;
+    let mut line_num = 0;
+    let mut run = RunState::default();
+    
+    if config.merge {
+        failed |= !merge::merge_files(&config, &mut line_num, &mut run);
+    }
+    
+    let sources = if config.merge { Vec::new() } else { config.sources() };
+    
+    let mut summary = config.summary.then(summary::Summary::default);
+    for (index, source) in sources.iter().enumerate() {
+        let config = config.for_file(index, source.path());
+        if config.max_count_total && run.max_count_reached(&config) {
+            break;
+        }
+        if !config.continuous_numbering {
+            line_num = 0;
+        }
+        // A service started just before may not have created its log yet
+        if let Some(path) = source.path().filter(|_| config.wait_for_file) {
+            wait_for_file(path, &source.name(), &config);
+        }
+        let started = Instant::now();
+        // A file being written to gets a newer mtime, and --meta showed the old one
+        let watched = source.path()
+            .filter(|path| path.is_file())
+            .filter(|_| config.warn_older_than.is_some() || config.warn_newer_than.is_some() || config.show_meta || config.freshness.is_some())
+            .and_then(|path| Some((path, freshness::check_before(path, &source.name(), &config)?)));
+        let ok = show_source(source, &config, &mut line_num, &mut run);
+        if let Some((path, before)) = watched {
+            freshness::check_after(path, &source.name(), before, &config);
+        }
+        failed |= !ok;
+        // So inputs without lines get an empty group too
+        if config.group_by_file && ok {
+            run.json_group(&source.name());
+        }
+        let stats = std::mem::take(&mut run.stats);
+        if let Some(summary) = summary.as_mut() {
+            summary.add(&source.name(), source.path(), stats, !ok, started.elapsed());
+        }
+    }
+    
+    // The array is opened by the first line, see LineWriter::write_line
+    if config.group_by_file {
+        let groups = std::mem::take(&mut run.json_groups);
+        send_output(&config, |out| write_json_groups(out, groups));
+    } else if config.json_array {
+        let closing = if run.json_lines == 0 { "[]" } else { "\n]" };
+        send_output(&config, |out| writeln!(out, "{}", closing));
+    }
+    if config.shell_quote_array && run.quoted_words > 0 {
+        send_output(&config, |out| out.write_all(if config.null_records { b"\0" } else { b"\n" }));
+    }
+    
+    if let Some(summary) = &summary {
+        summary.print(&config);
+    }
+    
+    terminal::restore_title();
+    failed |= !finish_tees();
+    failed |= !finish_output(&config);
+    
+    // Interactive mode prompt after all files are processed
+    if config.interactive_mode {
+        interactive_shell(&config, recorder);
+    }
+    
+    if config.edit {
+        if let Some(code) = launch_editor(&config) {
+            std::process::exit(code);
+        }
+    }
+    
+    if failed || run.undefined_env {
+        std::process::exit(1);
+    }
+}
+
+/// `--wait-for-file`: polls until `path` exists or `--wait-timeout` runs out.
+/// Opening it afterwards reports a file that never came like any missing one.
+fn wait_for_file(path: &Path, name: &str, config: &Config) {
+    let started = Instant::now();
+    let mut announced = false;
+    while !path.exists() {
+        if config.wait_timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            return;
+        }
+        // Only worth saying to someone watching
+        if !announced && atty::is(atty::Stream::Stderr) {
+            eprintln!("{}meow: {}{}", config.colors.dim, msg!("waiting-for-file", name), config.colors.reset);
+            announced = true;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Shows one input (a file, or stdin) the way the options ask. Returns false if
+/// something went wrong with it.
+fn show_source(source: &InputSource, config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let file_path = &source.name();
+    
+    // Not even a header, so the output is byte for byte the input
+    if config.plain {
+        return copy_plain(source, file_path, config);
+    }
+    
+    if let Some(path) = source.path() {
+        if let Some(kind) = special_file_kind(path).filter(|_| config.no_special) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("refusing-special", file_path, msg!(kind)), config.colors.reset);
+            return false;
+        }
+    }
+    
+    // Held until the input is open, so it only speaks up while waiting
+    let waiting = source.path().filter(|_| !config.quiet).and_then(|path| WaitNotice::start(path, file_path));
+    
+    if let Some(path) = source.path() {
+        
+        if !config.no_preprocess && !config.preprocessors.is_empty() {
+            if let Some(rule) = settings::find_preprocessor(&config.preprocessors, path, &read_head(path)) {
+                return run_preprocessor(rule, file_path, path, config, line_num, run);
+            }
+        }
+        
+        if config.list_dirs && path.is_dir() {
+            if config.files.len() > 1 {
+                print_file_header(file_path, path, None, config);
+            }
+            return list_directory(path, file_path, config);
+        }
+        
+        if config.list_archives {
+            if config.files.len() > 1 {
+                print_file_header(file_path, path, None, config);
+            }
+            return list_archive(path, file_path, config);
+        }
+    }
+    
+    // Only regular files have a position worth remembering
+    if let Some(path) = source.path().filter(|path| config.resume && path.is_file()) {
+        return resume_file(path, file_path, config, line_num, run);
+    }
+    
+    let input = match source.open() {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+    };
+    drop(waiting);
+    
+    // Like sha256sum, unless --meta asks for the hash in the header
+    if let Some(algorithm) = config.checksum {
+        if !config.show_meta || source.path().is_none() {
+            let name = if source.path().is_some() { file_path } else { "-" };
+            return print_checksum(algorithm, input.reader, name, config);
+        }
+    }
+    
+    if config.index {
+        return print_index(input.reader, file_path, config);
+    }
+    
+    // Megabytes of text would keep the terminal busy for minutes
+    let size = input.metadata.as_ref().filter(|metadata| metadata.is_file()).map(|metadata| match config.tail_bytes {
+        Some(tail) => metadata.len().min(tail),
+        None => metadata.len(),
+    });
+    let paged;
+    let config = match size.filter(|_| terminal::too_large(size, config)) {
+        Some(size) => match terminal::confirm_large(file_path, size, config) {
+            terminal::LargeInput::Show => config,
+            terminal::LargeInput::Page => {
+                paged = Config { page_mode: true, ..config.clone() };
+                &paged
+            },
+            terminal::LargeInput::Skip => return true,
+        },
+        None => config,
+    };
+    
+    if config.files.len() > 1 || config.checksum.is_some() {
+        print_file_header(file_path, Path::new(file_path), None, config);
+    }
+    
+    // Regular files can be sampled without reading them whole
+    if let Some(n) = config.sample_spread {
+        match input.reader {
+            InputReader::File(file) if input.is_regular_file() => {
+                send_output(config, |out| sample_by_offset(&file, file.metadata()?.len(), n, config, out));
+            },
+            reader => send_output(config, |out| sample_by_reservoir(reader, n, config, out)),
+        }
+        return true;
+    }
+    
+    // Skipping to --since loses the positions in the file, and what's judged over the whole input
+    let seek_since = config.since.filter(|_| {
+        !(config.json_array || config.blame || config.changes || config.find_dupes || config.detect_indent
+            || config.sample_every.is_some() || config.sample_probability.is_some() || config.preview.is_some())
+    });
+    let tail: io::Result<Box<dyn Read + Send>> = match (config.tail_bytes, input.reader) {
+        (Some(n), InputReader::File(file)) => tail_bytes(file, n),
+        (Some(n), reader) => tail_of_stream(reader, n).map(|tail| Box::new(tail) as Box<dyn Read + Send>),
+        (None, InputReader::File(file)) if seek_since.is_some() => {
+            logtime::seek_to(file, seek_since.unwrap_or_default(), config.time_format.as_deref())
+                .map(|reader| Box::new(reader) as Box<dyn Read + Send>)
+        },
+        (None, reader) => Ok(Box::new(reader)),
+    };
+    let input = match tail {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+    };
+    
+    // --more and --set-title show how far into the file it is, when reading all of it
+    let input: Box<dyn Read + Send> = if config.more || config.set_title {
+        let size = source.path()
+            .filter(|_| config.tail_bytes.is_none() && seek_since.is_none())
+            .and_then(|path| fs::metadata(path).ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len());
+        Box::new(more::track(input, size))
+    } else {
+        input
+    };
+    
+    if config.qr {
+        return show_qr(input, file_path, config);
+    }
+    
+    if let Some(filter) = &config.filter {
+        return run_filter(input, file_path, source.path(), filter, config, line_num, run);
+    }
+    
+    // Explicit --yaml/--toml, or the extension when the output is colored
+    let structured = config.structured.as_deref().or_else(|| {
+        source.path().filter(|_| config.use_colors && !config.plain).and_then(structured::format_for)
+    });
+    if let Some(format) = structured {
+        return show_structured(input, format, file_path, config, line_num, run);
+    }
+    
+    let mut reader = BufReader::new(input);
+    
+    // Images get a preview (or a notice) instead of raw bytes
+    if let Some(kind) = reader.fill_buf().ok().and_then(sniff_image) {
+        show_image(&mut reader, kind, file_path, config);
+        return true;
+    }
+    
+    display_input(&mut reader, config, file_path, line_num, run);
+    true
+}
+
+/// A line number for the gutter, in hexadecimal with `--number-hex`.
+fn format_line_number(number: usize, config: &Config) -> String {
+    match config.number_hex {
+        Some(prefix) => format!("{}{:x}", prefix, number),
+        None => number.to_string(),
+    }
+}
+
+/// Byte range of a `--expand-env` reference in the expanded line, and whether
+/// the variable was defined.
+type EnvSpan = (usize, usize, bool);
+
+/// What carries over from one input to the next in a run of meow. `main` owns
+/// the one for the inputs it shows; one-off uses of `process_input`, like a
+/// served page, start a fresh one.
+#[derive(Default)]
+struct RunState {
+    /// Set once a grep context group was written, so the next file's first
+    /// group gets a separator too
+    groups_written: bool,
+    /// Matching lines shown so far for `--max-count`: in this file, or in all
+    /// of them with `--max-count-total`
+    matches_shown: usize,
+    /// Lines written so far with `--json-array`, to place the commas and brackets
+    json_lines: usize,
+    /// `--group-by-file`: the JSON line objects of each input so far, in order.
+    /// They're only written once all inputs are read, see `write_json_groups`.
+    json_groups: Vec<(String, Vec<String>)>,
+    /// Lines written so far with `--shell-quote=array`, to place the spaces
+    quoted_words: usize,
+    /// Set when `--expand-env=strict` ran into an undefined variable
+    undefined_env: bool,
+    /// What `process_input` made of the input being shown. An input can go
+    /// through it more than once (a structured file and its error, say), so
+    /// they add up here until the `--summary` row takes them.
+    stats: summary::Stats,
+}
+
+impl RunState {
+    /// Whether `--max-count` matching lines were shown, so reading can stop.
+    fn max_count_reached(&self, config: &Config) -> bool {
+        config.searching() && config.max_count.is_some_and(|max| self.matches_shown >= max)
+    }
+    
+    /// The lines of `file_name`'s group, started unless it's the last one already.
+    fn json_group(&mut self, file_name: &str) -> &mut Vec<String> {
+        if self.json_groups.last().is_none_or(|(name, _)| name != file_name) {
+            self.json_groups.push((file_name.to_string(), Vec::new()));
+        }
+        &mut self.json_groups.last_mut().expect("a group was just added").1
+    }
+}
+
+/// `--group-by-file`: one object with a `{"lines": [...]}` member per input, in
+/// the order they were given (`{}` for none). An input given twice gets its
+/// lines added to the first one, as JSON keys should be unique.
+fn write_json_groups(out: &mut dyn Write, groups: Vec<(String, Vec<String>)>) -> io::Result<()> {
+    let mut merged: Vec<(String, Vec<String>)> = Vec::new();
+    for (name, lines) in groups {
+        match merged.iter_mut().find(|(seen, _)| *seen == name) {
+            Some((_, seen_lines)) => seen_lines.extend(lines),
+            None => merged.push((name, lines)),
+        }
+    }
+    if merged.is_empty() {
+        return writeln!(out, "{{}}");
+    }
+    write!(out, "{{")?;
+    for (i, (name, lines)) in merged.iter().enumerate() {
+        write!(out, "{}\n  {}: {{\"lines\": [", if i == 0 { "" } else { "," }, escape_line(name, "json"))?;
+        for (j, line) in lines.iter().enumerate() {
+            write!(out, "{}\n    {}", if j == 0 { "" } else { "," }, line)?;
+        }
+        write!(out, "{}]}}", if lines.is_empty() { "" } else { "\n  " })?;
+    }
+    writeln!(out, "\n}}")
+}
+
+/// Parses `NAME=value` lines of a dotenv file. `export` prefixes, comments and
+/// matching quotes around the value are dropped.
+fn parse_dotenv(text: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if let Some((name, value)) = line.split_once('=') {
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|&quote| value.strip_prefix(quote).and_then(|value| value.strip_suffix(quote)))
+                .unwrap_or(value);
+            vars.push((name.trim().to_string(), value.to_string()));
+        }
+    }
+    vars
+}
+
+/// Substitutes `$NAME` and `${NAME}` from `vars`. `\$` and undefined names are
+/// left as they are. Returns the new line and the spans of the references,
+/// flagged with whether they were defined.
+fn expand_env(line: &str, vars: &HashMap<String, String>) -> (String, Vec<EnvSpan>) {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::with_capacity(line.len());
+    let mut spans = Vec::new();
+    let mut rest = line;
+    
+    while let Some(pos) = rest.find('$') {
+        let escaped = rest[..pos].ends_with('\\');
+        expanded.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        
+        let (name, reference_len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) if end > 0 && braced[..end].chars().all(is_name_char) => (&braced[..end], end + 3),
+                _ => ("", 0),
+            }
+        } else {
+            let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+            (&after[..end], end + 1)
+        };
+        
+        if escaped || name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        
+        let start = expanded.len();
+        match vars.get(name) {
+            Some(value) => {
+                expanded.push_str(value);
+                spans.push((start, expanded.len(), true));
+            },
+            None => {
+                expanded.push_str(&rest[pos..pos + reference_len]);
+                spans.push((start, expanded.len(), false));
+            }
+        }
+        rest = &rest[pos + reference_len..];
+    }
+    expanded.push_str(rest);
+    
+    (expanded, spans)
+}
+
+/// `--grep-file`: one regex for all the patterns in `path`, taken literally
+/// and matched like `--grep`, so the regex engine can look for them all in one
+/// pass. Blank lines don't count, and neither do `#` lines with `skip_comments`.
+fn load_grep_file(path: &str, skip_comments: bool) -> Result<Regex, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let patterns: Vec<String> = text.lines()
+        .filter(|line| !(line.is_empty() || skip_comments && line.starts_with('#')))
+        .map(regex::escape)
+        .collect();
+    // A class with nothing in it: no patterns select no lines, like grep -f /dev/null
+    let any = if patterns.is_empty() { r"[^\s\S]".to_string() } else { patterns.join("|") };
+    regex::RegexBuilder::new(&any)
+        .size_limit(256 << 20)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// Opens the first grep match (or line 1) in the user's editor. Returns the exit
+/// code to propagate when the editor failed, `None` when it succeeded or was skipped.
+fn launch_editor(config: &Config) -> Option<i32> {
+    if !atty::is(atty::Stream::Stdout) {
+        return None;
+    }
+    
+    let mut targets: Vec<(&str, usize)> = Vec::new();
+    for file_path in &config.files {
+        let line = match &config.grep_pattern {
+            Some(pattern) => match first_match_line(Path::new(file_path), pattern) {
+                Some(line) => line,
+                None => continue,
+            },
+            None => 1,
+        };
+        targets.push((file_path, line));
+        if !config.edit_all {
+            break;
+        }
+    }
+    if targets.is_empty() {
+        if !config.quiet {
+            eprintln!("meow: {}", msg!("nothing-to-edit"));
+        }
+        return None;
+    }
+    
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok().filter(|editor| !editor.is_empty());
+    
+    // Named templates use $VISUAL/$EDITOR; anything else is a full command template
+    let template = match config.edit_cmd.as_deref() {
+        Some("vim") | Some("emacs") => "+{line} {file}".to_string(),
+        Some("code") => "--goto {file}:{line}".to_string(),
+        Some(custom) => custom.to_string(),
+        None if editor.as_deref().is_some_and(|editor| editor.ends_with("code")) => "--goto {file}:{line}".to_string(),
+        None => "+{line} {file}".to_string(),
+    };
+    let is_named = matches!(config.edit_cmd.as_deref(), None | Some("vim") | Some("emacs") | Some("code"));
+    
+    let mut words: Vec<&str> = template.split_whitespace().collect();
+    let program = if is_named {
+        match &editor {
+            // $EDITOR may carry its own flags, e.g. "code --wait"
+            Some(editor) => editor.clone(),
+            None => {
+                if !config.quiet {
+                    eprintln!("meow: {}", msg!("no-editor"));
+                }
+                return None;
+            }
+        }
+    } else if words.is_empty() {
+        return None;
+    } else {
+        words.remove(0).to_string()
+    };
+    
+    let mut program_words = program.split_whitespace();
+    let mut command = Command::new(program_words.next()?);
+    command.args(program_words);
+    
+    // Words mentioning {file} or {line} are repeated for every target
+    let (per_file, fixed): (Vec<&str>, Vec<&str>) = words.into_iter().partition(|word| word.contains("{file}") || word.contains("{line}"));
+    command.args(fixed);
+    for (file, line) in &targets {
+        command.args(per_file.iter().map(|word| word.replace("{file}", file).replace("{line}", &line.to_string())));
+    }
+    
+    match command.status() {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(status.code().unwrap_or(1)),
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-launch-editor", program, err), config.colors.reset);
+            Some(1)
+        }
+    }
+}
+
+fn first_match_line(path: &Path, pattern: &str) -> Option<usize> {
+    let reader = BufReader::new(File::open(path).ok()?);
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .position(|line| line.contains(pattern))
+        .map(|index| index + 1)
+}
+
+/// Positions a file at the first full line within its last `n` bytes. Regular
+/// files are seeked directly; pipes and devices fall back to buffering the stream.
+fn tail_bytes(file: File, n: u64) -> io::Result<Box<dyn Read + Send>> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Ok(Box::new(tail_of_stream(file, n)?));
+    }
+    
+    let len = metadata.len();
+    let mut reader = BufReader::new(file);
+    if len > n {
+        // Start one byte early so a tail that begins exactly on a line start is kept
+        reader.seek(SeekFrom::Start(len - n - 1))?;
+        reader.read_until(b'\n', &mut Vec::new())?;
+    }
+    Ok(Box::new(reader))
+}
+
+/// Keeps the last `n` bytes of a stream, dropping the partial line at the front.
+fn tail_of_stream<R: Read>(mut input: R, n: u64) -> io::Result<Cursor<Vec<u8>>> {
+    let limit = n as usize;
+    let mut tail: VecDeque<u8> = VecDeque::new();
+    let mut dropped = false;
+    let mut chunk = [0u8; 8192];
+    
+    loop {
+        let read = match input.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        
+        tail.extend(&chunk[..read]);
+        if tail.len() > limit {
+            // Remember the byte just before the window to know if it ended a line
+            let excess = tail.len() - limit;
+            dropped = tail[excess - 1] != b'\n';
+            tail.drain(..excess);
+        }
+    }
+    
+    let mut bytes: Vec<u8> = tail.into();
+    if dropped {
+        let start = bytes.iter().position(|&b| b == b'\n').map_or(bytes.len(), |pos| pos + 1);
+        bytes.drain(..start);
+    }
+    Ok(Cursor::new(bytes))
+}
+
+/// Prints `HASH  NAME` in the format of the `*sum` tools.
+fn print_checksum<R: Read>(algorithm: hash::Algorithm, mut input: R, name: &str, config: &Config) -> bool {
+    match hash::hash_reader(algorithm, &mut input) {
+        Ok(digest) => {
+            print_line(&format!("{}  {}", digest, name));
+            true
+        },
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset);
+            false
+        }
+    }
+}
+
+/// `--plain`: copies an input to stdout untouched, like cat without options.
+/// With `--raw-passthrough=timed`, asciinema recordings are played at their
+/// own pace instead.
+fn copy_plain(source: &InputSource, file_path: &str, config: &Config) -> bool {
+    let mut input = match source.open() {
+        Ok(input) => BufReader::with_capacity(64 * 1024, input.reader),
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+    };
+    if config.raw_timed {
+        if let Some(version) = input.fill_buf().ok().and_then(cast::version) {
+            return cast::play(input, version, file_path, config);
+        }
+    }
+    let mut out = io::stdout().lock();
+    loop {
+        let buffer = match input.fill_buf() {
+            Ok([]) => return true,
+            Ok(buffer) => buffer,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+                return false;
+            }
+        };
+        if let Err(err) = out.write_all(buffer) {
+            report_write_error(Err(err), config);
+            return true;
+        }
+        let read = buffer.len();
+        input.consume(read);
+    }
+}
+
+/// `--index`: a record per line, or per `--grep` match, with its number, the byte
+/// offset it starts at and its length in bytes without the line ending (`\n` or
+/// `\r\n`), so other tools can seek straight to it. With several files TSV
+/// records start with the file name; JSON records always have it.
+fn print_index<R: Read>(input: R, name: &str, config: &Config) -> bool {
+    let mut reader = BufReader::new(input);
+    let mut ok = true;
+    send_output(config, |out| {
+        let mut line = Vec::new();
+        let mut offset = 0;
+        let mut number = 0;
+        loop {
+            line.clear();
+            let read = match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(read) => read as u64,
+                Err(err) => {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset);
+                    ok = false;
+                    break;
+                }
+            };
+            let start = offset;
+            offset += read;
+            number += 1;
+            
+            let text = match line.strip_suffix(b"\n") {
+                Some(text) => text.strip_suffix(b"\r").unwrap_or(text),
+                None => &line,
+            };
+            if config.searching() && !config.grep_selects(&String::from_utf8_lossy(text)) {
+                continue;
+            }
+            if config.index_format == "json" {
+                writeln!(out, "{{\"file\": {}, \"line\": {}, \"offset\": {}, \"length\": {}}}", escape_line(name, "json"), number, start, text.len())?;
+            } else if config.files.len() > 1 {
+                writeln!(out, "{}\t{}\t{}\t{}", name, number, start, text.len())?;
+            } else {
+                writeln!(out, "{}\t{}\t{}", number, start, text.len())?;
+            }
+        }
+        Ok(())
+    });
+    ok
+}
+
+/// Longest line `--sample-spread` shows from a seekable file; the rest is skipped
+const SPREAD_LINE_BYTES: u64 = 64 * 1024;
+
+/// `--sample-spread` on a regular file: jumps to `n` evenly spaced byte offsets,
+/// skips to the start of the next line and shows that line, so only the sampled
+/// lines are read. Lines are prefixed with their byte offset, since the line
+/// number isn't known without reading everything before it. `size` is the
+/// length of the input.
+fn sample_by_offset<R: Read + Seek>(input: R, size: u64, n: usize, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let mut reader = BufReader::new(input);
+    let mut line = Vec::new();
+    // Start of the line after the last one shown
+    let mut next_start = 0;
+    
+    for k in 0..n {
+        let target = (size as u128 * k as u128 / n as u128) as u64;
+        let mut start = target.max(next_start);
+        if start >= size {
+            break;
+        }
+        
+        // Unless we're right after the previous sample, resync to the next line start
+        if start > next_start {
+            reader.seek(SeekFrom::Start(start - 1))?;
+            start = start - 1 + skip_line(&mut reader)?;
+            if start >= size {
+                break;
+            }
+            writeln!(out, "{}\u{00B7}\u{00B7}\u{00B7}{}", config.colors.dim, config.colors.reset)?;
+        } else {
+            reader.seek(SeekFrom::Start(start))?;
+        }
+        
+        line.clear();
+        let mut read = (&mut reader).take(SPREAD_LINE_BYTES).read_until(b'\n', &mut line)? as u64;
+        if line.last() != Some(&b'\n') {
+            read += skip_line(&mut reader)?;
+        }
+        next_start = start + read;
+        
+        let text = String::from_utf8_lossy(&line);
+        writeln!(out, "{}{:>10}{} | {}", config.colors.number, start, config.colors.reset, text.trim_end_matches(['\n', '\r']))?;
+    }
+    
+    Ok(())
+}
+
+/// Consumes input up to and including the next newline; returns the bytes skipped.
+fn skip_line<R: BufRead>(reader: &mut R) -> io::Result<u64> {
+    let mut skipped = 0;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(skipped);
+        }
+        match buffer.iter().position(|&b| b == b'\n') {
+            Some(pos) => {
+                reader.consume(pos + 1);
+                return Ok(skipped + pos as u64 + 1);
+            },
+            None => {
+                let len = buffer.len();
+                reader.consume(len);
+                skipped += len as u64;
+            }
+        }
+    }
+}
+
+/// `--sample-spread` on a stream: reservoir sampling keeps `n` uniformly chosen
+/// lines in memory, which are shown in their original order with line numbers.
+fn sample_by_reservoir<R: Read>(input: R, n: usize, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let mut rng = config.sample_seed.map_or_else(XorShift::from_time, XorShift::from_seed);
+    let mut reservoir: Vec<(usize, String)> = Vec::with_capacity(n);
+    
+    let mut lines = CappedLines::new(BufReader::new(input), config.max_line_bytes);
+    if let Some(separator) = &config.record_separator {
+        lines.separator = separator.clone();
+    }
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if reservoir.len() < n {
+            reservoir.push((i + 1, line));
+        } else {
+            let slot = (rng.next_u64() % (i as u64 + 1)) as usize;
+            if slot < n {
+                reservoir[slot] = (i + 1, line);
+            }
+        }
+    }
+    
+    reservoir.sort_by_key(|(number, _)| *number);
+    let mut prev = 0;
+    for (number, line) in reservoir {
+        if number > prev + 1 {
+            writeln!(out, "{}\u{00B7}\u{00B7}\u{00B7}{}", config.colors.dim, config.colors.reset)?;
+        }
+        prev = number;
+        writeln!(out, "{}{:>6}{} | {}", config.colors.number, format_line_number(number, config), config.colors.reset, line)?;
+    }
+    
+    Ok(())
+}
+
+/// Checks the files listed in checksum manifests (stdin if none are given), like
+/// `sha256sum -c`. Listed paths are relative to the manifest's directory. Returns
+/// false if a file failed, is missing, or a manifest had no usable lines.
+fn verify_manifests(config: &Config) -> bool {
+    let manifests: Vec<&str> = if config.files.is_empty() {
+        vec!["-"]
+    } else {
+        config.files.iter().map(String::as_str).collect()
+    };
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    let mut ok = true;
+    
+    for manifest in manifests {
+        let (text, base) = if manifest == "-" {
+            let mut text = String::new();
+            (io::stdin().read_to_string(&mut text).map(|_| text), PathBuf::from("."))
+        } else {
+            let base = Path::new(manifest).parent().map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+            (fs::read_to_string(manifest), base)
+        };
+        let text = match text {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", manifest, err), config.colors.reset);
+                ok = false;
+                continue;
+            }
+        };
+        
+        let mut entries = 0;
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = match hash::parse_manifest_line(line) {
+                Some(entry) => entry,
+                None => {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("malformed-checksum-line", manifest, number + 1), config.colors.reset);
+                    continue;
+                }
+            };
+            entries += 1;
+            
+            let result = File::open(base.join(&entry.file))
+                .and_then(|mut file| hash::hash_reader(entry.algorithm, &mut file));
+            let (color, status) = match result {
+                Ok(digest) if digest == entry.digest => {
+                    passed += 1;
+                    if config.quiet {
+                        continue;
+                    }
+                    (&config.colors.success, msg!("verify-ok"))
+                },
+                Ok(_) => {
+                    failed += 1;
+                    (&config.colors.error, msg!("verify-failed"))
+                },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    missing += 1;
+                    (&config.colors.error, msg!("verify-missing"))
+                },
+                Err(err) => {
+                    failed += 1;
+                    (&config.colors.error, format!("{} ({})", msg!("verify-failed"), err))
+                }
+            };
+            println!("{}: {}{}{}", entry.file, color, status, config.colors.reset);
+        }
+        
+        if entries == 0 {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("no-checksum-lines", manifest), config.colors.reset);
+            ok = false;
+        }
+    }
+    
+    if !config.quiet || failed + missing > 0 {
+        println!("{}", msg!("verify-summary", passed, failed, missing));
+    }
+    ok && failed + missing == 0
+}
+
+/// `--in-place`: rewrites every file with its processed output. Each file goes
+/// to a temporary file next to it, which is synced, given the original's
+/// permissions and renamed over it, so a failure leaves the original untouched.
+fn rewrite_in_place(config: &Config) -> bool {
+    if config.files.is_empty() {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("in-place-stdin"), config.colors.reset);
+        return false;
+    }
+    if config.filter.is_some() {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("in-place-filter"), config.colors.reset);
+        return false;
+    }
+    if !config.transforms_content() {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("in-place-no-transform"), config.colors.reset);
+        return false;
+    }
+    
+    // The file gets exactly what would be printed, minus the terminal dressing
+    let mut plain = config.clone();
+    plain.use_colors = false;
+    plain.colors = ColorConfig::new(false, plain.color_depth);
+    plain.page_mode = false;
+    plain.animate = false;
+    
+    let mut ok = true;
+    for file_path in &config.files {
+        match rewrite_file(Path::new(file_path), file_path, &plain) {
+            Ok(()) => {
+                if !config.quiet {
+                    println!("{}: {}{}{}", file_path, config.colors.success, msg!("rewritten"), config.colors.reset);
+                }
+            },
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+fn rewrite_file(path: &Path, file_path: &str, config: &Config) -> io::Result<()> {
+    // Write next to the real file, so a symlink keeps pointing at the new contents
+    let target = fs::canonicalize(path)?;
+    let metadata = fs::metadata(&target)?;
+    if !metadata.is_file() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg!("not-a-regular-file")));
+    }
+    
+    let dir = target.parent().unwrap_or(Path::new("."));
+    let name = target.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp_path = dir.join(format!(".{}.meow-{}.tmp", name, std::process::id()));
+    
+    let result = (|| {
+        let mut temp = fs::OpenOptions::new().write(true).create_new(true).open(&temp_path)?;
+        let mut input = BufReader::new(File::open(&target)?);
+        let mut out = io::BufWriter::new(&mut temp);
+        // A read error ends the input early, and the file would lose the rest
+        if process_input(&mut input, config, file_path, &mut 0, &mut RunState::default(), &mut out)?.errors > 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg!("in-place-incomplete")));
+        }
+        out.flush()?;
+        drop(out);
+        
+        temp.set_permissions(metadata.permissions())?;
+        if config.preserve_times {
+            temp.set_modified(metadata.modified()?)?;
+        }
+        temp.sync_all()?;
+        
+        if let Some(suffix) = &config.backup_suffix {
+            let mut backup = target.clone().into_os_string();
+            backup.push(suffix);
+            fs::copy(&target, backup)?;
+        }
+        fs::rename(&temp_path, &target)
+    })();
+    
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn print_file_header(file_path: &str, path: &Path, via: Option<&str>, config: &Config) {
+    let via = via.map(|program| format!(" ({})", program)).unwrap_or_default();
+    if config.flatten || config.json_array || config.summary_only {
+        return;
+    }
+    if config.tree {
+        let default = vec![file_path.to_string()];
+        let lines = config.tree_headers.get(file_path).unwrap_or(&default);
+        for (i, line) in lines.iter().enumerate() {
+            let last = i + 1 == lines.len();
+            print_line(&format!("{}{}{}{}", if last { &config.colors.filename } else { &config.colors.highlight }, line, config.colors.reset, if last { via.as_str() } else { "" }));
+        }
+        return;
+    }
+    
+    // --meta also says which profile was applied
+    let profile = config.profile.as_ref()
+        .filter(|_| config.show_meta)
+        .map(|pattern| format!(" [{}]", msg!("meta-profile", pattern)))
+        .unwrap_or_default();
+    print_line(&format!("\n===> {}{}{}{}{}{} <===",
+            config.colors.filename,
+            file_path,
+            config.colors.reset,
+            via,
+            if config.show_meta { get_file_meta(path, config) } else { freshness_stamp(path, config) },
+            profile));
+}
+
+/// `--freshness` without `--meta`: just the modification time, in its color.
+fn freshness_stamp(path: &Path, config: &Config) -> String {
+    match file_meta(path, config) {
+        Ok(meta) if config.freshness.is_some() => format!(" [{}]", freshness::paint(&meta.modified, meta.time, config)),
+        _ => String::new(),
+    }
+}
+
+/// `-R`: replaces directory arguments with the files below them, in name order.
+/// Symlinked directories are not followed, and `--exclude`/`--include` filter
+/// what is found below the arguments. Also records the `--tree` header lines:
+/// each file carries the lines of the directories entered just before it.
+fn expand_directories(config: &mut Config) {
+    let mut files = Vec::new();
+    let mut headers = HashMap::new();
+    
+    for arg in &config.files {
+        let path = Path::new(arg);
+        if !path.is_dir() {
+            files.push(arg.clone());
+            continue;
+        }
+        let mut pending = vec![format!("{}/", arg.trim_end_matches('/'))];
+        walk_directory(path, "", &mut pending, &mut files, &mut headers, config);
+    }
+    
+    config.files = files;
+    config.tree_headers = headers;
+}
+
+fn walk_directory(dir: &Path, prefix: &str, pending: &mut Vec<String>, files: &mut Vec<String>,
+                  headers: &mut HashMap<String, Vec<String>>, config: &Config) {
+    let mut entries: Vec<fs::DirEntry> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", dir.display(), err), config.colors.reset);
+            return;
+        }
+    };
+    // Filtered before drawing, so the tree's last entry really is the last
+    entries.retain(|entry| {
+        let path = entry.path();
+        // Links to directories aren't followed, and aren't files to show either
+        if entry.file_type().is_ok_and(|file_type| file_type.is_symlink()) && path.is_dir() {
+            return false;
+        }
+        if config.exclude.iter().any(|pattern| settings::matches_glob(pattern, &path)) {
+            return false;
+        }
+        config.include.is_empty()
+            || entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+            || config.include.iter().any(|pattern| settings::matches_glob(pattern, &path))
+    });
+    entries.sort_by_key(|entry| entry.file_name());
+    
+    for (i, entry) in entries.iter().enumerate() {
+        let last = i + 1 == entries.len();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let connector = if last { "\u{2514}\u{2500}\u{2500} " } else { "\u{251C}\u{2500}\u{2500} " };
+        let path = entry.path();
+        
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            pending.push(format!("{}{}{}/", prefix, connector, name));
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "\u{2502}   " });
+            walk_directory(&path, &child_prefix, pending, files, headers, config);
+        } else {
+            let file = path.to_string_lossy().into_owned();
+            pending.push(format!("{}{}{}", prefix, connector, name));
+            headers.insert(file.clone(), std::mem::take(pending));
+            files.push(file);
+        }
+    }
+}
+
+/// First bytes of a regular file, enough to recognize its type. Nothing for
+/// anything else: opening a FIFO blocks, and what is read from it is gone.
+fn read_head(path: &Path) -> Vec<u8> {
+    let mut head = Vec::new();
+    if !path.is_file() {
+        return head;
+    }
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(16).read_to_end(&mut head);
+    }
+    head
+}
+
+/// Shows a file as the output of its preprocessor, with a header naming the
+/// program. If the command can't run or fails, the file is reported as binary
+/// and false is returned.
+fn run_preprocessor(rule: &Preprocessor, file_path: &str, path: &Path, config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let path_str = path.to_string_lossy();
+    let mut command = if rule.shell {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(rule.command.replace("{}", &shell_quote(&path_str)));
+        command
+    } else {
+        // No shell: the path is substituted into a single argument, so spaces and
+        // quotes in file names can't change the command
+        let mut words = rule.command.split_whitespace().map(|word| word.replace("{}", &path_str));
+        let mut command = Command::new(words.next().unwrap_or_default());
+        command.args(words);
+        command
+    };
+    let program = rule.command.split_whitespace().next().unwrap_or_default();
+    
+    match command.stdin(Stdio::null()).output() {
+        Ok(output) if output.status.success() => {
+            print_file_header(file_path, path, Some(program), config);
+            display_input(&mut BufReader::new(Cursor::new(output.stdout)), config, file_path, line_num, run);
+            true
+        },
+        _ => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("preprocessor-failed", file_path, program), config.colors.reset);
+            false
+        }
+    }
+}
+
+fn list_preprocessors(config: &Config) {
+    if config.preprocessors.is_empty() {
+        let path = settings::config_path().map(|path| path.display().to_string()).unwrap_or_default();
+        println!("{}", msg!("no-preprocessors", path));
+        return;
+    }
+    
+    for rule in &config.preprocessors {
+        println!("{}{:<24}{} {}{}",
+                config.colors.highlight,
+                rule.pattern,
+                config.colors.reset,
+                rule.command,
+                if rule.shell { " (shell)" } else { "" });
+    }
+}
+
+/// `--list-views`: each view from the config file with its arguments.
+fn list_views(config: &Config) {
+    if config.views.is_empty() {
+        let path = settings::config_path().map(|path| path.display().to_string()).unwrap_or_default();
+        println!("{}", msg!("no-views", path));
+        return;
+    }
+    
+    for view in &config.views {
+        println!("{}{:<16}{} {}", config.colors.highlight, view.name, config.colors.reset, view.arguments.join(" "));
+    }
+}
+
+fn view_error(err: &ViewError, views: &[View]) -> String {
+    match err {
+        ViewError::Unknown(name) if views.is_empty() => msg!("unknown-view-none", name),
+        ViewError::Unknown(name) => {
+            let names: Vec<&str> = views.iter().map(|view| view.name.as_str()).collect();
+            msg!("unknown-view", name, names.join(", "))
+        },
+        ViewError::Recursive(name) => msg!("recursive-view", name),
+    }
+}
+
+/// Runs `write` against the pager (buffered, then paged) or straight against stdout.
+fn send_output(config: &Config, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) {
+    if config.summary_only {
+        // Still processed, for the counts
+        let _ = write(&mut io::sink());
+    } else if config.output.is_some() {
+        // Write errors are kept with the files and reported by finish_output
+        let _ = write(&mut OutputWriter);
+    } else if config.page_mode {
+        let mut buffer = Vec::new();
+        let result = write(&mut buffer);
+        write_tees(&buffer);
+        report_write_error(result, config);
+        page_content(&String::from_utf8_lossy(&buffer), config);
+    } else if config.tee_files.is_empty() {
+        let result = if config.more && more::available() {
+            write(&mut more::Pager::new(io::stdout(), config))
+        } else {
+            write(&mut io::stdout())
+        };
+        report_write_error(result, config);
+    } else {
+        let mut out = TeeWriter { stdout: io::stdout(), error: None };
+        let result = if config.more && more::available() {
+            write(&mut more::Pager::new(&mut out, config))
+        } else {
+            write(&mut out)
+        };
+        let result = result.and_then(|()| out.error.map_or(Ok(()), Err));
+        match result {
+            // Unlike plain output, a closed stdout doesn't end the run: the tees still want the rest
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {},
+            result => report_write_error(result, config),
+        }
+    }
+}
+
+/// A `--tee` or `--tee-plain` destination. Opened once in `main`, then fed
+/// everything that reaches stdout.
+struct TeeSink {
+    path: String,
+    file: BufWriter<File>,
+    /// `--tee-plain`: drop escape sequences
+    plain: bool,
+    escape: EscapeState,
+}
+
+static TEES: Mutex<Vec<TeeSink>> = Mutex::new(Vec::new());
+
+/// Set when a tee file couldn't be written, for the exit status.
+static TEE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Creates the tee files. Returns false (after reporting) if one can't be created.
+fn open_tees(config: &Config) -> bool {
+    let mut tees = TEES.lock().unwrap_or_else(PoisonError::into_inner);
+    for (path, plain) in &config.tee_files {
+        match File::create(path) {
+            Ok(file) => tees.push(TeeSink {
+                path: path.clone(),
+                file: BufWriter::new(file),
+                plain: *plain,
+                escape: EscapeState::Text,
+            }),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Copies output to the tee files. A tee that fails is reported and dropped;
+/// stdout and the other tees carry on.
+fn write_tees(bytes: &[u8]) {
+    let mut tees = TEES.lock().unwrap_or_else(PoisonError::into_inner);
+    tees.retain_mut(|tee| {
+        let result = if tee.plain {
+            let plain = strip_escapes(bytes, &mut tee.escape);
+            tee.file.write_all(&plain)
+        } else {
+            tee.file.write_all(bytes)
+        };
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("meow: {}", msg!("tee-error", tee.path, err));
+                TEE_FAILED.store(true, Ordering::Relaxed);
+                false
+            }
+        }
+    });
+}
+
+/// Flushes the tee files. Returns false if any tee failed along the way.
+fn finish_tees() -> bool {
+    let mut tees = TEES.lock().unwrap_or_else(PoisonError::into_inner);
+    for tee in tees.iter_mut() {
+        if let Err(err) = tee.file.flush() {
+            eprintln!("meow: {}", msg!("tee-error", tee.path, err));
+            TEE_FAILED.store(true, Ordering::Relaxed);
+        }
+    }
+    tees.clear();
+    !TEE_FAILED.load(Ordering::Relaxed)
+}
+
+/// `println!` for output written outside `send_output` (headers, checksums),
+/// so the tee files and `--output` get it too.
+fn print_line(text: &str) {
+    let line = format!("{}\n", text);
+    match OUTPUT.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+        Some(output) => {
+            let _ = output.write_all(line.as_bytes());
+        },
+        None => print!("{}", line),
+    }
+    write_tees(line.as_bytes());
+}
+
+/// The `--output` files, set up by `open_output` when the option is given.
+static OUTPUT: Mutex<Option<split::OutputFiles>> = Mutex::new(None);
+
+fn open_output(config: &Config) {
+    let output = match (&config.output, &config.output_template) {
+        (_, Some(template)) => split::OutputFiles::split(template.clone(), config.split_output),
+        (Some(path), None) => split::OutputFiles::single(path),
+        (None, None) => return,
+    };
+    *OUTPUT.lock().unwrap_or_else(PoisonError::into_inner) = Some(output);
+}
+
+/// Closes the `--output` files and lists the ones `--split-output` wrote.
+/// Returns false if one couldn't be written.
+fn finish_output(config: &Config) -> bool {
+    let Some(mut output) = OUTPUT.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+        return true;
+    };
+    output.finish();
+    if config.output_template.is_some() && !output.written.is_empty() {
+        eprintln!("meow: {}", msg!("split-written", output.written.len(), output.written.join(", ")));
+    }
+    match output.error {
+        Some((path, err)) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("output-error", path, err), config.colors.reset);
+            false
+        },
+        None => true,
+    }
+}
+
+/// Writes to the `--output` files and the tee files.
+struct OutputWriter;
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(output) = OUTPUT.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            output.write_all(buf)?;
+        }
+        write_tees(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes to stdout and the tee files. A stdout error is held until the end so
+/// the tees still receive everything.
+struct TeeWriter {
+    stdout: io::Stdout,
+    error: Option<io::Error>,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.error.is_none() {
+            if let Err(err) = self.stdout.write_all(buf) {
+                self.error = Some(err);
+            }
+        }
+        write_tees(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        if self.error.is_none() {
+            if let Err(err) = self.stdout.flush() {
+                self.error = Some(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where `strip_escapes` is inside an escape sequence, kept between writes
+/// since a sequence can be split across them.
+#[derive(Clone, Copy)]
+enum EscapeState {
+    Text,
+    Escape,
+    /// `ESC [` ... final byte, e.g. colors
+    Csi,
+    /// `ESC ]` ... BEL or `ESC \`, e.g. window titles and hyperlinks
+    Osc,
+    OscEscape,
+}
+
+fn strip_escapes(bytes: &[u8], state: &mut EscapeState) -> Vec<u8> {
+    let mut plain = Vec::with_capacity(bytes.len());
+    for &byte in bytes {
+        *state = match (*state, byte) {
+            (EscapeState::Text, 0x1B) => EscapeState::Escape,
+            (EscapeState::Text, _) => {
+                plain.push(byte);
+                EscapeState::Text
+            },
+            (EscapeState::Escape, b'[') => EscapeState::Csi,
+            (EscapeState::Escape, b']') => EscapeState::Osc,
+            // Two-byte sequences
+            (EscapeState::Escape, _) => EscapeState::Text,
+            (EscapeState::Csi, 0x40..=0x7E) => EscapeState::Text,
+            (EscapeState::Csi, _) => EscapeState::Csi,
+            (EscapeState::Osc, 0x07) => EscapeState::Text,
+            (EscapeState::Osc, 0x1B) => EscapeState::OscEscape,
+            (EscapeState::Osc, _) => EscapeState::Osc,
+            (EscapeState::OscEscape, _) => EscapeState::Text,
+        };
+    }
+    plain
+}
+
+/// Sends processed input to the pager or straight to stdout.
+fn display_input<R: Read>(reader: &mut BufReader<R>, config: &Config, file_name: &str, line_num: &mut usize, run: &mut RunState) {
+    send_output(config, |out| {
+        let stats = process_input(reader, config, file_name, line_num, run, out)?;
+        run.stats.add(&stats);
+        Ok(())
+    });
+}
+
+/// `--yaml`/`--toml`/`--xml`: validates the whole document, then shows it colorized. A
+/// document that doesn't parse is shown as-is, followed by the error and a
+/// caret under the position it points at.
+fn show_structured(mut input: impl Read, format: &str, file_path: &str, config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let mut bytes = Vec::new();
+    if let Err(err) = input.read_to_end(&mut bytes) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+        return false;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let error = structured::validate(&text, format).err();
+    
+    let mut local_config = config.clone();
+    local_config.structured = error.is_none().then(|| format.to_string());
+    let shown = match (format, config.fold_depth) {
+        ("xml", _) => xml::pretty_print(&text, config.xml_indent, &config.xml_collapse),
+        ("yaml", Some(depth)) if error.is_none() => structured::fold(&text, depth),
+        _ => text.to_string(),
+    };
+    display_input(&mut BufReader::new(shown.as_bytes()), &local_config, file_path, line_num, run);
+    
+    let Some(error) = error else {
+        return true;
+    };
+    eprintln!("{}meow: {}{}", config.colors.error, msg!("syntax-error", file_path, error.line, error.column, error.message), config.colors.reset);
+    if let Some(line) = text.lines().nth(error.line - 1) {
+        // Keep tabs so the caret lines up however wide the terminal draws them
+        let pad: String = line.chars().take(error.column - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+        eprintln!("{}", line);
+        eprintln!("{}{}^{}", pad, config.colors.error, config.colors.reset);
+    }
+    false
+}
+
+/// `--resume`: shows a file from the position saved by the last run (or from
+/// the start if there is none, or the file shrank or got older), then saves
+/// how far it got. Line numbers stay absolute.
+fn resume_file(path: &Path, file_path: &str, config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let state = resume::state_path();
+    let mut positions = state.as_deref().map(resume::load).unwrap_or_default();
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+    };
+    let start = file.metadata().ok()
+        .and_then(|metadata| positions.get(&key).filter(|position| resume::still_valid(position, &metadata)).copied())
+        .filter(|position| position.offset > 0);
+    if let Some(position) = start {
+        if let Err(err) = file.seek(SeekFrom::Start(position.offset)) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+        *line_num = position.line;
+    }
+    
+    if config.files.len() > 1 {
+        print_file_header(file_path, path, None, config);
+    }
+    
+    let mut reader = BufReader::new(resume::LineCounter::new(file));
+    send_output(config, |out| {
+        if let Some(position) = start {
+            writeln!(out, "{}{}{}", config.colors.dim, msg!("resumed-at", position.line + 1), config.colors.reset)?;
+        }
+        let stats = process_input(&mut reader, config, file_path, line_num, run, out)?;
+        run.stats.add(&stats);
+        Ok(())
+    });
+    
+    let counter = reader.get_ref();
+    let (offset, line) = start.map_or((0, 0), |position| (position.offset, position.line));
+    positions.insert(key, resume::Position {
+        offset: offset + counter.complete_bytes,
+        line: line + counter.lines,
+        mtime: fs::metadata(path).map_or(0, |metadata| resume::mtime_secs(&metadata)),
+        saved: resume::now_secs(),
+    });
+    if let Some(state) = state {
+        if let Err(err) = resume::store(&state, &positions) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("resume-save-error", state.display(), err), config.colors.reset);
+        }
+    }
+    true
+}
+
+/// `--resume=clear`: forgets the saved positions of the given files, or all of
+/// them without files.
+fn clear_resume_positions(config: &Config) -> bool {
+    let Some(state) = resume::state_path() else {
+        return true;
+    };
+    let mut positions = resume::load(&state);
+    if config.files.is_empty() {
+        positions.clear();
+    }
+    for file in &config.files {
+        let path = Path::new(file);
+        positions.remove(&fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+    }
+    
+    match resume::store(&state, &positions) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("resume-save-error", state.display(), err), config.colors.reset);
+            false
+        }
+    }
+}
+
+/// `--list-dirs`: a short `ls -l` style listing of a directory. Entries that
+/// can't be stat'ed are still listed, with an error marker.
+fn list_directory(path: &Path, file_path: &str, config: &Config) -> bool {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            return false;
+        }
+    };
+    
+    let mut entries: Vec<(String, io::Result<fs::Metadata>)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path().symlink_metadata()))
+        .collect();
+    
+    let is_dir = |meta: &io::Result<fs::Metadata>| meta.as_ref().is_ok_and(|meta| meta.is_dir());
+    entries.sort_by(|(a_name, a_meta), (b_name, b_meta)| {
+        let dirs_first = if config.dirs_first { is_dir(b_meta).cmp(&is_dir(a_meta)) } else { std::cmp::Ordering::Equal };
+        // Like ls -S and ls -t: biggest and newest first
+        let key = match config.dir_sort.as_str() {
+            "size" => {
+                let size = |meta: &io::Result<fs::Metadata>| meta.as_ref().map_or(0, |meta| meta.len());
+                size(b_meta).cmp(&size(a_meta))
+            },
+            "mtime" => {
+                let mtime = |meta: &io::Result<fs::Metadata>| meta.as_ref().ok().and_then(|meta| meta.modified().ok());
+                mtime(b_meta).cmp(&mtime(a_meta))
+            },
+            _ => std::cmp::Ordering::Equal,
+        };
+        dirs_first.then(key).then_with(|| a_name.cmp(b_name))
+    });
+    
+    send_output(config, |out| {
+        for (name, meta) in &entries {
+            match meta {
+                Ok(meta) => {
+                    let file_type = meta.file_type();
+                    let (kind, color) = if file_type.is_dir() {
+                        ('d', &config.colors.highlight)
+                    } else if file_type.is_symlink() {
+                        ('l', &config.colors.filename)
+                    } else if file_type.is_file() {
+                        ('-', &config.colors.normal)
+                    } else {
+                        ('?', &config.colors.number)
+                    };
+                    let modified = meta.modified().map_or_else(|_| msg!("meta-unknown-time"), |time| format_mtime(time, config));
+                    writeln!(out, "{} {:>10}  {:<16} {}{}{}{}",
+                             kind,
+                             if file_type.is_dir() { "-".to_string() } else { units::size(meta.len(), config) },
+                             modified,
+                             color,
+                             name,
+                             config.colors.reset,
+                             if file_type.is_dir() { "/" } else { "" })?;
+                },
+                Err(err) => {
+                    writeln!(out, "{}! {:>10}  {:<16} {}{} ({}){}", config.colors.error, "?", "?", name, config.colors.reset, err, config.colors.reset)?;
+                }
+            }
+        }
+        Ok(())
+    });
+    true
+}
+
+/// Runs `filter` through `sh -c` and displays its output instead of the raw input.
+/// `{}` in the command is replaced by the quoted file path, in which case the
+/// filter reads the file itself; otherwise the input is streamed to its stdin.
+fn run_filter<R: Read + Send + 'static>(mut input: R, file_name: &str, path: Option<&Path>, filter: &str, config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let uses_path = filter.contains("{}") && path.is_some();
+    let command = match path {
+        Some(path) => filter.replace("{}", &shell_quote(&path.to_string_lossy())),
+        None => filter.replace("{}", "-"),
+    };
+    
+    let spawned = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .stdin(if uses_path { Stdio::null() } else { Stdio::piped() })
+        .stdout(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-run-filter", file_name, filter, err), config.colors.reset);
+            return false;
+        }
+    };
+    
+    // Feed the filter from another thread so a slow or chatty filter can't deadlock us
+    let feeder = child.stdin.take().map(|mut stdin| {
+        thread::spawn(move || match io::copy(&mut input, &mut stdin) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+            _ => Ok(()),
+        })
+    });
+    
+    if let Some(stdout) = child.stdout.take() {
+        display_input(&mut BufReader::new(stdout), config, file_name, line_num, run);
+    }
+    
+    let mut ok = true;
+    if let Some(Ok(Err(err))) = feeder.map(|feeder| feeder.join()) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_name, err), config.colors.reset);
+        ok = false;
+    }
+    
+    match child.wait() {
+        Ok(status) if status.success() => ok,
+        Ok(status) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("filter-failed", file_name, filter, status), config.colors.reset);
+            false
+        },
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_name, err), config.colors.reset);
+            false
+        }
+    }
+}
+
+/// Quotes a string for POSIX shells using single quotes.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn special_file_kind(path: &Path) -> Option<&'static str> {
+    special_kind(&fs::metadata(path).ok()?.file_type())
+}
+
+/// The "waiting for input" notice for an input that can keep meow waiting:
+/// a FIFO blocks on open until a writer shows up, a terminal until something
+/// is typed. Other devices (`/dev/null`, ...) and pipes that have a writer
+/// already don't wait, so the FIFO's notice is only given if the open hasn't
+/// returned a moment later. Drop it once the input is open.
+struct WaitNotice {
+    _opened: Option<mpsc::Sender<()>>,
+}
+
+impl WaitNotice {
+    /// How long an open may take before the FIFO notice is given.
+    const GRACE: Duration = Duration::from_millis(200);
+    
+    fn start(path: &Path, file_path: &str) -> Option<Self> {
+        match special_file_kind(path)? {
+            "kind-fifo" => {
+                let (opened, wait) = mpsc::channel::<()>();
+                let notice = msg!("waiting-for-input", file_path, msg!("kind-fifo"));
+                // Dropping the sender ends the wait before the grace period is up
+                thread::spawn(move || {
+                    if wait.recv_timeout(Self::GRACE) == Err(mpsc::RecvTimeoutError::Timeout) {
+                        eprintln!("meow: {}", notice);
+                    }
+                });
+                Some(WaitNotice { _opened: Some(opened) })
+            },
+            "kind-char-device" if is_terminal(path) => {
+                eprintln!("meow: {}", msg!("waiting-for-input", file_path, msg!("kind-terminal")));
+                Some(WaitNotice { _opened: None })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Whether the device at `path` is a terminal. Opened without blocking, which
+/// is all a terminal would do.
+#[cfg(unix)]
+fn is_terminal(path: &Path) -> bool {
+    use std::io::IsTerminal;
+    use std::os::unix::fs::OpenOptionsExt;
+    
+    fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+        .open(path)
+        .is_ok_and(|file| file.is_terminal())
+}
+
+#[cfg(not(unix))]
+fn is_terminal(_path: &Path) -> bool {
+    false
+}
+
+/// The `kind-*` message for FIFOs, devices and sockets.
+#[cfg(unix)]
+fn special_kind(file_type: &fs::FileType) -> Option<&'static str> {
+    if file_type.is_fifo() {
+        Some("kind-fifo")
+    } else if file_type.is_char_device() {
+        Some("kind-char-device")
+    } else if file_type.is_block_device() {
+        Some("kind-block-device")
+    } else if file_type.is_socket() {
+        Some("kind-socket")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn special_kind(_file_type: &fs::FileType) -> Option<&'static str> {
+    None
+}
+
+/// Identifies image formats by their magic bytes.
+fn sniff_image(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("PNG")
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("JPEG")
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        Some("GIF")
+    } else {
+        None
+    }
+}
+
+fn show_image<R: Read>(reader: &mut BufReader<R>, kind: &str, file_path: &str, config: &Config) {
+    #[cfg(feature = "images")]
+    {
+        if config.use_colors && atty::is(atty::Stream::Stdout) {
+            let mut data = Vec::new();
+            if let Err(err) = reader.read_to_end(&mut data) {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+                return;
+            }
+            
+            let width = config.image_width.unwrap_or_else(terminal_width);
+            match preview::render_image(&data, &config.image_protocol, width, config.color_depth, &mut io::stdout()) {
+                Ok(()) => return,
+                Err(err) => {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-render-image", file_path, err), config.colors.reset);
+                }
+            }
+        }
+    }
+    #[cfg(not(feature = "images"))]
+    let _ = reader;
+    
+    eprintln!("{}meow: {}{}", config.colors.error, msg!("binary-image", file_path, kind), config.colors.reset);
+}
+
+/// `--list`: the entries of a zip archive. Returns false if it can't be read.
+fn list_archive(path: &Path, file_path: &str, config: &Config) -> bool {
+    #[cfg(feature = "zip")]
+    match archive::list(path, config) {
+        Ok(lines) => {
+            send_output(config, |out| lines.iter().try_for_each(|line| writeln!(out, "{}", line)));
+            true
+        },
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+            false
+        }
+    }
+    #[cfg(not(feature = "zip"))]
+    {
+        let _ = (path, file_path);
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("zip-unsupported"), config.colors.reset);
+        false
+    }
+}
+
+/// Renders the whole input as a QR code. Returns false if it can't be shown.
+fn show_qr<R: Read>(mut input: R, file_name: &str, config: &Config) -> bool {
+    #[cfg(feature = "qr")]
+    {
+        let mut data = Vec::new();
+        if let Err(err) = input.read_to_end(&mut data) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_name, err), config.colors.reset);
+            return false;
+        }
+        // A password file shouldn't scan with a stray newline at the end
+        if data.ends_with(b"\n") {
+            data.pop();
+            if data.ends_with(b"\r") {
+                data.pop();
+            }
+        }
+        
+        match qr::encode(&data, terminal_width()) {
+            Ok(code) => {
+                report_write_error(qr::write_qr(&code, config.qr_invert, config.use_colors, &mut io::stdout()), config);
+                true
+            },
+            Err(max_bytes) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("qr-too-large", file_name, data.len(), max_bytes), config.colors.reset);
+                false
+            }
+        }
+    }
+    #[cfg(not(feature = "qr"))]
+    {
+        let _ = (&mut input, file_name);
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("qr-unsupported"), config.colors.reset);
+        false
+    }
+}
+
+/// Prints the `--banner` cat and the `--title` header. Decorations only make
+/// sense on a terminal, so they are dropped for pipes unless `--force-art` is given.
+fn print_art(config: &Config) {
+    if config.plain || (config.banner.is_none() && config.title.is_none()) {
+        return;
+    }
+    if !config.force_art && !atty::is(atty::Stream::Stdout) {
+        return;
+    }
+    
+    let width = terminal_width();
+    let mut out = io::stdout().lock();
+    let mut result = Ok(());
+    
+    if let Some(cat) = config.banner.as_deref().and_then(art::cat_art) {
+        // A cat cut in half is worse than no cat
+        if cat.iter().all(|line| line.chars().count() <= width) {
+            for line in cat {
+                result = result.and_then(|_| writeln!(out, "{}{}{}", config.colors.filename, line, config.colors.reset));
+            }
+        }
+    }
+    
+    if let Some(title) = &config.title {
+        for row in art::render_title(title, width) {
+            result = result.and_then(|_| writeln!(out, "{}{}{}", config.colors.highlight, row, config.colors.reset));
+        }
+        result = result.and_then(|_| writeln!(out));
+    }
+    
+    report_write_error(result, config);
+}
+
+/// Height of the terminal attached to stdout, falling back to `$LINES` and then 24.
+fn terminal_height() -> usize {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass in
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_row > 0 {
+            return size.ws_row as usize;
+        }
+    }
+    
+    env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse().ok())
+        .filter(|&lines: &usize| lines > 0)
+        .unwrap_or(24)
+}
+
+/// Width of the terminal attached to stdout, falling back to `$COLUMNS` and then 80.
+fn terminal_width() -> usize {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass in
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns: &usize| columns > 0)
+        .unwrap_or(80)
+}
+
+fn report_write_error(result: io::Result<()>, config: &Config) {
+    match result {
+        Ok(()) => {},
+        // The reader went away (e.g. `meow file | head`), nothing left to do
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("write-error", err), config.colors.reset);
+        }
+    }
+}
+
+fn read_all_content<R: Read>(reader: &mut BufReader<R>) -> String {
+    let mut content = String::new();
+    if let Err(e) = reader.read_to_string(&mut content) {
+        eprintln!("meow: {}", msg!("read-error", e));
+    }
+    content
+}
+
+/// Shows `content` through `$MEOW_PAGER` (default `less -R`). When the pager
+/// can't be started the content goes to stdout instead.
+fn page_content(content: &str, config: &Config) {
+    let pager_command = env::var("MEOW_PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut words = pager_command.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let mut pager = match Command::new(program).args(words).stdin(Stdio::piped()).spawn() {
+        Ok(pager) => pager,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-start-pager", program, err), config.colors.reset);
+            report_write_error(io::stdout().write_all(content.as_bytes()), config);
+            return;
+        }
+    };
+    
+    if let Some(mut stdin) = pager.stdin.take() {
+        match stdin.write_all(content.as_bytes()) {
+            // Quitting the pager before the end closes the pipe, and that's all
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {},
+            result => report_write_error(result, config),
+        }
+    }
+    
+    let _ = pager.wait();
+}
+
+/// What `--meta` headers and `--stat` show about a file.
+struct FileMeta {
+    size: u64,
+    modified: String,
+    /// What `modified` was made from, for `--freshness`
+    time: Option<SystemTime>,
+    /// A `kind-*` message key
+    kind: &'static str,
+    permissions: String,
+}
+
+/// Metadata only, the file is never opened. Symlinks are followed, but reported
+/// as such.
+fn file_meta(path: &Path, config: &Config) -> io::Result<FileMeta> {
+    let is_link = fs::symlink_metadata(path)?.file_type().is_symlink();
+    let metadata = path.metadata()?;
+    let file_type = metadata.file_type();
+    
+    let kind = if is_link {
+        "kind-symlink"
+    } else if file_type.is_dir() {
+        "kind-directory"
+    } else if file_type.is_file() {
+        "kind-file"
+    } else {
+        special_kind(&file_type).unwrap_or("kind-other")
+    };
+    let time = metadata.modified().ok();
+    let modified = time.map_or_else(|| msg!("meta-unknown-time"), |time| format_mtime(time, config));
+    
+    Ok(FileMeta { size: metadata.len(), modified, time, kind, permissions: format_permissions(&metadata.permissions()) })
+}
+
+/// `rwxr-xr-x (755)`, like the mode column of ls.
+#[cfg(unix)]
+fn format_permissions(permissions: &fs::Permissions) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    
+    let mode = permissions.mode() & 0o777;
+    let flags: String = (0..9)
+        .map(|bit| if mode & (0o400 >> bit) != 0 { ['r', 'w', 'x'][bit % 3] } else { '-' })
+        .collect();
+    format!("{} ({:03o})", flags, mode)
+}
+
+#[cfg(not(unix))]
+fn format_permissions(permissions: &fs::Permissions) -> String {
+    msg!(if permissions.readonly() { "meta-read-only" } else { "meta-read-write" })
+}
+
+fn get_file_meta(path: &Path, config: &Config) -> String {
+    let meta = match file_meta(path, config) {
+        Ok(meta) => meta,
+        Err(_) => return "".to_string(),
+    };
+    
+    let checksum = config.checksum
+        .and_then(|algorithm| File::open(path).and_then(|mut file| hash::hash_reader(algorithm, &mut file)).ok())
+        .map(|digest| format!(" [{}]", digest))
+        .unwrap_or_default();
+    
+    format!(" [{}] [{}]{}", units::size(meta.size, config), freshness::paint(&meta.modified, meta.time, config), checksum)
+}
+
+/// `--list-matches`: tallies every match of `pattern` over all inputs (stdin if
+/// none), then prints each distinct one once in sorted order, like
+/// `grep -o | sort | uniq -c` with `--list-matches-count`.
+fn list_matches(pattern: &Regex, config: &Config) -> bool {
+    let mut ok = true;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let sources = config.sources();
+    for source in &sources {
+        let input = match source.open() {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", source.name(), err), config.colors.reset);
+                ok = false;
+                continue;
+            }
+        };
+        let mut lines = CappedLines::new(BufReader::new(input.reader), config.max_line_bytes);
+        if let Some(separator) = &config.record_separator {
+            lines.separator = separator.clone();
+        }
+        for line in lines {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", source.name(), err), config.colors.reset);
+                    ok = false;
+                    break;
+                }
+            };
+            for found in pattern.find_iter(&line).filter(|found| !found.is_empty()) {
+                *counts.entry(found.as_str().to_string()).or_default() += 1;
+            }
+        }
+    }
+    
+    let mut matches: Vec<(String, usize)> = counts.into_iter().collect();
+    matches.sort();
+    send_output(config, |out| {
+        for (text, count) in &matches {
+            if config.list_matches_count {
+                writeln!(out, "{}{:>7}{} {}", config.colors.number, count, config.colors.reset, text)?;
+            } else {
+                writeln!(out, "{}", text)?;
+            }
+        }
+        Ok(())
+    });
+    ok
+}
+
+/// `--stat`: prints the metadata of each file without reading it, one block per
+/// file or, for several files, an aligned table. Returns false if a file
+/// couldn't be stat'ed.
+fn print_stats(config: &Config) -> bool {
+    let mut ok = true;
+    let mut rows: Vec<[String; 5]> = Vec::new();
+    for file_path in &config.files {
+        match file_meta(Path::new(file_path), config) {
+            Ok(meta) => rows.push([
+                file_path.clone(),
+                units::size(meta.size, config),
+                freshness::paint(&meta.modified, meta.time, config),
+                msg!(meta.kind),
+                meta.permissions,
+            ]),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_path, err), config.colors.reset);
+                ok = false;
+            }
+        }
+    }
+    
+    if config.files.len() == 1 {
+        for [name, size, modified, kind, permissions] in &rows {
+            print_line(&format!("{}{}{}", config.colors.filename, name, config.colors.reset));
+            let fields = [("stat-size", size), ("stat-modified", modified), ("stat-type", kind), ("stat-permissions", permissions)];
+            let width = fields.iter().map(|(key, _)| msg!(*key).chars().count()).max().unwrap_or(0);
+            for (key, value) in fields {
+                let label = msg!(key);
+                print_line(&format!("  {}{}{}  {}{}", config.colors.dim, label, config.colors.reset, " ".repeat(width - label.chars().count()), value));
+            }
+        }
+        return ok;
+    }
+    
+    let header = ["stat-name", "stat-size", "stat-modified", "stat-type", "stat-permissions"]
+        .map(|key| format!("{}{}{}", config.colors.dim, msg!(key), config.colors.reset));
+    let table: Vec<Vec<String>> = std::iter::once(header).chain(rows).map(Vec::from).collect();
+    // Sizes line up on the right
+    for line in columns::table(&table, &[1]) {
+        print_line(&line);
+    }
+    ok
+}
+
+/// Modification time as `--date-format` asks, or relative ("3 hours ago").
+fn format_mtime(time: SystemTime, config: &Config) -> String {
+    if let Some(format) = &config.date_format {
+        return DateTime::<Local>::from(time).format(format).to_string();
+    }
+    
+    let Some(age) = freshness::age(time) else {
+        return msg!("meta-unknown-time");
+    };
+    
+    if age < 60 * 60 {
+        msg!("meta-minutes-ago", age / 60)
+    } else if age < 60 * 60 * 24 {
+        msg!("meta-hours-ago", age / (60 * 60))
+    } else {
+        msg!("meta-days-ago", age / (60 * 60 * 24))
+    }
+}
+
+/// `--prepend`/`--append`: written as is, outside the numbering and the gutter.
+fn write_banner(text: &str, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    if config.json_array {
+        return Ok(());
+    }
+    for line in text.lines() {
+        writeln!(out, "{}{}{}", config.colors.dim, line, config.colors.reset)?;
+    }
+    Ok(())
+}
+
+/// `line_num` is the last line number printed so far; it is carried over between
+/// inputs for `--continuous-numbering`, like the rest of `run`. Returns what was
+/// read and shown, for `--summary`.
+fn process_input<R: Read>(reader: &mut BufReader<R>, config: &Config, file_name: &str, line_num: &mut usize, run: &mut RunState, out: &mut dyn Write) -> io::Result<summary::Stats> {
+    // Check if we need to animate the output
+    if config.animate {
+        let content = read_all_content(reader);
+        animate_text(&content, out)?;
+        let lines = content.lines().count();
+        return Ok(summary::Stats { lines_read: lines, lines_printed: lines, ..Default::default() });
+    }
+    
+    if let Some(text) = &config.prepend {
+        write_banner(text, config, out)?;
+    }
+    
+    if config.marks_index && !config.json_array {
+        marks::write_index(out, file_name, config)?;
+    }
+    
+    terminal::set_title(file_name, config.searching().then_some(0), true, config);
+    let blame = if config.blame { load_blame(file_name, config) } else { None };
+    let mut writer = LineWriter {
+        config,
+        file_name,
+        blame_width: blame.as_ref().map_or(0, |lines| blame_gutter_width(lines)),
+        blame,
+        changes: if config.changes { load_changes(file_name, config) } else { None },
+        brackets: config.match_brackets.then(|| brackets::Brackets::new(file_name, config)),
+        heat: (config.number_heat && config.use_colors).then(|| numbers::Heat::for_input(file_name, config)),
+        // Sampled output is numbered by position in the source, not by output line
+        sampling: config.sample_every.is_some() || config.sample_probability.is_some() || config.preview.is_some(),
+        marks: marks::Marks::new(config),
+        written: 0,
+        ansi: config.preserve_ansi.then(sgr::Tracker::default),
+        run,
+    };
+    
+    let mut prev_source_line = 0;
+    let mut prev_blank = false;
+    let mut rng = XorShift::from_time();
+    let mut indent = IndentStats::default();
+    let mut match_count = 0;
+    let mut stats = summary::Stats::default();
+    let mut before: VecDeque<(usize, String, Vec<EnvSpan>, bool)> = VecDeque::new();
+    let mut dupes = DupeTracker::default();
+    let mut invisible = invisible::Tally::default();
+    let mut undefined_env: Vec<String> = Vec::new();
+    let mut after_remaining = 0;
+    let comment_style = if config.strip_comments {
+        config.comment_style.as_deref().or_else(|| comment_style_for(file_name))
+    } else {
+        None
+    };
+    let mut in_block_comment = false;
+    let mut entry_time: Option<logtime::Millis> = None;
+    let mut throttle = config.throttle.map(Throttle::new);
+    let mut each_line = config.each_line_command.as_deref().map(|argv| exec::EachLine::new(argv, config));
+    // Like grep, separate context groups, also across files
+    let separate_groups = config.searching() && (config.before_context > 0 || config.after_context > 0)
+        && config.group_separator.is_some() && !config.json_array;
+    let mut last_written: Option<usize> = None;
+    // -A already shows ESC as ^[
+    let mut escaping = config.safe == Some(true) && !config.show_all_nonprinting;
+    let mut neutralized = 0;
+    let mut redactions = 0;
+    let mut in_private_key = false;
+    if !config.max_count_total {
+        writer.run.matches_shown = 0;
+    }
+    
+    let mut lines = CappedLines::new(reader, config.max_line_bytes);
+    lines.keep_cr = config.show_ends || config.show_all_nonprinting;
+    if let Some(separator) = &config.record_separator {
+        lines.separator = separator.clone();
+    }
+    let numbered: Box<dyn Iterator<Item = (usize, io::Result<String>)>> = match config.preview {
+        Some(n) => Box::new(preview_lines(lines.by_ref(), n).into_iter()),
+        None => Box::new(lines.by_ref().enumerate().map(|(i, line)| (i + 1, line))),
+    };
+    for (source_line, line_result) in numbered {
+        // The rest of the file isn't read once the last match's context is out
+        if after_remaining == 0 && writer.run.max_count_reached(config) {
+            break;
+        }
+        match line_result {
+            Ok(line) => {
+                // --preview leaves a gap between the head and the tail
+                if source_line > prev_source_line + 1 && !config.json_array {
+                    writeln!(out, "{}{}{}", config.colors.highlight, msg!("preview-skipped", source_line - prev_source_line - 1), config.colors.reset)?;
+                }
+                prev_source_line = source_line;
+                
+                // As the input has it, before escaping or masking changes the line
+                if config.count_invisible {
+                    invisible.add(source_line, &line);
+                }
+                
+                // Terminal control sequences in the input could retitle the window,
+                // move the cursor over earlier lines, remap keys, ...
+                if config.safe.is_none() && !escaping && !config.show_all_nonprinting && has_control_sequences(&line) {
+                    escaping = true;
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("control-sequences-found", file_name), config.colors.reset);
+                }
+                let line = if escaping {
+                    let (escaped, count) = neutralize_escapes(&line);
+                    neutralized += count;
+                    escaped
+                } else {
+                    line
+                };
+                
+                // Secrets are masked before any other option gets to see the line
+                let line = match &config.redactor {
+                    Some(redactor) => {
+                        let (redacted, count) = redactor.redact(&line, &mut in_private_key);
+                        redactions += count;
+                        redacted
+                    },
+                    None => line,
+                };
+                
+                // Indentation is judged on the whole input, before any lines are dropped
+                if config.detect_indent {
+                    indent.add_line(&line);
+                }
+                // Marks are numbered counting the lines that aren't shown
+                if let Some(marks) = writer.marks.as_mut() {
+                    marks.add(source_line, &line);
+                }
+                // The brackets too, so the depths carry over lines that aren't shown
+                if let Some(brackets) = writer.brackets.as_mut() {
+                    brackets.add(&line, source_line, config);
+                }
+                // And the input's colors, which a line that isn't shown may still have set
+                if let Some(ansi) = writer.ansi.as_mut() {
+                    ansi.add(&line);
+                }
+                
+                // Comment-only lines are dropped rather than left blank
+                let line = match comment_style {
+                    Some(style) => match strip_comment(&line, style, &mut in_block_comment) {
+                        Some(stripped) => stripped,
+                        None => continue,
+                    },
+                    None => line,
+                };
+                
+                if !config.rules.is_empty() && rules::apply(&config.rules, &line).hidden {
+                    continue;
+                }
+                
+                // Duplicates are counted over the whole input, like the indentation
+                let repeated = (config.find_dupes || config.mark_dupes) && dupes.add(&line, source_line, config);
+                
+                // --since/--until: a line without a timestamp belongs to the entry above,
+                // so stack traces stay with their message, unless --skip-untimed
+                if config.since.is_some() || config.until.is_some() {
+                    match logtime::line_time(&line, config.time_format.as_deref()) {
+                        Some(time) => entry_time = Some(time),
+                        None if config.skip_untimed => continue,
+                        None => {},
+                    }
+                    let in_window = entry_time.is_some_and(|time| {
+                        config.since.is_none_or(|since| time >= since) && config.until.is_none_or(|until| time <= until)
+                    });
+                    if !in_window {
+                        continue;
+                    }
+                }
+                
+                if let Some(n) = config.sample_every {
+                    if source_line % n != 0 {
+                        continue;
+                    }
+                }
+                if let Some(p) = config.sample_probability {
+                    if rng.next_f64() >= p {
+                        continue;
+                    }
+                }
+                // Expanded first, so grep and highlights see the resolved values
+                let (line, env_spans) = match &config.env_vars {
+                    Some(vars) => {
+                        let (expanded, spans) = expand_env(&line, vars);
+                        for &(start, end, defined) in &spans {
+                            if !defined && config.env_strict {
+                                undefined_env.push(expanded[start..end].to_string());
+                            }
+                        }
+                        (expanded, spans)
+                    },
+                    None => (line, Vec::new()),
+                };
+                // A substituted variable may hold a secret too; masking moves the spans
+                let (line, env_spans) = match &config.redactor {
+                    Some(redactor) if !env_spans.is_empty() => {
+                        let (redacted, count) = redactor.redact(&line, &mut false);
+                        redactions += count;
+                        if count > 0 { (redacted, Vec::new()) } else { (line, env_spans) }
+                    },
+                    _ => (line, env_spans),
+                };
+                let is_blank = line.trim().is_empty();
+                
+                // Skip blank lines with squeeze_blank option
+                if config.squeeze_blank && is_blank && prev_blank {
+                    continue;
+                }
+                
+                // Lines that don't match the grep pattern are only shown as context.
+                // With --match-index every match but the Nth counts as context too.
+                // --min-length/--max-length narrow the selection like grep does
+                let length = line.chars().count();
+                let mut selected = config.min_length.is_none_or(|min| length >= min)
+                    && config.max_length.is_none_or(|max| length <= max);
+                // Past --max-count, matches are only after-context of the last one
+                if selected && config.searching() {
+                    selected = !writer.run.max_count_reached(config) && config.grep_selects(&line);
+                    if selected {
+                        match_count += 1;
+                        selected = config.match_index.is_none_or(|n| n == match_count);
+                    }
+                    if selected {
+                        writer.run.matches_shown += 1;
+                    }
+                }
+                if config.annotations_always && config.annotations.as_ref().is_some_and(|notes| notes.contains_key(&source_line)) {
+                    selected = true;
+                }
+                terminal::set_title(file_name, config.searching().then_some(match_count), false, config);
+                
+                // Keep a window of lines before the next match for --before-context
+                if !selected {
+                    if after_remaining > 0 {
+                        after_remaining -= 1;
+                    } else {
+                        if config.before_context > 0 {
+                            if before.len() == config.before_context {
+                                before.pop_front();
+                            }
+                            before.push_back((source_line, line, env_spans, repeated));
+                        }
+                        continue;
+                    }
+                } else {
+                    let group_start = before.front().map_or(source_line, |(context_line, ..)| *context_line);
+                    let after_gap = last_written.map_or(writer.run.groups_written, |last| group_start > last + 1);
+                    if separate_groups && after_gap {
+                        writeln!(out, "{}{}{}", config.colors.highlight, config.group_separator.as_deref().unwrap_or_default(), config.colors.reset)?;
+                    }
+                    for (context_line, context, context_spans, context_repeated) in before.drain(..) {
+                        if let Some(throttle) = throttle.as_mut() {
+                            throttle.pace(out)?;
+                        }
+                        writer.write_line(out, context, &context_spans, context_line, context_repeated, line_num)?;
+                        stats.lines_printed += 1;
+                    }
+                    after_remaining = config.after_context;
+                }
+                
+                prev_blank = is_blank;
+                if let Some(throttle) = throttle.as_mut() {
+                    throttle.pace(out)?;
+                }
+                // After-context lines come through here too, but weren't selected
+                let each_line_text = each_line.is_some().then(|| line.clone()).filter(|_| selected);
+                writer.write_line(out, line, &env_spans, source_line, repeated, line_num)?;
+                stats.lines_printed += 1;
+                if let (Some(each_line), Some(text)) = (each_line.as_mut(), each_line_text) {
+                    each_line.run(&text, out)?;
+                }
+                if separate_groups {
+                    last_written = Some(source_line);
+                    writer.run.groups_written = true;
+                }
+            },
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", file_name, err), config.colors.reset);
+                stats.errors += 1;
+                break;
+            }
+        }
+    }
+    
+    if let Some(text) = &config.append {
+        write_banner(text, config, out)?;
+    }
+    terminal::set_title(file_name, config.searching().then_some(match_count), true, config);
+    
+    if let Some(n) = config.match_index {
+        if config.searching() && match_count < n {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("match-index-out-of-range", file_name, n, match_count), config.colors.reset);
+        }
+    }
+    
+    if !undefined_env.is_empty() {
+        undefined_env.sort();
+        undefined_env.dedup();
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("undefined-variables", file_name, undefined_env.join(", ")), config.colors.reset);
+        writer.run.undefined_env = true;
+    }
+    
+    if lines.split {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("long-lines-split", file_name, config.max_line_bytes.unwrap_or(0)), config.colors.reset);
+    }
+    
+    if config.detect_indent {
+        eprintln!("meow: {}", msg!("indentation", file_name, indent.describe()));
+    }
+    
+    // The rest of the input wasn't read, so later marks may well be in it
+    if let Some(marks) = writer.marks.as_ref().filter(|_| !writer.run.max_count_reached(config)) {
+        marks.report(file_name, prev_source_line);
+    }
+    
+    if config.find_dupes {
+        dupes.report(file_name, config);
+    }
+    
+    if config.count_invisible {
+        invisible.report(file_name, config);
+    }
+    
+    if let Some(each_line) = each_line.as_mut() {
+        each_line.finish(file_name);
+    }
+    
+    // Openers still open have been shown already without a sign of it
+    if let Some(problems) = writer.brackets.as_ref().map(brackets::Brackets::problems).filter(|problems| !problems.is_empty()) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("brackets-unmatched", file_name, problems.len(), brackets::describe(&problems)), config.colors.reset);
+    }
+    
+    if neutralized > 0 && !config.quiet {
+        eprintln!("meow: {}", msg!("escapes-neutralized", file_name, neutralized));
+    }
+    
+    if config.redactor.is_some() && !config.quiet {
+        eprintln!("meow: {}", msg!("redactions", file_name, redactions));
+    }
+    
+    if let Some(notes) = &config.annotations {
+        let mut past_end: Vec<usize> = notes.keys().copied().filter(|&line| line > prev_source_line).collect();
+        if !past_end.is_empty() {
+            past_end.sort_unstable();
+            let lines: Vec<String> = past_end.iter().map(usize::to_string).collect();
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("annotations-past-end", file_name, prev_source_line, lines.join(", ")), config.colors.reset);
+        }
+    }
+    
+    stats.lines_read = prev_source_line;
+    stats.matches = match_count;
+    Ok(stats)
+}
+
+/// How `-A`, `-T` and `-E` show a control character, per `--nonprint-style`:
+/// `^[` (caret, with `^?` for DEL and `M-^[` for C1 controls), `\x1b` (hex),
+/// or a Unicode control picture like `␛` (hex for C1 controls, which have none).
+fn show_control(c: char, style: &str) -> String {
+    let code = c as u32;
+    match style {
+        "hex" => format!("\\x{:02x}", code),
+        "unicode" => match code {
+            0x00..=0x1F => char::from_u32(0x2400 + code).map_or_else(|| format!("\\x{:02x}", code), String::from),
+            0x7F => "\u{2421}".to_string(),
+            _ => format!("\\x{:02x}", code),
+        },
+        _ => match code {
+            0x00..=0x1F => format!("^{}", char::from_u32(code + 64).unwrap_or('?')),
+            0x7F => "^?".to_string(),
+            0x80..=0x9F => format!("M-^{}", char::from_u32(code - 0x80 + 64).unwrap_or('?')),
+            _ => c.to_string(),
+        },
+    }
+}
+
+/// Whether a line has escape sequences other than plain colors (SGR), or C1
+/// control characters, which some terminals act on like escape sequences.
+fn has_control_sequences(line: &str) -> bool {
+    line.char_indices().any(|(i, c)| match c {
+        '\x1B' => !is_sgr(&line[i + 1..]),
+        '\u{80}'..='\u{9F}' => true,
+        _ => false,
+    })
+}
+
+/// `ESC [ params m`, the sequence for colors and text attributes. `rest`
+/// starts after the ESC.
+fn is_sgr(rest: &str) -> bool {
+    rest.strip_prefix('[')
+        .map(|params| params.trim_start_matches(|c: char| c.is_ascii_digit() || c == ';' || c == ':'))
+        .is_some_and(|tail| tail.starts_with('m'))
+}
+
+/// `--safe`: shows ESC as `^[` and C1 controls as their 7-bit form (CSI as
+/// `^[[`), so no sequence reaches the terminal. Since every single ESC is
+/// replaced, sequences split across reads or lines can't sneak through.
+/// Returns the line and how many sequences were neutralized.
+fn neutralize_escapes(line: &str) -> (String, usize) {
+    let mut escaped = String::with_capacity(line.len());
+    let mut count = 0;
+    for c in line.chars() {
+        match c {
+            '\x1B' => {
+                escaped.push_str("^[");
+                count += 1;
+            },
+            '\u{80}'..='\u{9F}' => {
+                escaped.push_str("^[");
+                escaped.push((c as u8 - 0x40) as char);
+                count += 1;
+            },
+            _ => escaped.push(c),
+        }
+    }
+    (escaped, count)
+}
+
+/// Paces `--throttle` output. Lines are due at fixed intervals from the start,
+/// so time spent reading and rendering doesn't slow the rate down; after a
+/// stall (e.g. waiting on a pipe) the schedule restarts instead of bursting.
+struct Throttle {
+    interval: Duration,
+    next: Instant,
+}
+
+impl Throttle {
+    fn new(lines_per_sec: f64) -> Self {
+        Throttle {
+            interval: Duration::from_secs_f64(1.0 / lines_per_sec),
+            next: Instant::now(),
+        }
+    }
+    
+    /// Waits until the next line is due. What's been written so far is flushed
+    /// first so it shows up before the pause.
+    fn pace(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        let now = Instant::now();
+        if self.next > now {
+            out.flush()?;
+            thread::sleep(self.next - now);
+        } else if now - self.next > self.interval {
+            self.next = now;
+        }
+        self.next += self.interval;
+        Ok(())
+    }
+}
+
+/// Reads the whole input but keeps only the first and last `n` lines, with
+/// their 1-based positions. A read error ends the input and is passed along.
+fn preview_lines(lines: impl Iterator<Item = io::Result<String>>, n: usize) -> Vec<(usize, io::Result<String>)> {
+    let mut head = Vec::new();
+    let mut tail: VecDeque<(usize, io::Result<String>)> = VecDeque::with_capacity(n);
+    
+    for (i, line) in lines.enumerate() {
+        let failed = line.is_err();
+        if i < n {
+            head.push((i + 1, line));
+        } else {
+            if tail.len() == n {
+                tail.pop_front();
+            }
+            tail.push_back((i + 1, line));
+        }
+        if failed {
+            break;
+        }
+    }
+    
+    head.extend(tail);
+    head
+}
+
+/// Renders single lines with everything that goes around them: file name,
+/// blame gutter, change marker, number, and the line decorations.
+struct LineWriter<'a> {
+    config: &'a Config,
+    file_name: &'a str,
+    blame: Option<Vec<Option<BlameLine>>>,
+    blame_width: usize,
+    changes: Option<Vec<Option<ChangeMark>>>,
+    /// `--match-brackets`, fed every line of the input
+    brackets: Option<brackets::Brackets>,
+    /// `--number-heat`, with this input's thresholds
+    heat: Option<numbers::Heat>,
+    sampling: bool,
+    /// `--marks` and `--mark-pattern`, fed every line of the input
+    marks: Option<marks::Marks<'a>>,
+    /// Lines written so far, for `--ruler-every`
+    written: usize,
+    /// `--preserve-ansi`, fed every line of the input
+    ansi: Option<sgr::Tracker>,
+    run: &'a mut RunState,
+}
+
+impl LineWriter<'_> {
+    /// `source_line` is the 1-based position in the input, `line_num` the running
+    /// output number shared with `process_input`. `env_spans` are the `--expand-env`
+    /// substitutions in `line`, `repeated` whether it was already seen for `--mark-dupes`.
+    fn write_line(&mut self, out: &mut dyn Write, line: String, env_spans: &[EnvSpan], source_line: usize, repeated: bool, line_num: &mut usize) -> io::Result<()> {
+        let config = self.config;
+        
+        if config.json_array {
+            let text = match &config.fields {
+                Some(ranges) => select_fields(&line, ranges, config),
+                None => line,
+            };
+            if config.group_by_file {
+                let object = format!("{{\"line\": {}, \"text\": {}}}", source_line, escape_line(&text, "json"));
+                self.run.json_group(self.file_name).push(object);
+                return Ok(());
+            }
+            let opening = if self.run.json_lines == 0 { "[" } else { "," };
+            self.run.json_lines += 1;
+            return write!(out, "{}\n  {{\"file\": {}, \"line\": {}, \"text\": {}}}",
+                          opening, escape_line(self.file_name, "json"), source_line, escape_line(&text, "json"));
+        }
+        
+        // --shell-quote=array: the lines are words of one line, a space apart
+        if config.shell_quote_array {
+            if self.run.quoted_words > 0 {
+                write!(out, " ")?;
+            }
+            self.run.quoted_words += 1;
+        }
+        
+        if config.ruler && (self.written == 0 || config.ruler_every.is_some_and(|every| self.written.is_multiple_of(every))) {
+            self.write_ruler(out)?;
+        }
+        self.written += 1;
+        
+        let is_blank = line.trim().is_empty();
+        
+        // Marks on lines that aren't shown are dropped with them
+        if let Some(number) = self.marks.as_ref().and_then(|marks| marks.number(source_line)) {
+            if !config.shell_quote_array {
+                marks::write_rule(out, number, config)?;
+            }
+        }
+        
+        if config.with_filename {
+            write!(out, "{}{}{}:", config.colors.filename, self.file_name, config.colors.reset)?;
+        }
+        
+        // Blame gutter, keyed by the line's position in the source file
+        if let Some(blame_lines) = &self.blame {
+            let entry = blame_lines.get(source_line - 1).and_then(|entry| entry.as_ref());
+            write!(out, "{}", format_blame_gutter(entry, self.blame_width, config))?;
+        }
+        
+        // Diff markers also follow the source position so they survive grep
+        if let Some(marks) = &self.changes {
+            let mark = marks.get(source_line - 1).copied().flatten();
+            write!(out, "{} ", format_change_marker(mark, config))?;
+        }
+        
+        let notes = config.annotations.as_ref().map(|notes| notes.get(&source_line));
+        match notes {
+            Some(Some(_)) => write!(out, "{}*{} ", config.colors.highlight, config.colors.reset)?,
+            Some(None) => write!(out, "  ")?,
+            None => {},
+        }
+        
+        if config.rules.iter().any(|rule| rule.action == Some(rules::Action::Mark)) {
+            match rules::apply(&config.rules, &line).mark {
+                Some(style) => write!(out, "{}>{} ", style, config.colors.reset)?,
+                None => write!(out, "  ")?,
+            }
+        }
+        
+        // Context lines have no match to show
+        if let (true, Some(fuzzy)) = (config.show_distance, &config.grep_fuzzy) {
+            let width = fuzzy.distance.to_string().len() + 1;
+            match fuzzy.find(&line) {
+                Some(found) => write!(out, "{}{:>width$}{} ", config.colors.dim, format!("~{}", found.distance), config.colors.reset)?,
+                None => write!(out, "{:width$} ", "")?,
+            }
+        }
+        
+        if config.mark_dupes {
+            if repeated {
+                write!(out, "{}={} ", config.colors.number, config.colors.reset)?;
+            } else {
+                write!(out, "  ")?;
+            }
+        }
+        
+        // Handle line numbering
+        if config.number_nonblank {
+            if !is_blank {
+                *line_num = if self.sampling { source_line } else { *line_num + 1 };
+                write!(out, "{}{:>6}{} | ", config.colors.number, format_line_number(*line_num, config), config.colors.reset)?;
+            } else {
+                write!(out, "       | ")?;
+            }
+        } else if config.show_line_numbers {
+            if config.skip_blank_numbers && is_blank {
+                // Same gutter as -n, but blanks don't use up a number
+                write!(out, "       | ")?;
+            } else {
+                *line_num = if self.sampling { source_line } else { *line_num + 1 };
+                write!(out, "{}{:>6}{} | ", config.colors.number, format_line_number(*line_num, config), config.colors.reset)?;
+            }
+        }
+        
+        let bracket_paints = self.brackets.as_mut().map_or_else(Vec::new, |brackets| brackets.take(source_line));
+        
+        // Fields are cut after grep has seen the whole line
+        let line = match &config.fields {
+            Some(ranges) => select_fields(&line, ranges, config),
+            None => line,
+        };
+        
+        // Process and print the line
+        let mut output_line = String::new();
+        
+        let style = config.nonprint_style.as_str();
+        let shows_cr = config.show_ends && line.contains('\r');
+        if config.show_all_nonprinting {
+            // Show non-printing characters
+            for c in line.chars() {
+                if c.is_control() && c != '\t' || c == '\t' && config.show_tabs {
+                    output_line.push_str(&show_control(c, style));
+                } else {
+                    output_line.push(c);
+                }
+            }
+        } else {
+            // Normal printing with tab handling
+            if config.show_tabs {
+                output_line = line.replace('\t', &show_control('\t', style));
+            } else {
+                output_line = line;
+            }
+            // With -E a CR would hide behind the end marker (or overwrite the line), like -A show it
+            if shows_cr {
+                output_line = output_line.replace('\r', &show_control('\r', style));
+            }
+        }
+        
+        // Turn the line into a string literal for embedding
+        if let Some(mode) = &config.escape_mode {
+            output_line = escape_line(&output_line, mode);
+        }
+        
+        // --truncate-middle: --grep and friends have already seen the whole line
+        let truncated = match config.truncate_middle.and_then(|width| columns::truncate_middle(&output_line, width, config.tab_width)) {
+            Some(shortened) => {
+                output_line = shortened;
+                true
+            },
+            None => false,
+        };
+        
+        // Substitution spans only line up while the text is unchanged
+        let reshaped = config.show_all_nonprinting || config.show_tabs || config.escape_mode.is_some() || config.fields.is_some()
+            || shows_cr || truncated;
+        let env_spans = if reshaped { &[][..] } else { env_spans };
+        // Brackets were found before --expand-env, --redact and --strip-comments had their go
+        let brackets_moved = bracket_paints.iter().any(|&(at, _)| !matches!(output_line.as_bytes().get(at), Some(b'(' | b')' | b'[' | b']' | b'{' | b'}')));
+        let bracket_paints = if reshaped || brackets_moved { Vec::new() } else { bracket_paints };
+        
+        // --max-col: the rest is written separately, after the coloring below
+        let overflow = config.max_col
+            .and_then(|max_col| columns::column_end(&output_line, max_col, config.tab_width))
+            .map(|end| output_line.split_off(end));
+        let env_spans: Vec<EnvSpan> = env_spans.iter().copied().filter(|&(_, end, _)| end <= output_line.len()).collect();
+        
+        // Highlight literal and regex matches if specified
+        let mut rendered = if config.changed_lines.contains(&source_line) {
+            format!("{}{}{}", config.colors.highlight, output_line, config.colors.reset)
+        } else if config.highlight_pattern.is_some() || config.highlight_regex.is_some() || config.highlight_field.is_some()
+            || !env_spans.is_empty() || !config.rules.is_empty() || !bracket_paints.is_empty() || self.heat.is_some()
+            || config.grep_fuzzy.is_some() {
+            render_highlights(&output_line, &env_spans, &bracket_paints, self.heat.as_ref(), config)
+        } else if let Some(format) = &config.structured {
+            structured::colorize(&output_line, format, &config.colors)
+        } else if config.rainbow_mode {
+            // Rainbow mode - colorize each character, in a smooth gradient where the terminal can
+            rainbow(&output_line, config.color_depth, &config.colors)
+        } else {
+            output_line.clone()
+        };
+        
+        if let Some(overflow) = overflow {
+            if config.use_colors {
+                rendered.push_str(&format!("{}{}{}", config.colors.error, overflow, config.colors.reset));
+            } else {
+                rendered.push('>');
+                rendered.push_str(&overflow);
+            }
+            output_line.push_str(&overflow);
+        }
+        
+        // --preserve-ansi: the gutter reset the colors the text started in, and
+        // the next one shouldn't get the colors it ends in
+        if let Some(ansi) = &self.ansi {
+            rendered.insert_str(0, &ansi.restore());
+            if ansi.open() {
+                rendered.push_str("\x1B[0m");
+            }
+        }
+        
+        // --fold: continuation rows start below the text, past the gutter
+        match config.fold {
+            Some(width) => {
+                let continuation = format!("\n{}", " ".repeat(self.gutter_width() + config.fold_indent));
+                write!(out, "{}", columns::fold_words(&rendered, width, config.fold_indent, config.tab_width).join(&continuation))?;
+            },
+            None => write!(out, "{}", rendered)?,
+        }
+        
+        // Show line length if requested
+        if config.show_line_length {
+            write!(out, " {}[{}L, {}]{}", 
+                   config.colors.normal, 
+                   output_line.lines().count(), 
+                   length_text(&output_line),
+                   config.colors.reset)?;
+        }
+        
+        // Show end of line marker
+        if config.show_ends {
+            write!(out, "{}${}",
+                  if config.use_colors { config.colors.highlight.clone() } else { "".to_string() },
+                  config.colors.reset)?;
+        }
+        
+        match notes.flatten() {
+            Some(notes) if config.annotations_block => {
+                writeln!(out)?;
+                let indent = " ".repeat(self.gutter_width());
+                for (i, note) in notes.iter().enumerate() {
+                    let end = if i + 1 == notes.len() { "" } else { "\n" };
+                    write!(out, "{}{}\u{2514} {}{}{}", indent, config.colors.highlight, note, config.colors.reset, end)?;
+                }
+            },
+            Some(notes) => write!(out, "  {}\u{2190} {}{}", config.colors.highlight, notes.join("; "), config.colors.reset)?,
+            None => {},
+        }
+        
+        match (config.shell_quote_array, config.null_records) {
+            // Ended once, after the last word
+            (true, _) => {},
+            (false, true) => out.write_all(b"\0")?,
+            (false, false) => writeln!(out)?,
+        }
+        
+        Ok(())
+    }
+    
+    /// The two `--ruler` rows, shifted past the gutter so they line up with the text.
+    fn write_ruler(&self, out: &mut dyn Write) -> io::Result<()> {
+        let config = self.config;
+        let gutter = self.gutter_width();
+        let width = terminal_width().saturating_sub(gutter).max(config.max_col.map_or(10, |max_col| max_col + 1));
+        for row in columns::ruler(width, config.max_col, &config.colors) {
+            writeln!(out, "{}{}", " ".repeat(gutter), row)?;
+        }
+        Ok(())
+    }
+    
+    /// Columns taken by everything `write_line` puts before the text.
+    fn gutter_width(&self) -> usize {
+        let config = self.config;
+        let mut width = 0;
+        if config.with_filename {
+            width += columns::display_width(self.file_name) + 1;
+        }
+        if self.blame.is_some() {
+            width += self.blame_width + 10;
+        }
+        if self.changes.is_some() {
+            width += 2;
+        }
+        if config.annotations.is_some() {
+            width += 2;
+        }
+        if config.rules.iter().any(|rule| rule.action == Some(rules::Action::Mark)) {
+            width += 2;
+        }
+        if config.mark_dupes {
+            width += 2;
+        }
+        if config.number_nonblank || config.show_line_numbers {
+            width += 9;
+        }
+        width
+    }
+}
+
+/// Like `BufRead::lines`, but a line longer than `max_bytes` comes back as several
+/// chunks instead of being buffered whole, so a file without newlines can't eat
+/// all the memory. `split` records whether that happened.
+struct CappedLines<R> {
+    reader: R,
+    max_bytes: Option<usize>,
+    /// Bytes read for the next line: the start of a UTF-8 sequence cut off
+    /// by the previous chunk, or those read past it looking for a separator
+    carry: Vec<u8>,
+    split: bool,
+    /// Leave the CR of CRLF line ends in the line, for -E and -A to show
+    keep_cr: bool,
+    /// What ends a line, `--record-separator`
+    separator: Vec<u8>,
+}
+
+impl<R: BufRead> CappedLines<R> {
+    fn new(reader: R, max_bytes: Option<usize>) -> Self {
+        CappedLines { reader, max_bytes, carry: Vec::new(), split: false, keep_cr: false, separator: vec![b'\n'] }
+    }
+    
+    /// CRLF only ends lines when they are split at newlines
+    fn strip_cr(&self, line: &mut Vec<u8>) {
+        if self.separator == b"\n" && line.ends_with(b"\r") && !self.keep_cr {
+            line.pop();
+        }
+    }
+    
+    /// Reads the rest of the character `line` starts with, stopping early at a
+    /// byte that can't continue it.
+    fn complete_char(&mut self, line: &mut Vec<u8>) {
+        while std::str::from_utf8(line).is_err_and(|err| err.error_len().is_none()) {
+            match self.reader.fill_buf() {
+                Ok([byte, ..]) if byte & 0xC0 == 0x80 => {
+                    line.push(*byte);
+                    self.reader.consume(1);
+                },
+                _ => break,
+            }
+        }
+    }
+    
+    /// Whether a separator that started in `line`, or starts right after it,
+    /// ends within the next bytes; if so it's taken off `line` and read past.
+    /// The bytes read ahead that aren't part of it go to the next line.
+    fn separator_across_cap(&mut self, line: &mut Vec<u8>) -> bool {
+        let length = self.separator.len();
+        let mut ahead = Vec::new();
+        while ahead.len() < length {
+            match self.reader.fill_buf() {
+                Ok([]) | Err(_) => break,
+                Ok(next) => {
+                    let count = next.len().min(length - ahead.len());
+                    ahead.extend_from_slice(&next[..count]);
+                    self.reader.consume(count);
+                },
+            }
+        }
+        // The earliest start wins
+        let started = (0..length).rev().find(|&k| line.ends_with(&self.separator[..k]) && ahead.starts_with(&self.separator[k..]));
+        match started {
+            Some(k) => {
+                line.truncate(line.len() - k);
+                self.carry = ahead.split_off(length - k);
+                true
+            },
+            None => {
+                self.carry = ahead;
+                false
+            },
+        }
+    }
+    
+    /// How many bytes of `available` to take to include the separator, which may
+    /// have started in `line` already.
+    fn separator_end(separator: &[u8], line: &[u8], available: &[u8]) -> Option<usize> {
+        if let [byte] = separator {
+            return available.iter().position(|b| b == byte).map(|pos| pos + 1);
+        }
+        let overlap = line.len().min(separator.len() - 1);
+        let mut window = line[line.len() - overlap..].to_vec();
+        window.extend_from_slice(available);
+        window.windows(separator.len()).position(|candidate| candidate == separator).map(|pos| pos + separator.len() - overlap)
+    }
+}
+
+impl<R: BufRead> Iterator for CappedLines<R> {
+    type Item = io::Result<String>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let limit = self.max_bytes.unwrap_or(usize::MAX);
+        let mut line = std::mem::take(&mut self.carry);
+        
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Some(Err(err)),
+            };
+            if available.is_empty() {
+                if line.is_empty() {
+                    return None;
+                }
+                break;
+            }
+            
+            let take = available.len().min(limit.saturating_sub(line.len()));
+            // A separator that starts by the cap ends the line even if it goes on past it
+            let search = available.len().min(take + self.separator.len());
+            if let Some(end) = Self::separator_end(&self.separator, &line, &available[..search]) {
+                line.extend_from_slice(&available[..end]);
+                self.reader.consume(end);
+                line.truncate(line.len() - self.separator.len());
+                self.strip_cr(&mut line);
+                break;
+            }
+            line.extend_from_slice(&available[..take]);
+            self.reader.consume(take);
+            
+            if line.len() >= limit {
+                // Don't cut a character in half: its start moves to the next chunk,
+                // or the chunk is the whole character when the cap is smaller
+                if let Err(err) = std::str::from_utf8(&line) {
+                    if err.error_len().is_none() {
+                        if err.valid_up_to() > 0 {
+                            self.carry = line.split_off(err.valid_up_to());
+                        } else {
+                            self.complete_char(&mut line);
+                        }
+                    }
+                }
+                // The read ended by the cap: a separator may still start in the
+                // line's last bytes, or right at the cap, and end in what comes next
+                if self.carry.is_empty() && self.separator_across_cap(&mut line) {
+                    self.strip_cr(&mut line);
+                    break;
+                }
+                self.split = true;
+                break;
+            }
+        }
+        
+        Some(String::from_utf8(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)))
+    }
+}
+
+/// Lines longer than this are keyed and reported by their hash instead of the text
+const DUPE_HASH_THRESHOLD: usize = 200;
+
+/// Where each distinct line of an input occurs, for `--find-dupes`. Memory grows
+/// with the number of unique lines; blank lines are not tracked.
+#[derive(Default)]
+struct DupeTracker {
+    seen: HashMap<String, Vec<usize>>,
+}
+
+impl DupeTracker {
+    /// Records a line and returns whether it was seen before.
+    fn add(&mut self, line: &str, source_line: usize, config: &Config) -> bool {
+        if line.trim().is_empty() {
+            return false;
+        }
+        
+        let mut key = if config.dupes_ignore_whitespace {
+            line.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            line.to_string()
+        };
+        if config.dupes_ignore_case {
+            key = key.to_lowercase();
+        }
+        if key.len() > DUPE_HASH_THRESHOLD {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            key = msg!("dupes-hashed", format!("{:016x}", hasher.finish()), key.chars().count());
+        }
+        
+        let lines = self.seen.entry(key).or_default();
+        lines.push(source_line);
+        lines.len() > 1
+    }
+    
+    /// Prints the duplicated lines to stderr, most frequent first. The count is
+    /// colored by how often the line repeats.
+    fn report(&self, file_name: &str, config: &Config) {
+        let mut dupes: Vec<(&String, &Vec<usize>)> = self.seen.iter().filter(|(_, lines)| lines.len() > 1).collect();
+        if dupes.is_empty() {
+            eprintln!("meow: {}", msg!("dupes-none", file_name));
+            return;
+        }
+        dupes.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.1[0].cmp(&b.1[0])));
+        
+        eprintln!("meow: {}", msg!("dupes-found", file_name, dupes.len()));
+        for (text, lines) in dupes {
+            let color = match lines.len() {
+                2 => &config.colors.highlight,
+                3..=9 => &config.colors.number,
+                _ => &config.colors.error,
+            };
+            let mut numbers: Vec<String> = lines.iter().take(10).map(|n| n.to_string()).collect();
+            if lines.len() > 10 {
+                numbers.push("...".to_string());
+            }
+            eprintln!("{}{:>6}x{}  {}  ({})", color, lines.len(), config.colors.reset, text, msg!("dupes-lines", numbers.join(", ")));
+        }
+    }
+}
+
+/// Tallies leading whitespace the way editors guess a file's indentation:
+/// tab- vs space-indented lines, and how far the indent steps between lines.
+#[derive(Default)]
+struct IndentStats {
+    tab_lines: usize,
+    space_lines: usize,
+    /// Occurrences of each indent step between consecutive space-indented lines
+    steps: HashMap<usize, usize>,
+    prev_spaces: usize,
+}
+
+impl IndentStats {
+    fn add_line(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        
+        if line.starts_with('\t') {
+            self.tab_lines += 1;
+            return;
+        }
+        
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        if spaces > 0 {
+            self.space_lines += 1;
+        }
+        if spaces != self.prev_spaces {
+            *self.steps.entry(spaces.abs_diff(self.prev_spaces)).or_insert(0) += 1;
+        }
+        self.prev_spaces = spaces;
+    }
+    
+    fn describe(&self) -> String {
+        if self.tab_lines == 0 && self.space_lines == 0 {
+            return msg!("indent-none");
+        }
+        if self.tab_lines >= self.space_lines {
+            return msg!("indent-tabs");
+        }
+        
+        // Most frequent step wins; ties go to the smaller width
+        let width = self.steps.iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map_or(0, |(&width, _)| width);
+        msg!("indent-spaces", width)
+    }
+}
+
+/// Guesses the comment syntax from a file name for `--comment-style=auto`.
+/// Unknown types are left alone rather than risk eating `#` headings and the like.
+fn comment_style_for(file_name: &str) -> Option<&'static str> {
+    let path = Path::new(file_name);
+    let name = path.file_name()?.to_string_lossy();
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    
+    match extension.as_deref() {
+        Some("sh" | "bash" | "zsh" | "fish" | "py" | "rb" | "pl" | "r" | "toml" | "yaml" | "yml"
+             | "conf" | "cfg" | "env" | "properties" | "mk" | "cmake" | "tf" | "nix") => Some("hash"),
+        Some("c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "rs" | "go" | "java" | "kt" | "scala" | "swift"
+             | "js" | "mjs" | "ts" | "jsx" | "tsx" | "jsonc" | "json5" | "css" | "scss" | "less" | "php" | "dart") => Some("slash"),
+        Some("ini" | "asm" | "s" | "lisp" | "el" | "clj" | "scm" | "rkt") => Some("semicolon"),
+        Some(_) => None,
+        // Makefile, Dockerfile and dotfiles like .gitignore or .env
+        None if name.starts_with('.') || ["Makefile", "Dockerfile", "Containerfile", "Gemfile", "Rakefile"].contains(&name.as_ref()) => Some("hash"),
+        None => None,
+    }
+}
+
+/// Removes the comment from a line. Returns None for lines that held only a
+/// comment, so they can be dropped instead of shown as blank.
+///
+/// This is a heuristic, not a parser: quotes are tracked so `"#"` and `'//'`
+/// survive, but heredocs, raw strings, regex literals and Rust lifetimes can
+/// confuse it. `#` and `;` only start a comment at the beginning of the line or
+/// after whitespace, so `url#anchor` is kept.
+fn strip_comment(line: &str, style: &str, in_block: &mut bool) -> Option<String> {
+    let mut kept = String::with_capacity(line.len());
+    let mut commented = false;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut chars = line.char_indices().peekable();
+    
+    while let Some((i, c)) = chars.next() {
+        let rest = &line[i..];
+        
+        // Inside /* ... */, possibly opened on an earlier line
+        if *in_block {
+            commented = true;
+            if rest.starts_with("*/") {
+                *in_block = false;
+                chars.next();
+            }
+            continue;
+        }
+        
+        if let Some(q) = quote {
+            kept.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        
+        let at_word_start = kept.chars().next_back().is_none_or(char::is_whitespace);
+        let starts_comment = match style {
+            "hash" => c == '#' && at_word_start,
+            "semicolon" => c == ';' && at_word_start,
+            _ => rest.starts_with("//"),
+        };
+        if starts_comment {
+            commented = true;
+            break;
+        }
+        if style == "slash" && rest.starts_with("/*") {
+            *in_block = true;
+            commented = true;
+            chars.next();
+            continue;
+        }
+        
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+        }
+        kept.push(c);
+    }
+    
+    if !commented {
+        Some(kept)
+    } else if kept.trim().is_empty() {
+        None
+    } else {
+        Some(kept.trim_end().to_string())
+    }
+}
+
+/// Parses a `--fields` list like `1,3-5,7-`. Open starts begin at 1, open ends
+/// run to the last field.
+fn parse_field_list(list: &str) -> Option<Vec<(usize, usize)>> {
+    let parse = |n: &str, default: usize| if n.is_empty() { Some(default) } else { n.parse::<usize>().ok().filter(|&n| n > 0) };
+    
+    list.split(',')
+        .map(|part| {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) if !(start.is_empty() && end.is_empty()) => (parse(start, 1)?, parse(end, usize::MAX)?),
+                Some(_) => return None,
+                None => {
+                    let n = parse(part, 0).filter(|&n| n > 0)?;
+                    (n, n)
+                }
+            };
+            (start <= end).then_some((start, end))
+        })
+        .collect()
+}
+
+/// Cuts a line down to the `--fields` selection, keeping the input order like `cut`.
+/// Missing fields are simply left out.
+fn select_fields(line: &str, ranges: &[(usize, usize)], config: &Config) -> String {
+    let fields: Vec<&str> = match (&config.delimiter, config.csv) {
+        (delimiter, true) => split_csv(line, delimiter.as_deref().unwrap_or(",")),
+        (Some(delimiter), false) => line.split(delimiter.as_str()).collect(),
+        (None, false) => line.split_whitespace().collect(),
+    };
+    let output_delimiter = config.output_delimiter.as_deref()
+        .or(config.delimiter.as_deref())
+        .unwrap_or(if config.csv { "," } else { " " });
+    
+    fields.iter()
+        .enumerate()
+        .filter(|(i, _)| ranges.iter().any(|&(start, end)| (start..=end).contains(&(i + 1))) != config.fields_invert)
+        .map(|(_, field)| *field)
+        .collect::<Vec<&str>>()
+        .join(output_delimiter)
+}
+
+/// `--highlight-field`: the byte range of field `n` (from 1), split like
+/// `--fields` but on tabs when there's no `--delimiter`. None past the last field.
+fn field_span(line: &str, n: usize, config: &Config) -> Option<(usize, usize)> {
+    let field = match (&config.delimiter, config.csv) {
+        (delimiter, true) => split_csv(line, delimiter.as_deref().unwrap_or(",")).into_iter().nth(n - 1),
+        (Some(delimiter), false) => line.split(delimiter.as_str()).nth(n - 1),
+        (None, false) => line.split('\t').nth(n - 1),
+    }?;
+    // Fields are slices of the line, so where they start is how far in they are
+    let start = field.as_ptr() as usize - line.as_ptr() as usize;
+    Some((start, start + field.len()))
+}
+
+/// Splits a CSV record on `delimiter` outside of double quotes. Fields keep
+/// their quotes so the joined output is still valid CSV.
+fn split_csv<'a>(line: &'a str, delimiter: &str) -> Vec<&'a str> {
+    let mut fields = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let mut i = 0;
+    
+    while i < line.len() {
+        if line[i..].starts_with('"') {
+            // A doubled quote inside quotes toggles twice and stays quoted
+            in_quotes = !in_quotes;
+            i += 1;
+        } else if !in_quotes && line[i..].starts_with(delimiter) {
+            fields.push(&line[start..i]);
+            i += delimiter.len();
+            start = i;
+        } else {
+            i += line[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+/// `--record-separator`: the bytes of SEP, where `\n`, `\r`, `\t`, `\0`, `\\`
+/// and `\xHH` stand for what they do in C. Other backslashes are kept.
+fn parse_separator(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        rest = after;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let escaped = match rest.first() {
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'0') => 0,
+            Some(b'\\') => b'\\',
+            Some(b'x') => match rest.get(1..3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()) {
+                Some(value) => {
+                    rest = &rest[2..];
+                    value
+                },
+                None => {
+                    bytes.push(byte);
+                    continue;
+                }
+            },
+            _ => {
+                bytes.push(byte);
+                continue;
+            }
+        };
+        rest = &rest[1..];
+        bytes.push(escaped);
+    }
+    bytes
+}
+
+/// Escapes a line as a quoted literal for a shell script, C source, or JSON.
+fn escape_line(line: &str, mode: &str) -> String {
+    if mode == "shell" {
+        return shell_quote(line);
+    }
+    
+    let mut escaped = String::with_capacity(line.len() + 2);
+    escaped.push('"');
+    for c in line.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            // Octal escapes can't swallow following hex digits the way \x can in C
+            c if c.is_control() && mode == "c" => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Collects the byte ranges to highlight from the literal pattern and the regex,
+/// merging overlapping or touching spans so each run gets a single color code.
+fn highlight_spans(line: &str, config: &Config) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    
+    if let Some(pattern) = &config.highlight_pattern {
+        if !pattern.is_empty() {
+            spans.extend(line.match_indices(pattern.as_str()).map(|(start, m)| (start, start + m.len())));
+        }
+    }
+    if let Some(re) = &config.highlight_regex {
+        spans.extend(re.find_iter(line).filter(|m| !m.is_empty()).map(|m| (m.start(), m.end())));
+    }
+    
+    spans.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// `--rainbow`: each character of `line` in the next hue of a smooth gradient,
+/// or of the six basic colors where the terminal has no more. A character
+/// is a grapheme cluster, so an accent or an emoji sequence keeps one color.
+fn rainbow(line: &str, depth: Depth, colors: &ColorConfig) -> String {
+    let step = if depth == Depth::Ansi16 { 60 } else { 20 };
+    let paint = |(i, text): (usize, &str)| format!("{}{}{}", color::fg(color::hue((i % 360) as u32 * step), depth), text, colors.reset);
+    // ASCII has a cluster per byte
+    if line.is_ascii() {
+        line.char_indices().map(|(i, _)| paint((i, &line[i..=i]))).collect()
+    } else {
+        columns::graphemes(line).enumerate().map(paint).collect()
+    }
+}
+
+/// `-l`: the characters in `line`, and the columns they take when that's a
+/// different number, as wide and zero-width characters make it.
+fn length_text(line: &str) -> String {
+    if line.is_ascii() {
+        return format!("{}C", line.len());
+    }
+    let characters = columns::graphemes(line).count();
+    let width = columns::display_width(line);
+    if width == characters {
+        format!("{}C", characters)
+    } else {
+        format!("{}C, {}W", characters, width)
+    }
+}
+
+/// Colors the `--rules` matches, the highlight matches, the `--match-brackets`
+/// brackets, the `--number-heat` numbers and the `--expand-env` substitutions:
+/// defined ones like highlights, undefined ones in the error color under strict mode.
+fn render_highlights(line: &str, env_spans: &[EnvSpan], bracket_paints: &[(usize, brackets::Paint)], heat: Option<&numbers::Heat>, config: &Config) -> String {
+    let mut spans = rules::apply(&config.rules, line).spans;
+    spans.extend(highlight_spans(line, config).into_iter().map(|(start, end)| (start, end, config.colors.highlight.as_str())));
+    if let Some(fuzzy) = &config.grep_fuzzy {
+        spans.extend(fuzzy.find_iter(line).into_iter().map(|found| (found.start, found.end, config.colors.highlight.as_str())));
+    }
+    if let Some((start, end)) = config.highlight_field.and_then(|n| field_span(line, n, config)).filter(|(start, end)| start < end) {
+        spans.push((start, end, &config.colors.highlight));
+    }
+    for &(start, end, defined) in env_spans {
+        if defined {
+            spans.push((start, end, &config.colors.highlight));
+        } else if config.env_strict {
+            spans.push((start, end, &config.colors.error));
+        }
+    }
+    spans.extend(bracket_paints.iter().map(|(at, paint)| (*at, at + 1, paint.color(config))));
+    if let Some(heat) = heat {
+        spans.extend(heat.spans(line, config));
+    }
+    // Stable, so at the same start the rules come first
+    spans.sort_by_key(|&(start, _, _)| start);
+    
+    let mut rendered = String::with_capacity(line.len());
+    let mut pos = 0;
+    for (start, end, color) in spans {
+        // Overlaps keep the earlier span
+        if start < pos {
+            continue;
+        }
+        rendered.push_str(&line[pos..start]);
+        rendered.push_str(color);
+        rendered.push_str(&line[start..end]);
+        rendered.push_str(&config.colors.reset);
+        pos = end;
+    }
+    rendered.push_str(&line[pos..]);
+    rendered
+}
+
+/// Small xorshift generator for random sampling; quality is plenty for skimming.
+struct XorShift(u64);
+
+impl XorShift {
+    fn from_seed(seed: u64) -> Self {
+        // Spread small seeds over all bits; the state must not be zero
+        XorShift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+    
+
+    fn from_time() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+        XorShift(seed | 1)
+    }
+    
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn animate_text(content: &str, out: &mut dyn Write) -> io::Result<()> {
+    for line in content.lines() {
+        for c in line.chars() {
+            write!(out, "{}", c)?;
+            out.flush()?;
+            thread::sleep(Duration::from_millis(10));
+        }
+        writeln!(out)?;
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+/// Per-line attribution parsed from `git blame --porcelain`.
+#[derive(Clone)]
+struct BlameLine {
+    hash: String,
+    initials: String,
+    time: u64,
+}
+
+/// Runs `git <args> -- <file>` from the file's directory so the repository is
+/// discovered relative to the file rather than the current directory.
+fn run_git_for_file(path: &Path, args: &[&str]) -> Option<String> {
+    let name = path.file_name()?;
+    
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(git_dir_for(path))
+        .args(args)
+        .arg("--")
+        .arg(name)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        None
+    }
+}
+
+/// Whether `git <args>` succeeds from the file's directory, for telling
+/// why another command failed.
+fn git_succeeds_for_file(path: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(git_dir_for(path))
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn git_dir_for(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn load_blame(file_name: &str, config: &Config) -> Option<Vec<Option<BlameLine>>> {
+    let path = Path::new(file_name);
+    if !path.is_file() {
+        if !config.quiet {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("blame-regular-only", file_name), config.colors.reset);
+        }
+        return None;
+    }
+    
+    match run_git_for_file(path, &["blame", "--porcelain"]) {
+        Some(output) => Some(parse_blame_porcelain(&output)),
+        None => {
+            if !config.quiet {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("blame-failed", file_name), config.colors.reset);
+            }
+            None
+        }
+    }
+}
+
+/// Parses porcelain blame output into a list indexed by final line number (0-based).
+/// Commit details are only printed the first time a commit appears, so they are
+/// remembered by hash for the following groups.
+fn parse_blame_porcelain(output: &str) -> Vec<Option<BlameLine>> {
+    let mut commits: HashMap<String, (String, u64)> = HashMap::new();
+    let mut lines: Vec<Option<BlameLine>> = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            // Content line terminates the entry for the current header
+            if let Some((hash, final_line)) = current.take() {
+                let (author, time) = commits.get(&hash).cloned().unwrap_or_default();
+                if lines.len() < final_line {
+                    lines.resize(final_line, None);
+                }
+                lines[final_line - 1] = Some(BlameLine {
+                    hash: hash[..8.min(hash.len())].to_string(),
+                    initials: author_initials(&author),
+                    time,
+                });
+            }
+            continue;
+        }
+        
+        let mut fields = line.split_whitespace();
+        let first = fields.next().unwrap_or("");
+        
+        if first.len() == 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            let final_line = fields.nth(1).and_then(|n| n.parse::<usize>().ok()).unwrap_or(0);
+            if final_line > 0 {
+                commits.entry(first.to_string()).or_default();
+                current = Some((first.to_string(), final_line));
+            }
+        } else if let Some((hash, _)) = &current {
+            let entry = commits.entry(hash.clone()).or_default();
+            if let Some(author) = line.strip_prefix("author ") {
+                entry.0 = author.to_string();
+            } else if let Some(time) = line.strip_prefix("author-time ") {
+                entry.1 = time.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    
+    lines
+}
+
+fn author_initials(author: &str) -> String {
+    author
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .flat_map(|c| c.to_uppercase())
+        .take(3)
+        .collect()
+}
+
+fn blame_gutter_width(lines: &[Option<BlameLine>]) -> usize {
+    lines
+        .iter()
+        .flatten()
+        .map(|entry| entry.initials.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+fn format_blame_gutter(entry: Option<&BlameLine>, width: usize, config: &Config) -> String {
+    match entry {
+        Some(entry) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+            let age = now.saturating_sub(entry.time);
+            let bucket = if age < 60 * 60 * 24 {
+                0
+            } else if age < 60 * 60 * 24 * 7 {
+                1
+            } else if age < 60 * 60 * 24 * 30 {
+                2
+            } else if age < 60 * 60 * 24 * 365 {
+                3
+            } else {
+                4
+            };
+            
+            format!("{}{:8} {:<width$}{} ",
+                    config.colors.blame_age[bucket],
+                    entry.hash,
+                    entry.initials,
+                    config.colors.reset,
+                    width = width)
+        },
+        None => format!("{:width$} ", "", width = width + 9),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// How many line comparisons `middle_snake` may make before giving up on a
+/// shortest edit script, so two big files that differ all over don't take
+/// minutes.
+const DIFF_WORK_LIMIT: isize = 1 << 27;
+
+/// Line diff with Myers' O(ND) algorithm in linear space, so two big files
+/// cost memory in proportion to their length rather than its square.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    diff_range(old, new, &mut ops);
+    ops
+}
+
+/// Appends the ops turning `old` into `new`. The common prefix and suffix are
+/// taken off first, so mostly-similar files only pay for the changed middle,
+/// which is split where its shortest edit script crosses the middle and each
+/// half diffed in turn.
+fn diff_range(old: &[&str], new: &[&str], ops: &mut Vec<DiffOp>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    
+    ops.extend(std::iter::repeat_n(DiffOp::Equal, prefix));
+    if old_mid.is_empty() || new_mid.is_empty() {
+        ops.extend(std::iter::repeat_n(DiffOp::Delete, old_mid.len()));
+        ops.extend(std::iter::repeat_n(DiffOp::Insert, new_mid.len()));
+    } else {
+        match middle_snake(old_mid, new_mid) {
+            Some((x, y)) => {
+                diff_range(&old_mid[..x], &new_mid[..y], ops);
+                diff_range(&old_mid[x..], &new_mid[y..], ops);
+            },
+            None => {
+                ops.extend(std::iter::repeat_n(DiffOp::Delete, old_mid.len()));
+                ops.extend(std::iter::repeat_n(DiffOp::Insert, new_mid.len()));
+            }
+        }
+    }
+    ops.extend(std::iter::repeat_n(DiffOp::Equal, suffix));
+}
+
+/// Where a shortest edit script from `old` to `new` crosses its middle, found by
+/// following the furthest-reaching paths from both ends at once until they
+/// meet (Myers, "An O(ND) Difference Algorithm and Its Variations", 4b).
+/// `v[k]` is the furthest `x` reached on diagonal `k = x - y`, counted from
+/// the start going forward and from the end going back. Both inputs must be
+/// non-empty, and differ in their first and in their last line. None when they
+/// differ too much to be worth the `DIFF_WORK_LIMIT`, and are best shown as
+/// replaced whole.
+fn middle_snake(old: &[&str], new: &[&str]) -> Option<(usize, usize)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d + 1;
+    let give_up = max_d.min((DIFF_WORK_LIMIT / (n + m)).max(1));
+    let mut forward = vec![-1isize; 2 * offset as usize + 1];
+    let mut backward = forward.clone();
+    forward[offset as usize + 1] = 0;
+    backward[offset as usize + 1] = 0;
+    // With an odd difference in length the forward paths meet the backward
+    // ones, else the other way round
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    // Diagonals that have run off the edge of the grid aren't followed again
+    let (mut forward_start, mut forward_end, mut backward_start, mut backward_end) = (0, 0, 0, 0);
+    
+    for d in 0..give_up {
+        let mut k = -d + forward_start;
+        while k <= d - forward_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && forward[i - 1] < forward[i + 1]) { forward[i + 1] } else { forward[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[i] = x;
+            if x > n {
+                forward_end += 2;
+            } else if y > m {
+                forward_start += 2;
+            } else if odd {
+                let j = offset + delta - k;
+                if (0..backward.len() as isize).contains(&j) && backward[j as usize] != -1 && x >= n - backward[j as usize] {
+                    return Some((x as usize, y as usize));
+                }
+            }
+            k += 2;
+        }
+        
+        let mut k = -d + backward_start;
+        while k <= d - backward_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && backward[i - 1] < backward[i + 1]) { backward[i + 1] } else { backward[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[i] = x;
+            if x > n {
+                backward_end += 2;
+            } else if y > m {
+                backward_start += 2;
+            } else if !odd {
+                let j = offset + delta - k;
+                if (0..forward.len() as isize).contains(&j) && forward[j as usize] != -1 {
+                    let x = forward[j as usize];
+                    let y = x - (j - offset);
+                    if x >= n - backward[i] {
+                        return Some((x as usize, y as usize));
+                    }
+                }
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+/// Prints a colored unified diff with three lines of context around each change.
+fn render_diff(old: &[&str], new: &[&str], old_name: &str, new_name: &str, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    const CONTEXT: usize = 3;
+    let ops = diff_lines(old, new);
+    
+    if ops.iter().all(|op| *op == DiffOp::Equal) {
+        writeln!(out, "{}{}{}", config.colors.success, msg!("diff-identical"), config.colors.reset)?;
+        return Ok(());
+    }
+    
+    writeln!(out, "{}--- {}{}", config.colors.error, old_name, config.colors.reset)?;
+    writeln!(out, "{}+++ {}{}", config.colors.success, new_name, config.colors.reset)?;
+    
+    // Positions in old/new before each op, used for hunk headers
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for op in &ops {
+        positions.push((old_pos, new_pos));
+        match op {
+            DiffOp::Equal => {
+                old_pos += 1;
+                new_pos += 1;
+            },
+            DiffOp::Delete => old_pos += 1,
+            DiffOp::Insert => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+    
+    let mut index = 0;
+    while index < ops.len() {
+        if ops[index] == DiffOp::Equal {
+            index += 1;
+            continue;
+        }
+        
+        // Extend the hunk while changes are closer than twice the context
+        let start = index.saturating_sub(CONTEXT);
+        let mut end = index;
+        let mut equal_run = 0;
+        while end < ops.len() && equal_run <= CONTEXT * 2 {
+            equal_run = if ops[end] == DiffOp::Equal { equal_run + 1 } else { 0 };
+            end += 1;
+        }
+        end -= equal_run.saturating_sub(CONTEXT);
+        
+        // Empty ranges point at the line before them, like diff -u
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let old_count = old_end - old_start;
+        let new_count = new_end - new_start;
+        writeln!(out, "{}@@ -{},{} +{},{} @@{}",
+                 config.colors.highlight,
+                 old_start + (old_count > 0) as usize, old_count,
+                 new_start + (new_count > 0) as usize, new_count,
+                 config.colors.reset)?;
+        
+        for (op, &(old_index, new_index)) in ops[start..end].iter().zip(&positions[start..end]) {
+            match op {
+                DiffOp::Equal => writeln!(out, " {}", old[old_index])?,
+                DiffOp::Delete => writeln!(out, "{}-{}{}", config.colors.error, old[old_index], config.colors.reset)?,
+                DiffOp::Insert => writeln!(out, "{}+{}{}", config.colors.success, new[new_index], config.colors.reset)?,
+            }
+        }
+        
+        index = end;
+    }
+    
+    Ok(())
+}
+
+/// `recorder` is the `--record` file: what the shell shows and the commands
+/// typed into it go there too.
+fn interactive_shell(config: &Config, mut recorder: Option<record::Recorder>) {
+    let mut command_history: Vec<String> = Vec::new();
+    let mut out = record::ShellOutput::new(recorder.as_mut());
+    
+    let result = write!(out, "\n{}{}{}\n{}\n\n", config.colors.success, msg!("shell-title"), config.colors.reset, msg!("shell-intro"));
+    report_write_error(result, config);
+    
+    loop {
+        let result = write!(out, "{}meow>{} ", config.colors.success, config.colors.reset).and_then(|()| out.flush());
+        report_write_error(result, config);
+        
+        let mut input = String::new();
+        // Stop on EOF (Ctrl-D) as well as on read errors
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {},
+        }
+        if let Some(recorder) = out.recorder.as_mut() {
+            recorder.command(input.trim_end_matches(['\r', '\n']));
+        }
+        
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+        
+        command_history.push(input.to_string());
+        
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        
+        if parts.is_empty() {
+            continue;
+        }
+        if matches!(parts[0], "exit" | "quit") {
+            break;
+        }
+        
+        let result = shell_command(&parts, &command_history, config, &mut out);
+        report_write_error(result, config);
+    }
+}
+
+/// Runs one command typed into the interactive shell, writing what it shows to `out`.
+fn shell_command(parts: &[&str], command_history: &[String], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    match parts[0] {
+        "help" => {
+            writeln!(out, "{}", msg!("shell-commands"))?;
+            writeln!(out, "{}", msg!("shell-help-cat"))?;
+            writeln!(out, "{}", msg!("shell-help-grep"))?;
+            writeln!(out, "{}", msg!("shell-help-highlight"))?;
+            writeln!(out, "{}", msg!("shell-help-rainbow"))?;
+            writeln!(out, "{}", msg!("shell-help-diff"))?;
+            writeln!(out, "{}", msg!("shell-help-view"))?;
+            writeln!(out, "{}", msg!("shell-help-edit"))?;
+            writeln!(out, "{}", msg!("shell-help-history"))?;
+            writeln!(out, "{}", msg!("shell-help-exit"))?;
+        },
+        "cat" => {
+            if parts.len() < 2 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "cat <file>"), config.colors.reset);
+            }
+            
+            if let Ok(file) = File::open(parts[1]) {
+                let mut reader = BufReader::new(file);
+                process_input(&mut reader, config, parts[1], &mut 0, &mut RunState::default(), out)?;
+            } else {
+                writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[1]), config.colors.reset)?;
+            }
+        },
+        "grep" => {
+            if parts.len() < 3 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "grep <pattern> <file>"), config.colors.reset);
+            }
+            
+            if let Ok(file) = File::open(parts[2]) {
+                let mut local_config = config.clone();
+                local_config.grep_pattern = Some(parts[1].to_string());
+                let mut reader = BufReader::new(file);
+                process_input(&mut reader, &local_config, parts[2], &mut 0, &mut RunState::default(), out)?;
+            } else {
+                writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[2]), config.colors.reset)?;
+            }
+        },
+        "highlight" => {
+            if parts.len() < 3 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "highlight <pattern> <file>"), config.colors.reset);
+            }
+            
+            if let Ok(file) = File::open(parts[2]) {
+                let mut local_config = config.clone();
+                local_config.highlight_pattern = Some(parts[1].to_string());
+                let mut reader = BufReader::new(file);
+                process_input(&mut reader, &local_config, parts[2], &mut 0, &mut RunState::default(), out)?;
+            } else {
+                writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[2]), config.colors.reset)?;
+            }
+        },
+        "rainbow" => {
+            if parts.len() < 2 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "rainbow <file>"), config.colors.reset);
+            }
+            
+            if let Ok(file) = File::open(parts[1]) {
+                let mut local_config = config.clone();
+                local_config.rainbow_mode = true;
+                let mut reader = BufReader::new(file);
+                process_input(&mut reader, &local_config, parts[1], &mut 0, &mut RunState::default(), out)?;
+            } else {
+                writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[1]), config.colors.reset)?;
+            }
+        },
+        "diff" => {
+            if parts.len() < 3 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "diff <file1> <file2>"), config.colors.reset);
+            }
+            
+            let old = match fs::read_to_string(parts[1]) {
+                Ok(content) => content,
+                Err(_) => return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[1]), config.colors.reset),
+            };
+            let new = match fs::read_to_string(parts[2]) {
+                Ok(content) => content,
+                Err(_) => return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[2]), config.colors.reset),
+            };
+            
+            let old_lines: Vec<&str> = old.lines().collect();
+            let new_lines: Vec<&str> = new.lines().collect();
+            render_diff(&old_lines, &new_lines, parts[1], parts[2], config, out)?;
+        },
+        "view" => {
+            if parts.len() < 3 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "view <name> <file>"), config.colors.reset);
+            }
+            
+            let args = match settings::expand_views(&[String::new(), format!("@{}", parts[1])], &config.views) {
+                Ok(args) => args,
+                Err(err) => return writeln!(out, "{}{}{}", config.colors.error, view_error(&err, &config.views), config.colors.reset),
+            };
+            let mut local_config = config.clone();
+            if !local_config.parse_args(&args) {
+                return Ok(());
+            }
+            if let Ok(file) = File::open(parts[2]) {
+                let mut reader = BufReader::new(file);
+                process_input(&mut reader, &local_config, parts[2], &mut 0, &mut RunState::default(), out)?;
+            } else {
+                writeln!(out, "{}{}{}", config.colors.error, msg!("shell-open-error", parts[2]), config.colors.reset)?;
+            }
+        },
+        "edit" => {
+            if parts.len() < 2 {
+                return writeln!(out, "{}{}{}", config.colors.error, msg!("shell-usage", "edit <file>"), config.colors.reset);
+            }
+            
+            edit_in_shell(parts[1], config, out)?;
+        },
+        "history" => {
+            writeln!(out, "{}", msg!("shell-history"))?;
+            for (i, cmd) in command_history.iter().enumerate() {
+                writeln!(out, "  {}. {}", i + 1, cmd)?;
+            }
+        },
+        _ => {
+            writeln!(out, "{}{}{}", config.colors.error, msg!("shell-unknown-command", parts[0]), config.colors.reset)?;
+            writeln!(out, "{}", msg!("shell-unknown-hint"))?;
+        }
+    }
+    Ok(())
+}
+
+/// The shell's `edit` command: opens a file in $VISUAL/$EDITOR (vi, or nano
+/// if there's no vi, when neither is set) and waits for it to exit.
+fn edit_in_shell(file: &str, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let editors = match env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok().filter(|editor| !editor.trim().is_empty()) {
+        Some(editor) => vec![editor],
+        None => vec!["vi".to_string(), "nano".to_string()],
+    };
+    
+    for (i, editor) in editors.iter().enumerate() {
+        // $EDITOR may carry its own flags, e.g. "code --wait"
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or_default();
+        match Command::new(program).args(words).arg(file).status() {
+            Ok(status) if status.success() => {},
+            Ok(status) => writeln!(out, "{}{}{}", config.colors.error, msg!("editor-failed", program, status), config.colors.reset)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && i + 1 < editors.len() => continue,
+            Err(err) => writeln!(out, "{}{}{}", config.colors.error, msg!("cannot-launch-editor", program, err), config.colors.reset)?,
+        }
+        break;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum ChangeMark {
+    Added,
+    Modified,
+    Deleted,
+}
+
+fn load_changes(file_name: &str, config: &Config) -> Option<Vec<Option<ChangeMark>>> {
+    let path = Path::new(file_name);
+    if !path.is_file() {
+        if !config.quiet {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("changes-regular-only", file_name), config.colors.reset);
+        }
+        return None;
+    }
+    
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-U0"];
+    if let Some(reference) = &config.changes_ref {
+        args.push(reference);
+    }
+    
+    match run_git_for_file(path, &args) {
+        Some(output) => Some(parse_diff_hunks(&output)),
+        None => {
+            if !config.quiet {
+                // git's exit status is 128 for both, so ask it again
+                let reason = match &config.changes_ref {
+                    _ if !git_succeeds_for_file(path, &["rev-parse", "--git-dir"]) => msg!("changes-no-repo", file_name),
+                    Some(reference) if !git_succeeds_for_file(path, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", reference)]) => {
+                        msg!("changes-unknown-ref", file_name, reference)
+                    },
+                    _ => msg!("changes-failed", file_name),
+                };
+                eprintln!("{}meow: {}{}", config.colors.error, reason, config.colors.reset);
+            }
+            None
+        }
+    }
+}
+
+/// Maps `@@ -a,b +c,d @@` hunk headers to per-line markers indexed by new line number (0-based).
+/// Pure deletions have no lines of their own, so they are marked on the line above
+/// the removed block (or the first line when deleting from the top).
+fn parse_diff_hunks(diff: &str) -> Vec<Option<ChangeMark>> {
+    let mut marks: Vec<Option<ChangeMark>> = Vec::new();
+    
+    for line in diff.lines() {
+        let header = match line.strip_prefix("@@ ") {
+            Some(header) => header,
+            None => continue,
+        };
+        
+        let mut ranges = header.split_whitespace();
+        let old = ranges.next().and_then(|range| parse_hunk_range(range.strip_prefix('-')?));
+        let new = ranges.next().and_then(|range| parse_hunk_range(range.strip_prefix('+')?));
+        let ((_, old_count), (new_start, new_count)) = match (old, new) {
+            (Some(old), Some(new)) => (old, new),
+            _ => continue,
+        };
+        
+        let (first, count, mark) = if new_count == 0 {
+            (new_start.max(1), 1, ChangeMark::Deleted)
+        } else if old_count == 0 {
+            (new_start, new_count, ChangeMark::Added)
+        } else {
+            (new_start, new_count, ChangeMark::Modified)
+        };
+        
+        if marks.len() < first + count - 1 {
+            marks.resize(first + count - 1, None);
+        }
+        for slot in &mut marks[first - 1..first + count - 1] {
+            if slot.is_none() {
+                *slot = Some(mark);
+            }
+        }
+    }
+    
+    marks
+}
+
+/// Parses `start,count` (count defaults to 1 when omitted).
+fn parse_hunk_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn format_change_marker(mark: Option<ChangeMark>, config: &Config) -> String {
+    let (color, symbol) = match mark {
+        Some(ChangeMark::Added) => (&config.colors.success, '+'),
+        Some(ChangeMark::Modified) => (&config.colors.number, '~'),
+        Some(ChangeMark::Deleted) => (&config.colors.error, '_'),
+        None => return " ".to_string(),
+    };
+    
+    if config.use_colors {
+        format!("{}\u{258E}{}", color, config.colors.reset)
+    } else {
+        symbol.to_string()
+    }
+}
+
+/// Renders an option as it appears in the help, e.g. `-g, --grep=PATTERN`.
+fn option_label(spec: &OptionSpec) -> String {
+    let short = match spec.short {
+        Some(c) => format!("-{}, ", c),
+        None => "    ".to_string(),
+    };
+    let value = match (spec.value, spec.optional) {
+        (Some(value), true) => format!("[={}]", value),
+        (Some(value), false) => format!("={}", value),
+        (None, _) => "".to_string(),
+    };
+    format!("{}--{}{}", short, spec.long, value)
+}
+
+fn print_help(config: &Config) {
+    println!("{}{}{} meow [OPTIONS]... [FILE]...", config.colors.success, msg!("help-usage"), config.colors.reset);
+    println!("{}", msg!("help-description"));
+    println!();
+    println!("{}", msg!("help-stdin"));
+    println!();
+    for spec in OPTIONS.iter().filter(|spec| !spec.hidden) {
+        let label = option_label(spec);
+        let help = i18n::lookup(&format!("option.{}", spec.long)).unwrap_or(spec.help);
+        if label.len() < 25 {
+            println!("  {:<25}{}", label, help);
+        } else {
+            println!("  {}\n  {:<25}{}", label, "", help);
+        }
+    }
+    println!();
+    println!("{}", msg!("help-examples"));
+    for (i, (command, description)) in EXAMPLES.iter().enumerate() {
+        let description = i18n::lookup(&format!("example.{}", i)).unwrap_or(description);
+        println!("  {:<28}{}", command, description);
+    }
+    println!();
+    println!("{}", msg!("help-report-bugs", "github.com/anmitalidev/meow"));
+}
+
+/// Escapes text for roff: backslashes, hyphens and leading control characters.
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// `--show-colors`: each color role by name with a sample in its color, then
+/// the `--rainbow` gradient. Just the names without colors.
+fn show_colors(config: &Config) {
+    let colors = &config.colors;
+    let mut roles: Vec<(String, &str)> = vec![
+        ("normal".to_string(), &colors.normal),
+        ("number".to_string(), &colors.number),
+        ("highlight".to_string(), &colors.highlight),
+        ("error".to_string(), &colors.error),
+        ("success".to_string(), &colors.success),
+        ("filename".to_string(), &colors.filename),
+        ("dim".to_string(), &colors.dim),
+    ];
+    roles.extend(colors.blame_age.iter().enumerate().map(|(i, color)| (format!("blame-age-{}", i + 1), color.as_str())));
+    roles.extend(colors.file_palette.iter().enumerate().map(|(i, color)| (format!("file-{}", i + 1), color.as_str())));
+    
+    let sample = msg!("colors-sample");
+    let width = roles.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, color) in &roles {
+        if config.use_colors {
+            print_line(&format!("{:width$}  {}{}{}", name, color, sample, colors.reset));
+        } else {
+            print_line(name);
+        }
+    }
+    
+    // As --rainbow paints it
+    if config.use_colors {
+        print_line(&format!("{:width$}  {}", "rainbow", rainbow(&sample, config.color_depth, colors)));
+    } else {
+        print_line("rainbow");
+    }
+}
+
+/// Builds the meow(1) man page from the option, example and environment tables.
+fn generate_man() -> String {
+    let mut page = String::new();
+    
+    page.push_str(&format!(".TH MEOW 1 \"\" \"meow {}\" \"User Commands\"\n", env!("CARGO_PKG_VERSION")));
+    page.push_str(".SH NAME\nmeow \\- an enhanced cat utility\n");
+    page.push_str(".SH SYNOPSIS\n.B meow\n[\\fIOPTION\\fR]... [\\fIFILE\\fR]...\n");
+    page.push_str(".SH DESCRIPTION\nConcatenate FILE(s) to standard output with enhancements such as line numbering, \
+                   pattern filtering, highlighting and paging.\n.PP\nIf FILE is not specified or is \\-, read standard input.\n");
+    
+    page.push_str(".SH OPTIONS\n");
+    for spec in OPTIONS.iter().filter(|spec| !spec.hidden) {
+        page.push_str(".TP\n");
+        let long = match (spec.value, spec.optional) {
+            (Some(value), true) => format!("\\fB\\-\\-{}\\fR[=\\fI{}\\fR]", roff_escape(spec.long), value),
+            (Some(value), false) => format!("\\fB\\-\\-{}\\fR=\\fI{}\\fR", roff_escape(spec.long), value),
+            (None, _) => format!("\\fB\\-\\-{}\\fR", roff_escape(spec.long)),
+        };
+        match spec.short {
+            Some(c) => page.push_str(&format!("\\fB\\-{}\\fR, {}\n", c, long)),
+            None => page.push_str(&format!("{}\n", long)),
+        }
+        page.push_str(&format!("{}\n", roff_escape(spec.help)));
+    }
+    
+    page.push_str(".SH EXAMPLES\n");
+    for (command, description) in EXAMPLES {
+        page.push_str(&format!(".TP\n.B {}\n{}\n", roff_escape(command), roff_escape(description)));
+    }
+    
+    page.push_str(".SH ENVIRONMENT\n");
+    for (name, description) in ENVIRONMENT {
+        page.push_str(&format!(".TP\n.B {}\n{}\n", roff_escape(name), roff_escape(description)));
+    }
+    
+    page.push_str(".SH EXIT STATUS\n");
+    for (code, description) in EXIT_STATUS {
+        page.push_str(&format!(".TP\n.B {}\n{}\n", code, roff_escape(description)));
+    }
+    page.push_str("When \\fB\\-\\-edit\\fR launches an editor that fails, its exit status is returned.\n");
+    
+    page.push_str(".SH BUGS\nReport bugs to: github.com/anmitalidev/meow\n");
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const PORCELAIN: &str = "\
+1111111111111111111111111111111111111111 1 1 2
+author Ada Lovelace
+author-mail <ada@example.com>
+author-time 1700000000
+author-tz +0000
+summary first
+filename a.txt
+\tone
+1111111111111111111111111111111111111111 2 2
+\ttwo
+2222222222222222222222222222222222222222 3 4 1
+author Grace Brewster Murray Hopper
+author-time 1700000100
+summary second
+filename a.txt
+\tfour
+";
+    
+    #[test]
+    fn blame_porcelain_is_indexed_by_final_line() {
+        let lines = parse_blame_porcelain(PORCELAIN);
+        assert_eq!(lines.len(), 4);
+        let first = lines[0].as_ref().unwrap();
+        assert_eq!((first.hash.as_str(), first.initials.as_str(), first.time), ("11111111", "AL", 1700000000));
+        // Details given once are remembered for the commit's later groups
+        assert_eq!(lines[1].as_ref().unwrap().initials, "AL");
+        assert!(lines[2].is_none());
+        let last = lines[3].as_ref().unwrap();
+        assert_eq!((last.hash.as_str(), last.initials.as_str(), last.time), ("22222222", "GBM", 1700000100));
+    }
+    
+    #[test]
+    fn blame_gutter_is_as_wide_as_the_longest_initials() {
+        let lines = parse_blame_porcelain(PORCELAIN);
+        assert_eq!(blame_gutter_width(&lines), 3);
+        let mut config = Config::new();
+        config.colors = ColorConfig::new(false, config.color_depth);
+        assert_eq!(format_blame_gutter(lines[0].as_ref(), 3, &config), "11111111 AL  ");
+        assert_eq!(format_blame_gutter(None, 3, &config), format!("{:12} ", ""));
+    }
+    
+    #[test]
+    fn diff_hunks_mark_new_line_numbers() {
+        let diff = "diff --git a/a b/a\n@@ -2 +2 @@\n-two\n+TWO\n@@ -5,0 +6,2 @@\n+six\n+seven\n@@ -9,2 +9,0 @@\n-gone\n";
+        let marks: Vec<char> = parse_diff_hunks(diff).iter().map(|mark| match mark {
+            Some(ChangeMark::Added) => '+',
+            Some(ChangeMark::Modified) => '~',
+            Some(ChangeMark::Deleted) => '_',
+            None => ' ',
+        }).collect();
+        assert_eq!(marks.into_iter().collect::<String>(), " ~   ++ _");
+    }
+    
+    #[test]
+    #[cfg(unix)]
+    fn only_terminals_count_as_terminals() {
+        assert!(!is_terminal(Path::new("/dev/null")));
+        assert_eq!(special_file_kind(Path::new("/dev/null")), Some("kind-char-device"));
+        assert!(WaitNotice::start(Path::new("/dev/null"), "/dev/null").is_none());
+        assert!(WaitNotice::start(Path::new("Cargo.toml"), "Cargo.toml").is_none());
+    }
+    
+    #[test]
+    fn xorshift_stays_in_the_unit_interval() {
+        let mut rng = XorShift(12345);
+        let draws: Vec<f64> = (0..1000).map(|_| rng.next_f64()).collect();
+        assert!(draws.iter().all(|&x| (0.0..1.0).contains(&x)));
+        assert!(draws.iter().any(|&x| x < 0.1) && draws.iter().any(|&x| x > 0.9));
+    }
+    
+    #[test]
+    fn escape_line_quotes_for_each_language() {
+        assert_eq!(escape_line("it's $HOME", "shell"), "'it'\\''s $HOME'");
+        assert_eq!(escape_line("tab\there \"q\" \\", "c"), "\"tab\\there \\\"q\\\" \\\\\"");
+        // C gets octal, so a following digit can't join the escape
+        assert_eq!(escape_line("\x1b1", "c"), "\"\\0331\"");
+        assert_eq!(escape_line("\x1b1", "json"), "\"\\u001b1\"");
+        assert_eq!(escape_line("caf\u{e9}", "json"), "\"caf\u{e9}\"");
+    }
+    
+    #[test]
+    fn tail_of_stream_drops_the_partial_first_line() {
+        let mut tail = String::new();
+        tail_of_stream(&b"aaaa\nbbbb\ncccc\n"[..], 7).unwrap().read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "cccc\n");
+        let mut tail = String::new();
+        tail_of_stream(&b"aaaa\nbbbb\n"[..], 5).unwrap().read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "bbbb\n");
+    }
+    
+    #[test]
+    fn man_page_lists_every_option() {
+        let page = generate_man();
+        for spec in OPTIONS.iter().filter(|spec| !spec.hidden) {
+            let long = format!("\\fB\\-\\-{}\\fR", roff_escape(spec.long));
+            assert!(page.contains(&long), "--{} is missing from the man page", spec.long);
+            if let Some(c) = spec.short {
+                assert!(page.contains(&format!("\\fB\\-{}\\fR, {}", c, long)), "-{} is missing from the man page", c);
+            }
+        }
+    }
+    
+    #[test]
+    fn man_page_escapes_roff() {
+        assert_eq!(roff_escape("a-b\\c"), "a\\-b\\ec");
+        assert_eq!(roff_escape(".start"), "\\&.start");
+        assert_eq!(roff_escape("'quote"), "\\&'quote");
+        // No line of the page starts with a stray control character
+        let page = generate_man();
+        assert!(page.lines().all(|line| !line.starts_with('\'')));
+        assert!(page.lines().filter(|line| line.starts_with('.')).all(|line| line.len() > 1 && line[1..].starts_with(|c: char| c.is_ascii_uppercase())));
+    }
+    
+    /// Applies `ops` to `old`, checking they fit, and gives back the new lines.
+    fn apply_diff<'a>(old: &[&'a str], new: &[&'a str], ops: &[DiffOp]) -> Vec<&'a str> {
+        let (mut i, mut j) = (0, 0);
+        let mut result = Vec::new();
+        for op in ops {
+            match op {
+                DiffOp::Equal => {
+                    assert_eq!(old[i], new[j]);
+                    result.push(old[i]);
+                    i += 1;
+                    j += 1;
+                },
+                DiffOp::Delete => i += 1,
+                DiffOp::Insert => {
+                    result.push(new[j]);
+                    j += 1;
+                }
+            }
+        }
+        assert_eq!((i, j), (old.len(), new.len()));
+        result
+    }
+    
+    fn lcs_len(old: &[&str], new: &[&str]) -> usize {
+        let mut row = vec![0; new.len() + 1];
+        for a in old {
+            let mut diagonal = 0;
+            for (j, b) in new.iter().enumerate() {
+                let above = row[j + 1];
+                row[j + 1] = if a == b { diagonal + 1 } else { row[j + 1].max(row[j]) };
+                diagonal = above;
+            }
+        }
+        row[new.len()]
+    }
+    
+    #[test]
+    fn diff_lines_finds_a_shortest_edit_script() {
+        let words = ["a", "b", "c", "d"];
+        let mut rng = XorShift(42);
+        for _ in 0..2000 {
+            let mut pick = |len| -> Vec<&str> { (0..rng.next_u64() % len).map(|_| words[(rng.next_u64() % 4) as usize]).collect() };
+            let old = pick(12);
+            let new = pick(12);
+            let ops = diff_lines(&old, &new);
+            assert_eq!(apply_diff(&old, &new, &ops), new, "{:?} -> {:?}", old, new);
+            let edits = ops.iter().filter(|op| **op != DiffOp::Equal).count();
+            assert_eq!(edits, old.len() + new.len() - 2 * lcs_len(&old, &new), "{:?} -> {:?}", old, new);
+        }
+    }
+    
+    #[test]
+    fn diff_lines_handles_big_inputs() {
+        // A full table for these would be 100000 x 100000 cells
+        let old: Vec<String> = (0..100_000).map(|i| format!("line {}", i)).collect();
+        let mut new = old.clone();
+        for i in (0..new.len()).step_by(1000) {
+            new[i] = format!("changed {}", i);
+        }
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+        let ops = diff_lines(&old, &new);
+        assert_eq!(ops.iter().filter(|op| **op == DiffOp::Delete).count(), 100);
+        assert_eq!(apply_diff(&old, &new, &ops), new);
+    }
+    
+    #[test]
+    fn diff_lines_replaces_unrelated_inputs_whole() {
+        let old: Vec<String> = (0..30_000).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..30_000).map(|i| format!("new {}", i)).collect();
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+        let ops = diff_lines(&old, &new);
+        assert!(ops.iter().all(|op| *op != DiffOp::Equal));
+        assert_eq!(apply_diff(&old, &new, &ops), new);
+    }
+    
+    fn indentation_of(text: &str) -> String {
+        let mut indent = IndentStats::default();
+        for line in text.lines() {
+            indent.add_line(line);
+        }
+        indent.describe()
+    }
+    
+    #[test]
+    fn indent_stats_guess_the_indentation() {
+        assert_eq!(indentation_of("fn main() {\n\tif x {\n\t\ty();\n\t}\n}\n"), "tabs");
+        assert_eq!(indentation_of("a:\n  b:\n    c: 1\n  d: 2\n\n  e:\n    f: 3\n"), "2 spaces");
+        // The odd 4-space jump doesn't outvote the 2-space steps
+        assert_eq!(indentation_of("a\n  b\n    c\n  d\n      e\n"), "2 spaces");
+        assert_eq!(indentation_of("flat\ntext\n"), "none");
+    }
+    
+    fn capped_chunks(text: &str, max_bytes: usize) -> Vec<String> {
+        CappedLines::new(text.as_bytes(), Some(max_bytes)).map(Result::unwrap).collect()
+    }
+    
+    #[test]
+    fn capped_lines_split_long_lines() {
+        let line = "x".repeat(10_000);
+        let chunks = capped_chunks(&format!("{}\nshort\n", line), 4096);
+        assert_eq!(chunks, [&line[..4096], &line[4096..8192], &line[8192..], "short"]);
+        // A separator right at the cap doesn't leave an empty chunk
+        assert_eq!(capped_chunks("abcd\nef\n", 4), ["abcd", "ef"]);
+    }
+    
+    #[test]
+    fn capped_lines_keep_characters_whole_under_any_cap() {
+        let text = "aé€😀b\né\n";
+        for max_bytes in 1..=8 {
+            let chunks = capped_chunks(text, max_bytes);
+            assert_eq!(chunks.concat(), "aé€😀bé", "cap {}", max_bytes);
+            // A chunk only goes over the cap to hold a single character
+            assert!(chunks.iter().all(|chunk| chunk.len() <= max_bytes || chunk.chars().count() == 1), "cap {}: {:?}", max_bytes, chunks);
+            assert!(chunks.iter().all(|chunk| !chunk.is_empty()), "cap {}: {:?}", max_bytes, chunks);
+        }
+        assert_eq!(capped_chunks("😀😀", 1), ["😀", "😀"]);
+        assert_eq!(capped_chunks("a€b", 3), ["a", "€", "b"]);
+    }
+    
+    #[test]
+    fn capped_lines_report_invalid_utf8() {
+        let mut lines = CappedLines::new(&b"\xE2\x82x\n"[..], Some(1));
+        assert_eq!(lines.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn date_format_gives_an_absolute_time() {
+        let mut config = Config::new();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        assert!(config.apply_option("date-format", Some("%s")));
+        // %s is seconds since the epoch, the same in every time zone
+        assert_eq!(format_mtime(mtime, &config), "1000000000");
+        assert!(!config.apply_option("date-format", Some("%Q")));
+        config.date_format = None;
+        assert_eq!(format_mtime(SystemTime::now() - Duration::from_secs(3 * 60 * 60), &config), "3 hours ago");
+    }
+    
+    #[test]
+    fn file_colors_cycle_per_file() {
+        let mut config = Config::new();
+        config.colors = ColorConfig::new(true, Depth::Ansi16);
+        config.file_colors = true;
+        let palette = config.colors.file_palette.clone();
+        let highlights: Vec<String> = (0..=palette.len()).map(|index| config.for_file(index, None).colors.highlight).collect();
+        assert_ne!(highlights[0], highlights[1]);
+        assert_eq!(highlights[..palette.len()], palette[..]);
+        assert_eq!(highlights[palette.len()], highlights[0]);
+        assert_eq!(config.for_file(1, None).colors.filename, highlights[1]);
+        
+        config.file_colors = false;
+        assert_eq!(config.for_file(1, None).colors.highlight, config.colors.highlight);
+    }
+    
+    #[test]
+    fn expand_env_substitutes_defined_names() {
+        let vars: HashMap<String, String> = [("HOST", "db"), ("PORT", "5432")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let (line, spans) = expand_env("url=${HOST}:$PORT/$NAME \\$HOST $ ${} $1 ${BAD-NAME}", &vars);
+        assert_eq!(line, "url=db:5432/$NAME \\$HOST $ ${} $1 ${BAD-NAME}");
+        assert_eq!(spans, [(4, 6, true), (7, 11, true), (12, 17, false)]);
+    }
+    
+    #[test]
+    fn dotenv_values_lose_quotes_and_export() {
+        let vars = parse_dotenv("# comment\n\nexport A=1\nB = \"two words\"\nC='x=y'\nnot a var\n");
+        let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(vars, [("A", "1"), ("B", "two words"), ("C", "x=y")]);
+    }
+    
+    #[test]
+    fn field_lists_parse_open_ranges() {
+        assert_eq!(parse_field_list("1,3-5,7-"), Some(vec![(1, 1), (3, 5), (7, usize::MAX)]));
+        assert_eq!(parse_field_list("-2"), Some(vec![(1, 2)]));
+        for bad in ["0", "-", "3-1", "a", "1,,2", "0-2"] {
+            assert_eq!(parse_field_list(bad), None, "{}", bad);
+        }
+    }
+    
+    #[test]
+    fn csv_fields_keep_quoted_delimiters() {
+        assert_eq!(split_csv(r#""x,1",y,"z ""q"", w""#, ","), [r#""x,1""#, "y", r#""z ""q"", w""#]);
+        assert_eq!(split_csv("a;;b", ";"), ["a", "", "b"]);
+        assert_eq!(split_csv("", ","), [""]);
+    }
+    
+    #[test]
+    fn select_fields_like_cut() {
+        let mut config = Config::new();
+        let ranges = parse_field_list("2,4-").unwrap();
+        assert_eq!(select_fields("a  b\tc d e", &ranges, &config), "b d e");
+        // Lines short of the fields give what they have
+        assert_eq!(select_fields("a", &ranges, &config), "");
+        config.fields_invert = true;
+        assert_eq!(select_fields("a b c d e", &ranges, &config), "a c");
+        config.fields_invert = false;
+        config.delimiter = Some(",".to_string());
+        config.output_delimiter = Some(" | ".to_string());
+        assert_eq!(select_fields("a,b,c,d", &ranges, &config), "b | d");
+    }
+    
+    #[test]
+    fn strip_comment_hash_style() {
+        let mut in_block = false;
+        let mut strip = |line| strip_comment(line, "hash", &mut in_block);
+        assert_eq!(strip("# only a comment"), None);
+        assert_eq!(strip("key = 1  # note"), Some("key = 1".to_string()));
+        // Quoted and mid-word hashes aren't comments
+        assert_eq!(strip(r#"key = "a#b" # note"#), Some(r#"key = "a#b""#.to_string()));
+        assert_eq!(strip(r#"s = 'it\'s #1'"#), Some(r#"s = 'it\'s #1'"#.to_string()));
+        assert_eq!(strip("url=x#y"), Some("url=x#y".to_string()));
+        assert_eq!(strip(""), Some(String::new()));
+    }
+    
+    #[test]
+    fn strip_comment_slash_style() {
+        let mut in_block = false;
+        assert_eq!(strip_comment("// top", "slash", &mut in_block), None);
+        assert_eq!(strip_comment(r#"char *s = "//"; // z"#, "slash", &mut in_block), Some(r#"char *s = "//";"#.to_string()));
+        assert_eq!(strip_comment("int x; /* a", "slash", &mut in_block), Some("int x;".to_string()));
+        assert!(in_block);
+        assert_eq!(strip_comment("still inside", "slash", &mut in_block), None);
+        assert_eq!(strip_comment("b */ int y;", "slash", &mut in_block), Some(" int y;".to_string()));
+        assert!(!in_block);
+        assert_eq!(strip_comment("a /* b */ c", "slash", &mut in_block), Some("a  c".to_string()));
+    }
+    
+    #[test]
+    fn comment_style_from_the_file_name() {
+        assert_eq!(comment_style_for("dir/config.TOML"), Some("hash"));
+        assert_eq!(comment_style_for("Makefile"), Some("hash"));
+        assert_eq!(comment_style_for(".gitignore"), Some("hash"));
+        assert_eq!(comment_style_for("main.rs"), Some("slash"));
+        assert_eq!(comment_style_for("php.ini"), Some("semicolon"));
+        assert_eq!(comment_style_for("README.md"), None);
+        assert_eq!(comment_style_for("notes"), None);
+    }
+    
+    #[test]
+    fn dupe_tracker_normalizes_lines() {
+        let mut config = Config::new();
+        let mut dupes = DupeTracker::default();
+        let added: Vec<bool> = ["a b", "A  b", "a b", "", ""].iter().enumerate().map(|(i, line)| dupes.add(line, i + 1, &config)).collect();
+        assert_eq!(added, [false, false, true, false, false]);
+        
+        config.dupes_ignore_case = true;
+        config.dupes_ignore_whitespace = true;
+        let mut dupes = DupeTracker::default();
+        for (i, line) in ["a b", "A  b", " a b "].iter().enumerate() {
+            dupes.add(line, i + 1, &config);
+        }
+        assert_eq!(dupes.seen.len(), 1);
+        assert_eq!(dupes.seen["a b"], [1, 2, 3]);
+    }
+    
+    #[test]
+    fn dupe_tracker_hashes_long_lines() {
+        let config = Config::new();
+        let mut dupes = DupeTracker::default();
+        let long = "x".repeat(DUPE_HASH_THRESHOLD + 1);
+        assert!(!dupes.add(&long, 1, &config));
+        assert!(dupes.add(&long, 2, &config));
+        assert!(!dupes.add(&"y".repeat(DUPE_HASH_THRESHOLD + 1), 3, &config));
+        assert_eq!(dupes.seen.len(), 2);
+        assert!(dupes.seen.keys().all(|key| key.len() < DUPE_HASH_THRESHOLD));
+    }
+    
+    #[test]
+    fn preview_lines_keeps_head_and_tail() {
+        let numbers = |kept: Vec<(usize, io::Result<String>)>| kept.into_iter().map(|(n, line)| (n, line.unwrap())).collect::<Vec<_>>();
+        let lines = |count: usize| (1..=count).map(|i| Ok(i.to_string())).collect::<Vec<io::Result<String>>>().into_iter();
+        
+        let kept = numbers(preview_lines(lines(10), 2));
+        assert_eq!(kept, [(1, "1".to_string()), (2, "2".to_string()), (9, "9".to_string()), (10, "10".to_string())]);
+        // Short inputs are kept whole
+        assert_eq!(numbers(preview_lines(lines(3), 2)).len(), 3);
+        assert!(preview_lines(lines(0), 2).is_empty());
+    }
+    
+    #[test]
+    fn preview_lines_stops_at_a_read_error() {
+        let lines = vec![Ok("1".to_string()), Ok("2".to_string()), Ok("3".to_string()), Err(io::Error::other("gone")), Ok("5".to_string())];
+        let kept = preview_lines(lines.into_iter(), 1);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].0, 1);
+        assert_eq!(kept[1].0, 4);
+        assert!(kept[1].1.is_err());
+    }
+    
+    /// Counts the bytes read through it, to show what a function leaves unread.
+    struct CountingReader<R> {
+        inner: R,
+        read: std::rc::Rc<std::cell::Cell<u64>>,
+    }
+    
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read.set(self.read.get() + n as u64);
+            Ok(n)
+        }
+    }
+    
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+    
+    #[test]
+    fn sample_by_offset_reads_only_the_sampled_lines() {
+        let text: String = (0..200_000).map(|i| format!("line {:06}\n", i)).collect();
+        let size = text.len() as u64;
+        let read = std::rc::Rc::new(std::cell::Cell::new(0));
+        let input = CountingReader { inner: Cursor::new(text.into_bytes()), read: read.clone() };
+        let mut out = Vec::new();
+        sample_by_offset(input, size, 10, &Config::new(), &mut out).unwrap();
+        
+        let out = String::from_utf8(out).unwrap();
+        let shown: Vec<&str> = out.lines().filter(|line| !line.contains('\u{00B7}')).collect();
+        assert_eq!(shown.len(), 10);
+        assert_eq!(shown[0], "         0 | line 000000");
+        // Every sample starts at a line start, with its offset
+        for line in &shown {
+            let (offset, text) = line.split_once(" | ").unwrap();
+            let number: u64 = text.strip_prefix("line ").unwrap().parse().unwrap();
+            assert_eq!(offset.trim().parse::<u64>().unwrap(), number * 12);
+        }
+        assert_eq!(out.matches('\u{00B7}').count(), 9 * 3);
+        // A buffer's worth around each sample, not the 2.4 MB in between
+        assert!(read.get() < 10 * 16 * 1024, "read {} of {} bytes", read.get(), size);
+    }
+    
+    #[test]
+    fn sample_by_offset_on_a_small_input_shows_each_line_once() {
+        let text = "a\nb\nc\n";
+        let mut out = Vec::new();
+        sample_by_offset(Cursor::new(text), text.len() as u64, 10, &Config::new(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "         0 | a\n         2 | b\n         4 | c\n");
+    }
+    
+    #[test]
+    fn strip_escapes_across_writes() {
+        let mut state = EscapeState::Text;
+        let mut plain = strip_escapes(b"a\x1B[3", &mut state);
+        plain.extend(strip_escapes(b"6mb\x1B]0;title\x1B", &mut state));
+        plain.extend(strip_escapes(b"\\c\x1B]8;;url\x07d\x1B7e", &mut state));
+        assert_eq!(plain, b"abcde");
+    }
+    
+    #[test]
+    fn throttle_paces_lines_at_the_rate() {
+        let mut throttle = Throttle::new(100.0);
+        let mut out = Vec::new();
+        let start = Instant::now();
+        for _ in 0..21 {
+            throttle.pace(&mut out).unwrap();
+        }
+        // The first line is due at once, the other 20 every 10ms
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+    }
+    
+    #[test]
+    fn throttle_does_not_burst_after_a_stall() {
+        let mut throttle = Throttle::new(100.0);
+        let mut out = Vec::new();
+        throttle.pace(&mut out).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let start = Instant::now();
+        for _ in 0..6 {
+            throttle.pace(&mut out).unwrap();
+        }
+        // Without the restart the 10 missed slots would go out at once
+        assert!(start.elapsed() >= Duration::from_millis(40), "{:?}", start.elapsed());
+    }
+    
+    #[test]
+    fn control_sequences_other_than_colors_are_suspicious() {
+        assert!(!has_control_sequences("plain"));
+        assert!(!has_control_sequences("\x1B[31mred\x1B[0m \x1B[38;5;208mo\x1B[m"));
+        assert!(has_control_sequences("\x1B]0;pwned\x07"));
+        assert!(has_control_sequences("\x1B[?1049h"));
+        assert!(has_control_sequences("\x1BP+q\x1B\\"));
+        assert!(has_control_sequences("a\u{9B}2J"));
+        // A lone ESC at the end could start anything
+        assert!(has_control_sequences("\x1B"));
+    }
+    
+    #[test]
+    fn neutralize_escapes_replaces_every_esc() {
+        assert_eq!(neutralize_escapes("\x1B]0;pwned\x07 x"), ("^[]0;pwned\x07 x".to_string(), 1));
+        assert_eq!(neutralize_escapes("\x1B[?1049h\x1B[31m"), ("^[[?1049h^[[31m".to_string(), 2));
+        assert_eq!(neutralize_escapes("a\u{9B}2J\u{9D}0;t"), ("a^[[2J^[]0;t".to_string(), 2));
+        assert_eq!(neutralize_escapes("caf\u{e9}"), ("caf\u{e9}".to_string(), 0));
+    }
+    
+    #[test]
+    fn show_control_in_each_style() {
+        assert_eq!(show_control('\r', "caret"), "^M");
+        assert_eq!(show_control('\r', "hex"), "\\x0d");
+        assert_eq!(show_control('\r', "unicode"), "\u{240D}");
+        assert_eq!(show_control('\x1B', "caret"), "^[");
+        assert_eq!(show_control('\x7F', "caret"), "^?");
+        assert_eq!(show_control('\x7F', "unicode"), "\u{2421}");
+        assert_eq!(show_control('\u{85}', "caret"), "M-^E");
+    }
+    
+    #[test]
+    fn show_control_esc_and_tab() {
+        for (style, esc, tab) in [("caret", "^[", "^I"), ("hex", "\\x1b", "\\x09"), ("unicode", "\u{241B}", "\u{2409}")] {
+            assert_eq!(show_control('\x1B', style), esc);
+            assert_eq!(show_control('\t', style), tab);
+        }
+        assert_eq!(show_control('\u{85}', "unicode"), "\\x85");
+    }
+    
+    #[test]
+    fn line_numbers_in_hex() {
+        let mut config = Config::new();
+        assert_eq!(format_line_number(255, &config), "255");
+        config.apply_option("number-hex", None);
+        assert_eq!(format_line_number(255, &config), "ff");
+        assert_eq!(format_line_number(4096, &config), "1000");
+        config.apply_option("number-hex", Some("0x"));
+        assert_eq!(format_line_number(255, &config), "0xff");
+        assert!(!config.apply_option("number-hex", Some("0o")));
+    }
+    
+    #[test]
+    fn separators_take_c_escapes() {
+        assert_eq!(parse_separator(";"), b";");
+        assert_eq!(parse_separator("\\r"), b"\r");
+        assert_eq!(parse_separator("\\0"), b"\0");
+        assert_eq!(parse_separator("<\\x1e>"), b"<\x1e>");
+        assert_eq!(parse_separator("\\\\"), b"\\");
+        // Unknown escapes and short \x are kept as they are
+        assert_eq!(parse_separator("\\q\\x4"), b"\\q\\x4");
+        assert!(parse_separator("").is_empty());
+    }
+    
+    #[test]
+    fn records_split_on_any_separator() {
+        let records = |text: &str, separator: &[u8], max_bytes: Option<usize>| {
+            let mut lines = CappedLines::new(text.as_bytes(), max_bytes);
+            lines.separator = separator.to_vec();
+            lines.map(Result::unwrap).collect::<Vec<_>>()
+        };
+        assert_eq!(records("a;b;;c", b";", None), ["a", "b", "", "c"]);
+        assert_eq!(records("a\nb;c;", b";", None), ["a\nb", "c"]);
+        // \r isn't trimmed off records, only off newline-split lines
+        assert_eq!(records("one\r\ntwo\r\n", b"\n", None), ["one", "two"]);
+        assert_eq!(records("one\r\ntwo\r\n", b"--", None), ["one\r\ntwo\r\n"]);
+        assert_eq!(records("ab<>cd<>ef", b"<>", None), ["ab", "cd", "ef"]);
+        
+        // A separator longer than a byte, split between two reads
+        let mut lines = CappedLines::new(io::BufReader::with_capacity(3, "abc<>de<>f".as_bytes()), None);
+        lines.separator = b"<>".to_vec();
+        assert_eq!(lines.map(Result::unwrap).collect::<Vec<_>>(), ["abc", "de", "f"]);
+    }
+    
+    #[test]
+    fn records_split_at_a_separator_across_the_cap() {
+        let records = |text: &str, capacity: usize, max_bytes: usize| {
+            let mut lines = CappedLines::new(io::BufReader::with_capacity(capacity, text.as_bytes()), Some(max_bytes));
+            lines.separator = b"<>".to_vec();
+            lines.map(Result::unwrap).collect::<Vec<_>>()
+        };
+        for capacity in [1, 2, 3, 8192] {
+            assert_eq!(records("a<>bc", capacity, 2), ["a", "bc"], "capacity {}", capacity);
+            assert_eq!(records("ab<>cd", capacity, 2), ["ab", "cd"], "capacity {}", capacity);
+            assert_eq!(records("abc<>d<>", capacity, 2), ["ab", "c", "d"], "capacity {}", capacity);
+            assert_eq!(records("<><>x", capacity, 1), ["", "", "x"], "capacity {}", capacity);
+        }
+    }
+    
+    #[test]
+    fn field_spans() {
+        let mut config = Config::new();
+        let span = |line: &'static str, n: usize, config: &Config| field_span(line, n, config).map(|(start, end)| &line[start..end]);
+        assert_eq!(span("a\tbb\tccc", 2, &config), Some("bb"));
+        assert_eq!(span("a\tbb\tccc", 3, &config), Some("ccc"));
+        assert_eq!(span("a\tbb", 3, &config), None);
+        assert_eq!(span("a\t\tc", 2, &config), Some(""));
+        // Spaces don't split without a --delimiter
+        assert_eq!(span("a b\tc", 1, &config), Some("a b"));
+        
+        config.apply_option("delimiter", Some("::"));
+        assert_eq!(span("x::é::z", 2, &config), Some("é"));
+        config.apply_option("csv", None);
+        assert_eq!(span("x::\"a::b\"::z", 2, &config), Some("\"a::b\""));
+        let mut csv = Config::new();
+        csv.apply_option("csv", None);
+        assert_eq!(span("1,\"two, three\",4", 3, &csv), Some("4"));
+    }
+    
+    #[test]
+    fn grep_file_patterns_are_literal() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"disk full\na.b\n\n# comment\n(oops\n").unwrap();
+        let path = file.path().to_str().unwrap();
+        let any = load_grep_file(path, false).unwrap();
+        assert!(any.is_match("ERROR disk full"));
+        assert!(any.is_match("a.b"));
+        assert!(!any.is_match("axb"));
+        assert!(any.is_match("(oops"));
+        assert!(any.is_match("# comment"));
+        assert!(!any.is_match("nothing here"));
+        // The blank line doesn't match everything
+        assert!(!any.is_match(""));
+        let any = load_grep_file(path, true).unwrap();
+        assert!(!any.is_match("# comment"));
+        assert!(any.is_match("a.b"));
+    }
+    
+    #[test]
+    fn empty_grep_file_matches_nothing() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"\n# only a comment\n").unwrap();
+        let any = load_grep_file(file.path().to_str().unwrap(), true).unwrap();
+        assert!(!any.is_match(""));
+        assert!(!any.is_match("anything"));
+        assert!(load_grep_file("/nonexistent/patterns", false).is_err());
+    }
+    
+    /// `input` through `process_input` as `name`, with what it wrote.
+    fn process(input: &str, name: &str, config: &Config, run: &mut RunState) -> String {
+        let mut out = Vec::new();
+        process_input(&mut BufReader::new(input.as_bytes()), config, name, &mut 0, run, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+    
+    #[test]
+    fn max_count_stops_each_input() {
+        let mut config = Config::new();
+        config.apply_option("grep", Some("x"));
+        config.apply_option("max-count", Some("2"));
+        let mut run = RunState::default();
+        assert_eq!(process("x1\na\nx2\nx3\n", "a.txt", &config, &mut run), "x1\nx2\n");
+        assert!(run.max_count_reached(&config));
+        // The count starts over with the next input
+        assert_eq!(process("x4\nx5\nx6\n", "b.txt", &config, &mut run), "x4\nx5\n");
+    }
+    
+    #[test]
+    fn max_count_total_spans_inputs() {
+        let mut config = Config::new();
+        config.apply_option("grep", Some("x"));
+        config.apply_option("max-count", Some("3"));
+        config.apply_option("max-count-total", None);
+        let mut run = RunState::default();
+        assert_eq!(process("x1\nx2\n", "a.txt", &config, &mut run), "x1\nx2\n");
+        assert!(!run.max_count_reached(&config));
+        assert_eq!(process("x3\nx4\n", "b.txt", &config, &mut run), "x3\n");
+        assert_eq!(run.matches_shown, 3);
+        assert_eq!(process("x5\n", "c.txt", &config, &mut run), "");
+    }
+    
+    #[test]
+    fn max_count_keeps_the_after_context() {
+        let mut config = Config::new();
+        config.apply_option("grep", Some("x"));
+        config.apply_option("max-count", Some("1"));
+        config.apply_option("after-context", Some("2"));
+        let mut run = RunState::default();
+        // A match in the context is shown as context, not counted
+        assert_eq!(process("a\nx1\nb\nx2\nc\n", "a.txt", &config, &mut run), "x1\nb\nx2\n");
+        assert_eq!(run.matches_shown, 1);
+    }
+    
+    #[test]
+    fn group_separator_carries_over_inputs() {
+        let mut config = Config::new();
+        config.apply_option("grep", Some("x"));
+        config.apply_option("context", Some("1"));
+        let mut run = RunState::default();
+        assert_eq!(process("x\na\n", "a.txt", &config, &mut run), "x\na\n");
+        assert!(run.groups_written);
+        // The first group of the next input is set apart from the last one
+        assert_eq!(process("b\nx\n", "b.txt", &config, &mut run), "--\nb\nx\n");
+        assert_eq!(process("b\nx\n", "b.txt", &config, &mut RunState::default()), "b\nx\n");
+    }
+    
+    #[test]
+    fn json_lines_are_collected_in_the_run() {
+        let mut config = Config::new();
+        config.apply_option("json-array", None);
+        let mut run = RunState::default();
+        assert_eq!(process("a\n", "a.txt", &config, &mut run), "[\n  {\"file\": \"a.txt\", \"line\": 1, \"text\": \"a\"}");
+        assert_eq!(process("b\n", "b.txt", &config, &mut run), ",\n  {\"file\": \"b.txt\", \"line\": 1, \"text\": \"b\"}");
+        assert_eq!(run.json_lines, 2);
+    
+        config.apply_option("group-by-file", None);
+        let mut run = RunState::default();
+        assert_eq!(process("a\nb\n", "a.txt", &config, &mut run), "");
+        run.json_group("empty.txt");
+        let mut out = Vec::new();
+        write_json_groups(&mut out, run.json_groups).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), concat!(
+            "{\n",
+            "  \"a.txt\": {\"lines\": [\n",
+            "    {\"line\": 1, \"text\": \"a\"},\n",
+            "    {\"line\": 2, \"text\": \"b\"}\n",
+            "  ]},\n",
+            "  \"empty.txt\": {\"lines\": []}\n",
+            "}\n"));
+    }
+    
+    #[test]
+    fn quoted_words_are_spaced_across_inputs() {
+        let mut config = Config::new();
+        config.apply_option("shell-quote", Some("array"));
+        let mut run = RunState::default();
+        let first = process("a b\n", "a.txt", &config, &mut run);
+        let second = process("c\n", "b.txt", &config, &mut run);
+        assert_eq!(format!("{}{}", first, second), "'a b' 'c'");
+        assert_eq!(run.quoted_words, 2);
+    }
+    
+    #[test]
+    fn undefined_variables_fail_the_run() {
+        let mut config = Config::new();
+        config.apply_option("expand-env", Some("strict"));
+        let mut run = RunState::default();
+        process("$MEOW_SURELY_UNDEFINED_VARIABLE\n", "a.txt", &config, &mut run);
+        assert!(run.undefined_env);
+    }
+    
+    /// `text` without its escape sequences.
+    fn plain(text: &str) -> String {
+        String::from_utf8(strip_escapes(text.as_bytes(), &mut EscapeState::Text)).unwrap()
+    }
+    
+    #[test]
+    fn rainbow_paints_each_cluster_once() {
+        let colors = ColorConfig::new(true, Depth::Ansi16);
+        for line in [
+            "plain ascii",
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467} family",
+            "\u{1112}\u{1161}\u{11AB}\u{AD6D}\u{C5B4}",
+            "cafe\u{301} na\u{303}o",
+            "中文字",
+            "\u{1F1EB}\u{1F1F7}\u{2764}\u{FE0F}\u{1F44D}\u{1F3FD}",
+        ] {
+            let painted = rainbow(line, Depth::Ansi16, &colors);
+            assert_eq!(plain(&painted), line);
+            assert_eq!(painted.matches(colors.reset.as_str()).count(), columns::graphemes(line).count(), "{:?}", line);
+        }
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(rainbow(family, Depth::Ansi16, &colors), format!("{}{}{}", color::fg(color::hue(0), Depth::Ansi16), family, colors.reset));
+    }
+    
+    #[test]
+    fn length_counts_clusters_and_columns() {
+        assert_eq!(length_text("hello"), "5C");
+        assert_eq!(length_text("caf\u{e9}"), "4C");
+        assert_eq!(length_text("cafe\u{301}"), "4C");
+        assert_eq!(length_text("中文"), "2C, 4W");
+        assert_eq!(length_text("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}!"), "2C, 3W");
+        assert_eq!(length_text("\u{1112}\u{1161}\u{11AB}"), "1C, 2W");
+    }
+    
+    #[test]
+    fn wait_for_file_stops_at_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("later.log");
+        let mut config = Config::new();
+        config.apply_option("wait-timeout", Some("150"));
+        let started = Instant::now();
+        wait_for_file(&path, "later.log", &config);
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        
+        // A file that's there already isn't waited for
+        fs::write(&path, "x").unwrap();
+        let started = Instant::now();
+        wait_for_file(&path, "later.log", &config);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+    
+    #[test]
+    fn wait_for_file_returns_once_it_exists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("later.log");
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(200));
+                fs::write(path, "x").unwrap();
+            })
+        };
+        let started = Instant::now();
+        wait_for_file(&path, "later.log", &Config::new());
+        assert!(path.exists());
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::{columns, more, terminal_width, Config};
+
+/// Columns of a line's text the `--marks-index` shows.
+const PREVIEW_WIDTH: usize = 40;
+
+/// `--marks` and `--mark-pattern`: the lines of one input to draw a rule
+/// above. Marks are numbered in the order of their lines, counting those
+/// that aren't shown, so a number means the same with or without `--grep`.
+pub struct Marks<'a> {
+    config: &'a Config,
+    /// Marks so far
+    seen: usize,
+    /// The last line added and its mark number, if it is one
+    last: (usize, Option<usize>),
+}
+
+impl<'a> Marks<'a> {
+    /// None when no marks were asked for.
+    pub fn new(config: &'a Config) -> Option<Self> {
+        (!config.marks.is_empty() || config.mark_pattern.is_some()).then_some(Marks { config, seen: 0, last: (0, None) })
+    }
+
+    /// Takes the next line of the input, number `source_line`. Every line
+    /// goes through here, shown or not.
+    pub fn add(&mut self, source_line: usize, line: &str) {
+        let number = is_mark(source_line, line, self.config).then(|| {
+            self.seen += 1;
+            self.seen
+        });
+        self.last = (source_line, number);
+    }
+
+    /// The number of the mark on `source_line`, the last line added.
+    pub fn number(&self, source_line: usize) -> Option<usize> {
+        self.last.1.filter(|_| self.last.0 == source_line)
+    }
+
+    /// Reports the `--marks` past the end of an input of `lines` lines.
+    pub fn report(&self, file_name: &str, lines: usize) {
+        let past: Vec<String> = self.config.marks.iter().filter(|&&mark| mark > lines).map(usize::to_string).collect();
+        if !past.is_empty() {
+            eprintln!("{}meow: {}{}", self.config.colors.error, msg!("marks-past-end", file_name, lines, past.join(", ")), self.config.colors.reset);
+        }
+    }
+}
+
+fn is_mark(source_line: usize, line: &str, config: &Config) -> bool {
+    config.marks.binary_search(&source_line).is_ok() || config.mark_pattern.as_ref().is_some_and(|re| re.is_match(line))
+}
+
+/// The rule drawn above mark `number`, across the terminal.
+pub fn write_rule(out: &mut dyn Write, number: usize, config: &Config) -> io::Result<()> {
+    let label = format!("\u{2500}\u{2500} {} ", msg!("mark-label", number));
+    let rest = terminal_width().saturating_sub(columns::display_width(&label));
+    if config.more {
+        more::mark();
+    }
+    writeln!(out, "{}{}{}{}", config.colors.highlight, label, "\u{2500}".repeat(rest), config.colors.reset)
+}
+
+/// `--marks-index`: lists the marks of the file `file_name` before it's
+/// shown, with the start of each line. Other inputs can't be read twice
+/// and get a note instead.
+pub fn write_index(out: &mut dyn Write, file_name: &str, config: &Config) -> io::Result<()> {
+    let path = Path::new(file_name);
+    let file = match File::open(path) {
+        Ok(file) if path.is_file() => file,
+        _ => {
+            eprintln!("meow: {}", msg!("marks-index-needs-file", file_name));
+            return Ok(());
+        }
+    };
+    let mut number = 0;
+    for (i, line) in BufReader::new(file).split(b'\n').enumerate() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\r');
+        if !is_mark(i + 1, line, config) {
+            continue;
+        }
+        number += 1;
+        writeln!(out, "{}", msg!("mark-index-entry",
+            format!("{}{}{}", config.colors.highlight, msg!("mark-label", number), config.colors.reset),
+            format!("{}{}{}", config.colors.number, i + 1, config.colors.reset),
+            preview(line)))?;
+    }
+    if number > 0 {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// The first words of `line`, cut at a space with `…` if it goes on.
+fn preview(line: &str) -> String {
+    let line = line.trim();
+    if columns::display_width(line) <= PREVIEW_WIDTH {
+        return line.to_string();
+    }
+    let mut shown = String::new();
+    for word in line.split_whitespace() {
+        let next = if shown.is_empty() { word.to_string() } else { format!("{} {}", shown, word) };
+        if columns::display_width(&next) > PREVIEW_WIDTH - 1 {
+            break;
+        }
+        shown = next;
+    }
+    // A first word too long to fit is cut wherever the room ends, `fit` adds the `…`
+    if shown.is_empty() {
+        return columns::fit(line, PREVIEW_WIDTH, false, 8).remove(0);
+    }
+    shown.push('\u{2026}');
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_cuts_at_a_space() {
+        assert_eq!(preview("  short line  "), "short line");
+        let long = "the quick brown fox jumps over the lazy dog and keeps on running";
+        let shown = preview(long);
+        assert_eq!(shown, "the quick brown fox jumps over the lazy\u{2026}");
+        assert!(columns::display_width(&shown) <= PREVIEW_WIDTH);
+        let word = "x".repeat(60);
+        assert_eq!(columns::display_width(&preview(&word)), PREVIEW_WIDTH);
+        assert!(preview(&word).ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn marks_are_numbered_in_line_order() {
+        let mut config = Config::new();
+        config.apply_option("marks", Some("4,2"));
+        config.apply_option("mark-pattern", Some("^#"));
+        let mut marks = Marks::new(&config).unwrap();
+        let mut numbers = Vec::new();
+        for (i, line) in ["a", "b", "# c", "d", "e"].iter().enumerate() {
+            marks.add(i + 1, line);
+            numbers.push(marks.number(i + 1));
+        }
+        assert_eq!(numbers, [None, Some(1), Some(2), Some(3), None]);
+        // Only the last line added is known
+        assert_eq!(marks.number(2), None);
+    }
+
+    #[test]
+    fn no_marks_asked_for() {
+        assert!(Marks::new(&Config::new()).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::input::InputReader;
+use crate::{display_input, Config, RunState};
+
+/// `--merge`: reads several inputs a line at a time each, in turn. Without a
+/// delimiter line N of every input is given on its own line; with
+/// `--merge-delimiter` they're joined into one line, like paste(1).
+pub struct Merge {
+    inputs: Vec<BufReader<InputReader>>,
+    delimiter: Option<String>,
+    /// `--merge-shortest`: stop once any input ends, rather than filling in
+    /// blanks for it until the longest one does
+    shortest: bool,
+    /// The merged row not yet handed out
+    pending: Vec<u8>,
+    at: usize,
+    done: bool,
+}
+
+impl Merge {
+    pub fn new(inputs: Vec<InputReader>, delimiter: Option<String>, shortest: bool) -> Self {
+        Merge { inputs: inputs.into_iter().map(BufReader::new).collect(), delimiter, shortest, pending: Vec::new(), at: 0, done: false }
+    }
+
+    /// Reads the next line of every input into `pending`, or sets `done`.
+    fn next_row(&mut self) -> io::Result<()> {
+        let mut row = Vec::with_capacity(self.inputs.len());
+        for input in &mut self.inputs {
+            let mut line = Vec::new();
+            if input.read_until(b'\n', &mut line)? == 0 {
+                row.push(None);
+                continue;
+            }
+            if line.ends_with(b"\n") {
+                line.pop();
+            }
+            row.push(Some(line));
+        }
+        if row.iter().all(Option::is_none) || (self.shortest && row.iter().any(Option::is_none)) {
+            self.done = true;
+            return Ok(());
+        }
+
+        self.pending.clear();
+        self.at = 0;
+        match &self.delimiter {
+            Some(delimiter) => {
+                for (i, line) in row.into_iter().enumerate() {
+                    if i > 0 {
+                        self.pending.extend_from_slice(delimiter.as_bytes());
+                    }
+                    self.pending.extend(line.unwrap_or_default());
+                }
+                self.pending.push(b'\n');
+            },
+            None => {
+                for line in row {
+                    self.pending.extend(line.unwrap_or_default());
+                    self.pending.push(b'\n');
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+impl Read for Merge {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.at == self.pending.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.next_row()?;
+        }
+        let count = buf.len().min(self.pending.len() - self.at);
+        buf[..count].copy_from_slice(&self.pending[self.at..self.at + count]);
+        self.at += count;
+        Ok(count)
+    }
+}
+
+/// Opens every file (stdin for none) and shows them merged as one input.
+/// Nothing is shown if any of them can't be opened.
+pub fn merge_files(config: &Config, line_num: &mut usize, run: &mut RunState) -> bool {
+    let sources = config.sources();
+
+    let mut inputs = Vec::new();
+    let mut failed = false;
+    for source in &sources {
+        match source.open() {
+            Ok(input) => inputs.push(input.reader),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", source.name(), err), config.colors.reset);
+                failed = true;
+            },
+        }
+    }
+    if failed {
+        return false;
+    }
+
+    // Blame, changes and comment styles go by the first file
+    let name = sources[0].name();
+    let merged = Merge::new(inputs, config.merge_delimiter.clone(), config.merge_shortest);
+    display_input(&mut BufReader::new(merged), config, &name, line_num, run);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Seek, Write};
+
+    fn file(text: &str) -> InputReader {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        file.rewind().unwrap();
+        InputReader::File(file)
+    }
+
+    fn merged(texts: &[&str], delimiter: Option<&str>, shortest: bool) -> String {
+        let inputs = texts.iter().map(|text| file(text)).collect();
+        let mut output = String::new();
+        Merge::new(inputs, delimiter.map(str::to_string), shortest).read_to_string(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn interleaves_lines() {
+        assert_eq!(merged(&["a1\na2\n", "b1\nb2\n"], None, false), "a1\nb1\na2\nb2\n");
+        // The shorter input gets blank lines, and a last line needn't end in a newline
+        assert_eq!(merged(&["a1\na2\na3", "b1\n"], None, false), "a1\nb1\na2\n\na3\n\n");
+        assert_eq!(merged(&["a1\na2\na3\n", "b1\n"], None, true), "a1\nb1\n");
+    }
+
+    #[test]
+    fn joins_with_a_delimiter() {
+        assert_eq!(merged(&["a1\na2\n", "b1\n", "c1\nc2\nc3\n"], Some("\t"), false), "a1\tb1\tc1\na2\t\tc2\n\t\tc3\n");
+        assert_eq!(merged(&["a1\na2\n", "b1\n"], Some(", "), true), "a1, b1\n");
+        assert_eq!(merged(&["", ""], Some(","), false), "");
+    }
+}
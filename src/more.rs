@@ -0,0 +1,215 @@
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::columns::display_width;
+use crate::terminal::RawMode;
+use crate::{terminal_height, terminal_width, Config};
+
+/// Bytes read so far from the file being shown, and its size (0 if unknown),
+/// for the percentage in the prompt and the `--set-title` title.
+static READ: AtomicU64 = AtomicU64::new(0);
+static TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// The next line written is the rule of a `--marks` mark, see `mark`.
+static MARKED: AtomicBool = AtomicBool::new(false);
+
+/// Whether `--more` can work: keys come from stdin, so both ends must be a terminal.
+pub fn available() -> bool {
+    cfg!(unix) && atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout)
+}
+
+/// Tells the pager that the line about to be written starts a mark, for
+/// `m` and `M` to jump to.
+pub fn mark() {
+    MARKED.store(true, Ordering::Relaxed);
+}
+
+/// How much of the input has been read, None when its size is unknown.
+pub fn percent() -> Option<u64> {
+    (READ.load(Ordering::Relaxed) * 100).checked_div(TOTAL.load(Ordering::Relaxed)).map(|percent| percent.min(100))
+}
+
+/// Counts what's read from the input, see `READ`.
+pub struct Progress<R> {
+    inner: R,
+}
+
+/// Starts tracking a new input of `size` bytes, None when unknown.
+pub fn track<R: Read>(inner: R, size: Option<u64>) -> Progress<R> {
+    READ.store(0, Ordering::Relaxed);
+    TOTAL.store(size.unwrap_or(0), Ordering::Relaxed);
+    Progress { inner }
+}
+
+impl<R: Read> Read for Progress<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        READ.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// `--more`: passes output through, stopping with a `--More--` prompt once a
+/// screenful has been written. Space shows the next screen, Enter one more
+/// line, `q` quits (reported as a closed pipe, which ends the run quietly).
+/// With `--marks`, `m` skips ahead to the next mark and `M` goes back to the
+/// last one passed, to read on from there.
+pub struct Pager<'a, W: Write> {
+    inner: W,
+    config: &'a Config,
+    /// The line being written, to work out how many rows it wraps to
+    line: Vec<u8>,
+    /// Rows written since the last prompt, how many fit and how wide they are
+    rows: usize,
+    screen: usize,
+    width: usize,
+    /// A screen is full: prompt before anything else is written
+    full: bool,
+    /// `m`: lines are dropped until the next mark
+    skipping: bool,
+    /// The lines since the last mark passed, for `M`
+    since_mark: Option<Vec<Vec<u8>>>,
+}
+
+impl<'a, W: Write> Pager<'a, W> {
+    pub fn new(inner: W, config: &'a Config) -> Self {
+        Pager { inner, config, line: Vec::new(), rows: 0, screen: screen_rows(), width: terminal_width(), full: false, skipping: false, since_mark: None }
+    }
+
+    fn prompt(&mut self) -> io::Result<()> {
+        let text = match percent() {
+            Some(percent) => format!("--More--({}%)", percent),
+            None => "--More--".to_string(),
+        };
+        let (reverse, reset) = if self.config.use_colors { ("\x1B[7m", "\x1B[0m") } else { ("", "") };
+        write!(self.inner, "{}{}{}", reverse, text, reset)?;
+        self.inner.flush()?;
+
+        let key = loop {
+            match read_key()? {
+                key @ (b' ' | b'\r' | b'\n' | b'q' | b'Q') => break key,
+                key @ (b'm' | b'M') if !self.config.marks.is_empty() || self.config.mark_pattern.is_some() => break key,
+                // Ctrl-C: the terminal is back to normal already
+                3 => {
+                    writeln!(self.inner)?;
+                    self.inner.flush()?;
+                    std::process::exit(130);
+                },
+                _ => {},
+            }
+        };
+        // Blanks rather than an erase sequence, which dumb terminals lack
+        write!(self.inner, "\r{}\r", " ".repeat(text.len()))?;
+
+        // The terminal may have been resized while we waited
+        self.screen = screen_rows();
+        self.width = terminal_width();
+        self.full = false;
+        match key {
+            b'q' | b'Q' => Err(io::ErrorKind::BrokenPipe.into()),
+            b' ' => {
+                self.rows = 0;
+                Ok(())
+            },
+            b'm' => {
+                self.skipping = true;
+                Ok(())
+            },
+            // Nothing to go back to before the first mark
+            b'M' => match self.since_mark.clone() {
+                Some(lines) => self.replay(lines),
+                None => {
+                    self.rows = self.screen.saturating_sub(1);
+                    Ok(())
+                },
+            },
+            _ => {
+                self.rows = self.screen.saturating_sub(1);
+                Ok(())
+            },
+        }
+    }
+
+    /// `M`: shows `lines` again, those since the last mark, a screen at a
+    /// time. They end where the output was, so it carries on from there.
+    fn replay(&mut self, lines: Vec<Vec<u8>>) -> io::Result<()> {
+        self.rows = 0;
+        for line in lines {
+            while self.full && !self.skipping {
+                self.prompt()?;
+            }
+            if self.skipping {
+                break;
+            }
+            self.inner.write_all(&line)?;
+            self.count(&line);
+        }
+        Ok(())
+    }
+
+    /// Counts the rows `line` took, with its newline, on the screen.
+    fn count(&mut self, line: &[u8]) {
+        let width = display_width(&String::from_utf8_lossy(&line[..line.len() - 1]));
+        self.rows += width.div_ceil(self.width).max(1);
+        self.full = self.rows >= self.screen;
+    }
+}
+
+impl<W: Write> Write for Pager<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.line.is_empty() && MARKED.swap(false, Ordering::Relaxed) {
+                self.since_mark = Some(Vec::new());
+                // The screen `m` asked for starts with the mark
+                if self.skipping {
+                    self.skipping = false;
+                    self.rows = 0;
+                    self.full = false;
+                }
+            }
+            while self.full && !self.skipping {
+                self.prompt()?;
+            }
+            let end = rest.iter().position(|&b| b == b'\n').map_or(rest.len(), |at| at + 1);
+            let (chunk, after) = rest.split_at(end);
+            if !self.skipping {
+                self.inner.write_all(chunk)?;
+            }
+            self.line.extend_from_slice(chunk);
+
+            if chunk.ends_with(b"\n") {
+                let line = std::mem::take(&mut self.line);
+                if !self.skipping {
+                    self.count(&line);
+                }
+                if let Some(lines) = self.since_mark.as_mut() {
+                    lines.push(line);
+                }
+            }
+            rest = after;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Rows of output per screen: all but the one for the prompt.
+fn screen_rows() -> usize {
+    terminal_height().saturating_sub(1).max(1)
+}
+
+/// Waits for one key press on stdin with the terminal in raw mode, so it needn't
+/// be followed by Enter and Ctrl-C comes through as a byte. End of input is `q`.
+pub fn read_key() -> io::Result<u8> {
+    let _raw = match RawMode::enter() {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(b'q'),
+        Err(err) => return Err(err),
+    };
+    let mut key = [0];
+    Ok(if io::stdin().read(&mut key)? == 0 { b'q' } else { key[0] })
+}
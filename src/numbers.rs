@@ -0,0 +1,310 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::color::{self, Depth};
+use crate::Config;
+
+/// Suffixes `--heat-units` takes as part of a number, longest first so `ms`
+/// isn't read as `m` followed by a letter.
+const UNITS: [&str; 21] = [
+    "KiB", "MiB", "GiB", "TiB", "KB", "MB", "GB", "TB", "ns", "us", "µs", "ms",
+    "B", "K", "M", "G", "k", "s", "m", "h", "d",
+];
+
+/// A number found in a line: its byte range (with the unit, if any) and value.
+/// The value is as written, so `5ms` and `5s` are both 5.
+#[derive(Clone, Copy)]
+pub struct Number {
+    pub start: usize,
+    pub end: usize,
+    pub value: f64,
+}
+
+/// Whether `c` makes a digit next to it part of a word rather than a number.
+/// `#` and `.` count too, for `#123456` colors and `.5` in `v1.2.5`.
+fn joins(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '#' || c == '.'
+}
+
+/// The integers and decimals in `line` that stand on their own. Digits inside
+/// words and hex strings (`a1`, `0x1F`, `3f2a`) don't count, and neither do
+/// runs joined by more than one `.` or by `-`, `:` or `/`: versions, IP
+/// addresses, dates, times and fractions. With `units`, a suffix from `UNITS`
+/// may follow directly. Escape sequences are skipped.
+pub fn scan(line: &str, units: bool) -> Vec<Number> {
+    let bytes = line.as_bytes();
+    let mut numbers = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut i = 0;
+    while i < line.len() {
+        let c = line[i..].chars().next().unwrap_or_default();
+        if c == '\x1B' {
+            i = escape_end(line, i);
+            previous = None;
+            continue;
+        }
+        if !c.is_ascii_digit() || previous.is_some_and(joins) {
+            previous = Some(c);
+            i += c.len_utf8();
+            continue;
+        }
+
+        // The whole run of digit groups and the separators between them
+        let start = i;
+        let mut groups = 1;
+        let mut dotted = true;
+        while i < line.len() {
+            if bytes[i].is_ascii_digit() {
+                i += 1;
+            } else if matches!(bytes[i], b'.' | b'-' | b':' | b'/') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                dotted &= bytes[i] == b'.';
+                groups += 1;
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        let digits_end = i;
+        let unit = if units {
+            UNITS.iter().copied().find(|unit| line[i..].starts_with(unit)).unwrap_or("")
+        } else {
+            ""
+        };
+        i += unit.len();
+        let next = line[i..].chars().next();
+        previous = line[..i].chars().next_back();
+
+        if groups > 2 || groups == 2 && !dotted || next.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            // Skip the rest of the word too, so `12ab34` yields nothing
+            while let Some(c) = line[i..].chars().next().filter(|&c| joins(c)) {
+                i += c.len_utf8();
+                previous = Some(c);
+            }
+            continue;
+        }
+        if let Ok(value) = line[start..digits_end].parse::<f64>() {
+            numbers.push(Number { start, end: i, value });
+        }
+    }
+    numbers
+}
+
+/// Where the escape sequence starting at byte `at` ends.
+fn escape_end(line: &str, at: usize) -> usize {
+    let mut chars = line[at..].char_indices().skip(1).peekable();
+    if chars.next_if(|&(_, c)| c == '[').is_some() {
+        chars.find(|(_, c)| ('@'..='~').contains(c))
+            .map_or(line.len(), |(offset, c)| at + offset + c.len_utf8())
+    } else {
+        chars.next().map_or(line.len(), |(offset, c)| at + offset + c.len_utf8())
+    }
+}
+
+/// `--heat-thresholds`: two increasing numbers, `WARN,ERROR`.
+pub fn parse_thresholds(text: &str) -> Option<(f64, f64)> {
+    let (warn, error) = text.split_once(',')?;
+    let warn: f64 = warn.trim().parse().ok()?;
+    let error: f64 = error.trim().parse().ok()?;
+    (warn.is_finite() && error.is_finite() && warn < error).then_some((warn, error))
+}
+
+/// Steps between the warning and the error color on terminals that have them.
+const STEPS: usize = 4;
+
+/// `--number-heat`: the color for each number, by where it falls against two
+/// thresholds. Below the first it stays as it is; from there it goes from
+/// yellow towards red (in steps on terminals with more than 16 colors), and at
+/// the second it gets the error color.
+pub struct Heat {
+    warn: f64,
+    error: f64,
+    /// Yellow first; just the one on 16-color terminals
+    gradient: Vec<String>,
+    error_color: String,
+}
+
+impl Heat {
+    /// The heat for one input: from its own numbers with `--heat-auto` where
+    /// that works, else from `--heat-thresholds`.
+    pub fn for_input(file_name: &str, config: &Config) -> Self {
+        if config.heat_auto {
+            if let Some(heat) = Heat::from_file(file_name, config) {
+                return heat;
+            }
+            if !config.quiet {
+                eprintln!("meow: {}", msg!("heat-auto-fallback", file_name));
+            }
+        }
+        let (warn, error) = config.heat_thresholds;
+        Heat::new(warn, error, config)
+    }
+
+    pub fn new(warn: f64, error: f64, config: &Config) -> Self {
+        let steps = if config.color_depth == Depth::Ansi16 { 1 } else { STEPS };
+        // Hue 60 is yellow and 0 red; the last step stops short of red
+        let gradient = (0..steps)
+            .map(|step| color::fg(color::hue((60 - 60 * step / STEPS) as u32), config.color_depth))
+            .collect();
+        Heat { warn, error, gradient, error_color: config.colors.error.clone() }
+    }
+
+    /// `--heat-auto`: thresholds at the 90th and 99th percentile of the numbers
+    /// in a regular file, read once before it's shown. If they're the same, the
+    /// largest number is the error threshold; if that's no bigger either, nothing
+    /// stands out and nothing is colored. None for anything but a regular file
+    /// (a pipe can't be read twice) or a file without numbers.
+    fn from_file(file_name: &str, config: &Config) -> Option<Self> {
+        let path = Path::new(file_name);
+        if !path.is_file() {
+            return None;
+        }
+        let file = File::open(path).ok()?;
+        let mut values: Vec<f64> = Vec::new();
+        for line in BufReader::new(file).split(b'\n') {
+            let line = String::from_utf8_lossy(&line.ok()?).into_owned();
+            values.extend(scan(&line, config.heat_units).iter().map(|number| number.value));
+        }
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let at = |percent: usize| values[(values.len() - 1) * percent / 100];
+        let largest = values[values.len() - 1];
+        let warn = if at(90) < largest { at(90) } else { f64::INFINITY };
+        let error = if at(99) > at(90) { at(99) } else { largest };
+        Some(Heat::new(warn, error, config))
+    }
+
+    /// The color for `value`, None below the first threshold.
+    pub fn paint(&self, value: f64) -> Option<&str> {
+        if value < self.warn {
+            return None;
+        }
+        if value >= self.error {
+            return Some(&self.error_color);
+        }
+        // Measured on a log scale when it can be, as these often span magnitudes
+        let position = if self.warn > 0.0 {
+            (value / self.warn).ln() / (self.error / self.warn).ln()
+        } else {
+            (value - self.warn) / (self.error - self.warn)
+        };
+        let step = ((position * self.gradient.len() as f64) as usize).min(self.gradient.len() - 1);
+        Some(&self.gradient[step])
+    }
+
+    /// Color spans for the numbers in `line`, for `render_highlights`.
+    pub fn spans<'a>(&'a self, line: &str, config: &Config) -> Vec<(usize, usize, &'a str)> {
+        scan(line, config.heat_units).into_iter()
+            .filter_map(|number| Some((number.start, number.end, self.paint(number.value)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ColorConfig;
+    use std::io::Write;
+
+    /// The numbers `scan` finds in `line`, as written.
+    fn found(line: &str, units: bool) -> Vec<&str> {
+        scan(line, units).iter().map(|number| &line[number.start..number.end]).collect()
+    }
+
+    #[test]
+    fn plain_numbers() {
+        assert_eq!(found("took 250 ms, 3.75 avg, 0 errors", false), ["250", "3.75", "0"]);
+        assert_eq!(found("(42) [7], x=12;", false), ["42", "7", "12"]);
+        assert_eq!(scan("1.5", false)[0].value, 1.5);
+    }
+
+    #[test]
+    fn not_numbers() {
+        // Versions, IPs, dates, times, fractions
+        assert!(found("v1.2.3 1.2.3 10.0.0.1 2026-10-16 12:30:05 3/4", false).is_empty());
+        // Inside words and hex
+        assert!(found("a1 x86_64 0x1F 3f2a deadbeef12 #123456 12ab34", false).is_empty());
+        // The second part of a version after a letter
+        assert!(found("v2.5", false).is_empty());
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(found("5ms 10KiB 3s 2GB 7µs", true), ["5ms", "10KiB", "3s", "2GB", "7µs"]);
+        assert_eq!(scan("5ms", true)[0].value, 5.0);
+        // Without --heat-units they're a word
+        assert!(found("5ms 10KiB", false).is_empty());
+        assert!(found("5msec", true).is_empty());
+    }
+
+    #[test]
+    fn escapes_are_skipped() {
+        assert_eq!(found("\x1B[38;5;196m99\x1B[0m", false), ["99"]);
+    }
+
+    #[test]
+    fn thresholds() {
+        assert_eq!(parse_thresholds("100,1000"), Some((100.0, 1000.0)));
+        assert_eq!(parse_thresholds(" 0.5 , 2 "), Some((0.5, 2.0)));
+        assert_eq!(parse_thresholds("1000,100"), None);
+        assert_eq!(parse_thresholds("100,100"), None);
+        assert_eq!(parse_thresholds("100"), None);
+        assert_eq!(parse_thresholds("a,b"), None);
+        assert_eq!(parse_thresholds("1,inf"), None);
+    }
+
+    fn heat(depth: Depth) -> Heat {
+        let mut config = Config::new();
+        config.color_depth = depth;
+        config.colors = ColorConfig::new(true, depth);
+        Heat::new(100.0, 1000.0, &config)
+    }
+
+    #[test]
+    fn gradient() {
+        let heat = heat(Depth::TrueColor);
+        assert_eq!(heat.paint(99.0), None);
+        assert_eq!(heat.paint(100.0), Some("\x1B[38;2;255;255;0m"));
+        // Log scale: 300 is about halfway from 100 to 1000
+        assert_eq!(heat.paint(320.0), Some("\x1B[38;2;255;127;0m"));
+        assert_eq!(heat.paint(999.0), Some("\x1B[38;2;255;63;0m"));
+        assert_eq!(heat.paint(1000.0), Some("\x1B[31m"));
+        assert_eq!(heat.paint(1e9), Some("\x1B[31m"));
+
+        let heat = self::heat(Depth::Ansi16);
+        assert_eq!(heat.paint(100.0), heat.paint(999.0));
+        assert_eq!(heat.paint(1000.0), Some("\x1B[31m"));
+    }
+
+    #[test]
+    fn gradient_from_zero() {
+        let mut config = Config::new();
+        config.color_depth = Depth::TrueColor;
+        let heat = Heat::new(0.0, 4.0, &config);
+        assert_eq!(heat.paint(-1.0), None);
+        assert_eq!(heat.paint(0.0), Some("\x1B[38;2;255;255;0m"));
+        assert_eq!(heat.paint(3.0), Some("\x1B[38;2;255;63;0m"));
+    }
+
+    #[test]
+    fn auto_thresholds() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for i in 1..=100 {
+            writeln!(file, "value {}", i).unwrap();
+        }
+        let mut config = Config::new();
+        config.color_depth = Depth::TrueColor;
+        config.colors = ColorConfig::new(true, Depth::TrueColor);
+        let heat = Heat::from_file(file.path().to_str().unwrap(), &config).unwrap();
+        assert_eq!((heat.warn, heat.error), (90.0, 99.0));
+        assert!(Heat::from_file("/nonexistent", &config).is_none());
+
+        // All the same: nothing stands out
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "5 5 5").unwrap();
+        let heat = Heat::from_file(file.path().to_str().unwrap(), &config).unwrap();
+        assert_eq!(heat.paint(5.0), None);
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+
+use crate::columns::{display_width, graphemes};
+use crate::terminal::{read_byte, read_byte_soon, RawTerminal};
+use crate::{display_input, more, print_file_header, terminal_height, terminal_width, Config, RunState};
+
+/// `--pick`: lets the user choose a file in the current directory, shows it with
+/// the active options, and comes back to the list until Esc.
+pub fn run(config: &Config) -> bool {
+    if !more::available() {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("pick-needs-terminal"), config.colors.reset);
+        return false;
+    }
+    let mut picker = Picker { query: String::new(), selected: 0, top: 0, config };
+    loop {
+        let name = match picker.pick() {
+            Ok(Some(name)) => name,
+            Ok(None) => return true,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("pick-failed", err), config.colors.reset);
+                return false;
+            }
+        };
+
+        let path = Path::new(&name);
+        let file_config = config.for_file(0, Some(path));
+        match File::open(path) {
+            Ok(file) => {
+                print_file_header(&name, path, None, &file_config);
+                display_input(&mut BufReader::new(file), &file_config, &name, &mut 0, &mut RunState::default());
+            },
+            Err(err) => eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset),
+        }
+
+        print!("{}{}{}", config.colors.highlight, msg!("pick-return"), config.colors.reset);
+        let _ = io::stdout().flush();
+        let key = more::read_key();
+        println!();
+        if matches!(key, Ok(b'q' | b'Q' | 3 | 4) | Err(_)) {
+            return true;
+        }
+    }
+}
+
+/// Scores `name` against the typed `query`, None unless the query's characters
+/// all appear in it in order, ignoring case. Runs of consecutive characters and
+/// matches at the start of a word count extra.
+pub fn score(query: &str, name: &str) -> Option<i64> {
+    let mut wanted = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in name.chars() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        let matched = c.to_lowercase().eq(std::iter::once(next));
+        if matched {
+            wanted.next();
+            score += 1;
+            if previous_matched {
+                score += 5;
+            }
+            if previous.is_none_or(|previous| matches!(previous, '-' | '_' | '.' | ' ' | '/')) {
+                score += 3;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    wanted.peek().is_none().then_some(score)
+}
+
+/// Files in the current directory that match `query`, best first, then shorter
+/// and alphabetical. Hidden files only show up once the query starts with a dot.
+fn candidates(query: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(".") else {
+        return Vec::new();
+    };
+    let mut scored: Vec<(i64, String)> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.') || query.starts_with('.'))
+        .filter_map(|name| score(query, &name).map(|score| (score, name)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.len().cmp(&b.1.len())).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, name)| name).collect()
+}
+
+enum Key {
+    Char(char),
+    Backspace,
+    ClearQuery,
+    Up,
+    Down,
+    Enter,
+    Quit,
+    Other,
+}
+
+struct Picker<'a> {
+    query: String,
+    selected: usize,
+    /// First candidate on screen, so the selection can scroll past the bottom
+    top: usize,
+    config: &'a Config,
+}
+
+impl Picker<'_> {
+    /// Shows the list on the alternate screen until Enter (the chosen name) or
+    /// Esc (None). The terminal is put back however this returns.
+    fn pick(&mut self) -> io::Result<Option<String>> {
+        let _terminal = RawTerminal::enter()?;
+        let mut names = candidates(&self.query);
+        loop {
+            self.selected = self.selected.min(names.len().saturating_sub(1));
+            self.draw(&names)?;
+            match read_key()? {
+                Key::Char(c) => {
+                    self.query.push(c);
+                    names = candidates(&self.query);
+                    self.selected = 0;
+                },
+                Key::Backspace => {
+                    self.query.pop();
+                    names = candidates(&self.query);
+                    self.selected = 0;
+                },
+                Key::ClearQuery => {
+                    self.query.clear();
+                    names = candidates(&self.query);
+                    self.selected = 0;
+                },
+                Key::Up => self.selected = self.selected.saturating_sub(1),
+                Key::Down => self.selected += 1,
+                Key::Enter => {
+                    if let Some(name) = names.get(self.selected) {
+                        return Ok(Some(name.clone()));
+                    }
+                },
+                Key::Quit => return Ok(None),
+                Key::Other => {},
+            }
+        }
+    }
+
+    fn draw(&mut self, names: &[String]) -> io::Result<()> {
+        let config = self.config;
+        let width = terminal_width();
+        // One row for the query line
+        let rows = terminal_height().saturating_sub(1).max(1);
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+
+        let mut out = io::stdout().lock();
+        write!(out, "\x1B[H\x1B[2J")?;
+        write!(out, "{}>{} {}  {}{}/{}{}", config.colors.success, config.colors.reset, self.query,
+               config.colors.dim, names.len().min(self.selected + 1), names.len(), config.colors.reset)?;
+        for (i, name) in names.iter().enumerate().skip(self.top).take(rows) {
+            let shown = truncate(name, width.saturating_sub(2));
+            if i == self.selected {
+                let (reverse, reset) = if config.use_colors { ("\x1B[7m", "\x1B[0m") } else { ("", "") };
+                write!(out, "\n> {}{}{}", reverse, shown, reset)?;
+            } else {
+                write!(out, "\n  {}", shown)?;
+            }
+        }
+        // Leave the cursor after the query, where typing goes
+        write!(out, "\x1B[1;{}H", 3 + display_width(&self.query))?;
+        out.flush()
+    }
+}
+
+fn truncate(name: &str, width: usize) -> String {
+    if display_width(name) <= width {
+        return name.to_string();
+    }
+    let mut shown = String::new();
+    for cluster in graphemes(name) {
+        if display_width(&shown) + display_width(cluster) + 1 > width {
+            break;
+        }
+        shown.push_str(cluster);
+    }
+    shown.push('\u{2026}');
+    shown
+}
+
+fn read_key() -> io::Result<Key> {
+    let Some(byte) = read_byte()? else {
+        return Ok(Key::Quit);
+    };
+    Ok(match byte {
+        b'\r' | b'\n' => Key::Enter,
+        127 | 8 => Key::Backspace,
+        // Ctrl-U, like in a shell
+        21 => Key::ClearQuery,
+        // Ctrl-P and Ctrl-N
+        16 => Key::Up,
+        14 => Key::Down,
+        // Ctrl-C and Ctrl-D
+        3 | 4 => Key::Quit,
+        0x1B => match read_byte_soon()? {
+            None => Key::Quit,
+            Some(b'[' | b'O') => match read_byte_soon()? {
+                Some(b'A') => Key::Up,
+                Some(b'B') => Key::Down,
+                _ => Key::Other,
+            },
+            Some(_) => Key::Other,
+        },
+        byte if byte < 0x20 => Key::Other,
+        byte => {
+            // The rest of a UTF-8 character follows its first byte
+            let length = match byte {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            let mut bytes = vec![byte];
+            for _ in 1..length {
+                bytes.extend(read_byte()?);
+            }
+            match std::str::from_utf8(&bytes).ok().and_then(|text| text.chars().next()) {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_needs_the_query_in_order() {
+        assert_eq!(score("", "anything"), Some(0));
+        assert!(score("mn", "main.rs").is_some());
+        assert_eq!(score("nm", "main.rs"), None);
+        assert_eq!(score("xyz", "main.rs"), None);
+        assert!(score("MAIN", "main.rs").is_some());
+    }
+
+    #[test]
+    fn runs_and_word_starts_score_higher() {
+        let scored = |name: &str| score("rea", name).unwrap();
+        // A run at the start beats scattered letters
+        assert!(scored("readme.md") > scored("rename_all.txt"));
+        // The start of a word after a separator counts
+        assert!(scored("old-readme") > scored("oldreadme"));
+        assert!(score("ab", "a_b").unwrap() > score("ab", "axb").unwrap());
+    }
+
+    #[test]
+    fn truncate_long_names() {
+        assert_eq!(truncate("short.txt", 20), "short.txt");
+        assert_eq!(truncate("a-rather-long-name.txt", 10), "a-rather-\u{2026}");
+        assert_eq!(truncate("日本語のファイル.txt", 7), "日本語\u{2026}");
+    }
+}
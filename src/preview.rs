@@ -0,0 +1,191 @@
+use std::io::{self, Write};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+
+use crate::color::{self, Depth};
+
+/// Renders an image to the terminal using the requested protocol.
+/// `protocol` is one of `auto`, `blocks`, `kitty` or `iterm`; `depth` is
+/// what the block colors are reduced to.
+pub fn render_image(data: &[u8], protocol: &str, width: usize, depth: Depth, out: &mut dyn Write) -> io::Result<()> {
+    let protocol = if protocol == "auto" { detect_protocol() } else { protocol };
+
+    // iTerm2 decodes the original file itself, no need to touch the pixels
+    if protocol == "iterm" {
+        return write_iterm(data, out);
+    }
+
+    let img = image::load_from_memory(data)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    if protocol == "kitty" {
+        write_kitty(&img, kitty_columns(img.width(), width, cell_width()), out)
+    } else {
+        write_blocks(&downscale(&img, width), depth, out)
+    }
+}
+
+fn detect_protocol() -> &'static str {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+    if term == "xterm-kitty" || std::env::var_os("KITTY_WINDOW_ID").is_some() {
+        "kitty"
+    } else if term_program == "iTerm.app" {
+        "iterm"
+    } else {
+        "blocks"
+    }
+}
+
+/// Scales the image to at most `width` columns. Each cell shows two pixel rows,
+/// so the height is kept even and the aspect ratio is preserved.
+fn downscale(img: &DynamicImage, width: usize) -> RgbaImage {
+    let (img_width, img_height) = img.dimensions();
+    let target_width = (width as u32).clamp(1, img_width.max(1));
+    let mut target_height = (img_height as u64 * target_width as u64 / img_width.max(1) as u64) as u32;
+    target_height = target_height.max(2);
+    target_height += target_height % 2;
+
+    img.resize_exact(target_width, target_height, FilterType::Triangle).to_rgba8()
+}
+
+/// Draws the image with upper half blocks: the foreground color paints the top
+/// pixel and the background color the bottom one.
+fn write_blocks(img: &RgbaImage, depth: Depth, out: &mut dyn Write) -> io::Result<()> {
+    let (width, height) = img.dimensions();
+
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let top = blend(img.get_pixel(x, y).0);
+            let bottom = if y + 1 < height { blend(img.get_pixel(x, y + 1).0) } else { [0, 0, 0] };
+            write!(out, "{}{}\u{2580}", color::fg(top, depth), color::bg(bottom, depth))?;
+        }
+        writeln!(out, "\x1B[0m")?;
+    }
+
+    Ok(())
+}
+
+/// Composites a pixel onto a black background.
+fn blend(pixel: [u8; 4]) -> [u8; 3] {
+    let alpha = pixel[3] as u16;
+    [
+        (pixel[0] as u16 * alpha / 255) as u8,
+        (pixel[1] as u16 * alpha / 255) as u8,
+        (pixel[2] as u16 * alpha / 255) as u8,
+    ]
+}
+
+/// Pixels across a cell, for terminals that don't say.
+const DEFAULT_CELL_WIDTH: u32 = 8;
+
+/// The columns kitty is told to fit an image `img_width` pixels wide into: as
+/// many as it takes at the terminal's cell width, but no more than `width`.
+fn kitty_columns(img_width: u32, width: usize, cell_width: Option<u32>) -> usize {
+    let cell = cell_width.unwrap_or(DEFAULT_CELL_WIDTH);
+    (img_width.div_ceil(cell) as usize).clamp(1, width.max(1))
+}
+
+/// Pixels across a cell of the terminal on stdout, if it says.
+#[cfg(unix)]
+fn cell_width() -> Option<u32> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass in
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 || size.ws_xpixel == 0 {
+        return None;
+    }
+    Some((size.ws_xpixel / size.ws_col).max(1) as u32)
+}
+
+#[cfg(not(unix))]
+fn cell_width() -> Option<u32> {
+    None
+}
+
+/// Kitty graphics protocol: raw RGBA payload, base64 encoded in 4096 byte
+/// chunks, scaled by the terminal to `columns` cells across (`c=`), keeping
+/// its aspect ratio.
+fn write_kitty(img: &DynamicImage, columns: usize, out: &mut dyn Write) -> io::Result<()> {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let encoded = base64_encode(rgba.as_raw());
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            write!(out, "\x1B_Ga=T,f=32,s={},v={},c={},m={};", width, height, columns, more)?;
+        } else {
+            write!(out, "\x1B_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1B\\")?;
+    }
+    writeln!(out)
+}
+
+/// iTerm2 inline image protocol (OSC 1337) with the original file bytes.
+fn write_iterm(data: &[u8], out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "\x1B]1337;File=inline=1;size={}:{}\x07", data.len(), base64_encode(data))?;
+    writeln!(out)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        encoded.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        encoded.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        encoded.push(if chunk.len() > 1 { ALPHABET[(n >> 6) as usize & 63] as char } else { '=' });
+        encoded.push(if chunk.len() > 2 { ALPHABET[n as usize & 63] as char } else { '=' });
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kitty_fits_the_image_into_the_width() {
+        // 800 pixels at 10 a cell want 80 columns
+        assert_eq!(kitty_columns(800, 40, Some(10)), 40);
+        assert_eq!(kitty_columns(800, 120, Some(10)), 80);
+        // Small images aren't blown up, tiny ones still get a column
+        assert_eq!(kitty_columns(25, 80, Some(10)), 3);
+        assert_eq!(kitty_columns(1, 80, Some(10)), 1);
+        assert_eq!(kitty_columns(800, 60, None), 60);
+        assert_eq!(kitty_columns(80, 60, None), 10);
+    }
+
+    #[test]
+    fn kitty_asks_for_the_columns() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(4, 2));
+        let mut out = Vec::new();
+        write_kitty(&img, 12, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1B_Ga=T,f=32,s=4,v=2,c=12,m=0;"), "{:?}", out);
+        assert!(out.ends_with("\x1B\\\n"));
+    }
+
+    #[test]
+    fn blocks_are_no_wider_than_asked() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(100, 50));
+        let scaled = downscale(&img, 20);
+        assert_eq!(scaled.dimensions(), (20, 10));
+        // Never wider than the image itself
+        assert_eq!(downscale(&img, 400).width(), 100);
+    }
+
+    #[test]
+    fn base64_pads_the_last_group() {
+        assert_eq!(base64_encode(b"meow"), "bWVvdw==");
+        assert_eq!(base64_encode(b"cat"), "Y2F0");
+    }
+}
@@ -0,0 +1,154 @@
+use std::io::{self, Write};
+
+use qrcodegen::{DataTooLong, QrCode, QrCodeEcc, QrSegment, Version};
+
+/// Light modules around the symbol, as many as the spec asks for. Less
+/// confuses some scanners, on dark terminals especially.
+const QUIET_ZONE: i32 = 4;
+
+/// Encodes `data` in the smallest QR version that holds it, no wider than `max_width`
+/// columns. The error correction level is raised as far as the chosen version allows.
+/// On failure returns the maximum number of bytes that would have fit.
+pub fn encode(data: &[u8], max_width: usize) -> Result<QrCode, usize> {
+    // Each module is one column wide; version N is 17 + 4N modules across
+    let modules = max_width as i32 - 2 * QUIET_ZONE;
+    let max_version = ((modules - 17) / 4).clamp(Version::MIN.value() as i32, Version::MAX.value() as i32) as u8;
+
+    let segments = [QrSegment::make_bytes(data)];
+    QrCode::encode_segments_advanced(&segments, QrCodeEcc::Low, Version::MIN, Version::new(max_version), None, true)
+        .map_err(|err| match err {
+            // Capacity minus the byte-mode header (4 bit mode, 8 or 16 bit length)
+            DataTooLong::DataOverCapacity(_, capacity) => {
+                let header = if max_version < 10 { 12 } else { 20 };
+                capacity.saturating_sub(header) / 8
+            },
+            DataTooLong::SegmentTooLong => 0,
+        })
+}
+
+/// Draws the code with half blocks, two module rows per line. Filled cells are
+/// light modules, which reads correctly on a dark terminal; `invert` fills the
+/// dark modules instead for light backgrounds. With colors the terminal colors
+/// are pinned to black and white so the result scans either way.
+pub fn write_qr(code: &QrCode, invert: bool, use_colors: bool, out: &mut dyn Write) -> io::Result<()> {
+    let filled = |x: i32, y: i32| {
+        let dark = code.get_module(x, y);
+        if invert { dark } else { !dark }
+    };
+    let (start, end) = if use_colors {
+        (if invert { "\x1B[30;107m" } else { "\x1B[97;40m" }, "\x1B[0m")
+    } else {
+        ("", "")
+    };
+
+    let size = code.size();
+    for y in (-QUIET_ZONE..size + QUIET_ZONE).step_by(2) {
+        write!(out, "{}", start)?;
+        for x in -QUIET_ZONE..size + QUIET_ZONE {
+            // get_module treats coordinates outside the symbol as light
+            let cell = match (filled(x, y), filled(x, y + 1)) {
+                (true, true) => '\u{2588}',
+                (true, false) => '\u{2580}',
+                (false, true) => '\u{2584}',
+                (false, false) => ' ',
+            };
+            write!(out, "{}", cell)?;
+        }
+        writeln!(out, "{}", end)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads a rendering back into rows of modules, true for dark.
+    fn read_back(text: &str, invert: bool) -> Vec<Vec<bool>> {
+        let mut rows = Vec::new();
+        for line in text.lines() {
+            let (top, bottom): (Vec<bool>, Vec<bool>) = line.chars().map(|c| match c {
+                '\u{2588}' => (true, true),
+                '\u{2580}' => (true, false),
+                '\u{2584}' => (false, true),
+                ' ' => (false, false),
+                other => panic!("unexpected {:?} in a QR code", other),
+            }).unzip();
+            rows.push(top);
+            rows.push(bottom);
+        }
+        for row in &mut rows {
+            for module in row.iter_mut() {
+                // Filled cells are light unless inverted
+                *module = *module == invert;
+            }
+        }
+        rows
+    }
+
+    fn render(data: &[u8], invert: bool) -> (i32, Vec<Vec<bool>>) {
+        let code = encode(data, 80).unwrap();
+        let mut out = Vec::new();
+        write_qr(&code, invert, false, &mut out).unwrap();
+        (code.size(), read_back(&String::from_utf8(out).unwrap(), invert))
+    }
+
+    #[test]
+    fn rendering_has_the_symbol_and_a_quiet_zone() {
+        let (size, rows) = render(b"WIFI:S:home;T:WPA;P:hunter2;;", false);
+        let across = (size + 2 * QUIET_ZONE) as usize;
+        assert_eq!(size % 4, 1, "not a QR size: {}", size);
+        // Half blocks pair the rows, so an odd height gets one light row more
+        assert_eq!(rows.len(), across + 1);
+        assert!(rows.iter().all(|row| row.len() == across));
+        let quiet = QUIET_ZONE as usize;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &dark) in row.iter().enumerate() {
+                let inside = (quiet..across - quiet).contains(&x) && (quiet..across - quiet).contains(&y);
+                assert!(inside || !dark, "dark module in the quiet zone at {},{}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn rendering_has_the_three_finder_patterns() {
+        let (size, rows) = render(b"hello", false);
+        let quiet = QUIET_ZONE as usize;
+        let far = (size - 7) as usize;
+        for (left, top) in [(0, 0), (far, 0), (0, far)] {
+            for dy in 0..7 {
+                for dx in 0..7 {
+                    // Dark ring, light ring, dark 3x3 center
+                    let ring = (dx as i32 - 3).abs().max((dy as i32 - 3).abs());
+                    assert_eq!(rows[quiet + top + dy][quiet + left + dx], ring != 2, "finder at {},{}", left, top);
+                }
+            }
+        }
+        // The fourth corner has none
+        assert!(!rows[quiet + far + 1][quiet + far + 1..quiet + far + 6].iter().all(|&dark| dark));
+    }
+
+    #[test]
+    fn invert_gives_the_same_modules() {
+        assert_eq!(render(b"hello", true), render(b"hello", false));
+    }
+
+    #[test]
+    fn colors_pin_black_and_white() {
+        let code = encode(b"hello", 80).unwrap();
+        let mut out = Vec::new();
+        write_qr(&code, false, true, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.lines().all(|line| line.starts_with("\x1B[97;40m") && line.ends_with("\x1B[0m")));
+    }
+
+    #[test]
+    fn too_much_data_reports_what_would_fit() {
+        let max_bytes = encode(&[b'x'; 4000], 80).err().unwrap();
+        assert!(max_bytes > 0);
+        assert!(encode(&vec![b'x'; max_bytes], 80).is_ok());
+        assert_eq!(encode(&vec![b'x'; max_bytes + 1], 80).err(), Some(max_bytes));
+        assert!(encode(&vec![b'x'; max_bytes], 80).unwrap().size() + 2 * QUIET_ZONE <= 80);
+    }
+}
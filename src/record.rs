@@ -0,0 +1,268 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::Config;
+
+/// First line of a recording. The number goes up when the frame format changes.
+const MAGIC: &str = "meowrec";
+const VERSION: u32 = 1;
+
+/// What a frame holds.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// A line typed at the prompt, without its newline
+    Command,
+    /// Bytes the shell wrote to the terminal
+    Output,
+}
+
+impl Kind {
+    fn tag(self) -> char {
+        match self {
+            Kind::Command => 'c',
+            Kind::Output => 'o',
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Kind> {
+        match tag {
+            "c" => Some(Kind::Command),
+            "o" => Some(Kind::Output),
+            _ => None,
+        }
+    }
+}
+
+/// `--record`: writes an interactive session to a file as it happens. The file
+/// is `meowrec 1` and then one frame after another, each a header line
+/// `TAG MILLIS LENGTH` (tag `c` for a command, `o` for output, milliseconds
+/// since the start) followed by LENGTH bytes and a newline. Every frame is
+/// written in one go and never touched again, so a session that crashes still
+/// replays up to the last whole frame.
+pub struct Recorder {
+    path: String,
+    file: File,
+    start: Instant,
+    /// Set after the first write error, which is reported once
+    failed: bool,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{} {}", MAGIC, VERSION)?;
+        Ok(Recorder { path: path.to_string(), file, start: Instant::now(), failed: false })
+    }
+
+    fn frame(&mut self, kind: Kind, bytes: &[u8]) {
+        if self.failed || bytes.is_empty() {
+            return;
+        }
+        let mut frame = format!("{} {} {}\n", kind.tag(), self.start.elapsed().as_millis(), bytes.len()).into_bytes();
+        frame.extend_from_slice(bytes);
+        frame.push(b'\n');
+        if let Err(err) = self.file.write_all(&frame) {
+            eprintln!("meow: {}", msg!("record-error", self.path, err));
+            self.failed = true;
+        }
+    }
+
+    pub fn command(&mut self, line: &str) {
+        self.frame(Kind::Command, line.as_bytes());
+    }
+}
+
+/// Where the interactive shell writes: the terminal, and the recording if
+/// there is one. Output is recorded a line at a time (or up to a flush, like
+/// the prompt), rather than in the pieces `write!` hands over.
+pub struct ShellOutput<'a> {
+    stdout: io::Stdout,
+    pub recorder: Option<&'a mut Recorder>,
+    pending: Vec<u8>,
+}
+
+impl<'a> ShellOutput<'a> {
+    pub fn new(recorder: Option<&'a mut Recorder>) -> Self {
+        ShellOutput { stdout: io::stdout(), recorder, pending: Vec::new() }
+    }
+
+    fn record_pending(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.frame(Kind::Output, &self.pending);
+        }
+        self.pending.clear();
+    }
+}
+
+impl Write for ShellOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write_all(buf)?;
+        if self.recorder.is_some() {
+            self.pending.extend_from_slice(buf);
+            if self.pending.ends_with(b"\n") {
+                self.record_pending();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.record_pending();
+        self.stdout.flush()
+    }
+}
+
+impl Drop for ShellOutput<'_> {
+    fn drop(&mut self) {
+        self.record_pending();
+    }
+}
+
+/// `--replay-speed`: `2`, `2x` or `0.5x`.
+pub fn parse_speed(text: &str) -> Option<f64> {
+    text.strip_suffix(['x', 'X'])
+        .unwrap_or(text)
+        .parse::<f64>()
+        .ok()
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+}
+
+/// `--replay`: plays a recording back with its timing (scaled by
+/// `--replay-speed`, or none with `--replay-instant`). Output is written byte
+/// for byte, colors and all, and commands are shown as they were typed; nothing
+/// is run. A recording cut short by a crash plays up to its last whole frame.
+/// Returns false if the file can't be read or isn't a recording.
+pub fn replay(path: &str, config: &Config) -> bool {
+    let mut data = Vec::new();
+    if let Err(err) = File::open(path).and_then(|mut file| file.read_to_end(&mut data)) {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", path, err), config.colors.reset);
+        return false;
+    }
+
+    let Some((header, mut rest)) = split_line(&data) else {
+        eprintln!("{}meow: {}{}", config.colors.error, msg!("replay-not-recording", path), config.colors.reset);
+        return false;
+    };
+    match header.split_once(' ') {
+        Some((MAGIC, version)) if version.parse() == Ok(VERSION) => {},
+        Some((MAGIC, version)) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("replay-unsupported-version", path, version), config.colors.reset);
+            return false;
+        },
+        _ => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("replay-not-recording", path), config.colors.reset);
+            return false;
+        },
+    }
+
+    let start = Instant::now();
+    let mut out = io::stdout().lock();
+    while !rest.is_empty() {
+        let Some((kind, millis, bytes, after)) = parse_frame(rest) else {
+            if !config.quiet {
+                eprintln!("meow: {}", msg!("replay-truncated", path));
+            }
+            break;
+        };
+        rest = after;
+
+        if !config.replay_instant {
+            let due = Duration::from_secs_f64(millis as f64 / 1000.0 / config.replay_speed);
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+        let result = match kind {
+            Kind::Output => out.write_all(bytes).and_then(|()| out.flush()),
+            Kind::Command => out.write_all(bytes).and_then(|()| out.write_all(b"\n")).and_then(|()| out.flush()),
+        };
+        if result.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// The first line of `data` as text, and what follows it.
+fn split_line(data: &[u8]) -> Option<(&str, &[u8])> {
+    let end = data.iter().position(|&b| b == b'\n')?;
+    Some((std::str::from_utf8(&data[..end]).ok()?, &data[end + 1..]))
+}
+
+/// One frame from the start of `data`: its kind, time, content and what comes
+/// after it. None if it's malformed or cut off.
+fn parse_frame(data: &[u8]) -> Option<(Kind, u64, &[u8], &[u8])> {
+    let (header, rest) = split_line(data)?;
+    let mut fields = header.split(' ');
+    let kind = Kind::from_tag(fields.next()?)?;
+    let millis = fields.next()?.parse().ok()?;
+    let length: usize = fields.next()?.parse().ok()?;
+    if fields.next().is_some() || rest.len() <= length || rest[length] != b'\n' {
+        return None;
+    }
+    Some((kind, millis, &rest[..length], &rest[length + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speeds() {
+        assert_eq!(parse_speed("2"), Some(2.0));
+        assert_eq!(parse_speed("2x"), Some(2.0));
+        assert_eq!(parse_speed("0.5X"), Some(0.5));
+        assert_eq!(parse_speed("0"), None);
+        assert_eq!(parse_speed("-1x"), None);
+        assert_eq!(parse_speed("inf"), None);
+        assert_eq!(parse_speed("fast"), None);
+    }
+
+    #[test]
+    fn frames() {
+        let data = b"o 15 3\na\nb\nc 20 0\n\nrest";
+        let (kind, millis, bytes, rest) = parse_frame(data).unwrap();
+        assert!(kind == Kind::Output);
+        assert_eq!((millis, bytes), (15, &b"a\nb"[..]));
+        let (kind, millis, bytes, rest) = parse_frame(rest).unwrap();
+        assert!(kind == Kind::Command);
+        assert_eq!((millis, bytes, rest), (20, &b""[..], &b"rest"[..]));
+    }
+
+    #[test]
+    fn broken_frames() {
+        // Cut off, a wrong length, unknown tags, extra fields
+        for data in [&b"o 1 5\nab"[..], b"o 1 2\nabc\n", b"x 1 1\na\n", b"o 1 1 1\na\n", b"o one 1\na\n", b"o 1 1"] {
+            assert!(parse_frame(data).is_none(), "{:?}", String::from_utf8_lossy(data));
+        }
+    }
+
+    #[test]
+    fn recorder_writes_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.meowrec");
+        let mut recorder = Recorder::create(path.to_str().unwrap()).unwrap();
+        recorder.command("cat a.txt");
+        {
+            let mut output = ShellOutput::new(Some(&mut recorder));
+            // Pieces of a line make one frame, a flush ends one early
+            output.write_all(b"on").unwrap();
+            output.write_all(b"e\n").unwrap();
+            output.write_all(b"meow> ").unwrap();
+            output.flush().unwrap();
+        }
+        recorder.command("");
+        let data = std::fs::read(&path).unwrap();
+        let (header, mut rest) = split_line(&data).unwrap();
+        assert_eq!(header, "meowrec 1");
+        let mut frames = Vec::new();
+        while let Some((kind, _, bytes, after)) = parse_frame(rest) {
+            frames.push((kind.tag(), String::from_utf8_lossy(bytes).into_owned()));
+            rest = after;
+        }
+        assert!(rest.is_empty());
+        assert_eq!(frames, [('c', "cat a.txt".to_string()), ('o', "one\n".to_string()), ('o', "meow> ".to_string())]);
+    }
+}
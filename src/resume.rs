@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How far a file has been shown, as saved by `--resume`.
+#[derive(Clone, Copy)]
+pub struct Position {
+    /// Byte offset of the first line not shown yet
+    pub offset: u64,
+    /// Lines before `offset`
+    pub line: usize,
+    /// Modification time of the file when it was read, in seconds
+    pub mtime: u64,
+    /// When the position was saved, in seconds
+    pub saved: u64,
+}
+
+/// The state file: `meow/resume` under `$XDG_DATA_HOME` (or `~/.local/share`).
+pub fn state_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share")))?;
+    Some(base.join("meow").join("resume"))
+}
+
+/// Reads the saved positions, keyed by absolute path. A missing or damaged
+/// state file just means nothing was saved.
+pub fn load(state: &Path) -> HashMap<PathBuf, Position> {
+    let text = fs::read_to_string(state).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| {
+            // The path goes last so it may contain tabs
+            let mut fields = line.splitn(5, '\t');
+            let position = Position {
+                offset: fields.next()?.parse().ok()?,
+                line: fields.next()?.parse().ok()?,
+                mtime: fields.next()?.parse().ok()?,
+                saved: fields.next()?.parse().ok()?,
+            };
+            Some((PathBuf::from(fields.next()?), position))
+        })
+        .collect()
+}
+
+/// Writes the positions through a temporary file and a rename, so concurrent
+/// runs can't leave a half-written file behind; the last one to finish wins.
+pub fn store(state: &Path, positions: &HashMap<PathBuf, Position>) -> io::Result<()> {
+    if let Some(dir) = state.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut text = String::new();
+    for (path, position) in positions {
+        text.push_str(&format!("{}\t{}\t{}\t{}\t{}\n", position.offset, position.line, position.mtime, position.saved, path.display()));
+    }
+
+    let temp = state.with_extension(format!("tmp{}", std::process::id()));
+    let result = fs::File::create(&temp)
+        .and_then(|mut file| file.write_all(text.as_bytes()).and_then(|()| file.sync_all()))
+        .and_then(|()| fs::rename(&temp, state));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// The saved position, unless the file has shrunk below it or was modified
+/// before it was saved (replaced by an older copy): then it starts over.
+pub fn still_valid(position: &Position, metadata: &fs::Metadata) -> bool {
+    metadata.len() >= position.offset && mtime_secs(metadata) >= position.mtime
+}
+
+pub fn mtime_secs(metadata: &fs::Metadata) -> u64 {
+    metadata.modified().ok().map_or(0, secs_since_epoch)
+}
+
+pub fn now_secs() -> u64 {
+    secs_since_epoch(SystemTime::now())
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+/// Counts what passes through up to the last complete line, so a line still
+/// being written isn't cut in half on the next resume.
+pub struct LineCounter<R> {
+    inner: R,
+    /// Bytes read up to and including the last newline
+    pub complete_bytes: u64,
+    pub lines: usize,
+    pending: u64,
+}
+
+impl<R: Read> LineCounter<R> {
+    pub fn new(inner: R) -> Self {
+        LineCounter { inner, complete_bytes: 0, lines: 0, pending: 0 }
+    }
+}
+
+impl<R: Read> Read for LineCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for &byte in &buf[..read] {
+            self.pending += 1;
+            if byte == b'\n' {
+                self.complete_bytes += self.pending;
+                self.pending = 0;
+                self.lines += 1;
+            }
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_survive_a_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("meow").join("resume");
+        let mut positions = HashMap::new();
+        positions.insert(PathBuf::from("/logs/a\tb.log"), Position { offset: 120, line: 7, mtime: 1_700_000_000, saved: 1_700_000_100 });
+        store(&state, &positions).unwrap();
+
+        let loaded = load(&state);
+        let position = loaded[Path::new("/logs/a\tb.log")];
+        assert_eq!((position.offset, position.line, position.mtime, position.saved), (120, 7, 1_700_000_000, 1_700_000_100));
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(state.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn damaged_state_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("resume");
+        fs::write(&state, "garbage\n1\t2\t3\t4\t/ok\nx\t2\t3\t4\t/bad\n").unwrap();
+        let loaded = load(&state);
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains_key(Path::new("/ok")));
+        assert!(load(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn positions_past_a_shrunk_or_older_file_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.log");
+        fs::write(&path, "0123456789").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let mtime = mtime_secs(&metadata);
+        let at = |offset, mtime| Position { offset, line: 1, mtime, saved: mtime };
+        assert!(still_valid(&at(10, mtime), &metadata));
+        assert!(!still_valid(&at(11, mtime), &metadata));
+        assert!(!still_valid(&at(5, mtime + 60), &metadata));
+    }
+
+    #[test]
+    fn line_counter_stops_at_the_last_newline() {
+        let mut counter = LineCounter::new("one\ntwo\nthr".as_bytes());
+        let mut text = String::new();
+        counter.read_to_string(&mut text).unwrap();
+        assert_eq!((counter.complete_bytes, counter.lines), (8, 2));
+    }
+}
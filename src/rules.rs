@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex_automata::meta::Regex;
+use regex_automata::{Anchored, Input, MatchKind};
+use toml::{Table, Value};
+
+use crate::color::{self, Depth};
+use crate::settings;
+
+/// What a matching rule does to its line besides coloring the matches.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    /// Leave the line out
+    Hide,
+    /// Put a marker in the gutter, in the rule's style
+    Mark,
+    /// Don't try the rules after this one
+    Stop,
+}
+
+/// A `--rules` entry: a regex and how to show its matches.
+#[derive(Clone)]
+pub struct Rule {
+    /// Finds where the next match starts
+    first: Regex,
+    /// The longest match from there, so `GET|GETX` takes all of `GETX`
+    longest: Regex,
+    /// Escape sequences for the matches, empty without colors
+    pub style: String,
+    pub action: Option<Action>,
+}
+
+/// The color names a rule understands, in ANSI order. `bright-` picks the bright variant.
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// Where `--rules=NAME` looks: the path as given if it exists, else `NAME.toml`
+/// in the `rules` directory next to the config file.
+pub fn resolve(name: &str) -> PathBuf {
+    let given = PathBuf::from(name);
+    if given.exists() || name.contains('/') {
+        return given;
+    }
+    let file = if name.ends_with(".toml") { name.to_string() } else { format!("{}.toml", name) };
+    match settings::config_path().as_deref().and_then(Path::parent) {
+        Some(dir) => dir.join("rules").join(file),
+        None => given,
+    }
+}
+
+/// Reads a rules file: an array of `[[rule]]` tables, tried in order. Errors
+/// name the rule they're in, counting from 1.
+pub fn load(path: &Path, use_colors: bool, depth: Depth) -> Result<Vec<Rule>, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let table = text.parse::<Table>().map_err(|err| err.to_string())?;
+    let entries = match table.get("rule") {
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return Err("'rule' must be an array of tables ([[rule]])".to_string()),
+        None => return Ok(Vec::new()),
+    };
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| match entry {
+            Value::Table(entry) => parse_rule(entry, use_colors, depth).map_err(|err| format!("rule {}: {}", i + 1, err)),
+            _ => Err(format!("rule {}: expected a table", i + 1)),
+        })
+        .collect()
+}
+
+fn parse_rule(entry: &Table, use_colors: bool, depth: Depth) -> Result<Rule, String> {
+    let pattern = match entry.get("pattern") {
+        Some(Value::String(pattern)) => pattern,
+        Some(_) => return Err("'pattern' must be a string".to_string()),
+        None => return Err("missing 'pattern'".to_string()),
+    };
+    // The regex crate explains syntax errors better
+    regex::Regex::new(pattern).map_err(|err| format!("invalid regex '{}': {}", pattern, err))?;
+    let first = Regex::new(pattern).map_err(|err| format!("invalid regex '{}': {}", pattern, err))?;
+    let longest = Regex::builder()
+        .configure(Regex::config().match_kind(MatchKind::All))
+        .build(pattern)
+        .map_err(|err| format!("invalid regex '{}': {}", pattern, err))?;
+
+    let mut style = String::new();
+    let mut action = None;
+    for (key, value) in entry {
+        match (key.as_str(), value) {
+            ("pattern", _) => {},
+            ("color" | "background", Value::String(name)) => {
+                let escape = color_escape(name, key == "background", depth).ok_or_else(|| format!("unknown color '{}'", name))?;
+                style.push_str(&escape);
+            },
+            ("bold", Value::Boolean(true)) => style.push_str("\x1B[1m"),
+            ("underline", Value::Boolean(true)) => style.push_str("\x1B[4m"),
+            ("bold" | "underline", Value::Boolean(false)) => {},
+            ("action", Value::String(name)) => {
+                action = Some(match name.as_str() {
+                    "hide" => Action::Hide,
+                    "mark" => Action::Mark,
+                    "stop" => Action::Stop,
+                    _ => return Err(format!("unknown action '{}' (expected hide, mark or stop)", name)),
+                });
+            },
+            ("color" | "background" | "action", _) => return Err(format!("'{}' must be a string", key)),
+            ("bold" | "underline", _) => return Err(format!("'{}' must be true or false", key)),
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+    }
+    if !use_colors {
+        style.clear();
+    }
+    Ok(Rule { first, longest, style, action })
+}
+
+/// A color by name (`red`, `bright-red`), as `#rrggbb`, or as a 256-color index.
+pub fn color_escape(name: &str, background: bool, depth: Depth) -> Option<String> {
+    if let Some(hex) = name.strip_prefix('#').filter(|hex| hex.len() == 6) {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        let rgb = [channel(0)?, channel(2)?, channel(4)?];
+        return Some(if background { color::bg(rgb, depth) } else { color::fg(rgb, depth) });
+    }
+    if let Ok(index) = name.parse::<u8>() {
+        return Some(format!("\x1B[{};5;{}m", if background { 48 } else { 38 }, index));
+    }
+    let (bright, base) = match name.strip_prefix("bright-") {
+        Some(base) => (true, base),
+        None => (false, name),
+    };
+    let index = COLOR_NAMES.iter().position(|&known| known == base)?;
+    let code = match (background, bright) {
+        (false, false) => 30,
+        (false, true) => 90,
+        (true, false) => 40,
+        (true, true) => 100,
+    } + index;
+    Some(format!("\x1B[{}m", code))
+}
+
+/// What the rules make of one line.
+#[derive(Default)]
+pub struct Outcome<'a> {
+    /// Byte ranges to color and the style for each, not overlapping
+    pub spans: Vec<(usize, usize, &'a str)>,
+    pub hidden: bool,
+    /// The style of the first rule that marks the line
+    pub mark: Option<&'a str>,
+}
+
+/// Tries the rules on `line` in order. Where matches of different rules overlap
+/// the earlier rule wins.
+pub fn apply<'a>(rules: &'a [Rule], line: &str) -> Outcome<'a> {
+    let mut outcome = Outcome::default();
+    for rule in rules {
+        let earlier = outcome.spans.len();
+        let mut matched = false;
+        let mut at = 0;
+        while at <= line.len() {
+            let Some(found) = rule.first.find(Input::new(line).range(at..)) else {
+                break;
+            };
+            matched = true;
+            let start = found.start();
+            let end = rule.longest
+                .find(Input::new(line).range(start..).anchored(Anchored::Yes))
+                .map_or(found.end(), |longest| longest.end());
+            if end > start {
+                if !outcome.spans[..earlier].iter().any(|&(other_start, other_end, _)| other_start < end && start < other_end) {
+                    outcome.spans.push((start, end, &rule.style));
+                }
+                at = end;
+            } else {
+                // An empty match: move on by a whole character
+                at = start + line[start..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        if !matched {
+            continue;
+        }
+        match rule.action {
+            Some(Action::Hide) => outcome.hidden = true,
+            Some(Action::Mark) => {
+                outcome.mark.get_or_insert(&rule.style);
+            },
+            Some(Action::Stop) => break,
+            None => {},
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(text: &str) -> Result<Vec<Rule>, String> {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), text).unwrap();
+        load(file.path(), true, Depth::Ansi16)
+    }
+
+    fn matched<'a>(rules: &'a [Rule], line: &'a str) -> Vec<(&'a str, &'a str)> {
+        apply(rules, line).spans.iter().map(|&(start, end, style)| (&line[start..end], style)).collect()
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(color_escape("red", false, Depth::Ansi16).unwrap(), "\x1B[31m");
+        assert_eq!(color_escape("bright-blue", false, Depth::Ansi16).unwrap(), "\x1B[94m");
+        assert_eq!(color_escape("white", true, Depth::Ansi16).unwrap(), "\x1B[47m");
+        assert_eq!(color_escape("bright-black", true, Depth::Ansi16).unwrap(), "\x1B[100m");
+        assert_eq!(color_escape("208", false, Depth::Ansi16).unwrap(), "\x1B[38;5;208m");
+        assert_eq!(color_escape("#ff0000", false, Depth::TrueColor).unwrap(), "\x1B[38;2;255;0;0m");
+        assert!(color_escape("mauve", false, Depth::Ansi16).is_none());
+        assert!(color_escape("#ff00", false, Depth::Ansi16).is_none());
+        assert!(color_escape("#gg0000", false, Depth::Ansi16).is_none());
+    }
+
+    #[test]
+    fn styles_combine() {
+        let loaded = rules("[[rule]]\npattern = 'x'\ncolor = 'red'\nbackground = 'blue'\nbold = true\nunderline = true\n").unwrap();
+        // In the order of the keys, which TOML tables sort
+        assert_eq!(loaded[0].style, "\x1B[44m\x1B[1m\x1B[31m\x1B[4m");
+        assert!(loaded[0].action.is_none());
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "[[rule]]\npattern = 'x'\ncolor = 'red'\n").unwrap();
+        assert_eq!(load(file.path(), false, Depth::Ansi16).unwrap()[0].style, "");
+    }
+
+    #[test]
+    fn longest_match_within_a_rule() {
+        let loaded = rules("[[rule]]\npattern = 'GET|GETX'\ncolor = 'red'\n").unwrap();
+        assert_eq!(matched(&loaded, "GETX GET"), [("GETX", "\x1B[31m"), ("GET", "\x1B[31m")]);
+    }
+
+    #[test]
+    fn earlier_rules_win_overlaps() {
+        let loaded = rules("[[rule]]\npattern = 'abc'\ncolor = 'red'\n\n[[rule]]\npattern = 'bcd|e'\ncolor = 'green'\n").unwrap();
+        assert_eq!(matched(&loaded, "abcde"), [("abc", "\x1B[31m"), ("e", "\x1B[32m")]);
+    }
+
+    #[test]
+    fn empty_matches_move_on() {
+        let loaded = rules("[[rule]]\npattern = 'x*'\ncolor = 'red'\naction = 'mark'\n").unwrap();
+        assert_eq!(matched(&loaded, "éxx"), [("xx", "\x1B[31m")]);
+        assert_eq!(apply(&loaded, "none").mark, Some("\x1B[31m"));
+    }
+
+    #[test]
+    fn actions() {
+        let loaded = rules("[[rule]]\npattern = 'ok'\naction = 'stop'\n\n[[rule]]\npattern = 'bad'\naction = 'hide'\n\n[[rule]]\npattern = 'warn'\ncolor = 'yellow'\naction = 'mark'\n").unwrap();
+        assert!(apply(&loaded, "bad").hidden);
+        assert!(!apply(&loaded, "ok bad").hidden);
+        assert!(apply(&loaded, "ok warn").mark.is_none());
+        assert_eq!(apply(&loaded, "warn").mark, Some("\x1B[33m"));
+    }
+
+    #[test]
+    fn load_errors() {
+        let error = |text: &str| rules(text).err().unwrap();
+        assert!(error("[[rule]]\npattern = '('\n").starts_with("rule 1: invalid regex '('"));
+        assert_eq!(error("[[rule]]\npattern = 'a'\n[[rule]]\ncolor = 'red'\n"), "rule 2: missing 'pattern'");
+        assert_eq!(error("[[rule]]\npattern = 'a'\nshade = 'red'\n"), "rule 1: unknown key 'shade'");
+        assert_eq!(error("[[rule]]\npattern = 'a'\naction = 'explode'\n"), "rule 1: unknown action 'explode' (expected hide, mark or stop)");
+        assert_eq!(error("[[rule]]\npattern = 'a'\nbold = 'yes'\n"), "rule 1: 'bold' must be true or false");
+        assert_eq!(error("[[rule]]\npattern = 1\n"), "rule 1: 'pattern' must be a string");
+        assert_eq!(error("rule = 'a'\n"), "'rule' must be an array of tables ([[rule]])");
+        assert!(rules("# nothing yet\n").unwrap().is_empty());
+    }
+}
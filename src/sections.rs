@@ -0,0 +1,452 @@
+use std::io::{self, Read, Write};
+
+use regex::Regex;
+
+use crate::columns::{display_width, fit};
+use crate::input::InputSource;
+use crate::terminal::{read_byte, read_byte_soon, RawTerminal};
+use crate::{more, print_file_header, send_output, terminal_height, terminal_width, Config};
+
+/// A header line and the lines up to the next one, as indexes into
+/// `Folds::lines`. Folded unless `open`.
+pub struct Section {
+    pub header: usize,
+    pub end: usize,
+    pub open: bool,
+}
+
+/// `--fold-sections`: the lines of an input cut into sections at each line
+/// matching the header regex. Lines before the first header belong to none
+/// and are always shown.
+pub struct Folds {
+    pub lines: Vec<String>,
+    pub sections: Vec<Section>,
+}
+
+impl Folds {
+    pub fn new(lines: Vec<String>, header: &Regex) -> Self {
+        let starts: Vec<usize> = lines.iter().enumerate().filter(|(_, line)| header.is_match(line)).map(|(i, _)| i).collect();
+        let sections = starts.iter().enumerate()
+            .map(|(k, &start)| Section { header: start, end: starts.get(k + 1).copied().unwrap_or(lines.len()), open: false })
+            .collect();
+        Folds { lines, sections }
+    }
+
+    /// How many lines come before the first header.
+    fn preamble(&self) -> usize {
+        self.sections.first().map_or(self.lines.len(), |section| section.header)
+    }
+
+    /// The section `line` is in, None before the first header.
+    pub fn section_of(&self, line: usize) -> Option<usize> {
+        self.sections.partition_point(|section| section.header <= line).checked_sub(1)
+    }
+
+    /// The section `line` is the header of.
+    fn header_of(&self, line: usize) -> Option<usize> {
+        self.section_of(line).filter(|&section| self.sections[section].header == line)
+    }
+
+    /// The lines a folded section hides: all but its header.
+    pub fn hidden(&self, section: usize) -> usize {
+        let section = &self.sections[section];
+        section.end - section.header - 1
+    }
+
+    /// The lines shown, in order: the preamble, then each header, followed by
+    /// the rest of its section if that's open.
+    pub fn visible(&self) -> Vec<usize> {
+        let mut lines: Vec<usize> = (0..self.preamble()).collect();
+        for section in &self.sections {
+            if section.open {
+                lines.extend(section.header..section.end);
+            } else {
+                lines.push(section.header);
+            }
+        }
+        lines
+    }
+
+    pub fn set_all(&mut self, open: bool) {
+        for section in &mut self.sections {
+            section.open = open;
+        }
+    }
+
+    /// The next line after `from` containing `pattern`, folded or not, going
+    /// round to the start after the last line.
+    pub fn find(&self, pattern: &str, from: usize) -> Option<usize> {
+        let count = self.lines.len();
+        (1..=count).map(|step| (from + step) % count).find(|&line| self.lines[line].contains(pattern))
+    }
+
+    /// Opens the section holding `line`, so it can be shown.
+    pub fn reveal(&mut self, line: usize) {
+        if let Some(section) = self.section_of(line) {
+            self.sections[section].open = true;
+        }
+    }
+}
+
+fn read_lines(source: &InputSource) -> io::Result<Vec<String>> {
+    let mut bytes = Vec::new();
+    source.open()?.reader.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).lines().map(|line| line.trim_end_matches('\r').to_string()).collect())
+}
+
+/// `--fold-sections`: shows each input with its sections folded to their
+/// header. On a terminal they can be opened and searched, one input after
+/// the other; otherwise only the headers are printed, each with how many
+/// lines it stands for.
+pub fn run(header: &Regex, config: &Config) -> bool {
+    let interactive = more::available() && !config.files.is_empty() && !config.files.iter().any(|file| file == "-");
+    let sources = config.sources();
+    let mut ok = true;
+    for (index, source) in sources.iter().enumerate() {
+        let name = source.name();
+        let mut folds = match read_lines(source) {
+            Ok(lines) => Folds::new(lines, header),
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", name, err), config.colors.reset);
+                ok = false;
+                continue;
+            }
+        };
+        if !interactive {
+            if sources.len() > 1 {
+                print_file_header(&name, source.path().unwrap_or(name.as_ref()), None, config);
+            }
+            print_folded(&folds, config);
+            continue;
+        }
+
+        let mut viewer = Viewer { cursor: 0, top: 0, status: String::new(), search: String::new(), config };
+        let title = if sources.len() > 1 { format!("{} ({}/{})", name, index + 1, sources.len()) } else { name };
+        match viewer.view(&mut folds, &title) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("fold-sections-failed", err), config.colors.reset);
+                return false;
+            }
+        }
+    }
+    ok
+}
+
+/// The dim `(+ N lines)` after a folded header.
+fn hidden_note(count: usize, config: &Config) -> String {
+    format!(" {}{}{}", config.colors.dim, msg!("fold-sections-hidden", count), config.colors.reset)
+}
+
+fn line_number(line: usize, config: &Config) -> String {
+    if config.show_line_numbers {
+        format!("{}{:>6}{} | ", config.colors.number, line + 1, config.colors.reset)
+    } else {
+        String::new()
+    }
+}
+
+/// Without a terminal: the preamble as it is, then every header with the
+/// count of lines under it. With `-n` the numbers are those of the input.
+fn print_folded(folds: &Folds, config: &Config) {
+    send_output(config, |out| {
+        for line in folds.visible() {
+            let note = match folds.header_of(line) {
+                Some(section) if folds.hidden(section) > 0 => hidden_note(folds.hidden(section), config),
+                _ => String::new(),
+            };
+            writeln!(out, "{}{}{}", line_number(line, config), folds.lines[line], note)?;
+        }
+        Ok(())
+    });
+}
+
+enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Toggle,
+    Space,
+    ExpandAll,
+    CollapseAll,
+    Search,
+    Next,
+    /// On to the next input
+    Leave,
+    /// Ctrl-C: done with all of them
+    Quit,
+    Other,
+}
+
+fn read_key() -> io::Result<Key> {
+    let Some(byte) = read_byte()? else {
+        return Ok(Key::Quit);
+    };
+    Ok(match byte {
+        b'\r' | b'\n' => Key::Toggle,
+        b' ' => Key::Space,
+        b'k' | 16 => Key::Up,
+        b'j' | 14 => Key::Down,
+        b'b' => Key::PageUp,
+        b'f' => Key::PageDown,
+        b'g' => Key::Top,
+        b'G' => Key::Bottom,
+        b'a' => Key::ExpandAll,
+        b'c' => Key::CollapseAll,
+        b'/' => Key::Search,
+        b'n' => Key::Next,
+        b'q' | b'Q' | 4 => Key::Leave,
+        3 => Key::Quit,
+        0x1B => match read_byte_soon()? {
+            None => Key::Leave,
+            Some(b'[' | b'O') => match read_byte_soon()? {
+                Some(b'A') => Key::Up,
+                Some(b'B') => Key::Down,
+                Some(b'H') => Key::Top,
+                Some(b'F') => Key::Bottom,
+                // PageUp and PageDown end in `~`
+                Some(b'5') => {
+                    read_byte_soon()?;
+                    Key::PageUp
+                },
+                Some(b'6') => {
+                    read_byte_soon()?;
+                    Key::PageDown
+                },
+                _ => Key::Other,
+            },
+            Some(_) => Key::Other,
+        },
+        _ => Key::Other,
+    })
+}
+
+struct Viewer<'a> {
+    /// Position of the selected line among the visible ones
+    cursor: usize,
+    /// First visible line on screen
+    top: usize,
+    /// What the last search or key has to say, on the bottom row
+    status: String,
+    /// The last pattern searched for, for `n`
+    search: String,
+    config: &'a Config,
+}
+
+impl Viewer<'_> {
+    /// Shows `folds` on the alternate screen until q or Esc (true, go on with
+    /// the next input) or Ctrl-C (false).
+    fn view(&mut self, folds: &mut Folds, title: &str) -> io::Result<bool> {
+        let _terminal = RawTerminal::enter()?;
+        let mut visible = folds.visible();
+        loop {
+            let rows = terminal_height().saturating_sub(1).max(1);
+            self.cursor = self.cursor.min(visible.len().saturating_sub(1));
+            self.draw(folds, &visible, title, rows)?;
+            let key = read_key()?;
+            self.status.clear();
+            let line = visible.get(self.cursor).copied().unwrap_or_default();
+            let header = folds.header_of(line);
+            // The line to stay on when sections open or close
+            let keep = match key {
+                Key::Up => {
+                    self.cursor = self.cursor.saturating_sub(1);
+                    None
+                },
+                // Enter and Space open or close the section of a header, and
+                // move on a line or a page elsewhere
+                Key::Toggle | Key::Space if header.is_some() => {
+                    let open = &mut folds.sections[header.unwrap_or_default()].open;
+                    *open = !*open;
+                    Some(line)
+                },
+                Key::Down | Key::Toggle => {
+                    self.cursor += 1;
+                    None
+                },
+                Key::PageUp => {
+                    self.cursor = self.cursor.saturating_sub(rows);
+                    None
+                },
+                Key::PageDown | Key::Space => {
+                    self.cursor += rows;
+                    None
+                },
+                Key::Top => {
+                    self.cursor = 0;
+                    None
+                },
+                Key::Bottom => {
+                    self.cursor = visible.len();
+                    None
+                },
+                Key::ExpandAll | Key::CollapseAll => {
+                    folds.set_all(matches!(key, Key::ExpandAll));
+                    Some(line)
+                },
+                Key::Search => match self.prompt(rows)? {
+                    Some(pattern) => {
+                        self.search = pattern;
+                        self.find(folds, line)
+                    },
+                    None => None,
+                },
+                Key::Next if !self.search.is_empty() => self.find(folds, line),
+                Key::Next | Key::Other => None,
+                Key::Leave => return Ok(true),
+                Key::Quit => return Ok(false),
+            };
+            visible = folds.visible();
+            if let Some(line) = keep {
+                // A line folded away leaves the selection on its header
+                self.cursor = visible.partition_point(|&shown| shown <= line).saturating_sub(1);
+            }
+        }
+    }
+
+    /// Moves to the next line after `from` with the last search in it,
+    /// opening its section if that's folded.
+    fn find(&mut self, folds: &mut Folds, from: usize) -> Option<usize> {
+        match folds.find(&self.search, from) {
+            Some(line) => {
+                folds.reveal(line);
+                Some(line)
+            },
+            None => {
+                self.status = msg!("fold-sections-not-found", self.search);
+                None
+            }
+        }
+    }
+
+    /// Reads a search pattern on the bottom row, None when left with Esc.
+    fn prompt(&mut self, rows: usize) -> io::Result<Option<String>> {
+        let mut pattern = String::new();
+        let mut bytes = Vec::new();
+        loop {
+            let mut out = io::stdout().lock();
+            write!(out, "\x1B[{};1H\x1B[2K/{}", rows + 1, pattern)?;
+            out.flush()?;
+            let Some(byte) = read_byte()? else {
+                return Ok(None);
+            };
+            match byte {
+                b'\r' | b'\n' if pattern.is_empty() => return Ok((!self.search.is_empty()).then(|| self.search.clone())),
+                b'\r' | b'\n' => return Ok(Some(pattern)),
+                0x1B | 3 => return Ok(None),
+                127 | 8 => {
+                    pattern.pop();
+                },
+                byte if byte < 0x20 => {},
+                byte => {
+                    // Held until a whole UTF-8 character is in
+                    bytes.push(byte);
+                    if let Ok(text) = std::str::from_utf8(&bytes) {
+                        pattern.push_str(text);
+                        bytes.clear();
+                    } else if bytes.len() >= 4 {
+                        bytes.clear();
+                    }
+                },
+            }
+        }
+    }
+
+    fn draw(&mut self, folds: &Folds, visible: &[usize], title: &str, rows: usize) -> io::Result<()> {
+        let config = self.config;
+        let width = terminal_width();
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor >= self.top + rows {
+            self.top = self.cursor + 1 - rows;
+        }
+
+        let mut out = io::stdout().lock();
+        write!(out, "\x1B[H\x1B[2J")?;
+        for (row, (i, &line)) in visible.iter().enumerate().skip(self.top).take(rows).enumerate() {
+            if row > 0 {
+                writeln!(out)?;
+            }
+            let number = line_number(line, config);
+            let text: String = folds.lines[line].chars().map(|c| if c.is_control() && c != '\t' { '?' } else { c }).collect();
+            let (marker, note) = match folds.header_of(line) {
+                Some(section) if folds.hidden(section) == 0 => ("  ", String::new()),
+                Some(section) if folds.sections[section].open => ("\u{25BE} ", String::new()),
+                Some(section) => ("\u{25B8} ", hidden_note(folds.hidden(section), config)),
+                None => ("  ", String::new()),
+            };
+            let room = width.saturating_sub(display_width(&number) + 2 + display_width(&note)).max(1);
+            let shown = fit(&text, room, false, config.tab_width).swap_remove(0);
+            if i == self.cursor {
+                let (reverse, reset) = if config.use_colors { ("\x1B[7m", "\x1B[0m") } else { ("", "") };
+                write!(out, "{}{}{}{}{}{}", number, marker, reverse, shown, reset, note)?;
+            } else {
+                write!(out, "{}{}{}{}", number, marker, shown, note)?;
+            }
+        }
+        let position = visible.get(self.cursor).map_or(0, |&line| line + 1);
+        let status = if self.status.is_empty() {
+            msg!("fold-sections-status", title, position, folds.lines.len())
+        } else {
+            self.status.clone()
+        };
+        write!(out, "\x1B[{};1H{}{}{}", rows + 1, config.colors.dim, status, config.colors.reset)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folds(text: &str) -> Folds {
+        Folds::new(text.lines().map(str::to_string).collect(), &Regex::new("^##").unwrap())
+    }
+
+    #[test]
+    fn sections_run_to_the_next_header() {
+        let folds = folds("intro\n## one\na\nb\n## two\n## three\nc");
+        let bounds: Vec<(usize, usize)> = folds.sections.iter().map(|section| (section.header, section.end)).collect();
+        assert_eq!(bounds, [(1, 4), (4, 5), (5, 7)]);
+        assert_eq!((0..3).map(|section| folds.hidden(section)).collect::<Vec<_>>(), [2, 0, 1]);
+        assert_eq!(folds.section_of(0), None);
+        assert_eq!(folds.section_of(3), Some(0));
+        assert_eq!(folds.header_of(3), None);
+        assert_eq!(folds.header_of(4), Some(1));
+    }
+
+    #[test]
+    fn only_headers_show_when_folded() {
+        let mut folds = folds("intro\n## one\na\nb\n## two\nc");
+        assert_eq!(folds.visible(), [0, 1, 4]);
+        folds.sections[0].open = true;
+        assert_eq!(folds.visible(), [0, 1, 2, 3, 4]);
+        folds.set_all(true);
+        assert_eq!(folds.visible(), [0, 1, 2, 3, 4, 5]);
+        folds.set_all(false);
+        assert_eq!(folds.visible(), [0, 1, 4]);
+    }
+
+    #[test]
+    fn no_headers_leave_everything_shown() {
+        let folds = folds("a\nb");
+        assert!(folds.sections.is_empty());
+        assert_eq!(folds.visible(), [0, 1]);
+    }
+
+    #[test]
+    fn search_goes_round_and_reveals() {
+        let mut folds = folds("## one\nneedle\n## two\nneedle");
+        assert_eq!(folds.find("needle", 1), Some(3));
+        assert_eq!(folds.find("needle", 3), Some(1));
+        assert_eq!(folds.find("nothing", 0), None);
+        folds.reveal(3);
+        assert!(!folds.sections[0].open);
+        assert!(folds.sections[1].open);
+        assert_eq!(folds.visible(), [0, 2, 3]);
+    }
+}
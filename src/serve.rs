@@ -0,0 +1,295 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{process_input, ColorConfig, Config, RunState};
+
+/// How long a client may take to send its request or read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves the files from the command line (or stdin) over HTTP, one request at a
+/// time. Only `/` and `/N` for the Nth file are routed, so nothing outside the
+/// given files can be reached. Runs until interrupted, or for one request with
+/// `--serve-once`. Returns false if the server could not start.
+pub fn serve(config: &Config) -> bool {
+    let address = SocketAddr::new(config.serve_bind, config.serve_port);
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("cannot-listen", address, err), config.colors.reset);
+            return false;
+        }
+    };
+
+    // Stdin can only be read once, so it is captured up front
+    let stdin = if config.files.is_empty() {
+        let mut data = Vec::new();
+        if let Err(err) = io::stdin().read_to_end(&mut data) {
+            eprintln!("{}meow: {}{}", config.colors.error, msg!("file-error", "stdin", err), config.colors.reset);
+            return false;
+        }
+        Some(data)
+    } else {
+        None
+    };
+
+    let mut address = listener.local_addr().unwrap_or(address);
+    // Listening on every interface, the address to give out is the one the network sees
+    if address.ip().is_unspecified() {
+        address.set_ip(lan_address().unwrap_or(address.ip()));
+    }
+    println!("{}", msg!("serving", format!("http://{}/", address)));
+
+    // Plain text for the browser: no escape codes, no pager, no animation
+    let mut plain = config.clone();
+    plain.use_colors = false;
+    plain.colors = ColorConfig::new(false, plain.color_depth);
+    plain.page_mode = false;
+    plain.animate = false;
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                // One request at a time, so a client that stalls mustn't hold up the rest
+                let result = stream.set_read_timeout(Some(CLIENT_TIMEOUT))
+                    .and_then(|_| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+                    .and_then(|_| handle_request(stream, &plain, stdin.as_deref()));
+                if let Err(err) = result {
+                    eprintln!("{}meow: {}{}", config.colors.error, msg!("write-error", err), config.colors.reset);
+                }
+            },
+            Err(err) => {
+                eprintln!("{}meow: {}{}", config.colors.error, msg!("write-error", err), config.colors.reset);
+            }
+        }
+        if config.serve_once {
+            break;
+        }
+    }
+
+    true
+}
+
+/// The address other machines on the network can reach us at. Connecting a UDP
+/// socket sends nothing, it only makes the OS pick the outgoing interface.
+fn lan_address() -> Option<IpAddr> {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| socket.connect("192.0.2.1:80").map(|_| socket))
+        .and_then(|socket| socket.local_addr())
+        .map(|addr| addr.ip())
+        .ok()
+}
+
+fn handle_request(mut stream: TcpStream, config: &Config, stdin: Option<&[u8]>) -> io::Result<()> {
+    // Headers are capped so a client can't make us buffer forever
+    let mut reader = BufReader::new((&stream).take(64 * 1024));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = if method != "GET" && method != "HEAD" {
+        ("405 Method Not Allowed", "text/plain; charset=utf-8", b"method not allowed\n".to_vec())
+    } else {
+        route(path, config, stdin)
+    };
+
+    if !config.quiet {
+        eprintln!("meow: {} {} {}", method, target, status);
+    }
+
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+           status, content_type, body.len())?;
+    if method != "HEAD" {
+        stream.write_all(&body)?;
+    }
+    stream.flush()
+}
+
+fn route(path: &str, config: &Config, stdin: Option<&[u8]>) -> (&'static str, &'static str, Vec<u8>) {
+    let not_found = ("404 Not Found", "text/plain; charset=utf-8", b"not found\n".to_vec());
+
+    if let Some(data) = stdin {
+        return match path {
+            "/" | "/1" => render(data, "stdin", config),
+            _ => not_found,
+        };
+    }
+
+    if path == "/" && config.files.len() > 1 {
+        return ("200 OK", "text/html; charset=utf-8", index_page(&config.files).into_bytes());
+    }
+
+    let index = match path {
+        "/" => 1,
+        _ => match path.trim_start_matches('/').parse::<usize>() {
+            Ok(index) if (1..=config.files.len()).contains(&index) => index,
+            _ => return not_found,
+        },
+    };
+
+    let file_path = &config.files[index - 1];
+    let result = if config.serve_raw {
+        fs::read(file_path)
+    } else {
+        File::open(Path::new(file_path)).and_then(|file| {
+            let mut out = Vec::new();
+            process_input(&mut BufReader::new(file), config, file_path, &mut 0, &mut RunState::default(), &mut out).map(|_| out)
+        })
+    };
+
+    match result {
+        Ok(data) if config.serve_raw => raw_response(data),
+        Ok(data) => ("200 OK", "text/plain; charset=utf-8", data),
+        Err(err) => {
+            eprintln!("meow: {}", msg!("file-error", file_path, err));
+            not_found
+        }
+    }
+}
+
+fn render(data: &[u8], name: &str, config: &Config) -> (&'static str, &'static str, Vec<u8>) {
+    if config.serve_raw {
+        return raw_response(data.to_vec());
+    }
+    let mut out = Vec::new();
+    match process_input(&mut BufReader::new(data), config, name, &mut 0, &mut RunState::default(), &mut out) {
+        Ok(_) => ("200 OK", "text/plain; charset=utf-8", out),
+        Err(_) => ("500 Internal Server Error", "text/plain; charset=utf-8", b"error\n".to_vec()),
+    }
+}
+
+fn raw_response(data: Vec<u8>) -> (&'static str, &'static str, Vec<u8>) {
+    let content_type = if std::str::from_utf8(&data).is_ok() {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    };
+    ("200 OK", content_type, data)
+}
+
+fn index_page(files: &[String]) -> String {
+    let mut page = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>meow</title></head><body><ul>\n");
+    for (i, file) in files.iter().enumerate() {
+        page.push_str(&format!("<li><a href=\"/{}\">{}</a></li>\n", i + 1, html_escape(file)));
+    }
+    page.push_str("</ul></body></html>\n");
+    page
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Sends `request` to `handle_request` over a real socket and returns the
+    /// status line, the headers and the body of the response.
+    fn exchange(config: &Config, stdin: Option<&[u8]>, request: &str) -> (String, Vec<String>, Vec<u8>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        handle_request(server, config, stdin).unwrap();
+        
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        let end = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8(response[..end].to_vec()).unwrap();
+        let mut lines = head.split("\r\n").map(str::to_string);
+        let status = lines.next().unwrap();
+        (status, lines.collect(), response[end + 4..].to_vec())
+    }
+
+    fn config_for(files: &[&str]) -> Config {
+        let mut config = Config::new();
+        config.colors = ColorConfig::new(false, config.color_depth);
+        config.quiet = true;
+        config.files = files.iter().map(|file| file.to_string()).collect();
+        config
+    }
+
+    #[test]
+    fn serves_the_processed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "one\ntwo\n").unwrap();
+        let mut config = config_for(&[file.to_str().unwrap()]);
+        config.show_line_numbers = true;
+        let (status, headers, body) = exchange(&config, None, "GET / HTTP/1.1\r\nHost: x\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(String::from_utf8(body.clone()).unwrap(), "     1 | one\n     2 | two\n");
+        assert!(headers.contains(&"Content-Type: text/plain; charset=utf-8".to_string()), "{:?}", headers);
+        assert!(headers.contains(&format!("Content-Length: {}", body.len())), "{:?}", headers);
+    }
+
+    #[test]
+    fn head_sends_the_length_without_the_body() {
+        let (status, headers, body) = exchange(&config_for(&[]), Some(b"hello\n"), "HEAD / HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(headers.contains(&"Content-Length: 6".to_string()), "{:?}", headers);
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn raw_binary_is_an_octet_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.bin");
+        fs::write(&file, b"\xFF\x00\xFE").unwrap();
+        let mut config = config_for(&[file.to_str().unwrap()]);
+        config.serve_raw = true;
+        let (_, headers, body) = exchange(&config, None, "GET /1 HTTP/1.1\r\n\r\n");
+        assert!(headers.contains(&"Content-Type: application/octet-stream".to_string()), "{:?}", headers);
+        assert_eq!(body, b"\xFF\x00\xFE");
+    }
+
+    #[test]
+    fn only_the_given_files_are_reachable() {
+        let config = config_for(&["a.txt", "b <&>.txt"]);
+        let (status, headers, body) = exchange(&config, None, "GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(headers.contains(&"Content-Type: text/html; charset=utf-8".to_string()), "{:?}", headers);
+        let page = String::from_utf8(body).unwrap();
+        assert!(page.contains("<a href=\"/2\">b &lt;&amp;&gt;.txt</a>"), "{}", page);
+        for target in ["/3", "/0", "/../etc/passwd", "/etc/passwd", "/a.txt"] {
+            let (status, _, _) = exchange(&config, None, &format!("GET {} HTTP/1.1\r\n\r\n", target));
+            assert_eq!(status, "HTTP/1.1 404 Not Found", "{}", target);
+        }
+    }
+
+    #[test]
+    fn only_get_and_head_are_allowed() {
+        let (status, _, _) = exchange(&config_for(&[]), Some(b"x"), "POST / HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+    }
+
+    #[test]
+    fn a_silent_client_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        server.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+        let config = config_for(&[]);
+        let handler = thread::spawn(move || handle_request(server, &config, Some(b"x")));
+        let err = handler.join().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut), "{:?}", err);
+        drop(client);
+    }
+}
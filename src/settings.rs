@@ -0,0 +1,290 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+/// Location of the config file: `$MEOW_CONFIG`, else `meow/config.toml` under
+/// `$XDG_CONFIG_HOME` (or `~/.config`).
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("MEOW_CONFIG").filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("meow").join("config.toml"))
+}
+
+/// Reads and parses the config file. A missing file is an empty config.
+pub fn load(path: &Path) -> Result<Table, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => text.parse::<Table>().map_err(|err| err.to_string()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Table::new()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// A `[preprocessors]` rule: files matching `pattern` (an extension such as `pdf`,
+/// or a MIME type such as `application/pdf`) are shown as the output of `command`.
+#[derive(Clone)]
+pub struct Preprocessor {
+    pub pattern: String,
+    /// `{}` is replaced by the file path
+    pub command: String,
+    /// Run through `sh -c` instead of splitting on whitespace
+    pub shell: bool,
+}
+
+/// Collects the `[preprocessors]` rules. Each value is either a command string or
+/// a table with `command` and an optional `shell` flag.
+pub fn preprocessors(table: &Table) -> Result<Vec<Preprocessor>, String> {
+    let section = match table.get("preprocessors") {
+        Some(Value::Table(section)) => section,
+        Some(_) => return Err("'preprocessors' must be a table".to_string()),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut rules = Vec::new();
+    for (pattern, value) in section {
+        let (command, shell) = match value {
+            Value::String(command) => (command.clone(), false),
+            Value::Table(rule) => {
+                let command = match rule.get("command") {
+                    Some(Value::String(command)) => command.clone(),
+                    _ => return Err(format!("preprocessors.{}: missing 'command'", pattern)),
+                };
+                let shell = match rule.get("shell") {
+                    Some(Value::Boolean(shell)) => *shell,
+                    None => false,
+                    Some(_) => return Err(format!("preprocessors.{}: 'shell' must be true or false", pattern)),
+                };
+                (command, shell)
+            },
+            _ => return Err(format!("preprocessors.{}: expected a command or a table", pattern)),
+        };
+        rules.push(Preprocessor { pattern: pattern.to_lowercase(), command, shell });
+    }
+
+    Ok(rules)
+}
+
+/// Guesses a MIME type from the first bytes of a file, for MIME-keyed rules.
+pub fn sniff_mime(head: &[u8]) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+        (b"\x1F\x8B", "application/gzip"),
+        (b"\x89PNG\r\n\x1A\n", "image/png"),
+        (b"\xFF\xD8\xFF", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+        (b"\x7FELF", "application/x-executable"),
+    ];
+
+    MAGIC.iter().find(|(magic, _)| head.starts_with(magic)).map(|(_, mime)| *mime)
+}
+
+/// Picks the rule for a file: an extension match wins over a MIME match.
+pub fn find_preprocessor<'a>(rules: &'a [Preprocessor], path: &Path, head: &[u8]) -> Option<&'a Preprocessor> {
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    if let Some(rule) = extension.and_then(|ext| rules.iter().find(|rule| rule.pattern == ext)) {
+        return Some(rule);
+    }
+
+    let mime = sniff_mime(head)?;
+    rules.iter().find(|rule| rule.pattern == mime)
+}
+
+/// A `[profiles]` entry: options applied to files matching `pattern`, a glob
+/// such as `*.rs` or `Makefile` (see `matches_glob`).
+#[derive(Clone)]
+pub struct Profile {
+    pub pattern: String,
+    pub options: Vec<String>,
+}
+
+/// Collects the `[profiles]` entries. Each value is a string of options split on
+/// whitespace, like `MEOW_OPTS`, or an array of them.
+pub fn profiles(table: &Table) -> Result<Vec<Profile>, String> {
+    let section = match table.get("profiles") {
+        Some(Value::Table(section)) => section,
+        Some(_) => return Err("'profiles' must be a table".to_string()),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut profiles = Vec::new();
+    for (pattern, value) in section {
+        let options = arguments(value, "profiles", pattern)?;
+        if let Some(option) = options.iter().find(|option| !option.starts_with('-')) {
+            return Err(format!("profiles.{}: '{}' is not an option", pattern, option));
+        }
+        profiles.push(Profile { pattern: pattern.clone(), options });
+    }
+
+    Ok(profiles)
+}
+
+/// The arguments of a profile or view: a string split on whitespace, like
+/// `MEOW_OPTS`, or an array of strings.
+fn arguments(value: &Value, section: &str, key: &str) -> Result<Vec<String>, String> {
+    match value {
+        Value::String(arguments) => Ok(arguments.split_whitespace().map(str::to_string).collect()),
+        Value::Array(arguments) => arguments
+            .iter()
+            .map(|argument| argument.as_str().map(str::to_string))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("{}.{}: arguments must be strings", section, key)),
+        _ => Err(format!("{}.{}: expected a string or an array of arguments", section, key)),
+    }
+}
+
+/// Picks the profile for a file. When several patterns match, the most specific
+/// one wins: the one with the most characters that aren't wildcards.
+pub fn find_profile<'a>(profiles: &'a [Profile], path: &Path) -> Option<&'a Profile> {
+    profiles
+        .iter()
+        .filter(|profile| matches_glob(&profile.pattern, path))
+        .max_by_key(|profile| profile.pattern.chars().filter(|&c| c != '*' && c != '?').count())
+}
+
+/// Whether `path` matches a glob with `*` and `?`. Patterns with a `/` are
+/// matched against the whole path, others against the file name.
+pub fn matches_glob(pattern: &str, path: &Path) -> bool {
+    let subject = if pattern.contains('/') {
+        path.to_string_lossy()
+    } else {
+        path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default()
+    };
+    glob_match(&pattern.chars().collect::<Vec<_>>(), &subject.chars().collect::<Vec<_>>())
+}
+
+/// `*` matches any run of characters, `?` any single one.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob_match(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some((&c, rest)) => text.first() == Some(&c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// A `[views]` entry: a named set of arguments, used as `--view=NAME` or `@NAME`.
+#[derive(Clone)]
+pub struct View {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+/// Collects the `[views]` entries, written like `[profiles]` ones.
+pub fn views(table: &Table) -> Result<Vec<View>, String> {
+    let section = match table.get("views") {
+        Some(Value::Table(section)) => section,
+        Some(_) => return Err("'views' must be a table".to_string()),
+        None => return Ok(Vec::new()),
+    };
+
+    section
+        .iter()
+        .map(|(name, value)| Ok(View { name: name.clone(), arguments: arguments(value, "views", name)? }))
+        .collect()
+}
+
+pub enum ViewError {
+    Unknown(String),
+    /// A view that refers back to itself, directly or through others
+    Recursive(String),
+}
+
+/// Replaces each `--view=NAME` and `@NAME` in `args` with the arguments of that
+/// view, which may use other views in turn.
+pub fn expand_views(args: &[String], views: &[View]) -> Result<Vec<String>, ViewError> {
+    let mut expanded = Vec::with_capacity(args.len());
+    expand_into(args, views, &mut Vec::new(), &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand_into(args: &[String], views: &[View], active: &mut Vec<String>, expanded: &mut Vec<String>) -> Result<(), ViewError> {
+    for arg in args {
+        let Some(name) = arg.strip_prefix("--view=").or_else(|| arg.strip_prefix('@')) else {
+            expanded.push(arg.clone());
+            continue;
+        };
+        if active.iter().any(|open| open == name) {
+            return Err(ViewError::Recursive(name.to_string()));
+        }
+        let view = views.iter().find(|view| view.name == name).ok_or_else(|| ViewError::Unknown(name.to_string()))?;
+        active.push(view.name.clone());
+        expand_into(&view.arguments, views, active, expanded)?;
+        active.pop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(matches_glob("*.rs", Path::new("src/main.rs")));
+        assert!(matches_glob("Makefile", Path::new("/tmp/Makefile")));
+        assert!(!matches_glob("Makefile", Path::new("Makefile.am")));
+        assert!(matches_glob("data?.csv", Path::new("data1.csv")));
+        assert!(!matches_glob("data?.csv", Path::new("data10.csv")));
+        // With a slash, the whole path
+        assert!(matches_glob("src/*.rs", Path::new("src/main.rs")));
+        assert!(!matches_glob("src/*.rs", Path::new("main.rs")));
+    }
+
+    #[test]
+    fn profiles_from_strings_and_arrays() {
+        let table: Table = "[profiles]\n\"*.rs\" = \"-n --tab-width=4\"\n\"*.csv\" = [\"--fields=1\", \"--csv\"]\n".parse().unwrap();
+        let found = profiles(&table).unwrap();
+        let options: Vec<(&str, &[String])> = found.iter().map(|profile| (profile.pattern.as_str(), profile.options.as_slice())).collect();
+        assert_eq!(options, [("*.csv", &["--fields=1".to_string(), "--csv".to_string()][..]), ("*.rs", &["-n".to_string(), "--tab-width=4".to_string()][..])]);
+
+        let bad: Table = "[profiles]\n\"*.rs\" = \"numbers\"\n".parse().unwrap();
+        assert_eq!(profiles(&bad).err().unwrap(), "profiles.*.rs: 'numbers' is not an option");
+        let bad: Table = "profiles = 1\n".parse().unwrap();
+        assert!(profiles(&bad).is_err());
+        assert!(profiles(&Table::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn most_specific_profile_wins() {
+        let profile = |pattern: &str| Profile { pattern: pattern.to_string(), options: Vec::new() };
+        let profiles = [profile("*"), profile("*.log"), profile("access*.log")];
+        let found = |path: &str| find_profile(&profiles, Path::new(path)).map(|profile| profile.pattern.as_str());
+        assert_eq!(found("access.2024.log"), Some("access*.log"));
+        assert_eq!(found("error.log"), Some("*.log"));
+        assert_eq!(found("notes.txt"), Some("*"));
+        assert_eq!(find_profile(&profiles[1..], Path::new("notes.txt")).map(|profile| profile.pattern.as_str()), None);
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn views_expand_in_place() {
+        let table: Table = "[views]\nerrors = \"-n --grep=ERROR\"\nloud = [\"@errors\", \"--highlight=timeout\"]\n".parse().unwrap();
+        let found = views(&table).unwrap();
+        let expanded = expand_views(&args(&["meow", "-C", "@loud", "app.log", "--view=errors"]), &found).ok().unwrap();
+        assert_eq!(expanded, args(&["meow", "-C", "-n", "--grep=ERROR", "--highlight=timeout", "app.log", "-n", "--grep=ERROR"]));
+    }
+
+    #[test]
+    fn bad_views() {
+        let view = |name: &str, arguments: &[&str]| View { name: name.to_string(), arguments: args(arguments) };
+        let found = [view("a", &["@b"]), view("b", &["-n", "--view=a"]), view("c", &["@c"])];
+        assert!(matches!(expand_views(&args(&["@a"]), &found), Err(ViewError::Recursive(name)) if name == "a"));
+        assert!(matches!(expand_views(&args(&["@c"]), &found), Err(ViewError::Recursive(name)) if name == "c"));
+        assert!(matches!(expand_views(&args(&["@nope"]), &found), Err(ViewError::Unknown(name)) if name == "nope"));
+        // The same view twice side by side is fine
+        assert_eq!(expand_views(&args(&["@d", "@d"]), &[view("d", &["-n"])]).ok().unwrap(), args(&["-n", "-n"]));
+    }
+}
@@ -0,0 +1,165 @@
+/// The colors and attributes an SGR sequence sets, as the terminal keeps them
+/// between sequences.
+#[derive(Clone, Default, PartialEq)]
+struct Style {
+    /// Bold, dim, italic, underline, blink, reverse, hidden and strikethrough,
+    /// by their SGR number less one
+    attributes: [bool; 9],
+    /// Colors as their parameters: `31`, `38;5;208`, `48:2::0:0:0`
+    foreground: Option<String>,
+    background: Option<String>,
+    underline_color: Option<String>,
+}
+
+impl Style {
+    /// Applies the parameters of one `ESC [ ... m` sequence.
+    fn apply(&mut self, parameters: &str) {
+        let mut parameters = parameters.split(';');
+        while let Some(parameter) = parameters.next() {
+            let (code, sub) = parameter.split_once(':').unwrap_or((parameter, ""));
+            // An empty parameter is a 0
+            let n = if code.is_empty() { 0 } else {
+                match code.parse::<usize>() {
+                    Ok(n) => n,
+                    Err(_) => continue,
+                }
+            };
+            match n {
+                0 => *self = Style::default(),
+                // `4:0` is the other way to turn underline off
+                1..=9 => self.attributes[n - 1] = !(n == 4 && sub == "0"),
+                22 => self.attributes[..2].fill(false),
+                23 | 24 | 27..=29 => self.attributes[n - 21] = false,
+                25 => self.attributes[4..6].fill(false),
+                38 | 48 | 58 => {
+                    // `38;5;N` and `38;2;R;G;B` take the parameters after them;
+                    // `38:5:N` has them in one
+                    let color = if sub.is_empty() {
+                        let count = match parameters.next() {
+                            Some("5") => 1,
+                            Some("2") => 3,
+                            _ => continue,
+                        };
+                        let rest: Vec<&str> = parameters.by_ref().take(count).collect();
+                        format!("{};{};{}", code, if count == 1 { "5" } else { "2" }, rest.join(";"))
+                    } else {
+                        parameter.to_string()
+                    };
+                    *match n {
+                        38 => &mut self.foreground,
+                        48 => &mut self.background,
+                        _ => &mut self.underline_color,
+                    } = Some(color);
+                },
+                39 => self.foreground = None,
+                49 => self.background = None,
+                59 => self.underline_color = None,
+                30..=37 | 90..=97 => self.foreground = Some(n.to_string()),
+                40..=47 | 100..=107 => self.background = Some(n.to_string()),
+                _ => {},
+            }
+        }
+    }
+
+    /// One sequence that sets this style from none, empty for no style.
+    fn escape(&self) -> String {
+        let mut parameters: Vec<String> = (1..=9).filter(|&n| self.attributes[n - 1]).map(|n| n.to_string()).collect();
+        parameters.extend([&self.foreground, &self.background, &self.underline_color].into_iter().flatten().cloned());
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!("\x1B[{}m", parameters.join(";"))
+        }
+    }
+}
+
+/// `--preserve-ansi`: follows the colors the input sets, line by line, so
+/// a line can get back the style its text started in after meow's gutter
+/// has reset it.
+#[derive(Default)]
+pub struct Tracker {
+    /// In effect where the last line added starts
+    start: Style,
+    /// and where it ends
+    end: Style,
+}
+
+impl Tracker {
+    /// Follows the SGR sequences in the next line of the input. Other
+    /// sequences don't change colors and are skipped.
+    pub fn add(&mut self, line: &str) {
+        self.start = self.end.clone();
+        let mut rest = line;
+        while let Some(at) = rest.find("\x1B[") {
+            rest = &rest[at + 2..];
+            let end = rest.find(|c: char| ('@'..='~').contains(&c)).unwrap_or(rest.len());
+            if rest[end..].starts_with('m') {
+                self.end.apply(&rest[..end]);
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    /// The sequence that restores the style the last line started in.
+    pub fn restore(&self) -> String {
+        self.start.escape()
+    }
+
+    /// Whether the last line leaves a style on, to be reset before anything
+    /// of meow's is written after it.
+    pub fn open(&self) -> bool {
+        self.end != Style::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracked(lines: &[&str]) -> Tracker {
+        let mut tracker = Tracker::default();
+        for line in lines {
+            tracker.add(line);
+        }
+        tracker
+    }
+
+    #[test]
+    fn style_carries_to_the_next_line() {
+        let tracker = tracked(&["\x1B[1;31mred", "still red"]);
+        assert_eq!(tracker.restore(), "\x1B[1;31m");
+        assert!(tracker.open());
+        let tracker = tracked(&["\x1B[31mred", "still red\x1B[0m"]);
+        assert_eq!(tracker.restore(), "\x1B[31m");
+        assert!(!tracker.open());
+        let tracker = tracked(&["\x1B[31mred\x1B[m", "plain"]);
+        assert_eq!(tracker.restore(), "");
+        assert!(!tracker.open());
+    }
+
+    #[test]
+    fn extended_colors() {
+        assert_eq!(tracked(&["\x1B[38;5;208;48;2;1;2;3mx", ""]).restore(), "\x1B[38;5;208;48;2;1;2;3m");
+        assert_eq!(tracked(&["\x1B[38:2::10:20:30;58:5:4mx", ""]).restore(), "\x1B[38:2::10:20:30;58:5:4m");
+        // A later color replaces the earlier one, 39 and 49 drop it
+        assert_eq!(tracked(&["\x1B[31;44m\x1B[92mx", ""]).restore(), "\x1B[92;44m");
+        assert_eq!(tracked(&["\x1B[31;44m\x1B[39mx", ""]).restore(), "\x1B[44m");
+        assert_eq!(tracked(&["\x1B[31;44m\x1B[49mx", ""]).restore(), "\x1B[31m");
+    }
+
+    #[test]
+    fn attributes_turn_off_one_by_one() {
+        assert_eq!(tracked(&["\x1B[1;2;3;4m\x1B[22m", ""]).restore(), "\x1B[3;4m");
+        assert_eq!(tracked(&["\x1B[3;4;7m\x1B[23;27m", ""]).restore(), "\x1B[4m");
+        assert_eq!(tracked(&["\x1B[4m\x1B[4:0m", ""]).restore(), "");
+        assert_eq!(tracked(&["\x1B[5;9m\x1B[25;29m", ""]).restore(), "");
+    }
+
+    #[test]
+    fn other_sequences_are_skipped() {
+        let tracker = tracked(&["\x1B[2J\x1B[31m\x1B[1;1H\x1B[Kx", ""]);
+        assert_eq!(tracker.restore(), "\x1B[31m");
+        // A sequence cut off at the end of the line sets nothing
+        assert_eq!(tracked(&["\x1B[31", ""]).restore(), "");
+    }
+}
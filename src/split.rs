@@ -0,0 +1,271 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// A printf-style file name for `--output` with `--split-output`, such as
+/// `part_%03d.txt`: one integer placeholder, `%%` for a literal percent sign.
+#[derive(Clone)]
+pub struct Template {
+    before: String,
+    width: usize,
+    zero_pad: bool,
+    after: String,
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Template, String> {
+        let mut parts = [String::new(), String::new()];
+        let mut placeholder: Option<(bool, usize)> = None;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                parts[usize::from(placeholder.is_some())].push(c);
+                continue;
+            }
+            if chars.next_if_eq(&'%').is_some() {
+                parts[usize::from(placeholder.is_some())].push('%');
+                continue;
+            }
+            let zero_pad = chars.next_if_eq(&'0').is_some();
+            let mut width = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                width.push(digit);
+            }
+            if chars.next() != Some('d') {
+                return Err(msg!("split-template-placeholder", text));
+            }
+            if placeholder.is_some() {
+                return Err(msg!("split-template-count", text));
+            }
+            placeholder = Some((zero_pad, width.parse().unwrap_or(0)));
+        }
+        let Some((zero_pad, width)) = placeholder else {
+            return Err(msg!("split-template-count", text));
+        };
+        let [before, after] = parts;
+        Ok(Template { before, width, zero_pad, after })
+    }
+
+    pub fn format(&self, number: usize) -> String {
+        if self.zero_pad {
+            format!("{}{:0width$}{}", self.before, number, self.after, width = self.width)
+        } else {
+            format!("{}{:width$}{}", self.before, number, self.after, width = self.width)
+        }
+    }
+}
+
+/// `--split-output-bytes`: a count with an optional K, M or G suffix (powers of 1024).
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.strip_suffix(['B', 'b']).unwrap_or(text);
+    let (number, multiplier) = match text.char_indices().last()? {
+        (at, 'k' | 'K') => (&text[..at], 1 << 10),
+        (at, 'm' | 'M') => (&text[..at], 1 << 20),
+        (at, 'g' | 'G') => (&text[..at], 1 << 30),
+        _ => (text, 1),
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier).filter(|&size| size > 0)
+}
+
+/// When to start the next file.
+#[derive(Clone, Copy)]
+pub enum Limit {
+    /// `--output` alone: everything goes to one file
+    None,
+    Lines(usize),
+    Bytes(u64),
+}
+
+/// `--output`: receives the output instead of stdout and spreads it over files
+/// named by the template, a new one once the current one is full. Files are
+/// only split between lines and only created once there's a line for them.
+pub struct OutputFiles {
+    /// The file name as given when not splitting
+    path: Option<String>,
+    template: Option<Template>,
+    limit: Limit,
+    /// The start of a line whose end hasn't been written yet
+    partial: Vec<u8>,
+    current: Option<BufWriter<File>>,
+    lines: usize,
+    bytes: u64,
+    /// Names of the files created so far
+    pub written: Vec<String>,
+    /// The first write error, with the file it happened in; nothing more is
+    /// written after one
+    pub error: Option<(String, io::Error)>,
+}
+
+impl OutputFiles {
+    pub fn single(path: &str) -> Self {
+        Self::new(Some(path.to_string()), None, Limit::None)
+    }
+
+    pub fn split(template: Template, limit: Limit) -> Self {
+        Self::new(None, Some(template), limit)
+    }
+
+    fn new(path: Option<String>, template: Option<Template>, limit: Limit) -> Self {
+        OutputFiles { path, template, limit, partial: Vec::new(), current: None, lines: 0, bytes: 0, written: Vec::new(), error: None }
+    }
+
+    fn is_full(&self, next_line: usize) -> bool {
+        match self.limit {
+            Limit::None => false,
+            Limit::Lines(lines) => self.lines >= lines,
+            // A line longer than the limit still gets a file to itself
+            Limit::Bytes(bytes) => self.bytes > 0 && self.bytes + next_line as u64 > bytes,
+        }
+    }
+
+    /// Writes one whole line (or the unterminated end of the output), moving on
+    /// to the next file first if this one is full.
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.current.is_some() && self.is_full(line.len()) {
+            self.close()?;
+        }
+        if self.current.is_none() {
+            let name = match (&self.path, &self.template) {
+                (Some(path), _) => path.clone(),
+                (None, Some(template)) => template.format(self.written.len() + 1),
+                (None, None) => unreachable!("output files have a path or a template"),
+            };
+            self.current = Some(BufWriter::new(File::create(&name)?));
+            self.written.push(name);
+            self.lines = 0;
+            self.bytes = 0;
+        }
+        if let Some(file) = self.current.as_mut() {
+            file.write_all(line)?;
+        }
+        self.lines += 1;
+        self.bytes += line.len() as u64;
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        match self.current.take() {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn record(&mut self, result: io::Result<()>) {
+        if let Err(err) = result {
+            let name = self.written.last().cloned().or_else(|| self.path.clone()).or_else(|| self.template.as_ref().map(|template| template.format(1)));
+            self.error = Some((name.unwrap_or_default(), err));
+        }
+    }
+
+    /// Writes what's left of the last line and closes the file.
+    pub fn finish(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        if !self.partial.is_empty() {
+            let partial = std::mem::take(&mut self.partial);
+            let result = self.write_line(&partial);
+            self.record(result);
+        }
+        let result = self.close();
+        self.record(result);
+    }
+}
+
+impl Write for OutputFiles {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while self.error.is_none() {
+            let Some(at) = rest.iter().position(|&b| b == b'\n') else {
+                self.partial.extend_from_slice(rest);
+                break;
+            };
+            let result = if self.partial.is_empty() {
+                self.write_line(&rest[..=at])
+            } else {
+                let mut line = std::mem::take(&mut self.partial);
+                line.extend_from_slice(&rest[..=at]);
+                self.write_line(&line)
+            };
+            self.record(result);
+            rest = &rest[at + 1..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn templates() {
+        assert_eq!(Template::parse("part_%03d.txt").unwrap().format(7), "part_007.txt");
+        assert_eq!(Template::parse("part_%d").unwrap().format(12), "part_12");
+        assert_eq!(Template::parse("%4d.log").unwrap().format(3), "   3.log");
+        assert_eq!(Template::parse("100%%_%02d").unwrap().format(1), "100%_01");
+        assert_eq!(Template::parse("%03d").unwrap().format(1234), "1234");
+        assert!(Template::parse("part.txt").is_err());
+        assert!(Template::parse("%d_%d").is_err());
+        assert!(Template::parse("part_%s").is_err());
+        assert!(Template::parse("part_%").is_err());
+        assert!(Template::parse("%%d").is_err());
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("100"), Some(100));
+        assert_eq!(parse_size("512K"), Some(512 << 10));
+        assert_eq!(parse_size("1M"), Some(1 << 20));
+        assert_eq!(parse_size("2gb"), Some(2 << 30));
+        assert_eq!(parse_size("0"), None);
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("1.5M"), None);
+        assert_eq!(parse_size(&format!("{}G", u64::MAX)), None);
+    }
+
+    /// Writes `chunks` through split files named in a scratch directory and
+    /// returns the contents of each file written.
+    fn split(limit: Limit, chunks: &[&str]) -> Vec<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let template = Template::parse(&format!("{}/part_%02d", dir.path().display())).unwrap();
+        let mut output = OutputFiles::split(template, limit);
+        for chunk in chunks {
+            output.write_all(chunk.as_bytes()).unwrap();
+        }
+        output.finish();
+        assert!(output.error.is_none());
+        output.written.iter().map(|name| fs::read_to_string(name).unwrap()).collect()
+    }
+
+    #[test]
+    fn split_by_lines() {
+        assert_eq!(split(Limit::Lines(2), &["a\nb\nc\n", "d\ne"]), ["a\nb\n", "c\nd\n", "e"]);
+        // Lines given in pieces are still whole lines
+        assert_eq!(split(Limit::Lines(1), &["on", "e\ntw", "o\n"]), ["one\n", "two\n"]);
+        assert!(split(Limit::Lines(3), &[]).is_empty());
+    }
+
+    #[test]
+    fn split_by_bytes_between_lines() {
+        assert_eq!(split(Limit::Bytes(6), &["abc\nde\nfghij\nk\n"]), ["abc\n", "de\n", "fghij\n", "k\n"]);
+        assert_eq!(split(Limit::Bytes(7), &["ab\ncd\nef\n"]), ["ab\ncd\n", "ef\n"]);
+        // A line longer than the limit gets a file to itself
+        assert_eq!(split(Limit::Bytes(2), &["long line\nx\n"]), ["long line\n", "x\n"]);
+    }
+
+    #[test]
+    fn write_errors_are_kept() {
+        let template = Template::parse("/nonexistent-directory/part_%d").unwrap();
+        let mut output = OutputFiles::split(template, Limit::Lines(1));
+        output.write_all(b"a\nb\n").unwrap();
+        output.finish();
+        let (name, _) = output.error.unwrap();
+        assert_eq!(name, "/nonexistent-directory/part_1");
+        assert!(output.written.is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+use std::path::Path;
+
+use crate::{xml, ColorConfig};
+
+/// The format `--yaml`/`--toml` would pick for a file from its extension.
+pub fn format_for(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+        "yaml" | "yml" => Some("yaml"),
+        "toml" => Some("toml"),
+        _ => None,
+    }
+}
+
+/// Where a document failed to parse: 1-based line and column, and why.
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// XML is never rejected, see `xml::pretty_print`.
+pub fn validate(text: &str, format: &str) -> Result<(), SyntaxError> {
+    match format {
+        "toml" => validate_toml(text),
+        "yaml" => validate_yaml(text),
+        _ => Ok(()),
+    }
+}
+
+fn validate_toml(text: &str) -> Result<(), SyntaxError> {
+    let err = match text.parse::<toml::Table>() {
+        Ok(_) => return Ok(()),
+        Err(err) => err,
+    };
+
+    let offset = err.span().map_or(0, |span| span.start).min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |start| start.chars().count()) + 1;
+    Err(SyntaxError { line, column, message: err.message().to_string() })
+}
+
+/// Without a YAML parser at hand this only catches the classic mistakes: tabs
+/// in the indentation, quoted scalars that never close, and keys indented under
+/// a key that already has a value.
+fn validate_yaml(text: &str) -> Result<(), SyntaxError> {
+    // Indentation of the last `key: value` line, if its value was inline
+    let mut scalar_parent: Option<usize> = None;
+    let mut block_indent: Option<usize> = None;
+    // A quoted scalar that continues on the next lines: quote, where it opened, indentation of its key
+    let mut open_quote: Option<(char, SyntaxError, usize)> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+        let error = |column: usize, message: String| SyntaxError { line: i + 1, column, message };
+
+        if let Some((quote, opened, parent)) = open_quote.take() {
+            if !content.is_empty() && indent <= parent {
+                return Err(opened);
+            }
+            if closing_quote(line, quote).is_none() {
+                open_quote = Some((quote, opened, parent));
+            }
+            continue;
+        }
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        // Block scalar (`key: |`) content is free text
+        if let Some(parent) = block_indent {
+            if indent > parent {
+                continue;
+            }
+            block_indent = None;
+        }
+        if content.starts_with('\t') {
+            return Err(error(indent + 1, msg!("yaml-tab-indent")));
+        }
+
+        let item = content.trim_start_matches(['-', ' ']);
+        let item_indent = line.len() - item.len();
+        let key = split_key(item);
+        if scalar_parent.is_some_and(|parent| indent > parent) {
+            // A plain scalar may go on for several lines, but can't gain keys
+            if key.is_some() {
+                return Err(error(indent + 1, msg!("yaml-unexpected-indent")));
+            }
+            continue;
+        }
+
+        scalar_parent = None;
+        let value = key.map_or(item, |(_, rest)| rest.trim_start());
+        let value_column = line.len() - value.len() + 1;
+        let value = strip_comment(value).trim_end();
+        if key.is_some() {
+            if value.starts_with(['|', '>']) {
+                block_indent = Some(item_indent);
+            } else if !value.is_empty() && !value.starts_with(['&', '!', '[', '{']) {
+                scalar_parent = Some(item_indent);
+            }
+        }
+        if let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') {
+            if closing_quote(&value[1..], quote).is_none() {
+                let opened = error(value_column, msg!("yaml-unterminated-quote"));
+                open_quote = Some((quote, opened, item_indent));
+            }
+        }
+    }
+
+    match open_quote {
+        Some((_, opened, _)) => Err(opened),
+        None => Ok(()),
+    }
+}
+
+/// Byte position of the first unescaped `quote` in `text`. In single-quoted
+/// YAML strings a quote is escaped by doubling it.
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' && quote == '"' {
+            chars.next();
+        } else if c == quote {
+            if quote == '\'' && chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                chars.next();
+                continue;
+            }
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Splits `key: value` at the colon; the key may be quoted. Returns the byte
+/// position of the colon and the rest after it.
+fn split_key(text: &str) -> Option<(usize, &str)> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (None, '#') if i == 0 || text[..i].ends_with(' ') => return None,
+            (None, '[' | '{') if i == 0 => return None,
+            (None, ':') => {
+                let rest = &text[i + 1..];
+                if rest.is_empty() || rest.starts_with([' ', '\t']) {
+                    return Some((i, rest));
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Drops a ` # comment` outside of quotes.
+fn strip_comment(text: &str) -> &str {
+    comment_start(text).map_or(text, |start| &text[..start])
+}
+
+fn comment_start(text: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            },
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && (i == 0 || text[..i].ends_with([' ', '\t'])) => return Some(i),
+            None => {},
+        }
+    }
+    None
+}
+
+/// `--fold-depth`: collapses lines nested deeper than `depth` levels into one
+/// `…` line saying how many were hidden. A level is the smallest indentation
+/// step used in the document.
+pub fn fold(text: &str, depth: usize) -> String {
+    let indents = text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len());
+    let step = indents.filter(|&indent| indent > 0).min().unwrap_or(2);
+    let limit = step * depth;
+
+    let mut folded = String::with_capacity(text.len());
+    let mut hidden = 0;
+    let flush = |folded: &mut String, hidden: &mut usize| {
+        if *hidden > 0 {
+            folded.push_str(&format!("{}\u{2026} {}\n", " ".repeat(limit + step), msg!("folded-lines", hidden)));
+            *hidden = 0;
+        }
+    };
+    for line in text.lines() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > limit || (hidden > 0 && line.trim().is_empty()) {
+            hidden += 1;
+            continue;
+        }
+        flush(&mut folded, &mut hidden);
+        folded.push_str(line);
+        folded.push('\n');
+    }
+    flush(&mut folded, &mut hidden);
+    folded
+}
+
+/// Colors one line: keys, strings, numbers and booleans, YAML anchors, aliases
+/// and tags, TOML table headers, comments. Lines are colored on their own, so
+/// the contents of YAML block scalars can be mistaken for keys.
+pub fn colorize(line: &str, format: &str, colors: &ColorConfig) -> String {
+    match format {
+        "toml" => colorize_toml(line, colors),
+        "xml" => xml::colorize(line, colors),
+        _ => colorize_yaml(line, colors),
+    }
+}
+
+fn colorize_yaml(line: &str, colors: &ColorConfig) -> String {
+    let content = line.trim_start_matches([' ', '-']);
+    let (prefix, content) = line.split_at(line.len() - content.len());
+    let (body, comment) = split_comment(content);
+    let mut out = String::from(prefix);
+
+    if body.trim() == "---" || body.trim() == "..." {
+        out.push_str(&paint(body, &colors.dim, colors));
+    } else if let Some((colon, value)) = split_key(body) {
+        out.push_str(&paint(&body[..colon], &colors.highlight, colors));
+        out.push(':');
+        out.push_str(&colorize_yaml_value(value, colors));
+    } else {
+        out.push_str(&colorize_yaml_value(body, colors));
+    }
+    out.push_str(&paint(comment, &colors.dim, colors));
+    out
+}
+
+fn colorize_yaml_value(value: &str, colors: &ColorConfig) -> String {
+    let trimmed = value.trim_start();
+    let mut out = String::from(&value[..value.len() - trimmed.len()]);
+    let mut rest = trimmed;
+
+    // Anchors, aliases and tags come before the scalar
+    while rest.starts_with(['&', '*', '!']) {
+        let end = rest.find(' ').unwrap_or(rest.len());
+        out.push_str(&paint(&rest[..end], &colors.filename, colors));
+        let after = &rest[end..];
+        let next = after.trim_start();
+        out.push_str(&after[..after.len() - next.len()]);
+        rest = next;
+    }
+
+    let scalar = rest.trim_end();
+    if scalar.starts_with(['[', '{']) {
+        out.push_str(&colorize_tokens(scalar, colors));
+    } else if scalar.starts_with(['|', '>']) {
+        out.push_str(&paint(scalar, &colors.dim, colors));
+    } else if is_literal(scalar) {
+        out.push_str(&paint(scalar, &colors.number, colors));
+    } else {
+        out.push_str(&paint(scalar, &colors.success, colors));
+    }
+    out.push_str(&rest[scalar.len()..]);
+    out
+}
+
+fn colorize_toml(line: &str, colors: &ColorConfig) -> String {
+    let content = line.trim_start();
+    let (indent, content) = line.split_at(line.len() - content.len());
+    let (body, comment) = split_comment(content);
+    let mut out = String::from(indent);
+
+    if body.starts_with('[') {
+        out.push_str(&paint(body, &colors.filename, colors));
+    } else if let Some(eq) = key_end_toml(body) {
+        out.push_str(&paint(&body[..eq], &colors.highlight, colors));
+        out.push('=');
+        out.push_str(&colorize_tokens(&body[eq + 1..], colors));
+    } else {
+        // Continuation of a multi-line array or string
+        out.push_str(&colorize_tokens(body, colors));
+    }
+    out.push_str(&paint(comment, &colors.dim, colors));
+    out
+}
+
+/// Position of the `=` after a TOML key, skipping quoted key parts.
+fn key_end_toml(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '=') => return Some(i),
+            (None, c) if !(c.is_alphanumeric() || " \t._-".contains(c)) => return None,
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Colors strings, numbers and booleans inside a value, e.g. an inline array.
+fn colorize_tokens(text: &str, colors: &ColorConfig) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '"' || c == '\'' {
+            let mut end = rest.len();
+            let mut escaped = false;
+            for (i, d) in rest.char_indices().skip(1) {
+                if escaped {
+                    escaped = false;
+                } else if d == '\\' && c == '"' {
+                    escaped = true;
+                } else if d == c {
+                    end = i + 1;
+                    break;
+                }
+            }
+            out.push_str(&paint(&rest[..end], &colors.success, colors));
+            rest = &rest[end..];
+        } else if c.is_alphanumeric() || "+-.:_".contains(c) {
+            let end = rest.find(|d: char| !(d.is_alphanumeric() || "+-.:_".contains(d))).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if is_literal(word) {
+                out.push_str(&paint(word, &colors.number, colors));
+            } else {
+                out.push_str(word);
+            }
+            rest = &rest[end..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Numbers, booleans, null and dates.
+fn is_literal(word: &str) -> bool {
+    let lower = word.to_ascii_lowercase();
+    if ["true", "false", "null", "~", "yes", "no", "inf", "+inf", "-inf", "nan", ".inf", "-.inf", ".nan"].contains(&lower.as_str()) {
+        return true;
+    }
+    let digits = word.trim_start_matches(['+', '-']);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_hexdigit() || "._:+-xXoOTZ ".contains(c))
+}
+
+fn split_comment(text: &str) -> (&str, &str) {
+    match comment_start(text) {
+        Some(start) => text.split_at(start),
+        None => (text, ""),
+    }
+}
+
+pub fn paint(text: &str, color: &str, colors: &ColorConfig) -> String {
+    if text.is_empty() || color.is_empty() {
+        text.to_string()
+    } else {
+        format!("{}{}{}", color, text, colors.reset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Depth;
+
+    fn error_at(text: &str, format: &str) -> (usize, usize) {
+        let err = validate(text, format).expect_err("should not validate");
+        (err.line, err.column)
+    }
+
+    #[test]
+    fn format_from_the_extension() {
+        assert_eq!(format_for(Path::new("deploy.YAML")), Some("yaml"));
+        assert_eq!(format_for(Path::new("a.yml")), Some("yaml"));
+        assert_eq!(format_for(Path::new("Cargo.toml")), Some("toml"));
+        assert_eq!(format_for(Path::new("a.json")), None);
+        assert_eq!(format_for(Path::new("toml")), None);
+    }
+
+    #[test]
+    fn toml_errors_point_at_the_problem() {
+        assert!(validate("a = 1\n[t]\nb = \"x\"\n", "toml").is_ok());
+        assert_eq!(error_at("a = 1\nb = [1,\nc = \"x\"\n", "toml"), (3, 1));
+        assert_eq!(error_at("a = 1\na = 2\n", "toml").0, 2);
+        assert_eq!(error_at("k = \"\u{e9}t\u{e9} \n", "toml").0, 1);
+    }
+
+    #[test]
+    fn yaml_catches_the_classic_mistakes() {
+        let valid = "a:\n  b: 1\n  list:\n    - x\n    - y: 'it''s'\n  text: |\n    free: text\n    \tkept\n  long: plain\n    continued\n";
+        assert!(validate(valid, "yaml").is_ok());
+        assert_eq!(error_at("a:\n  b: 1\n\tc: 2\n", "yaml"), (3, 1));
+        assert_eq!(error_at("a: 1\n  b: 2\n", "yaml"), (2, 3));
+        assert_eq!(error_at("a: \"open\nb: 2\n", "yaml"), (1, 4));
+        assert_eq!(error_at("a: 'open\n", "yaml"), (1, 4));
+        // A quoted scalar may go on over indented lines
+        assert!(validate("a: \"one\n  two\"\nb: 2\n", "yaml").is_ok());
+    }
+
+    #[test]
+    fn fold_collapses_deep_levels() {
+        let text = "top:\n  mid:\n    deep: 1\n    deeper: 2\n  other: 3\n";
+        assert_eq!(fold(text, 1), "top:\n  mid:\n    \u{2026} 2 lines folded\n  other: 3\n");
+        assert_eq!(fold(text, 2), text);
+        assert_eq!(fold(text, 0), "top:\n  \u{2026} 4 lines folded\n");
+    }
+
+    #[test]
+    fn colorize_yaml_and_toml() {
+        let colors = ColorConfig::new(true, Depth::Ansi16);
+        let paint = |text: &str, color: &str| paint(text, color, &colors);
+        assert_eq!(colorize("name: web # app", "yaml", &colors),
+                   format!("{}: {} {}", paint("name", &colors.highlight), paint("web", &colors.success), paint("# app", &colors.dim)));
+        assert_eq!(colorize("- port: 80", "yaml", &colors),
+                   format!("- {}: {}", paint("port", &colors.highlight), paint("80", &colors.number)));
+        assert_eq!(colorize("base: &defaults", "yaml", &colors),
+                   format!("{}: {}", paint("base", &colors.highlight), paint("&defaults", &colors.filename)));
+        assert_eq!(colorize("[server]", "toml", &colors), paint("[server]", &colors.filename));
+        assert_eq!(colorize("ports = [80, \"x\"]", "toml", &colors),
+                   format!("{}= [{}, {}]", paint("ports ", &colors.highlight), paint("80", &colors.number), paint("\"x\"", &colors.success)));
+    }
+}
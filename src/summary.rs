@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{columns, print_line, units, Config};
+
+/// What `process_input` made of one input.
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    pub lines_read: usize,
+    pub lines_printed: usize,
+    /// Lines matching `--grep`
+    pub matches: usize,
+    /// Read errors, which end the input early
+    pub errors: usize,
+}
+
+impl Stats {
+    pub fn add(&mut self, other: &Stats) {
+        self.lines_read += other.lines_read;
+        self.lines_printed += other.lines_printed;
+        self.matches += other.matches;
+        self.errors += other.errors;
+    }
+}
+
+struct Row {
+    name: String,
+    /// None for stdin and anything else that isn't a regular file
+    size: Option<u64>,
+    stats: Stats,
+    elapsed: Duration,
+}
+
+/// `--summary`: a table with a row per input and the totals, printed after the content.
+#[derive(Default)]
+pub struct Summary {
+    rows: Vec<Row>,
+}
+
+impl Summary {
+    /// Adds an input with the `stats` of reading it, and `failed` if showing
+    /// it went wrong in a way that wasn't a read error (it couldn't be opened, say).
+    pub fn add(&mut self, name: &str, path: Option<&Path>, mut stats: Stats, failed: bool, elapsed: Duration) {
+        if failed {
+            stats.errors = stats.errors.max(1);
+        }
+        let size = path.and_then(|path| fs::metadata(path).ok()).filter(|metadata| metadata.is_file()).map(|metadata| metadata.len());
+        self.rows.push(Row { name: name.to_string(), size, stats, elapsed });
+    }
+
+    pub fn print(&self, config: &Config) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let grep = config.searching();
+        let paint = |color: &str, text: String| format!("{}{}{}", color, text, config.colors.reset);
+        let cells = |name: String, size: Option<u64>, stats: &Stats, elapsed: Duration| {
+            let mut cells = vec![
+                name,
+                size.map_or_else(|| "-".to_string(), |size| units::size(size, config)),
+                units::count(stats.lines_read as u64, config),
+                units::count(stats.lines_printed as u64, config),
+            ];
+            if grep {
+                let matches = units::count(stats.matches as u64, config);
+                cells.push(if stats.matches > 0 { paint(&config.colors.highlight, matches) } else { matches });
+            }
+            let errors = units::count(stats.errors as u64, config);
+            cells.push(if stats.errors > 0 { paint(&config.colors.error, errors) } else { errors });
+            cells.push(format_elapsed(elapsed));
+            cells
+        };
+
+        let mut header = vec![msg!("stat-name"), msg!("stat-size"), msg!("summary-read"), msg!("summary-printed")];
+        if grep {
+            header.push(msg!("summary-matches"));
+        }
+        header.push(msg!("summary-errors"));
+        header.push(msg!("summary-time"));
+        let mut table = vec![header.into_iter().map(|label| paint(&config.colors.dim, label)).collect()];
+
+        let mut total = Stats::default();
+        let mut total_size = None;
+        let mut total_elapsed = Duration::ZERO;
+        for row in &self.rows {
+            table.push(cells(paint(&config.colors.filename, row.name.clone()), row.size, &row.stats, row.elapsed));
+            total.add(&row.stats);
+            total_size = row.size.map(|size| total_size.unwrap_or(0) + size).or(total_size);
+            total_elapsed += row.elapsed;
+        }
+        table.push(cells(paint(&config.colors.dim, msg!("summary-total")), total_size, &total, total_elapsed));
+
+        // Set off from the content like a file header
+        if !config.summary_only {
+            print_line("");
+        }
+        // Everything but the name lines up on the right
+        let right: Vec<usize> = (1..table[0].len()).collect();
+        for line in columns::table(&table, &right) {
+            print_line(&line);
+        }
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{} ms", elapsed.as_millis())
+    } else {
+        format!("{:.1} s", elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elapsed_times() {
+        assert_eq!(format_elapsed(Duration::from_micros(900)), "0 ms");
+        assert_eq!(format_elapsed(Duration::from_millis(999)), "999 ms");
+        assert_eq!(format_elapsed(Duration::from_millis(1000)), "1.0 s");
+        assert_eq!(format_elapsed(Duration::from_millis(12_345)), "12.3 s");
+    }
+
+    #[test]
+    fn stats_add_up() {
+        let mut total = Stats { lines_read: 1, lines_printed: 2, matches: 3, errors: 0 };
+        total.add(&Stats { lines_read: 10, lines_printed: 20, matches: 30, errors: 1 });
+        assert_eq!((total.lines_read, total.lines_printed, total.matches, total.errors), (11, 22, 33, 1));
+    }
+
+    #[test]
+    fn rows_take_the_stats_given() {
+        let mut summary = Summary::default();
+        summary.add("a.txt", None, Stats { lines_read: 4, lines_printed: 2, matches: 2, errors: 0 }, false, Duration::ZERO);
+        // An input that couldn't be opened counts as one error
+        summary.add("gone.txt", None, Stats::default(), true, Duration::ZERO);
+        summary.add("bad.txt", None, Stats { errors: 3, ..Stats::default() }, true, Duration::ZERO);
+        let rows: Vec<(&str, usize, usize)> = summary.rows.iter().map(|row| (row.name.as_str(), row.stats.lines_read, row.stats.errors)).collect();
+        assert_eq!(rows, [("a.txt", 4, 0), ("gone.txt", 0, 1), ("bad.txt", 0, 3)]);
+    }
+}
@@ -0,0 +1,293 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::{more, units, Config};
+
+/// Least time between two title updates, so a slow connection isn't flooded.
+const TITLE_GAP: Duration = Duration::from_millis(250);
+
+/// When the title was last set, None before the first time.
+static TITLE_SET: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Set once the title was saved, so it's put back on the way out.
+static TITLE_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// xterm's title stack: push saves the window title, pop puts it back.
+const TITLE_PUSH: &str = "\x1B[22;0t";
+const TITLE_POP: &str = "\x1B[23;0t";
+
+/// Whether the terminal keeps a stack of titles, guessed from `$TERM` like
+/// the color depth is. Terminals that don't have one mostly ignore the push
+/// and pop, but then the title is cleared rather than left as ours.
+pub fn has_title_stack() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    ["xterm", "foot", "alacritty", "kitty", "tmux", "screen", "wezterm"].iter().any(|name| term.starts_with(name))
+}
+
+/// OSC 0, which sets the window and the icon title. Control characters in a
+/// file name would end it early, so they become `?`.
+pub fn title_sequence(title: &str) -> String {
+    let title: String = title.chars().map(|c| if c.is_control() { '?' } else { c }).collect();
+    format!("\x1B]0;{}\x07", title)
+}
+
+/// `meow: NAME (NN%, N matches)`, with what's known of the two.
+pub fn title_text(name: &str, percent: Option<u64>, matches: Option<usize>) -> String {
+    let mut status = Vec::new();
+    if let Some(percent) = percent {
+        status.push(format!("{}%", percent));
+    }
+    if let Some(matches) = matches {
+        status.push(msg!("title-matches", matches));
+    }
+    if status.is_empty() {
+        format!("meow: {}", name)
+    } else {
+        format!("meow: {} ({})", name, status.join(", "))
+    }
+}
+
+/// `--set-title` only writes to a terminal, and not with `--no-color` or
+/// `--safe`, which both ask for no escape sequences.
+fn title_enabled(config: &Config) -> bool {
+    config.use_colors && config.safe != Some(true) && atty::is(atty::Stream::Stdout)
+}
+
+/// `--set-title`: shows the input being read in the window title, with how
+/// far into it we are and, with `--grep`, how many lines matched. Updates come
+/// at most every `TITLE_GAP` unless `force`d, as at the start and end of an
+/// input. The first one saves the title there was.
+pub fn set_title(name: &str, matches: Option<usize>, force: bool, config: &Config) {
+    if !config.set_title {
+        return;
+    }
+    // Before the rest, as this runs for every line
+    let mut last = TITLE_SET.lock().unwrap_or_else(PoisonError::into_inner);
+    if !force && last.is_some_and(|time| time.elapsed() < TITLE_GAP) {
+        return;
+    }
+    *last = Some(Instant::now());
+    if !title_enabled(config) {
+        return;
+    }
+
+    let mut sequence = String::new();
+    if !TITLE_SAVED.swap(true, Ordering::Relaxed) && has_title_stack() {
+        sequence.push_str(TITLE_PUSH);
+    }
+    sequence.push_str(&title_sequence(&title_text(name, more::percent(), matches)));
+    let mut out = io::stdout();
+    let _ = out.write_all(sequence.as_bytes()).and_then(|()| out.flush());
+}
+
+/// Puts back the title saved by `set_title`, or clears ours where there's no
+/// stack to take it from.
+pub fn restore_title() {
+    if !TITLE_SAVED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let sequence = if has_title_stack() { TITLE_POP.to_string() } else { title_sequence("") };
+    let mut out = io::stdout();
+    let _ = out.write_all(sequence.as_bytes()).and_then(|()| out.flush());
+}
+
+/// What to do with an input too big to show on the terminal unasked.
+#[derive(Debug, PartialEq)]
+pub enum LargeInput {
+    Show,
+    /// Through the pager, as with `--page`
+    Page,
+    Skip,
+}
+
+/// Whether `size` bytes are more than `--max-terminal-bytes` lets go to the
+/// terminal without asking. Only a terminal with nothing paging it is at
+/// risk; `--force` takes it on anyway.
+pub fn too_large(size: Option<u64>, config: &Config) -> bool {
+    asks_first(size, config) && atty::is(atty::Stream::Stdout)
+}
+
+/// `too_large` but for the terminal, which is taken as there.
+fn asks_first(size: Option<u64>, config: &Config) -> bool {
+    let over = matches!((size, config.max_terminal_bytes), (Some(size), Some(max)) if size > max);
+    over && !config.force && !config.page_mode && !config.more && !config.summary_only && config.output.is_none()
+}
+
+/// Asks whether to show `name` of `size` bytes after all. The question goes
+/// to `/dev/tty` and the answer comes from there, so it works when stdin is
+/// part of the input. Without a tty to ask on, the input is shown.
+pub fn confirm_large(name: &str, size: u64, config: &Config) -> LargeInput {
+    let Ok(tty) = OpenOptions::new().read(true).write(true).open("/dev/tty") else {
+        return LargeInput::Show;
+    };
+    let mut writer = &tty;
+    let _ = write!(writer, "{}meow: {}{} ", config.colors.highlight, msg!("large-input-prompt", name, units::size(size, config)), config.colors.reset);
+    let _ = writer.flush();
+    let mut answer = String::new();
+    if BufReader::new(&tty).read_line(&mut answer).is_err() {
+        return LargeInput::Skip;
+    }
+    parse_answer(&answer)
+}
+
+/// The answer to `confirm_large`: anything but yes or page skips the input.
+fn parse_answer(answer: &str) -> LargeInput {
+    match answer.trim().to_lowercase().chars().next() {
+        Some('y') => LargeInput::Show,
+        Some('p') => LargeInput::Page,
+        _ => LargeInput::Skip,
+    }
+}
+
+/// Raw mode for as long as it lives: keys come one at a time without Enter,
+/// unechoed, and Ctrl-C comes through as a byte. The terminal is put back
+/// when it's dropped, also when an error or a panic cuts things short.
+pub struct RawMode {
+    #[cfg(unix)]
+    original: libc::termios,
+}
+
+impl RawMode {
+    #[cfg(unix)]
+    pub fn enter() -> io::Result<Self> {
+        let fd = libc::STDIN_FILENO;
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        // SAFETY: tcgetattr only writes into the termios struct we pass in
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // SAFETY: both structs are fully initialized by tcgetattr
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) };
+        Ok(RawMode { original })
+    }
+
+    #[cfg(not(unix))]
+    pub fn enter() -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: `original` was filled in by tcgetattr in `enter`, and
+        // tcsetattr only reads it
+        #[cfg(unix)]
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original)
+        };
+    }
+}
+
+/// Raw mode on the alternate screen for as long as it lives, so `--pick` and
+/// `--fold-sections` leave the scrollback as they found it, also when an error
+/// cuts them short.
+pub struct RawTerminal {
+    _raw: RawMode,
+}
+
+impl RawTerminal {
+    pub fn enter() -> io::Result<Self> {
+        let raw = RawMode::enter()?;
+        print!("\x1B[?1049h");
+        Ok(RawTerminal { _raw: raw })
+    }
+}
+
+/// Leaves the alternate screen; raw mode ends after, when `_raw` is dropped.
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1B[?1049l");
+        let _ = io::stdout().flush();
+    }
+}
+
+pub fn read_byte() -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    Ok((io::stdin().read(&mut byte)? == 1).then_some(byte[0]))
+}
+
+/// The next byte if one comes within a tenth of a second: what follows ESC in
+/// an arrow key's sequence, and nothing after a lone Esc.
+#[cfg(unix)]
+pub fn read_byte_soon() -> io::Result<Option<u8>> {
+    let fd = libc::STDIN_FILENO;
+    let mut current: libc::termios = unsafe { std::mem::zeroed() };
+    // SAFETY: as in RawTerminal::enter
+    if unsafe { libc::tcgetattr(fd, &mut current) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut waiting = current;
+    waiting.c_cc[libc::VMIN] = 0;
+    waiting.c_cc[libc::VTIME] = 1;
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &waiting) };
+    let byte = read_byte();
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &current) };
+    byte
+}
+
+#[cfg(not(unix))]
+pub fn read_byte_soon() -> io::Result<Option<u8>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_sequence_is_osc_0() {
+        assert_eq!(title_sequence("meow: a.txt"), "\x1B]0;meow: a.txt\x07");
+        // A BEL or ESC in the name would end the sequence early
+        assert_eq!(title_sequence("a\x07b\x1B]c\n"), "\x1B]0;a?b?]c?\x07");
+        assert_eq!(title_sequence(""), "\x1B]0;\x07");
+    }
+
+    #[test]
+    fn title_text_shows_what_is_known() {
+        assert_eq!(title_text("a.txt", None, None), "meow: a.txt");
+        assert_eq!(title_text("a.txt", Some(42), None), "meow: a.txt (42%)");
+        assert_eq!(title_text("a.txt", None, Some(3)), "meow: a.txt (3 matches)");
+        assert_eq!(title_text("a.txt", Some(100), Some(0)), "meow: a.txt (100%, 0 matches)");
+    }
+
+    #[test]
+    fn only_big_inputs_ask_first() {
+        let mut config = Config::new();
+        config.apply_option("max-terminal-bytes", Some("1000"));
+        assert!(!asks_first(Some(1000), &config));
+        assert!(asks_first(Some(1001), &config));
+        // Stdin and other inputs of no known size
+        assert!(!asks_first(None, &config));
+        config.apply_option("max-terminal-bytes", Some("0"));
+        assert!(!asks_first(Some(u64::MAX), &config));
+    }
+
+    #[test]
+    fn paging_or_forcing_doesnt_ask() {
+        for option in [("force", None), ("page", None), ("more", None), ("summary-only", None), ("output", Some("out.txt"))] {
+            let mut config = Config::new();
+            config.apply_option("max-terminal-bytes", Some("10"));
+            config.apply_option(option.0, option.1);
+            assert!(!asks_first(Some(11), &config), "--{}", option.0);
+        }
+    }
+
+    #[test]
+    fn answers_default_to_skip() {
+        assert_eq!(parse_answer("y\n"), LargeInput::Show);
+        assert_eq!(parse_answer("  Yes\n"), LargeInput::Show);
+        assert_eq!(parse_answer("p\n"), LargeInput::Page);
+        assert_eq!(parse_answer("PAGE\n"), LargeInput::Page);
+        assert_eq!(parse_answer("\n"), LargeInput::Skip);
+        assert_eq!(parse_answer("n\n"), LargeInput::Skip);
+        assert_eq!(parse_answer("whatever\n"), LargeInput::Skip);
+    }
+}
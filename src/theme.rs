@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::Path;
+
+use toml::{Table, Value};
+
+use crate::color::Depth;
+use crate::{rules, ColorConfig};
+
+/// The single colors of a theme, as `--show-colors` names them.
+const ROLES: [&str; 7] = ["normal", "number", "highlight", "error", "success", "filename", "dim"];
+
+fn role<'a>(colors: &'a mut ColorConfig, name: &str) -> Option<&'a mut String> {
+    Some(match name {
+        "normal" => &mut colors.normal,
+        "number" => &mut colors.number,
+        "highlight" => &mut colors.highlight,
+        "error" => &mut colors.error,
+        "success" => &mut colors.success,
+        "filename" => &mut colors.filename,
+        "dim" => &mut colors.dim,
+        _ => return None,
+    })
+}
+
+/// The parameters of the SGR sequences in `escape`, joined into one: `33`
+/// for `\e[33m`, `30;48;5;208` for `\e[30m\e[48;5;208m`.
+fn parameters(escape: &str) -> String {
+    escape.split("\x1B[").filter_map(|sequence| sequence.strip_suffix('m')).filter(|sequence| !sequence.is_empty())
+        .collect::<Vec<_>>().join(";")
+}
+
+/// A theme color: SGR parameters as `--export-theme` writes them (`1;33`),
+/// or a color like `--rules` takes one (`bright-red`, `#ff8800`).
+fn escape(value: &Value, depth: Depth) -> Result<String, String> {
+    let Value::String(text) = value else {
+        return Err(format!("expected a string, not {}", value.type_str()));
+    };
+    // An empty color leaves that role uncolored
+    if text.is_empty() {
+        return Ok(String::new());
+    }
+    if text.chars().all(|c| c.is_ascii_digit() || c == ';') {
+        return Ok(format!("\x1B[{}m", text));
+    }
+    rules::color_escape(text, false, depth).ok_or_else(|| format!("unknown color '{}'", text))
+}
+
+/// `--theme`: sets the colors `path` names over `colors`, leaving the rest
+/// as they were. The lists replace the default ones whole.
+pub fn load(path: &Path, colors: &mut ColorConfig, depth: Depth) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let table = text.parse::<Table>().map_err(|err| err.to_string())?;
+    for (key, value) in &table {
+        if let Some(slot) = role(colors, key) {
+            *slot = escape(value, depth).map_err(|err| format!("'{}': {}", key, err))?;
+            continue;
+        }
+        let list = match key.as_str() {
+            "blame-age" => &mut colors.blame_age,
+            "file-palette" => &mut colors.file_palette,
+            _ => return Err(format!("unknown key '{}'", key)),
+        };
+        let Value::Array(values) = value else {
+            return Err(format!("'{}' must be an array of colors", key));
+        };
+        if values.is_empty() {
+            return Err(format!("'{}' needs at least one color", key));
+        }
+        *list = values.iter().map(|value| escape(value, depth)).collect::<Result<_, _>>()
+            .map_err(|err| format!("'{}': {}", key, err))?;
+    }
+    Ok(())
+}
+
+/// `--export-theme`: writes `colors` to `path` in the form `load` reads.
+pub fn export(path: &Path, colors: &ColorConfig) -> std::io::Result<()> {
+    let mut text = format!("# meow theme, for --theme={}\n", path.display());
+    let roles = [&colors.normal, &colors.number, &colors.highlight, &colors.error, &colors.success, &colors.filename, &colors.dim];
+    for (name, escape) in ROLES.iter().zip(roles) {
+        text.push_str(&format!("{} = \"{}\"\n", name, parameters(escape)));
+    }
+    let list = |values: &[String]| values.iter().map(|escape| format!("\"{}\"", parameters(escape))).collect::<Vec<_>>().join(", ");
+    text.push_str(&format!("blame-age = [{}]\n", list(&colors.blame_age)));
+    text.push_str(&format!("file-palette = [{}]\n", list(&colors.file_palette)));
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every color of `colors` as its SGR parameters, in export order.
+    fn all_parameters(colors: &ColorConfig) -> Vec<String> {
+        [&colors.normal, &colors.number, &colors.highlight, &colors.error, &colors.success, &colors.filename, &colors.dim]
+            .into_iter().chain(&colors.blame_age).chain(&colors.file_palette).map(|escape| parameters(escape)).collect()
+    }
+
+    #[test]
+    fn parameters_join_sequences() {
+        assert_eq!(parameters("\x1B[33m"), "33");
+        assert_eq!(parameters("\x1B[30m\x1B[48;5;208m"), "30;48;5;208");
+        assert_eq!(parameters(""), "");
+    }
+
+    #[test]
+    fn exported_theme_loads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("theme.toml");
+        for depth in [Depth::Ansi16, Depth::Ansi256, Depth::TrueColor] {
+            let mut colors = ColorConfig::new(true, depth);
+            colors.highlight = "\x1B[1;91m".to_string();
+            colors.file_palette = vec!["\x1B[38;2;255;136;0m".to_string()];
+            export(&path, &colors).unwrap();
+            // Over no colors at all, so every one has to come from the file
+            let mut loaded = ColorConfig::new(false, depth);
+            load(&path, &mut loaded, depth).unwrap();
+            assert_eq!(all_parameters(&loaded), all_parameters(&colors));
+        }
+    }
+
+    #[test]
+    fn colors_by_name_or_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("theme.toml");
+        fs::write(&path, "highlight = \"1;35\"\nerror = \"bright-red\"\nnumber = \"\"\nfile-palette = [\"#ff8800\", \"34\"]\n").unwrap();
+        let mut colors = ColorConfig::new(true, Depth::TrueColor);
+        load(&path, &mut colors, Depth::TrueColor).unwrap();
+        assert_eq!(colors.highlight, "\x1B[1;35m");
+        assert_eq!(parameters(&colors.error), "91");
+        assert_eq!(colors.number, "");
+        assert_eq!(colors.file_palette.iter().map(|escape| parameters(escape)).collect::<Vec<_>>(), ["38;2;255;136;0", "34"]);
+        // What the file doesn't name stays
+        assert_eq!(colors.success, "\x1B[32m");
+    }
+
+    #[test]
+    fn bad_themes_say_what_is_wrong() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("theme.toml");
+        let mut colors = ColorConfig::new(true, Depth::Ansi16);
+        for (text, error) in [
+            ("shade = \"33\"\n", "unknown key 'shade'"),
+            ("error = \"puce\"\n", "'error': unknown color 'puce'"),
+            ("error = 31\n", "'error': expected a string, not integer"),
+            ("blame-age = \"33\"\n", "'blame-age' must be an array of colors"),
+            ("file-palette = []\n", "'file-palette' needs at least one color"),
+        ] {
+            fs::write(&path, text).unwrap();
+            assert_eq!(load(&path, &mut colors, Depth::Ansi16), Err(error.to_string()), "{}", text);
+        }
+        assert!(load(&dir.path().join("missing.toml"), &mut colors, Depth::Ansi16).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::Config;
+
+/// Steps of 1024, the default.
+const BINARY: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Steps of 1000, with `--si`.
+const DECIMAL: [&str; 7] = ["B", "KB", "MB", "GB", "TB", "PB", "EB"];
+
+/// `digits` with `separator` between groups of three, from the right.
+fn group(digits: &str, separator: &str) -> String {
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// A count such as lines read, with thousands separated the way the
+/// language writes them (`1,048,576`, or with spaces in Russian) unless
+/// `--thousands-separator` says otherwise. `--bytes-exact` leaves it bare.
+pub fn count(n: u64, config: &Config) -> String {
+    let digits = n.to_string();
+    if config.bytes_exact {
+        return digits;
+    }
+    match &config.thousands_separator {
+        Some(separator) => group(&digits, separator),
+        None => group(&digits, &msg!("thousands-separator")),
+    }
+}
+
+/// A size for people: bytes below the first step, then one decimal in
+/// KiB, MiB, ... or, with `--si`, KB, MB, .... A value that would round up
+/// to the next step is shown in that one, so there's no `1024.0 KiB`.
+/// `--bytes-exact` gives the plain number of bytes, for scripts.
+pub fn size(bytes: u64, config: &Config) -> String {
+    if config.bytes_exact {
+        return bytes.to_string();
+    }
+    let (step, units) = if config.si { (1000.0, &DECIMAL) } else { (1024.0, &BINARY) };
+    let mut value = bytes as f64;
+    if value < step {
+        return format!("{} {}", bytes, units[0]);
+    }
+    let mut unit = 0;
+    while unit + 1 < units.len() && (value * 10.0).round() / 10.0 >= step {
+        value /= step;
+        unit += 1;
+    }
+    let shown = format!("{:.1}", value).replacen('.', &msg!("decimal-point"), 1);
+    format!("{} {}", shown, units[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_step_at_1024() {
+        let config = Config::new();
+        assert_eq!(size(0, &config), "0 B");
+        assert_eq!(size(1023, &config), "1023 B");
+        assert_eq!(size(1024, &config), "1.0 KiB");
+        assert_eq!(size(1025, &config), "1.0 KiB");
+        assert_eq!(size(1536, &config), "1.5 KiB");
+        assert_eq!(size(5 << 30, &config), "5.0 GiB");
+        assert_eq!(size(u64::MAX, &config), "16.0 EiB");
+    }
+
+    #[test]
+    fn rounding_up_moves_to_the_next_unit() {
+        let config = Config::new();
+        // 1023.95 KiB would show as 1024.0 KiB
+        assert_eq!(size(1024 * 1024 - 50, &config), "1.0 MiB");
+        assert_eq!(size(1024 * 1024 - 200, &config), "1023.8 KiB");
+    }
+
+    #[test]
+    fn si_steps_at_1000() {
+        let mut config = Config::new();
+        config.apply_option("si", None);
+        assert_eq!(size(999, &config), "999 B");
+        assert_eq!(size(1000, &config), "1.0 KB");
+        assert_eq!(size(1023, &config), "1.0 KB");
+        assert_eq!(size(1024, &config), "1.0 KB");
+        assert_eq!(size(999_960, &config), "1.0 MB");
+        assert_eq!(size(u64::MAX, &config), "18.4 EB");
+    }
+
+    #[test]
+    fn bytes_exact_is_the_plain_number() {
+        let mut config = Config::new();
+        config.apply_option("bytes-exact", None);
+        assert_eq!(size(1025, &config), "1025");
+        assert_eq!(size(u64::MAX, &config), "18446744073709551615");
+        assert_eq!(count(1_048_576, &config), "1048576");
+    }
+
+    #[test]
+    fn counts_group_thousands() {
+        let mut config = Config::new();
+        assert_eq!(count(0, &config), "0");
+        assert_eq!(count(999, &config), "999");
+        assert_eq!(count(1000, &config), "1,000");
+        assert_eq!(count(1_048_576, &config), "1,048,576");
+        assert_eq!(count(u64::MAX, &config), "18,446,744,073,709,551,615");
+        config.apply_option("thousands-separator", Some("_"));
+        assert_eq!(count(1_000_000, &config), "1_000_000");
+        config.apply_option("thousands-separator", Some(""));
+        assert_eq!(count(1_000_000, &config), "1000000");
+    }
+}
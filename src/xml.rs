@@ -0,0 +1,376 @@
+use crate::structured::paint;
+use crate::ColorConfig;
+
+/// HTML elements that never have a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// HTML elements whose end tag may be left out before a sibling of the same kind.
+const SIBLING_CLOSED: &[&str] = &["dd", "dt", "li", "option", "p", "td", "th", "tr"];
+
+/// Elements whose content is raw text, so a `<` in there isn't markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+#[derive(Clone, Copy)]
+enum Token<'a> {
+    /// A start tag, `raw` being everything from `<` to `>`
+    Open { name: &'a str, raw: &'a str, self_closing: bool },
+    Close { name: &'a str, raw: &'a str },
+    /// Comments, CDATA sections, doctypes and processing instructions
+    Markup(&'a str),
+    Text(&'a str),
+}
+
+/// Splits a document into tags and text in one pass. Anything that doesn't
+/// look like markup (a stray `<`, a tag that never ends) is text.
+#[derive(Clone)]
+struct Tokens<'a> {
+    rest: &'a str,
+    /// Inside `<script>` or `<style>`: the element to look for the end of
+    raw_text: Option<&'a str>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Tokens { rest: text, raw_text: None }
+    }
+
+    fn take(&mut self, len: usize) -> &'a str {
+        let (token, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        token
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        if let Some(name) = self.raw_text.take() {
+            let end = find_close_tag(self.rest, name).unwrap_or(self.rest.len());
+            if end > 0 {
+                return Some(Token::Text(self.take(end)));
+            }
+        }
+
+        if !starts_markup(self.rest) {
+            let mut end = self.rest.len();
+            for (i, _) in self.rest.match_indices('<').filter(|&(i, _)| i > 0) {
+                if starts_markup(&self.rest[i..]) {
+                    end = i;
+                    break;
+                }
+            }
+            return Some(Token::Text(self.take(end)));
+        }
+
+        for (start, end) in [("<!--", "-->"), ("<![CDATA[", "]]>")] {
+            if self.rest.starts_with(start) {
+                let len = self.rest[start.len()..].find(end).map_or(self.rest.len(), |i| start.len() + i + end.len());
+                return Some(Token::Markup(self.take(len)));
+            }
+        }
+
+        let len = match tag_end(self.rest) {
+            Some(len) => len,
+            // A tag cut off at the end of the input
+            None => return Some(Token::Text(self.take(self.rest.len()))),
+        };
+        let raw = self.take(len);
+        if raw.starts_with("<!") || raw.starts_with("<?") {
+            return Some(Token::Markup(raw));
+        }
+        if let Some(tag) = raw.strip_prefix("</") {
+            return Some(Token::Close { name: tag_name(tag), raw });
+        }
+
+        let name = tag_name(&raw[1..]);
+        let self_closing = raw.ends_with("/>");
+        if !self_closing && RAW_TEXT_ELEMENTS.iter().any(|element| element.eq_ignore_ascii_case(name)) {
+            self.raw_text = Some(name);
+        }
+        Some(Token::Open { name, raw, self_closing })
+    }
+}
+
+fn starts_markup(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next() == Some('<') && chars.next().is_some_and(|c| c.is_alphabetic() || "/!?_".contains(c))
+}
+
+/// Length of the tag at the start of `text`, up to the `>` outside of quotes.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i + 1),
+            None => {},
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> &str {
+    let end = tag.find(|c: char| c.is_whitespace() || c == '/' || c == '>').unwrap_or(tag.len());
+    &tag[..end]
+}
+
+/// Where `</name` starts in `text`, ignoring case.
+fn find_close_tag(text: &str, name: &str) -> Option<usize> {
+    text.match_indices("</").map(|(i, _)| i).find(|&i| {
+        text.get(i + 2..i + 2 + name.len()).is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+    })
+}
+
+fn is_void(name: &str) -> bool {
+    VOID_ELEMENTS.iter().any(|element| element.eq_ignore_ascii_case(name))
+}
+
+/// `--xml`: puts every tag on its own line, indented by nesting depth. Elements
+/// holding only text stay on one line, and those named in `collapse` are
+/// summarized as `<name> … N elements </name>`. Text is kept as it is, minus
+/// surrounding whitespace. Unclosed elements are closed implicitly like HTML
+/// does; a closing tag that matches nothing means the structure can't be
+/// trusted, so the document is returned unchanged.
+pub fn pretty_print(text: &str, indent: usize, collapse: &[String]) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 4);
+    let mut open: Vec<&str> = Vec::new();
+    let mut tokens = Tokens::new(text);
+
+    let push_line = |out: &mut String, depth: usize, line: &str| {
+        out.push_str(&" ".repeat(depth * indent));
+        out.push_str(line);
+        out.push('\n');
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => {
+                let text = text.trim();
+                if !text.is_empty() {
+                    push_line(&mut out, open.len(), text);
+                }
+            },
+            Token::Markup(raw) => push_line(&mut out, open.len(), raw.trim_end()),
+            Token::Open { raw, self_closing: true, .. } => push_line(&mut out, open.len(), raw),
+            Token::Open { name, raw, .. } if is_void(name) => push_line(&mut out, open.len(), raw),
+            Token::Open { name, raw, .. } if collapse.iter().any(|tag| tag.eq_ignore_ascii_case(name)) => {
+                let (elements, close) = skip_element(&mut tokens, name);
+                let summary = format!("{} \u{2026} {} {}", raw, msg!("xml-collapsed", elements), close);
+                push_line(&mut out, open.len(), summary.trim_end());
+            },
+            Token::Open { name, raw, .. } => {
+                // <li>a<li>b: the second item closes the first
+                if open.last().is_some_and(|last| last.eq_ignore_ascii_case(name))
+                    && SIBLING_CLOSED.iter().any(|element| element.eq_ignore_ascii_case(name))
+                {
+                    open.pop();
+                }
+
+                // Keep <b>text</b> and <p></p> on one line
+                let mut ahead = tokens.clone();
+                match (ahead.next(), ahead.next()) {
+                    (Some(Token::Close { name: close, raw: close_raw }), _) if close.eq_ignore_ascii_case(name) => {
+                        push_line(&mut out, open.len(), &format!("{}{}", raw, close_raw));
+                        tokens.next();
+                    },
+                    (Some(Token::Text(text)), Some(Token::Close { name: close, raw: close_raw }))
+                        if close.eq_ignore_ascii_case(name) && !text.contains('\n') =>
+                    {
+                        push_line(&mut out, open.len(), &format!("{}{}{}", raw, text.trim(), close_raw));
+                        tokens.next();
+                        tokens.next();
+                    },
+                    _ => {
+                        push_line(&mut out, open.len(), raw);
+                        open.push(name);
+                    },
+                }
+            },
+            Token::Close { name, raw } => {
+                match open.iter().rposition(|element| element.eq_ignore_ascii_case(name)) {
+                    Some(depth) => {
+                        open.truncate(depth);
+                        push_line(&mut out, depth, raw);
+                    },
+                    // </br> and the like: nothing was opened
+                    None if is_void(name) => push_line(&mut out, open.len(), raw),
+                    None => return text.to_string(),
+                }
+            },
+        }
+    }
+    out
+}
+
+/// Skips to the end of a collapsed element, returning how many elements it
+/// contained and its closing tag (empty if it never closes).
+fn skip_element<'a>(tokens: &mut Tokens<'a>, name: &str) -> (usize, &'a str) {
+    let mut nested = 0;
+    let mut elements = 0;
+    for token in tokens {
+        match token {
+            Token::Open { name: inner, self_closing, .. } => {
+                elements += 1;
+                if inner.eq_ignore_ascii_case(name) && !self_closing {
+                    nested += 1;
+                }
+            },
+            Token::Close { name: inner, raw } if inner.eq_ignore_ascii_case(name) => {
+                if nested == 0 {
+                    return (elements, raw);
+                }
+                nested -= 1;
+            },
+            _ => {},
+        }
+    }
+    (elements, "")
+}
+
+/// Colors tag names, attribute names and values, comments and CDATA in one
+/// line of markup. Text is left alone. Lines are colored on their own, so the
+/// inner lines of a multi-line comment or tag aren't recognized.
+pub fn colorize(line: &str, colors: &ColorConfig) -> String {
+    let mut out = String::with_capacity(line.len());
+    for token in Tokens::new(line) {
+        match token {
+            Token::Text(text) => out.push_str(text),
+            Token::Markup(raw) if raw.starts_with("<![CDATA[") => out.push_str(&paint(raw, &colors.filename, colors)),
+            Token::Markup(raw) => out.push_str(&paint(raw, &colors.dim, colors)),
+            Token::Open { name, raw, .. } | Token::Close { name, raw } => {
+                let start = if raw.starts_with("</") { 2 } else { 1 };
+                out.push_str(&raw[..start]);
+                out.push_str(&paint(name, &colors.highlight, colors));
+                colorize_attributes(&raw[start + name.len()..], colors, &mut out);
+            },
+        }
+    }
+    out
+}
+
+fn colorize_attributes(text: &str, colors: &ColorConfig, out: &mut String) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' || c == '\'' {
+            let end = rest[1..].find(c).map_or(rest.len(), |i| i + 2);
+            out.push_str(&paint(&rest[..end], &colors.success, colors));
+            rest = &rest[end..];
+        } else if c.is_whitespace() || "=/>".contains(c) {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        } else {
+            let end = rest.find(|d: char| d.is_whitespace() || "=/>\"'".contains(d)).unwrap_or(rest.len());
+            // An unquoted value follows `=`, anything else is a name
+            let color = if out.ends_with('=') { &colors.success } else { &colors.number };
+            out.push_str(&paint(&rest[..end], color, colors));
+            rest = &rest[end..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Depth;
+
+    const MALFORMED_PAGE: &str = "<html><head><meta charset=utf-8><title>T</title><script>if (a<b) x();</script></head>\
+                                  <body><ul><li>one<li>two</ul><p>para<br>next<p>second<input disabled></body></html>";
+
+    #[test]
+    fn pretty_print_xml() {
+        let text = "<?xml version=\"1.0\"?><root a=\"1\"><!-- c --><item id=x>text</item><empty/>\
+                    <data><![CDATA[<raw>]]></data><long>one\ntwo</long></root>";
+        assert_eq!(pretty_print(text, 2, &[]), "\
+<?xml version=\"1.0\"?>
+<root a=\"1\">
+  <!-- c -->
+  <item id=x>text</item>
+  <empty/>
+  <data>
+    <![CDATA[<raw>]]>
+  </data>
+  <long>
+    one
+two
+  </long>
+</root>
+");
+    }
+
+    #[test]
+    fn pretty_print_forgives_malformed_html() {
+        assert_eq!(pretty_print(MALFORMED_PAGE, 2, &[]), "\
+<html>
+  <head>
+    <meta charset=utf-8>
+    <title>T</title>
+    <script>if (a<b) x();</script>
+  </head>
+  <body>
+    <ul>
+      <li>
+        one
+      <li>
+        two
+    </ul>
+    <p>
+      para
+      <br>
+      next
+    <p>
+      second
+      <input disabled>
+  </body>
+</html>
+");
+    }
+
+    #[test]
+    fn pretty_print_collapses_named_elements() {
+        let text = "<root><skip><a/><b><c/></b></skip><SKIP><skip></skip></SKIP><keep/></root>";
+        assert_eq!(pretty_print(text, 4, &["skip".to_string()]), "\
+<root>
+    <skip> \u{2026} 3 elements </skip>
+    <SKIP> \u{2026} 1 elements </SKIP>
+    <keep/>
+</root>
+");
+    }
+
+    #[test]
+    fn unbalanced_documents_are_left_alone() {
+        assert_eq!(pretty_print("<a><b></c></a>", 2, &[]), "<a><b></c></a>");
+        // A tag cut off at the end, or a stray `<`, is text
+        assert_eq!(pretty_print("<a>1 < 2<b", 2, &[]), "<a>\n  1 < 2\n  <b\n");
+    }
+
+    #[test]
+    fn pretty_print_is_linear() {
+        let text = format!("<root>{}</root>", "<item n=\"1\">a < b</item><br>".repeat(100_000));
+        let start = std::time::Instant::now();
+        let pretty = pretty_print(&text, 2, &[]);
+        assert_eq!(pretty.lines().count(), 200_002);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn colorize_tags_attributes_and_comments() {
+        let colors = ColorConfig::new(true, Depth::Ansi16);
+        let paint = |text: &str, color: &str| paint(text, color, &colors);
+        assert_eq!(colorize("<a href=\"x\" hidden>text</a>", &colors), format!(
+            "<{} {}={} {}>text</{}>",
+            paint("a", &colors.highlight), paint("href", &colors.number), paint("\"x\"", &colors.success),
+            paint("hidden", &colors.number), paint("a", &colors.highlight)));
+        assert_eq!(colorize("<!-- c --><![CDATA[x]]>", &colors), format!("{}{}", paint("<!-- c -->", &colors.dim), paint("<![CDATA[x]]>", &colors.filename)));
+        assert_eq!(colorize("<i n=1/>", &colors), format!("<{} {}={}/>", paint("i", &colors.highlight), paint("n", &colors.number), paint("1", &colors.success)));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::util::Scratch;
+
+fn fixture(scratch: &Scratch) {
+    scratch.write("a.txt", "alpha\nbeta\ngamma\n");
+    scratch.write("notes.txt", "# first and last line, and past the end\n1: start\n3: end\n3: really\n7: gone\n");
+    scratch.write("notes.json", "{\"2\": \"middle\", \"3\": [\"one\", \"two\"]}");
+}
+
+#[test]
+fn plain_notes_inline() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--annotations=notes.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, "* alpha  \u{2190} start\n  beta\n* gamma  \u{2190} end; really\n");
+    assert_eq!(run.stderr, "meow: a.txt: annotations past the last line (3): 7\n");
+}
+
+#[test]
+fn json_notes_line_up_with_numbers() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-n", "--annotations=notes.json", "a.txt"]).success();
+    assert_eq!(run.stdout, "       1 | alpha\n*      2 | beta  \u{2190} middle\n*      3 | gamma  \u{2190} one; two\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn block_notes_below_the_line() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-n", "--annotations-block", "--annotations=notes.json", "a.txt"]).success();
+    let indent = " ".repeat(11);
+    assert_eq!(run.stdout, format!("       1 | alpha\n*      2 | beta\n{0}\u{2514} middle\n*      3 | gamma\n{0}\u{2514} one\n{0}\u{2514} two\n", indent));
+}
+
+#[test]
+fn grep_and_annotations_always() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-g", "beta", "--annotations=notes.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, "  beta\n");
+    let run = scratch.run(&["-g", "beta", "--annotations-always", "--annotations=notes.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, "* alpha  \u{2190} start\n  beta\n* gamma  \u{2190} end; really\n");
+}
+
+#[test]
+fn bad_notes_file() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    scratch.write("bad.json", "{\"x\": \"y\"}");
+    let run = scratch.run(&["--annotations=bad.json", "a.txt"]).failure();
+    assert!(run.stderr.contains("line 1: invalid line number 'x'"), "{}", run.stderr);
+    assert_eq!(run.stdout, "");
+}
@@ -0,0 +1,39 @@
+use crate::util::Scratch;
+
+#[test]
+fn art_is_skipped_for_pipes() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run(&["--banner", "--title=Hi", "a.txt"]).success();
+    assert_eq!(run.stdout, "text\n");
+}
+
+#[test]
+fn force_art_prints_the_cat_and_title() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run(&["--banner=loaf", "--title=Hi", "--force-art", "a.txt"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines[..3], ["  /\\_/\\  ", " ( ^.^ ) ", " (\")_(\") "]);
+    assert_eq!(lines[3], "\u{2588}   \u{2588} \u{2588}\u{2588}\u{2588}");
+    assert_eq!(lines[8..], ["", "text"]);
+}
+
+#[test]
+fn art_on_a_terminal_is_colored() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run_tty(&["--banner", "a.txt"]).success();
+    assert!(run.stdout.starts_with("\x1b["), "{:?}", run.stdout);
+    assert!(run.stdout.contains("( o.o )"), "{:?}", run.stdout);
+    let run = scratch.run_tty_command(scratch.command(&["--banner", "a.txt"]).env("NO_COLOR", "1")).success();
+    assert!(run.stdout.starts_with(" /\\_/\\ \n( o.o )\n"), "{:?}", run.stdout);
+}
+
+#[test]
+fn banner_needs_a_known_variant() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--banner=tabby"]);
+    assert!(run.stderr.contains("tabby"), "{}", run.stderr);
+    assert!(run.stderr.contains("sitting, loaf, sleeping"), "{}", run.stderr);
+}
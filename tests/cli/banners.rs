@@ -0,0 +1,52 @@
+use crate::util::Scratch;
+
+#[test]
+fn inline_banners_around_each_input() {
+    let scratch = Scratch::new();
+    scratch.write("a.rs", "fn a() {}\n");
+    scratch.write("b.rs", "fn b() {}\n");
+    let run = scratch.run(&["--prepend=```rust", "--append=```", "a.rs", "b.rs"]).success();
+    // Inside each file's header
+    assert_eq!(run.stdout, "\n===> a.rs <===\n```rust\nfn a() {}\n```\n\n===> b.rs <===\n```rust\nfn b() {}\n```\n");
+}
+
+#[test]
+fn banners_from_files() {
+    let scratch = Scratch::new();
+    scratch.write("head.txt", "BEGIN\n  (generated)\n");
+    scratch.write("foot.txt", "END");
+    scratch.write("a.txt", "content\n");
+    let run = scratch.run(&["--prepend=@head.txt", "--append=@foot.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, "BEGIN\n  (generated)\ncontent\nEND\n");
+}
+
+#[test]
+fn banners_are_not_numbered() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--prepend=top\nmore", "--append=bottom"], "one\ntwo\n").success();
+    assert_eq!(run.stdout, "top\nmore\n     1 | one\n     2 | two\nbottom\n");
+}
+
+#[test]
+fn banners_are_dimmed_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "content\n");
+    let run = scratch.run_tty(&["--prepend=top", "--append=bottom", "a.txt"]).success();
+    assert!(run.stdout.contains("\x1B[2mtop\x1B[0m\n"), "{:?}", run.stdout);
+    assert!(run.stdout.contains("\x1B[2mbottom\x1B[0m\n"), "{:?}", run.stdout);
+}
+
+#[test]
+fn banners_stay_out_of_json() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--json-array", "--prepend=top", "--append=bottom"], "one\n").success();
+    assert!(!run.stdout.contains("top") && !run.stdout.contains("bottom"), "{}", run.stdout);
+}
+
+#[test]
+fn missing_banner_file() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--prepend=@nowhere.txt"], "one\n");
+    assert!(run.stderr.contains("meow: nowhere.txt"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
@@ -0,0 +1,75 @@
+use crate::util::Scratch;
+
+/// A command printing one line that stays and one with the run's number,
+/// failing on the third run so watching stops.
+fn counter(scratch: &Scratch) {
+    scratch.script("counter.sh", "n=$(($(cat count 2>/dev/null || echo 0) + 1))\n\
+                                  echo $n > count\n\
+                                  echo static\n\
+                                  echo \"run $n\"\n\
+                                  [ $n -lt 3 ]\n");
+}
+
+fn watch(scratch: &Scratch, extra: &[&str]) -> String {
+    let mut args = vec!["--exec=./counter.sh", "--watch-interval=50ms", "--exec-stop-on-error", "--bell"];
+    args.extend_from_slice(extra);
+    scratch.run(&args).failure().stderr
+}
+
+#[test]
+fn bell_rings_once_for_new_matching_lines() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    // Run 2 brings a new line; run 3 does too, but within the five seconds
+    // the bell stays quiet for, and watching stops before they're over
+    let stderr = watch(&scratch, &["--grep=run"]);
+    assert_eq!(stderr, "\x07meow: './counter.sh' failed: exit status: 1\n");
+}
+
+#[test]
+fn any_new_line_without_grep() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    assert_eq!(watch(&scratch, &[]), "\x07meow: './counter.sh' failed: exit status: 1\n");
+}
+
+#[test]
+fn no_bell_when_new_lines_dont_match() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    assert_eq!(watch(&scratch, &["--grep=static"]), "meow: './counter.sh' failed: exit status: 1\n");
+}
+
+#[test]
+fn bell_stays_out_of_stdout() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    let run = scratch.run(&["--exec=./counter.sh", "--watch-interval=50ms", "--exec-stop-on-error", "--bell"]).failure();
+    assert!(!run.stdout.contains('\x07'));
+}
+
+#[cfg(not(feature = "notify"))]
+#[test]
+fn notify_needs_the_feature() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--exec=true", "--bell=notify"]);
+    assert!(run.stderr.contains("rebuild with --features notify"), "{}", run.stderr);
+}
+
+#[test]
+fn unknown_bell_mode() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--exec=true", "--bell=loud"]);
+    assert!(run.stderr.contains("invalid --bell mode 'loud' (expected notify)"), "{}", run.stderr);
+}
+
+#[test]
+fn bell_needs_a_watched_command() {
+    let scratch = Scratch::new();
+    scratch.write("f.log", "error\n");
+    for args in [&["--bell", "f.log"][..], &["--bell", "--exec=true"], &["--bell", "--watch-interval=1s", "f.log"]] {
+        let run = scratch.run(args);
+        assert_eq!(run.stderr, "meow: --bell alerts on new lines of a watched command, add --exec and --watch-interval\n", "{:?}", args);
+        assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+    }
+}
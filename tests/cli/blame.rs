@@ -0,0 +1,37 @@
+use crate::util::Scratch;
+
+#[test]
+fn blame_shows_hash_and_initials() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\ntwo\n");
+    let hash = scratch.git(&["rev-parse", "--short=8", "HEAD"]);
+    let run = scratch.run(&["--blame", "a.txt"]).success();
+    assert_eq!(run.stdout, format!("{0} AL one\n{0} AL two\n", hash.trim()));
+}
+
+#[test]
+fn blame_marks_uncommitted_lines() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\n");
+    scratch.write("a.txt", "one\ntwo\n");
+    let run = scratch.run(&["--blame", "a.txt"]).success();
+    let last = run.stdout.lines().last().unwrap();
+    assert!(last.starts_with("00000000 "), "{}", last);
+    assert!(last.ends_with(" two"), "{}", last);
+}
+
+#[test]
+fn blame_outside_a_repository_shows_the_file() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\n");
+    let run = scratch.run(&["--blame", "a.txt"]).success();
+    assert_eq!(run.stdout, "one\n");
+    assert!(run.stderr.contains("git blame failed"), "{}", run.stderr);
+}
+
+#[test]
+fn blame_skips_stdin() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--blame"], "one\n").success();
+    assert_eq!(run.stdout, "one\n");
+}
@@ -0,0 +1,41 @@
+use crate::util::Scratch;
+
+/// An embedded CR, a CRLF line end and a plain one.
+const MIXED: &str = "a\rb\r\nc\n";
+
+#[test]
+fn show_ends_shows_embedded_and_trailing_crs() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-E"], MIXED).success();
+    assert_eq!(run.stdout, "a^Mb^M$\nc$\n");
+}
+
+#[test]
+fn show_nonprinting_shows_crs_without_the_end_marker() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-A"], MIXED).success();
+    assert_eq!(run.stdout, "a^Mb^M\nc\n");
+}
+
+#[test]
+fn crs_follow_the_nonprint_style() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-E", "--nonprint-style=hex"], MIXED).success();
+    assert_eq!(run.stdout, "a\\x0db\\x0d$\nc$\n");
+    let run = scratch.run_stdin(&["-E", "--nonprint-style=unicode"], MIXED).success();
+    assert_eq!(run.stdout, "a\u{240D}b\u{240D}$\nc$\n");
+}
+
+#[test]
+fn crs_with_numbers_and_tabs() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "-ET"], "a\tb\r\n").success();
+    assert_eq!(run.stdout, "     1 | a^Ib^M$\n");
+}
+
+#[test]
+fn plain_output_drops_only_the_crlf_cr() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&[] as &[&str], MIXED).success();
+    assert_eq!(run.stdout, "a\rb\nc\n");
+}
@@ -0,0 +1,47 @@
+use crate::util::Scratch;
+
+#[test]
+fn changes_marks_lines_against_the_index() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\ntwo\nthree\nfour\n");
+    scratch.write("a.txt", "one\nTWO\nthree\nfour\nfive\n");
+    let run = scratch.run(&["--changes", "a.txt"]).success();
+    assert_eq!(run.stdout, "  one\n~ TWO\n  three\n  four\n+ five\n");
+}
+
+#[test]
+fn changes_against_a_revision() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\n");
+    scratch.write("a.txt", "one\ntwo\n");
+    scratch.git(&["commit", "-q", "-am", "second"]);
+    let run = scratch.run(&["--changes=HEAD~1", "a.txt"]).success();
+    assert_eq!(run.stdout, "  one\n+ two\n");
+}
+
+#[test]
+fn changes_marks_deletions_on_the_line_above() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\ntwo\nthree\n");
+    scratch.write("a.txt", "one\nthree\n");
+    let run = scratch.run(&["--changes", "a.txt"]).success();
+    assert_eq!(run.stdout, "_ one\n  three\n");
+}
+
+#[test]
+fn changes_reports_an_unknown_revision() {
+    let scratch = Scratch::new();
+    scratch.git_repo("a.txt", "one\n");
+    let run = scratch.run(&["--changes=no-such-branch", "a.txt"]).success();
+    assert_eq!(run.stdout, "one\n");
+    assert_eq!(run.stderr, "meow: a.txt: unknown revision 'no-such-branch', ignoring --changes\n");
+}
+
+#[test]
+fn changes_outside_a_repository() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\n");
+    let run = scratch.run(&["--changes", "a.txt"]).success();
+    assert_eq!(run.stdout, "one\n");
+    assert_eq!(run.stderr, "meow: a.txt: not in a git repository, ignoring --changes\n");
+}
@@ -0,0 +1,55 @@
+use crate::util::Scratch;
+
+/// Reference digests of "alpha\n", from the coreutils *sum tools.
+const ALPHA_MD5: &str = "9f9f90dbe3e5ee1218c86b8839db1995";
+const ALPHA_SHA1: &str = "d046cd9b7ffb7661e449683313d41f6fc33e3130";
+const ALPHA_SHA256: &str = "b6a98d9ce9a2d9149288fa3df42d377c3e42737afdcdaf714e33c0a100b51060";
+/// Of the empty input, from the BLAKE3 and SHA-512 specifications.
+const EMPTY_BLAKE3: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+const EMPTY_SHA512: &str = "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e";
+
+#[test]
+fn checksum_matches_the_sum_tools() {
+    let scratch = Scratch::new();
+    scratch.write("alpha.txt", "alpha\n");
+    for (algorithm, digest) in [("md5", ALPHA_MD5), ("sha1", ALPHA_SHA1), ("sha256", ALPHA_SHA256)] {
+        let run = scratch.run(&[format!("--checksum={}", algorithm).as_str(), "alpha.txt"]).success();
+        assert_eq!(run.stdout, format!("{}  alpha.txt\n", digest), "{}", algorithm);
+    }
+}
+
+#[test]
+fn checksum_of_the_empty_input() {
+    let scratch = Scratch::new();
+    scratch.write("empty", "");
+    let run = scratch.run(&["--checksum=blake3", "empty"]).success();
+    assert_eq!(run.stdout, format!("{}  empty\n", EMPTY_BLAKE3));
+    let run = scratch.run(&["--checksum=sha512", "empty"]).success();
+    assert_eq!(run.stdout, format!("{}  empty\n", EMPTY_SHA512));
+}
+
+#[test]
+fn checksum_of_stdin_and_several_files() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--checksum=sha256"], "alpha\n").success();
+    assert_eq!(run.stdout, format!("{}  -\n", ALPHA_SHA256));
+    scratch.write("a.txt", "alpha\n");
+    scratch.write("b.txt", "alpha\n");
+    let run = scratch.run(&["--checksum=sha256", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, format!("{0}  a.txt\n{0}  b.txt\n", ALPHA_SHA256));
+}
+
+#[test]
+fn checksum_with_meta_goes_in_the_header() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "alpha\n");
+    let run = scratch.run(&["--checksum=sha256", "--meta", "a.txt"]).success();
+    assert!(run.stdout.contains(&format!("[{}] <===\nalpha\n", ALPHA_SHA256)), "{}", run.stdout);
+}
+
+#[test]
+fn checksum_of_a_missing_file_fails() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--checksum=md5", "missing"]).failure();
+    assert!(run.stderr.starts_with("meow: missing: "), "{}", run.stderr);
+}
@@ -0,0 +1,40 @@
+use crate::util::Scratch;
+
+fn rainbow(scratch: &Scratch, args: &[&str], env: &[(&str, &str)]) -> String {
+    let mut command = scratch.command(args);
+    command.envs(env.iter().copied());
+    scratch.run_tty_command(&mut command).success().stdout
+}
+
+#[test]
+fn rainbow_at_each_depth() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "meow meow\n");
+    let truecolor = rainbow(&scratch, &["-r", "--color-depth=truecolor", "a.txt"], &[]);
+    assert!(truecolor.contains("\x1B[38;2;"), "{:?}", truecolor);
+    let palette = rainbow(&scratch, &["-r", "--color-depth=256", "a.txt"], &[]);
+    assert!(palette.contains("\x1B[38;5;") && !palette.contains("38;2;"), "{:?}", palette);
+    let basic = rainbow(&scratch, &["-r", "--color-depth=16", "a.txt"], &[]);
+    assert!(basic.contains("\x1B[9") && !basic.contains("38;5;") && !basic.contains("38;2;"), "{:?}", basic);
+}
+
+#[test]
+fn depth_is_detected_from_the_environment() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "meow\n");
+    let truecolor = rainbow(&scratch, &["-r", "a.txt"], &[("COLORTERM", "truecolor")]);
+    assert!(truecolor.contains("\x1B[38;2;"), "{:?}", truecolor);
+    let palette = rainbow(&scratch, &["-r", "a.txt"], &[("TERM", "xterm-256color")]);
+    assert!(palette.contains("\x1B[38;5;"), "{:?}", palette);
+    // The option wins
+    let basic = rainbow(&scratch, &["-r", "--color-depth=16", "a.txt"], &[("COLORTERM", "truecolor")]);
+    assert!(!basic.contains("38;2;"), "{:?}", basic);
+}
+
+#[test]
+fn bad_depth() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--color-depth=88"]);
+    assert!(run.stderr.starts_with("meow: invalid"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
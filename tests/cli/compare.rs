@@ -0,0 +1,88 @@
+use crate::util::Scratch;
+
+fn hex_row(offset: &str, hex: &str, text: &str, name: &str) -> String {
+    format!("{}  {:<50}|{:<16}|  {}\n", offset, hex, text, name)
+}
+
+#[test]
+fn identical_files_exit_0() {
+    let scratch = Scratch::new();
+    scratch.write("a", "hello\nworld\n");
+    scratch.write("b", "hello\nworld\n");
+    let run = scratch.run(&["--compare", "a", "b"]);
+    assert_eq!(run.status.code(), Some(0));
+    assert_eq!(run.stdout, "a and b are identical (12 bytes)\n");
+}
+
+#[test]
+fn difference_at_the_first_byte() {
+    let scratch = Scratch::new();
+    scratch.write("a", "hello\nworld\n");
+    scratch.write("c", "Hello\nworld\n");
+    let run = scratch.run(&["--compare", "a", "c"]);
+    assert_eq!(run.status.code(), Some(1));
+    assert_eq!(run.stdout, [
+        "a and c differ\n",
+        "  first difference: byte 0 (0x0), line 1\n",
+        "  differing bytes: 1\n",
+        "\n",
+        &hex_row("00000000", "68 65 6c 6c 6f 0a 77 6f  72 6c 64 0a", "hello.world.", "a"),
+        &hex_row("00000000", "48 65 6c 6c 6f 0a 77 6f  72 6c 64 0a", "Hello.world.", "c"),
+    ].concat());
+}
+
+#[test]
+fn difference_in_the_middle_counts_lines() {
+    let scratch = Scratch::new();
+    scratch.write("a", "hello\nworld\n");
+    scratch.write("d", "hello\nwOrld\n");
+    let run = scratch.run(&["--compare", "a", "d"]);
+    assert_eq!(run.status.code(), Some(1));
+    assert!(run.stdout.starts_with("a and d differ\n  first difference: byte 7 (0x7), line 2\n  differing bytes: 1\n"), "{}", run.stdout);
+}
+
+#[test]
+fn files_differing_only_in_length() {
+    let scratch = Scratch::new();
+    scratch.write("a", "hello\nworld\n");
+    scratch.write("e", "hello\n");
+    let run = scratch.run(&["--compare", "a", "e"]);
+    assert_eq!(run.status.code(), Some(1));
+    assert_eq!(run.stdout, [
+        "a and e differ\n",
+        "  first difference: byte 6 (0x6), line 2\n",
+        "  sizes differ: a has 12 bytes, e has 6\n",
+        "\n",
+        &hex_row("00000000", "68 65 6c 6c 6f 0a 77 6f  72 6c 64 0a", "hello.world.", "a"),
+        &hex_row("00000000", "68 65 6c 6c 6f 0a", "hello.", "e"),
+    ].concat());
+    // Stdin's size isn't known, so it's read to the end
+    let run = scratch.run_stdin(&["--compare", "-", "e"], "hello\nworld\n");
+    assert_eq!(run.status.code(), Some(1));
+    assert!(run.stdout.contains("  differing bytes: 6\n  e ends after 6 bytes\n"), "{}", run.stdout);
+}
+
+#[test]
+fn only_the_first_rows_are_shown() {
+    let scratch = Scratch::new();
+    scratch.write("a", "a".repeat(80));
+    scratch.write("b", "b".repeat(80));
+    let run = scratch.run(&["--compare", "a", "b"]);
+    assert_eq!(run.status.code(), Some(1));
+    assert!(run.stdout.contains("  differing bytes: 80\n"), "{}", run.stdout);
+    assert!(run.stdout.contains("\n00000020  "), "{}", run.stdout);
+    assert!(!run.stdout.contains("\n00000030  "), "{}", run.stdout);
+}
+
+#[test]
+fn trouble_exits_2() {
+    let scratch = Scratch::new();
+    scratch.write("a", "a\n");
+    let run = scratch.run(&["--compare", "a", "missing"]);
+    assert_eq!(run.status.code(), Some(2));
+    assert!(run.stderr.contains("meow: missing: "), "{}", run.stderr);
+    let run = scratch.run(&["--compare", "a"]);
+    assert_eq!(run.status.code(), Some(2));
+    assert!(run.stderr.contains("--compare needs exactly two files"), "{}", run.stderr);
+    assert_eq!(scratch.run(&["--compare", "-", "-"]).status.code(), Some(2));
+}
@@ -0,0 +1,28 @@
+use crate::util::Scratch;
+
+#[test]
+fn numbers_restart_per_file_by_default() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\nb\n");
+    scratch.write("b.txt", "c\n");
+    let run = scratch.run(&["-n", "a.txt", "b.txt"]).success();
+    assert!(run.stdout.contains("     1 | c\n"), "{}", run.stdout);
+}
+
+#[test]
+fn continuous_numbering_carries_on_into_the_next_file() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\nb\n");
+    scratch.write("b.txt", "c\nd\n");
+    let run = scratch.run(&["-n", "--continuous-numbering", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, "\n===> a.txt <===\n     1 | a\n     2 | b\n\n===> b.txt <===\n     3 | c\n     4 | d\n");
+}
+
+#[test]
+fn continuous_numbering_with_number_nonblank() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n\nb\n");
+    scratch.write("b.txt", "\nc\n");
+    let run = scratch.run(&["-b", "--continuous-numbering", "a.txt", "b.txt"]).success();
+    assert!(run.stdout.ends_with("     3 | c\n"), "{}", run.stdout);
+}
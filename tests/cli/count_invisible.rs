@@ -0,0 +1,43 @@
+use crate::util::Scratch;
+
+#[test]
+fn lines_with_invisible_characters_are_reported() {
+    let scratch = Scratch::new();
+    scratch.write("inv.txt", "a\u{A0}b\nclean\nx\u{1}y\u{200B}z\r\n\u{FEFF}head\n");
+    let run = scratch.run(&["--count-invisible", "inv.txt"]).success();
+    // The content is shown as usual
+    assert_eq!(run.stdout, "a\u{A0}b\nclean\nx\u{1}y\u{200B}z\n\u{FEFF}head\n");
+    assert_eq!(run.stderr, concat!(
+        "meow: inv.txt:1: 1 \u{D7} no-break space\n",
+        "meow: inv.txt:3: 1 \u{D7} zero-width character, 1 \u{D7} control character\n",
+        "meow: inv.txt:4: 1 \u{D7} byte order mark\n",
+        "meow: inv.txt: invisible characters on 3 lines: 1 \u{D7} no-break space, 1 \u{D7} byte order mark, ",
+        "1 \u{D7} zero-width character, 1 \u{D7} control character\n",
+    ));
+}
+
+#[test]
+fn no_break_spaces_are_highlighted() {
+    let scratch = Scratch::new();
+    scratch.write("inv.txt", "a\u{A0}\u{A0}b\n");
+    let run = scratch.run_tty(&["--count-invisible", "inv.txt"]).success();
+    assert!(run.stderr.starts_with("meow: inv.txt:1: \x1B[36m2 \u{D7} no-break space\x1B[0m\n"), "{:?}", run.stderr);
+}
+
+#[test]
+fn clean_input_says_so() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--count-invisible"], "plain\ttext\r\n").success();
+    assert_eq!(run.stderr, "meow: stdin: no invisible characters\n");
+}
+
+#[test]
+fn past_twenty_lines_only_a_count() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--count-invisible"], "\u{A0}\n".repeat(25)).success();
+    let lines: Vec<&str> = run.stderr.lines().collect();
+    assert_eq!(lines.len(), 22, "{:?}", run.stderr);
+    assert_eq!(lines[19], "meow: stdin:20: 1 \u{D7} no-break space");
+    assert_eq!(lines[20], "meow: stdin: and 5 more lines");
+    assert_eq!(lines[21], "meow: stdin: invisible characters on 25 lines: 25 \u{D7} no-break space");
+}
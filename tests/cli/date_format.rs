@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+use crate::util::Scratch;
+
+#[test]
+fn date_format_shows_the_mtime_in_the_header() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    scratch.write("b.txt", "b\n");
+    // 2001-09-09 01:46:40 UTC
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    File::options().write(true).open(scratch.path("a.txt")).unwrap().set_modified(mtime).unwrap();
+    let run = scratch.run_command(scratch.command(&["--meta", "--date-format=%Y-%m-%d %H:%M:%S", "a.txt", "b.txt"]).env("TZ", "UTC"), b"").success();
+    assert!(run.stdout.starts_with("\n===> a.txt [2 B] [2001-09-09 01:46:40] <===\na\n"), "{}", run.stdout);
+}
+
+#[test]
+fn meta_time_is_relative_by_default() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    scratch.write("b.txt", "b\n");
+    let run = scratch.run(&["--meta", "a.txt", "b.txt"]).success();
+    assert!(run.stdout.contains("===> a.txt [2 B] [0 mins ago] <==="), "{}", run.stdout);
+}
+
+#[test]
+fn date_format_rejects_bad_specifiers() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--date-format=%Q"]);
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    assert!(run.stderr.contains("%Q"), "{}", run.stderr);
+}
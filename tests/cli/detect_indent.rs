@@ -0,0 +1,26 @@
+use crate::util::Scratch;
+
+#[test]
+fn detect_indent_tabs() {
+    let scratch = Scratch::new();
+    scratch.write("main.c", "int main() {\n\tif (x) {\n\t\treturn 1;\n\t}\n}\n");
+    let run = scratch.run(&["--detect-indent", "main.c"]).success();
+    assert_eq!(run.stderr, "meow: main.c: indentation: tabs\n");
+    assert_eq!(run.stdout, scratch.read("main.c"));
+}
+
+#[test]
+fn detect_indent_two_spaces() {
+    let scratch = Scratch::new();
+    scratch.write("a.yml", "a:\n  b:\n    c: 1\n  d:\n    - e\n");
+    let run = scratch.run(&["--detect-indent", "a.yml"]).success();
+    assert_eq!(run.stderr, "meow: a.yml: indentation: 2 spaces\n");
+}
+
+#[test]
+fn detect_indent_counts_lines_grep_hides() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--detect-indent", "--grep=x"], "x\n    y\n        z\n").success();
+    assert_eq!(run.stdout, "x\n");
+    assert_eq!(run.stderr, "meow: stdin: indentation: 4 spaces\n");
+}
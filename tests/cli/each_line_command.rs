@@ -0,0 +1,70 @@
+use crate::util::Scratch;
+
+const LOG: &str = "ok one\nERROR disk full\nok two\nERROR it's \"quoted\"\n";
+
+#[test]
+fn echo_per_line_with_the_line_substituted() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=ERROR", "--each-line-command=echo got: {}", "--each-line-output"], LOG).success();
+    assert_eq!(run.stdout, "ERROR disk full\ngot: ERROR disk full\nERROR it's \"quoted\"\ngot: ERROR it's \"quoted\"\n");
+}
+
+#[test]
+fn the_line_is_one_argument() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--each-line-command=sh -c 'echo $#' _ {}", "--each-line-output"], "a b  c\n").success();
+    assert_eq!(run.stdout, "a b  c\n1\n");
+    // Inside a word too
+    let run = scratch.run_stdin(&["--each-line-command=echo [{}]", "--each-line-output"], "x\n").success();
+    assert_eq!(run.stdout, "x\n[x]\n");
+}
+
+#[test]
+fn without_placeholder_the_line_goes_to_stdin() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=ERROR", "--each-line-command=tr a-z A-Z", "--each-line-output"], "ERROR low\nfine\n").success();
+    assert_eq!(run.stdout, "ERROR low\nERROR LOW\n");
+}
+
+#[test]
+fn output_is_discarded_by_default() {
+    let scratch = Scratch::new();
+    scratch.script("save.sh", "echo \"$1\" >> seen.txt\necho noise\n");
+    let run = scratch.run_stdin(&["--grep=ERROR", "--each-line-command=./save.sh {}"], LOG).success();
+    assert_eq!(run.stdout, "ERROR disk full\nERROR it's \"quoted\"\n");
+    let mut seen: Vec<String> = scratch.read("seen.txt").lines().map(str::to_string).collect();
+    seen.sort();
+    assert_eq!(seen, ["ERROR disk full", "ERROR it's \"quoted\""]);
+}
+
+#[test]
+fn jobs_cap_how_many_run_at_once() {
+    let scratch = Scratch::new();
+    scratch.script("job.sh", "echo start >> jobs.txt\nsleep 0.05\necho end >> jobs.txt\n");
+    let lines = "x\n".repeat(4);
+    scratch.run_stdin(&["--each-line-command=./job.sh {}", "--each-line-jobs=1"], &lines).success();
+    assert_eq!(scratch.read("jobs.txt"), "start\nend\n".repeat(4));
+}
+
+#[test]
+fn failures_are_counted() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--each-line-command=grep -q disk"], LOG).success();
+    assert_eq!(run.stderr, "meow: stdin: 'grep -q disk' failed for 3 lines\n");
+}
+
+#[test]
+fn missing_command_reported_once() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--each-line-command=./missing {}"], LOG).success();
+    assert_eq!(run.stdout, LOG);
+    assert_eq!(run.stderr.lines().count(), 1, "{}", run.stderr);
+    assert!(run.stderr.starts_with("meow: cannot run './missing {}'"), "{}", run.stderr);
+}
+
+#[test]
+fn help_points_at_exec() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--help"]).success();
+    assert!(run.stdout.contains("(--exec is the one that shows a command's output)"), "{}", run.stdout);
+}
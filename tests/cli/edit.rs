@@ -0,0 +1,58 @@
+use crate::util::Scratch;
+
+/// A stand-in editor that writes the arguments it gets to `args.txt`.
+fn fake_editor(scratch: &Scratch) -> String {
+    let path = scratch.script("editor.sh", "printf '%s\\n' \"$@\" > args.txt\n");
+    path.to_string_lossy().into_owned()
+}
+
+fn files(scratch: &Scratch) {
+    scratch.write("a.txt", "one\ntwo needle\n");
+    scratch.write("b.txt", "needle\n");
+}
+
+#[test]
+fn edit_opens_the_first_match() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let editor = fake_editor(&scratch);
+    scratch.run_tty_command(scratch.command(&["--edit", "--grep=needle", "a.txt", "b.txt"]).env("EDITOR", &editor)).success();
+    assert_eq!(scratch.read("args.txt"), "+2\na.txt\n");
+}
+
+#[test]
+fn edit_all_opens_every_matching_file() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let editor = fake_editor(&scratch);
+    scratch.run_tty_command(scratch.command(&["--edit-all", "--grep=needle", "a.txt", "b.txt"]).env("EDITOR", &editor)).success();
+    assert_eq!(scratch.read("args.txt"), "+2\na.txt\n+1\nb.txt\n");
+}
+
+#[test]
+fn edit_cmd_code_uses_goto() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let editor = fake_editor(&scratch);
+    scratch.run_tty_command(scratch.command(&["--edit", "--edit-cmd=code", "--grep=needle", "a.txt"]).env("VISUAL", &editor)).success();
+    assert_eq!(scratch.read("args.txt"), "--goto\na.txt:2\n");
+}
+
+#[test]
+fn edit_cmd_template_names_its_own_program() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let editor = fake_editor(&scratch);
+    scratch.run_tty(&["--edit", &format!("--edit-cmd={} {{file}}:{{line}}", editor), "b.txt"]).success();
+    assert_eq!(scratch.read("args.txt"), "b.txt:1\n");
+}
+
+#[test]
+fn edit_does_nothing_off_a_terminal() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let editor = fake_editor(&scratch);
+    let run = scratch.run_command(scratch.command(&["--edit", "a.txt"]).env("EDITOR", &editor), b"").success();
+    assert_eq!(run.stdout, "one\ntwo needle\n");
+    assert!(!scratch.path("args.txt").exists());
+}
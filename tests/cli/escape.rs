@@ -0,0 +1,26 @@
+use crate::util::Scratch;
+
+#[test]
+fn escape_shell_output_reads_back_in_sh() {
+    let scratch = Scratch::new();
+    let text = "it's a \"test\" with $HOME and `ticks`\n";
+    scratch.write("a.txt", text);
+    let run = scratch.run(&["--escape=shell", "a.txt"]).success();
+    let output = std::process::Command::new("sh").arg("-c").arg(format!("printf '%s\\n' {}", run.stdout.trim_end())).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), text);
+}
+
+#[test]
+fn escape_json_lines_are_json_strings() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--escape=json"], "say \"hi\"\tnow\n\n").success();
+    assert_eq!(run.stdout, "\"say \\\"hi\\\"\\tnow\"\n\"\"\n");
+}
+
+#[test]
+fn escape_needs_a_known_mode() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--escape=perl"]);
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    assert!(run.stderr.contains("perl"), "{}", run.stderr);
+}
@@ -0,0 +1,44 @@
+use crate::util::Scratch;
+
+fn fixture(scratch: &Scratch) {
+    scratch.write("proj/src/main.rs", "main\n");
+    scratch.write("proj/src/lib.rs", "lib\n");
+    scratch.write("proj/Cargo.lock", "lock\n");
+    scratch.write("proj/.git/HEAD", "head\n");
+    scratch.write("proj/target/debug/out.rs", "built\n");
+    scratch.write("proj/notes.txt", "notes\n");
+}
+
+#[test]
+fn exclude_skips_a_subdirectory() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--with-filename", "--flatten", "--exclude=.git", "--exclude=target", "--exclude=*.lock", "proj"]).success();
+    assert_eq!(run.stdout, "proj/notes.txt:notes\nproj/src/lib.rs:lib\nproj/src/main.rs:main\n");
+}
+
+#[test]
+fn patterns_with_a_slash_match_the_path() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--flatten", "--exclude=proj/src/*", "--exclude=.git", "proj"]).success();
+    assert_eq!(run.stdout, "lock\nnotes\nbuilt\n");
+}
+
+#[test]
+fn include_picks_files_but_exclude_wins() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--flatten", "--include=*.rs", "proj"]).success();
+    assert_eq!(run.stdout, "lib\nmain\nbuilt\n");
+    let run = scratch.run(&["-R", "--flatten", "--include=*.rs", "--exclude=target", "proj"]).success();
+    assert_eq!(run.stdout, "lib\nmain\n");
+}
+
+#[test]
+fn files_named_on_the_command_line_are_not_filtered() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--flatten", "--exclude=*.lock", "proj/Cargo.lock"]).success();
+    assert_eq!(run.stdout, "lock\n");
+}
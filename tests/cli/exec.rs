@@ -0,0 +1,103 @@
+use std::os::unix::process::ExitStatusExt;
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::util::Scratch;
+
+const CYAN: &str = "\x1B[36m";
+const RESET: &str = "\x1B[0m";
+
+/// A command whose output changes on every run: one line that stays, one
+/// with the run's number. It fails from the third run on.
+fn counter(scratch: &Scratch) {
+    scratch.script("counter.sh", "n=$(($(cat count 2>/dev/null || echo 0) + 1))\n\
+                                  echo $n > count\n\
+                                  echo static\n\
+                                  echo \"run $n\"\n\
+                                  echo \"err $n\" >&2\n\
+                                  [ $n -lt 3 ]\n");
+}
+
+/// The output without the status lines, which have the time in them.
+fn without_status(stdout: &str) -> String {
+    stdout.lines().filter(|line| !line.contains("Every 50ms: ./counter.sh")).map(|line| format!("{}\n", line)).collect()
+}
+
+#[test]
+fn exec_shows_the_commands_output() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    let run = scratch.run(&["-n", "--exec=./counter.sh"]).success();
+    assert_eq!(run.stdout, "     1 | static\n     2 | run 1\n");
+    assert_eq!(run.stderr, "err 1\n");
+}
+
+#[test]
+fn exec_splits_words_or_uses_the_shell() {
+    let scratch = Scratch::new();
+    assert_eq!(scratch.run(&["--exec=echo 'a  b' \"c\" d\\ e"]).success().stdout, "a  b c d e\n");
+    assert_eq!(scratch.run(&["--exec-shell", "--exec=echo a | tr a b"]).success().stdout, "b\n");
+    assert_eq!(scratch.run(&["--exec=echo a | tr a b"]).success().stdout, "a | tr a b\n");
+}
+
+#[test]
+fn exec_fails_with_the_command() {
+    let scratch = Scratch::new();
+    scratch.write("count", "2\n");
+    counter(&scratch);
+    let run = scratch.run(&["--exec=./counter.sh"]).failure();
+    assert_eq!(run.stdout, "static\nrun 3\n");
+    assert_eq!(run.stderr, "err 3\nmeow: './counter.sh' failed: exit status: 1\n");
+    let run = scratch.run(&["--exec=echo 'open"]).failure();
+    assert_eq!(run.stderr, "meow: cannot parse the command 'echo 'open' (unbalanced quotes?)\n");
+    let run = scratch.run(&["--exec=./missing"]).failure();
+    assert!(run.stderr.starts_with("meow: cannot run './missing': "), "{}", run.stderr);
+}
+
+#[test]
+fn watch_reruns_until_the_command_fails() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    let run = scratch.run(&["--exec=./counter.sh", "--watch-interval=50ms", "--exec-stop-on-error"]).failure();
+    assert_eq!(without_status(&run.stdout), "\nstatic\nrun 1\n\nstatic\nrun 2\n\nstatic\nrun 3\n");
+    // The failed run's status line says so
+    let last_status = run.stdout.lines().rfind(|line| line.starts_with("Every 50ms: ./counter.sh")).unwrap();
+    assert!(last_status.ends_with("[exit status: 1]"), "{}", last_status);
+    assert_eq!(run.stderr, "err 1\nerr 2\nerr 3\nmeow: './counter.sh' failed: exit status: 1\n");
+}
+
+#[test]
+fn watch_highlights_the_changed_lines() {
+    let scratch = Scratch::new();
+    counter(&scratch);
+    let run = scratch.run_tty(&["--exec=./counter.sh", "--watch-interval=50ms", "--exec-stop-on-error", "--diff-changes"]).failure();
+    let runs: Vec<String> = run.stdout.split("\x1B[2J\x1B[H").skip(1).map(|screen| screen.split_once("\n\n").unwrap().1.to_string()).collect();
+    assert_eq!(runs, [
+        "static\nrun 1\n".to_string(),
+        format!("static\n{CYAN}run 2{RESET}\n"),
+        format!("static\n{CYAN}run 3{RESET}\n"),
+    ]);
+}
+
+#[test]
+fn interrupt_stops_watching() {
+    let scratch = Scratch::new();
+    let mut child = scratch.command(&["--exec=echo hi", "--watch-interval=50ms"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(200));
+    // SAFETY: kill only sends a signal to the child we started
+    assert_eq!(unsafe { libc::kill(child.id() as i32, libc::SIGINT) }, 0);
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5), "meow kept watching");
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(status.signal(), Some(libc::SIGINT));
+}
@@ -0,0 +1,51 @@
+use crate::util::Scratch;
+
+const TEMPLATE: &str = "url=postgres://${DB_HOST}:$DB_PORT/app\nkey=$MISSING_KEY\nliteral=\\$DB_HOST\n";
+
+fn expand(scratch: &Scratch, args: &[&str], input: &str) -> crate::util::Run {
+    let mut command = scratch.command(args);
+    command.env("DB_HOST", "db.local").env("DB_PORT", "5432");
+    scratch.run_command(&mut command, input.as_bytes())
+}
+
+#[test]
+fn expand_env_fills_in_defined_variables() {
+    let scratch = Scratch::new();
+    let run = expand(&scratch, &["--expand-env"], TEMPLATE).success();
+    assert_eq!(run.stdout, "url=postgres://db.local:5432/app\nkey=$MISSING_KEY\nliteral=\\$DB_HOST\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn expand_env_strict_fails_on_undefined_variables() {
+    let scratch = Scratch::new();
+    let run = expand(&scratch, &["--expand-env=strict"], TEMPLATE).failure();
+    assert_eq!(run.stdout, "url=postgres://db.local:5432/app\nkey=$MISSING_KEY\nliteral=\\$DB_HOST\n");
+    assert_eq!(run.stderr, "meow: stdin: undefined variables: $MISSING_KEY\n");
+}
+
+#[test]
+fn expand_env_happens_before_grep() {
+    let scratch = Scratch::new();
+    let run = expand(&scratch, &["--expand-env", "--grep=5432"], TEMPLATE).success();
+    assert_eq!(run.stdout, "url=postgres://db.local:5432/app\n");
+}
+
+#[test]
+fn env_file_adds_variables() {
+    let scratch = Scratch::new();
+    scratch.write(".env", "# local\nexport MISSING_KEY=\"s3cret\"\n");
+    let run = expand(&scratch, &["--expand-env=strict", "--env-file=.env"], "key=$MISSING_KEY\n").success();
+    assert_eq!(run.stdout, "key=s3cret\n");
+}
+
+#[test]
+fn expand_env_colors_the_spans() {
+    let scratch = Scratch::new();
+    let mut command = scratch.command(&["--expand-env=strict"]);
+    command.env("DB_HOST", "db.local");
+    scratch.write("t.conf", "$DB_HOST $NOPE\n");
+    command.arg("t.conf");
+    let run = scratch.run_tty_command(&mut command).failure();
+    assert_eq!(run.stdout, "\x1b[36mdb.local\x1b[0m \x1b[31m$NOPE\x1b[0m\n");
+}
@@ -0,0 +1,61 @@
+use crate::util::Scratch;
+
+const CYAN: &str = "\x1B[36m";
+const RESET: &str = "\x1B[0m";
+
+const CSV: &str = "name,city,note\n\"Lovelace, Ada\",London,\"said \"\"hi\"\", left\"\nBabbage,London\n";
+
+#[test]
+fn fields_from_whitespace_delimited_lines() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fields=2-3"], "one  two\tthree four\nsolo\n").success();
+    assert_eq!(run.stdout, "two three\n\n");
+}
+
+#[test]
+fn fields_with_a_delimiter_and_output_delimiter() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fields=1,3-", "--delimiter=:"], "root:x:0:0:/root\n").success();
+    assert_eq!(run.stdout, "root:0:0:/root\n");
+    let run = scratch.run_stdin(&["--fields=1,3-", "--delimiter=:", "--output-delimiter=\t"], "root:x:0:0:/root\n").success();
+    assert_eq!(run.stdout, "root\t0\t0\t/root\n");
+}
+
+#[test]
+fn fields_invert_gives_the_rest() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fields=2", "--fields-invert", "--delimiter=,"], "a,b,c\n").success();
+    assert_eq!(run.stdout, "a,c\n");
+}
+
+#[test]
+fn csv_keeps_quoted_commas_in_one_field() {
+    let scratch = Scratch::new();
+    scratch.write("people.csv", CSV);
+    let run = scratch.run(&["--csv", "--fields=1,3", "people.csv"]).success();
+    assert_eq!(run.stdout, "name,note\n\"Lovelace, Ada\",\"said \"\"hi\"\", left\"\nBabbage\n");
+}
+
+#[test]
+fn grep_looks_at_the_whole_line() {
+    let scratch = Scratch::new();
+    scratch.write("people.csv", CSV);
+    // "left" is only in the note, which isn't shown
+    let run = scratch.run(&["--csv", "--fields=1", "--grep=left", "people.csv"]).success();
+    assert_eq!(run.stdout, "\"Lovelace, Ada\"\n");
+}
+
+#[test]
+fn highlight_applies_to_the_fields_shown() {
+    let scratch = Scratch::new();
+    scratch.write("a.csv", "London,London Bridge\n");
+    let run = scratch.run_tty(&["--csv", "--fields=2", "--highlight=London", "a.csv"]).success();
+    assert_eq!(run.stdout, format!("{CYAN}London{RESET} Bridge\n"));
+}
+
+#[test]
+fn invalid_field_list_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fields=0"], "a\n");
+    assert!(run.stderr.starts_with("meow: invalid field list '0'"), "{}", run.stderr);
+}
@@ -0,0 +1,42 @@
+use crate::util::Scratch;
+
+/// The escape sequence right before each `foo` in `output`.
+fn colors_before_foo(output: &str) -> Vec<&str> {
+    output.match_indices("foo").map(|(at, _)| {
+        let start = output[..at].rfind('\x1b').unwrap();
+        &output[start..at]
+    }).collect()
+}
+
+#[test]
+fn file_colors_give_each_file_its_own_highlight() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo 1\n");
+    scratch.write("b.txt", "foo 2\n");
+    let run = scratch.run_tty(&["--file-colors", "--with-filename", "--grep=foo", "--highlight=foo", "a.txt", "b.txt"]).success();
+    let colors = colors_before_foo(&run.stdout);
+    assert_eq!(colors.len(), 2, "{:?}", run.stdout);
+    assert_ne!(colors[0], colors[1], "{:?}", run.stdout);
+    // The file name prefix is in its file's color too
+    assert!(run.stdout.contains(&format!("{}a.txt\x1b[0m:", colors[0])), "{:?}", run.stdout);
+    assert!(run.stdout.contains(&format!("{}b.txt\x1b[0m:", colors[1])), "{:?}", run.stdout);
+}
+
+#[test]
+fn highlight_is_shared_without_file_colors() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo 1\n");
+    scratch.write("b.txt", "foo 2\n");
+    let run = scratch.run_tty(&["--highlight=foo", "a.txt", "b.txt"]).success();
+    let colors = colors_before_foo(&run.stdout);
+    assert_eq!(colors.len(), 2, "{:?}", run.stdout);
+    assert_eq!(colors[0], colors[1]);
+}
+
+#[test]
+fn with_filename_prefixes_each_line() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\ntwo\n");
+    let run = scratch.run(&["--with-filename", "a.txt"]).success();
+    assert_eq!(run.stdout, "a.txt:one\na.txt:two\n");
+}
@@ -0,0 +1,40 @@
+use crate::util::Scratch;
+
+#[test]
+fn filter_pipes_the_file_through_the_command() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "b\na\n");
+    let run = scratch.run(&["--filter=sort", "a.txt"]).success();
+    assert_eq!(run.stdout, "a\nb\n");
+}
+
+#[test]
+fn filter_output_gets_the_display_options() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "b\na\n");
+    let run = scratch.run(&["--filter=tr a-z A-Z", "-n", "a.txt"]).success();
+    assert_eq!(run.stdout, "     1 | B\n     2 | A\n");
+}
+
+#[test]
+fn filter_braces_stand_for_the_path() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "four\n");
+    let run = scratch.run(&["--filter=wc -c < {}", "a.txt"]).success();
+    assert_eq!(run.stdout.trim(), "5");
+}
+
+#[test]
+fn filter_works_on_stdin() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--filter=tr a-z A-Z"], "hi\n").success();
+    assert_eq!(run.stdout, "HI\n");
+}
+
+#[test]
+fn failing_filter_is_an_error() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "x\n");
+    let run = scratch.run(&["--filter=exit 3", "a.txt"]).failure();
+    assert!(run.stderr.contains("a.txt: filter 'exit 3' failed (exit status: 3)"), "{}", run.stderr);
+}
@@ -0,0 +1,43 @@
+use crate::util::Scratch;
+
+const LINES: &str = "a\nb\nA\na\n\n\nb\na \nc\n";
+
+#[test]
+fn find_dupes_reports_the_most_frequent_first() {
+    let scratch = Scratch::new();
+    scratch.write("d.txt", "b\na\na\nb\na\nc\n\n\n");
+    let run = scratch.run(&["--find-dupes", "d.txt"]).success();
+    assert_eq!(run.stdout, "b\na\na\nb\na\nc\n\n\n");
+    assert_eq!(run.stderr, "meow: d.txt: 2 duplicated lines\n     3x  a  (lines 2, 3, 5)\n     2x  b  (lines 1, 4)\n");
+}
+
+#[test]
+fn find_dupes_with_normalization() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--find-dupes"], LINES).success();
+    assert_eq!(run.stderr, "meow: stdin: 2 duplicated lines\n     2x  a  (lines 1, 4)\n     2x  b  (lines 2, 7)\n");
+    let run = scratch.run_stdin(&["--find-dupes", "--dupes-ignore-case", "--dupes-ignore-whitespace"], LINES).success();
+    assert_eq!(run.stderr, "meow: stdin: 2 duplicated lines\n     4x  a  (lines 1, 3, 4, 8)\n     2x  b  (lines 2, 7)\n");
+}
+
+#[test]
+fn find_dupes_lists_ten_lines_at_most() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--find-dupes"], "same\n".repeat(12)).success();
+    assert_eq!(run.stderr, "meow: stdin: 1 duplicated lines\n    12x  same  (lines 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, ...)\n");
+}
+
+#[test]
+fn find_dupes_without_any() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--find-dupes"], "x\ny\n\n\n").success();
+    assert_eq!(run.stderr, "meow: stdin: no duplicate lines\n");
+}
+
+#[test]
+fn mark_dupes_marks_later_occurrences() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--mark-dupes"], LINES).success();
+    assert_eq!(run.stdout, "  a\n  b\n  A\n= a\n  \n  \n= b\n  a \n  c\n");
+    assert_eq!(run.stderr, "");
+}
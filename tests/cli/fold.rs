@@ -0,0 +1,28 @@
+use crate::util::Scratch;
+
+const PROSE: &str = "It was a dark and stormy night; the rain fell in torrents, except at occasional intervals.\n";
+
+#[test]
+fn fold_at_word_boundaries() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fold=30"], PROSE).success();
+    assert_eq!(run.stdout, "It was a dark and stormy\nnight; the rain fell in\ntorrents, except at occasional\nintervals.\n");
+    assert!(run.stdout.lines().all(|line| line.chars().count() <= 30));
+}
+
+#[test]
+fn fold_indents_continuations() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fold=30", "--fold-indent=4"], PROSE).success();
+    assert_eq!(run.stdout, "It was a dark and stormy\n    night; the rain fell in\n    torrents, except at\n    occasional intervals.\n");
+}
+
+#[test]
+fn fold_with_line_numbers() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--fold=12"], "one two three four\n").success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("one two"), "{:?}", lines);
+    assert!(lines[1].ends_with("three four"), "{:?}", lines);
+}
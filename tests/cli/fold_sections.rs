@@ -0,0 +1,53 @@
+use crate::util::Scratch;
+
+const DOC: &str = "intro\n## one\na\nb\n## two\n## three\nc\n";
+
+/// Each screen the viewer drew, from clearing it to the next clear.
+fn screens(output: &str) -> Vec<&str> {
+    output.split("\x1B[H\x1B[2J").skip(1).collect()
+}
+
+#[test]
+fn folded_headers_off_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("doc.md", DOC);
+    let run = scratch.run(&["--fold-sections=^##", "doc.md"]).success();
+    assert_eq!(run.stdout, "intro\n## one (+ 2 lines)\n## two\n## three (+ 1 lines)\n");
+    let run = scratch.run(&["-n", "--fold-sections=^##", "doc.md"]).success();
+    assert_eq!(run.stdout, "     1 | intro\n     2 | ## one (+ 2 lines)\n     5 | ## two\n     6 | ## three (+ 1 lines)\n");
+}
+
+#[test]
+fn enter_opens_and_closes_a_section() {
+    let scratch = Scratch::new();
+    scratch.write("doc.md", DOC);
+    let keys = [("line 1/7", "j"), ("line 2/7", "\r"), ("\u{25BE} ", "\r"), ("\u{25B8} ## one", "q")];
+    let run = scratch.run_tty_keys(&["-C", "--fold-sections=^##", "doc.md"], &keys).success();
+    let screens = screens(&run.stdout);
+    assert_eq!(screens.len(), 4, "{:?}", run.stdout);
+    assert!(screens[1].starts_with("  intro\n\u{25B8} ## one (+ 2 lines)\n  ## two\n\u{25B8} ## three (+ 1 lines)"), "{:?}", screens[1]);
+    assert!(screens[2].starts_with("  intro\n\u{25BE} ## one\n  a\n  b\n  ## two\n"), "{:?}", screens[2]);
+    assert!(!screens[3].contains("\n  a\n"), "{:?}", screens[3]);
+    assert!(run.stdout.ends_with("\x1B[?1049l"), "{:?}", run.stdout);
+}
+
+#[test]
+fn a_opens_all_and_c_closes_all() {
+    let scratch = Scratch::new();
+    scratch.write("doc.md", DOC);
+    let keys = [("line 1/7", "a"), ("\u{25BE} ## three", "c"), ("\u{25B8} ## three", "q")];
+    let run = scratch.run_tty_keys(&["-C", "--fold-sections=^##", "doc.md"], &keys).success();
+    let screens = screens(&run.stdout);
+    assert!(screens[1].starts_with("  intro\n\u{25BE} ## one\n  a\n  b\n  ## two\n\u{25BE} ## three\n  c"), "{:?}", screens[1]);
+    assert!(screens[2].starts_with("  intro\n\u{25B8} ## one (+ 2 lines)\n"), "{:?}", screens[2]);
+}
+
+#[test]
+fn search_opens_the_section_it_lands_in() {
+    let scratch = Scratch::new();
+    scratch.write("doc.md", DOC);
+    let keys = [("line 1/7", "/"), ("/", "c\r"), ("line 7/7", "q")];
+    let run = scratch.run_tty_keys(&["-C", "--fold-sections=^##", "doc.md"], &keys).success();
+    let last = *screens(&run.stdout).last().unwrap();
+    assert!(last.contains("\u{25BE} ## three\n  c"), "{:?}", last);
+}
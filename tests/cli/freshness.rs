@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+use crate::util::Scratch;
+
+const HOUR: u64 = 60 * 60;
+
+/// `name` with `text`, last modified `secs` ago.
+fn aged(scratch: &Scratch, name: &str, text: &str, secs: u64) {
+    scratch.write(name, text);
+    let mtime = SystemTime::now() - Duration::from_secs(secs);
+    File::options().write(true).open(scratch.path(name)).unwrap().set_modified(mtime).unwrap();
+}
+
+#[test]
+fn headers_are_colored_by_age() {
+    let scratch = Scratch::new();
+    aged(&scratch, "new", "a\n", 5 * 60);
+    aged(&scratch, "mid", "b\n", 3 * HOUR);
+    aged(&scratch, "old", "c\n", 3 * 24 * HOUR);
+    let run = scratch.run_tty(&["--freshness", "new", "mid", "old"]).success();
+    assert_eq!(run.stdout, concat!(
+        "\n===> \x1B[35mnew\x1B[0m [\x1B[32m5 mins ago\x1B[0m] <===\na\n",
+        "\n===> \x1B[35mmid\x1B[0m [\x1B[33m3 hours ago\x1B[0m] <===\nb\n",
+        "\n===> \x1B[35mold\x1B[0m [\x1B[31m3 days ago\x1B[0m] <===\nc\n",
+    ));
+}
+
+#[test]
+fn thresholds_can_be_given() {
+    let scratch = Scratch::new();
+    aged(&scratch, "mid", "b\n", 3 * HOUR);
+    aged(&scratch, "old", "c\n", 3 * 24 * HOUR);
+    let run = scratch.run_tty(&["--freshness=1m,2h", "mid", "old"]).success();
+    assert!(run.stdout.contains("[\x1B[31m3 hours ago\x1B[0m]"), "{:?}", run.stdout);
+    let run = scratch.run_tty(&["--freshness=4h,1w", "mid", "old"]).success();
+    assert!(run.stdout.contains("[\x1B[32m3 hours ago\x1B[0m]"), "{:?}", run.stdout);
+    assert!(run.stdout.contains("[\x1B[33m3 days ago\x1B[0m]"), "{:?}", run.stdout);
+}
+
+#[test]
+fn meta_times_are_colored_too() {
+    let scratch = Scratch::new();
+    aged(&scratch, "old", "c\n", 3 * 24 * HOUR);
+    aged(&scratch, "new", "a\n", 5 * 60);
+    let run = scratch.run_tty(&["--meta", "--freshness", "old", "new"]).success();
+    assert!(run.stdout.contains("old\x1B[0m [2 B] [\x1B[31m3 days ago\x1B[0m]"), "{:?}", run.stdout);
+}
+
+#[test]
+fn no_colors_leave_the_plain_time() {
+    let scratch = Scratch::new();
+    aged(&scratch, "old", "c\n", 3 * 24 * HOUR);
+    aged(&scratch, "new", "a\n", 5 * 60);
+    let run = scratch.run(&["--freshness", "old", "new"]).success();
+    assert_eq!(run.stdout, "\n===> old [3 days ago] <===\nc\n\n===> new [5 mins ago] <===\na\n");
+}
+
+#[test]
+fn thresholds_must_increase() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--freshness=2h,1h"], "a\n");
+    assert!(run.stderr.contains("meow: invalid --freshness '2h,1h' (expected two increasing spans, e.g. 1h,1d)"), "{}", run.stderr);
+    // The usage instead of the input
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
+
+#[test]
+fn age_warnings() {
+    let scratch = Scratch::new();
+    aged(&scratch, "old", "c\n", 3 * 24 * HOUR);
+    aged(&scratch, "new", "a\n", 5 * 60);
+    let run = scratch.run(&["--warn-older-than=1d", "old", "new"]).success();
+    assert_eq!(run.stderr, "meow: WARNING: old was last modified 3 days ago, more than 1d ago\n");
+    let run = scratch.run(&["--warn-newer-than=1h", "old", "new"]).success();
+    assert_eq!(run.stderr, "meow: WARNING: new was modified 5 mins ago, less than 1h ago; it may still be being written\n");
+}
@@ -0,0 +1,41 @@
+use crate::util::Scratch;
+
+const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+/// The text of each colored piece of a `--rainbow` line, without its color.
+fn pieces(line: &str) -> Vec<&str> {
+    line.split("\x1B[0m").filter(|piece| !piece.is_empty()).map(|piece| &piece[piece.find('m').unwrap() + 1..]).collect()
+}
+
+#[test]
+fn length_counts_clusters_and_columns() {
+    let scratch = Scratch::new();
+    let input = format!("plain\ncafe\u{301}\n{} ok\n\u{1112}\u{1161}\u{11AB}\u{AD6D}\n中文\n", FAMILY);
+    let run = scratch.run_stdin(&["-l"], input).success();
+    assert_eq!(run.stdout, format!(
+        "plain [1L, 5C]\ncafe\u{301} [1L, 4C]\n{} ok [1L, 4C, 5W]\n\u{1112}\u{1161}\u{11AB}\u{AD6D} [1L, 2C, 4W]\n中文 [1L, 2C, 4W]\n",
+        FAMILY));
+}
+
+#[test]
+fn rainbow_colors_whole_clusters() {
+    let scratch = Scratch::new();
+    let line = format!("a{}e\u{301}\u{1F1EB}\u{1F1F7}\u{2764}\u{FE0F}한字", FAMILY);
+    scratch.write("a.txt", format!("{}\n", line));
+    let run = scratch.run_tty(&["-r", "--color-depth=16", "a.txt"]).success();
+    let shown = run.stdout.strip_suffix('\n').unwrap();
+    let pieces = pieces(shown);
+    assert_eq!(pieces, ["a", FAMILY, "e\u{301}", "\u{1F1EB}\u{1F1F7}", "\u{2764}\u{FE0F}", "한", "字"]);
+    assert_eq!(pieces.concat(), line);
+}
+
+#[test]
+fn folding_and_cutting_count_columns() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--fold=6"], format!("{0}{0} {0}中文\n", FAMILY)).success();
+    assert_eq!(run.stdout, format!("{0}{0}\n{0}中文\n", FAMILY));
+    let run = scratch.run_stdin(&["--truncate-middle=9"], "中文字のテキスト\n").success();
+    assert_eq!(run.stdout, "中文\u{2026}スト\n");
+    let run = scratch.run_stdin(&["--fold=3"], "e\u{301}e\u{301}e\u{301}e\u{301}\n").success();
+    assert_eq!(run.stdout, "e\u{301}e\u{301}e\u{301}\ne\u{301}\n");
+}
@@ -0,0 +1,52 @@
+use crate::util::Scratch;
+
+const LOG: &str = "INFO start\nERROR disk full\nWARN slow (3s)\nINFO a.b\nFATAL crash\nINFO axb\n";
+
+#[test]
+fn lines_with_any_pattern_are_shown() {
+    let scratch = Scratch::new();
+    scratch.write("patterns", "ERROR\nFATAL\na.b\n");
+    let run = scratch.run_stdin(&["--grep-file=patterns"], LOG).success();
+    // Taken literally, so a.b doesn't match axb
+    assert_eq!(run.stdout, "ERROR disk full\nINFO a.b\nFATAL crash\n");
+    let run = scratch.run_stdin(&["--grep-file=patterns", "-n"], LOG).success();
+    assert_eq!(run.stdout, "     1 | ERROR disk full\n     2 | INFO a.b\n     3 | FATAL crash\n");
+}
+
+#[test]
+fn regex_characters_are_literal() {
+    let scratch = Scratch::new();
+    scratch.write("patterns", "(3s)\n");
+    assert_eq!(scratch.run_stdin(&["--grep-file=patterns"], LOG).success().stdout, "WARN slow (3s)\n");
+}
+
+#[test]
+fn comments_are_skipped_when_asked() {
+    let scratch = Scratch::new();
+    scratch.write("patterns", "# INFO\nFATAL\n\n");
+    let input = "# INFO lines\nFATAL crash\nINFO start\n";
+    assert_eq!(scratch.run_stdin(&["--grep-file=patterns"], input).success().stdout, "# INFO lines\nFATAL crash\n");
+    assert_eq!(scratch.run_stdin(&["--grep-file=patterns", "--grep-file-comments"], input).success().stdout, "FATAL crash\n");
+}
+
+#[test]
+fn no_patterns_select_nothing() {
+    let scratch = Scratch::new();
+    scratch.write("patterns", "\n");
+    assert_eq!(scratch.run_stdin(&["--grep-file=patterns"], LOG).success().stdout, "");
+}
+
+#[test]
+fn grep_narrows_it_further() {
+    let scratch = Scratch::new();
+    scratch.write("patterns", "ERROR\nFATAL\na.b\n");
+    assert_eq!(scratch.run_stdin(&["--grep-file=patterns", "--grep=INFO"], LOG).success().stdout, "INFO a.b\n");
+}
+
+#[test]
+fn missing_patterns_file() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep-file=nope"], LOG).failure();
+    assert_eq!(run.stdout, "");
+    assert!(run.stderr.starts_with("meow: nope: "), "{}", run.stderr);
+}
@@ -0,0 +1,41 @@
+use crate::util::Scratch;
+
+const LOG: &str = "connection timeout\nconection timout\ntime0ut here\nnothing\ntimeot\n";
+
+#[test]
+fn lines_within_the_distance_are_shown() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep-fuzzy=timeout"], LOG).success();
+    assert_eq!(run.stdout, "connection timeout\nconection timout\ntime0ut here\ntimeot\n");
+}
+
+#[test]
+fn fuzzy_distance_sets_the_limit() {
+    let scratch = Scratch::new();
+    let input = "timeout\ntmeout\ntmout\n";
+    assert_eq!(scratch.run_stdin(&["--grep-fuzzy=timeout", "--fuzzy-distance=0"], input).success().stdout, "timeout\n");
+    assert_eq!(scratch.run_stdin(&["--grep-fuzzy=timeout", "--fuzzy-distance=1"], input).success().stdout, "timeout\ntmeout\n");
+    assert_eq!(scratch.run_stdin(&["--grep-fuzzy=timeout", "--fuzzy-distance=2"], input).success().stdout, input);
+}
+
+#[test]
+fn show_distance_prefixes_the_edits() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep-fuzzy=timeout", "--show-distance"], LOG).success();
+    assert_eq!(run.stdout, "~0 connection timeout\n~1 conection timout\n~1 time0ut here\n~1 timeot\n");
+}
+
+#[test]
+fn short_patterns_allow_fewer_edits() {
+    let scratch = Scratch::new();
+    // Two characters get one edit by default, or anything would match
+    assert_eq!(scratch.run_stdin(&["--grep-fuzzy=ab"], "ab\nax\nxy\n").success().stdout, "ab\nax\n");
+}
+
+#[test]
+fn distance_as_long_as_the_pattern_is_rejected() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep-fuzzy=abc", "--fuzzy-distance=3"], "abc\n");
+    assert!(run.stderr.contains("meow: --fuzzy-distance 3 would match anything: the --grep-fuzzy pattern is 3 characters long"), "{}", run.stderr);
+    assert!(!run.stdout.contains("abc\n"));
+}
@@ -0,0 +1,66 @@
+use crate::util::Scratch;
+
+fn entry(line: usize, text: &str) -> String {
+    format!("    {{\"line\": {}, \"text\": \"{}\"}}", line, text)
+}
+
+/// The `"file": {"lines": [...]}` member of the grouped object.
+fn group(file: &str, entries: &[String]) -> String {
+    if entries.is_empty() {
+        return format!("  \"{}\": {{\"lines\": []}}", file);
+    }
+    format!("  \"{}\": {{\"lines\": [\n{}\n  ]}}", file, entries.join(",\n"))
+}
+
+fn object(groups: &[String]) -> String {
+    format!("{{\n{}\n}}\n", groups.join(",\n"))
+}
+
+#[test]
+fn lines_are_grouped_under_their_file() {
+    let scratch = Scratch::new();
+    scratch.write("one.log", "ok\nERROR x\n");
+    scratch.write("two.log", "ERROR y\nERROR z\nok\n");
+    let run = scratch.run(&["--group-by-file", "--grep=ERROR", "one.log", "two.log"]).success();
+    assert_eq!(run.stdout, object(&[
+        group("one.log", &[entry(2, "ERROR x")]),
+        group("two.log", &[entry(1, "ERROR y"), entry(2, "ERROR z")]),
+    ]));
+}
+
+#[test]
+fn files_keep_the_order_they_were_given() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    scratch.write("b.txt", "b\n");
+    let run = scratch.run(&["--group-by-file", "b.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, object(&[group("b.txt", &[entry(1, "b")]), group("a.txt", &[entry(1, "a")])]));
+}
+
+#[test]
+fn a_file_without_matches_has_no_lines() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "ERROR\n");
+    scratch.write("b.txt", "fine\n");
+    let run = scratch.run(&["--group-by-file", "--grep=ERROR", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, object(&[group("a.txt", &[entry(1, "ERROR")]), group("b.txt", &[])]));
+}
+
+#[test]
+fn a_missing_file_is_left_out() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    let run = scratch.run(&["--group-by-file", "missing.txt", "a.txt"]).failure();
+    assert!(run.stderr.contains("meow: missing.txt: "), "{}", run.stderr);
+    assert_eq!(run.stdout, object(&[group("a.txt", &[entry(1, "a")])]));
+    // Nothing readable is still one document
+    let run = scratch.run(&["--group-by-file", "missing.txt"]).failure();
+    assert_eq!(run.stdout, "{}\n");
+}
+
+#[test]
+fn stdin_is_grouped_as_stdin() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--group-by-file"], "x \"q\"\n").success();
+    assert_eq!(run.stdout, object(&[group("stdin", &[entry(1, "x \\\"q\\\"")])]));
+}
@@ -0,0 +1,59 @@
+use crate::util::Scratch;
+
+const TWO_GROUPS: &str = "a\nfoo\nb\nc\nd\nfoo\ne\n";
+
+fn files(scratch: &Scratch) {
+    scratch.write("g1.txt", TWO_GROUPS);
+    scratch.write("g2.txt", "foo\nx\n");
+}
+
+#[test]
+fn separator_between_groups_not_before_the_first() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--context=1"], TWO_GROUPS).success();
+    assert_eq!(run.stdout, "a\nfoo\nb\n--\nd\nfoo\ne\n");
+}
+
+#[test]
+fn touching_groups_are_one_group() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--context=2"], TWO_GROUPS).success();
+    assert_eq!(run.stdout, TWO_GROUPS);
+    let run = scratch.run_stdin(&["--grep=foo", "--after-context=1"], "foo\nb\nfoo\n").success();
+    assert_eq!(run.stdout, "foo\nb\nfoo\n");
+}
+
+#[test]
+fn no_separator_without_context() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo"], TWO_GROUPS).success();
+    assert_eq!(run.stdout, "foo\nfoo\n");
+}
+
+#[test]
+fn separator_between_files() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    let run = scratch.run(&["--grep=foo", "--context=1", "--flatten", "g1.txt", "g2.txt"]).success();
+    assert_eq!(run.stdout, "a\nfoo\nb\n--\nd\nfoo\ne\n--\nfoo\nx\n");
+    let run = scratch.run(&["--grep=foo", "--context=1", "g1.txt", "g2.txt"]).success();
+    assert!(run.stdout.ends_with("===> g2.txt <===\n--\nfoo\nx\n"), "{}", run.stdout);
+}
+
+#[test]
+fn a_file_without_matches_adds_no_separator() {
+    let scratch = Scratch::new();
+    files(&scratch);
+    scratch.write("none.txt", "nothing\n");
+    let run = scratch.run(&["--grep=foo", "--context=1", "--flatten", "none.txt", "g2.txt"]).success();
+    assert_eq!(run.stdout, "foo\nx\n");
+}
+
+#[test]
+fn custom_and_no_separator() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--context=1", "--group-separator=~~"], TWO_GROUPS).success();
+    assert_eq!(run.stdout, "a\nfoo\nb\n~~\nd\nfoo\ne\n");
+    let run = scratch.run_stdin(&["--grep=foo", "--context=1", "--no-group-separator"], TWO_GROUPS).success();
+    assert_eq!(run.stdout, "a\nfoo\nb\nd\nfoo\ne\n");
+}
@@ -0,0 +1,37 @@
+use crate::util::Scratch;
+
+const CYAN: &str = "\x1B[36m";
+const RESET: &str = "\x1B[0m";
+
+#[test]
+fn highlight_regex_colors_every_match() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "order 12 of 345\nnone here\n");
+    let run = scratch.run_tty(&[r"--highlight-regex=\d+", "a.txt"]).success();
+    assert_eq!(run.stdout, format!("order {CYAN}12{RESET} of {CYAN}345{RESET}\nnone here\n"));
+}
+
+#[test]
+fn highlight_regex_and_literal_spans_merge() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "abc123\n");
+    let run = scratch.run_tty(&[r"--highlight-regex=\d+", "--highlight=c1", "a.txt"]).success();
+    assert_eq!(run.stdout, format!("ab{CYAN}c123{RESET}\n"));
+}
+
+#[test]
+fn highlight_regex_is_plain_when_not_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "order 12\n");
+    let run = scratch.run(&[r"--highlight-regex=\d+", "a.txt"]).success();
+    assert_eq!(run.stdout, "order 12\n");
+}
+
+#[test]
+fn highlight_regex_rejects_a_bad_pattern() {
+    let scratch = Scratch::new();
+    // Like any bad option, with the usage after it
+    let run = scratch.run(&["--highlight-regex=(", "a.txt"]);
+    assert!(run.stderr.starts_with("meow: invalid regex '('"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
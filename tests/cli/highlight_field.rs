@@ -0,0 +1,42 @@
+use crate::util::Scratch;
+
+const TABLE: &str = "id\tname\tstatus\n1\talpha\tok\n2\tbeta\tfailed\n3\n";
+
+#[test]
+fn second_tab_separated_field() {
+    let scratch = Scratch::new();
+    scratch.write("table.tsv", TABLE);
+    let run = scratch.run_tty(&["--highlight-field=2", "table.tsv"]).success();
+    assert_eq!(run.stdout, concat!(
+        "id\t\x1B[36mname\x1B[0m\tstatus\n",
+        "1\t\x1B[36malpha\x1B[0m\tok\n",
+        "2\t\x1B[36mbeta\x1B[0m\tfailed\n",
+        // Too few fields: the line is shown as it is
+        "3\n",
+    ));
+}
+
+#[test]
+fn other_delimiters_and_csv() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "x:y:z\n");
+    let run = scratch.run_tty(&["--highlight-field=3", "--delimiter=:", "a.txt"]).success();
+    assert_eq!(run.stdout, "x:y:\x1B[36mz\x1B[0m\n");
+    scratch.write("a.csv", "\"last, first\",42\n");
+    let run = scratch.run_tty(&["--highlight-field=2", "--csv", "a.csv"]).success();
+    assert_eq!(run.stdout, "\"last, first\",\x1B[36m42\x1B[0m\n");
+}
+
+#[test]
+fn whole_lines_without_colors() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--highlight-field=2"], TABLE).success();
+    assert_eq!(run.stdout, TABLE);
+}
+
+#[test]
+fn field_zero_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--highlight-field=0"], TABLE);
+    assert!(run.stderr.starts_with("meow: invalid"), "{}", run.stderr);
+}
@@ -0,0 +1,67 @@
+use crate::util::Scratch;
+
+/// A PNG's signature is all meow looks at to tell it's an image.
+const PNG_HEAD: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+#[test]
+fn image_is_not_dumped_off_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("cat.png", PNG_HEAD);
+    let run = scratch.run(&["cat.png"]).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "meow: cat.png: binary file (PNG image) not shown\n");
+}
+
+#[test]
+fn text_that_isnt_an_image_is_shown() {
+    let scratch = Scratch::new();
+    scratch.write("notes.png", "just text\n");
+    let run = scratch.run(&["notes.png"]).success();
+    assert_eq!(run.stdout, "just text\n");
+}
+
+#[cfg(feature = "images")]
+mod rendered {
+    use image::{Rgba, RgbaImage};
+
+    use crate::util::Scratch;
+
+    fn write_png(scratch: &Scratch, width: u32, height: u32) {
+        let img = RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255]));
+        img.save(scratch.path("red.png")).unwrap();
+    }
+
+    #[test]
+    fn blocks_take_the_image_width() {
+        let scratch = Scratch::new();
+        write_png(&scratch, 40, 20);
+        let run = scratch.run_tty(&["--image-protocol=blocks", "--image-width=8", "red.png"]).success();
+        let rows: Vec<&str> = run.stdout.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.matches('\u{2580}').count() == 8), "{:?}", rows);
+    }
+
+    #[test]
+    fn kitty_is_told_the_image_width() {
+        let scratch = Scratch::new();
+        write_png(&scratch, 400, 2);
+        let run = scratch.run_tty(&["--image-protocol=kitty", "--image-width=8", "red.png"]).success();
+        assert!(run.stdout.starts_with("\x1B_Ga=T,f=32,s=400,v=2,c=8,"), "{:?}", &run.stdout[..40]);
+    }
+
+    #[test]
+    fn kitty_defaults_to_the_terminal_width() {
+        let scratch = Scratch::new();
+        write_png(&scratch, 1200, 2);
+        let run = scratch.run_tty(&["--image-protocol=kitty", "red.png"]).success();
+        assert!(run.stdout.starts_with("\x1B_Ga=T,f=32,s=1200,v=2,c=80,"), "{:?}", &run.stdout[..40]);
+    }
+
+    #[test]
+    fn kitty_leaves_small_images_small() {
+        let scratch = Scratch::new();
+        write_png(&scratch, 16, 2);
+        let run = scratch.run_tty(&["--image-protocol=kitty", "red.png"]).success();
+        assert!(run.stdout.starts_with("\x1B_Ga=T,f=32,s=16,v=2,c=2,m=0;"), "{:?}", run.stdout);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::os::unix::process::CommandExt;
+use std::time::{Duration, SystemTime};
+
+use crate::util::Scratch;
+
+/// Names in the scratch directory `dir`, to see no temporary file was left.
+fn listing(scratch: &Scratch, dir: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(scratch.path(dir)).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn in_place_rewrites_and_keeps_the_permissions() {
+    let scratch = Scratch::new();
+    let path = scratch.write("dir/a.txt", "one\n\n\n\ntwo\n");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    let run = scratch.run(&["-s", "--in-place", "dir/a.txt"]).success();
+    assert_eq!(run.stdout, "dir/a.txt: rewritten\n");
+    assert_eq!(scratch.read("dir/a.txt"), "one\n\ntwo\n");
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+    assert_eq!(listing(&scratch, "dir"), ["a.txt"]);
+}
+
+#[test]
+fn in_place_preserve_times_and_backup() {
+    let scratch = Scratch::new();
+    let path = scratch.write("a.txt", "one\n\n\ntwo\n");
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    fs::File::options().write(true).open(&path).unwrap().set_modified(mtime).unwrap();
+    scratch.run(&["-s", "--in-place", "--preserve-times", "--backup=.bak", "a.txt"]).success();
+    assert_eq!(scratch.read("a.txt"), "one\n\ntwo\n");
+    assert_eq!(scratch.read("a.txt.bak"), "one\n\n\ntwo\n");
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), mtime);
+}
+
+#[test]
+fn in_place_through_a_symlink_rewrites_the_target() {
+    let scratch = Scratch::new();
+    scratch.write("real/a.txt", "one\n\n\ntwo\n");
+    fs::create_dir(scratch.path("links")).unwrap();
+    symlink("../real/a.txt", scratch.path("links/a.txt")).unwrap();
+    scratch.run(&["-s", "--in-place", "links/a.txt"]).success();
+    assert!(fs::symlink_metadata(scratch.path("links/a.txt")).unwrap().file_type().is_symlink());
+    assert_eq!(scratch.read("real/a.txt"), "one\n\ntwo\n");
+    assert_eq!(listing(&scratch, "real"), ["a.txt"]);
+    assert_eq!(listing(&scratch, "links"), ["a.txt"]);
+}
+
+#[test]
+fn in_place_leaves_a_file_it_cannot_read_whole() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", b"first\n\xFF\xFE not utf-8\nlast\n");
+    let run = scratch.run(&["-n", "--in-place", "a.txt"]).failure();
+    assert!(run.stderr.ends_with("meow: a.txt: could not be read to the end, left unchanged\n"), "{}", run.stderr);
+    assert_eq!(fs::read(scratch.path("a.txt")).unwrap(), b"first\n\xFF\xFE not utf-8\nlast\n");
+    assert_eq!(listing(&scratch, "."), ["a.txt"]);
+}
+
+#[test]
+fn in_place_leaves_the_file_when_writing_fails() {
+    let scratch = Scratch::new();
+    let original: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+    scratch.write("big.txt", &original);
+    let mut command = scratch.command(&["-n", "--in-place", "big.txt"]);
+    // SAFETY: setrlimit and signal are async-signal-safe. Files meow writes are
+    // capped at 4 KiB, so the temporary file fails partway, with EFBIG rather
+    // than a signal
+    unsafe {
+        command.pre_exec(|| {
+            let limit = libc::rlimit { rlim_cur: 4096, rlim_max: 4096 };
+            libc::setrlimit(libc::RLIMIT_FSIZE, &limit);
+            libc::signal(libc::SIGXFSZ, libc::SIG_IGN);
+            Ok(())
+        });
+    }
+    let run = scratch.run_command(&mut command, b"").failure();
+    assert!(run.stderr.starts_with("meow: big.txt: "), "{}", run.stderr);
+    assert_eq!(scratch.read("big.txt"), original);
+    assert_eq!(listing(&scratch, "."), ["big.txt"]);
+}
+
+#[test]
+fn in_place_goes_on_past_a_failed_file() {
+    let scratch = Scratch::new();
+    scratch.write("b.txt", "one\n\n\ntwo\n");
+    let run = scratch.run(&["-s", "--in-place", "missing.txt", "b.txt"]).failure();
+    assert!(run.stderr.starts_with("meow: missing.txt: "), "{}", run.stderr);
+    assert_eq!(run.stdout, "b.txt: rewritten\n");
+    assert_eq!(scratch.read("b.txt"), "one\n\ntwo\n");
+}
+
+#[test]
+fn in_place_refuses_stdin_and_no_transform() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-s", "--in-place"], "text\n").failure();
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run(&["--in-place", "a.txt"]).failure();
+    assert!(run.stderr.contains("--in-place needs an option that changes the text"), "{}", run.stderr);
+    assert_eq!(scratch.read("a.txt"), "text\n");
+}
@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::util::Scratch;
+
+/// Checks each `line offset length` record against the bytes of `name`: the
+/// line found there is `lines[line - 1]`.
+fn check_records(scratch: &Scratch, name: &str, index: &str, lines: &[&str]) {
+    let mut file = File::open(scratch.path(name)).unwrap();
+    for record in index.lines() {
+        let fields: Vec<u64> = record.split('\t').map(|field| field.parse().unwrap()).collect();
+        let (line, offset, length) = (fields[0], fields[1], fields[2]);
+        let mut bytes = vec![0; length as usize];
+        file.seek(SeekFrom::Start(offset)).unwrap();
+        file.read_exact(&mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), lines[line as usize - 1], "{}", record);
+    }
+}
+
+#[test]
+fn offsets_of_every_line() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\n\nthrée\nlast");
+    let run = scratch.run(&["--index", "a.txt"]).success();
+    assert_eq!(run.stdout, "1\t0\t3\n2\t4\t0\n3\t5\t6\n4\t12\t4\n");
+    check_records(&scratch, "a.txt", &run.stdout, &["one", "", "thrée", "last"]);
+}
+
+#[test]
+fn offsets_in_a_crlf_file() {
+    let scratch = Scratch::new();
+    scratch.write("dos.txt", "INFO a\r\nERROR b\r\n\r\nERROR c");
+    let run = scratch.run(&["--index", "dos.txt"]).success();
+    assert_eq!(run.stdout, "1\t0\t6\n2\t8\t7\n3\t17\t0\n4\t19\t7\n");
+    check_records(&scratch, "dos.txt", &run.stdout, &["INFO a", "ERROR b", "", "ERROR c"]);
+}
+
+#[test]
+fn grep_indexes_only_matches() {
+    let scratch = Scratch::new();
+    scratch.write("dos.txt", "INFO a\r\nERROR b\r\n\r\nERROR c");
+    let run = scratch.run(&["--index", "-g", "ERROR", "dos.txt"]).success();
+    assert_eq!(run.stdout, "2\t8\t7\n4\t19\t7\n");
+    check_records(&scratch, "dos.txt", &run.stdout, &["INFO a", "ERROR b", "", "ERROR c"]);
+}
+
+#[test]
+fn json_lines_and_several_files() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "x\n");
+    scratch.write("b.txt", "yy\n");
+    let run = scratch.run(&["--index", "--index-format=json", "a.txt"]).success();
+    assert_eq!(run.stdout, "{\"file\": \"a.txt\", \"line\": 1, \"offset\": 0, \"length\": 1}\n");
+    let run = scratch.run(&["--index", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, "a.txt\t1\t0\t1\nb.txt\t1\t0\t2\n");
+}
+
+#[test]
+fn index_to_an_output_file() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "x\nyy\n");
+    let run = scratch.run(&["--index", "--output=index.tsv", "a.txt"]).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(scratch.read("index.tsv"), "1\t0\t1\n2\t2\t2\n");
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::util::Scratch;
+
+/// Every kind of byte a display option could touch.
+const AWKWARD: &[u8] = b"plain\r\n\ttab\x1B[31mred\x1B[0m\n\xFF\xFEnot utf-8\n\n\n\nblank run\x00nul\nno newline";
+
+#[test]
+fn plain_copies_bytes_exactly() {
+    let scratch = Scratch::new();
+    scratch.write("awkward.bin", AWKWARD);
+    for args in [&["--plain", "awkward.bin"][..], &["--plain", "-n", "-A", "-s", "--grep=x", "awkward.bin"], &["-n", "--plain", "--fold=5", "--redact", "awkward.bin"]] {
+        let output = scratch.command(args).output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, AWKWARD, "{:?}", args);
+    }
+}
+
+#[test]
+fn plain_copies_stdin_and_several_files() {
+    let scratch = Scratch::new();
+    scratch.write("a", "first\n");
+    scratch.write("b", b"\xFFsecond");
+    let run = scratch.run_stdin(&["--plain", "a", "-", "b"], "middle\n").success();
+    assert_eq!(run.stdout, "first\nmiddle\n\u{FFFD}second");
+}
+
+#[test]
+fn init_prints_a_cat_function() {
+    let scratch = Scratch::new();
+    for shell in ["bash", "zsh"] {
+        let run = scratch.run(&[format!("--init={}", shell)]).success();
+        assert!(run.stdout.contains(&format!("eval \"$(meow --init={})\"", shell)), "{}", run.stdout);
+        assert!(run.stdout.contains("cat() {"));
+    }
+    let run = scratch.run(&["--init=fish"]).success();
+    assert!(run.stdout.contains("function cat --wraps cat"));
+    assert!(run.stdout.contains("meow --init=fish | source"));
+}
+
+#[test]
+fn unknown_shell_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--init=tcsh"]);
+    assert!(run.stderr.contains("unsupported shell 'tcsh' (expected bash, zsh or fish)"), "{}", run.stderr);
+}
+
+/// Runs `script` in bash with the `--init=bash` function loaded and this meow
+/// first on the PATH. Output goes to a pipe, as in a script.
+fn in_bash(scratch: &Scratch, script: &str) -> crate::util::Run {
+    let meow = Path::new(env!("CARGO_BIN_EXE_meow"));
+    let path = format!("{}:{}", meow.parent().unwrap().display(), std::env::var("PATH").unwrap_or_default());
+    let mut bash = Command::new("bash");
+    bash.arg("-c")
+        .arg(format!("eval \"$(meow --init=bash)\"\n{}", script))
+        .current_dir(scratch.path(""))
+        .env_clear()
+        .env("PATH", path)
+        .env("HOME", scratch.path(""))
+        .env("LANG", "C");
+    scratch.run_command(&mut bash, b"")
+}
+
+#[test]
+fn cat_function_keeps_scripts_working() {
+    let scratch = Scratch::new();
+    scratch.write("notes.txt", "alpha\tone\nbeta\n");
+
+    // No options: meow --plain, the same bytes
+    let run = in_bash(&scratch, "cat notes.txt").success();
+    assert_eq!(run.stdout, "alpha\tone\nbeta\n");
+
+    // cat's own options get real cat's output
+    let run = in_bash(&scratch, "cat -n notes.txt").success();
+    assert_eq!(run.stdout, "     1\talpha\tone\n     2\tbeta\n");
+    let run = in_bash(&scratch, "cat -ET notes.txt").success();
+    assert_eq!(run.stdout, "alpha^Ione$\nbeta$\n");
+
+    // Options only meow has still run meow
+    let run = in_bash(&scratch, "cat --grep=beta notes.txt").success();
+    assert_eq!(run.stdout, "beta\n");
+
+    // Options meow doesn't know go to cat, which complains as cat
+    let run = in_bash(&scratch, "cat --no-such-option notes.txt").failure();
+    assert!(run.stderr.contains("cat:"), "{}", run.stderr);
+}
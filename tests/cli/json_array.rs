@@ -0,0 +1,52 @@
+use crate::util::Scratch;
+
+fn entry(file: &str, line: usize, text: &str) -> String {
+    format!("  {{\"file\": \"{}\", \"line\": {}, \"text\": \"{}\"}}", file, line, text)
+}
+
+fn array(entries: &[String]) -> String {
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+#[test]
+fn json_array_of_every_line() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--json-array"], "a\nfoo \"q\"\n").success();
+    assert_eq!(run.stdout, array(&[entry("stdin", 1, "a"), entry("stdin", 2, "foo \\\"q\\\"")]));
+}
+
+#[test]
+fn json_array_of_nothing_is_empty() {
+    let scratch = Scratch::new();
+    assert_eq!(scratch.run_stdin(&["--json-array"], "").success().stdout, "[]\n");
+    assert_eq!(scratch.run_stdin(&["--json-array", "--grep=x"], "a\nb\n").success().stdout, "[]\n");
+    scratch.write("empty.txt", "");
+    assert_eq!(scratch.run(&["--json-array", "empty.txt", "empty.txt"]).success().stdout, "[]\n");
+}
+
+#[test]
+fn json_array_spans_all_files() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\ntwo\n");
+    scratch.write("b.txt", "three\n");
+    let run = scratch.run(&["--json-array", "--grep=t", "a.txt", "b.txt"]).success();
+    // One document, without the file headers
+    assert_eq!(run.stdout, array(&[entry("a.txt", 2, "two"), entry("b.txt", 1, "three")]));
+}
+
+#[test]
+fn json_array_escapes_control_characters() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--json-array"], "a\tb\\\u{1}\u{e9}\n").success();
+    assert_eq!(run.stdout, array(&[entry("stdin", 1, "a\\tb\\\\\\u0001\u{e9}")]));
+}
+
+#[test]
+fn json_array_ignores_display_options() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    let run = scratch.run_tty(&["--json-array", "-n", "--highlight=a", "a.txt"]).success();
+    assert_eq!(run.stdout, array(&[entry("a.txt", 1, "a")]));
+    let run = scratch.run_stdin(&["--json-array", "-n", "--grep=b", "--context=1"], "a\nb\nc\n").success();
+    assert_eq!(run.stdout, array(&[entry("stdin", 1, "a"), entry("stdin", 2, "b"), entry("stdin", 3, "c")]));
+}
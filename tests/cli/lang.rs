@@ -0,0 +1,41 @@
+use crate::util::Scratch;
+
+#[test]
+fn lang_from_the_environment() {
+    let scratch = Scratch::new();
+    let run = scratch.run_command(scratch.command(&["--bogus"]).env("LANG", "ru_RU.UTF-8"), b"");
+    assert!(run.stderr.starts_with("meow: неизвестная опция: --bogus"), "{}", run.stderr);
+}
+
+#[test]
+fn lc_messages_wins_over_lang() {
+    let scratch = Scratch::new();
+    let run = scratch.run_command(scratch.command(&["--bogus"]).env("LANG", "ru_RU.UTF-8").env("LC_MESSAGES", "en_US.UTF-8"), b"");
+    assert!(run.stderr.starts_with("meow: unknown option: --bogus"), "{}", run.stderr);
+}
+
+#[test]
+fn lang_option_wins_over_the_environment() {
+    let scratch = Scratch::new();
+    let run = scratch.run_command(scratch.command(&["--lang=en", "--bogus"]).env("LANG", "ru"), b"");
+    assert!(run.stderr.starts_with("meow: unknown option: --bogus"), "{}", run.stderr);
+    let run = scratch.run(&["--lang=ru", "--help"]).success();
+    assert!(run.stdout.contains("язык сообщений (en, ru)"), "{}", run.stdout);
+}
+
+#[test]
+fn lang_reports_a_language_it_lacks() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run(&["--lang=xx", "a.txt"]).success();
+    assert_eq!(run.stderr, "meow: unsupported language 'xx'\n");
+    assert_eq!(run.stdout, "text\n");
+}
+
+#[test]
+fn unsupported_language_in_the_error_color() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run_tty(&["--lang=xx", "a.txt"]).success();
+    assert_eq!(run.stderr, "\x1B[31mmeow: unsupported language 'xx'\x1B[0m\n");
+}
@@ -0,0 +1,40 @@
+use crate::util::Scratch;
+
+const PROMPT: &str = "big.txt is 31 B \u{2014} display anyway? [y/N/p(age)] ";
+
+fn big(scratch: &Scratch) {
+    scratch.write("big.txt", "0123456789\n".repeat(2) + "abcdefgh\n");
+}
+
+#[test]
+fn yes_shows_the_file() {
+    let scratch = Scratch::new();
+    big(&scratch);
+    let run = scratch.run_tty_answers(&["-C", "--max-terminal-bytes=10", "big.txt"], &[(PROMPT, "y\n")]).success();
+    assert_eq!(run.stdout, format!("meow: {}y\n0123456789\n0123456789\nabcdefgh\n", PROMPT));
+}
+
+#[test]
+fn anything_else_skips_it() {
+    let scratch = Scratch::new();
+    big(&scratch);
+    scratch.write("small.txt", "tiny\n");
+    let run = scratch.run_tty_answers(&["-C", "--max-terminal-bytes=10", "big.txt", "small.txt"], &[(PROMPT, "\n")]).success();
+    assert!(!run.stdout.contains("0123456789"), "{:?}", run.stdout);
+    // The next file is shown as usual
+    assert!(run.stdout.ends_with("\n===> small.txt <===\ntiny\n"), "{:?}", run.stdout);
+}
+
+#[test]
+fn small_files_forced_or_piped_are_not_asked_about() {
+    let scratch = Scratch::new();
+    big(&scratch);
+    let run = scratch.run_tty(&["--max-terminal-bytes=100", "big.txt"]).success();
+    assert_eq!(run.stdout, "0123456789\n0123456789\nabcdefgh\n");
+    let run = scratch.run_tty(&["--max-terminal-bytes=10", "--force", "big.txt"]).success();
+    assert_eq!(run.stdout, "0123456789\n0123456789\nabcdefgh\n");
+    let run = scratch.run(&["--max-terminal-bytes=10", "big.txt"]).success();
+    assert_eq!(run.stdout, "0123456789\n0123456789\nabcdefgh\n");
+    let run = scratch.run_tty(&["--max-terminal-bytes=0", "big.txt"]).success();
+    assert_eq!(run.stdout, "0123456789\n0123456789\nabcdefgh\n");
+}
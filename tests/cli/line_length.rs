@@ -0,0 +1,49 @@
+use crate::util::Scratch;
+
+const LINES: &str = "short\na much longer line here\n\nmid line\nfoo is long enough\n";
+
+#[test]
+fn min_length_shows_only_long_lines() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--min-length=9"], LINES).success();
+    assert_eq!(run.stdout, "a much longer line here\nfoo is long enough\n");
+}
+
+#[test]
+fn max_length_keeps_short_and_blank_lines() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--max-length=5"], LINES).success();
+    assert_eq!(run.stdout, "short\n\n");
+}
+
+#[test]
+fn both_bounds_are_inclusive() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--min-length=5", "--max-length=8"], LINES).success();
+    assert_eq!(run.stdout, "short\nmid line\n");
+}
+
+#[test]
+fn length_counts_characters_not_bytes() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--max-length=3"], "\u{e9}\u{e9}\u{e9}\nabcd\n").success();
+    assert_eq!(run.stdout, "\u{e9}\u{e9}\u{e9}\n");
+}
+
+#[test]
+fn length_combines_with_grep() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--min-length=6", "--grep=o"], LINES).success();
+    assert_eq!(run.stdout, "a much longer line here\nfoo is long enough\n");
+    // Lines too short are left for context, like lines grep doesn't match
+    let run = scratch.run_stdin(&["--min-length=19", "--grep=line", "--context=1"], LINES).success();
+    assert_eq!(run.stdout, "short\na much longer line here\n\n");
+}
+
+#[test]
+fn invalid_length_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--min-length=x"], LINES);
+    assert!(run.stderr.starts_with("meow: invalid value 'x' for --min-length\n"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::time::{Duration, SystemTime};
+
+use crate::util::Scratch;
+
+/// `dir` with a small and a big file, a subdirectory and a dangling link. The
+/// big file is the oldest.
+fn fixture(scratch: &Scratch) {
+    scratch.write("dir/b-small.txt", "hello\n");
+    let big = scratch.write("dir/c-big.txt", "x".repeat(3000));
+    fs::create_dir(scratch.path("dir/a-sub")).unwrap();
+    symlink("nowhere", scratch.path("dir/d-link")).unwrap();
+    let old = SystemTime::now() - Duration::from_secs(3 * 24 * 60 * 60);
+    fs::File::options().write(true).open(big).unwrap().set_modified(old).unwrap();
+}
+
+/// The names in a listing, in order.
+fn names(listing: &str) -> Vec<&str> {
+    listing.lines().map(|line| line.rsplit("  ").next().unwrap().trim_start()).collect()
+}
+
+#[test]
+fn directory_without_list_dirs_is_an_error() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["dir"]);
+    assert_eq!(run.stdout, "");
+    assert!(run.stderr.starts_with("meow: dir: "), "{}", run.stderr);
+}
+
+#[test]
+fn list_dirs_shows_type_size_time_and_name() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--list-dirs", "dir"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines, [
+        "d          -  0 mins ago       a-sub/",
+        "-        6 B  0 mins ago       b-small.txt",
+        "-    2.9 KiB  3 days ago       c-big.txt",
+        "l        7 B  0 mins ago       d-link",
+    ]);
+}
+
+#[test]
+fn list_dirs_sorting() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    // Directories sort by their own size, which depends on the file system
+    let run = scratch.run(&["--list-dirs", "--sort-dir=size", "dir"]).success();
+    let files: Vec<&str> = names(&run.stdout).into_iter().filter(|name| !name.ends_with('/')).collect();
+    assert_eq!(files, ["c-big.txt", "d-link", "b-small.txt"]);
+    let run = scratch.run(&["--list-dirs", "--sort-dir=size", "--dirs-first", "dir"]).success();
+    assert_eq!(names(&run.stdout), ["a-sub/", "c-big.txt", "d-link", "b-small.txt"]);
+    let run = scratch.run(&["--list-dirs", "--sort-dir=mtime", "dir"]).success();
+    assert_eq!(names(&run.stdout).last(), Some(&"c-big.txt"));
+}
+
+#[test]
+fn list_dirs_goes_through_the_output_sink() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--list-dirs", "--output=listing.txt", "dir"]).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(names(&scratch.read("listing.txt")), ["a-sub/", "b-small.txt", "c-big.txt", "d-link"]);
+}
+
+#[test]
+fn list_dirs_leaves_files_alone() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--list-dirs", "dir/b-small.txt"]).success();
+    assert_eq!(run.stdout, "hello\n");
+}
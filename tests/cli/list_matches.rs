@@ -0,0 +1,70 @@
+use crate::util::Scratch;
+
+/// A web log with status codes and sizes of three digits.
+const ACCESS_LOG: &str = "\
+GET /index.html 200 512
+GET /missing 404 128
+POST /login 302 64
+GET /index.html 200 512
+GET /crash 500 999
+GET /missing 404 128
+GET /index.html 200 1024
+";
+
+#[test]
+fn tallies_three_digit_numbers() {
+    let scratch = Scratch::new();
+    scratch.write("access.log", ACCESS_LOG);
+    let run = scratch.run(&["--list-matches=\\d{3}", "--list-matches-count", "access.log"]).success();
+    assert_eq!(run.stdout, concat!(
+        "      1 102\n",
+        "      2 128\n",
+        "      3 200\n",
+        "      1 302\n",
+        "      2 404\n",
+        "      1 500\n",
+        "      2 512\n",
+        "      1 999\n",
+    ));
+}
+
+#[test]
+fn distinct_matches_sorted() {
+    let scratch = Scratch::new();
+    scratch.write("access.log", ACCESS_LOG);
+    let run = scratch.run(&["--list-matches= [45]\\d\\d ", "access.log"]).success();
+    assert_eq!(run.stdout, " 404 \n 500 \n");
+}
+
+#[test]
+fn counts_across_inputs() {
+    let scratch = Scratch::new();
+    scratch.write("a.log", "E1 E2\nE1\n");
+    scratch.write("b.log", "E2 E3\n");
+    let run = scratch.run_stdin(&["--list-matches=E\\d", "--list-matches-count", "a.log", "-", "b.log"], "E3 E3\n").success();
+    assert_eq!(run.stdout, "      2 E1\n      2 E2\n      3 E3\n");
+}
+
+#[test]
+fn no_matches_prints_nothing() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--list-matches=x*"], "abc\n").success();
+    assert_eq!(run.stdout, "");
+}
+
+#[test]
+fn missing_file_fails_but_counts_the_rest() {
+    let scratch = Scratch::new();
+    scratch.write("a.log", "404\n");
+    let run = scratch.run(&["--list-matches=\\d+", "missing.log", "a.log"]).failure();
+    assert_eq!(run.stdout, "404\n");
+    assert!(run.stderr.contains("missing.log"), "{}", run.stderr);
+}
+
+#[test]
+fn bad_pattern() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--list-matches=("]);
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
@@ -0,0 +1,101 @@
+//! Runs the built binary the way a user would, one module per feature.
+#![cfg(unix)]
+
+mod util;
+
+mod blame;
+mod special_files;
+mod changes;
+mod highlight;
+mod images;
+mod sample;
+mod filter;
+mod escape;
+mod edit;
+mod tail_bytes;
+mod man;
+mod lang;
+mod art;
+mod detect_indent;
+mod continuous_numbering;
+mod qr;
+mod max_line_bytes;
+mod preprocessors;
+mod date_format;
+mod serve;
+mod file_colors;
+mod verify;
+mod skip_blank_numbers;
+mod in_place;
+mod match_index;
+mod list_dirs;
+mod recursive;
+mod expand_env;
+mod checksum;
+mod marks;
+mod line_length;
+mod fields;
+mod strip_comments;
+mod find_dupes;
+mod preview;
+mod tee;
+mod throttle;
+mod safe;
+mod carriage_returns;
+mod resume;
+mod json_array;
+mod exec;
+mod group_separator;
+mod structured;
+mod shell_edit;
+mod xml;
+mod stat;
+mod since_until;
+mod side_by_side;
+mod redact;
+mod nonprint_style;
+mod ruler;
+mod fold;
+mod profiles;
+mod color_depth;
+mod more;
+mod exclude;
+mod annotations;
+mod number_hex;
+mod views;
+mod merge;
+mod index;
+mod pick;
+mod record_separator;
+mod init;
+mod list_matches;
+mod rules;
+mod banners;
+mod split_output;
+mod highlight_field;
+mod match_brackets;
+mod bell;
+mod record;
+mod truncate_middle;
+mod number_heat;
+mod each_line_command;
+mod summary;
+mod raw_passthrough;
+mod shell_quote;
+mod group_by_file;
+mod grep_fuzzy;
+mod show_colors;
+mod compare;
+mod zip_entry;
+mod set_title;
+mod grep_file;
+mod fold_sections;
+mod max_count;
+mod count_invisible;
+mod freshness;
+mod theme;
+mod large_input;
+mod graphemes;
+mod preserve_ansi;
+mod wait_for_file;
+mod page;
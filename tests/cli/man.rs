@@ -0,0 +1,11 @@
+use crate::util::Scratch;
+
+#[test]
+fn generate_man_prints_a_page() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--generate-man"]).success();
+    assert!(run.stdout.starts_with(".TH MEOW 1 "), "{}", &run.stdout[..40]);
+    for section in [".SH NAME", ".SH SYNOPSIS", ".SH OPTIONS", ".SH EXAMPLES", ".SH ENVIRONMENT", ".SH EXIT STATUS"] {
+        assert!(run.stdout.contains(section), "{} is missing", section);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::util::Scratch;
+
+const RULE_WIDTH: usize = 80;
+
+fn rule(number: usize) -> String {
+    let label = format!("\u{2500}\u{2500} mark {} ", number);
+    let rest = RULE_WIDTH - label.chars().count();
+    format!("{}{}\n", label, "\u{2500}".repeat(rest))
+}
+
+fn lines(count: usize) -> String {
+    (1..=count).map(|i| format!("line {}\n", i)).collect()
+}
+
+#[test]
+fn marks_draw_numbered_rules_across_the_terminal() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--marks=3,1"], lines(4)).success();
+    assert_eq!(run.stdout, format!("{}line 1\nline 2\n{}line 3\nline 4\n", rule(1), rule(2)));
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn mark_pattern_marks_too_in_line_order() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--marks=2", "--mark-pattern=^#"], "# a\nb\nc\n# d\n").success();
+    assert_eq!(run.stdout, format!("{}# a\n{}b\nc\n{}# d\n", rule(1), rule(2), rule(3)));
+}
+
+#[test]
+fn marks_past_the_end_are_reported() {
+    let scratch = Scratch::new();
+    let path = scratch.write("twelve.txt", lines(12));
+    let run = scratch.run(&["--marks=2,20,30", "twelve.txt"]).success();
+    assert!(run.stdout.starts_with(&format!("line 1\n{}line 2\n", rule(1))));
+    assert_eq!(run.stderr, format!("meow: {}: marks past the end (12 lines): 20, 30\n", path.file_name().unwrap().to_str().unwrap()));
+}
+
+#[test]
+fn marks_keep_their_numbers_under_grep() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--marks=2,3,5", "--grep=line 3"], lines(6)).success();
+    // Line 3 is the second mark, even with line 2 left out
+    assert_eq!(run.stdout, format!("{}line 3\n", rule(2)));
+}
+
+#[test]
+fn marks_index_lists_the_marks_first() {
+    let scratch = Scratch::new();
+    scratch.write("notes.txt", "intro\n# setup\nbody\n# usage\n");
+    let run = scratch.run(&["--marks-index", "--mark-pattern=^#", "notes.txt"]).success();
+    assert_eq!(run.stdout, format!(
+        "mark 1 \u{2192} line 2: # setup\nmark 2 \u{2192} line 4: # usage\n\nintro\n{}# setup\nbody\n{}# usage\n",
+        rule(1), rule(2)));
+}
+
+#[test]
+fn marks_index_needs_a_file() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--marks-index", "--marks=1"], "a\nb\n").success();
+    assert_eq!(run.stdout, format!("{}a\nb\n", rule(1)));
+    assert_eq!(run.stderr, "meow: stdin: --marks-index needs a regular file, no index shown\n");
+}
+
+#[test]
+fn invalid_marks_are_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--marks=2,x"], "a\n");
+    assert!(run.stderr.starts_with("meow: invalid --marks '2,x'"), "{}", run.stderr);
+}
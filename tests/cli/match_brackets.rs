@@ -0,0 +1,72 @@
+use crate::util::Scratch;
+
+const BALANCED: &str = "(define (square x)\n  (* x x))\n";
+const UNBALANCED: &str = "fn main() {\n    let v = vec![(1, 2)];\n    }\n}\n";
+const STRINGS: &str = "{\"open\": \"((\", \"list\": [1, 2]}\n";
+
+#[test]
+fn balanced_pairs_colored_by_depth() {
+    let scratch = Scratch::new();
+    scratch.write("square.txt", BALANCED);
+    let run = scratch.run_tty(&["--match-brackets", "square.txt"]).success();
+    assert_eq!(run.stdout, concat!(
+        "\x1B[33m(\x1B[0mdefine \x1B[35m(\x1B[0msquare x\x1B[35m)\x1B[0m\n",
+        "  \x1B[35m(\x1B[0m* x x\x1B[35m)\x1B[0m\x1B[33m)\x1B[0m\n",
+    ));
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn unmatched_closer_in_the_error_color() {
+    let scratch = Scratch::new();
+    scratch.write("main.rs", UNBALANCED);
+    let run = scratch.run_tty(&["--match-brackets", "main.rs"]).success();
+    assert!(run.stdout.ends_with("\x1B[31m}\x1B[0m\n"), "{:?}", run.stdout);
+    assert!(run.stderr.contains("main.rs: 1 unmatched brackets: '}' 4:1"), "{}", run.stderr);
+}
+
+#[test]
+fn brackets_in_strings_are_skipped() {
+    let scratch = Scratch::new();
+    scratch.write("a.json", STRINGS);
+    let run = scratch.run_tty(&["--match-brackets", "a.json"]).success();
+    assert!(run.stdout.contains("\"((\""), "{:?}", run.stdout);
+    assert_eq!(run.stderr, "");
+    // A file of unknown type counts them
+    scratch.write("a.txt", STRINGS);
+    let run = scratch.run(&["--match-brackets=check", "a.txt"]).failure();
+    assert_eq!(run.stdout, concat!(
+        "a.txt:1:1: '{' is never closed\n",
+        "a.txt:1:11: '(' is never closed\n",
+        "a.txt:1:12: '(' is never closed\n",
+        "a.txt:1:30: '}' closes nothing\n",
+    ));
+}
+
+#[test]
+fn depth_is_kept_for_lines_grep_hides() {
+    let scratch = Scratch::new();
+    scratch.write("square.txt", BALANCED);
+    let run = scratch.run_tty(&["--match-brackets", "--grep=x x", "square.txt"]).success();
+    assert_eq!(run.stdout, "  \x1B[35m(\x1B[0m* x x\x1B[35m)\x1B[0m\x1B[33m)\x1B[0m\n");
+}
+
+#[test]
+fn check_reports_only() {
+    let scratch = Scratch::new();
+    scratch.write("square.txt", BALANCED);
+    scratch.write("main.rs", UNBALANCED);
+    let run = scratch.run(&["--match-brackets=check", "square.txt"]).success();
+    assert_eq!(run.stdout, "");
+    let run = scratch.run(&["--match-brackets=check", "square.txt", "main.rs"]).failure();
+    assert_eq!(run.stdout, "main.rs:4:1: '}' closes nothing\n");
+    let run = scratch.run_stdin(&["--match-brackets=check"], "(a\n[b]\n").failure();
+    assert_eq!(run.stdout, "stdin:1:1: '(' is never closed\n");
+}
+
+#[test]
+fn unknown_mode() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--match-brackets=fix"], "()\n");
+    assert!(run.stderr.contains("invalid --match-brackets mode 'fix' (expected check)"), "{}", run.stderr);
+}
@@ -0,0 +1,45 @@
+use crate::util::Scratch;
+
+const THREE_MATCHES: &str = "a\nfoo 1\nb\nc\nfoo 2\nd\ne\nfoo 3\nf\n";
+
+#[test]
+fn match_index_shows_only_that_match_and_its_context() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--context=1", "--match-index=2"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "c\nfoo 2\nd\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn match_index_with_one_sided_context() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--after-context=2", "--match-index=1"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "foo 1\nb\nc\n");
+    let run = scratch.run_stdin(&["--grep=foo", "--before-context=1", "--match-index=3"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "e\nfoo 3\n");
+}
+
+#[test]
+fn match_index_out_of_range_warns_and_prints_nothing() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo", "--context=1", "--match-index=5"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "meow: stdin: no match number 5 (found 3)\n");
+}
+
+#[test]
+fn context_groups_are_separated() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--grep=foo"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "foo 1\nfoo 2\nfoo 3\n");
+    let run = scratch.run_stdin(&["--grep=foo", "--after-context=1"], THREE_MATCHES).success();
+    assert_eq!(run.stdout, "foo 1\nb\n--\nfoo 2\nd\n--\nfoo 3\nf\n");
+}
+
+#[test]
+fn out_of_range_warning_in_the_error_color() {
+    let scratch = Scratch::new();
+    scratch.write("f.txt", "a\nmatch\n");
+    let run = scratch.run_tty(&["--grep=match", "--match-index=5", "f.txt"]).success();
+    assert_eq!(run.stderr, "\x1B[31mmeow: f.txt: no match number 5 (found 1)\x1B[0m\n");
+}
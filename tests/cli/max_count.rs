@@ -0,0 +1,52 @@
+use std::process::{Command, Stdio};
+
+use crate::util::Scratch;
+
+#[test]
+fn max_count_is_for_each_file() {
+    let scratch = Scratch::new();
+    scratch.write("a", "x1\nx2\nx3\n");
+    scratch.write("b", "x4\nx5\n");
+    let run = scratch.run(&["--grep=x", "--max-count=2", "a", "b"]).success();
+    assert_eq!(run.stdout, "\n===> a <===\nx1\nx2\n\n===> b <===\nx4\nx5\n");
+}
+
+#[test]
+fn max_count_total_stops_all_files() {
+    let scratch = Scratch::new();
+    scratch.write("a", "x1\nx2\nx3\n");
+    scratch.write("b", "x4\nx5\n");
+    let run = scratch.run(&["--grep=x", "--max-count=2", "--max-count-total", "a", "b"]).success();
+    // Not even a header for the files past the limit
+    assert_eq!(run.stdout, "\n===> a <===\nx1\nx2\n");
+    let run = scratch.run(&["--grep=x", "--max-count=3", "--max-count-total", "a", "b"]).success();
+    assert_eq!(run.stdout, "\n===> a <===\nx1\nx2\nx3\n");
+}
+
+#[test]
+fn max_count_keeps_the_last_context() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--grep=x", "--max-count=1", "--after-context=1"], "a\nx1\nx2\nx3\n").success();
+    assert_eq!(run.stdout, "     1 | x1\n     2 | x2\n");
+}
+
+#[test]
+fn max_count_needs_a_search() {
+    let scratch = Scratch::new();
+    assert_eq!(scratch.run_stdin(&["--max-count=1"], "a\nb\n").success().stdout, "a\nb\n");
+}
+
+#[test]
+fn reading_stops_at_the_limit() {
+    let scratch = Scratch::new();
+    // An input that never ends, like grep -m 1 on a log being written
+    let mut yes = Command::new("yes").arg("x").stdout(Stdio::piped()).spawn().expect("cannot run yes");
+    let output = scratch.command(&["--grep=x", "--max-count=2"])
+        .stdin(yes.stdout.take().unwrap())
+        .output()
+        .unwrap();
+    yes.kill().unwrap();
+    yes.wait().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"x\nx\n");
+}
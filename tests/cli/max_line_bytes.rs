@@ -0,0 +1,33 @@
+use crate::util::Scratch;
+
+#[test]
+fn max_line_bytes_splits_a_long_line_and_warns() {
+    let scratch = Scratch::new();
+    scratch.write("one-line.txt", "a".repeat(100_000));
+    let run = scratch.run(&["--max-line-bytes=40000", "one-line.txt"]).success();
+    let lines: Vec<usize> = run.stdout.lines().map(str::len).collect();
+    assert_eq!(lines, [40_000, 40_000, 20_000]);
+    assert_eq!(run.stderr, "meow: one-line.txt: lines longer than 40000 bytes were split\n");
+}
+
+#[test]
+fn max_line_bytes_smaller_than_a_character() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--max-line-bytes=1"], "é€\n").success();
+    assert_eq!(run.stdout, "é\n€\n");
+}
+
+#[test]
+fn max_line_bytes_leaves_short_lines_alone() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--max-line-bytes=10"], "short\nlines\n").success();
+    assert_eq!(run.stdout, "short\nlines\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn max_line_bytes_needs_a_positive_count() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--max-line-bytes=0"]);
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+}
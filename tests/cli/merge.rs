@@ -0,0 +1,41 @@
+use crate::util::Scratch;
+
+fn fixture(scratch: &Scratch) {
+    scratch.write("names.txt", "cat\ndog\nfox\n");
+    scratch.write("sounds.txt", "meow\nwoof\n");
+}
+
+#[test]
+fn merge_two_files() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--merge", "names.txt", "sounds.txt"]).success();
+    assert_eq!(run.stdout, "cat\nmeow\ndog\nwoof\nfox\n\n");
+}
+
+#[test]
+fn merge_with_a_delimiter_like_paste() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--merge", "--merge-delimiter=: ", "-n", "names.txt", "sounds.txt"]).success();
+    assert_eq!(run.stdout, "     1 | cat: meow\n     2 | dog: woof\n     3 | fox: \n");
+    let run = scratch.run(&["--merge", "--merge-delimiter=,", "--merge-shortest", "names.txt", "sounds.txt"]).success();
+    assert_eq!(run.stdout, "cat,meow\ndog,woof\n");
+}
+
+#[test]
+fn merge_with_stdin() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run_stdin(&["--merge", "--merge-delimiter= ", "names.txt", "-"], "1\n2\n3\n").success();
+    assert_eq!(run.stdout, "cat 1\ndog 2\nfox 3\n");
+}
+
+#[test]
+fn merge_shows_nothing_if_a_file_is_missing() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["--merge", "names.txt", "missing.txt"]).failure();
+    assert_eq!(run.stdout, "");
+    assert!(run.stderr.contains("missing.txt"), "{}", run.stderr);
+}
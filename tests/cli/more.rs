@@ -0,0 +1,69 @@
+use crate::util::Scratch;
+
+/// What clears the prompt once a key was pressed.
+const CLEAR: &str = "\r              \r";
+
+/// `lines` numbered lines.
+fn numbered(lines: std::ops::RangeInclusive<usize>) -> String {
+    lines.map(|i| format!("line {}\n", i)).collect()
+}
+
+fn prompt(percent: u64) -> String {
+    format!("\x1B[7m--More--({}%)\x1B[0m", percent)
+}
+
+#[test]
+fn space_shows_the_next_screen() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", numbered(1..=30));
+    let run = scratch.run_tty_keys(&["--more", "a.txt"], &[("--More--", " ")]).success();
+    // 24 rows, one of them for the prompt
+    assert_eq!(run.stdout, format!("{}{}{}{}", numbered(1..=23), prompt(100), CLEAR, numbered(24..=30)));
+}
+
+#[test]
+fn enter_shows_one_more_line_and_q_quits() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", numbered(1..=50));
+    let run = scratch.run_tty_keys(&["--more", "a.txt"], &[("--More--", "\r"), ("--More--", "q")]).success();
+    assert_eq!(run.stdout, format!("{0}{1}{2}line 24\n{1}{2}", numbered(1..=23), prompt(100), CLEAR));
+}
+
+#[test]
+fn percentage_of_a_large_file() {
+    let scratch = Scratch::new();
+    scratch.write("big.txt", numbered(1..=20_000));
+    let run = scratch.run_tty_keys(&["--more", "big.txt"], &[("--More--", "q")]).success();
+    let shown = run.stdout.split("--More--(").nth(1).unwrap();
+    let percent: u64 = shown[..shown.find('%').unwrap()].parse().unwrap();
+    assert!(percent < 10, "{}", percent);
+}
+
+#[test]
+fn ctrl_c_restores_the_terminal_and_exits() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", numbered(1..=30));
+    // run_tty_keys fails the test if the terminal is left in raw mode
+    let run = scratch.run_tty_keys(&["--more", "a.txt"], &[("--More--", "\x03")]);
+    assert_eq!(run.status.code(), Some(130));
+    assert!(!run.stdout.contains("line 24"));
+}
+
+#[test]
+fn short_output_needs_no_prompt() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", numbered(1..=5));
+    let run = scratch.run_tty_keys(&["--more", "a.txt"], &[]).success();
+    assert_eq!(run.stdout, numbered(1..=5));
+}
+
+#[test]
+fn continuous_output_off_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", numbered(1..=100));
+    let run = scratch.run(&["--more", "a.txt"]).success();
+    assert_eq!(run.stdout, numbered(1..=100));
+    // Stdin has to be a terminal too, for the keys
+    let run = scratch.run_tty(&["--more", "a.txt"]).success();
+    assert_eq!(run.stdout, numbered(1..=100));
+}
@@ -0,0 +1,27 @@
+use crate::util::Scratch;
+
+#[test]
+fn esc_and_tab_in_each_style() {
+    let scratch = Scratch::new();
+    for (style, expected) in [("caret", "a^[b^Ic\n"), ("hex", "a\\x1bb\\x09c\n"), ("unicode", "a\u{241B}b\u{2409}c\n")] {
+        let run = scratch.run_stdin(&["-AT", &format!("--nonprint-style={}", style)], "a\x1Bb\tc\n").success();
+        assert_eq!(run.stdout, expected, "{}", style);
+    }
+}
+
+#[test]
+fn caret_is_the_default() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-A"], "a\x1Bb\tc\x7F\n").success();
+    assert_eq!(run.stdout, "a^[b\tc^?\n");
+    let run = scratch.run_stdin(&["-T", "--nonprint-style=unicode"], "a\tb\n").success();
+    assert_eq!(run.stdout, "a\u{2409}b\n");
+}
+
+#[test]
+fn bad_style() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--nonprint-style=octal"]);
+    assert!(run.stderr.starts_with("meow: invalid"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
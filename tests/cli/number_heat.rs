@@ -0,0 +1,65 @@
+use crate::util::Scratch;
+
+const TIMINGS: &str = "GET / 50ms\nGET /a 500ms\nGET /b 5000ms v1.2.3\n";
+
+#[test]
+fn numbers_colored_by_size() {
+    let scratch = Scratch::new();
+    scratch.write("times.log", "took 50\ntook 500\ntook 5000 at 10.0.0.1\n");
+    let run = scratch.run_tty(&["--number-heat", "times.log"]).success();
+    assert_eq!(run.stdout, "took 50\ntook \x1B[93m500\x1B[0m\ntook \x1B[31m5000\x1B[0m at 10.0.0.1\n");
+}
+
+#[test]
+fn units_and_thresholds() {
+    let scratch = Scratch::new();
+    scratch.write("times.log", TIMINGS);
+    let run = scratch.run_tty(&["--number-heat", "--heat-units", "times.log"]).success();
+    assert_eq!(run.stdout, "GET / 50ms\nGET /a \x1B[93m500ms\x1B[0m\nGET /b \x1B[31m5000ms\x1B[0m v1.2.3\n");
+    // Without --heat-units, 50ms is a word
+    let run = scratch.run_tty(&["--number-heat", "times.log"]).success();
+    assert_eq!(run.stdout, TIMINGS);
+    let run = scratch.run_tty(&["--number-heat", "--heat-units", "--heat-thresholds=10,100", "times.log"]).success();
+    assert_eq!(run.stdout.matches("\x1B[31m").count(), 2, "{:?}", run.stdout);
+    assert!(run.stdout.starts_with("GET / \x1B[93m50ms"), "{:?}", run.stdout);
+}
+
+#[test]
+fn with_grep() {
+    let scratch = Scratch::new();
+    scratch.write("times.log", TIMINGS);
+    let run = scratch.run_tty(&["--number-heat", "--heat-units", "--grep=/b", "times.log"]).success();
+    assert_eq!(run.stdout, "GET /b \x1B[31m5000ms\x1B[0m v1.2.3\n");
+}
+
+#[test]
+fn thresholds_from_the_file() {
+    let scratch = Scratch::new();
+    let values: String = (1..=100).map(|i| format!("{}\n", i)).collect();
+    scratch.write("values.txt", &values);
+    let run = scratch.run_tty(&["--number-heat", "--heat-auto", "values.txt"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines[88], "89");
+    assert!(lines[89].contains("90") && lines[89].starts_with('\x1B'), "{:?}", lines[89]);
+    assert_eq!(lines[99], "\x1B[31m100\x1B[0m");
+    // A pipe can't be read twice
+    let run = scratch.run_tty(&["--number-heat", "--heat-auto", "-"]).success();
+    assert_eq!(run.stderr, "meow: stdin: --heat-auto needs a regular file with numbers in it, using --heat-thresholds\n");
+    let run = scratch.run_tty(&["--number-heat", "--heat-auto", "--quiet", "-"]).success();
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn no_colors_off_the_terminal() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--number-heat"], TIMINGS).success();
+    assert_eq!(run.stdout, TIMINGS);
+}
+
+#[test]
+fn bad_thresholds() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--number-heat", "--heat-thresholds=1000,100"], "1\n");
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
@@ -0,0 +1,38 @@
+use crate::util::Scratch;
+
+fn lines(count: usize) -> String {
+    (1..=count).map(|i| format!("{}\n", i)).collect()
+}
+
+#[test]
+fn line_255_is_ff() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--number-hex"], lines(256)).success();
+    let shown: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(shown[0], "     1 | 1");
+    assert_eq!(shown[9], "     a | 10");
+    assert_eq!(shown[254], "    ff | 255");
+    assert_eq!(shown[255], "   100 | 256");
+}
+
+#[test]
+fn prefixed_with_0x() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--number-hex=0x"], lines(255)).success();
+    assert_eq!(run.stdout.lines().last(), Some("  0xff | 255"));
+}
+
+#[test]
+fn nonblank_numbering_counts_in_hex_too() {
+    let scratch = Scratch::new();
+    let input: String = (1..=11).map(|i| format!("{}\n\n", i)).collect();
+    let run = scratch.run_stdin(&["-b", "--number-hex"], input).success();
+    assert_eq!(run.stdout.lines().rfind(|line| line.trim() != "|"), Some("     b | 11"));
+}
+
+#[test]
+fn bad_prefix() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--number-hex=0o"]);
+    assert!(run.stderr.starts_with("meow: invalid --number-hex value '0o'"), "{}", run.stderr);
+}
@@ -0,0 +1,36 @@
+use crate::util::{Run, Scratch};
+
+fn paged(scratch: &Scratch, pager: &str, args: &[&str]) -> Run {
+    let mut command = scratch.command(args);
+    command.env("MEOW_PAGER", pager);
+    scratch.run_command(&mut command, b"")
+}
+
+#[test]
+fn content_goes_through_the_pager() {
+    let scratch = Scratch::new();
+    scratch.write("f.txt", "a\nb\n");
+    let pager = scratch.script("pager", "echo paged; cat\n");
+    let run = paged(&scratch, pager.to_str().unwrap(), &["--page", "-n", "f.txt"]).success();
+    assert_eq!(run.stdout, "paged\n     1 | a\n     2 | b\n");
+}
+
+#[test]
+fn missing_pager_falls_back_to_stdout() {
+    let scratch = Scratch::new();
+    scratch.write("f.txt", "a\nb\n");
+    let run = paged(&scratch, "/nonexistent/pager -R", &["--page", "f.txt"]).success();
+    assert_eq!(run.stdout, "a\nb\n");
+    assert_eq!(run.stderr, "meow: cannot start pager '/nonexistent/pager', showing the output here: No such file or directory (os error 2)\n");
+}
+
+#[test]
+fn pager_quit_early_is_a_normal_end() {
+    let scratch = Scratch::new();
+    let lines: String = (1..=200_000).map(|i| format!("{}\n", i)).collect();
+    scratch.write("big.txt", lines);
+    scratch.write("small.txt", "a\n");
+    let run = paged(&scratch, "head -1", &["--page", "big.txt", "small.txt"]).success();
+    assert_eq!(run.stdout, "\n===> big.txt <===\n1\n\n===> small.txt <===\na\n");
+    assert_eq!(run.stderr, "");
+}
@@ -0,0 +1,46 @@
+use crate::util::Scratch;
+
+fn fixture() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("README.md", "# Read me\n");
+    scratch.write("main.rs", "fn main() {}\n");
+    scratch.write("notes.txt", "notes\n");
+    scratch.write(".hidden", "secret\n");
+    scratch
+}
+
+#[test]
+fn type_to_filter_then_show_the_file() {
+    let scratch = fixture();
+    // The counter reads selected/candidates; hidden files aren't candidates
+    let run = scratch.run_tty_keys(&["-n", "--pick"], &[("1/3", "rea"), ("1/1", "\r"), ("any key", "q")]).success();
+    assert!(run.stdout.contains(" | # Read me"), "{:?}", run.stdout);
+    assert!(!run.stdout.contains("secret"));
+    // Left the alternate screen before showing the file
+    let shown = run.stdout.find("# Read me").unwrap();
+    assert!(run.stdout[..shown].contains("\x1B[?1049l"), "{:?}", run.stdout);
+}
+
+#[test]
+fn arrows_move_and_the_list_comes_back() {
+    let scratch = fixture();
+    // main.rs, README.md, notes.txt: shortest first, then by name
+    let run = scratch.run_tty_keys(&["--pick"], &[("1/3", "\x1B[B"), ("2/3", "\r"), ("any key", " "), ("2/3", "\x1B")]).success();
+    assert!(run.stdout.contains("# Read me"), "{:?}", run.stdout);
+    assert!(!run.stdout.contains("fn main() {}"));
+}
+
+#[test]
+fn esc_quits_at_once() {
+    let scratch = fixture();
+    let run = scratch.run_tty_keys(&["--pick"], &[("1/3", "\x1B")]).success();
+    assert!(!run.stdout.contains("Read me"));
+    assert!(run.stdout.ends_with("\x1B[?1049l"), "{:?}", run.stdout);
+}
+
+#[test]
+fn needs_a_terminal() {
+    let scratch = fixture();
+    let run = scratch.run(&["--pick"]).failure();
+    assert_eq!(run.stderr, "meow: --pick needs a terminal for input and output\n");
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::util::{write_later, Scratch};
+
+/// A config file with `rules` as its `[preprocessors]` section.
+fn config(scratch: &Scratch, rules: &str) {
+    scratch.write(".config/meow/config.toml", format!("[preprocessors]\n{}", rules));
+}
+
+#[test]
+fn preprocessor_output_is_shown() {
+    let scratch = Scratch::new();
+    let upper = scratch.script("upper", "tr a-z A-Z < \"$1\"\n");
+    config(&scratch, &format!("txt = \"{} {{}}\"\n", upper.display()));
+    scratch.write("my notes.txt", "hello\n");
+    let run = scratch.run(&["-n", "my notes.txt"]).success();
+    assert!(run.stdout.contains(&format!("===> my notes.txt ({}) <===", upper.display())), "{}", run.stdout);
+    assert!(run.stdout.ends_with("     1 | HELLO\n"), "{}", run.stdout);
+}
+
+#[test]
+fn preprocessor_matched_by_mime_type() {
+    let scratch = Scratch::new();
+    let fake = scratch.script("pdftotext", "echo converted\n");
+    config(&scratch, &format!("\"application/pdf\" = \"{} {{}} -\"\n", fake.display()));
+    scratch.write("paper", "%PDF-1.4\nbinary\n");
+    let run = scratch.run(&["paper"]).success();
+    assert!(run.stdout.ends_with("converted\n"), "{}", run.stdout);
+    let run = scratch.run(&["--no-preprocess", "paper"]).success();
+    assert_eq!(run.stdout, "%PDF-1.4\nbinary\n");
+}
+
+#[test]
+fn preprocessor_without_shell_keeps_the_name_one_argument() {
+    let scratch = Scratch::new();
+    let args = scratch.script("args", "echo \"$#: $1\"\n");
+    config(&scratch, &format!("txt = \"{} {{}}\"\n", args.display()));
+    scratch.write("a; rm -rf $HOME.txt", "");
+    let run = scratch.run(&["a; rm -rf $HOME.txt"]).success();
+    assert!(run.stdout.ends_with("1: a; rm -rf $HOME.txt\n"), "{}", run.stdout);
+}
+
+#[test]
+fn preprocessor_with_shell_quotes_the_name() {
+    let scratch = Scratch::new();
+    config(&scratch, "[preprocessors.txt]\ncommand = \"wc -c < {} | tr -d ' '\"\nshell = true\n");
+    scratch.write("it's here.txt", "12345\n");
+    let run = scratch.run(&["it's here.txt"]).success();
+    assert!(run.stdout.ends_with("6\n"), "{}", run.stdout);
+}
+
+#[test]
+fn preprocessor_exiting_with_an_error_fails_the_file() {
+    let scratch = Scratch::new();
+    let broken = scratch.script("broken", "echo partial; exit 3\n");
+    config(&scratch, &format!("txt = \"{} {{}}\"\n", broken.display()));
+    scratch.write("a.txt", "text\n");
+    let run = scratch.run(&["a.txt"]).failure();
+    assert!(!run.stdout.contains("partial"), "{}", run.stdout);
+    assert!(run.stderr.contains("a.txt: binary file not shown (preprocessor"), "{}", run.stderr);
+    let summary = scratch.run(&["--summary", "a.txt"]).failure();
+    let row = summary.stdout.lines().find(|line| line.starts_with("a.txt")).unwrap();
+    // name, size, read, shown, errors
+    assert_eq!(row.split_whitespace().nth(5), Some("1"), "{}", summary.stdout);
+}
+
+#[test]
+fn preprocessor_that_cannot_start_fails_the_file() {
+    let scratch = Scratch::new();
+    config(&scratch, "txt = \"/nonexistent/program {}\"\n");
+    scratch.write("a.txt", "text\n");
+    scratch.write("b.md", "other\n");
+    let run = scratch.run(&["a.txt", "b.md"]).failure();
+    assert!(run.stderr.contains("a.txt: binary file not shown (preprocessor /nonexistent/program failed)"), "{}", run.stderr);
+    assert!(run.stdout.ends_with("other\n"), "{}", run.stdout);
+}
+
+#[test]
+fn preprocessors_do_not_sniff_fifos() {
+    let scratch = Scratch::new();
+    config(&scratch, "\"application/pdf\" = \"false\"\n");
+    let fifo = scratch.mkfifo("fifo");
+    let writer = write_later(&fifo, Duration::from_millis(100), "%PDF-1.4\n");
+    let run = scratch.run(&["fifo"]).success();
+    writer.join().unwrap();
+    assert_eq!(run.stdout, "%PDF-1.4\n");
+}
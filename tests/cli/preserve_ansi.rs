@@ -0,0 +1,36 @@
+use crate::util::Scratch;
+
+const COLORED: &str = "\x1B[31mred\nstill red\x1B[0m\nplain\n";
+
+#[test]
+fn color_comes_back_after_the_number() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--preserve-ansi"], COLORED).success();
+    assert_eq!(run.stdout, "     1 | \x1B[31mred\x1B[0m\n     2 | \x1B[31mstill red\x1B[0m\n     3 | plain\n");
+}
+
+#[test]
+fn without_it_the_color_runs_into_the_gutter() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n"], COLORED).success();
+    assert_eq!(run.stdout, "     1 | \x1B[31mred\n     2 | still red\x1B[0m\n     3 | plain\n");
+}
+
+#[test]
+fn colored_gutter_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("c.txt", COLORED);
+    let run = scratch.run_tty(&["-n", "--preserve-ansi", "--color-depth=16", "c.txt"]).success();
+    assert_eq!(run.stdout, concat!(
+        "\x1B[33m     1\x1B[0m | \x1B[31mred\x1B[0m\n",
+        "\x1B[33m     2\x1B[0m | \x1B[31mstill red\x1B[0m\n",
+        "\x1B[33m     3\x1B[0m | plain\n",
+    ));
+}
+
+#[test]
+fn lines_not_shown_still_set_the_style() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--preserve-ansi", "--grep=match"], "\x1B[1;32mhidden start\nmatch me\x1B[0m\nmatch plain\n").success();
+    assert_eq!(run.stdout, "     1 | \x1B[1;32mmatch me\x1B[0m\n     2 | match plain\n");
+}
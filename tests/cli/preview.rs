@@ -0,0 +1,44 @@
+use crate::util::Scratch;
+
+fn numbers(count: usize) -> String {
+    (1..=count).map(|i| format!("{}\n", i)).collect()
+}
+
+#[test]
+fn preview_counts_the_skipped_lines() {
+    let scratch = Scratch::new();
+    scratch.write("twenty.txt", numbers(20));
+    let run = scratch.run(&["--preview=3", "twenty.txt"]).success();
+    assert_eq!(run.stdout, "1\n2\n3\n... 14 lines skipped ...\n18\n19\n20\n");
+}
+
+#[test]
+fn preview_numbers_are_absolute() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--preview=2"], numbers(7)).success();
+    assert_eq!(run.stdout, "     1 | 1\n     2 | 2\n... 3 lines skipped ...\n     6 | 6\n     7 | 7\n");
+}
+
+#[test]
+fn preview_of_a_short_input_shows_it_all() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--preview=3"], numbers(6)).success();
+    assert_eq!(run.stdout, numbers(6));
+    let run = scratch.run_stdin(&["--preview=3"], "").success();
+    assert_eq!(run.stdout, "");
+}
+
+#[test]
+fn preview_then_grep() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--preview=2", "--grep=1"], numbers(20)).success();
+    assert_eq!(run.stdout, "1\n... 16 lines skipped ...\n19\n");
+}
+
+#[test]
+fn preview_of_zero_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--preview=0"], "a\n");
+    assert!(run.stderr.starts_with("meow: "), "{}", run.stderr);
+    assert!(run.stderr.contains("--preview"), "{}", run.stderr);
+}
@@ -0,0 +1,59 @@
+use crate::util::Scratch;
+
+/// A scratch directory with a `[profiles]` config and one file of each kind.
+fn fixture() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(".config/meow/config.toml", "\
+[profiles]
+\"*.rs\" = \"-n\"
+\"*.csv\" = [\"--fields=2\", \"--delimiter=,\"]
+Makefile = \"-T\"
+");
+    scratch.write("main.rs", "fn main() {}\n");
+    scratch.write("data.csv", "a,b,c\n1,2,3\n");
+    scratch.write("Makefile", "all:\n\tcc main.c\n");
+    scratch.write("notes.txt", "plain\ttext\n");
+    scratch
+}
+
+#[test]
+fn each_file_gets_its_profile() {
+    let scratch = fixture();
+    let run = scratch.run(&["--flatten", "main.rs", "data.csv", "Makefile", "notes.txt"]).success();
+    assert_eq!(run.stdout, "     1 | fn main() {}\nb\n2\nall:\n^Icc main.c\nplain\ttext\n");
+}
+
+#[test]
+fn command_line_wins() {
+    let scratch = fixture();
+    let run = scratch.run(&["--fields=1", "data.csv"]).success();
+    assert_eq!(run.stdout, "a\n1\n");
+}
+
+#[test]
+fn no_profiles_turns_them_off() {
+    let scratch = fixture();
+    let run = scratch.run(&["--no-profiles", "--flatten", "main.rs", "data.csv"]).success();
+    assert_eq!(run.stdout, "fn main() {}\na,b,c\n1,2,3\n");
+}
+
+#[test]
+fn meta_names_the_profile() {
+    let scratch = fixture();
+    let run = scratch.run(&["--meta", "main.rs", "notes.txt"]).success();
+    let headers: Vec<&str> = run.stdout.lines().filter(|line| line.contains("<===")).collect();
+    assert_eq!(headers.len(), 2, "{}", run.stdout);
+    assert!(headers[0].contains("[profile *.rs]"), "{}", headers[0]);
+    assert!(!headers[1].contains("profile"), "{}", headers[1]);
+}
+
+#[test]
+fn invalid_profile_is_reported_once() {
+    let scratch = Scratch::new();
+    scratch.write(".config/meow/config.toml", "[profiles]\n\"*.txt\" = \"--fold=x\"\n");
+    scratch.write("a.txt", "a\n");
+    scratch.write("b.txt", "b\n");
+    let run = scratch.run(&["--flatten", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, "a\nb\n");
+    assert_eq!(run.stderr.matches("profiles.*.txt: invalid options, profile ignored").count(), 1, "{}", run.stderr);
+}
@@ -0,0 +1,37 @@
+use crate::util::Scratch;
+
+#[cfg(feature = "qr")]
+#[test]
+fn qr_draws_a_square_of_half_blocks() {
+    let scratch = Scratch::new();
+    scratch.write("wifi.txt", "WIFI:S:home;T:WPA;P:hunter2;;");
+    let run = scratch.run(&["--qr", "wifi.txt"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    let width = lines[0].chars().count();
+    assert!(width <= 80);
+    assert_eq!(lines.len(), width.div_ceil(2));
+    assert!(lines.iter().all(|line| line.chars().count() == width && line.chars().all(|c| " \u{2580}\u{2584}\u{2588}".contains(c))));
+    // The quiet zone is light, which is filled unless inverted
+    assert!(lines[0].chars().all(|c| c == '\u{2588}'));
+    let inverted = scratch.run(&["--qr-invert", "wifi.txt"]).success();
+    assert!(inverted.stdout.lines().next().unwrap().chars().all(|c| c == ' '));
+}
+
+#[cfg(feature = "qr")]
+#[test]
+fn qr_refuses_what_does_not_fit() {
+    let scratch = Scratch::new();
+    scratch.write("big.txt", "x".repeat(3000));
+    let run = scratch.run(&["--qr", "big.txt"]).failure();
+    assert!(run.stdout.is_empty());
+    assert!(run.stderr.starts_with("meow: big.txt: 3000 bytes do not fit in a QR code at this terminal width (max "), "{}", run.stderr);
+}
+
+#[cfg(not(feature = "qr"))]
+#[test]
+fn qr_needs_the_feature() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "hello");
+    let run = scratch.run(&["--qr", "a.txt"]).failure();
+    assert!(run.stderr.contains("rebuild with --features qr"), "{}", run.stderr);
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::util::Scratch;
+
+const ART: &[u8] = include_bytes!("../fixtures/cat.ans");
+
+const CAST_V2: &str = "{\"version\": 2, \"width\": 80, \"height\": 24}\n\
+                       [0.0, \"o\", \"\\u001b[32m$ \\u001b[0m\"]\n\
+                       [0.1, \"i\", \"ls\\r\"]\n\
+                       [0.4, \"o\", \"ls\\r\\n\"]\n\
+                       [0.5, \"r\", \"100x30\"]\n\
+                       [0.6, \"o\", \"a.txt\\r\\n\"]\n";
+
+#[test]
+fn ansi_art_round_trips() {
+    let scratch = Scratch::new();
+    scratch.write("cat.ans", ART);
+    for args in [&["--raw-passthrough", "cat.ans"][..], &["--raw-passthrough", "-n", "--safe", "--grep=x", "cat.ans"]] {
+        let output = scratch.command(args).output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, ART, "{:?}", args);
+    }
+}
+
+#[test]
+fn ansi_art_from_stdin_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("cat.ans", ART);
+    let output = scratch.command(&["--raw-passthrough"]).stdin(std::fs::File::open(scratch.path("cat.ans")).unwrap()).output().unwrap();
+    assert_eq!(output.stdout, ART);
+    // Not even the terminal gets anything added
+    let run = scratch.run_tty(&["--raw-passthrough", "cat.ans"]).success();
+    assert!(run.stdout.starts_with("\x1B[0m\x1B[2J\x1B[H\x1B[1;31m"), "{:?}", run.stdout);
+    assert!(run.stdout.ends_with("SAUCE00cat art                            "), "{:?}", run.stdout);
+}
+
+#[test]
+fn cast_plays_output_events_in_time() {
+    let scratch = Scratch::new();
+    scratch.write("demo.cast", CAST_V2);
+    let start = Instant::now();
+    let run = scratch.run(&["--raw-passthrough=timed", "demo.cast"]).success();
+    assert!(start.elapsed() >= Duration::from_millis(600));
+    assert_eq!(run.stdout, "\x1B[32m$ \x1B[0mls\r\na.txt\r\n");
+
+    let start = Instant::now();
+    scratch.run(&["--raw-passthrough=timed", "--replay-speed=4x", "demo.cast"]).success();
+    assert!(start.elapsed() < Duration::from_millis(550));
+}
+
+#[test]
+fn cast_version_three_counts_from_the_last_event() {
+    let scratch = Scratch::new();
+    scratch.write("demo.cast", "{\"version\": 3, \"term\": {\"cols\": 80, \"rows\": 24}}\n[0.2, \"o\", \"one \"]\n[0.2, \"o\", \"two\"]\n");
+    let start = Instant::now();
+    let run = scratch.run(&["--raw-passthrough=timed", "demo.cast"]).success();
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert_eq!(run.stdout, "one two");
+}
+
+#[test]
+fn timed_copies_anything_else() {
+    let scratch = Scratch::new();
+    scratch.write("cat.ans", ART);
+    let output = scratch.command(&["--raw-passthrough=timed", "cat.ans"]).output().unwrap();
+    assert_eq!(output.stdout, ART);
+}
+
+#[test]
+fn broken_cast_event() {
+    let scratch = Scratch::new();
+    scratch.write("bad.cast", "{\"version\": 2}\n[0.0, \"o\", \"ok\"]\nnot an event\n");
+    let run = scratch.run(&["--raw-passthrough=timed", "bad.cast"]).failure();
+    assert_eq!(run.stdout, "ok");
+    assert_eq!(run.stderr, "meow: bad.cast: line 3 is not an asciinema event\n");
+}
@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+use crate::util::Scratch;
+
+const COMMANDS: [&str; 3] = ["cat a.txt", "help", "exit"];
+
+/// Records a session running `COMMANDS` and returns what it showed once the
+/// shell had started.
+fn record(scratch: &Scratch) -> String {
+    scratch.write("a.txt", "one\n\x1B[31mtwo\x1B[0m\n");
+    let input = COMMANDS.iter().map(|command| format!("{}\n", command)).collect::<String>();
+    let run = scratch.run_stdin(&["-i", "--record=session.meowrec", "a.txt"], input).success();
+    run.stdout.strip_prefix("one\n\x1B[31mtwo\x1B[0m\n").unwrap().to_string()
+}
+
+#[test]
+fn replay_reproduces_the_session() {
+    let scratch = Scratch::new();
+    let live = record(&scratch);
+    // What was typed shows after each prompt, where the terminal echoed it
+    let mut expected = String::new();
+    let mut commands = COMMANDS.iter();
+    for (i, part) in live.split("meow> ").enumerate() {
+        if i > 0 {
+            expected.push_str("meow> ");
+            if let Some(command) = commands.next() {
+                expected.push_str(command);
+                expected.push('\n');
+            }
+        }
+        expected.push_str(part);
+    }
+    let run = scratch.run(&["--replay=session.meowrec", "--replay-instant"]).success();
+    assert_eq!(run.stdout, expected);
+    assert!(run.stdout.contains("\x1B[31mtwo\x1B[0m"));
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn replay_runs_nothing() {
+    let scratch = Scratch::new();
+    scratch.write("session.meowrec", "meowrec 1\nc 0 13\nrm victim.txt\no 0 6\nmeow> \n");
+    scratch.write("victim.txt", "still here\n");
+    let run = scratch.run(&["-i", "--replay=session.meowrec", "--replay-instant"]).success();
+    assert_eq!(run.stdout, "rm victim.txt\nmeow> ");
+    assert_eq!(scratch.read("victim.txt"), "still here\n");
+}
+
+#[test]
+fn replay_keeps_the_timing() {
+    let scratch = Scratch::new();
+    scratch.write("session.meowrec", "meowrec 1\no 0 6\nfirst\n\no 600 7\nsecond\n\n");
+    let start = Instant::now();
+    assert_eq!(scratch.run(&["--replay=session.meowrec"]).success().stdout, "first\nsecond\n");
+    assert!(start.elapsed() >= Duration::from_millis(600));
+
+    let start = Instant::now();
+    scratch.run(&["--replay=session.meowrec", "--replay-speed=4x"]).success();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(150) && elapsed < Duration::from_millis(550), "{:?}", elapsed);
+
+    let start = Instant::now();
+    scratch.run(&["--replay=session.meowrec", "--replay-instant"]).success();
+    assert!(start.elapsed() < Duration::from_millis(550));
+}
+
+#[test]
+fn crashed_session_replays_to_the_crash() {
+    let scratch = Scratch::new();
+    record(&scratch);
+    let whole = std::fs::read(scratch.path("session.meowrec")).unwrap();
+    let full = scratch.run(&["--replay=session.meowrec", "--replay-instant"]).success().stdout;
+    // Cut in the middle of the last frame
+    scratch.write("cut.meowrec", &whole[..whole.len() - 3]);
+    let run = scratch.run(&["--replay=cut.meowrec", "--replay-instant"]).success();
+    assert!(full.starts_with(&run.stdout) && run.stdout.len() < full.len());
+    assert!(run.stdout.contains("Available commands:"), "{}", run.stdout);
+    assert_eq!(run.stderr, "meow: cut.meowrec ends in the middle of a frame, the rest was lost\n");
+}
+
+#[test]
+fn not_a_recording() {
+    let scratch = Scratch::new();
+    scratch.write("notes.txt", "hello\n");
+    let run = scratch.run(&["--replay=notes.txt"]).failure();
+    assert_eq!(run.stderr, "meow: notes.txt is not a meow recording\n");
+    scratch.write("future.meowrec", "meowrec 9\n");
+    let run = scratch.run(&["--replay=future.meowrec"]).failure();
+    assert!(run.stderr.contains("format version 9"), "{}", run.stderr);
+    let run = scratch.run(&["--replay=missing.meowrec"]).failure();
+    assert!(run.stderr.contains("missing.meowrec"), "{}", run.stderr);
+}
+
+#[test]
+fn record_needs_the_shell() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record=session.meowrec"], "a\n");
+    assert!(run.stderr.contains("add --interactive"), "{}", run.stderr);
+    assert!(!scratch.path("session.meowrec").exists());
+}
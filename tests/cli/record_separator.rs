@@ -0,0 +1,55 @@
+use crate::util::Scratch;
+
+#[test]
+fn records_split_on_a_separator() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=;"], "alpha;beta;gamma").success();
+    assert_eq!(run.stdout, "alpha\nbeta\ngamma\n");
+}
+
+#[test]
+fn line_options_work_on_records() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=;", "-n"], "alpha;beta;gamma;").success();
+    assert_eq!(run.stdout, "     1 | alpha\n     2 | beta\n     3 | gamma\n");
+    let run = scratch.run_stdin(&["--record-separator=;", "--grep=a"], "alpha;beta;omicron;gamma").success();
+    assert_eq!(run.stdout, "alpha\nbeta\ngamma\n");
+}
+
+#[test]
+fn old_mac_line_ends() {
+    let scratch = Scratch::new();
+    scratch.write("mac.txt", "first\rsecond\rthird\r");
+    let run = scratch.run(&["--record-separator=\\r", "-n", "mac.txt"]).success();
+    assert_eq!(run.stdout, "     1 | first\n     2 | second\n     3 | third\n");
+}
+
+#[test]
+fn nul_separated_names() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=\\0", "--grep=.log", "--null"], "a.log\0b.txt\0with\nnewline.log\0").success();
+    assert_eq!(run.stdout, "a.log\0with\nnewline.log\0");
+}
+
+#[test]
+fn multibyte_separator() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=\\x1e\\x1e"], "one\x1etwo\x1e\x1ethree").success();
+    assert_eq!(run.stdout, "one\x1etwo\nthree\n");
+}
+
+#[test]
+fn empty_separator_is_refused() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator="], "a;b\n");
+    assert!(run.stderr.contains("meow: the record separator can't be empty"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
+
+#[test]
+fn separator_across_the_byte_cap() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=<>", "--max-line-bytes=2"], "a<>bc<>de<>fgh").success();
+    assert_eq!(run.stdout, "a\nbc\nde\nfg\nh\n");
+    assert_eq!(run.stderr, "meow: stdin: lines longer than 2 bytes were split\n");
+}
@@ -0,0 +1,63 @@
+use std::os::unix::fs::symlink;
+
+use crate::util::Scratch;
+
+fn fixture(scratch: &Scratch) {
+    scratch.write("tr/top.txt", "1\n");
+    scratch.write("tr/a/mid.txt", "2\n");
+    scratch.write("tr/a/b/deep.txt", "3\n");
+}
+
+#[test]
+fn recursive_gives_each_file_a_header() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "tr"]).success();
+    assert_eq!(run.stdout, "\n===> tr/a/b/deep.txt <===\n3\n\n===> tr/a/mid.txt <===\n2\n\n===> tr/top.txt <===\n1\n");
+}
+
+#[test]
+fn flatten_is_one_stream() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--flatten", "tr"]).success();
+    assert_eq!(run.stdout, "3\n2\n1\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn tree_draws_the_directories() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--tree", "tr"]).success();
+    assert_eq!(run.stdout, "\
+tr/
+├── a/
+│   ├── b/
+│   │   └── deep.txt
+3
+│   └── mid.txt
+2
+└── top.txt
+1
+");
+}
+
+#[test]
+fn tree_leaves_out_excluded_files() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    let run = scratch.run(&["-R", "--tree", "--exclude=*mid*", "tr"]).success();
+    assert_eq!(run.stdout, "tr/\n├── a/\n│   └── b/\n│       └── deep.txt\n3\n└── top.txt\n1\n");
+}
+
+#[test]
+fn recursive_does_not_follow_directory_links() {
+    let scratch = Scratch::new();
+    fixture(&scratch);
+    symlink(".", scratch.path("tr/a/loop")).unwrap();
+    symlink("mid.txt", scratch.path("tr/a/link.txt")).unwrap();
+    let run = scratch.run(&["-R", "--flatten", "tr"]).success();
+    assert_eq!(run.stdout, "3\n2\n2\n1\n");
+    assert_eq!(run.stderr, "");
+}
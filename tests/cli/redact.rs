@@ -0,0 +1,48 @@
+use crate::util::Scratch;
+
+const CONFIG: &str = "\
+user = admin
+password = hunter2
+contact = cat@example.com
+";
+
+#[test]
+fn masks_secrets_and_counts_them() {
+    let scratch = Scratch::new();
+    scratch.write("app.conf", CONFIG);
+    let run = scratch.run(&["--redact", "app.conf"]).success();
+    assert_eq!(run.stdout, "user = admin\npassword = ████\ncontact = cat@example.com\n");
+    assert_eq!(run.stderr, "meow: app.conf: redacted values: 1\n");
+
+    let run = scratch.run(&["--redact=pii", "app.conf"]).success();
+    assert_eq!(run.stdout, "user = admin\npassword = ████\ncontact = ████\n");
+    assert_eq!(run.stderr, "meow: app.conf: redacted values: 2\n");
+}
+
+#[test]
+fn grep_never_sees_the_secret() {
+    let scratch = Scratch::new();
+    scratch.write("app.conf", CONFIG);
+    let run = scratch.run(&["--redact", "-g", "hunter2", "app.conf"]).success();
+    assert_eq!(run.stdout, "");
+    let run = scratch.run(&["--redact", "-q", "-g", "password", "app.conf"]).success();
+    assert_eq!(run.stdout, "password = ████\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn hash_style_and_custom_patterns() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--redact-style=hash", "--redact-pattern=id=(\\d+)"], "id=7 token=abc\nid=7\n").success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert!(lines[0].starts_with("id=\u{27E8}redacted:"), "{}", run.stdout);
+    assert_eq!(lines[0].split(' ').next(), Some(lines[1]));
+    assert!(!run.stdout.contains("abc"));
+}
+
+#[test]
+fn bad_style() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--redact-style=stars"]);
+    assert!(run.stderr.starts_with("meow: invalid redaction style 'stars'"), "{}", run.stderr);
+}
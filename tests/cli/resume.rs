@@ -0,0 +1,70 @@
+use std::fs;
+
+use crate::util::Scratch;
+
+#[test]
+fn resume_continues_after_the_last_run() {
+    let scratch = Scratch::new();
+    let path = scratch.write("app.log", "a\nb\n");
+    let run = scratch.run(&["--resume", "app.log"]).success();
+    assert_eq!(run.stdout, "a\nb\n");
+
+    fs::write(&path, "a\nb\nc\nd\n").unwrap();
+    let run = scratch.run(&["-n", "--resume", "app.log"]).success();
+    // Numbers stay those of the whole file
+    assert_eq!(run.stdout, "\u{00B7}\u{00B7}\u{00B7} resumed at line 3\n     3 | c\n     4 | d\n");
+    let run = scratch.run(&["--resume", "app.log"]).success();
+    assert_eq!(run.stdout, "\u{00B7}\u{00B7}\u{00B7} resumed at line 5\n");
+}
+
+#[test]
+fn resume_keeps_an_unfinished_line_for_next_time() {
+    let scratch = Scratch::new();
+    let path = scratch.write("app.log", "a\npart");
+    scratch.run(&["--resume", "app.log"]).success();
+    fs::write(&path, "a\npartial\n").unwrap();
+    let run = scratch.run(&["--resume", "app.log"]).success();
+    assert_eq!(run.stdout, "\u{00B7}\u{00B7}\u{00B7} resumed at line 2\npartial\n");
+}
+
+#[test]
+fn resume_starts_over_when_the_file_shrank() {
+    let scratch = Scratch::new();
+    let path = scratch.write("app.log", "a\nb\nc\n");
+    scratch.run(&["--resume", "app.log"]).success();
+    fs::write(&path, "x\n").unwrap();
+    let run = scratch.run(&["--resume", "app.log"]).success();
+    assert_eq!(run.stdout, "x\n");
+}
+
+#[test]
+fn resume_state_lives_under_xdg_data_home() {
+    let scratch = Scratch::new();
+    scratch.write("app.log", "a\n");
+    scratch.run_command(scratch.command(&["--resume", "app.log"]).env("XDG_DATA_HOME", scratch.path("data")), b"").success();
+    let state = scratch.read("data/meow/resume");
+    assert!(state.starts_with("2\t1\t"), "{}", state);
+    assert!(state.trim_end().ends_with("app.log"), "{}", state);
+    assert!(!scratch.path(".local/share/meow/resume").exists());
+}
+
+#[test]
+fn resume_clear_forgets_the_position() {
+    let scratch = Scratch::new();
+    scratch.write("app.log", "a\nb\n");
+    scratch.write("other.log", "c\n");
+    scratch.run(&["--resume", "app.log", "other.log"]).success();
+    scratch.run(&["--resume=clear", "app.log"]).success();
+    let run = scratch.run(&["--resume", "app.log"]).success();
+    assert_eq!(run.stdout, "a\nb\n");
+    // Other files keep theirs
+    let run = scratch.run(&["--resume", "other.log"]).success();
+    assert_eq!(run.stdout, "\u{00B7}\u{00B7}\u{00B7} resumed at line 2\n");
+}
+
+#[test]
+fn resume_rejects_other_values() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--resume=bogus"]);
+    assert!(run.stderr.starts_with("meow: invalid value 'bogus' for --resume (only 'clear' is accepted)"), "{}", run.stderr);
+}
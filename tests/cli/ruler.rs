@@ -0,0 +1,47 @@
+use crate::util::Scratch;
+
+const TENS: &str = "         1         2         3         4         5         6         7         8";
+
+fn units(width: usize) -> String {
+    (1..=width).map(|column| char::from(b'0' + (column % 10) as u8)).collect()
+}
+
+#[test]
+fn ruler_above_the_content() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--ruler"], "hello\n").success();
+    assert_eq!(run.stdout, format!("{}\n{}\nhello\n", TENS, units(80)));
+}
+
+#[test]
+fn ruler_lines_up_after_the_gutter() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--ruler-every=2"], "a\nb\nc\n").success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    let gutter = lines[2].find('a').unwrap();
+    assert_eq!(lines[1], format!("{}{}", " ".repeat(gutter), units(80 - gutter)));
+    assert_eq!(lines[3].find('b'), Some(gutter));
+    assert_eq!(lines[4..6], lines[0..2]);
+    assert_eq!(lines.len(), 7);
+}
+
+#[test]
+fn max_col_without_colors_marks_the_cut() {
+    let scratch = Scratch::new();
+    let input = "short\n123456789012\n\tab\n日本語\n";
+    let run = scratch.run_stdin(&["--max-col=10"], input).success();
+    // The tab takes columns 1-8, 日本語 1-6
+    assert_eq!(run.stdout, "short\n1234567890>12\n\tab\n日本語\n");
+
+    // Two tabs of 4 reach column 8; in the second line 本 takes 9-10, past 9
+    let run = scratch.run_stdin(&["--max-col=9", "--tab-width=4"], "\t\tabc\n日本語日本語\n").success();
+    assert_eq!(run.stdout, "\t\ta>bc\n日本語日>本語\n");
+}
+
+#[test]
+fn max_col_paints_the_rest_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "abcdef\n");
+    let run = scratch.run_tty(&["--max-col=4", "a.txt"]).success();
+    assert!(run.stdout.contains("abcd\x1B[31mef\x1B[0m"), "{:?}", run.stdout);
+}
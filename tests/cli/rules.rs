@@ -0,0 +1,66 @@
+use crate::util::Scratch;
+
+const NGINX_RULES: &str = include_str!("../fixtures/rules/nginx.toml");
+const NGINX_LOG: &str = include_str!("../fixtures/nginx-access.log");
+/// What the nginx rules make of the log on a terminal, with `\e` for ESC.
+const NGINX_OUTPUT: &str = include_str!("../fixtures/nginx-access.rules.out");
+
+#[test]
+fn nginx_rules_snapshot() {
+    let scratch = Scratch::new();
+    scratch.write("nginx.toml", NGINX_RULES);
+    scratch.write("access.log", NGINX_LOG);
+    let run = scratch.run_tty(&["--rules=nginx.toml", "access.log"]).success();
+    assert_eq!(run.stdout.replace('\x1B', "\\e"), NGINX_OUTPUT);
+}
+
+#[test]
+fn rules_found_by_name_in_the_config_directory() {
+    let scratch = Scratch::new();
+    scratch.write(".config/meow/rules/nginx.toml", NGINX_RULES);
+    scratch.write("access.log", NGINX_LOG);
+    let run = scratch.run_tty(&["--rules=nginx", "access.log"]).success();
+    assert_eq!(run.stdout.replace('\x1B', "\\e"), NGINX_OUTPUT);
+}
+
+#[test]
+fn without_colors_only_the_actions_remain() {
+    let scratch = Scratch::new();
+    scratch.write("nginx.toml", NGINX_RULES);
+    scratch.write("access.log", NGINX_LOG);
+    let run = scratch.run(&["--rules=nginx.toml", "access.log"]).success();
+    assert!(!run.stdout.contains('\x1B'), "{:?}", run.stdout);
+    assert!(!run.stdout.contains("health"), "{}", run.stdout);
+    assert_eq!(run.stdout.lines().count(), 5);
+    let marked: Vec<&str> = run.stdout.lines().filter(|line| line.starts_with("> ")).collect();
+    assert_eq!(marked.len(), 1);
+    assert!(marked[0].contains("\" 502 "), "{}", marked[0]);
+}
+
+#[test]
+fn stop_skips_later_rules() {
+    let scratch = Scratch::new();
+    scratch.write("stop.toml", "[[rule]]\npattern = 'keep'\naction = 'stop'\n\n[[rule]]\npattern = 'drop'\naction = 'hide'\n");
+    let run = scratch.run_stdin(&["--rules=stop.toml"], "keep drop\ndrop\nother\n").success();
+    assert_eq!(run.stdout, "keep drop\nother\n");
+}
+
+#[test]
+fn errors_name_the_rule() {
+    let scratch = Scratch::new();
+    scratch.write("bad.toml", "[[rule]]\npattern = 'fine'\n\n[[rule]]\npattern = '(unclosed'\ncolor = 'red'\n");
+    let run = scratch.run_stdin(&["--rules=bad.toml"], "text\n").failure();
+    assert!(run.stderr.contains("rule 2: invalid regex '(unclosed'"), "{}", run.stderr);
+    assert_eq!(run.stdout, "");
+
+    scratch.write("color.toml", "[[rule]]\npattern = 'x'\ncolor = 'mauve'\n");
+    let run = scratch.run_stdin(&["--rules=color.toml"], "text\n").failure();
+    assert!(run.stderr.contains("rule 1: unknown color 'mauve'"), "{}", run.stderr);
+}
+
+#[test]
+fn missing_rules_file() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--rules=nowhere"], "text\n").failure();
+    assert!(run.stderr.contains("nowhere"), "{}", run.stderr);
+}
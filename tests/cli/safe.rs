@@ -0,0 +1,55 @@
+use crate::util::Scratch;
+
+/// A window title change, the alternate screen (DECSET 1049) and a color.
+const HOSTILE: &str = "title \x1B]0;pwned\x07 here\n\x1B[?1049h alt screen\n\x1B[31mred\x1B[0m\n";
+const ESCAPED: &str = "title ^[]0;pwned\x07 here\n^[[?1049h alt screen\n^[[31mred^[[0m\n";
+
+#[test]
+fn suspicious_sequences_turn_escaping_on() {
+    let scratch = Scratch::new();
+    scratch.write("hostile.txt", HOSTILE);
+    let run = scratch.run_tty(&["hostile.txt"]).success();
+    assert_eq!(run.stdout, ESCAPED);
+    assert_eq!(run.stderr, "\x1B[31mmeow: hostile.txt: contains terminal control sequences, showing them escaped (--unsafe to pass them through)\x1B[0m\n\
+                           meow: hostile.txt: neutralized 4 escape sequences\n");
+}
+
+#[test]
+fn colors_alone_pass_through() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&[] as &[&str], "\x1B[31mred\x1B[0m\n").success();
+    assert_eq!(run.stdout, "\x1B[31mred\x1B[0m\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn safe_escapes_colors_too() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--safe"], "\x1B[31mred\x1B[0m\n").success();
+    assert_eq!(run.stdout, "^[[31mred^[[0m\n");
+    assert_eq!(run.stderr, "meow: stdin: neutralized 2 escape sequences\n");
+    let run = scratch.run_stdin(&["--safe", "--quiet"], "\x1B[31mred\x1B[0m\n").success();
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn unsafe_passes_everything_through() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--unsafe"], HOSTILE).success();
+    assert_eq!(run.stdout, HOSTILE);
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn c1_controls_are_neutralized() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&[] as &[&str], "a\u{9B}2Jb\n").success();
+    assert_eq!(run.stdout, "a^[[2Jb\n");
+}
+
+#[test]
+fn sequences_split_across_lines_are_still_escaped() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--safe"], "\x1B]0;half\n\x07rest\n").success();
+    assert_eq!(run.stdout, "^[]0;half\n\x07rest\n");
+}
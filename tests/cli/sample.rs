@@ -0,0 +1,73 @@
+use crate::util::Scratch;
+
+fn numbers(n: usize) -> String {
+    (1..=n).map(|i| format!("{}\n", i)).collect()
+}
+
+#[test]
+fn sample_keeps_every_nth_line() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--sample=3"], numbers(10)).success();
+    assert_eq!(run.stdout, "3\n6\n9\n");
+}
+
+#[test]
+fn sampled_lines_keep_their_source_numbers() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--sample=4", "-n"], numbers(9)).success();
+    assert_eq!(run.stdout, "     4 | 4\n     8 | 8\n");
+}
+
+#[test]
+fn sample_random_at_the_extremes() {
+    let scratch = Scratch::new();
+    assert_eq!(scratch.run_stdin(&["--sample-random=0"], numbers(50)).success().stdout, "");
+    assert_eq!(scratch.run_stdin(&["--sample-random=1"], numbers(50)).success().stdout, numbers(50));
+}
+
+#[test]
+fn sample_random_keeps_lines_in_order() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--sample-random=0.5"], numbers(1000)).success();
+    let kept: Vec<usize> = run.stdout.lines().map(|line| line.parse().unwrap()).collect();
+    assert!(kept.windows(2).all(|pair| pair[0] < pair[1]));
+    // Far enough from 0 and 1000 that it's no flake
+    assert!((300..700).contains(&kept.len()), "{}", kept.len());
+}
+
+#[test]
+fn sample_rejects_bad_values() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--sample=0"]);
+    assert!(run.stderr.starts_with("meow: invalid sample interval: 0"), "{}", run.stderr);
+    let run = scratch.run(&["--sample-random=1.5"]);
+    assert!(run.stderr.starts_with("meow: sample probability must be between 0 and 1"), "{}", run.stderr);
+}
+
+#[test]
+fn sample_spread_on_a_file_jumps_by_offset() {
+    let scratch = Scratch::new();
+    // Ten lines of ten bytes each
+    let text: String = (0..10).map(|i| format!("line {:04}\n", i)).collect();
+    scratch.write("a.txt", text);
+    let run = scratch.run(&["--sample-spread=2", "a.txt"]).success();
+    assert_eq!(run.stdout, "         0 | line 0000\n\u{00B7}\u{00B7}\u{00B7}\n        50 | line 0005\n");
+}
+
+#[test]
+fn sample_spread_on_a_stream_is_reproducible_with_a_seed() {
+    let scratch = Scratch::new();
+    let first = scratch.run_stdin(&["--sample-spread=5", "--sample-seed=7"], numbers(1000)).success().stdout;
+    let second = scratch.run_stdin(&["--sample-spread=5", "--sample-seed=7"], numbers(1000)).success().stdout;
+    assert_eq!(first, second);
+    let kept: Vec<usize> = first.lines().filter_map(|line| line.split_once(" | ")).map(|(_, text)| text.parse().unwrap()).collect();
+    assert_eq!(kept.len(), 5);
+    assert!(kept.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn sample_spread_of_a_short_stream_keeps_every_line() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--sample-spread=5"], numbers(3)).success();
+    assert_eq!(run.stdout, "     1 | 1\n     2 | 2\n     3 | 3\n");
+}
@@ -0,0 +1,56 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Stdio};
+
+use crate::util::Scratch;
+
+/// Starts `meow --serve` with `args` on a free port and returns it with the URL it printed.
+fn start(scratch: &Scratch, args: &[&str]) -> (Child, String) {
+    let mut args = args.to_vec();
+    args.extend(["--serve=0", "--serve-once"]);
+    let mut child = scratch.command(&args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.as_mut().unwrap()).read_line(&mut line).unwrap();
+    let url = line.split_whitespace().find(|word| word.starts_with("http://")).unwrap_or_else(|| panic!("no URL in {:?}", line)).to_string();
+    (child, url)
+}
+
+fn get(url: &str, path: &str) -> String {
+    let host = url.trim_start_matches("http://").trim_end_matches('/');
+    let mut stream = TcpStream::connect(host).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, host).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn serve_listens_on_localhost_by_default() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "shared\n");
+    let (child, url) = start(&scratch, &["-n", "a.txt"]);
+    assert!(url.starts_with("http://127.0.0.1:"), "{}", url);
+    let response = get(&url, "/");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.ends_with("\r\n\r\n     1 | shared\n"), "{}", response);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "meow: GET / 200 OK\n");
+}
+
+#[test]
+fn serve_bind_picks_the_address() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "shared\n");
+    let (child, url) = start(&scratch, &["--serve-bind=::1", "a.txt"]);
+    assert!(url.starts_with("http://[::1]:"), "{}", url);
+    assert!(get(&url, "/1").ends_with("\r\n\r\nshared\n"));
+    assert!(child.wait_with_output().unwrap().status.success());
+}
+
+#[test]
+fn serve_bind_needs_an_address() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--serve-bind=localhost"]);
+    assert!(run.stderr.starts_with("meow: invalid address 'localhost'"), "{}", run.stderr);
+}
@@ -0,0 +1,34 @@
+use crate::util::Scratch;
+
+#[test]
+fn title_is_set_and_put_back() {
+    let scratch = Scratch::new();
+    scratch.write("f.log", "a\nb\n");
+    let run = scratch.run_tty_command(scratch.command(&["--set-title", "f.log"]).env("TERM", "xterm")).success();
+    assert_eq!(run.stdout, "\x1B[22;0t\x1B]0;meow: f.log (100%)\x07a\nb\n\x1B]0;meow: f.log (100%)\x07\x1B[23;0t");
+}
+
+#[test]
+fn title_counts_grep_matches() {
+    let scratch = Scratch::new();
+    scratch.write("f.log", "a\nERROR b\nc\n");
+    let run = scratch.run_tty_command(scratch.command(&["--set-title", "--grep=ERROR", "f.log"]).env("TERM", "xterm")).success();
+    assert_eq!(run.stdout, "\x1B[22;0t\x1B]0;meow: f.log (100%, 0 matches)\x07ERROR b\n\x1B]0;meow: f.log (100%, 1 matches)\x07\x1B[23;0t");
+}
+
+#[test]
+fn title_is_cleared_without_a_title_stack() {
+    let scratch = Scratch::new();
+    scratch.write("f.log", "a\n");
+    let run = scratch.run_tty(&["--set-title", "f.log"]).success();
+    assert_eq!(run.stdout, "\x1B]0;meow: f.log (100%)\x07a\n\x1B]0;meow: f.log (100%)\x07\x1B]0;\x07");
+}
+
+#[test]
+fn no_title_off_a_terminal_or_without_escapes() {
+    let scratch = Scratch::new();
+    scratch.write("f.log", "a\n");
+    assert_eq!(scratch.run(&["--set-title", "f.log"]).success().stdout, "a\n");
+    assert_eq!(scratch.run_tty(&["--set-title", "-C", "f.log"]).success().stdout, "a\n");
+    assert_eq!(scratch.run_tty(&["--set-title", "--safe", "f.log"]).success().stdout, "a\n");
+}
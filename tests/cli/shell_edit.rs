@@ -0,0 +1,53 @@
+use crate::util::Scratch;
+
+/// The shell starts after `a.txt` has been shown.
+const BANNER: &str = "a\n\n=== Meow Interactive Shell ===\nType 'help' for available commands, 'exit' to quit\n\n";
+
+/// What the shell printed after its banner, one entry per prompt.
+fn replies(stdout: &str) -> Vec<&str> {
+    stdout.strip_prefix(BANNER).unwrap_or_else(|| panic!("no banner in {:?}", stdout)).split("meow> ").skip(1).collect()
+}
+
+#[test]
+fn edit_runs_the_editor_and_returns_to_the_shell() {
+    let scratch = Scratch::new();
+    let editor = scratch.script("editor.sh", "printf '%s\\n' \"$@\" > args.txt\n");
+    scratch.write("a.txt", "one\n");
+    let run = scratch.run_command(scratch.command(&["-i", "a.txt"]).env("EDITOR", format!("{} --wait", editor.display())), b"edit a.txt\ncat a.txt\nexit\n").success();
+    assert_eq!(scratch.read("args.txt"), "--wait\na.txt\n");
+    assert!(run.stdout.ends_with("meow> meow> one\nmeow> "), "{}", run.stdout);
+}
+
+#[test]
+fn visual_comes_before_editor() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    let visual = scratch.script("visual.sh", "echo visual > used.txt\n");
+    scratch.run_command(scratch.command(&["-i", "a.txt"]).env("VISUAL", &visual).env("EDITOR", "false"), b"edit a.txt\n").success();
+    assert_eq!(scratch.read("used.txt"), "visual\n");
+}
+
+#[test]
+fn edit_reports_editor_failures() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    let run = scratch.run_command(scratch.command(&["-i", "a.txt"]).env("EDITOR", "false"), b"edit a.txt\n").success();
+    assert_eq!(replies(&run.stdout), ["editor 'false' failed: exit status: 1\n", ""]);
+    let run = scratch.run_command(scratch.command(&["-i", "a.txt"]).env("EDITOR", "no-such-editor"), b"edit a.txt\n").success();
+    assert_eq!(replies(&run.stdout), ["cannot launch editor 'no-such-editor': No such file or directory (os error 2)\n", ""]);
+    let run = scratch.run_command(&mut scratch.command(&["-i", "a.txt"]), b"edit\n").success();
+    assert_eq!(replies(&run.stdout), ["Usage: edit <file>\n", ""]);
+}
+
+#[test]
+fn edit_falls_back_to_vi_then_nano() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    scratch.script("bin/nano", "echo nano > used.txt\n");
+    let run = scratch.run_command(scratch.command(&["-i", "a.txt"]).env("PATH", scratch.path("bin")), b"edit a.txt\n").success();
+    assert_eq!(scratch.read("used.txt"), "nano\n");
+    assert_eq!(replies(&run.stdout), ["", ""]);
+    scratch.script("bin/vi", "echo vi > used.txt\n");
+    scratch.run_command(scratch.command(&["-i", "a.txt"]).env("PATH", scratch.path("bin")), b"edit a.txt\n").success();
+    assert_eq!(scratch.read("used.txt"), "vi\n");
+}
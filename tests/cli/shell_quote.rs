@@ -0,0 +1,85 @@
+use std::process::Command;
+
+use crate::util::Scratch;
+
+/// Lines a shell would trip over unquoted.
+const AWKWARD: &[&str] = &[
+    "plain",
+    "two  spaces",
+    "it's",
+    "'quoted'",
+    "\"double\" $HOME `date` $(id)",
+    "back\\slash",
+    "*.txt ?",
+    "",
+    "-n",
+];
+
+/// Runs `script` in sh with `input` on stdin and returns what it printed.
+fn sh(script: &str, input: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new("sh").arg("-c").arg(script).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn each_line_quoted() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--shell-quote"], "a b\nit's\n").success();
+    assert_eq!(run.stdout, "'a b'\n'it'\\''s'\n");
+}
+
+#[test]
+fn quoted_lines_round_trip_through_sh() {
+    let scratch = Scratch::new();
+    let input = AWKWARD.join("\n") + "\n";
+    let run = scratch.run_stdin(&["--shell-quote"], &input).success();
+    // Each output line is one word to the shell, whatever was in it
+    let script = format!("set -- {}\nprintf '%s\\n' \"$@\"", run.stdout.lines().collect::<Vec<_>>().join(" "));
+    assert_eq!(String::from_utf8(sh(&script, b"")).unwrap(), input);
+}
+
+#[test]
+fn array_round_trips_through_eval() {
+    let scratch = Scratch::new();
+    scratch.write("list.txt", AWKWARD.join("\n") + "\n");
+    scratch.write("more.txt", "last one\n");
+    let run = scratch.run(&["--shell-quote=array", "list.txt", "more.txt"]).success();
+    assert_eq!(run.stdout.lines().count(), 1, "{}", run.stdout);
+    scratch.write("array.txt", &run.stdout);
+    let printed = sh(&format!("cd '{}' && eval \"set -- $(cat array.txt)\" && printf '%s\\n' \"$@\"", scratch.path("").display()), b"");
+    assert_eq!(String::from_utf8(printed).unwrap(), AWKWARD.join("\n") + "\nlast one\n");
+}
+
+#[test]
+fn quoting_turns_off_the_gutter() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "x y\n");
+    scratch.write("b.txt", "z\n");
+    let run = scratch.run(&["--shell-quote", "-n", "--with-filename", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout, "'x y'\n'z'\n");
+    let run = scratch.run_tty(&["--shell-quote", "a.txt"]).success();
+    assert_eq!(run.stdout, "'x y'\n");
+}
+
+#[test]
+fn null_terminated_names_with_newlines() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--record-separator=\\0", "--grep=.log", "--null"], "plain.log\0with\nnewline.log\0skip.txt\0it's here.log\0").success();
+    assert_eq!(run.stdout, "plain.log\0with\nnewline.log\0it's here.log\0");
+    // xargs -0 gets each name whole
+    let printed = sh("xargs -0 printf '[%s]'", run.stdout.as_bytes());
+    assert_eq!(printed, b"[plain.log][with\nnewline.log][it's here.log]");
+}
+
+#[test]
+fn null_with_shell_quote() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--null", "--shell-quote"], "a b\nc\n").success();
+    assert_eq!(run.stdout, "'a b'\0'c'\0");
+}
@@ -0,0 +1,47 @@
+use crate::util::Scratch;
+
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog 0123456789";
+
+const ROLES: [&str; 7] = ["normal", "number", "highlight", "error", "success", "filename", "dim"];
+
+#[test]
+fn every_role_is_shown_in_its_color() {
+    let scratch = Scratch::new();
+    let run = scratch.run_tty(&["--show-colors"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    for (role, line) in ROLES.iter().zip(&lines) {
+        assert!(line.starts_with(&format!("{:<11}  \x1B[", role)), "{:?}", line);
+        assert!(line.ends_with(&format!("m{}\x1B[0m", SAMPLE)), "{:?}", line);
+    }
+    assert!(lines[2].contains("\x1B[36m"), "{:?}", lines[2]);
+    assert!(lines[3].contains("\x1B[31m"), "{:?}", lines[3]);
+    for name in ["blame-age-1", "blame-age-5", "file-1", "file-6"] {
+        assert!(lines.iter().any(|line| line.starts_with(&format!("{:<11}  \x1B[", name))), "no {} in {:?}", name, lines);
+    }
+    // The gradient, a color for each character
+    let rainbow = lines.last().unwrap();
+    assert!(rainbow.starts_with("rainbow      \x1B["), "{:?}", rainbow);
+    assert_eq!(rainbow.matches("\x1B[0m").count(), SAMPLE.chars().count());
+}
+
+#[test]
+fn without_colors_only_the_names() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--show-colors"]).success();
+    assert!(!run.stdout.contains('\x1B'), "{:?}", run.stdout);
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines[..ROLES.len()], ROLES);
+    assert!(lines.contains(&"blame-age-1"));
+    assert_eq!(lines.last(), Some(&"rainbow"));
+    let run = scratch.run_tty(&["--show-colors", "-C"]).success();
+    assert!(!run.stdout.contains('\x1B'), "{:?}", run.stdout);
+}
+
+#[test]
+fn theme_colors_are_the_ones_shown() {
+    let scratch = Scratch::new();
+    scratch.write("theme.toml", "highlight = \"1;35\"\n");
+    let run = scratch.run_tty(&["--show-colors", "--theme=theme.toml"]).success();
+    let highlight = run.stdout.lines().find(|line| line.starts_with("highlight")).unwrap();
+    assert!(highlight.contains(&format!("\x1B[1;35m{}", SAMPLE)), "{:?}", highlight);
+}
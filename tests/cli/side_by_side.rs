@@ -0,0 +1,44 @@
+use crate::util::Scratch;
+
+#[test]
+fn files_of_different_lengths_line_up() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "one\ntwo\nthree\n");
+    scratch.write("b.txt", "uno\n");
+    let run = scratch.run(&["--side-by-side", "a.txt", "b.txt"]).success();
+    let column = (80 - 3) / 2;
+    let row = |left: &str, right: &str| format!("{:<column$} \u{2502} {}\n", left, right, column = column);
+    assert_eq!(run.stdout, [row("a.txt", "b.txt"), row("one", "uno"), row("two", ""), row("three", "")].concat());
+}
+
+#[test]
+fn numbers_and_wide_characters() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "日本語\n");
+    scratch.write("b.txt", "x\ny\n");
+    let run = scratch.run(&["--side-by-side", "-n", "a.txt", "b.txt"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines[1], format!("1 日本語{} \u{2502} 1 x", " ".repeat(36 - 6)));
+    assert_eq!(lines[2], format!("  {} \u{2502} 2 y", " ".repeat(36)));
+}
+
+#[test]
+fn long_lines_are_cut_or_wrapped() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", format!("{}\n", "x".repeat(50)));
+    scratch.write("b.txt", "b\n");
+    let run = scratch.run(&["--side-by-side", "a.txt", "b.txt"]).success();
+    assert_eq!(run.stdout.lines().nth(1).unwrap(), format!("{}\u{2026} \u{2502} b", "x".repeat(37)));
+
+    let run = scratch.run(&["--side-by-side", "--side-by-side-wrap", "a.txt", "b.txt"]).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines[1..], [format!("{} \u{2502} b", "x".repeat(38)), format!("{}{} \u{2502} ", "x".repeat(12), " ".repeat(26))]);
+}
+
+#[test]
+fn needs_two_files() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a\n");
+    let run = scratch.run(&["--side-by-side", "a.txt"]).failure();
+    assert_eq!(run.stderr, "meow: --side-by-side needs exactly two files\n");
+}
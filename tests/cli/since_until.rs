@@ -0,0 +1,90 @@
+use crate::util::Scratch;
+
+const LOG: &str = "\
+2024-08-07T13:59:00Z starting
+2024-08-07T14:00:01Z request one
+Traceback:
+  in handler
+1723039300 epoch seconds line
+[2024-08-07T14:05:00+02:00] an hour and more earlier
+2024-08-07T15:00:00Z shutting down
+";
+
+#[test]
+fn window_keeps_untimed_lines_with_their_entry() {
+    let scratch = Scratch::new();
+    scratch.write("app.log", LOG);
+    let run = scratch.run(&["--since=2024-08-07T14:00:00Z", "--until=2024-08-07T14:30:00Z", "app.log"]).success();
+    assert_eq!(run.stdout, "2024-08-07T14:00:01Z request one\nTraceback:\n  in handler\n1723039300 epoch seconds line\n");
+}
+
+#[test]
+fn skip_untimed_drops_them() {
+    let scratch = Scratch::new();
+    scratch.write("app.log", LOG);
+    let run = scratch.run(&["--since=2024-08-07T14:00:00Z", "--until=2024-08-07T14:30:00Z", "--skip-untimed", "app.log"]).success();
+    assert_eq!(run.stdout, "2024-08-07T14:00:01Z request one\n1723039300 epoch seconds line\n");
+}
+
+#[test]
+fn untimed_lines_before_any_entry_are_outside() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--since=2024-08-07T14:00:00Z"], "header\n2024-08-07T14:00:01Z in\n").success();
+    assert_eq!(run.stdout, "2024-08-07T14:00:01Z in\n");
+}
+
+#[test]
+fn custom_time_format() {
+    let scratch = Scratch::new();
+    let log = "07/08/2024 13:00:00 +0000 early\n07/08/2024 15:00:00 +0000 late\n";
+    let run = scratch.run_stdin(&["--time-format=%d/%m/%Y %H:%M:%S %z", "--since=2024-08-07T14:00:00Z"], log).success();
+    assert_eq!(run.stdout, "07/08/2024 15:00:00 +0000 late\n");
+}
+
+#[test]
+fn since_in_a_large_file_keeps_line_numbers() {
+    // Big enough that the start is found by seeking, not reading
+    let scratch = Scratch::new();
+    let log: String = (0..50_000).map(|i| format!("{} line {}\n", 1_700_000_000 + i, i)).collect();
+    scratch.write("big.log", log);
+    let run = scratch.run(&["--since=1700040000", "--until=1700040001", "big.log"]).success();
+    assert_eq!(run.stdout, "1700040000 line 40000\n1700040001 line 40001\n");
+}
+
+#[test]
+fn bad_time_is_an_error() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--since=whenever"]);
+    assert!(run.stderr.starts_with("meow: invalid time 'whenever'"), "{}", run.stderr);
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
+
+const ISO_LOG: &str = "\
+2024-08-07 09:59:59 before
+2024-08-07 10:00:00 first
+  continued
+2024-08-07 11:30:00 middle
+2024-08-07 12:00:00 last
+2024-08-07 12:00:01 after
+";
+
+#[test]
+fn iso_window_includes_both_bounds() {
+    // Without a zone both the lines and the bounds are local time
+    let scratch = Scratch::new();
+    scratch.write("iso.log", ISO_LOG);
+    let run = scratch.run(&["--since=2024-08-07 10:00", "--until=2024-08-07 12:00", "iso.log"]).success();
+    assert_eq!(run.stdout, "2024-08-07 10:00:00 first\n  continued\n2024-08-07 11:30:00 middle\n2024-08-07 12:00:00 last\n");
+    let run = scratch.run(&["--since=2024-08-07 10:00", "--until=2024-08-07 12:00", "--skip-untimed", "iso.log"]).success();
+    assert_eq!(run.stdout, "2024-08-07 10:00:00 first\n2024-08-07 11:30:00 middle\n2024-08-07 12:00:00 last\n");
+}
+
+#[test]
+fn iso_open_ended_windows() {
+    let scratch = Scratch::new();
+    scratch.write("iso.log", ISO_LOG);
+    let run = scratch.run(&["--since=2024-08-07T12:00:00", "iso.log"]).success();
+    assert_eq!(run.stdout, "2024-08-07 12:00:00 last\n2024-08-07 12:00:01 after\n");
+    let run = scratch.run(&["--until=2024-08-07 10:00:00", "-n", "iso.log"]).success();
+    assert_eq!(run.stdout, "     1 | 2024-08-07 09:59:59 before\n     2 | 2024-08-07 10:00:00 first\n     3 |   continued\n");
+}
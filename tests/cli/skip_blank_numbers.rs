@@ -0,0 +1,22 @@
+use crate::util::Scratch;
+
+#[test]
+fn skip_blank_numbers_leaves_blank_gutters_and_keeps_counting() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--skip-blank-numbers"], "a\n\nb\n\n\nc\n").success();
+    assert_eq!(run.stdout, "     1 | a\n       | \n     2 | b\n       | \n       | \n     3 | c\n");
+}
+
+#[test]
+fn number_counts_blank_lines_without_it() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n"], "a\n\nb\n").success();
+    assert_eq!(run.stdout, "     1 | a\n     2 | \n     3 | b\n");
+}
+
+#[test]
+fn skip_blank_numbers_treats_whitespace_lines_as_blank() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--skip-blank-numbers"], "a\n  \t\nb\n").success();
+    assert_eq!(run.stdout, "     1 | a\n       |   \t\n     2 | b\n");
+}
@@ -0,0 +1,56 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::util::{write_later, Scratch};
+
+#[test]
+fn fifo_without_a_writer_gives_the_notice() {
+    let scratch = Scratch::new();
+    let fifo = scratch.mkfifo("fifo");
+    let writer = write_later(&fifo, Duration::from_millis(600), "late\n");
+    let run = scratch.run(&["fifo"]).success();
+    writer.join().unwrap();
+    assert_eq!(run.stdout, "late\n");
+    assert_eq!(run.stderr, "meow: fifo: named pipe, waiting for input...\n");
+}
+
+#[test]
+fn fifo_with_a_writer_waiting_is_read_quietly() {
+    let scratch = Scratch::new();
+    let fifo = scratch.mkfifo("fifo");
+    let writer = write_later(&fifo, Duration::ZERO, "ready\n");
+    // Give the writer time to be blocked in its open
+    thread::sleep(Duration::from_millis(100));
+    let run = scratch.run(&["fifo"]).success();
+    writer.join().unwrap();
+    assert_eq!(run.stdout, "ready\n");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn quiet_drops_the_notice() {
+    let scratch = Scratch::new();
+    let fifo = scratch.mkfifo("fifo");
+    let writer = write_later(&fifo, Duration::from_millis(400), "late\n");
+    let run = scratch.run(&["-q", "fifo"]).success();
+    writer.join().unwrap();
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn dev_null_is_not_waited_on() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["/dev/null"]).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn no_special_refuses_devices_and_fifos() {
+    let scratch = Scratch::new();
+    scratch.mkfifo("fifo");
+    let run = scratch.run(&["--no-special", "fifo"]).failure();
+    assert_eq!(run.stderr, "meow: fifo: refusing to read named pipe\n");
+    let run = scratch.run(&["--no-special", "/dev/null"]).failure();
+    assert!(run.stderr.contains("character device"), "{}", run.stderr);
+}
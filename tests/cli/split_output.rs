@@ -0,0 +1,100 @@
+use crate::util::Scratch;
+
+/// Lines `1` to `n`, one number each.
+fn numbers(n: usize) -> String {
+    (1..=n).map(|i| format!("{}\n", i)).collect()
+}
+
+#[test]
+fn chunks_of_lines() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--split-output=4", "--output=part_%03d.txt"], numbers(10)).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "meow: files written (3): part_001.txt, part_002.txt, part_003.txt\n");
+    assert_eq!(scratch.read("part_001.txt"), "1\n2\n3\n4\n");
+    assert_eq!(scratch.read("part_002.txt"), "5\n6\n7\n8\n");
+    assert_eq!(scratch.read("part_003.txt"), "9\n10\n");
+}
+
+#[test]
+fn files_only_as_needed() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--split-output=5", "--output=part_%d"], numbers(10)).success();
+    assert_eq!(run.stderr, "meow: files written (2): part_1, part_2\n");
+    assert!(!scratch.path("part_3").exists());
+}
+
+#[test]
+fn lines_counted_after_formatting() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--grep=1", "--split-output=2", "--output=out_%02d"], numbers(12)).success();
+    assert!(run.stderr.contains("(2): out_01, out_02"), "{}", run.stderr);
+    assert_eq!(scratch.read("out_01"), "     1 | 1\n     2 | 10\n");
+    assert_eq!(scratch.read("out_02"), "     3 | 11\n     4 | 12\n");
+}
+
+#[test]
+fn inputs_run_on_across_files() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "a1\na2\na3\n");
+    scratch.write("b.txt", "b1\n");
+    scratch.run(&["--split-output=3", "--output=part_%d", "a.txt", "b.txt"]).success();
+    // The file headers are output lines too
+    let joined = scratch.read("part_1") + &scratch.read("part_2") + &scratch.read("part_3");
+    assert_eq!(joined, "\n===> a.txt <===\na1\na2\na3\n\n===> b.txt <===\nb1\n");
+    assert_eq!(scratch.read("part_1").lines().count(), 3);
+}
+
+#[test]
+fn colors_are_dropped() {
+    let scratch = Scratch::new();
+    scratch.write("numbers.txt", numbers(3));
+    scratch.run_tty(&["--highlight=2", "--split-output=10", "--output=part_%d", "numbers.txt"]).success();
+    assert_eq!(scratch.read("part_1"), "1\n2\n3\n");
+}
+
+#[test]
+fn chunks_of_bytes_keep_lines_whole() {
+    let scratch = Scratch::new();
+    let line = format!("{}\n", "x".repeat(99));
+    let run = scratch.run_stdin(&["--split-output-bytes=1K", "--output=part_%d"], line.repeat(25)).success();
+    assert!(run.stderr.contains("files written (3)"), "{}", run.stderr);
+    // Ten 100-byte lines fit in 1024 bytes, the eleventh doesn't
+    assert_eq!(scratch.read("part_1"), line.repeat(10));
+    assert_eq!(scratch.read("part_2"), line.repeat(10));
+    assert_eq!(scratch.read("part_3"), line.repeat(5));
+}
+
+#[test]
+fn single_output_file() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--output=all.txt"], "one\ntwo").success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "");
+    assert_eq!(scratch.read("all.txt"), "one\ntwo\n");
+}
+
+#[test]
+fn templates_checked_before_reading() {
+    let scratch = Scratch::new();
+    for (template, error) in [
+        ("part.txt", "the output template 'part.txt' needs exactly one number placeholder"),
+        ("part_%d_%d", "needs exactly one number placeholder"),
+        ("part_%s", "invalid placeholder in the output template 'part_%s'"),
+    ] {
+        let run = scratch.run_stdin(&["--split-output=2", &format!("--output={}", template)], "a\n");
+        assert!(run.stderr.contains(error), "{}: {}", template, run.stderr);
+    }
+    let run = scratch.run_stdin(&["--split-output=2"], "a\n");
+    assert!(run.stderr.contains("--split-output needs --output=TEMPLATE"), "{}", run.stderr);
+    let run = scratch.run_stdin(&["--split-output=0", "--output=part_%d"], "a\n");
+    assert!(run.stderr.starts_with("meow: invalid"), "{}", run.stderr);
+    assert!(!scratch.path("part_1").exists());
+}
+
+#[test]
+fn write_failure_sets_the_status() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--split-output=1", "--output=missing/part_%d"], "a\nb\n").failure();
+    assert!(run.stderr.contains("cannot write to missing/part_1"), "{}", run.stderr);
+}
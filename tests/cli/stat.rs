@@ -0,0 +1,55 @@
+use std::fs::{self, File};
+use std::os::unix::fs::PermissionsExt;
+use std::time::{Duration, SystemTime};
+
+use crate::util::Scratch;
+
+/// `name` with `contents`, mode 640 and modified 2001-09-09 01:46:40 UTC.
+fn fixture(scratch: &Scratch, name: &str, contents: &str) {
+    let path = scratch.write(name, contents);
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    File::options().write(true).open(&path).unwrap().set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)).unwrap();
+}
+
+#[test]
+fn stat_of_one_file() {
+    let scratch = Scratch::new();
+    fixture(&scratch, "a.txt", "hello\n");
+    let run = scratch.run(&["--stat", "--date-format=%s", "a.txt"]).success();
+    assert_eq!(run.stdout, "a.txt\n  size         6 B\n  modified     1000000000\n  type         regular file\n  permissions  rw-r----- (640)\n");
+}
+
+#[test]
+fn stat_of_several_files_is_a_table() {
+    let scratch = Scratch::new();
+    fixture(&scratch, "a.txt", "hello\n");
+    fixture(&scratch, "big.bin", &"x".repeat(2048));
+    let run = scratch.run(&["--stat", "--date-format=%s", "a.txt", "big.bin"]).success();
+    assert_eq!(run.stdout, "\
+name        size  modified    type          permissions
+a.txt        6 B  1000000000  regular file  rw-r----- (640)
+big.bin  2.0 KiB  1000000000  regular file  rw-r----- (640)
+");
+    let run = scratch.run(&["--stat", "--si", "--date-format=%s", "big.bin"]).success();
+    assert!(run.stdout.contains("  size         2.0 KB\n"), "{}", run.stdout);
+    let run = scratch.run(&["--stat", "--bytes-exact", "--date-format=%s", "big.bin"]).success();
+    assert!(run.stdout.contains("  size         2048\n"), "{}", run.stdout);
+}
+
+#[test]
+fn stat_does_not_read_the_content() {
+    let scratch = Scratch::new();
+    // Reading a FIFO nobody writes to would never end
+    scratch.mkfifo("pipe");
+    let run = scratch.run(&["--stat", "pipe"]).success();
+    assert!(run.stdout.contains("  type         named pipe\n"), "{}", run.stdout);
+}
+
+#[test]
+fn stat_reports_missing_files_and_goes_on() {
+    let scratch = Scratch::new();
+    fixture(&scratch, "a.txt", "hello\n");
+    let run = scratch.run(&["--stat", "--date-format=%s", "missing", "a.txt"]).failure();
+    assert_eq!(run.stderr, "meow: missing: No such file or directory (os error 2)\n");
+    assert_eq!(run.stdout, "name   size  modified    type          permissions\na.txt   6 B  1000000000  regular file  rw-r----- (640)\n");
+}
@@ -0,0 +1,42 @@
+use crate::util::Scratch;
+
+#[test]
+fn hash_comments_are_stripped_by_extension() {
+    let scratch = Scratch::new();
+    scratch.write("c.toml", "# header\nkey = \"a#b\" # note\n\nurl = \"x\"\n");
+    let run = scratch.run(&["--strip-comments", "c.toml"]).success();
+    assert_eq!(run.stdout, "key = \"a#b\"\n\nurl = \"x\"\n");
+}
+
+#[test]
+fn slash_comments_are_stripped_across_lines() {
+    let scratch = Scratch::new();
+    scratch.write("c.c", "// top\nint x; /* a\nb */ int y; // z\nchar *s = \"//\";\n");
+    let run = scratch.run(&["--strip-comments", "c.c"]).success();
+    assert_eq!(run.stdout, "int x;\n int y;\nchar *s = \"//\";\n");
+}
+
+#[test]
+fn unknown_types_are_left_alone() {
+    let scratch = Scratch::new();
+    scratch.write("c.md", "# Heading\ntext\n");
+    let run = scratch.run(&["--strip-comments", "c.md"]).success();
+    assert_eq!(run.stdout, "# Heading\ntext\n");
+    let run = scratch.run(&["--strip-comments", "--comment-style=hash", "c.md"]).success();
+    assert_eq!(run.stdout, "text\n");
+}
+
+#[test]
+fn comment_style_for_stdin() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--strip-comments", "--comment-style=semicolon"], "; x\na=1 ; y\n").success();
+    assert_eq!(run.stdout, "a=1\n");
+}
+
+#[test]
+fn stripped_lines_are_not_numbered() {
+    let scratch = Scratch::new();
+    scratch.write("c.sh", "#!/bin/sh\n# say hi\necho hi # greet\n");
+    let run = scratch.run(&["-n", "--strip-comments", "c.sh"]).success();
+    assert_eq!(run.stdout, "     1 | echo hi\n");
+}
@@ -0,0 +1,50 @@
+use crate::util::Scratch;
+
+const MANIFEST: &str = "kind: Deployment\nspec:\n  replicas: 3\n  template:\n    image: web\n    ports: [80]\n  paused: false\n";
+
+#[test]
+fn valid_yaml_is_shown_as_is_off_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("deploy.yaml", MANIFEST);
+    let run = scratch.run(&["--yaml", "deploy.yaml"]).success();
+    assert_eq!(run.stdout, MANIFEST);
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn yaml_is_colored_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("deploy.yaml", "replicas: 3\n");
+    let run = scratch.run_tty(&["deploy.yaml"]).success();
+    assert_eq!(run.stdout, "\x1B[36mreplicas\x1B[0m: \x1B[33m3\x1B[0m\n");
+    // --plain turns it off
+    let run = scratch.run_tty(&["--plain", "deploy.yaml"]).success();
+    assert_eq!(run.stdout, "replicas: 3\n");
+}
+
+#[test]
+fn broken_yaml_shows_the_content_and_a_caret() {
+    let scratch = Scratch::new();
+    scratch.write("bad.yaml", "a:\n  b: 1\n  c: \"open\n");
+    let run = scratch.run(&["--yaml", "bad.yaml"]).failure();
+    assert_eq!(run.stdout, "a:\n  b: 1\n  c: \"open\n");
+    assert_eq!(run.stderr, "meow: bad.yaml:3:6: unterminated quoted string\n  c: \"open\n     ^\n");
+}
+
+#[test]
+fn broken_toml_shows_the_content_and_a_caret() {
+    let scratch = Scratch::new();
+    scratch.write("bad.toml", "a = 1\nb = [1,\nc = \"x\"\n");
+    let run = scratch.run(&["--toml", "bad.toml"]).failure();
+    assert_eq!(run.stdout, "a = 1\nb = [1,\nc = \"x\"\n");
+    assert!(run.stderr.starts_with("meow: bad.toml:3:1: "), "{}", run.stderr);
+    assert!(run.stderr.ends_with("\nc = \"x\"\n^\n"), "{}", run.stderr);
+}
+
+#[test]
+fn fold_depth_collapses_deep_levels() {
+    let scratch = Scratch::new();
+    scratch.write("deploy.yaml", MANIFEST);
+    let run = scratch.run(&["--yaml", "--fold-depth=1", "deploy.yaml"]).success();
+    assert_eq!(run.stdout, "kind: Deployment\nspec:\n  replicas: 3\n  template:\n    \u{2026} 2 lines folded\n  paused: false\n");
+}
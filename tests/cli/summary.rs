@@ -0,0 +1,81 @@
+use crate::util::Scratch;
+
+/// The summary table at the end of `stdout`, without the time column, whose
+/// values depend on the machine.
+fn table(stdout: &str) -> Vec<String> {
+    let start = stdout.rfind("name ").expect("no summary table");
+    stdout[start..]
+        .lines()
+        .map(|line| line.rsplit_once("  ").map_or(line, |(rest, _)| rest).trim_end().to_string())
+        .collect()
+}
+
+fn scratch() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("one.log", "a\nERROR x\nb\n");
+    scratch.write("two.log", "ERROR y\nERROR z\n");
+    scratch
+}
+
+#[test]
+fn summary_after_the_content() {
+    let scratch = scratch();
+    let run = scratch.run(&["--summary", "one.log", "two.log"]).success();
+    assert!(run.stdout.starts_with("\n===> one.log <===\na\nERROR x\nb\n\n===> two.log <===\nERROR y\nERROR z\n\nname "), "{}", run.stdout);
+    assert_eq!(table(&run.stdout), [
+        "name     size  read  shown  errors",
+        "one.log  12 B     3      3       0",
+        "two.log  16 B     2      2       0",
+        "total    28 B     5      5       0",
+    ]);
+}
+
+#[test]
+fn summary_with_grep() {
+    let scratch = scratch();
+    let run = scratch.run(&["--summary", "--grep=ERROR", "one.log", "two.log"]).success();
+    assert_eq!(table(&run.stdout), [
+        "name     size  read  shown  matches  errors",
+        "one.log  12 B     3      1        1       0",
+        "two.log  16 B     2      2        2       0",
+        "total    28 B     5      3        3       0",
+    ]);
+}
+
+#[test]
+fn failing_file_gets_a_row() {
+    let scratch = scratch();
+    let run = scratch.run(&["--summary", "--grep=ERROR", "one.log", "missing.log", "two.log"]).failure();
+    assert!(run.stderr.contains("missing.log"), "{}", run.stderr);
+    assert_eq!(table(&run.stdout), [
+        "name         size  read  shown  matches  errors",
+        "one.log      12 B     3      1        1       0",
+        "missing.log     -     0      0        0       1",
+        "two.log      16 B     2      2        2       0",
+        "total        28 B     5      3        3       1",
+    ]);
+}
+
+#[test]
+fn summary_only() {
+    let scratch = scratch();
+    let run = scratch.run(&["--summary-only", "one.log", "two.log"]).success();
+    assert!(run.stdout.starts_with("name "), "{}", run.stdout);
+    assert_eq!(table(&run.stdout).len(), 4);
+}
+
+#[test]
+fn stdin_has_no_size() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--summary-only"], "x\ny\n").success();
+    assert_eq!(table(&run.stdout), ["name   size  read  shown  errors", "stdin     -     2      2       0", "total     -     2      2       0"]);
+}
+
+#[test]
+fn time_column() {
+    let scratch = scratch();
+    let run = scratch.run(&["--summary-only", "one.log"]).success();
+    for line in run.stdout.lines().skip(1) {
+        assert!(line.ends_with(" ms") || line.ends_with(" s"), "{}", line);
+    }
+}
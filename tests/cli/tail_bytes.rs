@@ -0,0 +1,36 @@
+use crate::util::Scratch;
+
+const TEXT: &str = "aaaa\nbbbb\ncccc\n";
+
+#[test]
+fn tail_bytes_shows_whole_lines_from_the_end() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", TEXT);
+    // Exactly the last line, then a cut into the line before it
+    assert_eq!(scratch.run(&["--tail-bytes=5", "a.txt"]).success().stdout, "cccc\n");
+    assert_eq!(scratch.run(&["--tail-bytes=7", "a.txt"]).success().stdout, "cccc\n");
+    assert_eq!(scratch.run(&["--tail-bytes=10", "a.txt"]).success().stdout, "bbbb\ncccc\n");
+    assert_eq!(scratch.run(&["--tail-bytes=100", "a.txt"]).success().stdout, TEXT);
+}
+
+#[test]
+fn tail_bytes_reads_streams_too() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--tail-bytes=7", "-n"], TEXT).success();
+    assert_eq!(run.stdout, "     1 | cccc\n");
+}
+
+#[test]
+fn tail_bytes_of_a_big_stream() {
+    let scratch = Scratch::new();
+    let input: String = (1..=100_000).map(|i| format!("{}\n", i)).collect();
+    let run = scratch.run_stdin(&["--tail-bytes=13"], input).success();
+    assert_eq!(run.stdout, "99999\n100000\n");
+}
+
+#[test]
+fn tail_bytes_needs_a_number() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--tail-bytes=x"]);
+    assert!(run.stderr.starts_with("meow: invalid byte count: x"), "{}", run.stderr);
+}
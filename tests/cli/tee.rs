@@ -0,0 +1,43 @@
+use crate::util::Scratch;
+
+const CYAN: &str = "\x1B[36m";
+const RESET: &str = "\x1B[0m";
+
+#[test]
+fn tee_copies_the_terminal_view_and_tee_plain_strips_it() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo bar\nbaz\n");
+    let run = scratch.run_tty(&["--highlight=foo", "--tee=colored.txt", "--tee-plain=plain.txt", "--tee-plain=plain2.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, format!("{CYAN}foo{RESET} bar\nbaz\n"));
+    assert_eq!(scratch.read("colored.txt"), run.stdout);
+    assert_eq!(scratch.read("plain.txt"), "foo bar\nbaz\n");
+    assert_eq!(scratch.read("plain2.txt"), "foo bar\nbaz\n");
+}
+
+#[test]
+fn tee_keeps_the_gutter() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo\n");
+    let run = scratch.run(&["-n", "--tee-plain=plain.txt", "a.txt"]).success();
+    assert_eq!(run.stdout, "     1 | foo\n");
+    assert_eq!(scratch.read("plain.txt"), run.stdout);
+}
+
+#[test]
+fn a_failing_tee_fails_the_run_but_not_the_output() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo\n");
+    let run = scratch.run(&["--tee-plain=/dev/full", "--tee=ok.txt", "a.txt"]).failure();
+    assert_eq!(run.stdout, "foo\n");
+    assert_eq!(scratch.read("ok.txt"), "foo\n");
+    assert_eq!(run.stderr, "meow: cannot write to /dev/full: No space left on device (os error 28)\n");
+}
+
+#[test]
+fn a_tee_that_cant_be_created_stops_the_run() {
+    let scratch = Scratch::new();
+    scratch.write("a.txt", "foo\n");
+    let run = scratch.run(&["--tee=missing/out.txt", "a.txt"]).failure();
+    assert_eq!(run.stdout, "");
+    assert!(run.stderr.starts_with("meow: missing/out.txt: "), "{}", run.stderr);
+}
@@ -0,0 +1,57 @@
+use crate::util::Scratch;
+
+const DEFAULT_THEME: &str = concat!(
+    "normal = \"0\"\n",
+    "number = \"33\"\n",
+    "highlight = \"36\"\n",
+    "error = \"31\"\n",
+    "success = \"32\"\n",
+    "filename = \"35\"\n",
+    "dim = \"2\"\n",
+    "blame-age = [\"30;47\", \"30;100\", \"37;100\", \"37;100\", \"37;40\"]\n",
+    "file-palette = [\"36\", \"33\", \"32\", \"35\", \"34\", \"31\"]\n",
+);
+
+#[test]
+fn export_writes_the_default_colors() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--export-theme=theme.toml"]).success();
+    assert_eq!(run.stdout, "");
+    assert_eq!(scratch.read("theme.toml"), format!("# meow theme, for --theme=theme.toml\n{}", DEFAULT_THEME));
+}
+
+#[test]
+fn exported_theme_round_trips() {
+    let scratch = Scratch::new();
+    scratch.write("mine.toml", "highlight = \"bright-red\"\nfile-palette = [\"#ff8800\", \"1;4\"]\n");
+    scratch.run(&["--theme=mine.toml", "--color-depth=truecolor", "--export-theme=first.toml"]).success();
+    scratch.run(&["--theme=first.toml", "--color-depth=truecolor", "--export-theme=second.toml"]).success();
+    let first = scratch.read("first.toml");
+    assert!(first.contains("highlight = \"91\"\n"), "{}", first);
+    assert!(first.contains("file-palette = [\"38;2;255;136;0\", \"1;4\"]\n"), "{}", first);
+    // Only the comment naming the file differs
+    let body = |text: &str| text.lines().skip(1).collect::<Vec<_>>().join("\n");
+    assert_eq!(body(&first), body(&scratch.read("second.toml")));
+}
+
+#[test]
+fn theme_colors_the_output() {
+    let scratch = Scratch::new();
+    scratch.write("theme.toml", "highlight = \"1;35\"\nnumber = \"34\"\n");
+    scratch.write("a.txt", "cat\n");
+    let run = scratch.run_tty(&["--theme=theme.toml", "-n", "--highlight=cat", "a.txt"]).success();
+    assert_eq!(run.stdout, "\x1B[34m     1\x1B[0m | \x1B[1;35mcat\x1B[0m\n");
+    // Nothing without colors
+    let run = scratch.run(&["--theme=theme.toml", "-n", "a.txt"]).success();
+    assert_eq!(run.stdout, "     1 | cat\n");
+}
+
+#[test]
+fn broken_theme_is_reported() {
+    let scratch = Scratch::new();
+    scratch.write("theme.toml", "error = \"puce\"\n");
+    let run = scratch.run_stdin(&["--theme=theme.toml"], "a\n").failure();
+    assert_eq!(run.stdout, "");
+    assert!(run.stderr.contains("theme.toml"), "{}", run.stderr);
+    assert!(run.stderr.contains("'error': unknown color 'puce'"), "{}", run.stderr);
+}
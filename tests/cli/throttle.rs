@@ -0,0 +1,38 @@
+use std::time::{Duration, Instant};
+
+use crate::util::Scratch;
+
+fn numbers(count: usize) -> String {
+    (1..=count).map(|i| format!("{}\n", i)).collect()
+}
+
+#[test]
+fn throttle_takes_as_long_as_the_rate_says() {
+    let scratch = Scratch::new();
+    let start = Instant::now();
+    let run = scratch.run_stdin(&["--throttle=50"], numbers(11)).success();
+    let elapsed = start.elapsed();
+    assert_eq!(run.stdout, numbers(11));
+    // Ten gaps of 20ms after the first line
+    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+}
+
+#[test]
+fn throttle_of_zero_is_no_limit() {
+    let scratch = Scratch::new();
+    let start = Instant::now();
+    let run = scratch.run_stdin(&["--throttle=0"], numbers(1000)).success();
+    assert_eq!(run.stdout, numbers(1000));
+    assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+}
+
+#[test]
+fn throttle_rejects_bad_rates() {
+    let scratch = Scratch::new();
+    for rate in ["-1", "fast", "inf"] {
+        let run = scratch.run_stdin(&[format!("--throttle={}", rate)], "a\n");
+        assert!(run.stderr.starts_with("meow: "), "{}: {}", rate, run.stderr);
+        assert!(run.stderr.contains(rate), "{}: {}", rate, run.stderr);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::util::Scratch;
+
+const PATH: &str = "/var/log/services/payments/2026/10/16/worker-7/requests-failed.log";
+
+#[test]
+fn long_path_keeps_both_ends() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--truncate-middle=40"], format!("{}\nshort\n", PATH)).success();
+    let lines: Vec<&str> = run.stdout.lines().collect();
+    assert_eq!(lines, ["/var/log/services/pa\u{2026}requests-failed.log", "short"]);
+    assert_eq!(lines[0].chars().count(), 40);
+    assert!(lines[0].starts_with("/var/log/") && lines[0].ends_with("requests-failed.log"));
+}
+
+#[test]
+fn grep_sees_the_whole_line() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--truncate-middle=20", "--grep=2026/10/16"], format!("{}\nother\n", PATH)).success();
+    assert_eq!(run.stdout, "/var/log/s\u{2026}ailed.log\n");
+}
+
+#[test]
+fn width_counts_after_the_gutter() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["-n", "--truncate-middle=11"], "abcdefghijklmnop\n").success();
+    assert_eq!(run.stdout, "     1 | abcde\u{2026}lmnop\n");
+}
+
+#[test]
+fn wide_characters() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--truncate-middle=9"], "日本語のテキストです\n").success();
+    assert_eq!(run.stdout, "日本\u{2026}です\n");
+}
@@ -0,0 +1,311 @@
+use std::ffi::{CString, OsStr};
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+/// What a run of meow printed.
+pub struct Run {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Run {
+    /// Fails the test with both streams when meow didn't exit with 0.
+    #[track_caller]
+    pub fn success(self) -> Self {
+        assert!(self.status.success(), "meow failed ({})\nstdout:\n{}\nstderr:\n{}", self.status, self.stdout, self.stderr);
+        self
+    }
+
+    #[track_caller]
+    pub fn failure(self) -> Self {
+        assert!(!self.status.success(), "meow succeeded\nstdout:\n{}\nstderr:\n{}", self.stdout, self.stderr);
+        self
+    }
+}
+
+/// A scratch directory meow runs in, with a home of its own so no config
+/// file, saved position or language from the real one gets in.
+pub struct Scratch {
+    dir: TempDir,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Scratch { dir: tempfile::tempdir().expect("cannot make a scratch directory") }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    /// Writes `name`, making its directories, and returns its path.
+    pub fn write(&self, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    pub fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.path(name)).unwrap()
+    }
+
+    /// Writes the shell script `name` with `body`, executable, and returns its path.
+    pub fn script(&self, name: &str, body: &str) -> PathBuf {
+        let path = self.write(name, format!("#!/bin/sh\n{}", body));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// Makes the named pipe `name` and returns its path.
+    pub fn mkfifo(&self, name: &str) -> PathBuf {
+        let path = self.path(name);
+        let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        // SAFETY: mkfifo only reads the NUL-terminated path
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        path
+    }
+
+    /// Runs git in the scratch directory as a fixed author, failing the test
+    /// if it fails.
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("HOME", self.dir.path())
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_AUTHOR_NAME", "Ada Lovelace")
+            .env("GIT_AUTHOR_EMAIL", "ada@example.com")
+            .env("GIT_COMMITTER_NAME", "Ada Lovelace")
+            .env("GIT_COMMITTER_EMAIL", "ada@example.com")
+            .output()
+            .expect("cannot run git");
+        assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// A git repository with `name` committed as `contents`.
+    pub fn git_repo(&self, name: &str, contents: &str) {
+        self.git(&["init", "-q", "-b", "main"]);
+        self.write(name, contents);
+        self.git(&["add", name]);
+        self.git(&["commit", "-q", "-m", "first"]);
+    }
+
+    /// meow with `args`, run in the scratch directory.
+    pub fn command<S: AsRef<OsStr>>(&self, args: &[S]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_meow"));
+        command
+            .args(args)
+            .current_dir(self.dir.path())
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.dir.path())
+            // Or a repository the scratch directory happens to be in is found
+            .env("GIT_CEILING_DIRECTORIES", self.dir.path().parent().unwrap())
+            .env("LANG", "C")
+            .env("COLUMNS", "80")
+            .env("LINES", "24");
+        command
+    }
+
+    pub fn run<S: AsRef<OsStr>>(&self, args: &[S]) -> Run {
+        self.run_command(&mut self.command(args), b"")
+    }
+
+    /// Like `run`, with `input` on stdin.
+    pub fn run_stdin<S: AsRef<OsStr>>(&self, args: &[S], input: impl AsRef<[u8]>) -> Run {
+        self.run_command(&mut self.command(args), input.as_ref())
+    }
+
+    pub fn run_command(&self, command: &mut Command, input: &[u8]) -> Run {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().expect("cannot run meow");
+        // Written from a thread, so a large input can't block on a full stdout
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_vec();
+        let writer = thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+        let output = child.wait_with_output().unwrap();
+        writer.join().unwrap();
+        Run {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    /// Runs meow with stdout on an 80x24 pseudo-terminal, for what it only
+    /// does there (colors, titles, prompts). Stdin is empty. The terminal's
+    /// `\r\n` line ends are given back as `\n`.
+    pub fn run_tty<S: AsRef<OsStr>>(&self, args: &[S]) -> Run {
+        self.run_tty_command(&mut self.command(args))
+    }
+
+    pub fn run_tty_command(&self, command: &mut Command) -> Run {
+        let (mut master, slave) = open_pty();
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(slave)
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("cannot run meow");
+        // Our copy of meow's end is closed, so reading stops with an error
+        // once meow has exited
+        command.stdout(Stdio::null());
+        let mut output = Vec::new();
+        read_until(&mut master, &mut output, None);
+        finish_tty(child, &output)
+    }
+
+    /// Runs meow with both stdin and stdout on an 80x24 pseudo-terminal, and
+    /// types keys at it: for each `(text, key)`, once `text` shows up in the
+    /// output after what the previous one waited for, `key` is typed. Fails
+    /// the test if meow leaves the terminal in raw mode.
+    pub fn run_tty_keys<S: AsRef<OsStr>>(&self, args: &[S], keys: &[(&str, &str)]) -> Run {
+        self.drive_tty(args, keys, true)
+    }
+
+    /// Like `run_tty_keys`, for questions asked a line at a time: the answers
+    /// are typed as soon as their text shows up, without raw mode.
+    pub fn run_tty_answers<S: AsRef<OsStr>>(&self, args: &[S], answers: &[(&str, &str)]) -> Run {
+        self.drive_tty(args, answers, false)
+    }
+
+    /// The pseudo-terminal is meow's controlling terminal, so `/dev/tty` is it too.
+    fn drive_tty<S: AsRef<OsStr>>(&self, args: &[S], keys: &[(&str, &str)], raw_keys: bool) -> Run {
+        use std::os::unix::process::CommandExt;
+
+        let (mut master, slave) = open_pty();
+        let mut command = self.command(args);
+        // SAFETY: only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() < 0 || libc::ioctl(libc::STDIN_FILENO, libc::TIOCSCTTY, 0) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command
+            .stdin(slave.try_clone().unwrap())
+            .stdout(slave)
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("cannot run meow");
+        command.stdin(Stdio::null()).stdout(Stdio::null());
+
+        // A key whose text never shows up would leave meow waiting for it forever
+        let pid = child.id() as libc::pid_t;
+        let finished = Arc::new(AtomicBool::new(false));
+        let watchdog = Arc::clone(&finished);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs(20));
+            if !watchdog.load(Ordering::SeqCst) {
+                // SAFETY: meow isn't waited for yet, so the process id is still its own
+                unsafe { libc::kill(pid, libc::SIGKILL) };
+            }
+        });
+
+        let mut output = Vec::new();
+        let mut seen = 0;
+        for (text, key) in keys {
+            seen = read_until(&mut master, &mut output, Some((seen, text.as_bytes())))
+                .unwrap_or_else(|| panic!("meow ended without showing {:?}:\n{}", text, String::from_utf8_lossy(&output)));
+            // Typed before meow switched to raw mode, the key would be echoed
+            for _ in 0..500 {
+                if !raw_keys || is_raw(&master) {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            master.write_all(key.as_bytes()).unwrap();
+        }
+        read_until(&mut master, &mut output, None);
+        finished.store(true, Ordering::SeqCst);
+        assert!(!is_raw(&master), "meow left the terminal in raw mode");
+        finish_tty(child, &output)
+    }
+}
+
+/// Whether the terminal of `master` is in raw mode, without line editing.
+fn is_raw(master: &fs::File) -> bool {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: tcgetattr only writes into the struct we pass in; on the
+    // master it reports the modes of meow's end
+    let mut modes: libc::termios = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::tcgetattr(master.as_raw_fd(), &mut modes) }, 0);
+    modes.c_lflag & libc::ICANON == 0
+}
+
+/// Reads from a pseudo-terminal's `master` into `output`, to the end or, with
+/// `wait`, until its text is in the output past its offset. Returns the offset
+/// after the text, None when the end came first.
+fn read_until(master: &mut fs::File, output: &mut Vec<u8>, wait: Option<(usize, &[u8])>) -> Option<usize> {
+    let mut buf = [0; 4096];
+    loop {
+        if let Some((from, text)) = wait {
+            if let Some(at) = output[from..].windows(text.len()).position(|window| window == text) {
+                return Some(from + at + text.len());
+            }
+        }
+        match master.read(&mut buf) {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+        }
+    }
+}
+
+/// Waits for meow on a pseudo-terminal, whose `output` was read already.
+fn finish_tty(child: std::process::Child, output: &[u8]) -> Run {
+    let result = child.wait_with_output().unwrap();
+    Run {
+        status: result.status,
+        stdout: String::from_utf8_lossy(output).replace("\r\n", "\n"),
+        stderr: String::from_utf8_lossy(&result.stderr).into_owned(),
+    }
+}
+
+/// Writes `text` into the FIFO at `path` after `delay`, from a thread.
+pub fn write_later(path: &Path, delay: Duration, text: &'static str) -> thread::JoinHandle<()> {
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        thread::sleep(delay);
+        let mut fifo = fs::OpenOptions::new().write(true).open(path).unwrap();
+        fifo.write_all(text.as_bytes()).unwrap();
+    })
+}
+
+/// A new pseudo-terminal of 80x24: the end read here, and the one given to meow.
+fn open_pty() -> (fs::File, fs::File) {
+    use std::os::fd::FromRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // SAFETY: plain calls on a descriptor we own, checked as we go; ptsname's
+    // buffer is copied out before any other call could reuse it
+    unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0, "cannot open a pseudo-terminal");
+        assert!(libc::grantpt(master) == 0 && libc::unlockpt(master) == 0);
+        let size = libc::winsize { ws_row: 24, ws_col: 80, ws_xpixel: 0, ws_ypixel: 0 };
+        libc::ioctl(master, libc::TIOCSWINSZ, &size);
+        let name = std::ffi::CStr::from_ptr(libc::ptsname(master)).to_owned();
+        let master = fs::File::from_raw_fd(master);
+        let slave = fs::OpenOptions::new().read(true).write(true).custom_flags(libc::O_NOCTTY).open(Path::new(OsStr::from_bytes(name.to_bytes()))).unwrap();
+        (master, slave)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::util::Scratch;
+
+const ALPHA_SHA256: &str = "b6a98d9ce9a2d9149288fa3df42d377c3e42737afdcdaf714e33c0a100b51060";
+const BETA_SHA256: &str = "f2c82decdd7181cf98945929a62598db7e6b477e11f6e0eb0ae97020eff151ad";
+const BETA_MD5: &str = "f0cf2a92516045024a0c99147b28f05b";
+
+/// `sums/alpha.txt` and `sums/beta.txt`, with a manifest next to them.
+fn files(scratch: &Scratch, manifest: &str) {
+    scratch.write("sums/alpha.txt", "alpha\n");
+    scratch.write("sums/beta.txt", "beta\n");
+    scratch.write("sums/SHA256SUMS", manifest);
+}
+
+#[test]
+fn verify_gnu_manifest_relative_to_its_directory() {
+    let scratch = Scratch::new();
+    files(&scratch, &format!("{}  alpha.txt\n{} *beta.txt\n", ALPHA_SHA256, BETA_SHA256));
+    let run = scratch.run(&["--verify", "sums/SHA256SUMS"]).success();
+    assert_eq!(run.stdout, "alpha.txt: OK\nbeta.txt: OK\n2 ok, 0 failed, 0 missing\n");
+}
+
+#[test]
+fn verify_bsd_manifest_with_mixed_algorithms() {
+    let scratch = Scratch::new();
+    files(&scratch, &format!("SHA256 (alpha.txt) = {}\nMD5 (beta.txt) = {}\n", ALPHA_SHA256, BETA_MD5));
+    let run = scratch.run(&["--verify", "sums/SHA256SUMS"]).success();
+    assert_eq!(run.stdout, "alpha.txt: OK\nbeta.txt: OK\n2 ok, 0 failed, 0 missing\n");
+}
+
+#[test]
+fn verify_reports_corrupted_and_missing_files() {
+    let scratch = Scratch::new();
+    files(&scratch, &format!("{}  alpha.txt\n{}  beta.txt\n{}  gone.txt\n", ALPHA_SHA256, BETA_SHA256, BETA_SHA256));
+    scratch.write("sums/beta.txt", "bit rot\n");
+    let run = scratch.run(&["--verify", "sums/SHA256SUMS"]).failure();
+    assert_eq!(run.stdout, "alpha.txt: OK\nbeta.txt: FAILED\ngone.txt: MISSING\n1 ok, 1 failed, 1 missing\n");
+}
+
+#[test]
+fn verify_quiet_prints_only_failures() {
+    let scratch = Scratch::new();
+    files(&scratch, &format!("{}  alpha.txt\n{}  gone.txt\n", ALPHA_SHA256, BETA_SHA256));
+    let run = scratch.run(&["--verify", "--quiet", "sums/SHA256SUMS"]).failure();
+    assert_eq!(run.stdout, "gone.txt: MISSING\n1 ok, 0 failed, 1 missing\n");
+    files(&scratch, &format!("{}  alpha.txt\n", ALPHA_SHA256));
+    let run = scratch.run(&["--verify", "--quiet", "sums/SHA256SUMS"]).success();
+    assert_eq!(run.stdout, "");
+}
+
+#[test]
+fn verify_reports_malformed_lines_by_number() {
+    let scratch = Scratch::new();
+    files(&scratch, &format!("# made by hand\n{}  alpha.txt\nnot a checksum\n", ALPHA_SHA256));
+    let run = scratch.run(&["--verify", "sums/SHA256SUMS"]).success();
+    assert_eq!(run.stderr, "meow: sums/SHA256SUMS:3: malformed checksum line\n");
+    assert_eq!(run.stdout, "alpha.txt: OK\n1 ok, 0 failed, 0 missing\n");
+}
+
+#[test]
+fn verify_fails_a_manifest_without_checksums() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--verify"], "hello\n").failure();
+    assert!(run.stderr.contains("-: no checksum lines found"), "{}", run.stderr);
+}
@@ -0,0 +1,64 @@
+use crate::util::Scratch;
+
+fn fixture() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(".config/meow/config.toml", "[views]\nerrors = \"-n --grep=ERROR\"\nloud = [\"@errors\", \"--highlight=bad\"]\nloop = \"@loop\"\n");
+    scratch.write("app.log", "INFO ok\nERROR bad\nERROR worse\n");
+    scratch
+}
+
+#[test]
+fn view_expands_in_place() {
+    let scratch = fixture();
+    let run = scratch.run(&["--view=errors", "app.log"]).success();
+    assert_eq!(run.stdout, "     1 | ERROR bad\n     2 | ERROR worse\n");
+    let run = scratch.run(&["@errors", "app.log"]).success();
+    assert_eq!(run.stdout, "     1 | ERROR bad\n     2 | ERROR worse\n");
+}
+
+#[test]
+fn later_flags_override_the_view() {
+    let scratch = fixture();
+    let run = scratch.run(&["@errors", "--grep=worse", "app.log"]).success();
+    assert_eq!(run.stdout, "     1 | ERROR worse\n");
+    // What comes before is overridden by the view instead
+    let run = scratch.run(&["--grep=worse", "@errors", "app.log"]).success();
+    assert_eq!(run.stdout, "     1 | ERROR bad\n     2 | ERROR worse\n");
+}
+
+#[test]
+fn views_use_other_views() {
+    let scratch = fixture();
+    let run = scratch.run_tty(&["@loud", "app.log"]).success();
+    assert!(run.stdout.contains("ERROR \x1B[36mbad\x1B[0m"), "{:?}", run.stdout);
+    assert!(!run.stdout.contains("INFO"));
+}
+
+#[test]
+fn unknown_and_recursive_views() {
+    let scratch = fixture();
+    let run = scratch.run(&["@nope", "app.log"]).failure();
+    assert_eq!(run.stderr, "meow: unknown view 'nope' (available: errors, loop, loud)\n");
+    let run = scratch.run(&["@loop", "app.log"]).failure();
+    assert_eq!(run.stderr, "meow: view 'loop' refers back to itself\n");
+}
+
+#[test]
+fn list_views() {
+    let scratch = fixture();
+    let run = scratch.run(&["--list-views"]).success();
+    assert_eq!(run.stdout, "errors           -n --grep=ERROR\nloop             @loop\nloud             @errors --highlight=bad\n");
+}
+
+#[test]
+fn view_in_the_shell() {
+    let scratch = fixture();
+    let run = scratch.run_stdin(&["-i", "app.log"], "view errors app.log\nview nope app.log\nview errors\n").success();
+    let replies: Vec<&str> = run.stdout.split("meow> ").skip(1).collect();
+    assert_eq!(replies, [
+        "     1 | ERROR bad\n     2 | ERROR worse\n",
+        "unknown view 'nope' (available: errors, loop, loud)\n",
+        "Usage: view <name> <file>\n",
+        "",
+    ]);
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::util::Scratch;
+
+/// Creates `path` with `text` after `delay`, all at once as a service's
+/// rename into place would.
+fn create_later(path: &Path, delay: Duration, text: &'static str) -> thread::JoinHandle<()> {
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        thread::sleep(delay);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, text).unwrap();
+        fs::rename(partial, path).unwrap();
+    })
+}
+
+#[test]
+fn shows_a_file_created_after_launch() {
+    let scratch = Scratch::new();
+    let writer = create_later(&scratch.path("service.log"), Duration::from_millis(300), "started\nlistening\n");
+    let started = Instant::now();
+    let run = scratch.run(&["-n", "--wait-for-file", "service.log"]).success();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert_eq!(run.stdout, "     1 | started\n     2 | listening\n");
+    assert_eq!(run.stderr, "");
+    writer.join().unwrap();
+}
+
+#[test]
+fn file_in_time_for_the_timeout() {
+    let scratch = Scratch::new();
+    let writer = create_later(&scratch.path("service.log"), Duration::from_millis(200), "up\n");
+    let run = scratch.run(&["--wait-for-file", "--wait-timeout=5000", "service.log"]).success();
+    assert_eq!(run.stdout, "up\n");
+    writer.join().unwrap();
+}
+
+#[test]
+fn timeout_reports_the_missing_file() {
+    let scratch = Scratch::new();
+    let started = Instant::now();
+    let run = scratch.run(&["--wait-for-file", "--wait-timeout=300", "never.log"]).failure();
+    let waited = started.elapsed();
+    assert!(waited >= Duration::from_millis(300), "{:?}", waited);
+    assert!(waited < Duration::from_secs(5), "{:?}", waited);
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "meow: never.log: No such file or directory (os error 2)\n");
+}
+
+#[test]
+fn files_are_waited_for_in_turn() {
+    let scratch = Scratch::new();
+    scratch.write("first.log", "one\n");
+    let writer = create_later(&scratch.path("second.log"), Duration::from_millis(200), "two\n");
+    let run = scratch.run(&["--wait-for-file", "--wait-timeout=1500", "first.log", "second.log", "third.log"]);
+    writer.join().unwrap();
+    // third.log never comes, so the wait for it runs out
+    let run = run.failure();
+    assert_eq!(run.stdout, "\n===> first.log <===\none\n\n===> second.log <===\ntwo\n");
+    assert!(run.stderr.contains("third.log: No such file or directory"), "{}", run.stderr);
+}
+
+#[test]
+fn without_it_a_missing_file_fails_at_once() {
+    let scratch = Scratch::new();
+    let started = Instant::now();
+    let run = scratch.run(&["--wait-timeout=5000", "never.log"]).failure();
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(run.stderr, "meow: never.log: No such file or directory (os error 2)\n");
+}
+
+#[test]
+fn bad_timeout() {
+    let scratch = Scratch::new();
+    let run = scratch.run(&["--wait-for-file", "--wait-timeout=soon", "a.log"]);
+    assert_eq!(run.stderr, "meow: invalid value 'soon' for --wait-timeout\n");
+    assert!(run.stdout.starts_with("Usage:"), "{}", run.stdout);
+}
@@ -0,0 +1,32 @@
+use crate::util::Scratch;
+
+#[test]
+fn xml_reindents_with_the_indent_width() {
+    let scratch = Scratch::new();
+    scratch.write("d.xml", "<root><item id=\"1\">text</item><empty/></root>");
+    let run = scratch.run(&["--xml", "--xml-indent=4", "d.xml"]).success();
+    assert_eq!(run.stdout, "<root>\n    <item id=\"1\">text</item>\n    <empty/>\n</root>\n");
+}
+
+#[test]
+fn xml_collapse_is_repeatable() {
+    let scratch = Scratch::new();
+    scratch.write("d.xml", "<root><a><x/></a><b><x/><x/></b><c/></root>");
+    let run = scratch.run(&["--xml", "--xml-collapse=a", "--xml-collapse=b", "d.xml"]).success();
+    assert_eq!(run.stdout, "<root>\n  <a> \u{2026} 1 elements </a>\n  <b> \u{2026} 2 elements </b>\n  <c/>\n</root>\n");
+}
+
+#[test]
+fn xml_colors_on_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("d.xml", "<a b=\"c\"/>");
+    let run = scratch.run_tty(&["--xml", "d.xml"]).success();
+    assert_eq!(run.stdout, "<\x1B[36ma\x1B[0m \x1B[33mb\x1B[0m=\x1B[32m\"c\"\x1B[0m/>\n");
+}
+
+#[test]
+fn unbalanced_xml_is_shown_unindented() {
+    let scratch = Scratch::new();
+    let run = scratch.run_stdin(&["--xml"], "<a><b></c></a>\n").success();
+    assert_eq!(run.stdout, "<a><b></c></a>\n");
+}
@@ -0,0 +1,66 @@
+/// `logs/`, `logs/app.log` (deflated) and `README` (stored).
+const ARCHIVE: &[u8] = include_bytes!("../fixtures/logs.zip");
+
+#[cfg(feature = "zip")]
+mod archives {
+    use super::ARCHIVE;
+    use crate::util::Scratch;
+
+    #[test]
+    fn zip_entry_is_shown() {
+        let scratch = Scratch::new();
+        scratch.write("logs.zip", ARCHIVE);
+        let run = scratch.run(&["--zip-entry=logs/app.log", "logs.zip"]).success();
+        assert_eq!(run.stdout, "INFO start\nERROR disk full\nINFO stop\n");
+        // The display options apply as to a file
+        let run = scratch.run(&["-n", "--grep=ERROR", "--zip-entry=logs/app.log", "logs.zip"]).success();
+        assert_eq!(run.stdout, "     1 | ERROR disk full\n");
+    }
+
+    #[test]
+    fn entry_after_a_colon() {
+        let scratch = Scratch::new();
+        scratch.write("logs.zip", ARCHIVE);
+        assert_eq!(scratch.run(&["logs.zip:README"]).success().stdout, "sample archive\n");
+    }
+
+    #[test]
+    fn list_shows_entries_and_sizes() {
+        let scratch = Scratch::new();
+        scratch.write("logs.zip", ARCHIVE);
+        let run = scratch.run(&["--list", "logs.zip"]).success();
+        assert_eq!(run.stdout, "   -  logs/\n37 B  logs/app.log\n15 B  README\n");
+    }
+
+    #[test]
+    fn missing_entry_is_an_error() {
+        let scratch = Scratch::new();
+        scratch.write("logs.zip", ARCHIVE);
+        let run = scratch.run(&["--zip-entry=nope", "logs.zip"]).failure();
+        assert_eq!(run.stdout, "");
+        assert_eq!(run.stderr, "meow: logs.zip:nope: no such entry in the archive (see meow --list)\n");
+        let run = scratch.run(&["logs.zip:nope"]).failure();
+        assert_eq!(run.stderr, "meow: logs.zip:nope: no such entry in the archive (see meow --list)\n");
+    }
+
+    #[test]
+    fn not_an_archive() {
+        let scratch = Scratch::new();
+        scratch.write("fake.zip", "just text\n");
+        let run = scratch.run(&["--list", "fake.zip"]).failure();
+        assert!(run.stderr.starts_with("meow: fake.zip: invalid Zip archive"), "{}", run.stderr);
+        let run = scratch.run(&["--zip-entry=a", "fake.zip"]).failure();
+        assert!(run.stderr.starts_with("meow: fake.zip:a: invalid Zip archive"), "{}", run.stderr);
+    }
+}
+
+#[cfg(not(feature = "zip"))]
+#[test]
+fn list_needs_the_feature() {
+    use crate::util::Scratch;
+
+    let scratch = Scratch::new();
+    scratch.write("logs.zip", ARCHIVE);
+    let run = scratch.run(&["--list", "logs.zip"]).failure();
+    assert!(run.stderr.contains("zip archives are not supported in this build"), "{}", run.stderr);
+}